@@ -1,4 +1,4 @@
-use resvg::usvg::{self, TreeParsing};
+use resvg::usvg;
 use std::fs;
 
 fn main() {
@@ -13,8 +13,7 @@ fn main() {
     let size = 256;
     let mut pixmap = tiny_skia::Pixmap::new(size, size).unwrap();
 
-    let rtree = resvg::Tree::from_usvg(&tree);
-    let svg_size = rtree.size;
+    let svg_size = tree.size();
 
     // Calculate scale to fit SVG into the target size
     let scale_x = size as f32 / svg_size.width();
@@ -22,7 +21,7 @@ fn main() {
     let scale = scale_x.min(scale_y); // Maintain aspect ratio
 
     let transform = tiny_skia::Transform::from_scale(scale, scale);
-    rtree.render(transform, &mut pixmap.as_mut());
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
 
     pixmap
         .save_png("heike_icon.png")
@@ -1,24 +1,56 @@
-use crate::config::{BookmarksConfig, Config};
+use crate::config::{BookmarksConfig, Config, HookConfig};
+use crate::core::keymap::Keymap;
+use crate::core::{self, Action, Browser, Effect};
 use crate::entry::FileEntry;
-use crate::io::{fuzzy_match, spawn_worker, IoCommand, IoResult};
+use crate::io::archive;
+use crate::io::broken_links;
+use crate::io::directory::{git_dir, project_root, read_directory, FLATTEN_MAX_DEPTH};
+use crate::io::fileops::unique_destination;
+use crate::io::git::{self, GitAction, RepoInfo};
+use crate::io::hooks;
+use crate::io::job_runner::{JobEvent, JobRunner};
+use crate::io::labels::LabelStore;
+use crate::io::maintenance::TrashUsage;
+use crate::io::oplog::{self, OperationLog};
+use crate::io::photo;
+use crate::io::search::{build_matcher, capture_names, replace_preview};
+use crate::io::session::{Session, SessionTab};
+use crate::io::split;
+use crate::io::textdiff;
+use crate::io::tools::{self, Capabilities, Tool};
+use crate::io::worker::{WorkerHandle, SHUTDOWN_GRACE};
+use crate::io::{spawn_worker, IoCommand, IoResult};
+use crate::platform::{
+    self,
+    tray::{TrayAction, TrayIcon, TrayMenu},
+};
+use crate::state::entries::carry_details;
 use crate::state::{
-    AppMode, ClipboardOp, EntryState, ModeState, NavigationState, SelectionState, TabsManager,
-    UIState,
+    bounded_history, distinct_roots, expand_braces, expand_name_template, file_hit_counts,
+    listing_age, mirror_path, near_matches, photo_names, prune_history, rank_by_file,
+    recent_locations, root_of, AppMode, BatchJob, BrokenLink, CardKind, ClipboardOp, ComputerCard,
+    ConflictPolicy, DiffKind, DirSnapshot, DryRunReport, ExitChoice, HookCheck, JobErrorAction,
+    JobKind, JobStart, LabelColor, ListingCache, ModeState, PeekState, PlannedChange,
+    PropertiesState, QueuedJob, SearchResult, SettingsState, Severity, SnapshotChange, SortBy,
+    SortOptions, SortOrder, TabState, TabsManager, UIState,
 };
 use crate::style::{self, Theme};
 use crate::view;
 
 use eframe::egui;
 use notify::{Event, RecursiveMode, Watcher};
-use std::cell::RefCell;
-use std::collections::HashSet;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
 use std::fs;
-use std::path::PathBuf;
-use std::sync::mpsc::{channel, Receiver, SyncSender};
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::time::{Duration, Instant, SystemTime};
 use syntect::highlighting::ThemeSet;
-use syntect::parsing::SyntaxSet;
+use syntect::parsing::{SyntaxSet, SyntaxSetBuilder};
+
+/// Deferred action queued from inside a UI closure and applied after rendering
+pub(crate) type ContextAction = RefCell<Option<Box<dyn FnOnce(&mut Heike)>>>;
 
 enum TabAction {
     SwitchTo(usize),
@@ -26,47 +58,118 @@ enum TabAction {
     New,
 }
 
+/// Settle time before re-fetching git status after the repository or a file changed
+const DETAILS_REFRESH_DELAY: Duration = Duration::from_millis(300);
+
 pub struct Heike {
     // Persistent configuration
     pub config: Config,
+    /// Config as last written to or read from config.toml, so unchanged settings are not rewritten
+    pub saved_config: Config,
 
     // Tabs management
     pub tabs: TabsManager,
 
-    // Current tab state (synced with active tab)
-    pub navigation: NavigationState,
-    pub selection: SelectionState,
-    pub entries: EntryState,
+    // Current tab state (synced with active tab) and the shared clipboard
+    pub browser: Browser,
 
     // Global state
     pub ui: UIState,
     pub mode: ModeState,
 
-    // Clipboard operations (shared across tabs)
-    pub clipboard: HashSet<PathBuf>,
-    pub clipboard_op: Option<ClipboardOp>,
+    // Files waiting to be handed to their default application, opened in batches
+    pub open_queue: VecDeque<PathBuf>,
+    pub last_open_batch: Instant,
 
     // Async I/O channels (bounded to prevent memory exhaustion)
-    pub command_tx: SyncSender<IoCommand>,
-    pub result_rx: Receiver<IoResult>,
+    /// Background I/O thread; shut down in `on_exit`
+    pub worker: WorkerHandle,
     pub watcher: Option<Box<dyn Watcher>>,
     pub watcher_rx: Receiver<Result<Event, notify::Error>>,
+    /// Watches `notifications.watch` folders for new files
+    pub folder_watcher: Option<Box<dyn Watcher>>,
+    pub folder_watch_rx: Receiver<Result<Event, notify::Error>>,
+    /// Watches the config folder so edits to config.toml made elsewhere are picked up
+    pub config_watcher: Option<Box<dyn Watcher>>,
+    pub config_watch_rx: Receiver<Result<Event, notify::Error>>,
     pub watched_path: Option<PathBuf>,
+    /// Git directory watched alongside the current one, so HEAD/index changes refresh markers
+    pub watched_git_dir: Option<PathBuf>,
+    /// When to re-fetch entry details (git status) after repository or file changes
+    pub details_refresh_due: Option<Instant>,
+    /// Branch info for the top bar git menu, `None` outside a repository
+    pub git_repo: Option<RepoInfo>,
 
     // Resources
     pub syntax_set: SyntaxSet,
+    /// Extensions of the syntaxes folder, previewed as text unless the config says otherwise
+    pub syntax_handlers: HashMap<String, String>,
     pub theme_set: ThemeSet,
     pub bookmarks: BookmarksConfig,
+    pub capabilities: Capabilities,
+    pub labels: LabelStore,
+    /// Count and `g` prefix state of normal-mode key sequences
+    pub keymap: Keymap,
+    /// Directory listings recorded with `:snapshot` this session, by directory
+    pub snapshots: HashMap<PathBuf, DirSnapshot>,
+    pub properties: Option<PropertiesState>,
+    /// Sibling directory shown in the listing pane until it is opened or dismissed
+    pub peek: Option<PeekState>,
+    /// Draft edited in the settings window while it is open
+    pub settings: Option<SettingsState>,
+    /// Paste or delete in progress; its items run one at a time on `job_runner`
+    pub job: Option<BatchJob>,
+    pub job_runner: JobRunner,
+    /// Jobs waiting for the current one to finish or for their start time
+    pub queued_jobs: Vec<QueuedJob>,
+    /// Requested jobs waiting for their before hooks
+    pub hook_checks: Vec<HookCheck>,
+    /// The window was closed with jobs pending; quit as soon as they are done
+    pub exit_when_idle: bool,
+    /// "Run in background" was chosen: the window is minimized until the jobs are done
+    pub in_background: bool,
+    /// Shown per `file_ops.tray_icon`
+    pub tray: Option<TrayIcon>,
+    /// The tray icon could not be shown; not tried again until the settings change
+    pub tray_failed: bool,
+    /// `:q` was typed; the window is closed next frame, through the same checks as the close button
+    pub quit_requested: bool,
+    /// Last window title sent, so progress updates only go out when it changes
+    pub window_title: String,
+    /// Last trash scan for the maintenance panel; `None` while a scan is running
+    pub trash_usage: Option<Result<TrashUsage, String>>,
 
     // Preview system
     pub preview_registry: view::PreviewRegistry,
 
     // Caching (interior mutability for preview cache)
     pub preview_cache: RefCell<view::PreviewCache>,
+    pub preview_tab: Cell<view::PreviewTab>,
+    pub preview_top_line: RefCell<Option<(PathBuf, usize)>>,
+    /// Entry the preview showed last frame; leaving it cancels its worker jobs
+    preview_target: Option<PathBuf>,
+    /// Listing shown by the directory preview
+    preview_listing: Option<view::PreviewListing>,
+    /// Directory whose preview listing the worker is reading
+    preview_listing_pending: Option<PathBuf>,
+    /// Listing shown by the archive preview
+    archive_listing: Option<view::ArchiveListing>,
+    /// Archive and position of the listing page the worker is reading
+    archive_listing_pending: Option<(PathBuf, u64)>,
+    /// `<archive>/<member>` of a search match whose text the worker is reading
+    archive_member_pending: Option<PathBuf>,
+    /// Archive member that could not be read for the search results preview
+    archive_member_error: Option<(PathBuf, String)>,
 
     // Parent directory cache to avoid redundant reads
     pub cached_parent_path: Option<PathBuf>,
     pub cached_show_hidden: bool,
+    /// Listings of slow (network) directories, shown at once while they are read again
+    pub listing_cache: ListingCache,
+    /// File operations done this session, for `:oplog export`
+    pub oplog: OperationLog,
+    /// Tabs and histories as last written to the session file
+    saved_session: Session,
 }
 impl Heike {
     pub fn new(
@@ -74,6 +177,8 @@ impl Heike {
         config: crate::config::Config,
         cli_start_dir: Option<PathBuf>,
     ) -> Self {
+        // A directory given on the command line starts a fresh session
+        let restore_session = cli_start_dir.is_none();
         let start_path = if let Some(dir) = cli_start_dir {
             // Use CLI-provided directory if valid
             if dir.is_dir() {
@@ -92,105 +197,428 @@ impl Heike {
         };
 
         let worker = spawn_worker(ctx.clone());
-        let cmd_tx = worker.command_tx;
-        let res_rx = worker.result_rx;
-        // Note: worker.thread_handle is dropped here, but the thread continues running
-        // The worker will exit gracefully when command_tx is dropped (on app close)
         let (_watch_tx, watch_rx) = channel();
-
-        // Parse theme from config
-        let theme = match config.theme.mode.as_str() {
-            "light" => Theme::Light,
-            _ => Theme::Dark,
-        };
-
-        // Parse sort options from config
-        let sort_by = match config.ui.sort_by.as_str() {
-            "size" => crate::state::SortBy::Size,
-            "modified" => crate::state::SortBy::Modified,
-            "extension" => crate::state::SortBy::Extension,
-            _ => crate::state::SortBy::Name,
-        };
-
-        let sort_order = match config.ui.sort_order.as_str() {
-            "desc" => crate::state::SortOrder::Descending,
-            _ => crate::state::SortOrder::Ascending,
-        };
-
-        let sort_options = crate::state::SortOptions {
-            sort_by,
-            sort_order,
-            dirs_first: config.ui.dirs_first,
-        };
-
-        let mut ui_state = UIState::new(theme.clone(), sort_options);
-        ui_state.show_hidden = config.ui.show_hidden;
-        ui_state.panel_widths = [config.panel.parent_width, config.panel.preview_width];
-
-        // Create preview registry and configure enabled handlers
-        let mut preview_registry = view::create_default_registry();
-        preview_registry.set_enabled_handlers(config.previews.enabled.clone());
+        let (_folder_tx, folder_rx) = channel();
+        let (_config_tx, config_rx) = channel();
 
         // Initialize tabs manager
         let tabs = TabsManager::new(start_path.clone());
 
         let mut app = Self {
             config: config.clone(),
+            saved_config: config.clone(),
             tabs,
-            navigation: NavigationState::new(start_path.clone()),
-            selection: SelectionState::new(),
-            entries: EntryState::new(),
-            ui: ui_state,
+            browser: Browser::new(start_path.clone()),
+            // Theme, sort order and the rest come from the config in `apply_settings`
+            ui: UIState::new(Theme::Dark, SortOptions::default()),
             mode: ModeState::new(),
-            clipboard: HashSet::new(),
-            clipboard_op: None,
-            command_tx: cmd_tx,
-            result_rx: res_rx,
+            open_queue: VecDeque::new(),
+            last_open_batch: Instant::now(),
+            worker,
             watcher: None,
             watcher_rx: watch_rx,
+            folder_watcher: None,
+            folder_watch_rx: folder_rx,
+            config_watcher: None,
+            config_watch_rx: config_rx,
             watched_path: None,
+            watched_git_dir: None,
+            git_repo: None,
+            details_refresh_due: None,
             syntax_set: SyntaxSet::load_defaults_newlines(),
+            syntax_handlers: HashMap::new(),
             theme_set: ThemeSet::load_defaults(),
             bookmarks: config.bookmarks.clone(),
-            preview_registry,
+            capabilities: Capabilities::detect(&config.tools),
+            labels: LabelStore::load(),
+            keymap: Keymap::new(Duration::from_millis(style::KEY_SEQUENCE_TIMEOUT_MS)),
+            snapshots: HashMap::new(),
+            properties: None,
+            peek: None,
+            settings: None,
+            job: None,
+            job_runner: JobRunner::spawn(ctx.clone()),
+            queued_jobs: Vec::new(),
+            hook_checks: Vec::new(),
+            exit_when_idle: false,
+            in_background: false,
+            tray: None,
+            tray_failed: false,
+            quit_requested: false,
+            window_title: "Heike".into(),
+            trash_usage: None,
+            preview_registry: view::create_default_registry(),
             preview_cache: RefCell::new(view::PreviewCache::new()),
+            preview_tab: Cell::new(view::PreviewTab::Content),
+            preview_top_line: RefCell::new(None),
+            preview_target: None,
+            preview_listing: None,
+            preview_listing_pending: None,
+            archive_listing: None,
+            archive_listing_pending: None,
+            archive_member_pending: None,
+            archive_member_error: None,
             cached_parent_path: None,
             cached_show_hidden: false,
+            listing_cache: ListingCache::default(),
+            oplog: OperationLog::new(None),
+            saved_session: Session::default(),
         };
 
+        app.apply_settings();
+        // With nothing to reopen, start on the Computer page
+        if restore_session && !app.restore_session() {
+            app.open_computer();
+        }
+        if let Some(dir) = app
+            .oplog
+            .daily_dir
+            .as_deref()
+            .filter(|_| config.file_ops.log_keep_days > 0)
+        {
+            oplog::prune_daily(
+                dir,
+                config.file_ops.log_keep_days,
+                chrono::Local::now().date_naive(),
+            );
+        }
+        if config.maintenance.trash_retention_days > 0 {
+            let _ = app.worker.command_tx.send(IoCommand::EmptyTrash {
+                older_than_days: Some(config.maintenance.trash_retention_days),
+            });
+        }
+
+        app.load_code_themes();
+        app.load_user_syntaxes();
+        app.setup_folder_watcher(&ctx);
         app.request_refresh();
         app
     }
 
+    /// Bring the UI, bookmarks and preview handlers in line with `self.config`
+    fn apply_settings(&mut self) {
+        git::set_enabled(self.capabilities.missing_reason(Tool::Git).is_none());
+        archive::set_helper(self.capabilities.usable_command(Tool::Archiver));
+        self.tray_failed = false;
+        let config = &self.config;
+        self.ui.theme = match config.theme.mode.as_str() {
+            "light" => Theme::Light,
+            _ => Theme::Dark,
+        };
+        self.ui.sort_options = SortOptions {
+            sort_by: SortBy::from_name(&config.ui.sort_by).unwrap_or(SortBy::Name),
+            sort_order: SortOrder::from_name(&config.ui.sort_order).unwrap_or(SortOrder::Ascending),
+            dirs_first: config.ui.dirs_first,
+        };
+        self.ui.show_hidden = config.ui.show_hidden;
+        self.ui.filter_preview = config.ui.filter_preview;
+        self.ui.show_clipboard = config.ui.clipboard_panel;
+        self.ui.group_headers = config
+            .ui
+            .group_headers
+            .iter()
+            .filter_map(|name| SortBy::from_name(name))
+            .collect();
+        self.ui.search_options.skip_dirs = config.ui.skipped_dirs();
+        self.ui.name_ellipsis = style::Ellipsis::from_config(&config.ui.name_ellipsis);
+        self.ui.panel_widths = [config.panel.parent_width, config.panel.preview_width];
+
+        self.bookmarks = config.bookmarks.clone();
+        self.keymap.set_bookmark_keys(config.bookmarks.keys());
+
+        self.preview_registry
+            .set_enabled_handlers(config.previews.enabled.clone());
+        self.preview_registry
+            .set_priority_overrides(config.previews.priorities.clone());
+        self.preview_cache
+            .borrow_mut()
+            .set_max_bytes(config.maintenance.preview_cache_mb * 1024 * 1024);
+        self.oplog.daily_dir = config
+            .file_ops
+            .daily_log
+            .then(oplog::default_log_dir)
+            .flatten();
+        self.apply_extension_handlers();
+    }
+
+    /// Watch the folder holding config.toml once it exists; editors often save by replacing the file
+    fn setup_config_watcher(&mut self, ctx: &egui::Context) {
+        let Some(dir) = Config::config_path().and_then(|path| Some(path.parent()?.to_path_buf()))
+        else {
+            return;
+        };
+        if !dir.is_dir() {
+            return;
+        }
+        let (tx, rx) = channel();
+        let ctx_clone = ctx.clone();
+        let mut watcher =
+            match notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
+                let _ = tx.send(res);
+                ctx_clone.request_repaint();
+            }) {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    self.ui
+                        .set_warning(format!("Failed to create config watcher: {}", e));
+                    // Stand-in so the failed setup is not retried every frame
+                    self.config_watcher = Some(Box::new(notify::NullWatcher));
+                    return;
+                }
+            };
+        if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            self.ui
+                .set_warning(format!("Cannot watch {}: {}", dir.display(), e));
+            self.config_watcher = Some(Box::new(notify::NullWatcher));
+            return;
+        }
+        self.config_watcher = Some(Box::new(watcher));
+        self.config_watch_rx = rx;
+    }
+
+    /// `:config [edit]`: go to the config folder with config.toml selected, and with
+    /// `edit` open it in the editor; saved edits are applied by `process_config_events`
+    fn open_config(&mut self, edit: bool) {
+        let Some(path) = Config::config_path() else {
+            self.ui
+                .set_error("Could not determine config directory".into());
+            return;
+        };
+        if !path.exists() {
+            if let Err(e) = self.write_config() {
+                self.ui
+                    .set_error(format!("Could not create {}: {}", path.display(), e));
+                return;
+            }
+        }
+        if edit {
+            if let Err(e) = self.capabilities.open_in_editor_at(&path, None) {
+                self.ui.set_error(format!("Could not open editor: {}", e));
+            }
+        }
+        if let Some(dir) = path.parent() {
+            let dir = dir.to_path_buf();
+            self.browser.navigation.pending_selection_path = Some(path);
+            self.navigate_to(dir);
+        }
+    }
+
+    /// Take over config.toml after it was edited outside Heike
+    ///
+    /// Our own saves come back as events too; they match `saved_config` and are ignored.
+    fn process_config_events(&mut self, ctx: &egui::Context) {
+        if self.config_watcher.is_none() {
+            // The config folder only appears with the first save
+            self.setup_config_watcher(ctx);
+        }
+        let Some(path) = Config::config_path() else {
+            return;
+        };
+        let mut touched = false;
+        while let Ok(event) = self.config_watch_rx.try_recv() {
+            touched |=
+                event.is_ok_and(|event| !event.kind.is_access() && event.paths.contains(&path));
+        }
+        if !touched || !path.exists() {
+            return;
+        }
+        let config = match Config::read(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                self.ui
+                    .set_error(format!("{}; keeping the current settings", e));
+                return;
+            }
+        };
+        if config == self.saved_config {
+            return;
+        }
+        if config.tools != self.config.tools {
+            self.capabilities = Capabilities::detect(&config.tools);
+        }
+        self.config = config.clone();
+        self.saved_config = config;
+        self.apply_settings();
+        self.request_refresh();
+        self.ui
+            .set_info(format!("Reloaded settings from {}", path.display()));
+    }
+
+    /// Add `.tmTheme` files from the themes folder and check the configured code themes exist
+    fn load_code_themes(&mut self) {
+        if let Some(dir) = Config::themes_dir().filter(|dir| dir.is_dir()) {
+            if let Err(e) = self.theme_set.add_from_folder(&dir) {
+                self.ui.set_warning(format!(
+                    "Failed to load themes from {}: {}",
+                    dir.display(),
+                    e
+                ));
+            }
+        }
+        for name in [
+            &self.config.previews.code_theme_dark,
+            &self.config.previews.code_theme_light,
+        ] {
+            if !self.theme_set.themes.contains_key(name) {
+                let mut available: Vec<_> = self.theme_set.themes.keys().cloned().collect();
+                available.sort();
+                self.ui.set_warning(format!(
+                    "Unknown code theme \"{}\", available: {}",
+                    name,
+                    available.join(", ")
+                ));
+            }
+        }
+    }
+
+    /// Add `.sublime-syntax` files from the syntaxes folder and route their
+    /// extensions to the text preview
+    fn load_user_syntaxes(&mut self) {
+        let Some(dir) = Config::syntaxes_dir().filter(|dir| dir.is_dir()) else {
+            return;
+        };
+        let mut extra = SyntaxSetBuilder::new();
+        if let Err(e) = extra.add_from_folder(&dir, true) {
+            self.ui.set_warning(format!(
+                "Failed to load syntaxes from {}: {}",
+                dir.display(),
+                e
+            ));
+            return;
+        }
+
+        self.syntax_handlers = extra
+            .syntaxes()
+            .iter()
+            .flat_map(|syntax| syntax.file_extensions.iter())
+            .map(|ext| (ext.clone(), "text".to_string()))
+            .collect();
+        let mut builder = self.syntax_set.clone().into_builder();
+        for syntax in extra.syntaxes() {
+            builder.add(syntax.clone());
+        }
+        self.syntax_set = builder.build();
+        self.apply_extension_handlers();
+    }
+
+    /// Route extensions to preview handlers: user syntaxes to text, then the config's mappings
+    fn apply_extension_handlers(&mut self) {
+        let mut handlers = self.syntax_handlers.clone();
+        // Mappings from the config take precedence
+        handlers.extend(self.config.previews.handlers.clone());
+        self.preview_registry.set_extension_handlers(handlers);
+    }
+
+    /// Syntect theme for code previews in the current light/dark mode
+    fn code_theme(&self) -> &str {
+        match self.ui.theme {
+            Theme::Dark => &self.config.previews.code_theme_dark,
+            Theme::Light => &self.config.previews.code_theme_light,
+        }
+    }
+
     // --- Tab Management ---
 
     fn save_current_tab_state(&mut self) {
         if let Some(tab) = self.tabs.get_active_mut() {
-            tab.current_path = self.navigation.current_path.clone();
-            tab.history = self.navigation.history.clone();
-            tab.history_index = self.navigation.history_index;
-            tab.all_entries = self.entries.all_entries.clone();
-            tab.visible_entries = self.entries.visible_entries.clone();
-            tab.parent_entries = self.entries.parent_entries.clone();
-            tab.selected_index = self.selection.selected_index;
-            tab.directory_selections = self.selection.directory_selections.clone();
-            tab.pending_selection_path = self.navigation.pending_selection_path.clone();
+            tab.current_path = self.browser.navigation.current_path.clone();
+            tab.history = self.browser.navigation.history.clone();
+            tab.history_index = self.browser.navigation.history_index;
+            tab.all_entries = self.browser.entries.all_entries.clone();
+            tab.visible_entries = self.browser.entries.visible_entries.clone();
+            tab.parent_entries = self.browser.entries.parent_entries.clone();
+            tab.selected_index = self.browser.selection.selected_index;
+            tab.directory_selections = self.browser.selection.directory_selections.clone();
+            tab.pending_selection_path = self.browser.navigation.pending_selection_path.clone();
             tab.update_label();
         }
     }
 
     fn load_active_tab_state(&mut self) {
+        self.peek = None;
         if let Some(tab) = self.tabs.get_active() {
-            self.navigation.current_path = tab.current_path.clone();
-            self.navigation.history = tab.history.clone();
-            self.navigation.history_index = tab.history_index;
-            self.entries.all_entries = tab.all_entries.clone();
-            self.entries.visible_entries = tab.visible_entries.clone();
-            self.entries.parent_entries = tab.parent_entries.clone();
-            self.selection.selected_index = tab.selected_index;
-            self.selection.directory_selections = tab.directory_selections.clone();
-            self.navigation.pending_selection_path = tab.pending_selection_path.clone();
+            self.browser.navigation.current_path = tab.current_path.clone();
+            self.browser.navigation.history = tab.history.clone();
+            self.browser.navigation.history_index = tab.history_index;
+            self.browser.entries.all_entries = tab.all_entries.clone();
+            self.browser.entries.visible_entries = tab.visible_entries.clone();
+            self.browser.entries.parent_entries = tab.parent_entries.clone();
+            self.browser.selection.selected_index = tab.selected_index;
+            self.browser.selection.directory_selections = tab.directory_selections.clone();
+            self.browser.navigation.pending_selection_path = tab.pending_selection_path.clone();
+        }
+    }
+
+    /// Reopen the last session's tabs with their history, leaving out directories
+    /// deleted since; false when there was no usable session
+    fn restore_session(&mut self) -> bool {
+        let Some(session) = Session::load() else {
+            return false;
+        };
+        let mut tabs = Vec::new();
+        let mut active_tab = 0;
+        for (i, saved) in session.tabs.into_iter().enumerate() {
+            let history = saved.history.into_iter().map(PathBuf::from).collect();
+            let Some(tab) = prune_history(history, saved.history_index, Path::is_dir)
+                .and_then(|(history, index)| TabState::with_history(history, index))
+            else {
+                continue;
+            };
+            if i <= session.active_tab {
+                active_tab = tabs.len();
+            }
+            tabs.push(tab);
+        }
+        if tabs.is_empty() {
+            return false;
+        }
+        self.tabs.tabs = tabs;
+        self.tabs.active_tab = active_tab;
+        self.load_active_tab_state();
+        true
+    }
+
+    /// Every tab's history as it would be saved, the active one read from the browser
+    fn current_session(&self) -> Session {
+        let tabs = self
+            .tabs
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| {
+                let (history, index) = if i == self.tabs.active_tab {
+                    let navigation = &self.browser.navigation;
+                    bounded_history(&navigation.history, navigation.history_index)
+                } else {
+                    bounded_history(&tab.history, tab.history_index)
+                };
+                SessionTab {
+                    history: history
+                        .iter()
+                        .map(|path| path.to_string_lossy().into_owned())
+                        .collect(),
+                    history_index: index,
+                }
+            })
+            .collect();
+        Session {
+            active_tab: self.tabs.active_tab,
+            tabs,
+        }
+    }
+
+    /// Write the session file when tabs or history changed since the last write
+    fn save_session(&mut self) {
+        let session = self.current_session();
+        if session == self.saved_session {
+            return;
         }
+        if let Err(e) = session.save() {
+            self.ui
+                .set_warning(format!("Could not save session: {}", e));
+        }
+        // A failed write is not retried until something changes
+        self.saved_session = session;
     }
 
     pub(crate) fn switch_to_tab(&mut self, index: usize) {
@@ -208,7 +636,7 @@ impl Heike {
     }
 
     pub(crate) fn new_tab(&mut self, path: Option<PathBuf>) {
-        let path = path.unwrap_or_else(|| self.navigation.current_path.clone());
+        let path = path.unwrap_or_else(|| self.browser.navigation.current_path.clone());
         // Save current tab state
         self.save_current_tab_state();
         // Create new tab
@@ -240,6 +668,7 @@ impl Heike {
         self.save_current_tab_state();
         self.tabs.next_tab();
         self.load_active_tab_state();
+        self.request_refresh();
     }
 
     pub(crate) fn prev_tab(&mut self) {
@@ -249,19 +678,69 @@ impl Heike {
         self.save_current_tab_state();
         self.tabs.prev_tab();
         self.load_active_tab_state();
+        self.request_refresh();
+    }
+
+    /// Point another tab (default: the next one) at the same subpath under its own root
+    pub(crate) fn mirror_path_to_tab(&mut self, index: Option<usize>) {
+        if self.tabs.tab_count() <= 1 {
+            self.ui.set_error("Mirror needs a second tab".into());
+            return;
+        }
+        let active = self.tabs.active_tab;
+        let index = index.unwrap_or((active + 1) % self.tabs.tab_count());
+        if index == active || index >= self.tabs.tab_count() {
+            self.ui.set_error(format!("Invalid tab: {}", index + 1));
+            return;
+        }
+
+        let source = self.browser.navigation.current_path.clone();
+        let selected_name = self
+            .browser
+            .selection
+            .selected_index
+            .and_then(|idx| self.browser.entries.visible_entries.get(idx))
+            .map(|entry| entry.name.clone());
+        let tab = &mut self.tabs.tabs[index];
+        let Some(target) = mirror_path(&source, &tab.current_path, |p| p.is_dir()) else {
+            self.ui
+                .set_error("Tabs share the same tree, nothing to mirror".into());
+            return;
+        };
+
+        if target != tab.current_path {
+            tab.history.truncate(tab.history_index + 1);
+            tab.history.push(target.clone());
+            tab.history_index = tab.history.len() - 1;
+            tab.current_path = target.clone();
+            tab.all_entries.clear();
+            tab.visible_entries.clear();
+            tab.parent_entries.clear();
+            tab.selected_index = None;
+            tab.update_label();
+        }
+        tab.pending_selection_path = selected_name
+            .map(|name| target.join(name))
+            .filter(|p| p.exists());
+
+        self.ui
+            .set_info(format!("Tab {} → {}", index + 1, target.display()));
     }
 
     // --- Directory and File Operations ---
 
     pub(crate) fn request_refresh(&mut self) {
         self.ui.is_loading = true;
-        self.ui.error_message = None;
+        self.ui.clear_errors();
         // Keep info message if it's fresh, or maybe clear it? Let's keep it for feedback.
-        let _ = self.command_tx.send(IoCommand::LoadDirectory(
-            self.navigation.current_path.clone(),
-            self.ui.show_hidden,
-        ));
-        if let Some(parent) = self.navigation.current_path.parent() {
+        let path = self.browser.navigation.current_path.clone();
+        self.show_cached_listing(&path);
+        let _ = self.worker.command_tx.send(if self.ui.flatten {
+            IoCommand::LoadFlattened(path, self.ui.show_hidden)
+        } else {
+            IoCommand::LoadDirectory(path, self.ui.show_hidden)
+        });
+        if let Some(parent) = self.browser.navigation.current_path.parent() {
             let parent_path = parent.to_path_buf();
 
             // Only reload parent if it's different from the cached one or show_hidden changed
@@ -269,7 +748,7 @@ impl Heike {
                 && self.cached_show_hidden == self.ui.show_hidden;
 
             if !cache_valid {
-                let _ = self.command_tx.send(IoCommand::LoadParent(
+                let _ = self.worker.command_tx.send(IoCommand::LoadParent(
                     parent_path.clone(),
                     self.ui.show_hidden,
                 ));
@@ -279,102 +758,63 @@ impl Heike {
             }
             // else: parent is cached and settings unchanged, skip redundant read
         } else {
-            self.entries.parent_entries.clear();
+            self.browser.entries.parent_entries.clear();
             self.cached_parent_path = None;
         }
     }
 
-    pub(crate) fn apply_filter(&mut self) {
-        // Save currently selected item path before filtering
-        let previously_selected = self
-            .selection
-            .selected_index
-            .and_then(|idx| self.entries.visible_entries.get(idx))
-            .map(|e| e.path.clone());
-
-        if self.mode.mode == AppMode::Filtering && !self.mode.command_buffer.is_empty() {
-            let query = self.mode.command_buffer.clone();
-            self.entries.visible_entries = self
-                .entries
-                .all_entries
-                .iter()
-                .filter(|e| fuzzy_match(&e.name, &query))
-                .cloned()
-                .collect();
-        } else {
-            self.entries.visible_entries = self.entries.all_entries.clone();
-        }
-
-        // Apply sorting
-        self.sort_visible_entries();
-
-        // Restore selection to previously selected item if possible
-        if let Some(path) = previously_selected {
-            if let Some(idx) = self
-                .entries
-                .visible_entries
-                .iter()
-                .position(|e| e.path == path)
-            {
-                self.selection.selected_index = Some(idx);
-            }
-            // If path not found, keep current selection if valid; otherwise default to 0
-        }
-
-        // Validate/fix selection if it's now out of bounds
-        if self.entries.visible_entries.is_empty() {
-            self.selection.selected_index = None;
-        } else if let Some(idx) = self.selection.selected_index {
-            if idx >= self.entries.visible_entries.len() {
-                self.selection.selected_index = Some(self.entries.visible_entries.len() - 1);
-            }
-        } else if self.selection.selected_index.is_none() {
-            self.selection.selected_index = Some(0);
+    /// On entering a slow directory, show its last listing while the fresh one is read
+    fn show_cached_listing(&mut self, path: &Path) {
+        let listed_here = self
+            .browser
+            .entries
+            .all_entries
+            .first()
+            .is_some_and(|entry| entry.path.parent() == Some(path));
+        if self.ui.flatten || listed_here {
+            return;
         }
-        self.validate_selection();
+        self.ui.stale_listing = None;
+        let Some(cached) = self.listing_cache.get(path, self.ui.show_hidden) else {
+            return;
+        };
+        self.ui.stale_listing = Some(cached.loaded_at);
+        self.browser.entries.all_entries = cached.entries.clone();
+        self.apply_filter();
+        self.browser.restore_selection_after_load();
     }
 
-    fn sort_visible_entries(&mut self) {
-        use crate::state::{SortBy, SortOrder};
-
-        // Separate directories and files if dirs_first is enabled
-        let (mut dirs, mut files): (Vec<_>, Vec<_>) = self
-            .entries
-            .visible_entries
-            .drain(..)
-            .partition(|e| e.is_dir);
-
-        // Sort both groups by the selected criteria
-        let sort_fn = |a: &FileEntry, b: &FileEntry| -> std::cmp::Ordering {
-            let cmp = match self.ui.sort_options.sort_by {
-                SortBy::Name => a.name.cmp(&b.name),
-                SortBy::Size => a.size.cmp(&b.size),
-                SortBy::Modified => a.modified.cmp(&b.modified),
-                SortBy::Extension => a.extension.cmp(&b.extension),
-            };
-
-            match self.ui.sort_options.sort_order {
-                SortOrder::Ascending => cmp,
-                SortOrder::Descending => cmp.reverse(),
+    /// Toggle the recursive "show all files" listing of the current directory
+    pub(crate) fn toggle_flatten(&mut self) {
+        self.ui.flatten = !self.ui.flatten;
+        self.browser.selection.selected_index = Some(0);
+        self.request_refresh();
+        self.ui.set_info(
+            if self.ui.flatten {
+                "Flatten view: showing all files below this directory"
+            } else {
+                "Flatten view off"
             }
-        };
-
-        dirs.sort_by(sort_fn);
-        files.sort_by(sort_fn);
+            .into(),
+        );
+    }
 
-        // Combine back, with dirs first if enabled
-        if self.ui.sort_options.dirs_first {
-            self.entries.visible_entries.extend(dirs);
-            self.entries.visible_entries.extend(files);
+    pub(crate) fn apply_filter(&mut self) {
+        let query = if self.mode.mode == AppMode::Filtering {
+            self.mode.command_buffer.clone()
         } else {
-            self.entries.visible_entries.extend(files);
-            self.entries.visible_entries.extend(dirs);
-        }
+            String::new()
+        };
+        self.ui.filter_query.clone_from(&query);
+        self.dispatch(Action::Filter {
+            query,
+            sort: self.ui.sort_options,
+        });
     }
 
     fn setup_watcher(&mut self, ctx: &egui::Context) {
         // Only setup if path changed
-        if self.watched_path.as_ref() == Some(&self.navigation.current_path) {
+        if self.watched_path.as_ref() == Some(&self.browser.navigation.current_path) {
             return;
         }
 
@@ -390,21 +830,26 @@ impl Heike {
         }) {
             Ok(mut watcher) => {
                 // Watch the current directory
-                if let Err(e) =
-                    watcher.watch(&self.navigation.current_path, RecursiveMode::NonRecursive)
-                {
-                    self.ui.error_message =
-                        Some((format!("Failed to watch directory: {}", e), Instant::now()));
+                if let Err(e) = watcher.watch(
+                    &self.browser.navigation.current_path,
+                    RecursiveMode::NonRecursive,
+                ) {
+                    self.ui
+                        .set_warning(format!("Failed to watch directory: {}", e));
                     self.watcher = None;
                     self.watched_path = None;
                 } else {
+                    // Commits and checkouts rewrite HEAD / index inside the git directory
+                    self.watched_git_dir = git_dir(&self.browser.navigation.current_path)
+                        .filter(|dir| watcher.watch(dir, RecursiveMode::NonRecursive).is_ok());
+                    self.refresh_git_repo();
                     self.watcher = Some(Box::new(watcher));
-                    self.watched_path = Some(self.navigation.current_path.clone());
+                    self.watched_path = Some(self.browser.navigation.current_path.clone());
                 }
             }
             Err(e) => {
-                self.ui.error_message =
-                    Some((format!("Failed to create watcher: {}", e), Instant::now()));
+                self.ui
+                    .set_warning(format!("Failed to create watcher: {}", e));
                 self.watcher = None;
                 self.watched_path = None;
             }
@@ -426,9 +871,196 @@ impl Heike {
         }
     }
 
+    /// Watch the `notifications.watch` folders for the rest of the session
+    fn setup_folder_watcher(&mut self, ctx: &egui::Context) {
+        if !self.config.notifications.enabled || self.config.notifications.watch.is_empty() {
+            return;
+        }
+        let (tx, rx) = channel();
+        let ctx_clone = ctx.clone();
+        let mut watcher =
+            match notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
+                let _ = tx.send(res);
+                ctx_clone.request_repaint();
+            }) {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    self.ui
+                        .set_warning(format!("Failed to create folder watcher: {}", e));
+                    return;
+                }
+            };
+        for folder in self.config.notifications.watch.clone() {
+            let path = self.resolve_command_path(&folder);
+            if let Err(e) = watcher.watch(&path, RecursiveMode::NonRecursive) {
+                self.ui
+                    .set_warning(format!("Cannot watch {}: {}", path.display(), e));
+            }
+        }
+        self.folder_watcher = Some(Box::new(watcher));
+        self.folder_watch_rx = rx;
+    }
+
+    /// Announce files that appeared in watched folders while the window is unfocused
+    fn process_folder_events(&mut self, ctx: &egui::Context) {
+        let mut arrived: Vec<PathBuf> = Vec::new();
+        while let Ok(event) = self.folder_watch_rx.try_recv() {
+            let Ok(event) = event else {
+                continue;
+            };
+            // Downloads usually land as a temp file that is renamed when complete
+            let created = matches!(
+                event.kind,
+                notify::EventKind::Create(_)
+                    | notify::EventKind::Modify(notify::event::ModifyKind::Name(
+                        notify::event::RenameMode::To | notify::event::RenameMode::Both
+                    ))
+            );
+            if let Some(path) = event.paths.last().filter(|_| created) {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let partial = name.starts_with('.')
+                    || [".part", ".partial", ".crdownload", ".download", ".tmp"]
+                        .iter()
+                        .any(|ext| name.ends_with(ext));
+                if !partial && !arrived.contains(path) {
+                    arrived.push(path.clone());
+                }
+            }
+        }
+        if arrived.is_empty() || ctx.input(|i| i.viewport().focused).unwrap_or(true) {
+            return;
+        }
+
+        let folder = arrived[0]
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let names: Vec<String> = arrived
+            .iter()
+            .map(|p| {
+                p.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        let body = if names.len() == 1 {
+            names[0].clone()
+        } else {
+            format!("{} files: {}", names.len(), names.join(", "))
+        };
+        self.notify(&format!("New in {}", folder), &body);
+    }
+
+    /// Desktop notification, unless `notifications.enabled` is off
+    fn notify(&self, title: &str, body: &str) {
+        if self.config.notifications.enabled {
+            // A missing notification daemon is not worth an error toast
+            let _ = platform::notify(title, body);
+        }
+    }
+
+    /// Whether a job has run long enough to be worth a desktop notification
+    fn job_is_long(&self, job: &BatchJob) -> bool {
+        job.started.elapsed() >= Duration::from_secs(self.config.notifications.job_min_secs)
+    }
+
+    /// Re-fetch git status and other details for the listed entries once changes settle
+    fn refresh_details_if_due(&mut self, ctx: &egui::Context) {
+        let Some(due) = self.details_refresh_due else {
+            return;
+        };
+        let now = Instant::now();
+        if now < due {
+            ctx.request_repaint_after(due - now);
+            return;
+        }
+        self.details_refresh_due = None;
+
+        let current = self.browser.navigation.current_path.clone();
+        let mut listings = Vec::new();
+        if !self.ui.flatten {
+            listings.push((current.clone(), &self.browser.entries.all_entries));
+        }
+        if let Some(parent) = current.parent() {
+            listings.push((parent.to_path_buf(), &self.browser.entries.parent_entries));
+        }
+        for (dir, entries) in listings {
+            let paths = entries.iter().map(|e| e.path.clone()).collect();
+            // Not critical: a full worker queue just means this refresh is dropped
+            let _ = self
+                .worker
+                .command_tx
+                .try_send(IoCommand::EnrichEntries { dir, paths });
+        }
+    }
+
+    /// Ask the worker to re-read the branch shown in the top bar git menu
+    fn refresh_git_repo(&mut self) {
+        let git_usable = self.capabilities.missing_reason(Tool::Git).is_none();
+        if self.watched_git_dir.is_none() || !git_usable {
+            self.git_repo = None;
+            return;
+        }
+        let _ = self.worker.command_tx.try_send(IoCommand::LoadGitRepo(
+            self.browser.navigation.current_path.clone(),
+        ));
+    }
+
+    /// Checkout or stash on the worker; the result comes back as `GitDone`
+    fn run_git(&mut self, action: GitAction) {
+        let dir = self.browser.navigation.current_path.clone();
+        if self
+            .worker
+            .command_tx
+            .try_send(IoCommand::RunGit { dir, action })
+            .is_err()
+        {
+            self.ui.set_error("Busy, try again in a moment".into());
+        }
+    }
+
+    pub(crate) fn git_checkout(&mut self, branch: &str) {
+        self.run_git(GitAction::Checkout(branch.to_string()));
+    }
+
+    pub(crate) fn git_stash_untracked(&mut self) {
+        self.run_git(GitAction::StashUntracked);
+    }
+
+    pub(crate) fn git_unstash_untracked(&mut self) {
+        self.run_git(GitAction::UnstashUntracked);
+    }
+
     fn handle_fs_event(&mut self, event: Event) {
         use notify::EventKind;
 
+        if let Some(git_dir) = &self.watched_git_dir {
+            if event.paths.iter().any(|p| p.starts_with(git_dir)) {
+                let head_or_index = event.paths.iter().any(|p| {
+                    p.parent() == Some(git_dir.as_path())
+                        && matches!(
+                            p.file_name().and_then(|n| n.to_str()),
+                            Some("HEAD" | "index")
+                        )
+                });
+                if head_or_index {
+                    self.details_refresh_due = Some(Instant::now() + DETAILS_REFRESH_DELAY);
+                    self.refresh_git_repo();
+                }
+                return;
+            }
+        }
+
+        // Folder sizes above a change are out of date even when the folder's own mtime is not
+        {
+            let mut cache = self.preview_cache.borrow_mut();
+            for path in &event.paths {
+                cache.remove_section_up(path, view::DIR_SIZE_SECTION);
+            }
+        }
+
         // Check if event affects the cached parent directory
         if let Some(cached_parent) = &self.cached_parent_path {
             let affects_parent = event.paths.iter().any(|p| {
@@ -443,23 +1075,32 @@ impl Heike {
 
         // Only handle events for the current directory
         let in_current_dir = event.paths.iter().any(|p| {
-            p.parent() == Some(self.navigation.current_path.as_path())
-                || p.as_path() == self.navigation.current_path.as_path()
+            p.parent() == Some(self.browser.navigation.current_path.as_path())
+                || p.as_path() == self.browser.navigation.current_path.as_path()
         });
 
         if !in_current_dir {
             return;
         }
+        // Edits change git status too (modified, untracked)
+        self.details_refresh_due = Some(Instant::now() + DETAILS_REFRESH_DELAY);
 
         match event.kind {
             EventKind::Create(_) => {
                 // File/directory created - add to entries
                 for path in &event.paths {
-                    if path.parent() == Some(self.navigation.current_path.as_path()) {
-                        if let Some(new_entry) = FileEntry::from_path(path.clone()) {
+                    if path.parent() == Some(self.browser.navigation.current_path.as_path()) {
+                        if let Some(mut new_entry) = FileEntry::from_path(path.clone()) {
+                            self.adjust_link_stats(std::slice::from_mut(&mut new_entry));
                             // Check if entry already exists
-                            if !self.entries.all_entries.iter().any(|e| &e.path == path) {
-                                self.entries.all_entries.push(new_entry);
+                            if !self
+                                .browser
+                                .entries
+                                .all_entries
+                                .iter()
+                                .any(|e| &e.path == path)
+                            {
+                                self.browser.entries.all_entries.push(new_entry);
                             }
                         }
                     }
@@ -469,30 +1110,48 @@ impl Heike {
             EventKind::Remove(_) => {
                 // File/directory removed - remove from entries
                 for path in &event.paths {
-                    self.entries.all_entries.retain(|e| &e.path != path);
-                    self.entries.visible_entries.retain(|e| &e.path != path);
-                    self.entries.parent_entries.retain(|e| &e.path != path);
+                    // visible_entries is rebuilt by apply_filter, which needs the old
+                    // order to keep the cursor on the same file
+                    self.browser.entries.all_entries.retain(|e| &e.path != path);
+                    self.browser
+                        .entries
+                        .parent_entries
+                        .retain(|e| &e.path != path);
                     // Remove from multi-selection if present
-                    self.selection.multi_selection.remove(path);
+                    self.browser.selection.multi_selection.remove(path);
                 }
                 self.apply_filter();
-                self.validate_selection();
+                self.browser.clamp_selection();
             }
             EventKind::Modify(_) => {
                 // File modified - update entry metadata
                 for path in &event.paths {
-                    if let Some(updated_entry) = FileEntry::from_path(path.clone()) {
-                        // Update in all_entries
-                        if let Some(entry) = self
+                    if let Some(mut updated_entry) = FileEntry::from_path(path.clone()) {
+                        self.adjust_link_stats(std::slice::from_mut(&mut updated_entry));
+                        // Keep the details until the pending refresh replaces them
+                        if let Some(existing) = self
+                            .browser
                             .entries
                             .all_entries
-                            .iter_mut()
+                            .iter()
+                            .chain(&self.browser.entries.parent_entries)
+                            .find(|e| &e.path == path)
+                        {
+                            updated_entry.details = existing.details.clone();
+                        }
+                        // Update in all_entries
+                        if let Some(entry) = self
+                            .browser
+                            .entries
+                            .all_entries
+                            .iter_mut()
                             .find(|e| &e.path == path)
                         {
                             *entry = updated_entry.clone();
                         }
                         // Update in visible_entries
                         if let Some(entry) = self
+                            .browser
                             .entries
                             .visible_entries
                             .iter_mut()
@@ -502,6 +1161,7 @@ impl Heike {
                         }
                         // Update in parent_entries
                         if let Some(entry) = self
+                            .browser
                             .entries
                             .parent_entries
                             .iter_mut()
@@ -519,45 +1179,109 @@ impl Heike {
         }
     }
 
+    /// Apply `ui.link_target_stats` to freshly read entries
+    fn adjust_link_stats(&self, entries: &mut [FileEntry]) {
+        if !self.config.ui.link_target_stats {
+            entries.iter_mut().for_each(FileEntry::use_link_stats);
+        }
+    }
+
     fn process_async_results(&mut self) {
-        while let Ok(result) = self.result_rx.try_recv() {
+        while let Ok(result) = self.worker.result_rx.try_recv() {
             match result {
-                IoResult::DirectoryLoaded { path, entries } => {
-                    if path != self.navigation.current_path {
+                IoResult::DirectoryLoaded {
+                    path,
+                    mut entries,
+                    elapsed,
+                } => {
+                    self.adjust_link_stats(&mut entries);
+                    self.listing_cache.record(
+                        &path,
+                        &entries,
+                        self.ui.show_hidden,
+                        elapsed,
+                        Instant::now(),
+                    );
+                    if path != self.browser.navigation.current_path {
+                        continue;
+                    }
+
+                    // A plain listing arriving after flatten was switched on is stale
+                    if self.ui.flatten {
                         continue;
                     }
 
-                    self.entries.all_entries = entries;
+                    carry_details(&self.browser.entries.all_entries, &mut entries);
+                    self.labels.refresh(&entries);
+                    self.browser.entries.all_entries = entries;
                     self.ui.is_loading = false;
+                    self.ui.stale_listing = None;
                     self.apply_filter();
-
-                    // If there's a pending selection path, find and select it
-                    if let Some(pending_path) = self.navigation.pending_selection_path.take() {
-                        if let Some(idx) = self
-                            .entries
-                            .visible_entries
-                            .iter()
-                            .position(|e| e.path == pending_path)
-                        {
-                            self.selection.selected_index = Some(idx);
-                        }
+                    self.browser.restore_selection_after_load();
+                }
+                IoResult::FlattenBatch {
+                    path,
+                    mut entries,
+                    first,
+                    done,
+                    truncated,
+                } => {
+                    if path != self.browser.navigation.current_path || !self.ui.flatten {
+                        continue;
                     }
 
-                    // Validate selection after loading
-                    if let Some(idx) = self.selection.selected_index {
-                        if idx >= self.entries.visible_entries.len()
-                            && !self.entries.visible_entries.is_empty()
-                        {
-                            self.selection.selected_index =
-                                Some(self.entries.visible_entries.len() - 1);
+                    self.adjust_link_stats(&mut entries);
+                    self.labels.refresh(&entries);
+                    if first {
+                        self.browser.entries.all_entries = entries;
+                    } else {
+                        self.browser.entries.all_entries.extend(entries);
+                    }
+                    self.apply_filter();
+                    if done {
+                        self.ui.is_loading = false;
+                        self.browser.restore_selection_after_load();
+                        if truncated {
+                            self.ui.set_warning(format!(
+                                "Flatten view limited to {} files within {} levels",
+                                self.browser.entries.all_entries.len(),
+                                FLATTEN_MAX_DEPTH
+                            ));
                         }
                     }
                 }
-                IoResult::ParentLoaded(entries) => {
-                    self.entries.parent_entries = entries;
+                IoResult::PeekLoaded { path, entries } => {
+                    if let Some(peek) = self.peek.as_mut().filter(|peek| peek.path == path) {
+                        peek.entries = Some(entries.map(|mut entries| {
+                            if !self.config.ui.link_target_stats {
+                                entries.iter_mut().for_each(FileEntry::use_link_stats);
+                            }
+                            crate::core::sort_entries(&mut entries, self.ui.sort_options);
+                            entries
+                        }));
+                    }
+                }
+                IoResult::ParentLoaded(mut entries) => {
+                    self.adjust_link_stats(&mut entries);
+                    carry_details(&self.browser.entries.parent_entries, &mut entries);
+                    self.browser.entries.parent_entries = entries;
                 }
-                IoResult::SearchCompleted(results) => {
+                IoResult::EntriesEnriched(details) => {
+                    self.browser.entries.apply_details(details);
+                }
+                IoResult::SearchCompleted {
+                    mut results,
+                    capped,
+                    archives,
+                } => {
                     self.ui.search_in_progress = false;
+                    self.archive_member_error = None;
+                    if self.ui.search_options.rank_by_matches {
+                        rank_by_file(&mut results);
+                    }
+                    self.ui.search_file_hits = file_hit_counts(&results);
+                    self.ui.search_capped = capped.into_iter().collect();
+                    self.ui.search_archive_mtimes = archives;
                     let result_count = results.len();
                     // Handle empty results: use None-like value (usize::MAX) to indicate no selection
                     let selected_index = if results.is_empty() { usize::MAX } else { 0 };
@@ -565,6 +1289,7 @@ impl Heike {
                         query: self.ui.search_query.clone(),
                         results,
                         selected_index,
+                        marked: HashSet::new(),
                     });
                     if result_count == 0 {
                         self.ui.set_info("No matches found".into());
@@ -584,12 +1309,283 @@ impl Heike {
                     self.ui.search_files_skipped = files_skipped;
                     self.ui.search_errors = errors;
                 }
+                IoResult::DirDiffCompleted {
+                    left,
+                    right,
+                    entries,
+                    truncated,
+                } => {
+                    self.ui.is_loading = false;
+                    if entries.is_empty() {
+                        self.ui.set_info(format!(
+                            "No differences between {} and {}",
+                            left.display(),
+                            right.display()
+                        ));
+                    } else {
+                        if truncated {
+                            self.ui.set_warning(format!(
+                                "Showing the first {} differences only; compare subfolders for the rest",
+                                entries.len()
+                            ));
+                        } else {
+                            self.ui
+                                .set_info(format!("{} differences found", entries.len()));
+                        }
+                        self.mode.set_mode(AppMode::DirDiff {
+                            left,
+                            right,
+                            entries,
+                            selected_index: 0,
+                        });
+                    }
+                }
+                IoResult::EmptyFound { root, items } => {
+                    self.ui.is_loading = false;
+                    if items.is_empty() {
+                        self.ui.set_info(format!(
+                            "No empty folders or zero-byte files under {}",
+                            root.display()
+                        ));
+                    } else {
+                        self.ui.set_info(format!(
+                            "{} empty items found, ticked ones go to the {}",
+                            items.len(),
+                            platform::TRASH_NAME
+                        ));
+                        self.mode.set_mode(AppMode::Cleanup {
+                            root,
+                            items,
+                            selected_index: 0,
+                        });
+                    }
+                }
+                IoResult::BrokenLinksFound { root, links } => {
+                    self.ui.is_loading = false;
+                    if links.is_empty() {
+                        self.ui.set_info(format!(
+                            "No broken links or shortcuts under {}",
+                            root.display()
+                        ));
+                    } else {
+                        self.ui
+                            .set_info(format!("{} broken links found", links.len()));
+                        self.mode.set_mode(AppMode::BrokenLinks {
+                            root,
+                            links,
+                            selected_index: 0,
+                            retarget: None,
+                        });
+                    }
+                }
+                IoResult::NamesAudited { root, issues } => {
+                    self.ui.is_loading = false;
+                    if issues.is_empty() {
+                        self.ui.set_info(format!(
+                            "Every name under {} is valid on Windows and SMB shares",
+                            root.display()
+                        ));
+                    } else {
+                        self.ui
+                            .set_info(format!("{} names need renaming", issues.len()));
+                        self.mode.set_mode(AppMode::NameAudit {
+                            root,
+                            issues,
+                            selected_index: 0,
+                        });
+                    }
+                }
+                IoResult::DirDiffFailed(msg) => {
+                    self.ui.is_loading = false;
+                    self.ui.set_error(format!("Compare failed: {}", msg));
+                }
+                IoResult::ChecksumComputed {
+                    path,
+                    modified,
+                    result,
+                } => {
+                    let digest = result.unwrap_or_else(|e| {
+                        self.ui.set_error(format!("Checksum failed: {}", e));
+                        format!("Failed: {}", e)
+                    });
+                    self.preview_cache.borrow_mut().insert_section(
+                        path,
+                        view::CHECKSUM_SECTION,
+                        digest,
+                        modified,
+                    );
+                }
+                IoResult::DirSizeComputed {
+                    path,
+                    modified,
+                    usage,
+                } => {
+                    self.preview_cache.borrow_mut().insert_section(
+                        path,
+                        view::DIR_SIZE_SECTION,
+                        usage.to_section(),
+                        modified,
+                    );
+                }
+                IoResult::ArchiveListed {
+                    path,
+                    modified,
+                    resume,
+                    page,
+                } => {
+                    if self.archive_listing_pending.as_ref() == Some(&(path.clone(), resume)) {
+                        self.archive_listing_pending = None;
+                    }
+                    if resume == 0 {
+                        let mut listing = view::ArchiveListing::new(path, modified);
+                        listing.add_page(page);
+                        self.archive_listing = Some(listing);
+                    } else if let Some(listing) = self.archive_listing.as_mut().filter(|l| {
+                        l.path == path && l.modified == modified && l.next == Some(resume)
+                    }) {
+                        listing.add_page(page);
+                    }
+                }
+                IoResult::ArchiveCounted {
+                    path,
+                    modified,
+                    result,
+                } => {
+                    let totals = result
+                        .map(|totals| totals.to_section())
+                        .unwrap_or_else(|e| format!("Count failed: {}", e));
+                    self.preview_cache.borrow_mut().insert_section(
+                        path,
+                        view::ARCHIVE_TOTALS_SECTION,
+                        totals,
+                        modified,
+                    );
+                }
+                IoResult::ArchiveMemberRead {
+                    archive,
+                    member,
+                    modified,
+                    text,
+                } => {
+                    let key = archive.join(&member);
+                    if self.archive_member_pending.as_ref() == Some(&key) {
+                        self.archive_member_pending = None;
+                    }
+                    match text {
+                        Ok(text) => self.preview_cache.borrow_mut().insert(key, text, modified),
+                        Err(e) => self.archive_member_error = Some((key, e)),
+                    }
+                }
+                IoResult::ArchiveMemberExtracted { member, line, path } => {
+                    self.open_extracted_member(&member, line, path);
+                }
+                IoResult::PreviewListingLoaded {
+                    path,
+                    show_hidden,
+                    modified,
+                    entries,
+                } => {
+                    if self.preview_listing_pending.as_ref() == Some(&path) {
+                        self.preview_listing_pending = None;
+                    }
+                    self.preview_listing = Some(view::PreviewListing {
+                        path,
+                        modified,
+                        show_hidden,
+                        entries,
+                    });
+                }
+                IoResult::PreviewWorkCancelled(path) => {
+                    if self.preview_listing_pending.as_ref() == Some(&path) {
+                        self.preview_listing_pending = None;
+                    }
+                    if self
+                        .archive_listing_pending
+                        .as_ref()
+                        .is_some_and(|(pending, _)| *pending == path)
+                    {
+                        self.archive_listing_pending = None;
+                    }
+                    if let Some(listing) = self.archive_listing.as_mut() {
+                        listing.loading &= listing.path != path;
+                    }
+                    // Drop the placeholder so the preview asks again when the cursor returns
+                    let mut cache = self.preview_cache.borrow_mut();
+                    cache.remove_section(&path, view::DIR_SIZE_SECTION);
+                    cache.remove_section(&path, view::ARCHIVE_TOTALS_SECTION);
+                }
+                IoResult::GitRepoLoaded { dir, repo } => {
+                    // A reply for a directory left meanwhile is dropped
+                    if dir == self.browser.navigation.current_path {
+                        self.git_repo = repo;
+                    }
+                }
+                IoResult::LastCommitLoaded {
+                    path,
+                    modified,
+                    log,
+                } => {
+                    self.preview_cache.borrow_mut().insert_section(
+                        path,
+                        view::GIT_SECTION,
+                        log,
+                        modified,
+                    );
+                }
+                IoResult::GitDone(result) => {
+                    match result {
+                        Ok(message) => self.ui.set_info(message),
+                        Err(e) => self.ui.set_error(e),
+                    }
+                    self.request_refresh();
+                }
+                IoResult::VolumesListed(volumes) => {
+                    if let AppMode::Computer {
+                        cards,
+                        selected_index,
+                        loading_volumes,
+                    } = &mut self.mode.mode
+                    {
+                        // Drives go first; the cursor stays on the card it was on
+                        let count = volumes.len();
+                        cards.splice(0..0, volumes.into_iter().map(ComputerCard::from_volume));
+                        *selected_index += count;
+                        *loading_volumes = false;
+                    }
+                }
+                IoResult::TrashScanned(result) => self.trash_usage = Some(result),
+                IoResult::DestinationChecked(warning) => {
+                    if let Some(warning) = warning {
+                        self.ui.set_warning(warning);
+                    }
+                }
+                IoResult::TrashEmptied(result) => {
+                    match result {
+                        Ok(0) => {}
+                        Ok(count) => self.ui.set_info(format!(
+                            "Permanently deleted {} item(s) from {}",
+                            count,
+                            platform::TRASH_NAME
+                        )),
+                        Err(e) => self.ui.set_error(format!(
+                            "Could not empty {}: {}",
+                            platform::TRASH_NAME,
+                            e
+                        )),
+                    }
+                    if matches!(self.mode.mode, AppMode::Maintenance { .. }) {
+                        self.scan_trash();
+                    }
+                }
                 IoResult::Error(msg) => {
                     self.ui.is_loading = false;
                     self.ui.search_in_progress = false;
                     self.ui.set_error(msg);
-                    self.entries.all_entries.clear();
-                    self.entries.visible_entries.clear();
+                    // An unreachable share keeps showing its last known listing
+                    if self.ui.stale_listing.is_none() {
+                        self.browser.entries.all_entries.clear();
+                        self.browser.entries.visible_entries.clear();
+                    }
                 }
             }
         }
@@ -598,255 +1594,1755 @@ impl Heike {
     // --- Navigation Logic ---
 
     pub(crate) fn navigate_to(&mut self, path: PathBuf) {
-        if path.is_dir() {
-            // Save current selection before navigating away
-            if let Some(idx) = self.selection.selected_index {
-                self.selection
-                    .directory_selections
-                    .insert(self.navigation.current_path.clone(), idx);
-            }
-
-            self.navigation.current_path = path.clone();
+        self.dispatch(Action::Open(path));
+    }
 
-            if self.navigation.history_index < self.navigation.history.len() - 1 {
-                self.navigation
-                    .history
-                    .truncate(self.navigation.history_index + 1);
-            }
-            self.navigation.history.push(path);
-            self.navigation.history_index = self.navigation.history.len() - 1;
+    /// Show a sibling directory in the listing pane without leaving the current one;
+    /// peeking at the current directory, or at the one already shown, ends the peek
+    pub(crate) fn peek_at(&mut self, path: PathBuf) {
+        let same = self.peek.as_ref().is_some_and(|peek| peek.path == path);
+        if same || path == self.browser.navigation.current_path || !path.is_dir() {
+            self.peek = None;
+            return;
+        }
+        let _ = self
+            .worker
+            .command_tx
+            .send(IoCommand::LoadPeek(path.clone(), self.ui.show_hidden));
+        self.peek = Some(PeekState::new(path));
+    }
 
-            self.finish_navigation();
-        } else if let Err(e) = open::that(&path) {
-            self.ui.set_error(format!("Could not open file: {}", e));
+    /// Navigate to the peeked directory, with the cursor on `select` when given
+    pub(crate) fn open_peek(&mut self, select: Option<PathBuf>) {
+        let Some(peek) = self.peek.take() else {
+            return;
+        };
+        if let Some(select) = select {
+            self.browser.navigation.pending_selection_path = Some(select);
         }
+        self.navigate_to(peek.path);
     }
 
-    pub(crate) fn navigate_up(&mut self) {
-        if let Some(parent) = self.navigation.current_path.parent() {
-            // Save current selection before navigating up
-            if let Some(idx) = self.selection.selected_index {
-                self.selection
-                    .directory_selections
-                    .insert(self.navigation.current_path.clone(), idx);
+    // --- Computer Page ---
+
+    /// Show drives, places and recent locations in place of the listing; drives
+    /// arrive from the worker since a stalled network mount would block the frame
+    pub(crate) fn open_computer(&mut self) {
+        let mut cards = Vec::new();
+        if let Some(dirs) = directories::UserDirs::new() {
+            cards.push(ComputerCard::new(
+                CardKind::Home,
+                "Home".into(),
+                dirs.home_dir().to_path_buf(),
+            ));
+        }
+        let mut keys = self.bookmarks.keys();
+        keys.sort();
+        for key in keys {
+            if let Some(path) = self.bookmarks.resolve_path(&key) {
+                let name = path.file_name().map_or_else(
+                    || path.display().to_string(),
+                    |n| n.to_string_lossy().into(),
+                );
+                cards.push(ComputerCard::new(
+                    CardKind::Bookmark,
+                    format!("{}  (g{})", name, key),
+                    path,
+                ));
             }
-            // When navigating to parent, select the child directory we came from
-            self.navigation.pending_selection_path = Some(self.navigation.current_path.clone());
-            self.navigate_to(parent.to_path_buf());
         }
+        let navigation = &self.browser.navigation;
+        let others: Vec<PathBuf> = self
+            .tabs
+            .tabs
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != self.tabs.active_tab)
+            .map(|(_, tab)| tab.current_path.clone())
+            .collect();
+        for path in recent_locations(
+            (&navigation.history, navigation.history_index),
+            &others,
+            Path::is_dir,
+        ) {
+            let name = path.file_name().map_or_else(
+                || path.display().to_string(),
+                |n| n.to_string_lossy().into(),
+            );
+            cards.push(ComputerCard::new(CardKind::Recent, name, path));
+        }
+
+        let loading_volumes = self.worker.command_tx.send(IoCommand::ListVolumes).is_ok();
+        self.mode.set_mode(AppMode::Computer {
+            cards,
+            selected_index: 0,
+            loading_volumes,
+        });
     }
 
-    pub(crate) fn navigate_back(&mut self) {
-        if self.navigation.history_index == 0 {
+    /// Leave the Computer page for the card's directory
+    pub(crate) fn open_computer_card(&mut self, index: usize) {
+        let AppMode::Computer { cards, .. } = &self.mode.mode else {
+            return;
+        };
+        let Some(path) = cards.get(index).map(|card| card.path.clone()) else {
             return;
+        };
+        self.mode.set_mode(AppMode::Normal);
+        if path.is_dir() {
+            self.navigate_to(path);
+        } else {
+            self.ui
+                .set_error(format!("{} is not available", path.display()));
         }
+    }
 
-        if let Some(idx) = self.selection.selected_index {
-            self.selection
-                .directory_selections
-                .insert(self.navigation.current_path.clone(), idx);
+    /// Go to the top of the git project holding the current directory
+    pub(crate) fn jump_to_project_root(&mut self) {
+        match project_root(&self.browser.navigation.current_path) {
+            Some(root) => self.jump_to_ancestor(root, "project root"),
+            None => self
+                .ui
+                .set_info("Not inside a git project (no .git found above)".into()),
         }
+    }
 
-        let mut idx = self.navigation.history_index;
-        while idx > 0 {
-            idx -= 1;
-            let target = self.navigation.history[idx].clone();
-            if target.is_dir() {
-                self.navigation.history_index = idx;
-                self.navigation.current_path = target;
-                self.finish_navigation();
-                return;
-            } else {
-                self.navigation.history.remove(idx);
-                self.navigation.history_index -= 1;
-            }
+    /// Go to the mount point of the filesystem holding the current directory
+    pub(crate) fn jump_to_mount_root(&mut self) {
+        match platform::mount_root(&self.browser.navigation.current_path) {
+            Some(root) => self.jump_to_ancestor(root, "mount root"),
+            None => self
+                .ui
+                .set_error("Could not determine the mount point of this directory".into()),
         }
-
-        self.ui
-            .set_error("Previous directory no longer exists".into());
     }
 
-    pub(crate) fn navigate_forward(&mut self) {
-        if self.navigation.history_index >= self.navigation.history.len() - 1 {
+    /// Navigate up to `root`, selecting the entry the current directory lies under
+    fn jump_to_ancestor(&mut self, root: PathBuf, what: &str) {
+        let current = self.browser.navigation.current_path.clone();
+        if root == current {
+            self.ui.set_info(format!("Already at the {}", what));
             return;
         }
+        self.browser.navigation.pending_selection_path = current
+            .ancestors()
+            .find(|dir| dir.parent() == Some(root.as_path()))
+            .map(Path::to_path_buf);
+        self.navigate_to(root);
+    }
 
-        if let Some(idx) = self.selection.selected_index {
-            self.selection
-                .directory_selections
-                .insert(self.navigation.current_path.clone(), idx);
+    /// Go to a bookmarked directory, or to the folder of a bookmarked file with it selected
+    pub(crate) fn jump_to_bookmark(&mut self, key: &str) {
+        match self.bookmarks.resolve_path(key) {
+            Some(path) if path.is_dir() => self.navigate_to(path),
+            Some(path) if path.exists() => {
+                if let Some(parent) = path.parent() {
+                    let parent = parent.to_path_buf();
+                    self.browser.navigation.pending_selection_path = Some(path);
+                    self.navigate_to(parent);
+                }
+            }
+            Some(path) => self.ui.set_error(format!(
+                "Bookmark '{}' points to {}, which does not exist",
+                key,
+                path.display()
+            )),
+            None => self.ui.set_info(format!("No bookmark '{}' defined", key)),
         }
+    }
+
+    /// Act on the preview: enter a previewed directory, or open a text file in the
+    /// editor at the first line visible in the preview
+    /// Open a search hit; matches inside an archive are extracted and opened at the line
+    pub(crate) fn open_search_result(&mut self, result: &SearchResult) {
+        let Some(member) = &result.archive_member else {
+            if result.file_path.is_file() {
+                self.open_file(&result.file_path);
+            }
+            return;
+        };
+        self.ui.set_info(format!("Extracting {}…", member));
+        let _ = self
+            .worker
+            .command_tx
+            .send(IoCommand::ExtractArchiveMember {
+                archive: result.file_path.clone(),
+                member: member.clone(),
+                line: result.line_number,
+            });
+    }
 
-        let idx = self.navigation.history_index + 1;
-        // idx doesn't change - when we remove at idx, the next element shifts down to idx
-        while idx < self.navigation.history.len() {
-            let target = self.navigation.history[idx].clone();
-            if target.is_dir() {
-                self.navigation.history_index = idx;
-                self.navigation.current_path = target;
-                self.finish_navigation();
+    /// Open a member the worker extracted for `open_search_result`
+    fn open_extracted_member(&mut self, member: &str, line: usize, path: Result<PathBuf, String>) {
+        let path = match path {
+            Ok(path) => path,
+            Err(e) => {
+                self.ui
+                    .set_error(format!("Could not extract {}: {}", member, e));
                 return;
             }
-            // Remove invalid (non-directory) entry; next element shifts to current idx
-            // so we don't increment idx. history_index doesn't change since entries
-            // before current position don't shift.
-            self.navigation.history.remove(idx);
+        };
+        match self.capabilities.open_in_editor_at(&path, Some(line)) {
+            Ok(()) => self.ui.set_info(format!(
+                "Opened {} at line {} (extracted copy)",
+                member, line
+            )),
+            // No editor configured: hand the extracted copy to the default application
+            Err(_) => self.open_file(&path),
         }
-
-        self.ui.set_error("Next directory no longer exists".into());
     }
 
-    fn finish_navigation(&mut self) {
-        self.mode.command_buffer.clear();
+    /// Leave the search results with the marked files as the multi-selection
+    ///
+    /// Files that share one folder are shown there; otherwise the selection spans
+    /// folders and batch actions (copy, cut, stage, delete) still cover all of it.
+    pub(crate) fn select_marked_results(&mut self, marked: HashSet<PathBuf>) {
+        if marked.is_empty() {
+            self.ui
+                .set_info("No files marked (Space marks the file of a result)".into());
+            return;
+        }
+        let mut paths: Vec<PathBuf> = marked.into_iter().filter(|p| p.exists()).collect();
+        if paths.is_empty() {
+            self.ui
+                .set_warning("The marked files no longer exist".into());
+            return;
+        }
+        paths.sort();
+        let folders: HashSet<PathBuf> = paths
+            .iter()
+            .filter_map(|p| p.parent().map(Path::to_path_buf))
+            .collect();
         self.mode.set_mode(AppMode::Normal);
-        self.selection.multi_selection.clear();
-        // Restore saved selection for this directory, or default to 0
-        self.selection.selected_index = self
-            .selection
-            .directory_selections
-            .get(&self.navigation.current_path)
-            .copied()
-            .or(Some(0));
-        // Re-enable autoscroll when navigating to ensure view centers on selection
-        self.selection.disable_autoscroll = false;
-        self.request_refresh();
+        if let [folder] = folders.iter().collect::<Vec<_>>()[..] {
+            if *folder != self.browser.navigation.current_path {
+                self.browser.navigation.pending_selection_path = paths.first().cloned();
+                self.navigate_to(folder.clone());
+            }
+        }
+        let message = if folders.len() > 1 {
+            format!(
+                "Selected {} marked file(s) in {} folders",
+                paths.len(),
+                folders.len()
+            )
+        } else {
+            format!("Selected {} marked file(s)", paths.len())
+        };
+        self.browser.selection.multi_selection = paths.into_iter().collect();
+        self.ui.set_info(message);
     }
 
-    // --- File Operations (Injected) ---
-
-    pub(crate) fn yank_selection(&mut self, op: ClipboardOp) {
-        self.clipboard.clear();
-        self.clipboard_op = Some(op);
-
-        if !self.selection.multi_selection.is_empty() {
-            self.clipboard = self.selection.multi_selection.clone();
-            self.mode.set_mode(AppMode::Normal);
-            self.selection.multi_selection.clear();
-        } else if let Some(idx) = self.selection.selected_index {
-            if let Some(entry) = self.entries.visible_entries.get(idx) {
-                self.clipboard.insert(entry.path.clone());
+    /// Lines around an archive match for the search results preview
+    ///
+    /// The worker decompresses the member once; its text is cached under a virtual
+    /// `<archive>/<member>` path keyed by the archive's mtime as of the search. Until
+    /// it arrives the member is put in `request` and `None` is returned.
+    pub(crate) fn archive_match_context(
+        &self,
+        result: &SearchResult,
+        radius: usize,
+        request: &RefCell<Option<(PathBuf, String, SystemTime)>>,
+    ) -> Option<Result<Vec<(usize, String)>, String>> {
+        let member = result.archive_member.as_ref()?;
+        let modified = *self.ui.search_archive_mtimes.get(&result.file_path)?;
+        let key = result.file_path.join(member);
+        if let Some((failed, e)) = &self.archive_member_error {
+            if *failed == key {
+                return Some(Err(e.clone()));
             }
         }
+        let Some(text) = self.preview_cache.borrow().get(&key, modified) else {
+            *request.borrow_mut() = Some((result.file_path.clone(), member.clone(), modified));
+            return None;
+        };
+        let first = result.line_number.saturating_sub(radius).max(1);
+        Some(Ok(text
+            .lines()
+            .enumerate()
+            .skip(first - 1)
+            .take(result.line_number + radius + 1 - first)
+            .map(|(index, line)| (index + 1, line.to_string()))
+            .collect()))
+    }
 
-        let op_text = if self.clipboard_op == Some(ClipboardOp::Copy) {
-            "Yanked"
+    /// Open `path` in the editor, or every selected file in one invocation when
+    /// `path` is part of the multi-selection
+    pub(crate) fn open_in_editor(&mut self, path: PathBuf) {
+        let multi = &self.browser.selection.multi_selection;
+        let mut paths: Vec<PathBuf> = if multi.contains(&path) {
+            multi.iter().filter(|p| !p.is_dir()).cloned().collect()
         } else {
-            "Cut"
+            vec![path]
         };
-        self.ui
-            .set_info(format!("{} {} files", op_text, self.clipboard.len()));
+        paths.sort();
+        match self.capabilities.open_files_in_editor(&paths) {
+            Ok(()) if paths.len() > 1 => self
+                .ui
+                .set_info(format!("Opened {} files in the editor", paths.len())),
+            Ok(()) => {}
+            Err(e) => self.ui.set_error(format!("Could not open editor: {}", e)),
+        }
     }
 
-    pub(crate) fn paste_clipboard(&mut self) {
-        if self.clipboard.is_empty() {
+    pub(crate) fn open_at_preview(&mut self) {
+        let Some(entry) = self
+            .browser
+            .selection
+            .selected_index
+            .and_then(|idx| self.browser.entries.visible_entries.get(idx))
+            .cloned()
+        else {
             return;
-        }
-        let op = match self.clipboard_op {
-            Some(o) => o,
-            None => return,
         };
+        if entry.is_dir {
+            self.navigate_to(entry.path);
+            return;
+        }
+        if self.browser.selection.multi_selection.contains(&entry.path) {
+            self.open_in_editor(entry.path);
+            return;
+        }
 
-        let mut count = 0;
-        let mut errors = Vec::new();
-        let mut missing_paths = Vec::new();
-
-        for src in &self.clipboard {
-            if !src.exists() {
-                errors.push(format!("Source missing: {}", src.display()));
-                missing_paths.push(src.clone());
-                continue;
-            }
-
-            if let Some(name) = src.file_name() {
-                let dest = self.navigation.current_path.join(name);
-                if src.is_dir() {
-                    if op == ClipboardOp::Cut {
-                        if let Err(e) = fs::rename(src, &dest) {
-                            errors.push(format!("Move dir failed: {}", e));
-                        } else {
-                            count += 1;
-                        }
-                    } else {
-                        errors.push("Copying directories not supported in  Heike (lite)".into());
-                    }
-                } else if op == ClipboardOp::Copy {
-                    if let Err(e) = fs::copy(src, &dest) {
-                        errors.push(format!("Copy file failed: {}", e));
-                    } else {
-                        count += 1;
-                    }
-                } else if let Err(e) = fs::rename(src, &dest) {
-                    errors.push(format!("Move file failed: {}", e));
-                } else {
-                    count += 1;
+        let line = self
+            .preview_top_line
+            .borrow()
+            .as_ref()
+            .filter(|(path, _)| *path == entry.path)
+            .map(|(_, line)| *line);
+        match self.capabilities.open_in_editor_at(&entry.path, line) {
+            Ok(()) => {
+                if let Some(line) = line {
+                    self.ui
+                        .set_info(format!("Opened {} at line {}", entry.name, line));
                 }
             }
+            Err(e) => self.ui.set_error(format!("Could not open editor: {}", e)),
         }
+    }
 
-        for path in missing_paths {
-            self.clipboard.remove(&path);
-        }
+    // --- Opening Many Files ---
 
-        if !errors.is_empty() {
-            self.ui.set_error(errors.join(" | "));
+    /// Open every file in the multi-selection, asking first above the configured threshold
+    pub(crate) fn open_selection(&mut self) {
+        let paths = self.selected_files();
+        if paths.is_empty() {
+            self.ui.set_info("No files in selection".into());
+        } else if paths.len() > self.config.open.confirm_threshold {
+            self.mode.set_mode(AppMode::OpenConfirm { paths });
         } else {
-            self.ui.set_info(format!("Processed {} files", count));
+            self.queue_open(paths);
         }
+    }
 
-        if op == ClipboardOp::Cut {
-            self.clipboard.clear();
-            self.clipboard_op = None;
+    /// Ask for one application to open the selection (or the current file) with
+    pub(crate) fn start_open_with(&mut self, paths: Vec<PathBuf>) {
+        if paths.is_empty() {
+            self.ui.set_info("No files to open".into());
+            return;
         }
-        self.request_refresh();
+        // Pre-fill the current association so it can be reused or edited
+        self.mode.command_buffer = tools::common_extension(&paths)
+            .and_then(|ext| self.config.associations.get(&ext).cloned())
+            .unwrap_or_default();
+        self.mode.set_mode(AppMode::OpenWith {
+            paths,
+            remember: false,
+        });
+        self.mode.focus_input = true;
     }
 
-    pub(crate) fn perform_delete(&mut self) {
-        let targets = if !self.selection.multi_selection.is_empty() {
-            self.selection.multi_selection.clone()
-        } else if let Some(idx) = self.selection.selected_index {
-            if let Some(entry) = self.entries.visible_entries.get(idx) {
-                HashSet::from([entry.path.clone()])
-            } else {
-                HashSet::new()
-            }
-        } else {
-            HashSet::new()
-        };
+    /// Files of the multi-selection in listing order, or the current file when nothing is selected
+    pub(crate) fn selected_files(&self) -> Vec<PathBuf> {
+        if self.browser.selection.multi_selection.is_empty() {
+            return self
+                .browser
+                .selection
+                .selected_index
+                .and_then(|idx| self.browser.entries.visible_entries.get(idx))
+                .filter(|e| !e.is_dir)
+                .map(|e| vec![e.path.clone()])
+                .unwrap_or_default();
+        }
+        self.browser
+            .entries
+            .visible_entries
+            .iter()
+            .filter(|e| !e.is_dir && self.browser.selection.multi_selection.contains(&e.path))
+            .map(|e| e.path.clone())
+            .collect()
+    }
 
-        let mut error_count = 0;
-        for path in targets {
-            match trash::delete(&path) {
-                Ok(_) => {}
-                Err(e) => {
-                    error_count += 1;
-                    eprintln!("Failed to move to trash: {}", e);
-                }
+    pub(crate) fn queue_open(&mut self, paths: Vec<PathBuf>) {
+        self.mode.set_mode(AppMode::Normal);
+        self.ui
+            .set_info(format!("Opening {} file(s)...", paths.len()));
+        self.open_queue.extend(paths);
+    }
+
+    pub(crate) fn perform_open_with(&mut self) {
+        let AppMode::OpenWith { paths, remember } = self.mode.mode.clone() else {
+            return;
+        };
+        let command = self.mode.command_buffer.trim().to_string();
+        if command.is_empty() {
+            return;
+        }
+        if remember {
+            if let Some(ext) = tools::common_extension(&paths) {
+                self.set_association(ext, Some(command.clone()));
             }
         }
-
+        let paths = &paths;
+        match tools::open_files_with(&command, paths) {
+            Ok(()) => self
+                .ui
+                .set_info(format!("Opened {} file(s) with {}", paths.len(), command)),
+            Err(e) => self
+                .ui
+                .set_error(format!("Could not run '{}': {}", command, e)),
+        }
+        self.mode.command_buffer.clear();
         self.mode.set_mode(AppMode::Normal);
-        self.selection.multi_selection.clear();
-        self.request_refresh();
+    }
 
-        if error_count > 0 {
+    /// Open a file with its configured association or the OS default application
+    pub(crate) fn open_file(&mut self, path: &std::path::Path) {
+        if let Err(e) = tools::open_path(path, &self.config.associations) {
             self.ui
-                .set_error(format!("Failed to delete {} item(s)", error_count));
-        } else {
-            self.ui.set_info("Items moved to trash".into());
+                .set_error(format!("Could not open {}: {}", path.display(), e));
         }
     }
 
-    pub(crate) fn perform_rename(&mut self) {
-        if let Some(idx) = self.selection.selected_index {
-            if let Some(entry) = self.entries.visible_entries.get(idx) {
-                let new_name = self.mode.command_buffer.trim();
-                if !new_name.is_empty() {
-                    if let Some(parent) = entry.path.parent() {
-                        let new_path = parent.join(new_name);
-                        if let Err(e) = fs::rename(&entry.path, &new_path) {
-                            self.ui.set_error(format!("Rename failed: {}", e));
-                        } else {
-                            self.ui.set_info("Renamed successfully".into());
+    /// Set (or with `None` remove) the application used for an extension and save the config
+    pub(crate) fn set_association(&mut self, ext: String, command: Option<String>) {
+        let ext = ext.trim_start_matches('.').to_lowercase();
+        let message = match command {
+            Some(command) => {
+                let message = format!("Always opening .{} with {}", ext, command);
+                self.config.associations.insert(ext, command);
+                message
+            }
+            None => {
+                self.config.associations.remove(&ext);
+                format!("Removed association for .{}", ext)
+            }
+        };
+        match self.write_config() {
+            Ok(()) => self.ui.set_info(message),
+            Err(e) => self.ui.set_error(format!("{} (not saved: {})", message, e)),
+        }
+    }
+
+    /// Hand the next batch of queued files to their default applications
+    fn process_open_queue(&mut self, ctx: &egui::Context) {
+        const BATCH_INTERVAL: Duration = Duration::from_millis(500);
+        if self.open_queue.is_empty() {
+            return;
+        }
+        if self.last_open_batch.elapsed() < BATCH_INTERVAL {
+            ctx.request_repaint_after(BATCH_INTERVAL);
+            return;
+        }
+        self.last_open_batch = Instant::now();
+        let batch = self
+            .config
+            .open
+            .batch_size
+            .max(1)
+            .min(self.open_queue.len());
+        for path in self.open_queue.drain(..batch).collect::<Vec<_>>() {
+            self.open_file(&path);
+        }
+        if !self.open_queue.is_empty() {
+            ctx.request_repaint_after(BATCH_INTERVAL);
+        }
+    }
+
+    pub(crate) fn navigate_up(&mut self) {
+        self.dispatch(Action::Up);
+    }
+
+    pub(crate) fn navigate_back(&mut self) {
+        self.dispatch(Action::Back);
+    }
+
+    pub(crate) fn navigate_forward(&mut self) {
+        self.dispatch(Action::Forward);
+    }
+
+    /// Run an action through the browser core and carry out its effects
+    pub(crate) fn dispatch(&mut self, action: Action) {
+        let previous_path = self.browser.navigation.current_path.clone();
+        let effects = self.browser.dispatch(action);
+        if self.browser.navigation.current_path != previous_path {
+            self.peek = None;
+        }
+        for effect in effects {
+            match effect {
+                Effect::Refresh => self.request_refresh(),
+                Effect::OpenFile(path) => self.open_file(&path),
+                Effect::ExitMode => {
+                    self.mode.command_buffer.clear();
+                    self.mode.set_mode(AppMode::Normal);
+                }
+                Effect::Info(message) => self.ui.set_info(message),
+                Effect::Error(message) => self.ui.set_error(message),
+            }
+        }
+    }
+
+    // --- File Operations (Injected) ---
+
+    pub(crate) fn yank_selection(&mut self, op: ClipboardOp) {
+        self.dispatch(Action::Yank(op));
+    }
+
+    /// Configured `file_ops.conflict_policy`, falling back to asking
+    pub(crate) fn conflict_policy(&self) -> ConflictPolicy {
+        ConflictPolicy::from_name(&self.config.file_ops.conflict_policy)
+            .unwrap_or(ConflictPolicy::Ask)
+    }
+
+    pub(crate) fn paste_clipboard(&mut self) {
+        if self.browser.clipboard.is_empty() || self.browser.clipboard_op.is_none() {
+            return;
+        }
+        let policy = self.conflict_policy();
+        if policy == ConflictPolicy::Ask {
+            let mut names: Vec<String> = Vec::new();
+            let mut comparable = HashSet::new();
+            for src in &self.browser.clipboard {
+                let Some(name) = src.file_name() else {
+                    continue;
+                };
+                let dest = self.browser.navigation.current_path.join(name);
+                // Pasting onto itself is handled without asking
+                if dest == *src || fs::symlink_metadata(&dest).is_err() {
+                    continue;
+                }
+                let name = name.to_string_lossy().to_string();
+                if textdiff::is_comparable(src) && textdiff::is_comparable(&dest) {
+                    comparable.insert(name.clone());
+                }
+                names.push(name);
+            }
+            if !names.is_empty() {
+                names.sort();
+                self.mode.set_mode(AppMode::PasteConflict {
+                    names,
+                    remember: false,
+                    comparable,
+                    diff: None,
+                });
+                return;
+            }
+        }
+        self.paste_with_policy(policy);
+    }
+
+    /// Show how a conflicting text file differs from the one that would replace it;
+    /// comparing the name already shown hides the diff again
+    pub(crate) fn compare_paste_conflict(&mut self, name: &str) {
+        let AppMode::PasteConflict { diff, .. } = &mut self.mode.mode else {
+            return;
+        };
+        if diff.as_ref().is_some_and(|(shown, _)| shown == name) {
+            *diff = None;
+            return;
+        }
+        let Some(src) = self
+            .browser
+            .clipboard
+            .iter()
+            .find(|src| src.file_name().is_some_and(|n| n.to_string_lossy() == name))
+        else {
+            return;
+        };
+        let dest = self.browser.navigation.current_path.join(name);
+        *diff = Some((name.to_string(), textdiff::compare_files(&dest, src)));
+    }
+
+    /// Compare the next comparable conflict after the one shown, for the `c` key
+    pub(crate) fn compare_next_paste_conflict(&mut self) {
+        let AppMode::PasteConflict {
+            names,
+            comparable,
+            diff,
+            ..
+        } = &self.mode.mode
+        else {
+            return;
+        };
+        let candidates: Vec<&String> = names.iter().filter(|n| comparable.contains(*n)).collect();
+        let next = match diff {
+            Some((shown, _)) => candidates
+                .iter()
+                .position(|n| *n == shown)
+                .and_then(|i| candidates.get(i + 1)),
+            None => candidates.first(),
+        };
+        match next.map(|n| n.to_string()) {
+            Some(name) => self.compare_paste_conflict(&name),
+            None => {
+                if let AppMode::PasteConflict { diff, .. } = &mut self.mode.mode {
+                    *diff = None;
+                }
+            }
+        }
+    }
+
+    /// Finish a paste from the conflict dialog, optionally saving the choice
+    pub(crate) fn resolve_paste_conflict(&mut self, policy: ConflictPolicy) {
+        if let AppMode::PasteConflict { remember: true, .. } = self.mode.mode {
+            self.config.file_ops.conflict_policy = policy.name().to_string();
+            if let Err(e) = self.write_config() {
+                self.ui
+                    .set_error(format!("Could not save conflict policy: {}", e));
+            }
+        }
+        self.mode.set_mode(AppMode::Normal);
+        self.paste_with_policy(policy);
+    }
+
+    fn paste_with_policy(&mut self, policy: ConflictPolicy) {
+        let Some(op) = self.browser.clipboard_op else {
+            return;
+        };
+        let mut items: Vec<PathBuf> = self.browser.clipboard.iter().cloned().collect();
+        items.sort();
+        self.start_job(
+            JobKind::Paste {
+                op,
+                policy,
+                dest_dir: self.browser.navigation.current_path.clone(),
+            },
+            items,
+        );
+    }
+
+    // --- Directory Snapshots ---
+
+    /// Everything in the current directory, hidden entries included
+    fn full_listing(&mut self) -> Option<Vec<FileEntry>> {
+        let dir = &self.browser.navigation.current_path;
+        match read_directory(dir, true) {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                self.ui
+                    .set_error(format!("Cannot read {}: {}", dir.display(), e));
+                None
+            }
+        }
+    }
+
+    /// Record the current directory's listing for a later `compare_snapshot`
+    pub(crate) fn take_snapshot(&mut self) {
+        let Some(entries) = self.full_listing() else {
+            return;
+        };
+        self.ui.set_info(format!(
+            "Snapshot taken of {} entries (compare with :snapshot compare)",
+            entries.len()
+        ));
+        self.snapshots.insert(
+            self.browser.navigation.current_path.clone(),
+            DirSnapshot::capture(&entries),
+        );
+    }
+
+    /// Mark entries added or changed since the snapshot and report removed ones
+    pub(crate) fn compare_snapshot(&mut self) {
+        let dir = self.browser.navigation.current_path.clone();
+        if !self.snapshots.contains_key(&dir) {
+            self.ui
+                .set_error("No snapshot of this directory (take one with :snapshot)".into());
+            return;
+        }
+        let Some(entries) = self.full_listing() else {
+            return;
+        };
+        let Some(snapshot) = self.snapshots.get_mut(&dir) else {
+            return;
+        };
+        snapshot.highlight = true;
+        let (mut added, mut changed) = (0, 0);
+        for entry in &entries {
+            match snapshot.change(entry) {
+                Some(SnapshotChange::Added) => added += 1,
+                Some(SnapshotChange::Changed) => changed += 1,
+                None => {}
+            }
+        }
+        let removed = snapshot.removed(&entries);
+        let mut message = format!(
+            "Since {}: {} added, {} changed, {} removed",
+            snapshot.taken.format("%H:%M:%S"),
+            added,
+            changed,
+            removed.len()
+        );
+        if !removed.is_empty() {
+            message.push_str(&format!(" ({})", removed.join(", ")));
+        }
+        self.ui.set_info(message);
+    }
+
+    // --- Staging Area ---
+
+    /// Stage the selection in the active tab, or unstage it if all of it is staged already
+    pub(crate) fn toggle_staged(&mut self) {
+        let mut paths: Vec<PathBuf> = if self.browser.selection.multi_selection.is_empty() {
+            self.browser
+                .selection
+                .selected_index
+                .and_then(|idx| self.browser.entries.visible_entries.get(idx))
+                .map(|entry| vec![entry.path.clone()])
+                .unwrap_or_default()
+        } else {
+            self.browser
+                .selection
+                .multi_selection
+                .iter()
+                .cloned()
+                .collect()
+        };
+        paths.sort();
+        let Some(tab) = self.tabs.get_active_mut() else {
+            return;
+        };
+        if paths.is_empty() {
+            return;
+        }
+        let message = if paths.iter().all(|path| tab.staged.contains(path)) {
+            tab.staged.retain(|path| !paths.contains(path));
+            format!("Unstaged {} item(s)", paths.len())
+        } else {
+            let count = paths.len();
+            for path in paths {
+                if !tab.staged.contains(&path) {
+                    tab.staged.push(path);
+                }
+            }
+            format!(
+                "Staged {} item(s), {} in the staging area",
+                count,
+                tab.staged.len()
+            )
+        };
+        self.ui.set_info(message);
+    }
+
+    pub(crate) fn unstage(&mut self, path: &Path) {
+        if let Some(tab) = self.tabs.get_active_mut() {
+            tab.staged.retain(|staged| staged != path);
+        }
+    }
+
+    pub(crate) fn clear_staged(&mut self) {
+        if let Some(tab) = self.tabs.get_active_mut() {
+            tab.staged.clear();
+        }
+    }
+
+    /// Take one item off the clipboard before pasting
+    pub(crate) fn remove_from_clipboard(&mut self, path: &Path) {
+        self.browser.clipboard.remove(path);
+        if self.browser.clipboard.is_empty() {
+            self.browser.clipboard_op = None;
+        }
+    }
+
+    pub(crate) fn clear_clipboard(&mut self) {
+        self.browser.clipboard.clear();
+        self.browser.clipboard_op = None;
+    }
+
+    /// Copy or move everything staged in the active tab into the current directory as one job
+    pub(crate) fn drop_staged(&mut self, op: ClipboardOp) {
+        let Some(items) = self
+            .tabs
+            .get_active_mut()
+            .map(|tab| std::mem::take(&mut tab.staged))
+        else {
+            return;
+        };
+        // A batch from many directories can't stop for a dialog per name
+        let policy = match self.conflict_policy() {
+            ConflictPolicy::Ask => ConflictPolicy::Rename,
+            policy => policy,
+        };
+        self.start_job(
+            JobKind::Paste {
+                op,
+                policy,
+                dest_dir: self.browser.navigation.current_path.clone(),
+            },
+            items,
+        );
+    }
+
+    // --- Batch Jobs ---
+
+    /// Start a multi-file operation, or queue it behind the running one
+    ///
+    /// `run_job` works through the active job over the next frames.
+    pub(crate) fn start_job(&mut self, kind: JobKind, items: Vec<PathBuf>) {
+        if items.is_empty() {
+            return;
+        }
+        self.request_job(kind, items, JobStart::AfterCurrent);
+    }
+
+    /// Go through the job's before hooks, asking for a hook's confirmation word first,
+    /// then start or queue it
+    fn request_job(&mut self, kind: JobKind, items: Vec<PathBuf>, start: JobStart) {
+        if self.ui.dry_run {
+            self.dry_run_job(&kind, &items);
+            return;
+        }
+        let phrase = self
+            .job_hooks("before", &kind, &items)
+            .into_iter()
+            .map(|hook| hook.confirm.trim().to_string())
+            .find(|phrase| !phrase.is_empty());
+        if let Some(phrase) = phrase {
+            self.mode.command_buffer.clear();
+            self.mode.set_mode(AppMode::HookConfirm {
+                kind,
+                items,
+                start,
+                phrase,
+            });
+            self.mode.focus_input = true;
+            return;
+        }
+        self.accept_job(kind, items, start);
+    }
+
+    /// Enter in the hook confirmation popup: go ahead when the word was typed
+    pub(crate) fn confirm_hook(&mut self) {
+        let AppMode::HookConfirm {
+            kind,
+            items,
+            start,
+            phrase,
+        } = self.mode.mode.clone()
+        else {
+            return;
+        };
+        if self.mode.command_buffer.trim() != phrase {
+            self.ui
+                .set_error(format!("Type {} to go ahead, or Esc to cancel", phrase));
+            return;
+        }
+        self.mode.command_buffer.clear();
+        self.mode.set_mode(AppMode::Normal);
+        self.accept_job(kind, items, start);
+    }
+
+    /// `[[hooks]]` entries for `when` ("before"/"after") of a job on `items`
+    fn job_hooks(&self, when: &str, kind: &JobKind, items: &[PathBuf]) -> Vec<HookConfig> {
+        let mut touched = items.to_vec();
+        touched.extend(kind.folder(items));
+        self.config
+            .hooks
+            .iter()
+            .filter(|hook| hook.applies(when, kind.operation(), &touched))
+            .cloned()
+            .collect()
+    }
+
+    /// Start the job's before hook commands on their own thread; false when there are
+    /// none and the job can go ahead right away
+    fn start_before_hooks(&mut self, kind: &JobKind, items: &[PathBuf], start: JobStart) -> bool {
+        let Some(folder) = kind.folder(items) else {
+            return false;
+        };
+        let commands: Vec<String> = self
+            .job_hooks("before", kind, items)
+            .into_iter()
+            .map(|hook| hook.command)
+            .filter(|command| !command.trim().is_empty())
+            .collect();
+        if commands.is_empty() {
+            return false;
+        }
+        self.ui.set_info(format!(
+            "{}: running before hooks…",
+            kind.describe(items.len())
+        ));
+        self.hook_checks.push(HookCheck {
+            kind: kind.clone(),
+            items: items.to_vec(),
+            start,
+            outcome: hooks::spawn_before(commands, kind.operation(), folder, items.to_vec()),
+        });
+        true
+    }
+
+    /// Go ahead with jobs whose before hooks passed, and report the ones they stopped
+    fn process_hook_checks(&mut self, ctx: &egui::Context) {
+        let mut index = 0;
+        while index < self.hook_checks.len() {
+            let outcome = match self.hook_checks[index].outcome.try_recv() {
+                Ok(outcome) => outcome,
+                Err(TryRecvError::Empty) => {
+                    index += 1;
+                    continue;
+                }
+                Err(TryRecvError::Disconnected) => Err(("?".into(), "hook thread stopped".into())),
+            };
+            let check = self.hook_checks.remove(index);
+            match outcome {
+                Ok(()) => self.go_ahead_with_job(check.kind, check.items, check.start),
+                Err((command, e)) => self.ui.set_error(format!(
+                    "{} stopped by hook '{}': {}",
+                    check.kind.describe(check.items.len()),
+                    command,
+                    e
+                )),
+            }
+        }
+        if !self.hook_checks.is_empty() {
+            ctx.request_repaint_after(Duration::from_millis(50));
+        }
+    }
+
+    /// Start the job's after hook commands on the items it did
+    fn run_after_hooks(&mut self, kind: &JobKind, items: &[PathBuf]) {
+        let Some(folder) = kind.folder(items) else {
+            return;
+        };
+        for hook in self.job_hooks("after", kind, items) {
+            if hook.command.trim().is_empty() {
+                continue;
+            }
+            if let Err(e) = hooks::spawn_after(&hook.command, kind.operation(), &folder, items) {
+                self.ui
+                    .set_warning(format!("Could not run hook '{}': {}", hook.command, e));
+            }
+        }
+    }
+
+    /// Run the job's before hooks, if any, then start or queue it
+    fn accept_job(&mut self, kind: JobKind, items: Vec<PathBuf>, start: JobStart) {
+        if !self.start_before_hooks(&kind, &items, start) {
+            self.go_ahead_with_job(kind, items, start);
+        }
+    }
+
+    /// Start or queue a job whose hooks agreed
+    fn go_ahead_with_job(&mut self, kind: JobKind, items: Vec<PathBuf>, start: JobStart) {
+        if let JobStart::At(_) = start {
+            self.schedule_job(kind, items, start);
+            return;
+        }
+        self.check_destination(&kind, &items);
+        if self.job.is_some() || !self.queued_jobs.is_empty() {
+            self.schedule_job(kind, items, JobStart::AfterCurrent);
+            return;
+        }
+        self.job = Some(BatchJob::new(kind, items));
+    }
+
+    /// Have the worker point out a read-only destination, or a move that has to copy and
+    /// delete because it leaves the filesystem its items are on
+    fn check_destination(&mut self, kind: &JobKind, items: &[PathBuf]) {
+        let JobKind::Paste { op, dest_dir, .. } = kind else {
+            return;
+        };
+        let _ = self
+            .worker
+            .command_tx
+            .try_send(IoCommand::CheckDestination {
+                op: *op,
+                dest_dir: dest_dir.clone(),
+                items: items.to_vec(),
+            });
+    }
+
+    /// Put a job in the task center to run after the current one or at a set time
+    fn schedule_job(&mut self, kind: JobKind, items: Vec<PathBuf>, start: JobStart) {
+        if self.ui.dry_run {
+            self.dry_run_job(&kind, &items);
+            return;
+        }
+        let job = BatchJob::new(kind, items);
+        self.ui.set_info(format!(
+            "Queued: {} ({}, see :tasks)",
+            job.kind.describe(job.total),
+            start.describe()
+        ));
+        self.queued_jobs.push(QueuedJob { job, start });
+    }
+
+    // --- Split and Join ---
+
+    /// Queue a split of each selected file into parts of `size` ("650M", "fat32", …)
+    pub(crate) fn split_selected(&mut self, size: &str) {
+        let Some(chunk_size) = split::parse_chunk_size(size) else {
+            self.ui
+                .set_error("Usage: split <size> (e.g. 100M, 4G or fat32)".into());
+            return;
+        };
+        let files = self.selected_files();
+        if files.is_empty() {
+            self.ui.set_error("Select a file to split".into());
+            return;
+        }
+        for source in files {
+            match split::split_outputs(&source, chunk_size) {
+                Ok(outputs) => self.start_job(JobKind::Split { source, chunk_size }, outputs),
+                Err(e) => self.ui.set_error(format!(
+                    "Cannot split {}: {}",
+                    source.file_name().unwrap_or_default().to_string_lossy(),
+                    e
+                )),
+            }
+        }
+    }
+
+    /// Queue a join of the parts behind each selected manifest or numbered part
+    pub(crate) fn join_selected(&mut self) {
+        let files = self.selected_files();
+        if files.is_empty() {
+            self.ui
+                .set_error("Select a split manifest or a .001 part to join".into());
+            return;
+        }
+        // Several selected parts of one set join it once
+        let mut joined: HashSet<PathBuf> = HashSet::new();
+        for selected in files {
+            match split::join_inputs(&selected) {
+                Ok((_, parts)) if !joined.insert(parts[0].clone()) => {}
+                Ok((dest, parts)) => {
+                    self.start_job(
+                        JobKind::Join {
+                            dest,
+                            parts: parts.clone(),
+                        },
+                        parts,
+                    );
+                }
+                Err(e) => self.ui.set_error(format!(
+                    "Cannot join {}: {}",
+                    selected.file_name().unwrap_or_default().to_string_lossy(),
+                    e
+                )),
+            }
+        }
+    }
+
+    // --- Dry Runs ---
+
+    /// Turn dry run on or off for every batch tool
+    pub(crate) fn toggle_dry_run(&mut self) {
+        self.ui.dry_run = !self.ui.dry_run;
+        self.ui.set_info(if self.ui.dry_run {
+            "Dry run on: paste, trash, link, split, join and renames only report what they would do"
+                .into()
+        } else {
+            "Dry run off".into()
+        });
+    }
+
+    /// Report what a job would do to each item instead of queueing it
+    fn dry_run_job(&mut self, kind: &JobKind, items: &[PathBuf]) {
+        let changes = items.iter().map(|path| plan_job_item(kind, path)).collect();
+        self.show_dry_run(DryRunReport::new(kind.describe(items.len()), changes), None);
+    }
+
+    fn show_dry_run(&mut self, report: DryRunReport, return_to: Option<AppMode>) {
+        self.mode.set_mode(AppMode::DryRun {
+            report,
+            return_to: return_to.map(Box::new),
+        });
+    }
+
+    /// Leave the report, back to the dialog it came from if any
+    pub(crate) fn close_dry_run(&mut self) {
+        let AppMode::DryRun { return_to, .. } = &mut self.mode.mode else {
+            return;
+        };
+        let previous = return_to.take().map(|mode| *mode);
+        self.mode.set_mode(previous.unwrap_or(AppMode::Normal));
+    }
+
+    /// Write the open report as a text file into the current directory
+    pub(crate) fn save_dry_run_report(&mut self) {
+        let AppMode::DryRun { report, .. } = &self.mode.mode else {
+            return;
+        };
+        let name = format!(
+            "dry-run-{}.txt",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        );
+        let path = unique_destination(&self.browser.navigation.current_path.join(name));
+        match fs::write(&path, report.to_text()) {
+            Ok(()) => {
+                self.ui
+                    .set_info(format!("Dry run report saved to {}", path.display()));
+                self.request_refresh();
+            }
+            Err(e) => self
+                .ui
+                .set_error(format!("Could not save {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Promote the first queued job whose start condition is met
+    fn start_due_job(&mut self, ctx: &egui::Context) {
+        let now = chrono::Local::now();
+        if let Some(index) = self.queued_jobs.iter().position(|q| q.start.is_due(now)) {
+            let mut job = self.queued_jobs.remove(index).job;
+            job.started = Instant::now();
+            self.job = Some(job);
+        } else if !self.queued_jobs.is_empty() {
+            // Timed jobs are waiting: check the clock again shortly
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+    }
+
+    /// `:paste HH:MM` - paste the clipboard here at the next matching time
+    fn schedule_paste(&mut self, time: &str) {
+        let Some(start) = JobStart::parse_time(time) else {
+            self.ui
+                .set_error(format!("Invalid time '{}' (use HH:MM)", time));
+            return;
+        };
+        let Some(op) = self
+            .browser
+            .clipboard_op
+            .filter(|_| !self.browser.clipboard.is_empty())
+        else {
+            self.ui.set_error("Clipboard is empty".into());
+            return;
+        };
+        // Nobody is around to answer the conflict dialog at that time
+        let policy = match self.conflict_policy() {
+            ConflictPolicy::Ask => ConflictPolicy::Skip,
+            policy => policy,
+        };
+        let mut items: Vec<PathBuf> = self.browser.clipboard.iter().cloned().collect();
+        items.sort();
+        self.request_job(
+            JobKind::Paste {
+                op,
+                policy,
+                dest_dir: self.browser.navigation.current_path.clone(),
+            },
+            items,
+            start,
+        );
+    }
+
+    /// Hand items to the job thread and take in what it reports, pausing on failures
+    ///
+    /// Waits for the thread at most a few milliseconds per frame, so small items go
+    /// through many per frame while a long one leaves the window responsive.
+    fn run_job(&mut self, ctx: &egui::Context) {
+        const FRAME_BUDGET: Duration = Duration::from_millis(30);
+        if matches!(self.mode.mode, AppMode::JobError { .. }) {
+            return;
+        }
+        if self.job.is_none() {
+            self.start_due_job(ctx);
+        }
+
+        let started = Instant::now();
+        loop {
+            let Some(job) = self.job.as_mut() else {
+                return;
+            };
+            if job.running.is_none() {
+                if job.paused {
+                    return;
+                }
+                let Some(path) = job.pending.pop_front() else {
+                    self.finish_job();
+                    return;
+                };
+                if let Err(error) = self.job_runner.run(job.kind.clone(), path.clone()) {
+                    job.pending.push_front(path);
+                    self.ui.set_error(error);
+                    return;
+                }
+                job.item_bytes = 0;
+                job.running = Some(path);
+            }
+            let wait = FRAME_BUDGET.saturating_sub(started.elapsed());
+            match self.job_runner.events.recv_timeout(wait) {
+                Ok(JobEvent::Progress(bytes)) => job.item_bytes = bytes,
+                Ok(JobEvent::Done(outcome)) => {
+                    let Some(path) = job.running.take() else {
+                        continue;
+                    };
+                    if !self.job_item_done(ctx, path, outcome) {
+                        return;
+                    }
+                }
+                // The job thread asks for a repaint when it has news
+                Err(_) => return,
+            }
+        }
+    }
+
+    /// Count a finished item; false when the job stops to ask about a failure
+    fn job_item_done(
+        &mut self,
+        ctx: &egui::Context,
+        path: PathBuf,
+        outcome: Result<bool, String>,
+    ) -> bool {
+        let Some(job) = self.job.as_mut() else {
+            return false;
+        };
+        let (action, destination) = job.kind.audit(&path);
+        if let Err(warning) = self
+            .oplog
+            .record(action, &path, destination.as_deref(), &outcome)
+        {
+            self.ui.set_warning(warning);
+        }
+        match outcome {
+            Ok(true) => {
+                job.done += 1;
+                // A copied dirdiff entry is no longer a difference
+                if let (
+                    JobKind::Mirror { from, .. },
+                    AppMode::DirDiff {
+                        entries,
+                        selected_index,
+                        ..
+                    },
+                ) = (&job.kind, &mut self.mode.mode)
+                {
+                    entries.retain(|entry| from.join(&entry.relative_path) != path);
+                    *selected_index = (*selected_index).min(entries.len().saturating_sub(1));
+                }
+                job.completed.push(path);
+            }
+            Ok(false) => job.skipped += 1,
+            Err(error) if job.skip_errors || self.job_runner.is_cancelled() => {
+                job.failed.push((path, error))
+            }
+            Err(error) => {
+                // Put the item back in front so Retry can pick it up again
+                job.pending.push_front(path.clone());
+                if job.started.elapsed()
+                    >= Duration::from_secs(self.config.notifications.job_min_secs)
+                {
+                    let title = format!("{} stopped on an error", job.kind.describe(job.total));
+                    let body = format!(
+                        "{}: {}",
+                        path.file_name().unwrap_or_default().to_string_lossy(),
+                        error
+                    );
+                    self.notify(&title, &body);
+                }
+                self.mode.set_mode(AppMode::JobError { path, error });
+                if self.exit_when_idle {
+                    // Running in the background: bring the window back for the decision
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                }
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Stop the running job: the item in progress is cancelled, the rest left untouched
+    pub(crate) fn cancel_job(&mut self) {
+        let Some(job) = self.job.as_mut() else {
+            return;
+        };
+        job.abort();
+        if job.running.is_some() {
+            // Finished once the job thread reports the cancelled item
+            self.job_runner.cancel();
+        } else {
+            self.finish_job();
+        }
+    }
+
+    /// Ask what to do with pending jobs when the window is closed, instead of
+    /// cutting transfers off mid-item
+    ///
+    /// Once the user chose to wait or run in the background, a second close quits immediately.
+    fn handle_close_request(&mut self, ctx: &egui::Context) {
+        if std::mem::take(&mut self.quit_requested) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+        let jobs_pending = self.job.is_some() || !self.queued_jobs.is_empty();
+        if !ctx.input(|i| i.viewport().close_requested())
+            || !jobs_pending
+            || self.exit_when_idle
+            || !self.config.file_ops.finish_jobs_on_close
+        {
+            return;
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+        self.mode.set_mode(AppMode::ExitConfirm);
+    }
+
+    /// Answer the dialog shown when closing with jobs pending
+    pub(crate) fn resolve_exit(&mut self, ctx: &egui::Context, choice: ExitChoice) {
+        self.mode.set_mode(AppMode::Normal);
+        let jobs = self.job.iter().count() + self.queued_jobs.len();
+        match choice {
+            ExitChoice::Wait => {
+                self.exit_when_idle = true;
+                self.ui.set_info(format!(
+                    "Quitting once {} job(s) are done; close again to quit now",
+                    jobs
+                ));
+            }
+            ExitChoice::Background => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+                self.exit_when_idle = true;
+                self.in_background = true;
+                self.ui.set_info(format!(
+                    "Finishing {} job(s) in the background; close again to quit now",
+                    jobs
+                ));
+            }
+            ExitChoice::CancelAll => {
+                self.queued_jobs.clear();
+                self.hook_checks.clear();
+                self.cancel_job();
+                if self.job.is_some() {
+                    // Quit once the cancelled item has cleaned up after itself
+                    self.exit_when_idle = true;
+                } else {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            }
+            ExitChoice::Stay => {}
+        }
+    }
+
+    /// Show job progress in the window title while in the background, and quit when idle
+    fn update_background_mode(&mut self, ctx: &egui::Context) {
+        if !self.exit_when_idle {
+            return;
+        }
+        if self.job.is_none() && self.queued_jobs.is_empty() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            return;
+        }
+        let title = match &self.job {
+            Some(job) => format!(
+                "Heike — {} {}/{}{}",
+                job.kind.verb(),
+                job.processed(),
+                job.total,
+                if job.paused { " (paused)" } else { "" }
+            ),
+            None => format!("Heike — {} job(s) waiting", self.queued_jobs.len()),
+        };
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+    }
+
+    /// Show, update or remove the tray icon and act on what was picked in its menu
+    fn update_tray(&mut self, ctx: &egui::Context) {
+        let wanted = match self.config.file_ops.tray_icon.as_str() {
+            "always" => true,
+            "never" => false,
+            _ => self.in_background,
+        };
+        if !wanted || self.tray_failed {
+            self.tray = None;
+            return;
+        }
+        let menu = self.tray_menu();
+        let tray = self
+            .tray
+            .get_or_insert_with(|| TrayIcon::spawn(ctx.clone(), menu.clone()));
+        tray.set_menu(menu);
+        let events: Vec<_> = std::iter::from_fn(|| tray.try_event()).collect();
+        for event in events {
+            match event {
+                Ok(action) => self.tray_action(ctx, action),
+                Err(e) => {
+                    self.tray = None;
+                    self.tray_failed = true;
+                    self.ui.set_warning(format!("No tray icon: {}", e));
+                }
+            }
+        }
+    }
+
+    /// Jobs and bookmarks for the tray menu
+    fn tray_menu(&self) -> TrayMenu {
+        let running = self.job.iter().map(|job| {
+            format!(
+                "{} ({}/{}{})",
+                job.kind.describe(job.total),
+                job.processed(),
+                job.total,
+                if job.paused { ", paused" } else { "" }
+            )
+        });
+        let queued = self.queued_jobs.iter().map(|queued| {
+            format!(
+                "{} ({})",
+                queued.job.kind.describe(queued.job.total),
+                queued.start.describe()
+            )
+        });
+        let mut keys = self.bookmarks.keys();
+        keys.sort();
+        TrayMenu {
+            jobs: running.chain(queued).collect(),
+            bookmarks: keys
+                .into_iter()
+                .filter_map(|key| {
+                    let path = self.bookmarks.resolve_path(&key)?;
+                    Some((format!("{}  ({})", path.display(), key), path))
+                })
+                .collect(),
+        }
+    }
+
+    fn tray_action(&mut self, ctx: &egui::Context, action: TrayAction) {
+        if action == TrayAction::Quit {
+            if self.job.is_some() || !self.queued_jobs.is_empty() {
+                self.resolve_exit(ctx, ExitChoice::CancelAll);
+            } else {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            return;
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        match action {
+            TrayAction::ShowJobs => self.ui.show_task_center = true,
+            TrayAction::OpenBookmark(path) => self.new_tab(Some(path)),
+            TrayAction::Show | TrayAction::Quit => {}
+        }
+    }
+
+    /// `:oplog` reports what was logged, `:oplog export <file.json|file.csv>` writes it out
+    fn operation_log_command(&mut self, args: &[&str]) {
+        match args {
+            [] => {
+                let daily = match &self.oplog.daily_dir {
+                    Some(dir) => format!("; daily files in {}", dir.display()),
+                    None => String::new(),
+                };
+                self.ui.set_info(format!(
+                    "{} operation(s) logged in session {}{}",
+                    self.oplog.records.len(),
+                    self.oplog.session,
+                    daily
+                ));
+            }
+            ["export", path @ ..] if !path.is_empty() => {
+                let path = self.resolve_command_path(&path.join(" "));
+                match oplog::export(&self.oplog.records, &path) {
+                    Ok(()) => self.ui.set_info(format!(
+                        "Exported {} operation(s) to {}",
+                        self.oplog.records.len(),
+                        path.display()
+                    )),
+                    Err(e) => self.ui.set_error(format!("Export failed: {}", e)),
+                }
+            }
+            _ => self
+                .ui
+                .set_error("Usage: oplog [export <file.json|file.csv>]".into()),
+        }
+    }
+
+    /// Hold or continue the running job (status bar button or `:job pause|resume`)
+    pub(crate) fn set_job_paused(&mut self, paused: bool) {
+        match self.job.as_mut() {
+            Some(job) => {
+                job.paused = paused;
+                self.ui.set_info(format!(
+                    "{} ({} of {} done)",
+                    if paused { "Paused" } else { "Resumed" },
+                    job.processed(),
+                    job.total
+                ));
+            }
+            None => self.ui.set_info("No file operation running".into()),
+        }
+    }
+
+    /// Answer the failure dialog of a paused job
+    pub(crate) fn resolve_job_error(&mut self, action: JobErrorAction) {
+        let AppMode::JobError { path, error } = self.mode.mode.clone() else {
+            return;
+        };
+        self.mode.set_mode(AppMode::Normal);
+        let Some(job) = self.job.as_mut() else {
+            return;
+        };
+        match action {
+            JobErrorAction::Retry => {}
+            JobErrorAction::Skip | JobErrorAction::SkipAll => {
+                job.pending.pop_front();
+                job.failed.push((path, error));
+                job.skip_errors |= action == JobErrorAction::SkipAll;
+            }
+            JobErrorAction::Abort => {
+                job.pending.pop_front();
+                job.failed.push((path, error));
+                job.abort();
+                self.finish_job();
+            }
+        }
+    }
+
+    fn finish_job(&mut self) {
+        let Some(job) = self.job.take() else {
+            return;
+        };
+        // An abort is the user's own doing and needs no announcement
+        let announce = job.aborted.is_none() && self.job_is_long(&job);
+
+        if let JobKind::Paste { .. } = job.kind {
+            // Moved items are gone from their source; queued pastes may share the clipboard
+            self.browser.clipboard.retain(|p| p.exists());
+            if self.browser.clipboard.is_empty() {
+                self.browser.clipboard_op = None;
+            }
+        }
+
+        let summary = job.summary();
+        if announce {
+            let body = if job.failed.is_empty() {
+                summary.clone()
+            } else {
+                format!("{}, {} failed", summary, job.failed.len())
+            };
+            self.notify("Heike: job finished", &body);
+        }
+        if !job.completed.is_empty() {
+            self.run_after_hooks(&job.kind, &job.completed);
+        }
+        if job.failed.is_empty() {
+            self.ui.set_info(summary);
+        } else {
+            let failures: Vec<String> = job
+                .failed
+                .iter()
+                .map(|(path, error)| {
+                    format!(
+                        "{}: {}",
+                        path.file_name().unwrap_or_default().to_string_lossy(),
+                        error
+                    )
+                })
+                .collect();
+            self.ui.set_error(format!(
+                "{}, {} failed: {}",
+                summary,
+                job.failed.len(),
+                failures.join(" | ")
+            ));
+        }
+        self.request_refresh();
+    }
+
+    // --- Properties ---
+
+    pub(crate) fn open_properties(&mut self, path: PathBuf) {
+        let Some(entry) = FileEntry::from_path(path) else {
+            self.ui.set_error("Could not read file properties".into());
+            return;
+        };
+        let mut properties = PropertiesState::new(entry);
+        properties.filesystem = platform::filesystem_info(&properties.entry.path);
+        self.properties = Some(properties);
+        self.reload_properties_xattrs();
+        self.mode.set_mode(AppMode::Properties);
+    }
+
+    /// Open the settings window on a copy of the current config (`:settings`, ⚙ button)
+    pub(crate) fn open_settings(&mut self) {
+        self.sync_settings_into_config();
+        self.settings = Some(SettingsState::new(self.config.clone()));
+        self.mode.set_mode(AppMode::Settings);
+    }
+
+    pub(crate) fn close_settings(&mut self) {
+        self.settings = None;
+        self.mode.set_mode(AppMode::Normal);
+    }
+
+    /// Make the settings draft the config: apply it live and write config.toml
+    pub(crate) fn save_settings_draft(&mut self) {
+        let Some(draft) = self.settings.as_ref().map(|s| s.draft.clone()) else {
+            return;
+        };
+        if draft.tools != self.config.tools {
+            self.capabilities = Capabilities::detect(&draft.tools);
+        }
+        // Cached folder sizes were totalled with the old choice of folders
+        if draft.ui.source_size != self.config.ui.source_size
+            || draft.ui.skipped_dirs() != self.config.ui.skipped_dirs()
+        {
+            self.preview_cache.borrow_mut().clear();
+        }
+        self.config = draft;
+        self.apply_settings();
+        self.request_refresh();
+        match self.write_config() {
+            Ok(()) => self.ui.set_info("Settings saved".into()),
+            Err(e) => self
+                .ui
+                .set_error(format!("Settings applied but not saved: {}", e)),
+        }
+    }
+
+    pub(crate) fn close_properties(&mut self) {
+        self.properties = None;
+        self.mode.set_mode(AppMode::Normal);
+    }
+
+    fn reload_properties_xattrs(&mut self) {
+        if !platform::HAS_XATTRS {
+            return;
+        }
+        let Some(props) = self.properties.as_mut() else {
+            return;
+        };
+        props.security_context = platform::security_context(&props.entry.path);
+        match platform::list_xattrs(&props.entry.path) {
+            Ok(attrs) => props.xattrs = attrs,
+            Err(e) => {
+                props.xattrs.clear();
+                self.ui
+                    .set_error(format!("Could not read extended attributes: {}", e));
+            }
+        }
+    }
+
+    pub(crate) fn add_properties_xattr(&mut self) {
+        let Some(props) = self.properties.as_mut() else {
+            return;
+        };
+        let name = props.new_xattr_name.trim();
+        let name = if name.starts_with("user.") {
+            name.to_string()
+        } else {
+            format!("user.{}", name)
+        };
+        match platform::set_user_xattr(&props.entry.path, &name, &props.new_xattr_value) {
+            Ok(()) => {
+                props.new_xattr_name.clear();
+                props.new_xattr_value.clear();
+                self.ui.set_info(format!("Set {}", name));
+                self.reload_properties_xattrs();
+            }
+            Err(e) => self.ui.set_error(format!("Failed to set {}: {}", name, e)),
+        }
+    }
+
+    pub(crate) fn remove_properties_xattr(&mut self, name: &str) {
+        let Some(props) = self.properties.as_ref() else {
+            return;
+        };
+        match platform::remove_user_xattr(&props.entry.path, name) {
+            Ok(()) => {
+                self.ui.set_info(format!("Removed {}", name));
+                self.reload_properties_xattrs();
+            }
+            Err(e) => self
+                .ui
+                .set_error(format!("Failed to remove {}: {}", name, e)),
+        }
+    }
+
+    /// Apply a color label to the multi-selection, or the focused entry
+    pub(crate) fn set_label(&mut self, label: Option<LabelColor>) {
+        let targets: Vec<PathBuf> = if !self.browser.selection.multi_selection.is_empty() {
+            self.browser
+                .selection
+                .multi_selection
+                .iter()
+                .cloned()
+                .collect()
+        } else if let Some(entry) = self
+            .browser
+            .selection
+            .selected_index
+            .and_then(|idx| self.browser.entries.visible_entries.get(idx))
+        {
+            vec![entry.path.clone()]
+        } else {
+            return;
+        };
+
+        let mut errors = Vec::new();
+        for path in &targets {
+            if let Err(e) = self.labels.set(path, label) {
+                errors.push(e.to_string());
+            }
+        }
+        if let Some(first) = errors.first() {
+            self.ui.set_error(format!(
+                "Failed to label {} item(s): {}",
+                errors.len(),
+                first
+            ));
+        } else {
+            let name = label.map(|l| l.name()).unwrap_or("None");
+            self.ui
+                .set_info(format!("Label {} on {} item(s)", name, targets.len()));
+        }
+    }
+
+    pub(crate) fn perform_delete(&mut self) {
+        let targets = if !self.browser.selection.multi_selection.is_empty() {
+            self.browser.selection.multi_selection.clone()
+        } else if let Some(idx) = self.browser.selection.selected_index {
+            if let Some(entry) = self.browser.entries.visible_entries.get(idx) {
+                HashSet::from([entry.path.clone()])
+            } else {
+                HashSet::new()
+            }
+        } else {
+            HashSet::new()
+        };
+
+        let mut targets: Vec<PathBuf> = targets.into_iter().collect();
+        targets.sort();
+
+        self.mode.set_mode(AppMode::Normal);
+        self.browser.selection.multi_selection.clear();
+        self.start_job(JobKind::Trash, targets);
+    }
+
+    pub(crate) fn perform_rename(&mut self) {
+        if let Some(idx) = self.browser.selection.selected_index {
+            if let Some(entry) = self.browser.entries.visible_entries.get(idx) {
+                let new_name = self.mode.command_buffer.trim();
+                if !new_name.is_empty() {
+                    if let Some(parent) = entry.path.parent() {
+                        let new_name = expand_name_template(new_name, parent, chrono::Local::now());
+                        let new_path = parent.join(new_name);
+                        let old_path = entry.path.clone();
+                        let result = fs::rename(&old_path, &new_path);
+                        if let Err(warning) = self.oplog.record(
+                            "rename",
+                            &old_path,
+                            Some(&new_path),
+                            &result.as_ref().map(|_| true).map_err(|e| e.to_string()),
+                        ) {
+                            self.ui.set_warning(warning);
+                        }
+                        if let Err(e) = result {
+                            self.ui.set_error(format!("Rename failed: {}", e));
+                        } else {
+                            self.ui.set_info("Renamed successfully".into());
                         }
                     } else {
                         self.ui.set_error("Cannot rename root path".into());
@@ -859,14 +3355,125 @@ impl Heike {
         self.request_refresh();
     }
 
+    /// Create the directories or empty files named by a `mkdir`/`touch` argument
+    ///
+    /// Brace alternatives give several entries, templates are expanded per entry, and missing
+    /// intermediate directories are created like `mkdir -p`. Existing files are not truncated.
+    fn create_entries(&mut self, arg: &str, directory: bool) {
+        let dir = self.browser.navigation.current_path.clone();
+        let now = chrono::Local::now();
+        let kind = if directory { "directory" } else { "file" };
+        let mut created = Vec::new();
+        let mut failure = None;
+        for name in expand_braces(arg) {
+            let name = expand_name_template(&name, &dir, now);
+            let path = dir.join(&name);
+            let result = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| {
+                    if directory {
+                        fs::create_dir(&path)
+                    } else {
+                        fs::OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(&path)
+                            .map(|_| ())
+                    }
+                });
+            if let Err(warning) = self.oplog.record(
+                "create",
+                &path,
+                None,
+                &result.as_ref().map(|_| true).map_err(|e| e.to_string()),
+            ) {
+                self.ui.set_warning(warning);
+            }
+            match result {
+                Ok(()) => created.push(name),
+                Err(e) => {
+                    failure = Some(format!("Failed to create {} {}: {}", kind, name, e));
+                    break;
+                }
+            }
+        }
+        if !created.is_empty() {
+            if created.len() == 1 {
+                self.ui
+                    .set_info(format!("Created {}: {}", kind, created[0]));
+            } else {
+                self.ui.set_info(format!(
+                    "Created {} entries: {}",
+                    created.len(),
+                    created.join(", ")
+                ));
+            }
+            self.request_refresh();
+        }
+        // Reported last so the failure is what stays on screen
+        if let Some(message) = failure {
+            self.ui.set_error(message);
+        }
+    }
+
+    /// Command words whose argument names a new entry in the current directory
+    const NAME_COMMANDS: [&'static str; 2] = ["mkdir", "touch"];
+
+    /// Whether the open prompt takes a name that Tab can complete from sibling names
+    pub(crate) fn accepts_name_completion(&self) -> bool {
+        match self.mode.mode {
+            AppMode::Rename => true,
+            AppMode::Command => self
+                .mode
+                .command_buffer
+                .split_once(' ')
+                .is_some_and(|(cmd, _)| Self::NAME_COMMANDS.contains(&cmd)),
+            _ => false,
+        }
+    }
+
+    /// Complete the name being typed against the entries of the current directory
+    pub(crate) fn complete_input_name(&mut self, backward: bool) {
+        if !self.accepts_name_completion() {
+            return;
+        }
+        let (command, typed) = match self.mode.command_buffer.split_once(' ') {
+            Some((cmd, arg)) if self.mode.mode == AppMode::Command => {
+                (format!("{} ", cmd), arg.to_string())
+            }
+            _ => (String::new(), self.mode.command_buffer.clone()),
+        };
+        let names = self
+            .browser
+            .entries
+            .all_entries
+            .iter()
+            .map(|e| e.name.as_str());
+        match self.mode.completion.complete(&typed, names, backward) {
+            Some(name) => {
+                self.mode.command_buffer = command + &name;
+                self.mode.cursor_to_end = true;
+            }
+            None => self
+                .ui
+                .set_info(format!("No names start with \"{}\"", typed)),
+        }
+    }
+
     pub(crate) fn enter_bulk_rename_mode(&mut self) {
         // Determine which files to rename
-        let files_to_rename: Vec<PathBuf> = if !self.selection.multi_selection.is_empty() {
+        let files_to_rename: Vec<PathBuf> = if !self.browser.selection.multi_selection.is_empty() {
             // Use multi-selection if available
-            self.selection.multi_selection.iter().cloned().collect()
-        } else if let Some(idx) = self.selection.selected_index {
+            self.browser
+                .selection
+                .multi_selection
+                .iter()
+                .cloned()
+                .collect()
+        } else if let Some(idx) = self.browser.selection.selected_index {
             // Use current selection if no multi-selection
-            if let Some(entry) = self.entries.visible_entries.get(idx) {
+            if let Some(entry) = self.browser.entries.visible_entries.get(idx) {
                 vec![entry.path.clone()]
             } else {
                 vec![]
@@ -893,110 +3500,697 @@ impl Heike {
             original_paths: files_to_rename,
             edit_buffer,
             cursor_line: 0,
+            auto_suffix: self.conflict_policy() == ConflictPolicy::Rename,
+            photo_pattern: self.config.file_ops.photo_pattern.clone(),
         });
         self.mode.focus_input = true;
     }
 
+    /// Fill the bulk rename buffer from the photo pattern and each file's capture time
+    pub(crate) fn apply_photo_dates(&mut self) {
+        let AppMode::BulkRename {
+            original_paths,
+            edit_buffer,
+            photo_pattern,
+            ..
+        } = &mut self.mode.mode
+        else {
+            return;
+        };
+        let mut from_exif = 0;
+        let times: Vec<chrono::NaiveDateTime> = original_paths
+            .iter()
+            .map(|path| match photo::capture_time(path) {
+                Some(time) => {
+                    from_exif += 1;
+                    time
+                }
+                None => fs::metadata(path)
+                    .and_then(|m| m.modified())
+                    .map(|modified| chrono::DateTime::<chrono::Local>::from(modified).naive_local())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        *edit_buffer = photo_names(edit_buffer, &times, photo_pattern);
+        let fallback = times.len() - from_exif;
+        self.ui.set_info(if fallback == 0 {
+            format!("Named {} file(s) by EXIF capture time", from_exif)
+        } else {
+            format!(
+                "Named {} file(s) by EXIF capture time, {} by modification time",
+                from_exif, fallback
+            )
+        });
+    }
+
     pub(crate) fn apply_bulk_rename(&mut self) {
-        if let AppMode::BulkRename {
+        let AppMode::BulkRename {
             original_paths,
             edit_buffer,
+            auto_suffix,
             ..
         } = &self.mode.mode
-        {
-            let new_names: Vec<&str> = edit_buffer.lines().collect();
-
-            // Validation: number of lines must match number of files
-            if new_names.len() != original_paths.len() {
-                self.ui.set_error(format!(
-                    "Line count mismatch: {} files but {} names",
-                    original_paths.len(),
-                    new_names.len()
-                ));
+        else {
+            return;
+        };
+        let plan = match core::plan_bulk_rename(original_paths, edit_buffer) {
+            Ok(plan) => plan,
+            Err(e) => {
+                self.ui.set_error(e);
                 return;
             }
+        };
+        if self.ui.dry_run {
+            let report = DryRunReport::new(
+                format!("Rename {} items", plan.len()),
+                core::plan_renames(&plan, *auto_suffix),
+            );
+            let return_to = self.mode.mode.clone();
+            self.show_dry_run(report, Some(return_to));
+            return;
+        }
+        let mut renamed = Vec::new();
+        let (success_count, errors) = core::apply_renames(&plan, *auto_suffix, |old, new| {
+            renamed.push((old.to_path_buf(), new.to_path_buf()))
+        });
+        for (old, new) in renamed {
+            if let Err(warning) = self.oplog.record("rename", &old, Some(&new), &Ok(true)) {
+                self.ui.set_warning(warning);
+            }
+        }
+
+        // Clear multi-selection after bulk rename
+        self.browser.selection.multi_selection.clear();
+
+        if !errors.is_empty() {
+            self.ui.set_error(format!(
+                "Renamed {}/{} files. Errors: {}",
+                success_count,
+                plan.len(),
+                errors.join(", ")
+            ));
+        } else {
+            self.ui
+                .set_info(format!("Successfully renamed {} file(s)", success_count));
+        }
+
+        self.mode.set_mode(AppMode::Normal);
+        self.request_refresh();
+    }
+
+    // --- Preview Checksums ---
+
+    /// Queue a SHA-256 computation for the Metadata tab
+    ///
+    /// An empty cache entry marks the request as in flight so the tab shows a
+    /// spinner instead of sending it again.
+    fn request_checksum(&mut self, path: PathBuf) {
+        let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) else {
+            return;
+        };
+        self.preview_cache.borrow_mut().insert_section(
+            path.clone(),
+            view::CHECKSUM_SECTION,
+            String::new(),
+            modified,
+        );
+        let _ = self
+            .worker
+            .command_tx
+            .send(IoCommand::ComputeChecksum(path));
+    }
+
+    /// Ask the worker for the Git tab's last commit, once per file version
+    fn request_last_commit(&mut self, path: PathBuf) {
+        let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) else {
+            return;
+        };
+        if self
+            .worker
+            .command_tx
+            .try_send(IoCommand::LoadLastCommit(path.clone()))
+            .is_ok()
+        {
+            self.preview_cache.borrow_mut().insert_section(
+                path,
+                view::GIT_SECTION,
+                String::new(),
+                modified,
+            );
+        }
+    }
+
+    /// Ask the worker for the text around a search match inside an archive
+    fn request_archive_member(&mut self, archive: PathBuf, member: String, modified: SystemTime) {
+        let key = archive.join(&member);
+        if self.archive_member_pending.as_ref() == Some(&key) {
+            return;
+        }
+        if self
+            .worker
+            .command_tx
+            .try_send(IoCommand::ReadArchiveMember {
+                archive,
+                member,
+                modified,
+            })
+            .is_ok()
+        {
+            self.archive_member_pending = Some(key);
+        }
+    }
+
+    /// Queue a full member count for an archive whose preview lists only some pages
+    fn request_archive_count(&mut self, path: PathBuf) {
+        let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) else {
+            return;
+        };
+        self.preview_cache.borrow_mut().insert_section(
+            path.clone(),
+            view::ARCHIVE_TOTALS_SECTION,
+            String::new(),
+            modified,
+        );
+        let _ = self.worker.command_tx.send(IoCommand::CountArchive {
+            path,
+            generation: self.worker.preview_generation(),
+        });
+    }
+
+    // --- Maintenance ---
+
+    pub(crate) fn open_maintenance(&mut self) {
+        self.mode.set_mode(AppMode::Maintenance {
+            confirm_empty: false,
+        });
+        self.scan_trash();
+    }
+
+    pub(crate) fn scan_trash(&mut self) {
+        self.trash_usage = None;
+        let _ = self.worker.command_tx.send(IoCommand::ScanTrash);
+    }
+
+    /// Purge the whole trash; the panel rescans once the worker reports back
+    pub(crate) fn empty_trash(&mut self) {
+        self.trash_usage = None;
+        let _ = self.worker.command_tx.send(IoCommand::EmptyTrash {
+            older_than_days: None,
+        });
+    }
+
+    /// Queue a recursive size computation for the directory preview summary
+    ///
+    /// Uses `try_send` so browsing never blocks on a busy worker; the preview
+    /// asks again on the next frame.
+    /// Ask the worker for the listing the directory preview shows, once per directory
+    fn request_preview_listing(&mut self, path: PathBuf, modified: SystemTime) {
+        if self.preview_listing_pending.as_ref() == Some(&path) {
+            return;
+        }
+        if self
+            .worker
+            .command_tx
+            .try_send(IoCommand::LoadPreviewListing {
+                path: path.clone(),
+                show_hidden: self.ui.show_hidden,
+                modified,
+                generation: self.worker.preview_generation(),
+            })
+            .is_ok()
+        {
+            self.preview_listing_pending = Some(path);
+        }
+    }
 
-            // Validation: no empty names
-            if new_names.iter().any(|n| n.trim().is_empty()) {
-                self.ui.set_error("Empty filename not allowed".into());
+    /// Ask the worker for the archive preview's first page, or the one starting at `resume`
+    fn request_archive_page(&mut self, path: PathBuf, modified: SystemTime, resume: u64) {
+        if archive::needs_helper(&path) {
+            if let Some(reason) = self.capabilities.missing_reason(Tool::Archiver) {
+                let mut listing = view::ArchiveListing::new(path, modified);
+                listing.add_page(Err(reason));
+                self.archive_listing = Some(listing);
                 return;
             }
+        }
+        if self
+            .archive_listing_pending
+            .as_ref()
+            .is_some_and(|(pending, at)| *pending == path && *at == resume)
+        {
+            return;
+        }
+        if self
+            .worker
+            .command_tx
+            .try_send(IoCommand::ListArchive {
+                path: path.clone(),
+                modified,
+                resume,
+                limit: view::ArchiveListing::PAGE_ITEMS,
+                generation: self.worker.preview_generation(),
+            })
+            .is_ok()
+        {
+            if let Some(listing) = self.archive_listing.as_mut() {
+                listing.loading = resume > 0 && listing.path == path;
+            }
+            self.archive_listing_pending = Some((path, resume));
+        }
+    }
 
-            // Validation: no duplicate names
-            let mut seen = std::collections::HashSet::new();
-            for name in &new_names {
-                if !seen.insert(name.trim()) {
-                    self.ui
-                        .set_error(format!("Duplicate filename: {}", name.trim()));
-                    return;
+    pub(crate) fn request_dir_size(&mut self, path: PathBuf) {
+        let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) else {
+            return;
+        };
+        if self
+            .worker
+            .command_tx
+            .try_send(IoCommand::ComputeDirSize {
+                path: path.clone(),
+                source: self.config.ui.source_size,
+                skip: self.config.ui.skipped_dirs(),
+                generation: self.worker.preview_generation(),
+            })
+            .is_ok()
+        {
+            self.preview_cache.borrow_mut().insert_section(
+                path,
+                view::DIR_SIZE_SECTION,
+                String::new(),
+                modified,
+            );
+        }
+    }
+
+    // --- Directory Comparison ---
+
+    pub(crate) fn start_dir_diff(&mut self, other: PathBuf, compare_contents: bool) {
+        if !other.is_dir() {
+            self.ui
+                .set_error(format!("Not a directory: {}", other.display()));
+            return;
+        }
+        if other == self.browser.navigation.current_path {
+            self.ui
+                .set_error("Cannot compare a directory with itself".into());
+            return;
+        }
+        self.ui.is_loading = true;
+        self.ui
+            .set_info(format!("Comparing with {}...", other.display()));
+        let _ = self.worker.command_tx.send(IoCommand::CompareDirectories {
+            left: self.browser.navigation.current_path.clone(),
+            right: other,
+            compare_contents,
+        });
+    }
+
+    /// Copy one dirdiff entry across; `to_right` copies from the current directory to the other.
+    pub(crate) fn copy_dir_diff_entry(&mut self, index: usize, to_right: bool) {
+        let AppMode::DirDiff {
+            left,
+            right,
+            entries,
+            ..
+        } = &self.mode.mode
+        else {
+            return;
+        };
+        let Some(entry) = entries.get(index) else {
+            return;
+        };
+
+        let applicable = match entry.kind {
+            DiffKind::OnlyLeft => to_right,
+            DiffKind::OnlyRight => !to_right,
+            DiffKind::Different => true,
+        };
+        if !applicable {
+            self.ui
+                .set_error("Nothing to copy in that direction".into());
+            return;
+        }
+
+        let (from, to) = if to_right {
+            (left.clone(), right.clone())
+        } else {
+            (right.clone(), left.clone())
+        };
+        let (src, dest) = (
+            from.join(&entry.relative_path),
+            to.join(&entry.relative_path),
+        );
+        if dest.exists() && dest.is_dir() != src.is_dir() {
+            self.ui.set_error(format!(
+                "{}: file/directory type mismatch, resolve manually",
+                entry.relative_path.display()
+            ));
+            return;
+        }
+        self.start_job(JobKind::Mirror { from, to }, vec![src]);
+    }
+
+    /// Copy every entry missing on the target side
+    pub(crate) fn copy_dir_diff_missing(&mut self, to_right: bool) {
+        let AppMode::DirDiff {
+            left,
+            right,
+            entries,
+            ..
+        } = &self.mode.mode
+        else {
+            return;
+        };
+        let (missing_kind, from, to) = if to_right {
+            (DiffKind::OnlyLeft, left.clone(), right.clone())
+        } else {
+            (DiffKind::OnlyRight, right.clone(), left.clone())
+        };
+        let items: Vec<PathBuf> = entries
+            .iter()
+            .filter(|entry| entry.kind == missing_kind)
+            .map(|entry| from.join(&entry.relative_path))
+            .collect();
+        if items.is_empty() {
+            self.ui.set_info("Nothing missing on that side".into());
+            return;
+        }
+        self.start_job(JobKind::Mirror { from, to }, items);
+    }
+
+    // --- Empty Item Cleanup ---
+
+    pub(crate) fn start_cleanup_scan(&mut self) {
+        self.ui.is_loading = true;
+        self.ui.set_info(format!(
+            "Looking for empty items under {}...",
+            self.browser.navigation.current_path.display()
+        ));
+        let _ = self.worker.command_tx.send(IoCommand::FindEmpty(
+            self.browser.navigation.current_path.clone(),
+        ));
+    }
+
+    /// Tick or untick one cleanup item, or all of them when `index` is `None`
+    pub(crate) fn toggle_cleanup_item(&mut self, index: Option<usize>) {
+        let AppMode::Cleanup { items, .. } = &mut self.mode.mode else {
+            return;
+        };
+        match index {
+            Some(index) => {
+                if let Some(item) = items.get_mut(index) {
+                    item.checked = !item.checked;
+                }
+            }
+            None => {
+                let checked = !items.iter().all(|item| item.checked);
+                for item in items.iter_mut() {
+                    item.checked = checked;
                 }
             }
+        }
+    }
 
-            // Perform renames
-            let mut success_count = 0;
-            let mut errors = Vec::new();
+    /// Send the ticked cleanup items through the trash job
+    pub(crate) fn trash_cleanup_items(&mut self) {
+        let AppMode::Cleanup { root, items, .. } = &self.mode.mode else {
+            return;
+        };
+        let targets: Vec<PathBuf> = items
+            .iter()
+            .filter(|item| item.checked)
+            .map(|item| root.join(&item.relative_path))
+            .collect();
+        if targets.is_empty() {
+            self.ui.set_error("Nothing ticked to delete".into());
+            return;
+        }
+        self.mode.set_mode(AppMode::Normal);
+        self.start_job(JobKind::Trash, targets);
+    }
 
-            for (old_path, new_name) in original_paths.iter().zip(new_names.iter()) {
-                let new_name = new_name.trim();
-                if let Some(parent) = old_path.parent() {
-                    let new_path = parent.join(new_name);
+    // --- Broken Links ---
 
-                    // Skip if name hasn't changed
-                    if let Some(old_name) = old_path.file_name().and_then(|n| n.to_str()) {
-                        if old_name == new_name {
-                            success_count += 1;
-                            continue;
-                        }
-                    }
+    pub(crate) fn start_broken_link_scan(&mut self) {
+        self.ui.is_loading = true;
+        self.ui.set_info(format!(
+            "Looking for broken links under {}...",
+            self.browser.navigation.current_path.display()
+        ));
+        let _ = self.worker.command_tx.send(IoCommand::FindBrokenLinks(
+            self.browser.navigation.current_path.clone(),
+        ));
+    }
 
-                    // Check if target already exists (unless it's a case-only change)
-                    if new_path.exists() && new_path != *old_path {
-                        errors.push(format!("{}: target already exists", new_name));
-                        continue;
-                    }
+    fn selected_broken_link(&self) -> Option<BrokenLink> {
+        let AppMode::BrokenLinks {
+            links,
+            selected_index,
+            ..
+        } = &self.mode.mode
+        else {
+            return None;
+        };
+        links.get(*selected_index).cloned()
+    }
 
-                    match fs::rename(old_path, &new_path) {
-                        Ok(()) => success_count += 1,
-                        Err(e) => errors.push(format!("{}: {}", new_name, e)),
-                    }
-                }
+    /// Drop a link from the report once it has been fixed or deleted
+    fn remove_broken_link(&mut self, path: &Path) {
+        let AppMode::BrokenLinks {
+            links,
+            selected_index,
+            retarget,
+            ..
+        } = &mut self.mode.mode
+        else {
+            return;
+        };
+        links.retain(|link| link.path != path);
+        *retarget = None;
+        if links.is_empty() {
+            self.mode.set_mode(AppMode::Normal);
+        } else if *selected_index >= links.len() {
+            *selected_index = links.len() - 1;
+        }
+    }
+
+    /// Close the report and select the link in its folder
+    pub(crate) fn reveal_broken_link(&mut self) {
+        let Some(link) = self.selected_broken_link() else {
+            return;
+        };
+        self.mode.set_mode(AppMode::Normal);
+        if let Some(parent) = link.path.parent() {
+            let parent = parent.to_path_buf();
+            self.browser.navigation.pending_selection_path = Some(link.path);
+            self.navigate_to(parent);
+        }
+    }
+
+    /// Start typing a new target for the selected link, pre-filled with the old one
+    pub(crate) fn begin_retarget_link(&mut self) {
+        let Some(link) = self.selected_broken_link() else {
+            return;
+        };
+        if !link.kind.can_retarget() {
+            self.ui
+                .set_error("Windows shortcuts can only be deleted".into());
+            return;
+        }
+        if let AppMode::BrokenLinks { retarget, .. } = &mut self.mode.mode {
+            *retarget = Some(link.target);
+            self.mode.focus_input = true;
+        }
+    }
+
+    pub(crate) fn apply_retarget_link(&mut self) {
+        let (Some(link), AppMode::BrokenLinks { retarget, .. }) =
+            (self.selected_broken_link(), &mut self.mode.mode)
+        else {
+            return;
+        };
+        let Some(target) = retarget.take() else {
+            return;
+        };
+        let target = target.trim();
+        if target.is_empty() {
+            return;
+        }
+        match broken_links::retarget(&link, target) {
+            Ok(()) => {
+                self.ui
+                    .set_info(format!("{} now points to {}", link.path.display(), target));
+                self.remove_broken_link(&link.path);
+                self.request_refresh();
+            }
+            Err(e) => {
+                self.ui
+                    .set_error(format!("Could not retarget {}: {}", link.path.display(), e))
+            }
+        }
+    }
+
+    /// Send the selected link, or every listed one, through the trash job
+    pub(crate) fn trash_broken_links(&mut self, all: bool) {
+        let AppMode::BrokenLinks {
+            links,
+            selected_index,
+            ..
+        } = &self.mode.mode
+        else {
+            return;
+        };
+        let targets: Vec<PathBuf> = if all {
+            links.iter().map(|link| link.path.clone()).collect()
+        } else {
+            links
+                .get(*selected_index)
+                .map(|link| vec![link.path.clone()])
+                .unwrap_or_default()
+        };
+        for path in &targets {
+            self.remove_broken_link(path);
+        }
+        self.start_job(JobKind::Trash, targets);
+    }
+
+    // --- Portable Name Audit ---
+
+    pub(crate) fn start_name_audit(&mut self) {
+        self.ui.is_loading = true;
+        self.ui.set_info(format!(
+            "Checking names under {}...",
+            self.browser.navigation.current_path.display()
+        ));
+        let _ = self.worker.command_tx.send(IoCommand::AuditNames(
+            self.browser.navigation.current_path.clone(),
+        ));
+    }
+
+    /// Give every audited item its portable name, or only report the renames when
+    /// `report_only` or dry run is on. Taken names are numbered.
+    pub(crate) fn sanitize_audited_names(&mut self, report_only: bool) {
+        let AppMode::NameAudit { issues, .. } = &self.mode.mode else {
+            return;
+        };
+        // Issues come deepest first, so a folder is renamed after everything inside it
+        let plan: Vec<(PathBuf, &str)> = issues
+            .iter()
+            .map(|issue| (issue.path.clone(), issue.suggestion.as_str()))
+            .collect();
+        if report_only || self.ui.dry_run {
+            let report = DryRunReport::new(
+                format!("Sanitize {} names", plan.len()),
+                core::plan_renames(&plan, true),
+            );
+            let return_to = self.mode.mode.clone();
+            self.show_dry_run(report, Some(return_to));
+            return;
+        }
+        let mut renamed = Vec::new();
+        let (success_count, errors) = core::apply_renames(&plan, true, |old, new| {
+            renamed.push((old.to_path_buf(), new.to_path_buf()))
+        });
+        for (old, new) in renamed {
+            if let Err(warning) = self.oplog.record("rename", &old, Some(&new), &Ok(true)) {
+                self.ui.set_warning(warning);
             }
+        }
+        if errors.is_empty() {
+            self.ui.set_info(format!(
+                "Renamed {} item(s) to portable names",
+                success_count
+            ));
+        } else {
+            self.ui.set_error(format!(
+                "Renamed {}/{} items. Errors: {}",
+                success_count,
+                plan.len(),
+                errors.join(", ")
+            ));
+        }
+        self.mode.set_mode(AppMode::Normal);
+        self.request_refresh();
+    }
 
-            // Clear multi-selection after bulk rename
-            self.selection.multi_selection.clear();
-
-            // Show results
-            if !errors.is_empty() {
-                self.ui.set_error(format!(
-                    "Renamed {}/{} files. Errors: {}",
-                    success_count,
-                    original_paths.len(),
-                    errors.join(", ")
-                ));
-            } else {
-                self.ui
-                    .set_info(format!("Successfully renamed {} file(s)", success_count));
-            }
+    /// Folders the content search covers: the current one unless left out, then the picked ones
+    fn search_roots(&self) -> Vec<PathBuf> {
+        let current = self
+            .ui
+            .search_current
+            .then(|| self.browser.navigation.current_path.clone());
+        distinct_roots(
+            current
+                .into_iter()
+                .chain(self.ui.search_roots.iter().cloned())
+                .collect(),
+        )
+    }
 
-            self.mode.set_mode(AppMode::Normal);
-            self.request_refresh();
+    /// Start the content search from the search dialog over every chosen root
+    pub(crate) fn start_content_search(&mut self) {
+        let roots = self.search_roots();
+        if roots.is_empty() {
+            self.ui
+                .set_error("Pick this folder or a bookmark to search in".into());
+            return;
         }
+        self.ui.search_in_progress = true;
+        // Reset search statistics
+        self.ui.search_file_count = 0;
+        self.ui.search_files_skipped = 0;
+        self.ui.search_errors = 0;
+        self.ui.searched_roots = roots.clone();
+        let _ = self.worker.command_tx.send(IoCommand::SearchContent {
+            query: self.ui.search_query.clone(),
+            roots,
+            options: self.ui.search_options.clone(),
+        });
+        self.mode.set_mode(AppMode::Normal);
     }
 
-    // --- Selection Validation ---
+    /// `:group`: show or hide group header rows for the current sort field
+    pub(crate) fn toggle_group_headers(&mut self) {
+        let sort_by = self.ui.sort_options.sort_by;
+        if sort_by == SortBy::Size {
+            self.ui
+                .set_error("Sizes have no groups; sort by name, date or extension".into());
+        } else if let Some(at) = self.ui.group_headers.iter().position(|s| *s == sort_by) {
+            self.ui.group_headers.remove(at);
+        } else {
+            self.ui.group_headers.push(sort_by);
+        }
+    }
 
-    fn validate_selection(&mut self) {
-        if let Some(idx) = self.selection.selected_index {
-            if self.entries.visible_entries.is_empty() {
-                self.selection.selected_index = None;
-            } else if idx >= self.entries.visible_entries.len() {
-                self.selection.selected_index = Some(self.entries.visible_entries.len() - 1);
+    /// Resolve a path argument from command mode (`~` expansion, relative to current dir)
+    pub(crate) fn resolve_command_path(&self, path_str: &str) -> PathBuf {
+        if let Some(rest) = path_str.strip_prefix('~') {
+            if let Some(home) = directories::UserDirs::new() {
+                return home.home_dir().join(rest.trim_start_matches(['/', '\\']));
             }
+            PathBuf::from(path_str)
+        } else if path_str.starts_with('/') {
+            PathBuf::from(path_str)
+        } else {
+            self.browser.navigation.current_path.join(path_str)
         }
     }
 
     /// Save current UI settings to configuration file
     fn save_settings(&mut self) {
+        self.sync_settings_into_config();
+
+        // Unchanged settings are left alone, keeping the file's formatting and comments
+        if self.config != self.saved_config {
+            let _ = self.write_config();
+        }
+        self.save_session();
+        self.ui.last_settings_save = Instant::now();
+    }
+
+    /// Copy what was changed through the UI (theme, panels, sorting, …) into `self.config`
+    fn sync_settings_into_config(&mut self) {
         use crate::style::Theme;
 
         let theme_mode = match self.ui.theme {
@@ -1009,13 +4203,15 @@ impl Heike {
         self.config.panel.parent_width = self.ui.panel_widths[0];
         self.config.panel.preview_width = self.ui.panel_widths[1];
         self.config.ui.show_hidden = self.ui.show_hidden;
-        self.config.ui.sort_by = match self.ui.sort_options.sort_by {
-            crate::state::SortBy::Name => "name",
-            crate::state::SortBy::Size => "size",
-            crate::state::SortBy::Modified => "modified",
-            crate::state::SortBy::Extension => "extension",
-        }
-        .to_string();
+        self.config.ui.filter_preview = self.ui.filter_preview;
+        self.config.ui.clipboard_panel = self.ui.show_clipboard;
+        self.config.ui.sort_by = self.ui.sort_options.sort_by.name().to_string();
+        self.config.ui.group_headers = self
+            .ui
+            .group_headers
+            .iter()
+            .map(|sort_by| sort_by.name().to_string())
+            .collect();
         self.config.ui.sort_order = match self.ui.sort_options.sort_order {
             crate::state::SortOrder::Ascending => "asc",
             crate::state::SortOrder::Descending => "desc",
@@ -1028,21 +4224,49 @@ impl Heike {
 
         // Update enabled previews
         self.config.previews.enabled = self.preview_registry.enabled_handler_names();
+    }
 
-        let _ = self.config.save();
-        self.ui.last_settings_save = Instant::now();
+    /// Write `self.config` to config.toml and remember it as the saved state
+    fn write_config(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.config.save()?;
+        self.saved_config = self.config.clone();
+        Ok(())
     }
 
     // --- Drag and Drop Handling ---
     // (Currently handled in the eframe::App update method)
 
+    /// `:preview-as <handler>`: preview the selected file with a chosen handler;
+    /// without a name (or with `auto`) the normal handler choice returns
+    fn preview_as(&mut self, name: Option<&str>) {
+        let Some(path) = self.browser.selected_entry().map(|e| e.path.clone()) else {
+            self.ui.set_error("No file selected".into());
+            return;
+        };
+        let name = name.filter(|name| *name != "auto");
+        match self.preview_registry.set_temporary_override(path, name) {
+            Ok(()) => self.ui.set_info(match name {
+                Some(name) => format!("Previewing as {}", name),
+                None => "Preview handler chosen automatically".into(),
+            }),
+            Err(e) => self.ui.set_error(e),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn render_preview(
         &self,
         ui: &mut egui::Ui,
         next_navigation: &std::cell::RefCell<Option<PathBuf>>,
         pending_selection: &std::cell::RefCell<Option<PathBuf>>,
+        checksum_request: &std::cell::RefCell<Option<PathBuf>>,
+        dir_size_request: &std::cell::RefCell<Option<PathBuf>>,
+        archive_count_request: &std::cell::RefCell<Option<PathBuf>>,
+        dir_listing_request: &std::cell::RefCell<Option<(PathBuf, SystemTime)>>,
+        archive_page_request: &std::cell::RefCell<Option<(PathBuf, SystemTime, u64)>>,
+        last_commit_request: &std::cell::RefCell<Option<PathBuf>>,
     ) {
-        let idx = match self.selection.selected_index {
+        let idx = match self.browser.selection.selected_index {
             Some(i) => i,
             None => {
                 ui.centered_and_justified(|ui| {
@@ -1051,7 +4275,7 @@ impl Heike {
                 return;
             }
         };
-        let entry = match self.entries.visible_entries.get(idx) {
+        let entry = match self.browser.entries.visible_entries.get(idx) {
             Some(e) => e,
             None => return,
         };
@@ -1062,14 +4286,31 @@ impl Heike {
             entry,
             &self.preview_registry,
             self.ui.show_hidden,
-            self.selection.last_selection_change,
-            &self.selection.directory_selections,
+            self.browser.selection.last_selection_change,
+            &self.browser.selection.directory_selections,
             &self.syntax_set,
             &self.theme_set,
-            self.ui.theme,
+            self.code_theme(),
+            self.ui.name_ellipsis,
+            self.config.previews.archive_details,
+            if self.ui.filter_preview {
+                &self.ui.filter_query
+            } else {
+                ""
+            },
             next_navigation,
             pending_selection,
             &self.preview_cache,
+            &self.preview_tab,
+            checksum_request,
+            dir_size_request,
+            archive_count_request,
+            self.preview_listing.as_ref(),
+            dir_listing_request,
+            self.archive_listing.as_ref(),
+            archive_page_request,
+            last_commit_request,
+            &self.preview_top_line,
         );
     }
 
@@ -1077,51 +4318,79 @@ impl Heike {
 
     // --- Rendering Methods ---
 
+    /// Built-in command words with a short description, for suggestions after a typo
+    /// and the which-key overlay
+    pub(crate) const COMMANDS: &'static [(&'static str, &'static str)] = &[
+        ("q", "Quit"),
+        ("quit", "Quit"),
+        ("mkdir", "New folder(s) <name>"),
+        ("touch", "New file(s) <name>"),
+        ("cd", "Go to <path>, home without one"),
+        ("maintenance", "Trash usage and cleanup"),
+        ("cleanup", "Trash usage and cleanup"),
+        ("diagnostics", "Check tools and settings"),
+        ("checkhealth", "Check tools and settings"),
+        ("sort", "Sort by <field> [asc|desc]"),
+        ("snapshot", "Record the listing [compare|clear]"),
+        ("project", "Go to the project root"),
+        ("mount", "Go to the mount point"),
+        ("config", "Show config.toml [edit]"),
+        ("settings", "Settings window"),
+        ("preferences", "Settings window"),
+        ("alias", "List command aliases"),
+        ("terminal", "Open a terminal here"),
+        ("assoc", "File associations [ext] [command|none]"),
+        ("preview-as", "Preview with [handler]"),
+        ("label", "Label <color|none>"),
+        ("mirror", "Show this folder in [tab]"),
+        ("dirdiff", "Compare with <dir> [--hash]"),
+        ("empty", "Find empty files and folders"),
+        ("brokenlinks", "Find broken links"),
+        ("portable", "Find names Windows rejects"),
+        ("split", "Split into <size|fat32> parts"),
+        ("join", "Join split parts"),
+        ("flatten", "List all files below"),
+        ("job", "Pause or resume the file operation"),
+        ("oplog", "Operation log [export <file.json|csv>]"),
+        ("tasks", "Task center"),
+        ("paste", "Paste, or at [HH:MM]"),
+        ("dryrun", "Dry run on / off"),
+        ("computer", "Drives and places"),
+        ("clipboard", "Clipboard panel"),
+        ("group", "Group headers for this sort field"),
+        ("messages", "Message history"),
+        ("help", "List the commands"),
+    ];
+
     pub(crate) fn execute_command(&mut self, _ctx: &egui::Context) {
-        let parts: Vec<&str> = self.mode.command_buffer.trim().split_whitespace().collect();
+        let line = std::mem::take(&mut self.mode.command_buffer);
+        // Commands that open a modal set their own mode afterwards
+        self.mode.set_mode(AppMode::Normal);
+        for command in self.config.expand_aliases(&line) {
+            self.run_command(&command);
+        }
+    }
+
+    pub(crate) fn run_command(&mut self, command: &str) {
+        let parts: Vec<&str> = command.split_whitespace().collect();
         if parts.is_empty() {
-            self.mode.set_mode(AppMode::Normal);
-            self.mode.command_buffer.clear();
             return;
         }
 
         match parts[0] {
-            "q" | "quit" => {
-                std::process::exit(0);
-            }
+            "q" | "quit" => self.quit_requested = true,
             "mkdir" => {
                 if parts.len() < 2 {
                     self.ui.set_error("Usage: mkdir <name>".into());
                 } else {
-                    let dir_name = parts[1..].join(" ");
-                    let new_dir = self.navigation.current_path.join(&dir_name);
-                    match fs::create_dir(&new_dir) {
-                        Ok(_) => {
-                            self.ui.set_info(format!("Created directory: {}", dir_name));
-                            self.request_refresh();
-                        }
-                        Err(e) => {
-                            self.ui
-                                .set_error(format!("Failed to create directory: {}", e));
-                        }
-                    }
+                    self.create_entries(&parts[1..].join(" "), true);
                 }
             }
             "touch" => {
                 if parts.len() < 2 {
                     self.ui.set_error("Usage: touch <filename>".into());
                 } else {
-                    let file_name = parts[1..].join(" ");
-                    let new_file = self.navigation.current_path.join(&file_name);
-                    match fs::File::create(&new_file) {
-                        Ok(_) => {
-                            self.ui.set_info(format!("Created file: {}", file_name));
-                            self.request_refresh();
-                        }
-                        Err(e) => {
-                            self.ui.set_error(format!("Failed to create file: {}", e));
-                        }
-                    }
+                    self.create_entries(&parts[1..].join(" "), false);
                 }
             }
             "cd" => {
@@ -1131,37 +4400,172 @@ impl Heike {
                         self.navigate_to(home.home_dir().to_path_buf());
                     }
                 } else {
-                    let path_str = parts[1..].join(" ");
-                    let path = if path_str.starts_with('~') {
-                        if let Some(home) = directories::UserDirs::new() {
-                            let rest = &path_str[1..];
-                            home.home_dir().join(rest)
-                        } else {
-                            PathBuf::from(path_str)
-                        }
-                    } else if path_str.starts_with('/') {
-                        PathBuf::from(path_str)
-                    } else {
-                        self.navigation.current_path.join(path_str)
-                    };
+                    let path = self.resolve_command_path(&parts[1..].join(" "));
                     self.navigate_to(path);
                 }
             }
+            "maintenance" | "cleanup" => self.open_maintenance(),
+            "diagnostics" | "checkhealth" => self.mode.set_mode(AppMode::Diagnostics),
+            "sort" => match (
+                parts.get(1).and_then(|name| SortBy::from_name(name)),
+                parts.get(2).map(|order| SortOrder::from_name(order)),
+            ) {
+                (Some(sort_by), order) if order != Some(None) => {
+                    self.ui.sort_options.sort_by = sort_by;
+                    if let Some(Some(order)) = order {
+                        self.ui.sort_options.sort_order = order;
+                    }
+                    self.apply_filter();
+                }
+                _ => self.ui.set_error(
+                    "Usage: sort <name|size|modified|created|extension> [asc|desc]".into(),
+                ),
+            },
+            "snapshot" => match parts.get(1).copied() {
+                None => self.take_snapshot(),
+                Some("compare") => self.compare_snapshot(),
+                Some("clear") => {
+                    self.snapshots.remove(&self.browser.navigation.current_path);
+                }
+                Some(_) => self.ui.set_error("Usage: snapshot [compare|clear]".into()),
+            },
+            "settings" | "preferences" => self.open_settings(),
+            "config" => match parts.get(1).copied() {
+                None => self.open_config(false),
+                Some("edit") => self.open_config(true),
+                Some(_) => self.ui.set_error("Usage: config [edit]".into()),
+            },
+            "project" => self.jump_to_project_root(),
+            "mount" => self.jump_to_mount_root(),
+            "alias" => {
+                let mut list: Vec<String> = self
+                    .config
+                    .aliases
+                    .iter()
+                    .map(|(name, expansion)| format!("{} → {}", name, expansion))
+                    .collect();
+                list.sort();
+                self.ui.set_info(if list.is_empty() {
+                    "No aliases (define them in the [aliases] section of config.toml)".into()
+                } else {
+                    format!("Aliases: {}", list.join(", "))
+                });
+            }
+            "terminal" => {
+                let dir = self.browser.navigation.current_path.clone();
+                if let Err(e) = self.capabilities.open_terminal(&dir) {
+                    self.ui.set_error(format!("Could not open terminal: {}", e));
+                }
+            }
+            "assoc" => match (parts.get(1), parts.len()) {
+                (None, _) => {
+                    let mut list: Vec<String> = self
+                        .config
+                        .associations
+                        .iter()
+                        .map(|(ext, command)| format!(".{} → {}", ext, command))
+                        .collect();
+                    list.sort();
+                    self.ui.set_info(if list.is_empty() {
+                        "No associations (usage: assoc <ext> <command|none>)".into()
+                    } else {
+                        format!("Associations: {}", list.join(", "))
+                    });
+                }
+                (Some(ext), 2) => self
+                    .ui
+                    .set_error(format!("Usage: assoc {} <command|none>", ext)),
+                (Some(ext), _) if parts[2] == "none" => self.set_association(ext.to_string(), None),
+                (Some(ext), _) => self.set_association(ext.to_string(), Some(parts[2..].join(" "))),
+            },
+            "preview-as" => {
+                let name = parts.get(1).map(|name| name.to_string());
+                self.preview_as(name.as_deref());
+            }
+            "label" => match parts.get(1).copied() {
+                Some("none" | "clear") => self.set_label(None),
+                Some(name) => match LabelColor::from_name(name) {
+                    Some(label) => self.set_label(Some(label)),
+                    None => self.ui.set_error(format!("Unknown label: {}", name)),
+                },
+                None => self.ui.set_error(
+                    "Usage: label <red|orange|yellow|green|blue|purple|gray|none>".into(),
+                ),
+            },
+            "mirror" => match parts.get(1).map(|n| n.parse::<usize>()) {
+                None => self.mirror_path_to_tab(None),
+                Some(Ok(n)) if n >= 1 => self.mirror_path_to_tab(Some(n - 1)),
+                _ => self.ui.set_error("Usage: mirror [tab-number]".into()),
+            },
+            "dirdiff" => {
+                let compare_contents = parts[1..].contains(&"--hash");
+                let path_parts: Vec<&str> = parts[1..]
+                    .iter()
+                    .copied()
+                    .filter(|p| *p != "--hash")
+                    .collect();
+                if path_parts.is_empty() {
+                    self.ui
+                        .set_error("Usage: dirdiff <other-dir> [--hash]".into());
+                } else {
+                    let other = self.resolve_command_path(&path_parts.join(" "));
+                    self.start_dir_diff(other, compare_contents);
+                }
+            }
+            "empty" => self.start_cleanup_scan(),
+            "brokenlinks" => self.start_broken_link_scan(),
+            "portable" => self.start_name_audit(),
+            "split" => self.split_selected(&parts[1..].join("")),
+            "join" => self.join_selected(),
+            "flatten" => self.toggle_flatten(),
+            "job" => match parts.get(1).copied() {
+                Some("pause") => self.set_job_paused(true),
+                Some("resume") => self.set_job_paused(false),
+                _ => self.ui.set_error("Usage: job pause|resume".into()),
+            },
+            "oplog" => self.operation_log_command(&parts[1..]),
+            "tasks" => self.ui.show_task_center = !self.ui.show_task_center,
+            "paste" => match parts.get(1).map(|time| time.to_string()) {
+                Some(time) => self.schedule_paste(&time),
+                None => self.paste_clipboard(),
+            },
+            "dryrun" => self.toggle_dry_run(),
+            "computer" => self.open_computer(),
+            "clipboard" => self.ui.show_clipboard = !self.ui.show_clipboard,
+            "group" => self.toggle_group_headers(),
+            "messages" => self.ui.show_message_history = !self.ui.show_message_history,
             "help" => {
                 self.ui.set_info(
-                    "Commands: q/quit, mkdir <name>, touch <file>, cd <path>, help".into(),
+                    "Commands: q/quit, mkdir <name>, touch <file>, cd <path>, dirdiff <dir> [--hash], empty, brokenlinks, portable, split <size|fat32>, join, mirror [tab], flatten, assoc [ext] [command|none], preview-as [handler], messages, job pause|resume, oplog [export <file>], tasks, paste [HH:MM], clipboard, group, dryrun, label <color|none>, sort <field> [asc|desc], snapshot [compare|clear], project, mount, computer, config [edit], settings, alias, terminal, maintenance, diagnostics, help"
+                        .into(),
                 );
             }
             _ => {
-                self.ui.set_error(format!(
-                    "Unknown command: {}. Type 'help' for available commands.",
-                    parts[0]
-                ));
+                // Autocd: a bare directory path navigates there
+                let path = self.resolve_command_path(command.trim());
+                if path.is_dir() {
+                    self.navigate_to(path);
+                    return;
+                }
+                let known = Self::COMMANDS
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .chain(self.config.aliases.keys().map(String::as_str));
+                let suggestions = near_matches(parts[0], known);
+                self.ui.set_error(if suggestions.is_empty() {
+                    format!(
+                        "Unknown command: {}. Type 'help' for available commands.",
+                        parts[0]
+                    )
+                } else {
+                    format!(
+                        "Unknown command: {}. Did you mean: {}?",
+                        parts[0],
+                        suggestions.join(", ")
+                    )
+                });
             }
         }
-
-        self.mode.set_mode(AppMode::Normal);
-        self.mode.command_buffer.clear();
     }
 }
 
@@ -1175,6 +4579,10 @@ impl eframe::App for Heike {
 
         // Auto-dismiss old messages
         self.ui.clear_expired_messages(style::MESSAGE_TIMEOUT_SECS);
+        if self.ui.messages.toasts().next().is_some() {
+            // Keep repainting so toasts expire without further input
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
 
         // Periodically save settings (every 10 seconds)
         if self.ui.last_settings_save.elapsed() > Duration::from_secs(10) {
@@ -1183,28 +4591,51 @@ impl eframe::App for Heike {
 
         self.setup_watcher(ctx);
         self.process_watcher_events();
+        self.refresh_details_if_due(ctx);
+        self.process_folder_events(ctx);
+        self.process_config_events(ctx);
         self.process_async_results();
+        self.process_open_queue(ctx);
+        self.process_hook_checks(ctx);
+        self.run_job(ctx);
+        self.handle_close_request(ctx);
+        self.update_background_mode(ctx);
+        self.update_tray(ctx);
         self.handle_input(ctx);
 
         // Handle files dropped from external sources
         ctx.input(|i| {
             if !i.raw.dropped_files.is_empty() {
-                self.handle_dropped_files(&i.raw.dropped_files);
+                self.handle_dropped_files(&i.raw.dropped_files, i.modifiers);
             }
         });
 
         if self.mode.mode == AppMode::Filtering {
-            let old_len = self.entries.visible_entries.len();
+            let old_len = self.browser.entries.visible_entries.len();
             self.apply_filter();
-            if self.entries.visible_entries.len() != old_len {
-                self.selection.last_selection_change = Instant::now();
+            if self.browser.entries.visible_entries.len() != old_len {
+                self.browser.selection.last_selection_change = Instant::now();
             }
         }
 
+        // Folder sizes and archive counts for an entry the cursor has left are dropped
+        if self.browser.selected_entry().map(|e| &e.path) != self.preview_target.as_ref() {
+            self.worker.cancel_preview_work();
+            self.preview_target = self.browser.selected_entry().map(|e| e.path.clone());
+        }
+
         let next_navigation = std::cell::RefCell::new(None);
         let next_selection = std::cell::RefCell::new(None);
         let pending_selection = std::cell::RefCell::new(None);
-        let context_action = std::cell::RefCell::new(None::<Box<dyn FnOnce(&mut Self)>>);
+        let context_action: ContextAction = RefCell::new(None);
+        let checksum_request = std::cell::RefCell::new(None);
+        let dir_size_request = std::cell::RefCell::new(None);
+        let archive_count_request = std::cell::RefCell::new(None);
+        let dir_listing_request = std::cell::RefCell::new(None);
+        let archive_page_request = std::cell::RefCell::new(None);
+        let last_commit_request = std::cell::RefCell::new(None);
+
+        self.render_menu_bar(ctx, &context_action);
 
         // Render tab bar if multiple tabs exist
         let tab_count = self.tabs.tab_count();
@@ -1276,29 +4707,91 @@ impl eframe::App for Heike {
                 if ui.button("⬆").on_hover_text("Up (Backspace)").clicked() {
                     self.navigate_up();
                 }
+                ui.menu_button("📍", |ui| {
+                    if let Some(home) = directories::UserDirs::new() {
+                        if ui.button("🏠 Home").clicked() {
+                            *next_navigation.borrow_mut() = Some(home.home_dir().to_path_buf());
+                            ui.close();
+                        }
+                    }
+                    let mut keys = self.bookmarks.keys();
+                    keys.sort();
+                    for key in keys {
+                        if let Some(path) = self.bookmarks.resolve_path(&key) {
+                            if ui
+                                .button(format!("🔖 {}  ({})", path.display(), key))
+                                .clicked()
+                            {
+                                *context_action.borrow_mut() =
+                                    Some(Box::new(move |app: &mut Heike| {
+                                        app.jump_to_bookmark(&key)
+                                    }));
+                                ui.close();
+                            }
+                        }
+                    }
+                    ui.separator();
+                    for place in platform::drives() {
+                        if ui.button(format!("💾 {}", place.name)).clicked() {
+                            *next_navigation.borrow_mut() = Some(place.path);
+                            ui.close();
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("Places");
+                if let Some(repo) = &self.git_repo {
+                    let branch = repo.branch.as_deref().unwrap_or("(detached)");
+                    ui.menu_button(format!("⎇ {}", branch), |ui| {
+                        ui.menu_button("Switch branch", |ui| {
+                            for name in &repo.branches {
+                                let current = repo.branch.as_ref() == Some(name);
+                                if ui.add_enabled(!current, egui::Button::new(name)).clicked() {
+                                    let name = name.clone();
+                                    *context_action.borrow_mut() =
+                                        Some(Box::new(move |app: &mut Heike| {
+                                            app.git_checkout(&name)
+                                        }));
+                                    ui.close();
+                                }
+                            }
+                        });
+                        ui.separator();
+                        if ui.button("Stash untracked files here").clicked() {
+                            *context_action.borrow_mut() =
+                                Some(Box::new(|app: &mut Heike| app.git_stash_untracked()));
+                            ui.close();
+                        }
+                        if ui.button("Unstash untracked files here").clicked() {
+                            *context_action.borrow_mut() =
+                                Some(Box::new(|app: &mut Heike| app.git_unstash_untracked()));
+                            ui.close();
+                        }
+                        ui.separator();
+                        if ui.button("Open repo root").clicked() {
+                            *next_navigation.borrow_mut() = Some(repo.root.clone());
+                            ui.close();
+                        }
+                    })
+                    .response
+                    .on_hover_text(format!("Git: {}", repo.root.display()));
+                }
                 ui.add_space(10.0);
 
+                // The Computer page sits above every root
+                if ui
+                    .small_button("🖥")
+                    .on_hover_text("Computer: drives, places and recent locations (gC)")
+                    .clicked()
+                {
+                    *context_action.borrow_mut() =
+                        Some(Box::new(|app: &mut Heike| app.open_computer()));
+                }
+                ui.label(">");
+
                 // Breadcrumbs (scrollable) - reserve space for right controls
                 let breadcrumb_width = ui.available_width() - 180.0;
-                egui::ScrollArea::horizontal()
-                    .id_salt("breadcrumbs")
-                    .max_width(breadcrumb_width)
-                    .show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            let components: Vec<_> =
-                                self.navigation.current_path.components().collect();
-                            let mut path_acc = PathBuf::new();
-                            for component in components {
-                                path_acc.push(component);
-                                let name = component.as_os_str().to_string_lossy();
-                                let label = if name.is_empty() { "/" } else { &name };
-                                if ui.button(label).clicked() {
-                                    *next_navigation.borrow_mut() = Some(path_acc.clone());
-                                }
-                                ui.label(">");
-                            }
-                        });
-                    });
+                self.render_breadcrumbs(ui, breadcrumb_width, &next_navigation);
 
                 // Right controls in remaining space
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -1308,6 +4801,14 @@ impl eframe::App for Heike {
                     {
                         self.request_refresh();
                     }
+                    let mut flatten = self.ui.flatten;
+                    if ui
+                        .checkbox(&mut flatten, "Flatten")
+                        .on_hover_text("List all files below this directory (Shift+F)")
+                        .changed()
+                    {
+                        self.toggle_flatten();
+                    }
 
                     // Theme toggle
                     let theme_icon = match self.ui.theme {
@@ -1325,6 +4826,10 @@ impl eframe::App for Heike {
                         };
                     }
 
+                    if ui.button("⚙").on_hover_text("Settings").clicked() {
+                        self.open_settings();
+                    }
+
                     if ui.button("?").clicked() {
                         self.mode.set_mode(AppMode::Help);
                     }
@@ -1346,22 +4851,106 @@ impl eframe::App for Heike {
                         AppMode::Help => {
                             ui.colored_label(egui::Color32::GREEN, "HELP");
                         }
+                        AppMode::Diagnostics => {
+                            ui.colored_label(egui::Color32::GREEN, "DIAGNOSTICS");
+                        }
+                        AppMode::Properties => {
+                            ui.colored_label(egui::Color32::GREEN, "PROPERTIES");
+                        }
+                        AppMode::Settings => {
+                            ui.colored_label(egui::Color32::GREEN, "SETTINGS");
+                        }
+                        AppMode::Maintenance { .. } => {
+                            ui.colored_label(egui::Color32::GREEN, "MAINTENANCE");
+                        }
                         AppMode::Rename => {
                             ui.colored_label(egui::Color32::ORANGE, "RENAME");
                         }
                         AppMode::BulkRename { .. } => {
                             ui.colored_label(egui::Color32::ORANGE, "BULK RENAME");
                         }
+                        AppMode::JobError { .. } => {
+                            ui.colored_label(egui::Color32::RED, "OPERATION FAILED");
+                        }
+                        AppMode::ExitConfirm => {
+                            ui.colored_label(egui::Color32::ORANGE, "QUIT WITH JOBS PENDING?");
+                        }
+                        AppMode::PasteConflict { names, .. } => {
+                            ui.colored_label(
+                                egui::Color32::ORANGE,
+                                format!("{} ALREADY EXIST", names.len()),
+                            );
+                        }
+                        AppMode::OpenConfirm { paths } => {
+                            ui.colored_label(
+                                egui::Color32::ORANGE,
+                                format!("OPEN {} FILES? (y)es / (w)ith... / (n)o", paths.len()),
+                            );
+                        }
+                        AppMode::OpenWith { paths, .. } => {
+                            ui.colored_label(
+                                egui::Color32::ORANGE,
+                                format!("OPEN {} WITH", paths.len()),
+                            );
+                        }
+                        AppMode::HookConfirm { .. } => {
+                            ui.colored_label(egui::Color32::RED, "CONFIRM (hook)");
+                        }
                         AppMode::DeleteConfirm => {
-                            ui.colored_label(egui::Color32::RED, "CONFIRM DELETE?");
+                            ui.colored_label(
+                                egui::Color32::RED,
+                                format!("MOVE TO {}?", platform::TRASH_NAME.to_uppercase()),
+                            );
                         }
                         AppMode::SearchInput => {
                             ui.colored_label(egui::Color32::LIGHT_BLUE, "SEARCH");
                         }
-                        AppMode::SearchResults { results, .. } => {
+                        AppMode::SearchResults {
+                            results, marked, ..
+                        } => {
+                            let text = if marked.is_empty() {
+                                format!("SEARCH ({} results)", results.len())
+                            } else {
+                                format!(
+                                    "SEARCH ({} results, {} files marked)",
+                                    results.len(),
+                                    marked.len()
+                                )
+                            };
+                            ui.colored_label(egui::Color32::LIGHT_BLUE, text);
+                        }
+                        AppMode::DirDiff { entries, .. } => {
                             ui.colored_label(
                                 egui::Color32::LIGHT_BLUE,
-                                format!("SEARCH ({} results)", results.len()),
+                                format!("DIRDIFF ({} differences)", entries.len()),
+                            );
+                        }
+                        AppMode::Cleanup { items, .. } => {
+                            let checked = items.iter().filter(|item| item.checked).count();
+                            ui.colored_label(
+                                egui::Color32::ORANGE,
+                                format!("CLEANUP ({} of {} ticked)", checked, items.len()),
+                            );
+                        }
+                        AppMode::BrokenLinks { links, .. } => {
+                            ui.colored_label(
+                                egui::Color32::ORANGE,
+                                format!("BROKEN LINKS ({})", links.len()),
+                            );
+                        }
+                        AppMode::NameAudit { issues, .. } => {
+                            ui.colored_label(
+                                egui::Color32::ORANGE,
+                                format!("NAME AUDIT ({} to rename)", issues.len()),
+                            );
+                        }
+                        AppMode::Computer { .. } => {
+                            ui.colored_label(egui::Color32::LIGHT_BLUE, "COMPUTER");
+                        }
+                        AppMode::DryRun { report, .. } => {
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                format!("DRY RUN ({} items)", report.changes.len()),
                             );
                         }
                     }
@@ -1375,13 +4964,13 @@ impl eframe::App for Heike {
                 // Item counts
                 ui.label(format!(
                     "{}/{} items",
-                    self.entries.visible_entries.len(),
-                    self.entries.all_entries.len()
+                    self.browser.entries.visible_entries.len(),
+                    self.browser.entries.all_entries.len()
                 ));
 
                 // Show current selected file info
-                if let Some(idx) = self.selection.selected_index {
-                    if let Some(entry) = self.entries.visible_entries.get(idx) {
+                if let Some(idx) = self.browser.selection.selected_index {
+                    if let Some(entry) = self.browser.entries.visible_entries.get(idx) {
                         ui.separator();
                         let type_str = if entry.is_dir { "dir" } else { "file" };
                         ui.label(format!("{}: {}", type_str, bytesize::ByteSize(entry.size)));
@@ -1392,36 +4981,131 @@ impl eframe::App for Heike {
                 ui.separator();
                 ui.label(self.ui.sort_options.display_string());
 
+                // Unfinished key sequence, e.g. a count or `g` waiting for its next key
+                let pending = self.keymap.pending();
+                if !pending.is_empty() {
+                    ui.separator();
+                    ui.label(egui::RichText::new(pending).monospace().strong());
+                }
+
                 // Show current path
                 ui.separator();
-                style::truncated_label(ui, format!("{}", self.navigation.current_path.display()));
+                style::truncated_label(
+                    ui,
+                    format!("{}", self.browser.navigation.current_path.display()),
+                );
 
                 if self.ui.is_loading {
                     ui.spinner();
                 }
+                if let Some(loaded_at) = self.ui.stale_listing {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "⏳ cached {} ago",
+                            listing_age(loaded_at.elapsed())
+                        ))
+                        .weak(),
+                    )
+                    .on_hover_text(
+                        "This directory is slow to read, so its last listing is shown \
+                         until the fresh one arrives",
+                    );
+                }
+                if self.ui.dry_run {
+                    ui.separator();
+                    if ui
+                        .small_button(egui::RichText::new("DRY RUN").color(egui::Color32::YELLOW))
+                        .on_hover_text(
+                            "Batch tools only report what they would change; click to turn off (:dryrun)",
+                        )
+                        .clicked()
+                    {
+                        self.toggle_dry_run();
+                    }
+                }
+                let mut toggle_pause = None;
+                if self.job.is_some() || !self.queued_jobs.is_empty() {
+                    ui.separator();
+                    if ui
+                        .small_button(format!("📋 {}", self.queued_jobs.len()))
+                        .on_hover_text("Task center: queued and scheduled jobs (:tasks)")
+                        .clicked()
+                    {
+                        self.ui.show_task_center = !self.ui.show_task_center;
+                    }
+                }
+                if let Some(job) = &self.job {
+                    if job.paused {
+                        ui.label("⏸");
+                    } else {
+                        ui.spinner();
+                    }
+                    let written = if job.item_bytes > 0 {
+                        format!(" · {}", bytesize::ByteSize(job.item_bytes))
+                    } else {
+                        String::new()
+                    };
+                    ui.label(format!(
+                        "{} {}/{}{}",
+                        job.kind.verb(),
+                        job.processed(),
+                        job.total,
+                        written
+                    ));
+                    let (icon, hint) = if job.paused {
+                        ("▶", "Resume")
+                    } else {
+                        ("⏸", "Pause")
+                    };
+                    if ui.small_button(icon).on_hover_text(hint).clicked() {
+                        toggle_pause = Some(!job.paused);
+                    }
+                }
+                if let Some(paused) = toggle_pause {
+                    self.set_job_paused(paused);
+                }
 
-                if let Some((msg, _)) = &self.ui.info_message {
-                    ui.colored_label(egui::Color32::GREEN, msg);
+                if !self.browser.clipboard.is_empty() || self.ui.show_clipboard {
+                    let icon = match self.browser.clipboard_op {
+                        Some(ClipboardOp::Cut) => "✂",
+                        _ => "📎",
+                    };
+                    if ui
+                        .selectable_label(
+                            self.ui.show_clipboard,
+                            format!("{} {}", icon, self.browser.clipboard.len()),
+                        )
+                        .on_hover_text("Clipboard panel (:clipboard)")
+                        .clicked()
+                    {
+                        self.ui.show_clipboard = !self.ui.show_clipboard;
+                    }
                 }
-                if let Some((err, _)) = &self.ui.error_message {
-                    ui.colored_label(egui::Color32::RED, format!(" | {}", err));
+                let history_count = self.ui.messages.history().count();
+                if ui
+                    .small_button(format!("🔔 {}", history_count))
+                    .on_hover_text("Message history (:messages)")
+                    .clicked()
+                {
+                    self.ui.show_message_history = !self.ui.show_message_history;
                 }
 
-                if !self.selection.multi_selection.is_empty() {
+                if !self.browser.selection.multi_selection.is_empty() {
                     ui.separator();
                     // Calculate total size of selected files
                     let total_size: u64 = self
+                        .browser
                         .entries
                         .all_entries
                         .iter()
-                        .filter(|e| self.selection.multi_selection.contains(&e.path))
+                        .filter(|e| self.browser.selection.multi_selection.contains(&e.path))
                         .map(|e| e.size)
                         .sum();
                     ui.colored_label(
                         egui::Color32::LIGHT_BLUE,
                         format!(
                             "{} selected ({})",
-                            self.selection.multi_selection.len(),
+                            self.browser.selection.multi_selection.len(),
                             bytesize::ByteSize(total_size)
                         ),
                     );
@@ -1434,10 +5118,24 @@ impl eframe::App for Heike {
             ref query,
             ref results,
             selected_index,
+            ref marked,
         } = self.mode.mode
         {
             // Track click selection
             let next_result_selection = std::cell::RefCell::new(None);
+            let archive_member_request = std::cell::RefCell::new(None);
+            // Replace preview: compiled once per frame from the search's own pattern
+            let replacement = self.ui.replace_text.as_str();
+            let replace_matcher =
+                (!replacement.is_empty()).then(|| build_matcher(query, &self.ui.search_options));
+            let capture_labels = match &replace_matcher {
+                Some(Ok(matcher)) => capture_names(matcher, query),
+                _ => Vec::new(),
+            };
+            let preview_replace = |line: &str| match &replace_matcher {
+                Some(Ok(matcher)) => replace_preview(matcher, line, replacement),
+                _ => None,
+            };
 
             egui::CentralPanel::default().show(ctx, |ui| {
                 ui.add_space(4.0);
@@ -1445,8 +5143,80 @@ impl eframe::App for Heike {
                     ui.heading(format!("Search Results: \"{}\"", query));
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.label(format!("{} matches", results.len()));
+                        if !marked.is_empty()
+                            && ui
+                                .button(format!("Select {} marked (Shift+Enter)", marked.len()))
+                                .on_hover_text(
+                                    "Return to the browser with the marked files selected",
+                                )
+                                .clicked()
+                        {
+                            let marked = marked.clone();
+                            *context_action.borrow_mut() =
+                                Some(Box::new(move |app: &mut Heike| {
+                                    app.select_marked_results(marked)
+                                }));
+                        }
                     });
                 });
+                match &replace_matcher {
+                    Some(Ok(_)) => {
+                        ui.horizontal(|ui| {
+                            ui.label("Replace preview:");
+                            ui.label(egui::RichText::new(replacement).code());
+                            ui.label(
+                                egui::RichText::new("(nothing is written; Shift+S to edit)")
+                                    .weak()
+                                    .italics(),
+                            );
+                            if ui
+                                .small_button("Dry run report")
+                                .on_hover_text(
+                                    "Every line the replacement would change, for review or export",
+                                )
+                                .clicked()
+                            {
+                                let changes = results
+                                    .iter()
+                                    .filter_map(|result| {
+                                        let preview = preview_replace(&result.line_content)?;
+                                        let source = result.file_path.clone();
+                                        Some(match &result.archive_member {
+                                            Some(member) => {
+                                                PlannedChange::new("skip", source, None).with_note(
+                                                    format!("inside the archive, at {}", member),
+                                                )
+                                            }
+                                            None => PlannedChange::new("replace", source, None)
+                                                .with_note(format!(
+                                                    "line {}: {} → {}",
+                                                    result.line_number,
+                                                    result.line_content.trim(),
+                                                    preview.replaced.trim()
+                                                )),
+                                        })
+                                    })
+                                    .collect();
+                                let report = DryRunReport::new(
+                                    format!("Replace \"{}\" with \"{}\"", query, replacement),
+                                    changes,
+                                );
+                                *context_action.borrow_mut() =
+                                    Some(Box::new(move |app: &mut Heike| {
+                                        let return_to = app.mode.mode.clone();
+                                        app.show_dry_run(report, Some(return_to));
+                                    }));
+                            }
+                        });
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(
+                            style::severity_color(Severity::Error),
+                            format!("Cannot preview replace: {}", e),
+                        );
+                    }
+                    None => {}
+                }
                 ui.separator();
                 ui.add_space(4.0);
 
@@ -1474,7 +5244,12 @@ impl eframe::App for Heike {
                                 }
 
                                 table.body(|body| {
-                                    body.rows(40.0, results.len(), |mut row| {
+                                    let row_height = if replace_matcher.is_some() {
+                                        56.0
+                                    } else {
+                                        40.0
+                                    };
+                                    body.rows(row_height, results.len(), |mut row| {
                                         let row_index = row.index();
                                         let result = &results[row_index];
                                         let is_selected = selected_index == row_index;
@@ -1485,9 +5260,59 @@ impl eframe::App for Heike {
 
                                         row.col(|ui| {
                                             ui.vertical(|ui| {
+                                                let mark = if marked.contains(&result.file_path) {
+                                                    "✔ "
+                                                } else {
+                                                    ""
+                                                };
+                                                // Hits from several roots name theirs
+                                                let root = match &self.ui.searched_roots[..] {
+                                                    [_, _, ..] => root_of(
+                                                        &result.file_path,
+                                                        &self.ui.searched_roots,
+                                                    )
+                                                    .map(|root| {
+                                                        format!(
+                                                            "[{}] ",
+                                                            root.file_name()
+                                                                .unwrap_or(root.as_os_str())
+                                                                .to_string_lossy()
+                                                        )
+                                                    }),
+                                                    _ => None,
+                                                };
+                                                // The first hit of each file carries its subtotal
+                                                let subtotal = if row_index == 0
+                                                    || results[row_index - 1].file_path
+                                                        != result.file_path
+                                                {
+                                                    let hits = self
+                                                        .ui
+                                                        .search_file_hits
+                                                        .get(&result.file_path)
+                                                        .copied()
+                                                        .unwrap_or(1);
+                                                    if self
+                                                        .ui
+                                                        .search_capped
+                                                        .contains(&result.file_path)
+                                                    {
+                                                        format!("  · {}+ hits (capped)", hits)
+                                                    } else if hits > 1 {
+                                                        format!("  · {} hits", hits)
+                                                    } else {
+                                                        String::new()
+                                                    }
+                                                } else {
+                                                    String::new()
+                                                };
                                                 let file_label = format!(
-                                                    "{}:{}",
-                                                    result.file_name, result.line_number
+                                                    "{}{}{}:{}{}",
+                                                    mark,
+                                                    root.unwrap_or_default(),
+                                                    result.file_name,
+                                                    result.line_number,
+                                                    subtotal
                                                 );
                                                 let text = if is_selected {
                                                     egui::RichText::new(&file_label).color(
@@ -1535,6 +5360,22 @@ impl eframe::App for Heike {
                                                     *next_result_selection.borrow_mut() =
                                                         Some(row_index);
                                                 }
+
+                                                if let Some(preview) =
+                                                    preview_replace(&result.line_content)
+                                                {
+                                                    style::truncated_label(
+                                                        ui,
+                                                        egui::RichText::new(format!(
+                                                            "→ {}",
+                                                            preview.replaced
+                                                        ))
+                                                        .size(10.0)
+                                                        .color(egui::Color32::from_rgb(
+                                                            120, 200, 120,
+                                                        )),
+                                                    );
+                                                }
                                             });
                                         });
                                     });
@@ -1558,10 +5399,89 @@ impl eframe::App for Heike {
                                 .max_height(ui.available_height())
                                 .show(ui, |ui| {
                                     ui.set_max_width(ui.available_width());
-                                    ui.horizontal(|ui| {
-                                        ui.label(format!("Line {}:", result.line_number));
-                                        ui.label(egui::RichText::new(&result.line_content).code());
-                                    });
+                                    match self.archive_match_context(
+                                        result,
+                                        5,
+                                        &archive_member_request,
+                                    ) {
+                                        Some(Ok(lines)) => {
+                                            let accent = egui::Color32::from_rgb(100, 200, 255);
+                                            for (number, line) in lines {
+                                                let text = egui::RichText::new(format!(
+                                                    "{:>5}  {}",
+                                                    number, line
+                                                ))
+                                                .monospace();
+                                                ui.label(if number == result.line_number {
+                                                    text.color(accent).strong()
+                                                } else {
+                                                    text
+                                                });
+                                            }
+                                        }
+                                        context => {
+                                            if let Some(Err(e)) = context {
+                                                ui.colored_label(
+                                                    egui::Color32::RED,
+                                                    format!("Cannot read archive member: {}", e),
+                                                );
+                                            }
+                                            ui.horizontal(|ui| {
+                                                ui.label(format!("Line {}:", result.line_number));
+                                                ui.label(
+                                                    egui::RichText::new(&result.line_content)
+                                                        .code(),
+                                                );
+                                            });
+                                        }
+                                    }
+
+                                    if let Some(preview) = preview_replace(&result.line_content) {
+                                        ui.add_space(10.0);
+                                        ui.label(egui::RichText::new("Replace preview").strong());
+                                        egui::Grid::new("replace_captures_grid")
+                                            .num_columns(2)
+                                            .striped(true)
+                                            .show(ui, |ui| {
+                                                for (index, group) in
+                                                    preview.groups.iter().enumerate()
+                                                {
+                                                    let label = match capture_labels.get(index) {
+                                                        Some(Some(name)) => {
+                                                            format!("${} ({})", index, name)
+                                                        }
+                                                        _ => format!("${}", index),
+                                                    };
+                                                    ui.monospace(label);
+                                                    match group {
+                                                        Some(text) => {
+                                                            ui.label(
+                                                                egui::RichText::new(text).code(),
+                                                            );
+                                                        }
+                                                        None => {
+                                                            ui.label(
+                                                                egui::RichText::new("unmatched")
+                                                                    .weak()
+                                                                    .italics(),
+                                                            );
+                                                        }
+                                                    }
+                                                    ui.end_row();
+                                                }
+                                                ui.label("Before");
+                                                ui.label(
+                                                    egui::RichText::new(&result.line_content)
+                                                        .code(),
+                                                );
+                                                ui.end_row();
+                                                ui.label("After");
+                                                ui.label(
+                                                    egui::RichText::new(&preview.replaced).code(),
+                                                );
+                                                ui.end_row();
+                                            });
+                                    }
 
                                     ui.add_space(10.0);
                                     ui.label("Full file path:");
@@ -1574,7 +5494,11 @@ impl eframe::App for Heike {
                                     ui.horizontal(|ui| {
                                         ui.label("Press");
                                         ui.label(egui::RichText::new("Enter").strong());
-                                        ui.label("to open file,");
+                                        ui.label(if result.archive_member.is_some() {
+                                            "to extract and open at this line,"
+                                        } else {
+                                            "to open file,"
+                                        });
                                         ui.label(egui::RichText::new("n/N").strong());
                                         ui.label("for next/previous,");
                                         ui.label(egui::RichText::new("Esc").strong());
@@ -1589,26 +5513,31 @@ impl eframe::App for Heike {
             // Apply deferred selection from click
             if let Some(new_index) = next_result_selection.into_inner() {
                 if let AppMode::SearchResults {
-                    ref query,
-                    ref results,
-                    selected_index: _,
+                    ref mut selected_index,
+                    ..
                 } = self.mode.mode
                 {
-                    self.mode.set_mode(AppMode::SearchResults {
-                        query: query.clone(),
-                        results: results.clone(),
-                        selected_index: new_index,
-                    });
+                    *selected_index = new_index;
                 }
             }
+            if let Some((archive, member, modified)) = archive_member_request.into_inner() {
+                self.request_archive_member(archive, member, modified);
+            }
+        } else if matches!(self.mode.mode, AppMode::Computer { .. }) {
+            self.render_computer(ctx);
         } else {
             // Normal file browser view
             // Visual feedback for drag and drop
-            let is_being_dragged_over = ctx.input(|i| !i.raw.hovered_files.is_empty());
+            let hovered_drop = ctx.input(|i| {
+                (!i.raw.hovered_files.is_empty()).then(|| crate::input::drop_action(i.modifiers))
+            });
+
+            self.render_clipboard_panel(ctx);
+            self.render_staging_panel(ctx);
 
             egui::CentralPanel::default().show(ctx, |ui| {
                 // Show drop zone overlay when files are being dragged over
-                if is_being_dragged_over {
+                if let Some(action) = hovered_drop {
                     let painter = ui.painter();
                     let rect = ui.available_rect_before_wrap();
                     painter.rect_stroke(
@@ -1617,17 +5546,39 @@ impl eframe::App for Heike {
                         egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 200, 255)),
                         egui::epaint::StrokeKind::Outside,
                     );
+                    let keys = if cfg!(target_os = "macos") {
+                        "Option: copy · Cmd: move · Option+Cmd: link"
+                    } else {
+                        "Ctrl: copy · Shift: move · Alt: link"
+                    };
                     ui.label(
-                        egui::RichText::new("📁 Drop files here to copy them to this directory")
-                            .size(16.0)
-                            .color(egui::Color32::from_rgb(100, 200, 255)),
+                        egui::RichText::new(format!(
+                            "📁 Drop files here to {} them to this directory",
+                            action.verb()
+                        ))
+                        .size(16.0)
+                        .color(egui::Color32::from_rgb(100, 200, 255)),
                     );
+                    ui.label(egui::RichText::new(keys).weak());
                 }
                 // Render modals
                 self.render_help_modal(ctx);
                 self.render_search_input_modal(ctx);
                 self.render_input_modal(ctx);
                 self.render_bulk_rename_modal(ctx);
+                self.render_dirdiff_modal(ctx);
+                self.render_cleanup_modal(ctx);
+                self.render_broken_links_modal(ctx);
+                self.render_name_audit_modal(ctx);
+                self.render_dry_run_modal(ctx);
+                self.render_diagnostics_modal(ctx);
+                self.render_properties_modal(ctx);
+                self.render_settings_modal(ctx);
+                self.render_maintenance_modal(ctx);
+                self.render_paste_conflict_modal(ctx);
+                self.render_job_error_modal(ctx);
+                self.render_exit_confirm_modal(ctx);
+                self.render_which_key(ctx);
 
                 // Strip-based layout with three panes and dividers
                 use egui_extras::{Size, StripBuilder};
@@ -1638,7 +5589,9 @@ impl eframe::App for Heike {
                     .size(Size::exact(style::DIVIDER_WIDTH))
                     .size(Size::exact(self.ui.panel_widths[1]).at_least(style::PREVIEW_MIN))
                     .horizontal(|mut strip| {
-                        strip.cell(|ui| self.render_parent_pane(ui, &next_navigation));
+                        strip.cell(|ui| {
+                            self.render_parent_pane(ui, &next_navigation, &context_action)
+                        });
                         strip.cell(|ui| self.render_divider(ui, 0));
                         strip.cell(|ui| {
                             self.render_current_pane(
@@ -1652,27 +5605,93 @@ impl eframe::App for Heike {
                         strip.cell(|ui| self.render_divider(ui, 1));
                         strip.cell(|ui| {
                             ui.add_space(4.0);
-                            self.render_preview(ui, &next_navigation, &pending_selection);
+                            self.render_preview(
+                                ui,
+                                &next_navigation,
+                                &pending_selection,
+                                &checksum_request,
+                                &dir_size_request,
+                                &archive_count_request,
+                                &dir_listing_request,
+                                &archive_page_request,
+                                &last_commit_request,
+                            );
                         });
                     });
             });
         } // End of else block for normal file browser view
 
+        // Messages are shown over every view, including search results
+        self.render_message_history(ctx);
+        self.render_task_center(ctx);
+        self.render_toasts(ctx);
+
         if let Some(idx) = next_selection.into_inner() {
-            self.selection.selected_index = Some(idx);
+            self.browser.selection.selected_index = Some(idx);
         }
         if let Some(pending) = pending_selection.into_inner() {
-            self.navigation.pending_selection_path = Some(pending);
+            self.browser.navigation.pending_selection_path = Some(pending);
         }
         if let Some(path) = next_navigation.into_inner() {
             self.navigate_to(path);
         }
+        if let Some(path) = checksum_request.into_inner() {
+            self.request_checksum(path);
+        }
+        if let Some(path) = dir_size_request.into_inner() {
+            self.request_dir_size(path);
+        }
+        if let Some(path) = archive_count_request.into_inner() {
+            self.request_archive_count(path);
+        }
+        if let Some((path, modified)) = dir_listing_request.into_inner() {
+            self.request_preview_listing(path, modified);
+        }
+        if let Some((path, modified, resume)) = archive_page_request.into_inner() {
+            self.request_archive_page(path, modified, resume);
+        }
+        if let Some(path) = last_commit_request.into_inner() {
+            self.request_last_commit(path);
+        }
         if let Some(action) = context_action.into_inner() {
             action(self);
         }
     }
 
+    /// The one way out: the close button, `:q` and finished background jobs all end here
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         self.save_settings();
+        self.worker.shutdown(SHUTDOWN_GRACE);
+    }
+}
+
+/// What `run_job_item` would do, for a dry run
+fn plan_job_item(kind: &JobKind, path: &std::path::Path) -> PlannedChange {
+    match kind {
+        JobKind::Trash if fs::symlink_metadata(path).is_err() => {
+            PlannedChange::failure(path.to_path_buf(), "no longer exists")
+        }
+        JobKind::Trash => PlannedChange::new("trash", path.to_path_buf(), None),
+        JobKind::Link { dest_dir } => core::plan_link_item(path, dest_dir),
+        JobKind::Paste {
+            op,
+            policy,
+            dest_dir,
+        } => core::plan_paste_item(path, *op, *policy, dest_dir),
+        JobKind::Split { source, .. } => {
+            PlannedChange::new("write", source.clone(), Some(path.to_path_buf()))
+        }
+        JobKind::Join { dest, .. } => {
+            PlannedChange::new("append", path.to_path_buf(), Some(dest.clone()))
+        }
+        JobKind::Mirror { from, to } => match core::mirror_dir(path, from, to) {
+            Ok(dest_dir) => core::plan_paste_item(
+                path,
+                ClipboardOp::Copy,
+                ConflictPolicy::Overwrite,
+                &dest_dir,
+            ),
+            Err(e) => PlannedChange::failure(path.to_path_buf(), e),
+        },
     }
 }
@@ -1,10 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Application configuration
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Config {
     pub theme: ThemeConfig,
     pub panel: PanelConfig,
@@ -14,17 +14,38 @@ pub struct Config {
     pub bookmarks: BookmarksConfig,
     #[serde(default)]
     pub previews: PreviewConfig,
+    #[serde(default)]
+    pub tools: ToolsConfig,
+    #[serde(default)]
+    pub open: OpenConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub file_ops: FileOpsConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    /// Command-mode aliases, each running a `;`-separated command sequence
+    /// Example: {"proj" = "cd ~/Projects; sort modified desc"}
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    /// Per-extension application overrides consulted before the OS default
+    /// Example: {"pdf" = "zathura", "md" = "code"}
+    #[serde(default)]
+    pub associations: HashMap<String, String>,
+    /// Shell commands run before or after file operations, in order (`[[hooks]]`)
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
 }
 
 /// Theme configuration
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct ThemeConfig {
     /// "dark" or "light"
     pub mode: String,
 }
 
 /// Panel layout configuration
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct PanelConfig {
     /// Width of parent directory pane (in pixels)
     pub parent_width: f32,
@@ -33,7 +54,7 @@ pub struct PanelConfig {
 }
 
 /// Font and text rendering configuration
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct FontConfig {
     /// Size of the main interface font (in points)
     pub font_size: f32,
@@ -45,31 +66,283 @@ pub struct FontConfig {
 }
 
 /// UI behavior configuration
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct UiConfig {
     /// Show hidden files by default
     pub show_hidden: bool,
-    /// Default sort field: "name", "size", "modified", "extension"
+    /// Default sort field: "name", "size", "modified", "created", "extension"
     pub sort_by: String,
     /// Sort order: "asc" or "desc"
     pub sort_order: String,
     /// Show directories first in sorting
     pub dirs_first: bool,
+    /// Draw a bar behind each file size, proportional to the largest file listed
+    #[serde(default = "default_true")]
+    pub size_bars: bool,
+    /// Also total directories without hidden and git-ignored files ("source size")
+    #[serde(default)]
+    pub source_size: bool,
+    /// Folder names (at any depth) that content search and folder sizes leave out
+    #[serde(default = "default_build_dirs")]
+    pub build_dirs: Vec<String>,
+    /// Leave out `build_dirs`; false walks into them too
+    #[serde(default = "default_true")]
+    pub skip_build_dirs: bool,
+    /// Where long names are cut: "end", or "middle" to keep the extension visible
+    #[serde(default = "default_name_ellipsis")]
+    pub name_ellipsis: String,
+    /// Apply the `/` filter to the folder shown in the preview pane as well
+    #[serde(default)]
+    pub filter_preview: bool,
+    /// Dock the clipboard panel (yanked and cut items) beside the listing
+    #[serde(default)]
+    pub clipboard_panel: bool,
+    /// Show the File / Edit / View / Tools / Tabs / Help menu bar
+    #[serde(default = "default_true")]
+    pub menu_bar: bool,
+    /// Sort fields ("name", "modified", "created", "extension") whose listings get group
+    /// header rows: Today / Yesterday / This week / Older, first letter, or extension
+    #[serde(default)]
+    pub group_headers: Vec<String>,
+    /// Size and modification time of a symlink are its target's; false shows the link's own
+    #[serde(default = "default_true")]
+    pub link_target_stats: bool,
+}
+
+impl UiConfig {
+    /// Folder names recursive walks skip, empty when `skip_build_dirs` is off
+    pub fn skipped_dirs(&self) -> Vec<String> {
+        if self.skip_build_dirs {
+            self.build_dirs.clone()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+fn default_build_dirs() -> Vec<String> {
+    ["node_modules", "target", ".venv", ".git"]
+        .map(String::from)
+        .to_vec()
+}
+
+fn default_name_ellipsis() -> String {
+    "end".into()
+}
+
+fn default_true() -> bool {
+    true
 }
 
-/// Bookmarks configuration - map of single character to directory path
-/// Example: {"d" = "~/Downloads", "h" = "~", "p" = "~/Projects"}
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+/// Bookmarks configuration - map of key sequence (typed after `g`) to a directory or file
+/// Example: {"d" = "~/Downloads", "h" = "~", "dd" = "$PROJECTS/docs", "n" = "~/notes.md"}
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct BookmarksConfig {
     pub shortcuts: HashMap<String, String>,
 }
 
 /// Preview configuration - control which preview handlers are enabled
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct PreviewConfig {
     /// List of enabled preview handlers
     /// Available: "directory", "image", "markdown", "archive", "pdf", "office", "audio", "text", "binary"
     pub enabled: Vec<String>,
+    /// Handler priority overrides, lower is tried first (e.g. `text = 1`)
+    #[serde(default)]
+    pub priorities: HashMap<String, i32>,
+    /// Extension → handler name, used regardless of priority (e.g. `svg = "text"`)
+    #[serde(default)]
+    pub handlers: HashMap<String, String>,
+    /// Syntect theme for code previews in dark mode; `.tmTheme` files in the
+    /// `themes` folder next to the config are available too
+    #[serde(default = "default_code_theme_dark")]
+    pub code_theme_dark: String,
+    /// Syntect theme for code previews in light mode
+    #[serde(default = "default_code_theme_light")]
+    pub code_theme_light: String,
+    /// Show packed size, compression ratio and CRC-32 columns for zip members
+    #[serde(default)]
+    pub archive_details: bool,
+}
+
+fn default_code_theme_dark() -> String {
+    "base16-ocean.dark".into()
+}
+
+fn default_code_theme_light() -> String {
+    "base16-ocean.light".into()
+}
+
+/// External tool configuration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ToolsConfig {
+    /// Probe for external tools at startup; when false every tool is assumed present
+    pub check_on_startup: bool,
+    /// Editor command (e.g. "code -w"); falls back to $VISUAL / $EDITOR
+    #[serde(default)]
+    pub editor: Option<String>,
+    /// Terminal emulator command; falls back to $TERMINAL / platform default
+    #[serde(default)]
+    pub terminal: Option<String>,
+    /// bsdtar-compatible command for 7z, rar, xz and bz2 previews; falls back to
+    /// `bsdtar` on PATH (or `tar` on macOS and Windows)
+    #[serde(default)]
+    pub archiver: Option<String>,
+}
+
+/// Opening many files at once
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct OpenConfig {
+    /// Ask before opening more than this many files from a selection
+    pub confirm_threshold: usize,
+    /// Files handed to the default application per batch (one batch every half second)
+    pub batch_size: usize,
+}
+
+impl Default for OpenConfig {
+    fn default() -> Self {
+        OpenConfig {
+            confirm_threshold: 10,
+            batch_size: 5,
+        }
+    }
+}
+
+/// Copy, move and rename behaviour
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct FileOpsConfig {
+    /// When a pasted item already exists: "ask", "rename" (keep both as "name (1)"),
+    /// "overwrite" (existing item goes to the trash) or "skip".
+    /// "rename" also makes bulk rename suffix taken names instead of failing them.
+    pub conflict_policy: String,
+    /// Closing the window while jobs are running or queued asks whether to wait for
+    /// them, cancel them or finish them minimized (closing again then quits right away)
+    pub finish_jobs_on_close: bool,
+    /// Tray icon listing jobs, with bookmarks to open and Quit: "background" while
+    /// finishing jobs minimized, "always" or "never" (Linux, `tray` build feature)
+    pub tray_icon: String,
+    /// Name pattern of the bulk rename "Photo date" preset: `{date}` (YYYYMMDD), `{time}`
+    /// (HHMMSS) and `{seq}` (001, 002, … per day) from the EXIF capture time or the mtime
+    pub photo_pattern: String,
+    /// Also append every logged operation to one file per day under the data directory
+    pub daily_log: bool,
+    /// Delete daily log files older than this many days at startup (0 = keep them all)
+    pub log_keep_days: u32,
+}
+
+impl Default for FileOpsConfig {
+    fn default() -> Self {
+        FileOpsConfig {
+            conflict_policy: "ask".to_string(),
+            finish_jobs_on_close: true,
+            tray_icon: "background".to_string(),
+            photo_pattern: "IMG_{date}_{seq}".to_string(),
+            daily_log: false,
+            log_keep_days: 90,
+        }
+    }
+}
+
+/// A shell command run around paste, trash, link, split and join jobs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct HookConfig {
+    /// "before" (a failing command stops the job) or "after" (runs once the job is done)
+    pub when: String,
+    /// "copy", "move", "paste" (either), "trash" (or "delete"), "link", "split", "join";
+    /// empty or "*" for any
+    pub operation: String,
+    /// Only for jobs whose items or destination are this folder or inside it (empty = anywhere)
+    pub path: String,
+    /// Run through the shell in the job's folder with `HEIKE_OPERATION`, `HEIKE_DIR`,
+    /// `HEIKE_FILES` (one path per line, left unset for very large selections) and
+    /// `HEIKE_FILE_LIST` (a file of NUL-terminated paths) set; may be empty for a
+    /// confirm-only hook
+    pub command: String,
+    /// Before hooks: a word to type before the job goes ahead (empty = no question)
+    pub confirm: String,
+}
+
+impl Default for HookConfig {
+    fn default() -> Self {
+        HookConfig {
+            when: "after".to_string(),
+            operation: String::new(),
+            path: String::new(),
+            command: String::new(),
+            confirm: String::new(),
+        }
+    }
+}
+
+impl HookConfig {
+    /// Whether the hook is for `when` ("before"/"after") of `operation` touching any of `paths`
+    pub fn applies(&self, when: &str, operation: &str, paths: &[PathBuf]) -> bool {
+        let operation_matches = match self.operation.trim() {
+            "" | "*" => true,
+            "paste" => matches!(operation, "copy" | "move"),
+            "delete" => operation == "trash",
+            name => name == operation,
+        };
+        let path_matches = self.path.trim().is_empty() || {
+            let folder = expand_path(self.path.trim());
+            paths.iter().any(|path| path.starts_with(&folder))
+        };
+        self.when.trim() == when && operation_matches && path_matches
+    }
+}
+
+/// Desktop notifications
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    pub enabled: bool,
+    /// Notify when a paste/delete job that ran at least this long finishes or stops on an error
+    pub job_min_secs: u64,
+    /// Folders (e.g. "~/Downloads") whose new files are announced while Heike is unfocused
+    pub watch: Vec<String>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        NotificationsConfig {
+            enabled: true,
+            job_min_secs: 30,
+            watch: Vec::new(),
+        }
+    }
+}
+
+/// Cache limits and automatic cleanup
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    /// Memory budget for cached text previews, in MiB (0 = only the entry limit applies)
+    pub preview_cache_mb: usize,
+    /// Purge trashed items older than this many days at startup (0 = never)
+    pub trash_retention_days: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        MaintenanceConfig {
+            preview_cache_mb: 64,
+            trash_retention_days: 0,
+        }
+    }
+}
+
+impl Default for ToolsConfig {
+    fn default() -> Self {
+        ToolsConfig {
+            check_on_startup: true,
+            editor: None,
+            terminal: None,
+            archiver: None,
+        }
+    }
 }
 
 impl Default for PreviewConfig {
@@ -86,27 +359,64 @@ impl Default for PreviewConfig {
                 "text".to_string(),
                 "binary".to_string(),
             ],
+            priorities: HashMap::new(),
+            handlers: HashMap::new(),
+            code_theme_dark: default_code_theme_dark(),
+            code_theme_light: default_code_theme_light(),
+            archive_details: false,
+        }
+    }
+}
+
+/// Replace `$VAR` and `${VAR}` with environment values; unset variables stay as written
+fn expand_env_vars(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(dollar) = rest.find('$') {
+        out.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        let (name, consumed) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        match std::env::var(name) {
+            Ok(value) if !name.is_empty() => out.push_str(&value),
+            _ => out.push_str(&rest[dollar..dollar + 1 + consumed]),
         }
+        rest = &after[consumed..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Expand `$VAR`/`${VAR}` and a leading ~ in a configured path
+fn expand_path(path_str: &str) -> PathBuf {
+    let path_str = &expand_env_vars(path_str);
+    if let Some(rest) = path_str.strip_prefix('~') {
+        if let Some(home_dir) = directories::UserDirs::new().map(|ud| ud.home_dir().to_path_buf()) {
+            home_dir.join(rest.trim_start_matches(['/', '\\']))
+        } else {
+            PathBuf::from(path_str)
+        }
+    } else {
+        PathBuf::from(path_str)
     }
 }
 
 impl BookmarksConfig {
-    /// Resolve a bookmark path, expanding ~ to home directory
+    /// Resolve a bookmark path, expanding `$VAR`/`${VAR}` and ~ to home directory
     pub fn resolve_path(&self, key: &str) -> Option<PathBuf> {
-        self.shortcuts.get(key).map(|path_str| {
-            if path_str.starts_with('~') {
-                if let Some(home_dir) =
-                    directories::UserDirs::new().map(|ud| ud.home_dir().to_path_buf())
-                {
-                    let rest = &path_str[1..];
-                    home_dir.join(rest)
-                } else {
-                    PathBuf::from(path_str)
-                }
-            } else {
-                PathBuf::from(path_str)
-            }
-        })
+        self.shortcuts
+            .get(key)
+            .map(|path_str| expand_path(path_str))
     }
 
     /// Get all available bookmark keys
@@ -143,9 +453,27 @@ impl Default for Config {
                 sort_by: "name".to_string(),
                 sort_order: "asc".to_string(),
                 dirs_first: true,
+                size_bars: true,
+                source_size: false,
+                build_dirs: default_build_dirs(),
+                skip_build_dirs: true,
+                name_ellipsis: default_name_ellipsis(),
+                filter_preview: false,
+                clipboard_panel: false,
+                menu_bar: true,
+                group_headers: Vec::new(),
+                link_target_stats: true,
             },
             bookmarks: BookmarksConfig { shortcuts },
             previews: PreviewConfig::default(),
+            tools: ToolsConfig::default(),
+            open: OpenConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            file_ops: FileOpsConfig::default(),
+            notifications: NotificationsConfig::default(),
+            aliases: HashMap::new(),
+            associations: HashMap::new(),
+            hooks: Vec::new(),
         }
     }
 }
@@ -161,20 +489,24 @@ impl Config {
         None
     }
 
+    /// Folder searched for extra `.tmTheme` syntax themes
+    pub fn themes_dir() -> Option<PathBuf> {
+        Self::config_path().and_then(|path| Some(path.parent()?.join("themes")))
+    }
+
+    /// Folder searched for extra `.sublime-syntax` definitions
+    pub fn syntaxes_dir() -> Option<PathBuf> {
+        Self::config_path().and_then(|path| Some(path.parent()?.join("syntaxes")))
+    }
+
     /// Load configuration from file, or return defaults if file doesn't exist
     pub fn load() -> Self {
         if let Some(path) = Self::config_path() {
             if path.exists() {
-                match fs::read_to_string(&path) {
-                    Ok(contents) => match toml::from_str::<Config>(&contents) {
-                        Ok(config) => return config,
-                        Err(e) => {
-                            eprintln!("Failed to parse config file: {}", e);
-                            eprintln!("Using default configuration");
-                        }
-                    },
+                match Self::read(&path) {
+                    Ok(config) => return config,
                     Err(e) => {
-                        eprintln!("Failed to read config file: {}", e);
+                        eprintln!("{}", e);
                         eprintln!("Using default configuration");
                     }
                 }
@@ -183,6 +515,13 @@ impl Config {
         Config::default()
     }
 
+    /// Read and parse a config file, e.g. again after it was edited outside Heike
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read config file: {}", e))?;
+        toml::from_str(&contents).map_err(|e| format!("Failed to parse config file: {}", e))
+    }
+
     /// Save configuration to file
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(path) = Self::config_path() {
@@ -198,6 +537,80 @@ impl Config {
 
         Err("Could not determine config directory".into())
     }
+
+    /// Split a command line on `;` and replace alias names by the commands they stand for
+    ///
+    /// A `;` inside quotes or written as `\;` belongs to the command instead (see
+    /// `split_commands`). Arguments given after an alias are appended to its last command.
+    /// Aliases may use other aliases; one that refers back to itself, like
+    /// `ls = "ls --long"`, runs the command itself.
+    pub fn expand_aliases(&self, line: &str) -> Vec<String> {
+        let mut commands = Vec::new();
+        self.expand_aliases_into(line, &mut Vec::new(), &mut commands);
+        commands
+    }
+
+    fn expand_aliases_into<'a>(
+        &'a self,
+        line: &str,
+        expanding: &mut Vec<&'a str>,
+        commands: &mut Vec<String>,
+    ) {
+        for command in split_commands(line) {
+            let command = command.as_str();
+            let (name, args) = command
+                .split_once(char::is_whitespace)
+                .map_or((command, ""), |(name, args)| (name, args.trim()));
+            match self.aliases.get_key_value(name) {
+                Some((alias, expansion)) if !expanding.contains(&alias.as_str()) => {
+                    expanding.push(alias);
+                    let first = commands.len();
+                    self.expand_aliases_into(expansion, expanding, commands);
+                    expanding.pop();
+                    if let (Some(last), false) = (commands[first..].last_mut(), args.is_empty()) {
+                        last.push(' ');
+                        last.push_str(args);
+                    }
+                }
+                _ => commands.push(command.to_string()),
+            }
+        }
+    }
+}
+
+/// Split a command line into its `;`-separated commands, trimmed and without empty ones
+///
+/// Quoted text (`"…"` or `'…'`) keeps its `;` and its quotes; `\;` is a literal `;`. Other
+/// backslashes are left alone, so Windows paths need no escaping.
+fn split_commands(line: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', _) if chars.peek() == Some(&';') => {
+                current.push(';');
+                chars.next();
+            }
+            (';', None) => commands.push(std::mem::take(&mut current)),
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (c, Some(open)) if c == open => {
+                quote = None;
+                current.push(c);
+            }
+            (c, _) => current.push(c),
+        }
+    }
+    commands.push(current);
+    commands
+        .into_iter()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
 }
 
 #[cfg(test)]
@@ -222,4 +635,207 @@ mod tests {
         let deserialized: Config = toml::from_str(&toml_str).expect("Failed to deserialize");
         assert_eq!(config.theme.mode, deserialized.theme.mode);
     }
+
+    #[test]
+    fn test_aliases_expand_to_command_sequences() {
+        let mut config = Config::default();
+        for (name, expansion) in [
+            ("proj", "cd ~/Projects; sort modified desc"),
+            ("work", "proj; mirror"),
+            ("ls", "ls --long"),
+            ("mk", "mkdir"),
+        ] {
+            config.aliases.insert(name.into(), expansion.into());
+        }
+
+        assert_eq!(
+            config.expand_aliases("work ; tasks"),
+            vec!["cd ~/Projects", "sort modified desc", "mirror", "tasks"]
+        );
+        assert_eq!(config.expand_aliases("mk {a,b}"), vec!["mkdir {a,b}"]);
+        assert_eq!(config.expand_aliases("ls"), vec!["ls --long"]);
+        assert_eq!(config.expand_aliases("cd /tmp"), vec!["cd /tmp"]);
+        assert!(config.expand_aliases(" ; ").is_empty());
+    }
+
+    #[test]
+    fn test_quoted_and_escaped_semicolons_stay_in_the_command() {
+        let mut config = Config::default();
+        config
+            .aliases
+            .insert("both".into(), r#"shell echo "a;b"; tasks"#.into());
+
+        assert_eq!(
+            config.expand_aliases(r"shell echo a\;b; tasks"),
+            vec!["shell echo a;b", "tasks"]
+        );
+        assert_eq!(
+            config.expand_aliases("mkdir 'x;y' ; sort name"),
+            vec!["mkdir 'x;y'", "sort name"]
+        );
+        assert_eq!(
+            config.expand_aliases("both"),
+            vec![r#"shell echo "a;b""#, "tasks"]
+        );
+        assert_eq!(
+            config.expand_aliases(r"cd C:\Users\me"),
+            vec![r"cd C:\Users\me"]
+        );
+    }
+
+    #[test]
+    fn test_associations_round_trip() {
+        let mut config = Config::default();
+        config
+            .associations
+            .insert("pdf".to_string(), "zathura".to_string());
+        let toml_str = toml::to_string(&config).expect("Failed to serialize");
+        let deserialized: Config = toml::from_str(&toml_str).expect("Failed to deserialize");
+        assert_eq!(
+            deserialized.associations.get("pdf").map(String::as_str),
+            Some("zathura")
+        );
+    }
+
+    #[test]
+    fn test_preview_overrides_parse() {
+        let previews: PreviewConfig = toml::from_str(
+            "enabled = [\"text\"]\n[priorities]\ntext = 1\n[handlers]\nsvg = \"text\"\n",
+        )
+        .expect("Failed to parse");
+        assert_eq!(previews.priorities.get("text"), Some(&1));
+        assert_eq!(previews.code_theme_dark, "base16-ocean.dark");
+        assert_eq!(previews.code_theme_light, "base16-ocean.light");
+        assert!(!previews.archive_details);
+        assert_eq!(
+            previews.handlers.get("svg").map(String::as_str),
+            Some("text")
+        );
+
+        let legacy: PreviewConfig =
+            toml::from_str("enabled = [\"text\"]\n").expect("Failed to parse");
+        assert!(legacy.priorities.is_empty() && legacy.handlers.is_empty());
+    }
+
+    #[test]
+    fn test_notifications_parse() {
+        let notifications: NotificationsConfig =
+            toml::from_str("watch = [\"~/Downloads\"]\n").expect("Failed to parse");
+        assert!(notifications.enabled);
+        assert_eq!(notifications.job_min_secs, 30);
+        assert_eq!(notifications.watch, vec!["~/Downloads".to_string()]);
+    }
+
+    #[test]
+    fn test_hooks_parse_and_match() {
+        #[derive(Deserialize)]
+        struct Hooks {
+            hooks: Vec<HookConfig>,
+        }
+        let Hooks { hooks } = toml::from_str(
+            "[[hooks]]\noperation = \"paste\"\npath = \"/srv/photos\"\ncommand = \"sort-photos\"\n\n\
+             [[hooks]]\nwhen = \"before\"\noperation = \"delete\"\npath = \"/srv\"\nconfirm = \"DELETE\"\n",
+        )
+        .expect("Failed to parse");
+        let (after_paste, before_delete) = (&hooks[0], &hooks[1]);
+        assert_eq!(after_paste.when, "after");
+        assert!(after_paste.confirm.is_empty());
+
+        let photos = [PathBuf::from("/srv/photos")];
+        assert!(after_paste.applies("after", "move", &photos));
+        assert!(!after_paste.applies("before", "move", &photos));
+        assert!(!after_paste.applies("after", "link", &photos));
+        assert!(!after_paste.applies("after", "copy", &[PathBuf::from("/srv/photos-old")]));
+        assert!(before_delete.applies("before", "trash", &[PathBuf::from("/srv/a/b.txt")]));
+        assert!(!before_delete.applies("before", "trash", &[PathBuf::from("/home/b.txt")]));
+    }
+
+    #[test]
+    fn test_size_bars_default_when_missing() {
+        let ui: UiConfig = toml::from_str(
+            "show_hidden = false\nsort_by = \"name\"\nsort_order = \"asc\"\ndirs_first = true\n",
+        )
+        .expect("Failed to deserialize");
+        assert!(ui.size_bars);
+        assert!(!ui.source_size);
+        assert_eq!(
+            ui.skipped_dirs(),
+            ["node_modules", "target", ".venv", ".git"]
+        );
+        assert_eq!(ui.name_ellipsis, "end");
+        assert!(!ui.filter_preview);
+        assert!(!ui.clipboard_panel);
+        assert!(ui.menu_bar);
+        assert!(ui.group_headers.is_empty());
+        assert!(ui.link_target_stats);
+    }
+
+    #[test]
+    fn test_maintenance_defaults_when_missing() {
+        let maintenance: MaintenanceConfig =
+            toml::from_str("trash_retention_days = 30\n").expect("Failed to deserialize");
+        assert_eq!(maintenance.trash_retention_days, 30);
+        assert_eq!(maintenance.preview_cache_mb, 64);
+    }
+
+    #[test]
+    fn test_file_ops_default_when_missing() {
+        let config: Config =
+            toml::from_str(&toml::to_string(&Config::default()).expect("Failed to serialize"))
+                .expect("Failed to deserialize");
+        assert_eq!(config.file_ops.conflict_policy, "ask");
+        let file_ops: FileOpsConfig = toml::from_str("").expect("Failed to deserialize");
+        assert_eq!(file_ops.conflict_policy, "ask");
+        assert!(!file_ops.daily_log);
+        assert_eq!(file_ops.log_keep_days, 90);
+    }
+
+    #[test]
+    fn test_bookmark_paths_expand_env_vars() {
+        let path = std::env::var("PATH").expect("PATH is set");
+        let bookmarks: BookmarksConfig = toml::from_str(
+            r#"
+            [shortcuts]
+            dd = "$PATH/docs"
+            db = "${PATH}docs"
+            u = "/srv/$HEIKE_UNSET_TEST_VAR/${HEIKE_UNSET_TEST_VAR}"
+            c = "/tmp/$5.txt"
+            "#,
+        )
+        .expect("Failed to deserialize");
+        assert_eq!(
+            bookmarks.resolve_path("dd"),
+            Some(PathBuf::from(format!("{}/docs", path)))
+        );
+        assert_eq!(
+            bookmarks.resolve_path("db"),
+            Some(PathBuf::from(format!("{}docs", path)))
+        );
+        assert_eq!(
+            bookmarks.resolve_path("u"),
+            Some(PathBuf::from(
+                "/srv/$HEIKE_UNSET_TEST_VAR/${HEIKE_UNSET_TEST_VAR}"
+            ))
+        );
+        assert_eq!(
+            bookmarks.resolve_path("c"),
+            Some(PathBuf::from("/tmp/$5.txt"))
+        );
+    }
+
+    #[test]
+    fn test_read_round_trips_and_reports_errors() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut config = Config::default();
+        config.ui.show_hidden = true;
+        fs::write(&path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        // Unchanged settings compare equal, so they need not be written again
+        assert_eq!(Config::read(&path), Ok(config));
+
+        fs::write(&path, "[ui\nshow_hidden = true").unwrap();
+        assert!(Config::read(&path)
+            .unwrap_err()
+            .starts_with("Failed to parse config file"));
+    }
 }
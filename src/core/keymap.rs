@@ -0,0 +1,708 @@
+// Normal-mode keymap - key presses in, one command per completed sequence out
+//
+// Presses are fed one at a time from the frame's key events, so a press can only
+// ever trigger a single binding. A small state machine collects a count prefix
+// ("5j") and the `g` prefix ("gg" to the top, "gP"/"gM" to the project or mount
+// root, "gC" to the Computer page, "g<keys>" for a bookmark); an
+// unfinished sequence is dropped once it has been idle longer than the timeout,
+// except a bookmark that a longer one extends ("gd" next to "gdd"), which fires then.
+// Once a `g` sequence has waited a moment, its continuations can be listed for a
+// which-key overlay; listing them holds the sequence open until the next key.
+
+use std::time::{Duration, Instant};
+
+/// A key, independent of the windowing toolkit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    /// Letters in lowercase (Shift is a modifier); digits and punctuation as typed
+    Char(char),
+    Enter,
+    Escape,
+    Backspace,
+    Tab,
+    Space,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyPress {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyPress {
+    pub fn new(key: Key, ctrl: bool, shift: bool, alt: bool) -> Self {
+        // Punctuation often needs Shift to type; ':' means ':' on every layout
+        let shift = match key {
+            Key::Char(c) if !c.is_ascii_alphanumeric() => false,
+            _ => shift,
+        };
+        Self {
+            key,
+            ctrl,
+            shift,
+            alt,
+        }
+    }
+
+    fn is_plain(&self) -> bool {
+        !self.ctrl && !self.shift && !self.alt
+    }
+
+    fn is_shifted(&self) -> bool {
+        self.shift && !self.ctrl && !self.alt
+    }
+}
+
+/// What a key sequence asks for in normal and visual mode
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Down,
+    Up,
+    /// First entry, or the entry numbered by the count
+    Top,
+    /// Last entry, or the entry numbered by the count
+    Bottom,
+    HalfPageDown,
+    HalfPageUp,
+    PageDown,
+    PageUp,
+    Parent,
+    /// Enter the directory under the cursor (files are left alone)
+    EnterDir,
+    /// Enter a directory or open a file; opens every file of a multi-selection
+    Open,
+    /// Enter a directory or open a file with its application
+    OpenEntry,
+    OpenWith,
+    OpenAtPreview,
+    Properties,
+    ExtractHint,
+    Back,
+    Forward,
+    /// Leave visual mode and clear the filter
+    Cancel,
+    /// Open the `:` command line
+    Colon,
+    Filter,
+    Search,
+    Help,
+    ToggleHidden,
+    ToggleFlatten,
+    CycleSort,
+    ToggleSortOrder,
+    ToggleDirsFirst,
+    PreviewTab {
+        forward: bool,
+    },
+    NewTab,
+    CloseTab,
+    NextTab,
+    PrevTab,
+    MirrorToTab,
+    /// Zero-based tab index
+    SwitchTab(usize),
+    Visual,
+    VisualAll,
+    SelectAll,
+    ToggleSelect,
+    InvertSelection,
+    Copy,
+    Cut,
+    Paste,
+    Stage,
+    Delete,
+    Rename,
+    BulkRename,
+    /// Keys typed after `g`, naming a bookmark that may not exist
+    Bookmark(String),
+    /// Top of the git project holding the current directory
+    ProjectRoot,
+    /// Mount point of the filesystem holding the current directory
+    MountRoot,
+    /// Overview of drives, places and recent locations
+    Computer,
+}
+
+impl Command {
+    /// Short description for the which-key overlay
+    pub fn description(&self) -> &'static str {
+        match self {
+            Command::Down => "Next entry",
+            Command::Up => "Previous entry",
+            Command::Top => "First entry",
+            Command::Bottom => "Last entry",
+            Command::HalfPageDown => "Half a page down",
+            Command::HalfPageUp => "Half a page up",
+            Command::PageDown => "Page down",
+            Command::PageUp => "Page up",
+            Command::Parent => "Parent folder",
+            Command::EnterDir => "Enter folder",
+            Command::Open => "Open the selection",
+            Command::OpenEntry => "Open with its application",
+            Command::OpenWith => "Open with…",
+            Command::OpenAtPreview => "Open at the previewed line",
+            Command::Properties => "Properties",
+            Command::ExtractHint => "Archive extraction hint",
+            Command::Back => "Back",
+            Command::Forward => "Forward",
+            Command::Cancel => "Leave visual mode, clear the filter",
+            Command::Colon => "Command line",
+            Command::Filter => "Filter",
+            Command::Search => "Search in files",
+            Command::Help => "Key bindings",
+            Command::ToggleHidden => "Show hidden files",
+            Command::ToggleFlatten => "Flatten view",
+            Command::CycleSort => "Next sort field",
+            Command::ToggleSortOrder => "Reverse sort order",
+            Command::ToggleDirsFirst => "Folders first",
+            Command::PreviewTab { forward: true } => "Next preview tab",
+            Command::PreviewTab { forward: false } => "Previous preview tab",
+            Command::NewTab => "New tab",
+            Command::CloseTab => "Close tab",
+            Command::NextTab => "Next tab",
+            Command::PrevTab => "Previous tab",
+            Command::MirrorToTab => "Show this folder in the next tab",
+            Command::SwitchTab(_) => "Switch tab",
+            Command::Visual => "Visual mode on / off",
+            Command::VisualAll | Command::SelectAll => "Select everything",
+            Command::ToggleSelect => "Toggle the entry under the cursor",
+            Command::InvertSelection => "Invert the selection",
+            Command::Copy => "Copy",
+            Command::Cut => "Cut",
+            Command::Paste => "Paste",
+            Command::Stage => "Stage",
+            Command::Delete => "Move to trash",
+            Command::Rename => "Rename",
+            Command::BulkRename => "Bulk rename",
+            Command::Bookmark(_) => "Bookmark",
+            Command::ProjectRoot => "Project root",
+            Command::MountRoot => "Mount point",
+            Command::Computer => "Computer",
+        }
+    }
+}
+
+/// What is done with a selection, listed once visual mode has been entered
+pub fn visual_commands() -> [Command; 11] {
+    [
+        Command::Down,
+        Command::Up,
+        Command::ToggleSelect,
+        Command::SelectAll,
+        Command::InvertSelection,
+        Command::Copy,
+        Command::Cut,
+        Command::Delete,
+        Command::BulkRename,
+        Command::Stage,
+        Command::Visual,
+    ]
+}
+
+/// One-key completions of `g` besides bookmarks: the key, whether Shift is held, the command
+fn g_builtins() -> [(char, bool, Command); 4] {
+    [
+        ('g', false, Command::Top),
+        ('p', true, Command::ProjectRoot),
+        ('m', true, Command::MountRoot),
+        ('c', true, Command::Computer),
+    ]
+}
+
+/// How a press is written for people: "j", "Shift+R", "Ctrl+Tab"
+pub fn press_label(press: KeyPress) -> String {
+    if press.is_plain() {
+        if let Key::Char(c) = press.key {
+            return c.to_string();
+        }
+    }
+    let key = match press.key {
+        Key::Char(c) => c.to_ascii_uppercase().to_string(),
+        Key::Enter => "Enter".into(),
+        Key::Escape => "Esc".into(),
+        Key::Backspace => "Backspace".into(),
+        Key::Tab => "Tab".into(),
+        Key::Space => "Space".into(),
+        Key::Up => "Up".into(),
+        Key::Down => "Down".into(),
+        Key::Left => "Left".into(),
+        Key::Right => "Right".into(),
+    };
+    let mut label = String::new();
+    for (held, name) in [
+        (press.ctrl, "Ctrl+"),
+        (press.alt, "Alt+"),
+        (press.shift, "Shift+"),
+    ] {
+        if held {
+            label.push_str(name);
+        }
+    }
+    label + &key
+}
+
+/// The simplest single press bound to `command`, found by asking the bindings themselves
+pub fn key_for(command: &Command) -> Option<KeyPress> {
+    let keys: Vec<Key> = ('a'..='z')
+        .chain('0'..='9')
+        .chain(":/?.[]-".chars())
+        .map(Key::Char)
+        .chain([
+            Key::Enter,
+            Key::Escape,
+            Key::Backspace,
+            Key::Tab,
+            Key::Space,
+            Key::Up,
+            Key::Down,
+            Key::Left,
+            Key::Right,
+        ])
+        .collect();
+    // Fewest modifiers first, so "y" wins over anything held down
+    let modifiers = [
+        (false, false, false),
+        (false, true, false),
+        (true, false, false),
+        (false, false, true),
+        (true, true, false),
+    ];
+    modifiers.iter().find_map(|&(ctrl, shift, alt)| {
+        keys.iter()
+            .map(|&key| KeyPress::new(key, ctrl, shift, alt))
+            .find(|&press| binding(press).as_ref() == Some(command))
+    })
+}
+
+/// Whether `keys` can be typed after `g` as a bookmark: lowercase letters and digits,
+/// not starting with the `g` of "gg"
+pub fn is_bookmark_sequence(keys: &str) -> bool {
+    !keys.is_empty()
+        && !keys.starts_with('g')
+        && keys
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+}
+
+/// Largest count prefix accepted; further digits are ignored
+const MAX_COUNT: usize = 99_999;
+
+/// Sequence state of the normal-mode keymap
+pub struct Keymap {
+    count: Option<usize>,
+    /// Keys typed after `g`, `None` outside a `g` sequence
+    after_g: Option<String>,
+    /// When the unfinished sequence last received a key
+    last_press: Option<Instant>,
+    timeout: Duration,
+    bookmark_keys: Vec<String>,
+    /// The continuations are on screen, so the sequence waits for its next key
+    held: bool,
+}
+
+impl Keymap {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            count: None,
+            after_g: None,
+            last_press: None,
+            timeout,
+            bookmark_keys: Vec::new(),
+            held: false,
+        }
+    }
+
+    /// Bookmark names reachable as `g<keys>`, so sequences know when they are complete
+    pub fn set_bookmark_keys(&mut self, keys: impl IntoIterator<Item = String>) {
+        self.bookmark_keys = keys.into_iter().collect();
+    }
+
+    /// Keys of the unfinished sequence, e.g. "12g", for the status bar
+    pub fn pending(&self) -> String {
+        let mut keys = self.count.map(|n| n.to_string()).unwrap_or_default();
+        if let Some(after_g) = &self.after_g {
+            keys.push('g');
+            keys.push_str(after_g);
+        }
+        keys
+    }
+
+    pub fn reset(&mut self) {
+        self.count = None;
+        self.after_g = None;
+        self.last_press = None;
+        self.held = false;
+    }
+
+    /// Whether the unfinished sequence has been idle longer than the timeout
+    fn is_stale(&self, now: Instant) -> bool {
+        !self.held
+            && self
+                .last_press
+                .is_some_and(|last| now.duration_since(last) > self.timeout)
+    }
+
+    /// Keys that can follow a `g` sequence idle for at least `delay`, each with the
+    /// command it completes (bookmarks extending what was typed, then the built-in
+    /// jumps). The sequence is then held until the next key, except a complete
+    /// bookmark that a longer one extends, which still fires on the timeout.
+    pub fn continuations(
+        &mut self,
+        now: Instant,
+        delay: Duration,
+    ) -> Option<Vec<(String, Command)>> {
+        let typed = self.after_g.as_deref()?;
+        let idle = self
+            .last_press
+            .is_some_and(|last| now.duration_since(last) >= delay);
+        if !self.held && !idle {
+            return None;
+        }
+        let mut keys: Vec<&String> = self
+            .bookmark_keys
+            .iter()
+            .filter(|key| key.starts_with(typed))
+            .collect();
+        keys.sort();
+        let mut continuations: Vec<(String, Command)> = keys
+            .into_iter()
+            .map(|key| (key.clone(), Command::Bookmark(key.clone())))
+            .collect();
+        if typed.is_empty() {
+            for (key, shifted, command) in g_builtins() {
+                let label = if shifted {
+                    key.to_ascii_uppercase().to_string()
+                } else {
+                    key.to_string()
+                };
+                continuations.push((label, command));
+            }
+        }
+        self.held = !self.bookmark_keys.iter().any(|key| key == typed);
+        Some(continuations)
+    }
+
+    /// Finish a timed-out sequence: a bookmark that a longer one extends fires now,
+    /// anything else is dropped
+    pub fn poll(&mut self, now: Instant) -> Option<(Command, Option<usize>)> {
+        if !self.is_stale(now) {
+            return None;
+        }
+        let typed = self.after_g.take().filter(|keys| !keys.is_empty());
+        self.reset();
+        typed
+            .filter(|keys| self.bookmark_keys.contains(keys))
+            .map(|keys| (Command::Bookmark(keys), None))
+    }
+
+    /// Resolve one press; a command comes back with its count prefix once a sequence completes
+    pub fn feed(&mut self, press: KeyPress, now: Instant) -> Option<(Command, Option<usize>)> {
+        // Callers poll every frame, so a sequence still waiting here is simply stale
+        if self.is_stale(now) {
+            self.reset();
+        }
+
+        if let Some(mut typed) = self.after_g.take() {
+            let count = self.count;
+            let builtin = g_builtins().into_iter().find(|(key, shifted, _)| {
+                press.key == Key::Char(*key)
+                    && if *shifted {
+                        press.is_shifted()
+                    } else {
+                        press.is_plain()
+                    }
+            });
+            match press.key {
+                _ if typed.is_empty() && builtin.is_some() => {
+                    self.reset();
+                    let command = builtin.map(|(_, _, command)| command)?;
+                    // Only "gg" takes a count, as the entry to jump to
+                    let count = if command == Command::Top { count } else { None };
+                    Some((command, count))
+                }
+                Key::Char(c) if c.is_ascii_alphanumeric() && press.is_plain() => {
+                    typed.push(c);
+                    let longer = self
+                        .bookmark_keys
+                        .iter()
+                        .any(|key| key.len() > typed.len() && key.starts_with(typed.as_str()));
+                    if longer {
+                        self.after_g = Some(typed);
+                        self.last_press = Some(now);
+                        None
+                    } else {
+                        // Complete, or matching nothing: either way the app reports on it
+                        self.reset();
+                        Some((Command::Bookmark(typed), None))
+                    }
+                }
+                _ => {
+                    self.reset();
+                    None
+                }
+            }
+        } else {
+            self.feed_fresh(press, now)
+        }
+    }
+
+    fn feed_fresh(&mut self, press: KeyPress, now: Instant) -> Option<(Command, Option<usize>)> {
+        match press.key {
+            Key::Char(digit @ '0'..='9')
+                if press.is_plain() && (digit != '0' || self.count.is_some()) =>
+            {
+                let value = self.count.unwrap_or(0) * 10 + digit as usize - '0' as usize;
+                self.count = Some(value.min(MAX_COUNT));
+                self.last_press = Some(now);
+                None
+            }
+            Key::Char('g') if press.is_plain() => {
+                self.after_g = Some(String::new());
+                self.last_press = Some(now);
+                None
+            }
+            _ => {
+                let count = self.count;
+                self.reset();
+                binding(press).map(|command| (command, count))
+            }
+        }
+    }
+}
+
+/// Single-press bindings
+fn binding(press: KeyPress) -> Option<Command> {
+    use Command::*;
+    let KeyPress {
+        key,
+        ctrl,
+        shift,
+        alt,
+    } = press;
+    let command = match (key, ctrl, shift, alt) {
+        (Key::Escape, ..) => Cancel,
+        (Key::Char('j') | Key::Down, false, false, false) => Down,
+        (Key::Char('k') | Key::Up, false, false, false) => Up,
+        (Key::Char('g'), false, true, false) => Bottom,
+        (Key::Char('d'), true, false, false) => HalfPageDown,
+        (Key::Char('u'), true, false, false) => HalfPageUp,
+        (Key::Char('f'), true, false, false) => PageDown,
+        (Key::Char('b'), true, false, false) => PageUp,
+        (Key::Char('h' | '-') | Key::Left | Key::Backspace, false, false, false) => Parent,
+        (Key::Char('l') | Key::Right, false, false, false) => EnterDir,
+        (Key::Left, false, false, true) => Back,
+        (Key::Right, false, false, true) => Forward,
+        (Key::Enter, false, false, false) => Open,
+        (Key::Enter, false, true, false) => OpenWith,
+        (Key::Enter, false, false, true) => Properties,
+        (Key::Char('e'), false, false, false) => OpenEntry,
+        (Key::Char('e'), false, true, false) => ExtractHint,
+        (Key::Char('o'), false, false, false) => OpenAtPreview,
+        (Key::Char('o'), false, true, false) => CycleSort,
+        (Key::Char('o'), false, false, true) => ToggleSortOrder,
+        (Key::Char('o'), true, false, false) => ToggleDirsFirst,
+        (Key::Char(':'), false, _, false) => Colon,
+        (Key::Char('/'), false, _, false) => Filter,
+        (Key::Char('?'), false, _, false) => Help,
+        (Key::Char('.'), false, _, false) => ToggleHidden,
+        (Key::Char('['), false, _, false) => PreviewTab { forward: false },
+        (Key::Char(']'), false, _, false) => PreviewTab { forward: true },
+        (Key::Char('f'), false, true, false) => ToggleFlatten,
+        (Key::Char('s'), false, false, false) => Stage,
+        (Key::Char('s'), false, true, false) => Search,
+        (Key::Char('t'), true, false, false) => NewTab,
+        (Key::Char('w'), true, false, false) => CloseTab,
+        (Key::Tab, true, false, false) => NextTab,
+        (Key::Tab, true, true, false) => PrevTab,
+        (Key::Char('m'), true, false, false) => MirrorToTab,
+        (Key::Char(digit @ '1'..='9'), false, false, true) => {
+            SwitchTab(digit as usize - '1' as usize)
+        }
+        (Key::Char('v'), false, false, false) => Visual,
+        (Key::Char('v'), false, true, false) => VisualAll,
+        (Key::Char('a'), true, false, false) => SelectAll,
+        (Key::Space, false, false, false) => ToggleSelect,
+        (Key::Char('r'), true, false, false) => InvertSelection,
+        (Key::Char('y'), false, false, false) => Copy,
+        (Key::Char('x'), false, false, false) => Cut,
+        (Key::Char('p'), false, false, false) => Paste,
+        (Key::Char('d'), false, false, false) => Delete,
+        (Key::Char('r'), false, false, false) => Rename,
+        (Key::Char('r'), false, true, false) => BulkRename,
+        _ => return None,
+    };
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keymap_resolves_each_press_once() {
+        let plain = |c| KeyPress::new(Key::Char(c), false, false, false);
+        let shifted = |c| KeyPress::new(Key::Char(c), false, true, false);
+        let mut keymap = Keymap::new(Duration::from_millis(500));
+        let now = Instant::now();
+
+        // E and Shift+E are distinct bindings, never both
+        assert_eq!(
+            keymap.feed(plain('e'), now),
+            Some((Command::OpenEntry, None))
+        );
+        assert_eq!(
+            keymap.feed(shifted('e'), now),
+            Some((Command::ExtractHint, None))
+        );
+        // Punctuation matches whatever modifier its layout needs
+        assert_eq!(keymap.feed(shifted(':'), now), Some((Command::Colon, None)));
+
+        // Counts and the g prefix
+        assert_eq!(keymap.feed(plain('1'), now), None);
+        assert_eq!(keymap.feed(plain('0'), now), None);
+        assert_eq!(keymap.pending(), "10");
+        assert_eq!(
+            keymap.feed(plain('j'), now),
+            Some((Command::Down, Some(10)))
+        );
+        assert_eq!(keymap.feed(plain('g'), now), None);
+        assert_eq!(keymap.feed(plain('g'), now), Some((Command::Top, None)));
+        assert_eq!(keymap.feed(plain('g'), now), None);
+        assert_eq!(
+            keymap.feed(plain('d'), now),
+            Some((Command::Bookmark("d".into()), None))
+        );
+        assert_eq!(
+            keymap.feed(shifted('g'), now),
+            Some((Command::Bottom, None))
+        );
+
+        // An idle prefix expires, so the next key acts on its own
+        assert_eq!(keymap.feed(plain('g'), now), None);
+        let later = now + Duration::from_secs(1);
+        assert_eq!(
+            keymap.feed(plain('d'), later),
+            Some((Command::Delete, None))
+        );
+        assert!(keymap.pending().is_empty());
+    }
+
+    #[test]
+    fn keymap_waits_for_longer_bookmark_sequences() {
+        let plain = |c| KeyPress::new(Key::Char(c), false, false, false);
+        let mut keymap = Keymap::new(Duration::from_millis(500));
+        keymap.set_bookmark_keys(["d", "dd", "p"].map(String::from));
+        let now = Instant::now();
+
+        // "gdd" completes without waiting
+        assert_eq!(keymap.feed(plain('g'), now), None);
+        assert_eq!(keymap.feed(plain('d'), now), None);
+        assert_eq!(keymap.pending(), "gd");
+        assert_eq!(
+            keymap.feed(plain('d'), now),
+            Some((Command::Bookmark("dd".into()), None))
+        );
+
+        // "gd" alone fires once the sequence times out
+        keymap.feed(plain('g'), now);
+        keymap.feed(plain('d'), now);
+        assert_eq!(keymap.poll(now), None);
+        let later = now + Duration::from_secs(1);
+        assert_eq!(
+            keymap.poll(later),
+            Some((Command::Bookmark("d".into()), None))
+        );
+        assert!(keymap.pending().is_empty());
+
+        // Unambiguous and unknown keys resolve at once, the app reports missing bookmarks
+        keymap.feed(plain('g'), now);
+        assert_eq!(
+            keymap.feed(plain('p'), now),
+            Some((Command::Bookmark("p".into()), None))
+        );
+        keymap.feed(plain('g'), now);
+        assert_eq!(
+            keymap.feed(plain('z'), now),
+            Some((Command::Bookmark("z".into()), None))
+        );
+        // Shifted letters after "g" are the root jumps, not bookmarks
+        let shifted = |c| KeyPress::new(Key::Char(c), false, true, false);
+        keymap.feed(plain('g'), now);
+        assert_eq!(
+            keymap.feed(shifted('p'), now),
+            Some((Command::ProjectRoot, None))
+        );
+        keymap.feed(plain('g'), now);
+        assert_eq!(
+            keymap.feed(shifted('m'), now),
+            Some((Command::MountRoot, None))
+        );
+        keymap.feed(plain('g'), now);
+        assert_eq!(
+            keymap.feed(shifted('c'), now),
+            Some((Command::Computer, None))
+        );
+        // A bare "g" that times out is dropped
+        keymap.feed(plain('g'), now);
+        assert_eq!(keymap.poll(later), None);
+    }
+
+    #[test]
+    fn bookmark_sequences_avoid_the_builtin_g_keys() {
+        assert!(is_bookmark_sequence("d"));
+        assert!(is_bookmark_sequence("dd2"));
+        assert!(!is_bookmark_sequence(""));
+        assert!(!is_bookmark_sequence("gd"));
+        assert!(!is_bookmark_sequence("D"));
+        assert!(!is_bookmark_sequence("d-"));
+    }
+
+    #[test]
+    fn which_key_lists_continuations_and_holds_the_prefix() {
+        let plain = |c| KeyPress::new(Key::Char(c), false, false, false);
+        let delay = Duration::from_millis(300);
+        let mut keymap = Keymap::new(Duration::from_millis(500));
+        keymap.set_bookmark_keys(["d", "dd", "p"].map(String::from));
+        let now = Instant::now();
+
+        // Nothing until the prefix has waited a moment
+        keymap.feed(plain('g'), now);
+        assert_eq!(keymap.continuations(now, delay), None);
+        let paused = now + delay;
+        let listed = keymap.continuations(paused, delay).unwrap();
+        let keys: Vec<&str> = listed.iter().map(|(keys, _)| keys.as_str()).collect();
+        assert_eq!(keys, ["d", "dd", "p", "g", "P", "M", "C"]);
+        assert_eq!(listed[3].1, Command::Top);
+
+        // Listed, the prefix outlives the timeout and still completes
+        let much_later = now + Duration::from_secs(5);
+        assert_eq!(keymap.poll(much_later), None);
+        assert_eq!(
+            keymap.feed(plain('p'), much_later),
+            Some((Command::Bookmark("p".into()), None))
+        );
+
+        // A complete bookmark that a longer one extends still fires on the timeout
+        keymap.feed(plain('g'), now);
+        keymap.feed(plain('d'), now);
+        let listed = keymap.continuations(paused, delay).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(
+            keymap.poll(much_later),
+            Some((Command::Bookmark("d".into()), None))
+        );
+
+        // Key labels come from the bindings themselves
+        let label = |command| key_for(&command).map(press_label);
+        assert_eq!(label(Command::Copy).as_deref(), Some("y"));
+        assert_eq!(label(Command::BulkRename).as_deref(), Some("Shift+R"));
+        assert_eq!(label(Command::InvertSelection).as_deref(), Some("Ctrl+R"));
+        assert_eq!(label(Command::ToggleSelect).as_deref(), Some("Space"));
+    }
+}
@@ -0,0 +1,334 @@
+// Browser core - navigation, filtering and clipboard state without any egui
+//
+// The UI turns key presses and clicks into `Action`s, `Browser::dispatch` applies
+// them to the state and returns the `Effect`s (reloads, messages, mode changes)
+// for the app layer to carry out. File operations that mutate the disk live in
+// `ops` as plain functions so they can be tested against a temp directory.
+
+pub mod keymap;
+pub mod ops;
+#[cfg(test)]
+mod tests;
+
+use crate::entry::{compare_names, FileEntry};
+use crate::io::fuzzy_match;
+use crate::state::{
+    ClipboardOp, EntryState, NavigationState, SelectionState, SortBy, SortOptions, SortOrder,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+pub use ops::{
+    apply_renames, destination_warning, mirror_dir, plan_bulk_rename, plan_link_item,
+    plan_paste_item, plan_renames, run_job_item,
+};
+
+/// Something the user asked the browser to do
+#[derive(Clone, Debug)]
+pub enum Action {
+    /// Enter a directory, or open a file
+    Open(PathBuf),
+    Up,
+    Back,
+    Forward,
+    /// Narrow the listing to entries fuzzy-matching `query` (empty shows all), then sort
+    Filter {
+        query: String,
+        sort: SortOptions,
+    },
+    /// Put the multi-selection, or the entry under the cursor, on the clipboard
+    Yank(ClipboardOp),
+}
+
+/// Work an action leaves for the app layer
+#[derive(Clone, Debug, PartialEq)]
+pub enum Effect {
+    /// Reload the listing of the current directory
+    Refresh,
+    /// Hand a file to its application
+    OpenFile(PathBuf),
+    /// Return to normal mode and clear the input line
+    ExitMode,
+    Info(String),
+    Error(String),
+}
+
+/// State of the active tab plus the shared clipboard
+pub struct Browser {
+    pub navigation: NavigationState,
+    pub selection: SelectionState,
+    pub entries: EntryState,
+    // Shared across tabs
+    pub clipboard: HashSet<PathBuf>,
+    pub clipboard_op: Option<ClipboardOp>,
+}
+
+impl Browser {
+    pub fn new(start_path: PathBuf) -> Self {
+        Self {
+            navigation: NavigationState::new(start_path),
+            selection: SelectionState::new(),
+            entries: EntryState::new(),
+            clipboard: HashSet::new(),
+            clipboard_op: None,
+        }
+    }
+
+    pub fn dispatch(&mut self, action: Action) -> Vec<Effect> {
+        match action {
+            Action::Open(path) => self.open(path),
+            Action::Up => self.up(),
+            Action::Back => self.back(),
+            Action::Forward => self.forward(),
+            Action::Filter { query, sort } => {
+                self.filter(&query, sort);
+                Vec::new()
+            }
+            Action::Yank(op) => self.yank(op),
+        }
+    }
+
+    /// Entry under the cursor
+    pub fn selected_entry(&self) -> Option<&FileEntry> {
+        self.selection
+            .selected_index
+            .and_then(|idx| self.entries.visible_entries.get(idx))
+    }
+
+    fn open(&mut self, path: PathBuf) -> Vec<Effect> {
+        if !path.is_dir() {
+            return vec![Effect::OpenFile(path)];
+        }
+        self.remember_selection();
+        self.navigation.current_path = path.clone();
+
+        if self.navigation.history_index < self.navigation.history.len() - 1 {
+            self.navigation
+                .history
+                .truncate(self.navigation.history_index + 1);
+        }
+        self.navigation.history.push(path);
+        self.navigation.history_index = self.navigation.history.len() - 1;
+
+        self.arrive()
+    }
+
+    fn up(&mut self) -> Vec<Effect> {
+        let Some(parent) = self.navigation.current_path.parent() else {
+            return Vec::new();
+        };
+        let parent = parent.to_path_buf();
+        // When navigating to parent, select the child directory we came from
+        self.navigation.pending_selection_path = Some(self.navigation.current_path.clone());
+        self.open(parent)
+    }
+
+    fn back(&mut self) -> Vec<Effect> {
+        if self.navigation.history_index == 0 {
+            return Vec::new();
+        }
+        self.remember_selection();
+
+        let mut idx = self.navigation.history_index;
+        while idx > 0 {
+            idx -= 1;
+            let target = self.navigation.history[idx].clone();
+            if target.is_dir() {
+                self.navigation.history_index = idx;
+                self.navigation.current_path = target;
+                return self.arrive();
+            }
+            self.navigation.history.remove(idx);
+            self.navigation.history_index -= 1;
+        }
+
+        vec![Effect::Error("Previous directory no longer exists".into())]
+    }
+
+    fn forward(&mut self) -> Vec<Effect> {
+        if self.navigation.history_index >= self.navigation.history.len() - 1 {
+            return Vec::new();
+        }
+        self.remember_selection();
+
+        let idx = self.navigation.history_index + 1;
+        // idx doesn't change - when we remove at idx, the next element shifts down to idx
+        while idx < self.navigation.history.len() {
+            let target = self.navigation.history[idx].clone();
+            if target.is_dir() {
+                self.navigation.history_index = idx;
+                self.navigation.current_path = target;
+                return self.arrive();
+            }
+            // Entries before the current position don't shift, so history_index stays
+            self.navigation.history.remove(idx);
+        }
+
+        vec![Effect::Error("Next directory no longer exists".into())]
+    }
+
+    /// Save the cursor of the directory we are leaving
+    fn remember_selection(&mut self) {
+        if let Some(idx) = self.selection.selected_index {
+            self.selection
+                .directory_selections
+                .insert(self.navigation.current_path.clone(), idx);
+        }
+    }
+
+    fn arrive(&mut self) -> Vec<Effect> {
+        self.selection.multi_selection.clear();
+        // Restore saved selection for this directory, or default to 0
+        self.selection.selected_index = self
+            .selection
+            .directory_selections
+            .get(&self.navigation.current_path)
+            .copied()
+            .or(Some(0));
+        // Re-enable autoscroll when navigating to ensure view centers on selection
+        self.selection.disable_autoscroll = false;
+        vec![Effect::ExitMode, Effect::Refresh]
+    }
+
+    fn filter(&mut self, query: &str, sort: SortOptions) {
+        // The old listing anchors the cursor to a path while the new one is built
+        let previous = self
+            .selection
+            .selected_index
+            .map(|idx| (idx, std::mem::take(&mut self.entries.visible_entries)));
+
+        self.entries.visible_entries = if query.is_empty() {
+            self.entries.all_entries.clone()
+        } else {
+            self.entries
+                .all_entries
+                .iter()
+                .filter(|e| fuzzy_match(&e.name, query))
+                .cloned()
+                .collect()
+        };
+        sort_entries(&mut self.entries.visible_entries, sort);
+
+        if let Some((idx, previous)) = previous {
+            if let Some(new_idx) = self.resolve_anchor(&previous, idx) {
+                self.selection.selected_index = Some(new_idx);
+            }
+        }
+
+        if self.entries.visible_entries.is_empty() {
+            self.selection.selected_index = None;
+        } else if self.selection.selected_index.is_none() {
+            self.selection.selected_index = Some(0);
+        }
+        self.clamp_selection();
+    }
+
+    /// Where the entry at `idx` in `previous` sits in the rebuilt listing; when it is
+    /// gone, the nearest entry that followed it (or else preceded it) and survived, so
+    /// the cursor stays put instead of landing on whatever shifted into its slot
+    fn resolve_anchor(&self, previous: &[FileEntry], idx: usize) -> Option<usize> {
+        let anchor = previous.get(idx)?;
+        let position = |path: &PathBuf| {
+            self.entries
+                .visible_entries
+                .iter()
+                .position(|e| &e.path == path)
+        };
+        if let Some(found) = position(&anchor.path) {
+            return Some(found);
+        }
+        let positions: HashMap<&PathBuf, usize> = self
+            .entries
+            .visible_entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (&e.path, i))
+            .collect();
+        previous[idx + 1..]
+            .iter()
+            .chain(previous[..idx].iter().rev())
+            .find_map(|e| positions.get(&e.path).copied())
+    }
+
+    /// Apply a pending selection and clamp the selected index once a listing has loaded
+    pub fn restore_selection_after_load(&mut self) {
+        if let Some(pending_path) = self.navigation.pending_selection_path.take() {
+            if let Some(idx) = self
+                .entries
+                .visible_entries
+                .iter()
+                .position(|e| e.path == pending_path)
+            {
+                self.selection.selected_index = Some(idx);
+            }
+        }
+        self.clamp_selection();
+    }
+
+    /// Keep the cursor inside the visible listing
+    pub fn clamp_selection(&mut self) {
+        if let Some(idx) = self.selection.selected_index {
+            if self.entries.visible_entries.is_empty() {
+                self.selection.selected_index = None;
+            } else if idx >= self.entries.visible_entries.len() {
+                self.selection.selected_index = Some(self.entries.visible_entries.len() - 1);
+            }
+        }
+    }
+
+    fn yank(&mut self, op: ClipboardOp) -> Vec<Effect> {
+        let mut effects = Vec::new();
+        self.clipboard.clear();
+        self.clipboard_op = Some(op);
+
+        if !self.selection.multi_selection.is_empty() {
+            self.clipboard = std::mem::take(&mut self.selection.multi_selection);
+            effects.push(Effect::ExitMode);
+        } else if let Some(path) = self.selected_entry().map(|e| e.path.clone()) {
+            self.clipboard.insert(path);
+        }
+
+        let op_text = match op {
+            ClipboardOp::Copy => "Yanked",
+            ClipboardOp::Cut => "Cut",
+        };
+        effects.push(Effect::Info(format!(
+            "{} {} files",
+            op_text,
+            self.clipboard.len()
+        )));
+        effects
+    }
+}
+
+/// Sort a listing by `sort`, keeping directories and files in separate groups
+pub fn sort_entries(entries: &mut Vec<FileEntry>, sort: SortOptions) {
+    let (mut dirs, mut files): (Vec<_>, Vec<_>) = entries.drain(..).partition(|e| e.is_dir);
+
+    let sort_fn = |a: &FileEntry, b: &FileEntry| -> std::cmp::Ordering {
+        let cmp = match sort.sort_by {
+            SortBy::Name => compare_names(&a.name, &b.name),
+            SortBy::Size => a.size.cmp(&b.size),
+            SortBy::Modified => a.modified.cmp(&b.modified),
+            SortBy::Created => a
+                .created
+                .unwrap_or(a.modified)
+                .cmp(&b.created.unwrap_or(b.modified)),
+            SortBy::Extension => a.extension.cmp(&b.extension),
+        };
+        match sort.sort_order {
+            SortOrder::Ascending => cmp,
+            SortOrder::Descending => cmp.reverse(),
+        }
+    };
+    dirs.sort_by(sort_fn);
+    files.sort_by(sort_fn);
+
+    if sort.dirs_first {
+        entries.extend(dirs);
+        entries.extend(files);
+    } else {
+        entries.extend(files);
+        entries.extend(dirs);
+    }
+}
@@ -0,0 +1,376 @@
+// File operations behind paste and rename, free of UI state
+use crate::io::fileops::{copy_for_move, copy_recursive, unique_destination, Progress};
+use crate::io::split;
+use crate::state::{ClipboardOp, ConflictPolicy, JobKind, PlannedChange};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where a pasted item goes once conflicts are resolved
+enum PasteTarget {
+    /// Skipped on purpose: moved onto itself, or the name is taken under Skip/Ask
+    Skip(&'static str),
+    /// A free name in the destination
+    Free(PathBuf),
+    /// The existing item of that name is trashed first
+    Replace(PathBuf),
+}
+
+fn paste_target(
+    path: &Path,
+    op: ClipboardOp,
+    policy: ConflictPolicy,
+    dest_dir: &Path,
+) -> Result<PasteTarget, String> {
+    if !path.exists() {
+        return Err("source no longer exists".into());
+    }
+    let Some(name) = path.file_name() else {
+        return Ok(PasteTarget::Skip("no file name"));
+    };
+    let dest = dest_dir.join(name);
+    let target = if dest == path {
+        // Moving onto itself is a no-op; copying onto itself duplicates
+        if op == ClipboardOp::Cut {
+            return Ok(PasteTarget::Skip("already there"));
+        }
+        PasteTarget::Free(unique_destination(&dest))
+    } else if fs::symlink_metadata(&dest).is_err() {
+        PasteTarget::Free(dest)
+    } else {
+        match policy {
+            ConflictPolicy::Rename => PasteTarget::Free(unique_destination(&dest)),
+            ConflictPolicy::Overwrite => PasteTarget::Replace(dest),
+            ConflictPolicy::Skip | ConflictPolicy::Ask => {
+                return Ok(PasteTarget::Skip("name taken"))
+            }
+        }
+    };
+    if op == ClipboardOp::Copy && path.is_dir() && dest_dir.starts_with(path) {
+        return Err("cannot copy a folder into itself".into());
+    }
+    Ok(target)
+}
+
+/// Perform one item of a batch job; `Ok(false)` means it was skipped on purpose
+pub fn run_job_item(kind: &JobKind, path: &Path, progress: Progress) -> Result<bool, String> {
+    match kind {
+        JobKind::Trash => trash::delete(path).map(|_| true).map_err(|e| e.to_string()),
+        JobKind::Link { dest_dir } => link_item(path, dest_dir),
+        JobKind::Paste {
+            op,
+            policy,
+            dest_dir,
+        } => paste_item(path, *op, *policy, dest_dir, progress),
+        JobKind::Split { source, chunk_size } => {
+            split::write_split_output(source, *chunk_size, path, progress)
+        }
+        JobKind::Join { dest, parts } => split::join_part(parts, path, dest, progress),
+        JobKind::Mirror { from, to } => {
+            let dest_dir = mirror_dir(path, from, to)?;
+            fs::create_dir_all(&dest_dir).map_err(|e| e.to_string())?;
+            paste_item(
+                path,
+                ClipboardOp::Copy,
+                ConflictPolicy::Overwrite,
+                &dest_dir,
+                progress,
+            )
+        }
+    }
+}
+
+/// Folder below `to` that takes `path`, which lies below `from`
+pub fn mirror_dir(path: &Path, from: &Path, to: &Path) -> Result<PathBuf, String> {
+    let relative = path
+        .strip_prefix(from)
+        .map_err(|_| format!("not below {}", from.display()))?;
+    Ok(to.join(relative.parent().unwrap_or(Path::new(""))))
+}
+
+/// Paste one clipboard item into `dest_dir`; `Ok(false)` means it was skipped on purpose
+pub fn paste_item(
+    path: &Path,
+    op: ClipboardOp,
+    policy: ConflictPolicy,
+    dest_dir: &Path,
+    progress: Progress,
+) -> Result<bool, String> {
+    let dest = match paste_target(path, op, policy, dest_dir)? {
+        PasteTarget::Skip(_) => return Ok(false),
+        PasteTarget::Free(dest) => dest,
+        PasteTarget::Replace(dest) => {
+            trash::delete(&dest).map_err(|e| format!("could not replace existing item: {}", e))?;
+            dest
+        }
+    };
+    let result = match op {
+        ClipboardOp::Cut => move_item(path, &dest, progress),
+        ClipboardOp::Copy => copy_recursive(path, &dest, progress)
+            .map(|_| ())
+            .inspect_err(|_| remove_partial(&dest)),
+    };
+    result.map(|_| true).map_err(|e| e.to_string())
+}
+
+/// Rename `path` to `dest`; across filesystems (EXDEV), where a rename is impossible,
+/// copy it with its metadata, verify the copy and only then delete the original
+fn move_item(path: &Path, dest: &Path, progress: Progress) -> io::Result<()> {
+    match fs::rename(path, dest) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            let is_dir = fs::symlink_metadata(path)?.is_dir();
+            if let Err(e) = copy_for_move(path, dest, progress) {
+                if e.kind() != io::ErrorKind::AlreadyExists {
+                    // The original is untouched
+                    remove_partial(dest);
+                }
+                return Err(e);
+            }
+            if is_dir {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            }
+        }
+        result => result,
+    }
+}
+
+/// Leave no half-written copy behind after a failed or cancelled copy
+fn remove_partial(dest: &Path) {
+    let _ = match fs::symlink_metadata(dest) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(dest),
+        Ok(_) => fs::remove_file(dest),
+        Err(_) => Ok(()),
+    };
+}
+
+/// What `paste_item` would do with `path`, without touching the disk
+pub fn plan_paste_item(
+    path: &Path,
+    op: ClipboardOp,
+    policy: ConflictPolicy,
+    dest_dir: &Path,
+) -> PlannedChange {
+    let action = if op == ClipboardOp::Copy {
+        "copy"
+    } else {
+        "move"
+    };
+    let source = path.to_path_buf();
+    match paste_target(path, op, policy, dest_dir) {
+        Err(e) => PlannedChange::failure(source, e),
+        Ok(PasteTarget::Skip(reason)) => PlannedChange::new("skip", source, None).with_note(reason),
+        Ok(PasteTarget::Free(dest)) => {
+            let numbered = dest.file_name() != path.file_name();
+            let crosses = op == ClipboardOp::Cut
+                && crate::platform::same_filesystem(path, dest_dir) == Some(false);
+            let change = PlannedChange::new(action, source, Some(dest));
+            if numbered {
+                change.with_note("name taken, numbered")
+            } else if crosses {
+                change.with_note("other filesystem: copied, verified, then deleted")
+            } else {
+                change
+            }
+        }
+        Ok(PasteTarget::Replace(dest)) => PlannedChange::new(action, source, Some(dest)).with_note(
+            format!("existing item goes to the {}", crate::platform::TRASH_NAME),
+        ),
+    }
+}
+
+/// Warning for a paste into a read-only filesystem, or a move that has to copy and
+/// delete because it leaves the filesystem its items are on
+///
+/// The destination is probed once and each distinct source folder once, as these
+/// calls can be slow on network mounts.
+pub fn destination_warning(op: ClipboardOp, dest_dir: &Path, items: &[PathBuf]) -> Option<String> {
+    let info = crate::platform::filesystem_info(dest_dir)?;
+    let fs_type = info.fs_type.as_deref().unwrap_or("filesystem");
+    if info.read_only {
+        return Some(format!(
+            "{} is on a read-only {} mounted at {}",
+            dest_dir.display(),
+            fs_type,
+            info.mount_point.display()
+        ));
+    }
+    if op != ClipboardOp::Cut {
+        return None;
+    }
+    let mut crosses: HashMap<&Path, bool> = HashMap::new();
+    let crossing = items
+        .iter()
+        .filter(|item| {
+            let folder = item.parent().unwrap_or(item);
+            *crosses.entry(folder).or_insert_with(|| {
+                crate::platform::same_filesystem(folder, dest_dir) == Some(false)
+            })
+        })
+        .count();
+    (crossing > 0).then(|| {
+        format!(
+            "Moving {} item(s) onto another filesystem ({} at {}): each is copied, verified, then deleted",
+            crossing,
+            fs_type,
+            info.mount_point.display()
+        )
+    })
+}
+
+/// Name for a link to `path` in `dest_dir`, numbered when taken
+fn link_target(path: &Path, dest_dir: &Path) -> Result<Option<PathBuf>, String> {
+    if !path.exists() {
+        return Err("source no longer exists".into());
+    }
+    let Some(name) = path.file_name() else {
+        return Ok(None);
+    };
+    let dest = dest_dir.join(name);
+    if fs::symlink_metadata(&dest).is_ok() {
+        return Ok(Some(unique_destination(&dest)));
+    }
+    Ok(Some(dest))
+}
+
+/// Create a symbolic link to `path` in `dest_dir`, numbering the name when taken
+pub fn link_item(path: &Path, dest_dir: &Path) -> Result<bool, String> {
+    let Some(dest) = link_target(path, dest_dir)? else {
+        return Ok(false);
+    };
+    #[cfg(unix)]
+    let result = std::os::unix::fs::symlink(path, &dest);
+    #[cfg(windows)]
+    let result = if path.is_dir() {
+        std::os::windows::fs::symlink_dir(path, &dest)
+    } else {
+        std::os::windows::fs::symlink_file(path, &dest)
+    };
+    result.map(|_| true).map_err(|e| e.to_string())
+}
+
+/// What `link_item` would do with `path`, without touching the disk
+pub fn plan_link_item(path: &Path, dest_dir: &Path) -> PlannedChange {
+    let source = path.to_path_buf();
+    match link_target(path, dest_dir) {
+        Err(e) => PlannedChange::failure(source, e),
+        Ok(None) => PlannedChange::new("skip", source, None).with_note("no file name"),
+        Ok(Some(dest)) => PlannedChange::new("link", source, Some(dest)),
+    }
+}
+
+/// Pair each original path with its new name from a bulk rename buffer
+///
+/// Fails on a line count mismatch, an empty name or a name used twice.
+pub fn plan_bulk_rename<'a>(
+    original_paths: &[PathBuf],
+    buffer: &'a str,
+) -> Result<Vec<(PathBuf, &'a str)>, String> {
+    let new_names: Vec<&str> = buffer.lines().map(str::trim).collect();
+
+    if new_names.len() != original_paths.len() {
+        return Err(format!(
+            "Line count mismatch: {} files but {} names",
+            original_paths.len(),
+            new_names.len()
+        ));
+    }
+    if new_names.iter().any(|n| n.is_empty()) {
+        return Err("Empty filename not allowed".into());
+    }
+    let mut seen = std::collections::HashSet::new();
+    for name in &new_names {
+        if !seen.insert(*name) {
+            return Err(format!("Duplicate filename: {}", name));
+        }
+    }
+
+    Ok(original_paths.iter().cloned().zip(new_names).collect())
+}
+
+/// New path for one bulk rename item; `Ok(None)` when it keeps its name
+fn rename_target(
+    old_path: &Path,
+    new_name: &str,
+    auto_suffix: bool,
+) -> Result<Option<PathBuf>, String> {
+    let Some(parent) = old_path.parent() else {
+        return Ok(None);
+    };
+    if old_path.file_name().and_then(|n| n.to_str()) == Some(new_name) {
+        return Ok(None);
+    }
+    let new_path = parent.join(new_name);
+    // Check if target already exists
+    if new_path.exists() && new_path != *old_path {
+        if !auto_suffix {
+            return Err(format!("{}: target already exists", new_name));
+        }
+        return Ok(Some(unique_destination(&new_path)));
+    }
+    Ok(Some(new_path))
+}
+
+/// Rename each path to its new name within the same directory
+///
+/// Returns how many entries now carry their new name (unchanged ones included)
+/// and one message per failure. Taken names fail unless `auto_suffix` is set;
+/// `renamed` gets the old and new path of each rename made.
+pub fn apply_renames(
+    plan: &[(PathBuf, &str)],
+    auto_suffix: bool,
+    mut renamed: impl FnMut(&Path, &Path),
+) -> (usize, Vec<String>) {
+    let mut success_count = 0;
+    let mut errors = Vec::new();
+
+    for (old_path, new_name) in plan {
+        let new_path = match rename_target(old_path, new_name, auto_suffix) {
+            Ok(Some(new_path)) => new_path,
+            Ok(None) => {
+                success_count += 1;
+                continue;
+            }
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        match fs::rename(old_path, &new_path) {
+            Ok(()) => {
+                success_count += 1;
+                renamed(old_path, &new_path);
+            }
+            Err(e) => errors.push(format!("{}: {}", new_name, e)),
+        }
+    }
+
+    (success_count, errors)
+}
+
+/// What `apply_renames` would do, without touching the disk
+///
+/// Each item is checked against the disk as it is now, so a name freed by an
+/// earlier rename in the same batch still counts as taken.
+pub fn plan_renames(plan: &[(PathBuf, &str)], auto_suffix: bool) -> Vec<PlannedChange> {
+    plan.iter()
+        .map(|(old_path, new_name)| {
+            let source = old_path.clone();
+            match rename_target(old_path, new_name, auto_suffix) {
+                Ok(None) => PlannedChange::new("keep", source, None),
+                Ok(Some(new_path)) => {
+                    let numbered = new_path.file_name().and_then(|n| n.to_str()) != Some(new_name);
+                    let change = PlannedChange::new("rename", source, Some(new_path));
+                    if numbered {
+                        change.with_note("name taken, numbered")
+                    } else {
+                        change
+                    }
+                }
+                Err(e) => PlannedChange::failure(source, e),
+            }
+        })
+        .collect()
+}
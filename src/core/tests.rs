@@ -0,0 +1,662 @@
+use super::ops::{link_item, paste_item};
+use super::*;
+use crate::io::directory::read_directory;
+use crate::state::ConflictPolicy;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Temp tree: `a.txt`, `b.md`, `notes.txt`, `docs/`, `docs/inner/`, `src/`
+fn fixture() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+    fs::write(dir.path().join("b.md"), "bravo").unwrap();
+    fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+    fs::create_dir_all(dir.path().join("docs/inner")).unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    dir
+}
+
+/// Load the current directory the way the app does once the worker replies
+fn load(browser: &mut Browser) {
+    browser.entries.all_entries = read_directory(&browser.navigation.current_path, false).unwrap();
+    browser.dispatch(Action::Filter {
+        query: String::new(),
+        sort: SortOptions::default(),
+    });
+    browser.restore_selection_after_load();
+}
+
+fn browser_at(path: &Path) -> Browser {
+    let mut browser = Browser::new(path.to_path_buf());
+    load(&mut browser);
+    browser
+}
+
+fn visible_names(browser: &Browser) -> Vec<&str> {
+    browser
+        .entries
+        .visible_entries
+        .iter()
+        .map(|e| e.name.as_str())
+        .collect()
+}
+
+fn select(browser: &mut Browser, name: &str) {
+    browser.selection.selected_index = browser
+        .entries
+        .visible_entries
+        .iter()
+        .position(|e| e.name == name);
+}
+
+// --- Navigation ---
+
+#[test]
+fn open_directory_pushes_history_and_refreshes() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+
+    let effects = browser.dispatch(Action::Open(dir.path().join("docs")));
+
+    assert_eq!(effects, vec![Effect::ExitMode, Effect::Refresh]);
+    assert_eq!(browser.navigation.current_path, dir.path().join("docs"));
+    assert_eq!(browser.navigation.history.len(), 2);
+    assert_eq!(browser.navigation.history_index, 1);
+    assert_eq!(browser.selection.selected_index, Some(0));
+}
+
+#[test]
+fn open_file_is_left_to_the_app() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+    let file = dir.path().join("a.txt");
+
+    let effects = browser.dispatch(Action::Open(file.clone()));
+
+    assert_eq!(effects, vec![Effect::OpenFile(file)]);
+    assert_eq!(browser.navigation.current_path, dir.path());
+    assert_eq!(browser.navigation.history.len(), 1);
+}
+
+#[test]
+fn back_and_forward_walk_history() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+    browser.dispatch(Action::Open(dir.path().join("docs")));
+    browser.dispatch(Action::Open(dir.path().join("docs/inner")));
+
+    browser.dispatch(Action::Back);
+    assert_eq!(browser.navigation.current_path, dir.path().join("docs"));
+    browser.dispatch(Action::Back);
+    assert_eq!(browser.navigation.current_path, dir.path());
+    assert!(browser.dispatch(Action::Back).is_empty());
+
+    browser.dispatch(Action::Forward);
+    assert_eq!(browser.navigation.current_path, dir.path().join("docs"));
+
+    // A new location drops the forward history
+    browser.dispatch(Action::Open(dir.path().join("src")));
+    assert_eq!(browser.navigation.history.len(), 3);
+    assert!(browser.dispatch(Action::Forward).is_empty());
+}
+
+#[test]
+fn back_skips_deleted_directories() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+    browser.dispatch(Action::Open(dir.path().join("src")));
+    browser.dispatch(Action::Open(dir.path().join("docs")));
+    fs::remove_dir(dir.path().join("src")).unwrap();
+
+    browser.dispatch(Action::Back);
+
+    assert_eq!(browser.navigation.current_path, dir.path());
+    assert_eq!(browser.navigation.history.len(), 2);
+    assert_eq!(browser.navigation.history_index, 0);
+}
+
+#[test]
+fn forward_reports_a_missing_directory() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+    browser.dispatch(Action::Open(dir.path().join("src")));
+    browser.dispatch(Action::Back);
+    fs::remove_dir(dir.path().join("src")).unwrap();
+
+    let effects = browser.dispatch(Action::Forward);
+
+    assert_eq!(
+        effects,
+        vec![Effect::Error("Next directory no longer exists".into())]
+    );
+    assert_eq!(browser.navigation.current_path, dir.path());
+}
+
+#[test]
+fn up_selects_the_directory_we_came_from() {
+    let dir = fixture();
+    let mut browser = Browser::new(dir.path().join("src"));
+
+    browser.dispatch(Action::Up);
+    load(&mut browser);
+
+    assert_eq!(browser.navigation.current_path, dir.path());
+    assert_eq!(
+        browser.selected_entry().map(|e| e.path.clone()),
+        Some(dir.path().join("src"))
+    );
+}
+
+#[test]
+fn cursor_is_restored_when_returning_to_a_directory() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+    select(&mut browser, "notes.txt");
+
+    browser.dispatch(Action::Open(dir.path().join("docs")));
+    load(&mut browser);
+    browser.dispatch(Action::Back);
+    load(&mut browser);
+
+    assert_eq!(
+        browser.selected_entry().map(|e| e.name.as_str()),
+        Some("notes.txt")
+    );
+}
+
+// --- Filtering and sorting ---
+
+#[test]
+fn listing_puts_directories_first_sorted_by_name() {
+    let dir = fixture();
+    let browser = browser_at(dir.path());
+
+    assert_eq!(
+        visible_names(&browser),
+        ["docs", "src", "a.txt", "b.md", "notes.txt"]
+    );
+}
+
+#[test]
+fn sort_options_are_applied() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+
+    browser.dispatch(Action::Filter {
+        query: String::new(),
+        sort: SortOptions {
+            sort_by: SortBy::Extension,
+            sort_order: SortOrder::Descending,
+            dirs_first: false,
+        },
+    });
+
+    assert_eq!(
+        &visible_names(&browser)[..3],
+        ["a.txt", "notes.txt", "b.md"]
+    );
+}
+
+#[test]
+fn filter_narrows_listing_and_keeps_cursor() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+    select(&mut browser, "notes.txt");
+
+    browser.dispatch(Action::Filter {
+        query: "txt".into(),
+        sort: SortOptions::default(),
+    });
+
+    assert_eq!(visible_names(&browser), ["a.txt", "notes.txt"]);
+    assert_eq!(
+        browser.selected_entry().map(|e| e.name.as_str()),
+        Some("notes.txt")
+    );
+
+    browser.dispatch(Action::Filter {
+        query: String::new(),
+        sort: SortOptions::default(),
+    });
+    assert_eq!(browser.entries.visible_entries.len(), 5);
+    assert_eq!(
+        browser.selected_entry().map(|e| e.name.as_str()),
+        Some("notes.txt")
+    );
+}
+
+#[test]
+fn refresh_keeps_cursor_on_the_same_file_as_entries_come_and_go() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+    select(&mut browser, "b.md");
+
+    // An entry sorting before the cursor appears, as a watcher insert would add it
+    fs::write(dir.path().join("0.txt"), "zero").unwrap();
+    load(&mut browser);
+    assert_eq!(
+        browser.selected_entry().map(|e| e.name.as_str()),
+        Some("b.md")
+    );
+
+    // Entries before the cursor disappear
+    fs::remove_file(dir.path().join("0.txt")).unwrap();
+    fs::remove_file(dir.path().join("a.txt")).unwrap();
+    load(&mut browser);
+    assert_eq!(
+        browser.selected_entry().map(|e| e.name.as_str()),
+        Some("b.md")
+    );
+
+    // The selected file itself goes: the cursor moves to the entry that followed it
+    select(&mut browser, "docs");
+    fs::remove_dir_all(dir.path().join("docs")).unwrap();
+    load(&mut browser);
+    assert_eq!(
+        browser.selected_entry().map(|e| e.name.as_str()),
+        Some("src")
+    );
+}
+
+#[test]
+fn filter_without_matches_clears_cursor() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+
+    browser.dispatch(Action::Filter {
+        query: "zzz".into(),
+        sort: SortOptions::default(),
+    });
+
+    assert!(browser.entries.visible_entries.is_empty());
+    assert_eq!(browser.selection.selected_index, None);
+}
+
+// --- Clipboard ---
+
+#[test]
+fn yank_takes_the_entry_under_the_cursor() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+    select(&mut browser, "a.txt");
+
+    let effects = browser.dispatch(Action::Yank(ClipboardOp::Copy));
+
+    assert_eq!(effects, vec![Effect::Info("Yanked 1 files".into())]);
+    assert_eq!(browser.clipboard_op, Some(ClipboardOp::Copy));
+    assert!(browser.clipboard.contains(&dir.path().join("a.txt")));
+}
+
+#[test]
+fn yank_takes_the_multi_selection_and_leaves_visual_mode() {
+    let dir = fixture();
+    let mut browser = browser_at(dir.path());
+    browser.selection.multi_selection =
+        HashSet::from([dir.path().join("a.txt"), dir.path().join("b.md")]);
+
+    let effects = browser.dispatch(Action::Yank(ClipboardOp::Cut));
+
+    assert_eq!(
+        effects,
+        vec![Effect::ExitMode, Effect::Info("Cut 2 files".into())]
+    );
+    assert_eq!(browser.clipboard.len(), 2);
+    assert!(browser.selection.multi_selection.is_empty());
+}
+
+#[test]
+fn paste_copies_and_moves_files() {
+    let dir = fixture();
+    let dest = dir.path().join("docs");
+
+    let copied = paste_item(
+        &dir.path().join("a.txt"),
+        ClipboardOp::Copy,
+        ConflictPolicy::Ask,
+        &dest,
+        &mut |_| true,
+    );
+    let moved = paste_item(
+        &dir.path().join("b.md"),
+        ClipboardOp::Cut,
+        ConflictPolicy::Ask,
+        &dest,
+        &mut |_| true,
+    );
+
+    assert_eq!(copied, Ok(true));
+    assert_eq!(moved, Ok(true));
+    assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "alpha");
+    assert!(dir.path().join("a.txt").exists());
+    assert!(dest.join("b.md").exists());
+    assert!(!dir.path().join("b.md").exists());
+}
+
+#[test]
+fn paste_conflicts_follow_the_policy() {
+    let dir = fixture();
+    let dest = dir.path().join("docs");
+    fs::write(dest.join("a.txt"), "existing").unwrap();
+    let source = dir.path().join("a.txt");
+
+    let skipped = paste_item(
+        &source,
+        ClipboardOp::Copy,
+        ConflictPolicy::Skip,
+        &dest,
+        &mut |_| true,
+    );
+    assert_eq!(skipped, Ok(false));
+    assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "existing");
+
+    let renamed = paste_item(
+        &source,
+        ClipboardOp::Copy,
+        ConflictPolicy::Rename,
+        &dest,
+        &mut |_| true,
+    );
+    assert_eq!(renamed, Ok(true));
+    assert_eq!(fs::read_to_string(dest.join("a (1).txt")).unwrap(), "alpha");
+}
+
+#[test]
+fn paste_into_the_same_directory() {
+    let dir = fixture();
+    let source = dir.path().join("a.txt");
+
+    // Cutting onto itself does nothing, copying makes a numbered duplicate
+    let moved = paste_item(
+        &source,
+        ClipboardOp::Cut,
+        ConflictPolicy::Ask,
+        dir.path(),
+        &mut |_| true,
+    );
+    let copied = paste_item(
+        &source,
+        ClipboardOp::Copy,
+        ConflictPolicy::Ask,
+        dir.path(),
+        &mut |_| true,
+    );
+
+    assert_eq!(moved, Ok(false));
+    assert_eq!(copied, Ok(true));
+    assert!(source.exists());
+    assert!(dir.path().join("a (1).txt").exists());
+}
+
+#[test]
+fn paste_reports_missing_sources_and_copies_directories() {
+    let dir = fixture();
+    fs::write(dir.path().join("docs/inner/deep.txt"), "deep").unwrap();
+    let dest = dir.path().join("src");
+
+    let missing = paste_item(
+        &dir.path().join("gone.txt"),
+        ClipboardOp::Copy,
+        ConflictPolicy::Ask,
+        &dest,
+        &mut |_| true,
+    );
+    let directory = paste_item(
+        &dir.path().join("docs"),
+        ClipboardOp::Copy,
+        ConflictPolicy::Ask,
+        &dest,
+        &mut |_| true,
+    );
+
+    let into_itself = paste_item(
+        &dir.path().join("docs"),
+        ClipboardOp::Copy,
+        ConflictPolicy::Ask,
+        &dir.path().join("docs/inner"),
+        &mut |_| true,
+    );
+
+    assert!(missing.is_err());
+    assert_eq!(directory, Ok(true));
+    assert!(dest.join("docs/inner/deep.txt").exists());
+    assert!(dir.path().join("docs/inner/deep.txt").exists());
+    assert!(into_itself.is_err());
+    assert!(!dir.path().join("docs/inner/docs").exists());
+}
+
+#[test]
+fn cancelled_paste_leaves_no_partial_copy() {
+    let dir = fixture();
+    fs::write(dir.path().join("docs/inner/deep.txt"), "deep").unwrap();
+    let dest = dir.path().join("src");
+
+    let copied = paste_item(
+        &dir.path().join("docs"),
+        ClipboardOp::Copy,
+        ConflictPolicy::Ask,
+        &dest,
+        &mut |_| false,
+    );
+
+    assert!(copied.is_err());
+    assert!(!dest.join("docs").exists());
+    assert!(dir.path().join("docs/inner/deep.txt").exists());
+}
+
+#[test]
+fn mirror_items_land_at_the_same_place_on_the_other_side() {
+    use super::run_job_item;
+    use crate::state::JobKind;
+
+    let dir = fixture();
+    let (left, right) = (dir.path().join("docs"), dir.path().join("src"));
+    fs::write(left.join("inner/deep.txt"), "new").unwrap();
+    let kind = JobKind::Mirror {
+        from: left.clone(),
+        to: right.clone(),
+    };
+
+    assert_eq!(
+        run_job_item(&kind, &left.join("inner/deep.txt"), &mut |_| true),
+        Ok(true)
+    );
+    assert_eq!(
+        fs::read_to_string(right.join("inner/deep.txt")).unwrap(),
+        "new"
+    );
+    assert!(run_job_item(&kind, &dir.path().join("a.txt"), &mut |_| true).is_err());
+}
+
+#[test]
+fn dry_run_pastes_report_targets_without_writing() {
+    let dir = fixture();
+    let dest = dir.path().join("docs");
+    fs::write(dest.join("a.txt"), "existing").unwrap();
+
+    let renamed = plan_paste_item(
+        &dir.path().join("a.txt"),
+        ClipboardOp::Cut,
+        ConflictPolicy::Rename,
+        &dest,
+    );
+    let skipped = plan_paste_item(
+        &dir.path().join("a.txt"),
+        ClipboardOp::Copy,
+        ConflictPolicy::Skip,
+        &dest,
+    );
+    let directory = plan_paste_item(
+        &dir.path().join("src"),
+        ClipboardOp::Copy,
+        ConflictPolicy::Ask,
+        &dest,
+    );
+    let into_itself = plan_paste_item(
+        &dir.path().join("docs"),
+        ClipboardOp::Copy,
+        ConflictPolicy::Ask,
+        &dest,
+    );
+
+    assert_eq!(renamed.action, "move");
+    assert_eq!(renamed.target, Some(dest.join("a (1).txt")));
+    assert_eq!(skipped.action, "skip");
+    assert_eq!(directory.action, "copy");
+    assert!(!directory.problem);
+    assert!(into_itself.problem);
+    assert!(dir.path().join("a.txt").exists());
+    assert!(!dest.join("a (1).txt").exists());
+    assert!(!dest.join("src").exists());
+}
+
+// --- Bulk rename ---
+
+#[test]
+fn bulk_rename_plan_is_validated() {
+    let paths = vec![PathBuf::from("/x/a.txt"), PathBuf::from("/x/b.txt")];
+
+    assert!(plan_bulk_rename(&paths, "one.txt")
+        .unwrap_err()
+        .starts_with("Line count mismatch"));
+    assert_eq!(
+        plan_bulk_rename(&paths, "one.txt\n  "),
+        Err("Empty filename not allowed".into())
+    );
+    assert_eq!(
+        plan_bulk_rename(&paths, "same.txt\nsame.txt "),
+        Err("Duplicate filename: same.txt".into())
+    );
+    assert_eq!(
+        plan_bulk_rename(&paths, " one.txt\ntwo.txt").unwrap(),
+        vec![
+            (PathBuf::from("/x/a.txt"), "one.txt"),
+            (PathBuf::from("/x/b.txt"), "two.txt"),
+        ]
+    );
+}
+
+#[test]
+fn bulk_rename_renames_files_in_place() {
+    let dir = fixture();
+    let paths = vec![dir.path().join("a.txt"), dir.path().join("b.md")];
+    let plan = plan_bulk_rename(&paths, "first.txt\nb.md").unwrap();
+
+    let mut reported = Vec::new();
+    let (renamed, errors) = apply_renames(&plan, false, |old, new| {
+        reported.push((old.to_path_buf(), new.to_path_buf()))
+    });
+
+    assert_eq!(renamed, 2);
+    assert_eq!(
+        reported,
+        [(dir.path().join("a.txt"), dir.path().join("first.txt"))]
+    );
+    assert!(errors.is_empty());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("first.txt")).unwrap(),
+        "alpha"
+    );
+    assert!(!dir.path().join("a.txt").exists());
+    assert!(dir.path().join("b.md").exists());
+}
+
+#[test]
+fn bulk_rename_onto_a_taken_name() {
+    let dir = fixture();
+    let paths = vec![dir.path().join("a.txt")];
+    let plan = plan_bulk_rename(&paths, "notes.txt").unwrap();
+
+    let (renamed, errors) = apply_renames(&plan, false, |_, _| {});
+    assert_eq!(renamed, 0);
+    assert_eq!(errors, ["notes.txt: target already exists"]);
+    assert!(dir.path().join("a.txt").exists());
+
+    let (renamed, errors) = apply_renames(&plan, true, |_, _| {});
+    assert_eq!(renamed, 1);
+    assert!(errors.is_empty());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("notes (1).txt")).unwrap(),
+        "alpha"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("notes.txt")).unwrap(),
+        "notes"
+    );
+}
+
+#[test]
+fn dry_run_renames_match_the_real_run() {
+    let dir = fixture();
+    let paths = vec![
+        dir.path().join("a.txt"),
+        dir.path().join("b.md"),
+        dir.path().join("docs"),
+    ];
+    let plan = plan_bulk_rename(&paths, "notes.txt\nbravo.md\ndocs").unwrap();
+
+    let strict = plan_renames(&plan, false);
+    let numbered = plan_renames(&plan, true);
+
+    assert!(strict[0].problem);
+    assert_eq!(numbered[0].target, Some(dir.path().join("notes (1).txt")));
+    assert_eq!(numbered[1].action, "rename");
+    assert_eq!(numbered[1].target, Some(dir.path().join("bravo.md")));
+    assert_eq!(numbered[2].action, "keep");
+    assert!(dir.path().join("a.txt").exists());
+    assert!(!dir.path().join("bravo.md").exists());
+}
+
+// --- Entry details ---
+
+#[test]
+fn enrichment_fills_details_and_survives_a_reload() {
+    let dir = fixture();
+    fs::write(dir.path().join("README"), "plain text").unwrap();
+    let mut browser = browser_at(dir.path());
+    assert!(browser.entries.all_entries[0].details.mime.is_none());
+
+    let paths: Vec<PathBuf> = browser
+        .entries
+        .all_entries
+        .iter()
+        .map(|e| e.path.clone())
+        .collect();
+    browser
+        .entries
+        .apply_details(crate::io::directory::enrich_entries(dir.path(), &paths));
+
+    let mime = |browser: &Browser, name: &str| {
+        browser
+            .entries
+            .visible_entries
+            .iter()
+            .find(|e| e.name == name)
+            .and_then(|e| e.details.mime)
+    };
+    assert_eq!(mime(&browser, "docs"), Some("inode/directory"));
+    assert_eq!(mime(&browser, "b.md"), Some("text/markdown"));
+    assert_eq!(mime(&browser, "README"), Some("text/plain"));
+    assert!(browser.entries.all_entries[0].details.permissions.is_some());
+
+    let mut reloaded = read_directory(dir.path(), false).unwrap();
+    crate::state::entries::carry_details(&browser.entries.all_entries, &mut reloaded);
+    assert!(reloaded.iter().all(|e| e.details.mime.is_some()));
+}
+
+#[cfg(unix)]
+#[test]
+fn link_item_numbers_taken_names() {
+    let dir = fixture();
+    let dest = dir.path().join("docs");
+    assert_eq!(link_item(&dir.path().join("a.txt"), &dest), Ok(true));
+    assert_eq!(link_item(&dir.path().join("a.txt"), &dest), Ok(true));
+    assert_eq!(
+        fs::read_link(dest.join("a.txt")).unwrap(),
+        dir.path().join("a.txt")
+    );
+    assert!(fs::symlink_metadata(dest.join("a (1).txt"))
+        .unwrap()
+        .is_symlink());
+    assert!(link_item(&dir.path().join("missing"), &dest).is_err());
+}
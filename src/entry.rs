@@ -1,5 +1,8 @@
+use icu_collator::{Collator, CollatorOptions, Strength};
+use std::cmp::Ordering;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::SystemTime;
 
 #[cfg(unix)]
@@ -14,6 +17,29 @@ pub enum GitStatus {
     Conflict,
 }
 
+/// Attributes that cost extra syscalls, a `git` call or a content sniff
+///
+/// Listings arrive without them; the worker fills them in afterwards
+/// (`IoResult::EntriesEnriched`), so every field may still be `None`.
+#[derive(Clone, Debug, Default)]
+pub struct EntryDetails {
+    pub git_status: Option<GitStatus>,
+    pub permissions: Option<fs::Permissions>,
+    /// "user:group", falling back to numeric ids
+    pub owner: Option<String>,
+    pub mime: Option<&'static str>,
+    /// Top-level directory of the git project holding the entry
+    pub git_root: Option<Arc<Path>>,
+}
+
+/// Where a symlink points, as written, with the link's own size and modification time
+#[derive(Clone, Debug)]
+pub struct LinkInfo {
+    pub target: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
 #[derive(Clone, Debug)]
 pub struct FileEntry {
     pub path: PathBuf,
@@ -22,8 +48,14 @@ pub struct FileEntry {
     pub is_symlink: bool,
     pub size: u64,
     pub modified: SystemTime,
+    /// Last access; `None` where the platform or filesystem doesn't record it
+    pub accessed: Option<SystemTime>,
+    /// Birth time; `None` where the platform or filesystem doesn't record it
+    pub created: Option<SystemTime>,
     pub extension: String,
-    pub git_status: Option<GitStatus>,
+    /// Set for symlinks, whose `size` and times above are the target's
+    pub link: Option<LinkInfo>,
+    pub details: EntryDetails,
 }
 
 impl FileEntry {
@@ -45,6 +77,16 @@ impl FileEntry {
             .and_then(|m| m.modified().ok())
             .or_else(|| symlink_meta.modified().ok())
             .unwrap_or(SystemTime::now());
+        let accessed = metadata.as_ref().and_then(|m| m.accessed().ok());
+        let created = metadata.as_ref().and_then(|m| m.created().ok());
+        let link = is_symlink
+            .then(|| fs::read_link(&path).ok())
+            .flatten()
+            .map(|target| LinkInfo {
+                target,
+                size: symlink_meta.len(),
+                modified: symlink_meta.modified().unwrap_or(modified),
+            });
 
         Some(Self {
             path,
@@ -53,11 +95,23 @@ impl FileEntry {
             is_symlink,
             size,
             modified,
+            accessed,
+            created,
             extension,
-            git_status: None,
+            link,
+            details: EntryDetails::default(),
         })
     }
 
+    /// Report a symlink's own size and modification time instead of its target's
+    /// (`ui.link_target_stats = false`)
+    pub fn use_link_stats(&mut self) {
+        if let Some(link) = &self.link {
+            self.size = link.size;
+            self.modified = link.modified;
+        }
+    }
+
     pub fn get_icon(&self) -> &str {
         if self.is_dir {
             return "\u{f07b}";
@@ -111,12 +165,28 @@ impl FileEntry {
         }
     }
 
+    /// MIME type from the enriched details, or guessed from the extension
+    pub fn mime_type(&self) -> &'static str {
+        self.details.mime.unwrap_or_else(|| {
+            if self.is_dir {
+                "inode/directory"
+            } else {
+                mime_from_extension(&self.extension).unwrap_or("application/octet-stream")
+            }
+        })
+    }
+
     pub fn get_permissions_string(&self) -> String {
+        let permissions = match &self.details.permissions {
+            Some(permissions) => Ok(permissions.clone()),
+            None => fs::metadata(&self.path).map(|m| m.permissions()),
+        };
+
         #[cfg(unix)]
         {
-            match fs::metadata(&self.path) {
-                Ok(metadata) => {
-                    let mode = metadata.permissions().mode();
+            match permissions {
+                Ok(permissions) => {
+                    let mode = permissions.mode();
                     let owner = format_perms((mode >> 6) & 0o7);
                     let group = format_perms((mode >> 3) & 0o7);
                     let others = format_perms(mode & 0o7);
@@ -128,9 +198,9 @@ impl FileEntry {
 
         #[cfg(not(unix))]
         {
-            match fs::metadata(&self.path) {
-                Ok(metadata) => {
-                    if metadata.permissions().readonly() {
+            match permissions {
+                Ok(permissions) => {
+                    if permissions.readonly() {
                         "read-only".to_string()
                     } else {
                         "read-write".to_string()
@@ -206,6 +276,95 @@ impl FileEntry {
     }
 }
 
+/// Order file names by the Unicode root collation
+///
+/// Accents, case and composed vs decomposed forms only break ties, so
+/// "écrit" sorts with "ecrit" rather than after "z". Names the collator
+/// considers equal fall back to byte order to keep sorting stable.
+pub fn compare_names(a: &str, b: &str) -> Ordering {
+    // The collator is not `Sync`, so the UI and the worker each build their own
+    thread_local! {
+        static COLLATOR: Option<Collator> =
+            Collator::try_new(&Default::default(), CollatorOptions::new()).ok();
+    }
+    COLLATOR.with(|collator| match collator {
+        Some(collator) => collator.compare(a, b).then_with(|| a.cmp(b)),
+        None => a
+            .to_lowercase()
+            .cmp(&b.to_lowercase())
+            .then_with(|| a.cmp(b)),
+    })
+}
+
+/// Whether two letters differ at most by case or accents, e.g. "É" and "e",
+/// so name groups follow the collation order
+pub fn same_base_letter(a: &str, b: &str) -> bool {
+    thread_local! {
+        static PRIMARY: Option<Collator> = {
+            let mut options = CollatorOptions::new();
+            options.strength = Some(Strength::Primary);
+            Collator::try_new(&Default::default(), options).ok()
+        };
+    }
+    PRIMARY.with(|collator| match collator {
+        Some(collator) => collator.compare(a, b) == Ordering::Equal,
+        None => a.to_lowercase() == b.to_lowercase(),
+    })
+}
+
+/// MIME type for common extensions (lowercase, without the dot)
+pub fn mime_from_extension(extension: &str) -> Option<&'static str> {
+    Some(match extension {
+        "txt" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "xml" => "application/xml",
+        "json" => "application/json",
+        "toml" => "application/toml",
+        "yaml" | "yml" => "application/yaml",
+        "js" | "mjs" => "text/javascript",
+        "ts" | "tsx" => "text/typescript",
+        "rs" => "text/x-rust",
+        "py" => "text/x-python",
+        "c" | "h" => "text/x-c",
+        "cpp" | "cc" | "cxx" | "hpp" => "text/x-c++",
+        "go" => "text/x-go",
+        "java" => "text/x-java",
+        "sh" | "bash" | "zsh" => "application/x-sh",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/vnd.microsoft.icon",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "ogg" => "audio/ogg",
+        "m4a" => "audio/mp4",
+        "mp4" => "video/mp4",
+        "mkv" => "video/x-matroska",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "avi" => "video/x-msvideo",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "tar" => "application/x-tar",
+        "gz" | "tgz" => "application/gzip",
+        "7z" => "application/x-7z-compressed",
+        "rar" => "application/vnd.rar",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "doc" => "application/msword",
+        "xls" => "application/vnd.ms-excel",
+        _ => return None,
+    })
+}
+
 #[cfg(unix)]
 fn format_perms(mode: u32) -> String {
     let r = if mode & 0o4 != 0 { "r" } else { "-" };
@@ -213,3 +372,56 @@ fn format_perms(mode: u32) -> String {
     let x = if mode & 0o1 != 0 { "x" } else { "-" };
     format!("{}{}{}", r, w, x)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_sort_uses_unicode_collation() {
+        // "é" both precomposed and as e + combining acute
+        let mut names = [
+            "zebra",
+            "Éclair",
+            "e\u{301}tude",
+            "apple",
+            "Banana",
+            "😀 smile",
+        ];
+        names.sort_by(|a, b| compare_names(a, b));
+        assert_eq!(
+            names,
+            [
+                "😀 smile",
+                "apple",
+                "Banana",
+                "Éclair",
+                "e\u{301}tude",
+                "zebra"
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_entries_know_their_target_and_own_stats() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+        std::os::unix::fs::symlink("notes.txt", dir.path().join("link")).unwrap();
+
+        let mut entry = FileEntry::from_path(dir.path().join("link")).unwrap();
+        let link = entry.link.clone().unwrap();
+        assert_eq!(link.target, Path::new("notes.txt"));
+        assert_eq!(entry.size, 5, "the target's size by default");
+        assert_eq!(entry.display_name(), "link \u{2192}");
+
+        entry.use_link_stats();
+        assert_eq!(entry.size, link.size);
+        assert_eq!(entry.size, "notes.txt".len() as u64);
+        assert!(FileEntry::from_path(dir.path().join("a.txt"))
+            .unwrap()
+            .link
+            .is_none());
+    }
+}
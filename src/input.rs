@@ -2,50 +2,81 @@
 // Keyboard and mouse input processing
 
 use crate::app::Heike;
-use crate::io::worker::IoCommand;
+use crate::core::keymap::{Command, Key, KeyPress};
 use crate::state::AppMode;
 use crate::state::ClipboardOp;
+use crate::state::ConflictPolicy;
+use crate::state::ExitChoice;
+use crate::state::JobErrorAction;
+use crate::state::{DropAction, JobKind};
 use crate::style;
+use crate::view;
 use eframe::egui;
-use std::fs;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
-impl Heike {
-    pub fn handle_dropped_files(&mut self, dropped_files: &[egui::DroppedFile]) {
-        let mut count = 0;
-        let mut errors = Vec::new();
-
-        for file in dropped_files {
-            if let Some(path) = &file.path {
-                let dest = self
-                    .navigation
-                    .current_path
-                    .join(path.file_name().unwrap_or_default());
-
-                // Copy the dropped file to current directory
-                if path.is_dir() {
-                    errors.push("Copying directories not supported".into());
-                } else {
-                    match fs::copy(path, &dest) {
-                        Ok(_) => count += 1,
-                        Err(e) => errors.push(format!("Copy failed: {}", e)),
-                    }
-                }
-            }
-        }
-
-        if !errors.is_empty() {
-            self.ui.set_error(errors.join(" | "));
-        } else if count > 0 {
-            self.ui.set_info(format!("Copied {} file(s)", count));
-        }
+/// What a drop with these modifiers held would do
+pub fn drop_action(modifiers: egui::Modifiers) -> DropAction {
+    DropAction::from_modifiers(
+        modifiers.ctrl,
+        modifiers.shift,
+        modifiers.alt,
+        modifiers.mac_cmd,
+    )
+}
 
-        if count > 0 {
-            self.request_refresh();
+impl Heike {
+    /// Copy, move or link files dropped from another application, depending on
+    /// the modifiers held (see `DropAction::from_modifiers`)
+    pub fn handle_dropped_files(
+        &mut self,
+        dropped_files: &[egui::DroppedFile],
+        modifiers: egui::Modifiers,
+    ) {
+        let mut items: Vec<PathBuf> = dropped_files
+            .iter()
+            .filter_map(|file| file.path.clone())
+            .collect();
+        if items.is_empty() {
+            return;
         }
+        items.sort();
+        let dest_dir = self.browser.navigation.current_path.clone();
+        let kind = match drop_action(modifiers) {
+            DropAction::Link => JobKind::Link { dest_dir },
+            action => JobKind::Paste {
+                op: if action == DropAction::Move {
+                    ClipboardOp::Cut
+                } else {
+                    ClipboardOp::Copy
+                },
+                // The conflict dialog works on the clipboard; keep both copies instead
+                policy: match self.conflict_policy() {
+                    ConflictPolicy::Ask => ConflictPolicy::Rename,
+                    policy => policy,
+                },
+                dest_dir,
+            },
+        };
+        self.start_job(kind, items);
     }
 
     pub fn handle_input(&mut self, ctx: &egui::Context) {
+        let typed = ctx.input(|i| {
+            i.events.iter().any(|event| {
+                matches!(
+                    event,
+                    egui::Event::Key { pressed: true, .. } | egui::Event::Text(_)
+                )
+            })
+        });
+        if typed {
+            self.ui.last_key_press = Instant::now();
+            self.ui.visual_hint = false;
+            // Wake up once the pause is long enough for the which-key overlay
+            ctx.request_repaint_after(Duration::from_millis(style::WHICH_KEY_DELAY_MS));
+        }
+
         // 1. Bulk Rename Mode
         if matches!(self.mode.mode, AppMode::BulkRename { .. }) {
             if ctx.input(|i| i.key_pressed(egui::Key::Enter) && i.modifiers.ctrl) {
@@ -60,11 +91,31 @@ impl Heike {
         // 2. Modal Inputs (Command, Filter, Rename, SearchInput)
         if matches!(
             self.mode.mode,
-            AppMode::Command | AppMode::Filtering | AppMode::Rename | AppMode::SearchInput
+            AppMode::Command
+                | AppMode::Filtering
+                | AppMode::Rename
+                | AppMode::SearchInput
+                | AppMode::OpenWith { .. }
+                | AppMode::HookConfirm { .. }
         ) {
+            // Consumed before the text field sees it, so Tab completes instead of moving focus
+            let tab = ctx.input_mut(|i| {
+                if i.consume_key(egui::Modifiers::SHIFT, egui::Key::Tab) {
+                    Some(true)
+                } else if i.consume_key(egui::Modifiers::NONE, egui::Key::Tab) {
+                    Some(false)
+                } else {
+                    None
+                }
+            });
+            if let Some(backward) = tab {
+                self.complete_input_name(backward);
+            }
             if ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
                 match self.mode.mode {
                     AppMode::Rename => self.perform_rename(),
+                    AppMode::OpenWith { .. } => self.perform_open_with(),
+                    AppMode::HookConfirm { .. } => self.confirm_hook(),
                     AppMode::Command => self.execute_command(ctx),
                     AppMode::Filtering => {
                         // Finalize search and allow navigation in filtered results
@@ -72,20 +123,11 @@ impl Heike {
                         // Keep the filtered results
                     }
                     AppMode::SearchInput => {
-                        // Start search
-                        if !self.ui.search_query.is_empty() {
-                            self.ui.search_in_progress = true;
-                            // Reset search statistics
-                            self.ui.search_file_count = 0;
-                            self.ui.search_files_skipped = 0;
-                            self.ui.search_errors = 0;
-                            let _ = self.command_tx.send(IoCommand::SearchContent {
-                                query: self.ui.search_query.clone(),
-                                root_path: self.navigation.current_path.clone(),
-                                options: self.ui.search_options.clone(),
-                            });
+                        if self.ui.search_query.is_empty() {
+                            self.mode.set_mode(AppMode::Normal);
+                        } else {
+                            self.start_content_search();
                         }
-                        self.mode.set_mode(AppMode::Normal);
                     }
                     _ => {}
                 }
@@ -112,6 +154,91 @@ impl Heike {
             return;
         }
 
+        if let AppMode::OpenConfirm { paths } = &self.mode.mode {
+            let paths = paths.clone();
+            if ctx.input(|i| i.key_pressed(egui::Key::Y) || i.key_pressed(egui::Key::Enter)) {
+                self.queue_open(paths);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::W)) {
+                self.start_open_with(paths);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::N) || i.key_pressed(egui::Key::Escape))
+            {
+                self.mode.set_mode(AppMode::Normal);
+            }
+            return;
+        }
+
+        if matches!(self.mode.mode, AppMode::JobError { .. }) {
+            if ctx.input(|i| i.key_pressed(egui::Key::R)) {
+                self.resolve_job_error(JobErrorAction::Retry);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::S) && !i.modifiers.shift) {
+                self.resolve_job_error(JobErrorAction::Skip);
+            } else if ctx.input(|i| {
+                i.key_pressed(egui::Key::A) || (i.key_pressed(egui::Key::S) && i.modifiers.shift)
+            }) {
+                self.resolve_job_error(JobErrorAction::SkipAll);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                self.resolve_job_error(JobErrorAction::Abort);
+            }
+            return;
+        }
+
+        if self.mode.mode == AppMode::ExitConfirm {
+            if ctx.input(|i| i.key_pressed(egui::Key::W) || i.key_pressed(egui::Key::Enter)) {
+                self.resolve_exit(ctx, ExitChoice::Wait);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::C)) {
+                self.resolve_exit(ctx, ExitChoice::CancelAll);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::B)) {
+                self.resolve_exit(ctx, ExitChoice::Background);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                self.resolve_exit(ctx, ExitChoice::Stay);
+            }
+            return;
+        }
+
+        if matches!(self.mode.mode, AppMode::PasteConflict { .. }) {
+            if ctx.input(|i| i.key_pressed(egui::Key::R) || i.key_pressed(egui::Key::K)) {
+                self.resolve_paste_conflict(ConflictPolicy::Rename);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::O)) {
+                self.resolve_paste_conflict(ConflictPolicy::Overwrite);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::S)) {
+                self.resolve_paste_conflict(ConflictPolicy::Skip);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::C)) {
+                self.compare_next_paste_conflict();
+            } else if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                self.mode.set_mode(AppMode::Normal);
+            }
+            return;
+        }
+
+        if self.mode.mode == AppMode::Properties {
+            if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                self.close_properties();
+            }
+            return;
+        }
+
+        // Typing goes to the settings fields; only Escape is ours
+        if self.mode.mode == AppMode::Settings {
+            if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                self.close_settings();
+            }
+            return;
+        }
+
+        if matches!(self.mode.mode, AppMode::Maintenance { .. }) {
+            if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::Q)) {
+                self.mode.set_mode(AppMode::Normal);
+            }
+            return;
+        }
+
+        if self.mode.mode == AppMode::Diagnostics {
+            if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::Q)) {
+                self.mode.set_mode(AppMode::Normal);
+            }
+            return;
+        }
+
         if self.mode.mode == AppMode::Help {
             if ctx.input(|i| {
                 i.key_pressed(egui::Key::Escape)
@@ -128,6 +255,7 @@ impl Heike {
             query: ref current_query,
             ref results,
             ref mut selected_index,
+            ref mut marked,
         } = self.mode.mode
         {
             if ctx.input(|i| i.key_pressed(egui::Key::S) && i.modifiers.shift) {
@@ -158,12 +286,25 @@ impl Heike {
                 }
                 return;
             }
-            if ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
-                // Open the file at the match location
+            if ctx.input(|i| i.key_pressed(egui::Key::Space)) {
+                // Mark or unmark the result's file, then move on like `n`
                 if let Some(result) = results.get(*selected_index) {
-                    if result.file_path.is_file() {
-                        let _ = open::that(&result.file_path);
+                    if !marked.remove(&result.file_path) {
+                        marked.insert(result.file_path.clone());
                     }
+                    *selected_index = (*selected_index + 1) % results.len();
+                }
+                return;
+            }
+            if ctx.input(|i| i.key_pressed(egui::Key::Enter) && i.modifiers.shift) {
+                let marked = std::mem::take(marked);
+                self.select_marked_results(marked);
+                return;
+            }
+            if ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+                // Open the file (or extracted archive member) at the match location
+                if let Some(result) = results.get(*selected_index).cloned() {
+                    self.open_search_result(&result);
                 }
                 return;
             }
@@ -187,236 +328,348 @@ impl Heike {
             return; // Don't process other keys in search results mode
         }
 
-        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
-            // Clear stale 'g' press timer when exiting modes
-            self.selection.last_g_press = None;
-            self.mode.set_mode(AppMode::Normal);
-            self.mode.command_buffer.clear();
-            self.selection.multi_selection.clear();
-            self.apply_filter();
+        // Computer page: move between cards, open one or go back to the listing
+        if let AppMode::Computer {
+            ref cards,
+            ref mut selected_index,
+            ..
+        } = self.mode.mode
+        {
+            let (back, forward, open, close) = ctx.input(|i| {
+                (
+                    [
+                        egui::Key::K,
+                        egui::Key::H,
+                        egui::Key::ArrowUp,
+                        egui::Key::ArrowLeft,
+                    ]
+                    .iter()
+                    .any(|key| i.key_pressed(*key)),
+                    [
+                        egui::Key::J,
+                        egui::Key::L,
+                        egui::Key::ArrowDown,
+                        egui::Key::ArrowRight,
+                    ]
+                    .iter()
+                    .any(|key| i.key_pressed(*key)),
+                    i.key_pressed(egui::Key::Enter),
+                    i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::Q),
+                )
+            });
+            if back {
+                *selected_index = selected_index.saturating_sub(1);
+            } else if forward && *selected_index + 1 < cards.len() {
+                *selected_index += 1;
+            } else if open {
+                let index = *selected_index;
+                self.open_computer_card(index);
+            } else if close {
+                self.mode.set_mode(AppMode::Normal);
+            }
             return;
         }
 
-        // 3. Global History keys
-        if ctx.input(|i| i.modifiers.alt && i.key_pressed(egui::Key::ArrowLeft)) {
-            self.navigate_back();
-            return;
-        }
-        if ctx.input(|i| i.modifiers.alt && i.key_pressed(egui::Key::ArrowRight)) {
-            self.navigate_forward();
+        // Dry run report: read-only, Esc or q goes back
+        if matches!(self.mode.mode, AppMode::DryRun { .. }) {
+            if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::Q)) {
+                self.close_dry_run();
+            }
             return;
         }
 
-        // 4. Normal Mode Triggers
-        if ctx.input(|i| i.key_pressed(egui::Key::Colon)) {
-            self.mode.set_mode(AppMode::Command);
-            self.mode.focus_input = true;
-            self.mode.command_buffer.clear();
-            return;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::Slash)) {
-            self.mode.set_mode(AppMode::Filtering);
-            self.mode.focus_input = true;
-            self.mode.command_buffer.clear();
-            return;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::Period)) {
-            self.ui.show_hidden = !self.ui.show_hidden;
-            self.request_refresh();
-            return;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::O) && i.modifiers.shift) {
-            self.ui.sort_options.cycle_sort_by();
-            self.apply_filter();
-            return;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::O) && i.modifiers.alt) {
-            self.ui.sort_options.toggle_order();
-            self.apply_filter();
-            return;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::O) && i.modifiers.ctrl) {
-            self.ui.sort_options.toggle_dirs_first();
-            self.apply_filter();
-            return;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::Questionmark)) {
-            self.mode.set_mode(AppMode::Help);
+        // Handle DirDiff mode: navigate and copy entries across
+        if let AppMode::DirDiff {
+            ref entries,
+            ref mut selected_index,
+            ..
+        } = self.mode.mode
+        {
+            if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::Q)) {
+                self.mode.set_mode(AppMode::Normal);
+                return;
+            }
+            if ctx.input(|i| i.key_pressed(egui::Key::ArrowDown) || i.key_pressed(egui::Key::J)) {
+                if !entries.is_empty() {
+                    *selected_index = (*selected_index + 1).min(entries.len() - 1);
+                }
+                return;
+            }
+            if ctx.input(|i| i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::K)) {
+                *selected_index = selected_index.saturating_sub(1);
+                return;
+            }
+            let index = *selected_index;
+            let (shift, to_right, to_left) = ctx.input(|i| {
+                (
+                    i.modifiers.shift,
+                    i.key_pressed(egui::Key::L) || i.key_pressed(egui::Key::ArrowRight),
+                    i.key_pressed(egui::Key::H) || i.key_pressed(egui::Key::ArrowLeft),
+                )
+            });
+            if to_right || to_left {
+                if shift {
+                    self.copy_dir_diff_missing(to_right);
+                } else {
+                    self.copy_dir_diff_entry(index, to_right);
+                }
+            }
             return;
         }
 
-        // --- Tab Management ---
-        if ctx.input(|i| i.key_pressed(egui::Key::T) && i.modifiers.ctrl) {
-            // Ctrl+T: New tab in current directory
-            self.new_tab(None);
-            return;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::W) && i.modifiers.ctrl) {
-            // Ctrl+W: Close current tab
-            self.close_current_tab();
-            return;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::Tab) && i.modifiers.ctrl && !i.modifiers.shift) {
-            // Ctrl+Tab: Next tab
-            self.next_tab();
-            return;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::Tab) && i.modifiers.ctrl && i.modifiers.shift) {
-            // Ctrl+Shift+Tab: Previous tab
-            self.prev_tab();
-            return;
-        }
-        // Alt+1 through Alt+9 to switch tabs
-        for i in 1..=9 {
-            let key_name = i.to_string();
-            if let Some(key) = egui::Key::from_name(&key_name) {
-                if ctx.input(|input| input.modifiers.alt && input.key_pressed(key)) {
-                    self.switch_to_tab(i - 1);
-                    return;
+        // Handle BrokenLinks mode: the retarget field takes typing until Enter or Escape
+        if let AppMode::BrokenLinks {
+            ref links,
+            ref mut selected_index,
+            ref mut retarget,
+            ..
+        } = self.mode.mode
+        {
+            if retarget.is_some() {
+                if ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    self.apply_retarget_link();
+                } else if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    *retarget = None;
                 }
+            } else if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::Q))
+            {
+                self.mode.set_mode(AppMode::Normal);
+            } else if ctx
+                .input(|i| i.key_pressed(egui::Key::ArrowDown) || i.key_pressed(egui::Key::J))
+            {
+                *selected_index = (*selected_index + 1).min(links.len().saturating_sub(1));
+            } else if ctx
+                .input(|i| i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::K))
+            {
+                *selected_index = selected_index.saturating_sub(1);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::Enter) || i.key_pressed(egui::Key::G))
+            {
+                self.reveal_broken_link();
+            } else if ctx.input(|i| i.key_pressed(egui::Key::R)) {
+                self.begin_retarget_link();
+            } else if ctx.input(|i| i.key_pressed(egui::Key::D)) {
+                let all = ctx.input(|i| i.modifiers.shift);
+                self.trash_broken_links(all);
             }
+            return;
         }
 
-        if ctx.input(|i| i.key_pressed(egui::Key::V) && !i.modifiers.shift) {
-            if self.mode.mode == AppMode::Normal {
-                // Enter visual mode
-                self.mode.set_mode(AppMode::Visual);
-                if let Some(idx) = self.selection.selected_index {
-                    if let Some(entry) = self.entries.visible_entries.get(idx) {
-                        self.selection.multi_selection.insert(entry.path.clone());
-                    }
-                }
-            } else if self.mode.mode == AppMode::Visual {
-                // Exit visual mode (unset)
+        // Handle NameAudit mode: review the portable names, then rename or report
+        if let AppMode::NameAudit {
+            ref issues,
+            ref mut selected_index,
+            ..
+        } = self.mode.mode
+        {
+            if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::Q)) {
                 self.mode.set_mode(AppMode::Normal);
-                self.selection.multi_selection.clear();
+            } else if ctx
+                .input(|i| i.key_pressed(egui::Key::ArrowDown) || i.key_pressed(egui::Key::J))
+            {
+                *selected_index = (*selected_index + 1).min(issues.len().saturating_sub(1));
+            } else if ctx
+                .input(|i| i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::K))
+            {
+                *selected_index = selected_index.saturating_sub(1);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.sanitize_audited_names(false);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::D)) {
+                self.sanitize_audited_names(true);
             }
             return;
         }
-        if self.mode.mode == AppMode::Normal
-            && ctx.input(|i| i.key_pressed(egui::Key::V) && i.modifiers.shift)
+
+        // Handle Cleanup mode: tick items and send them to the trash
+        if let AppMode::Cleanup {
+            ref items,
+            ref mut selected_index,
+            ..
+        } = self.mode.mode
         {
-            // Shift+V: Enter visual mode and select all
-            self.mode.set_mode(AppMode::Visual);
-            self.selection.multi_selection.clear();
-            for entry in &self.entries.visible_entries {
-                self.selection.multi_selection.insert(entry.path.clone());
+            if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::Q)) {
+                self.mode.set_mode(AppMode::Normal);
+            } else if ctx
+                .input(|i| i.key_pressed(egui::Key::ArrowDown) || i.key_pressed(egui::Key::J))
+            {
+                *selected_index = (*selected_index + 1).min(items.len().saturating_sub(1));
+            } else if ctx
+                .input(|i| i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::K))
+            {
+                *selected_index = selected_index.saturating_sub(1);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::Space)) {
+                let index = *selected_index;
+                self.toggle_cleanup_item(Some(index));
+            } else if ctx.input(|i| i.key_pressed(egui::Key::A)) {
+                self.toggle_cleanup_item(None);
+            } else if ctx.input(|i| i.key_pressed(egui::Key::Enter) || i.key_pressed(egui::Key::D))
+            {
+                self.trash_cleanup_items();
             }
             return;
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::A) && i.modifiers.ctrl) {
-            // Ctrl+A: Select all
-            if self.mode.mode != AppMode::Visual {
-                self.mode.set_mode(AppMode::Visual);
-            }
-            self.selection.multi_selection.clear();
-            for entry in &self.entries.visible_entries {
-                self.selection.multi_selection.insert(entry.path.clone());
-            }
+
+        if !matches!(self.mode.mode, AppMode::Normal | AppMode::Visual) {
             return;
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::Space)) {
-            // Space: Toggle selection of current item
-            if let Some(idx) = self.selection.selected_index {
-                if let Some(entry) = self.entries.visible_entries.get(idx) {
-                    if self.selection.multi_selection.contains(&entry.path) {
-                        self.selection.multi_selection.remove(&entry.path);
-                    } else {
-                        if self.mode.mode != AppMode::Visual {
-                            self.mode.set_mode(AppMode::Visual);
-                        }
-                        self.selection.multi_selection.insert(entry.path.clone());
-                    }
-                }
+
+        // A peeked sibling stands in for the listing; keys must not act on the hidden one
+        if self.peek.is_some() {
+            if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::Q)) {
+                self.peek = None;
+            } else if ctx.input(|i| i.key_pressed(egui::Key::Enter) || i.key_pressed(egui::Key::L))
+            {
+                self.open_peek(None);
             }
             return;
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::R) && i.modifiers.ctrl) {
-            // Ctrl+R: Invert selection (select unselected, deselect selected)
-            let unselected: Vec<_> = self
-                .entries
-                .visible_entries
+
+        // 4. Normal and visual mode: every key event goes through the keymap once
+        let presses: Vec<KeyPress> = ctx.input(|i| {
+            i.events
                 .iter()
-                .filter(|e| !self.selection.multi_selection.contains(&e.path))
-                .map(|e| e.path.clone())
-                .collect();
+                .filter_map(|event| match event {
+                    egui::Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => key_press(*key, *modifiers),
+                    _ => None,
+                })
+                .collect()
+        });
+        // A bookmark that a longer one extends fires once the sequence times out
+        if let Some((command, count)) = self.keymap.poll(Instant::now()) {
+            self.run_key_command(command, count);
+        }
+        for press in presses {
+            if let Some((command, count)) = self.keymap.feed(press, Instant::now()) {
+                self.run_key_command(command, count);
+            }
+            // Keys after one that opened a prompt or dialog belong to it
+            if !matches!(self.mode.mode, AppMode::Normal | AppMode::Visual) {
+                self.keymap.reset();
+                break;
+            }
+        }
+        if !self.keymap.pending().is_empty() {
+            ctx.request_repaint_after(Duration::from_millis(style::KEY_SEQUENCE_TIMEOUT_MS));
+        }
+    }
 
-            self.selection.multi_selection.clear();
-            for path in unselected {
-                self.selection.multi_selection.insert(path);
-            }
+    /// Entry under the cursor
+    fn cursor_entry(&self) -> Option<&crate::entry::FileEntry> {
+        self.browser
+            .selection
+            .selected_index
+            .and_then(|idx| self.browser.entries.visible_entries.get(idx))
+    }
 
-            // Enter visual mode if we have selections
-            if !self.selection.multi_selection.is_empty() {
-                self.mode.set_mode(AppMode::Visual);
+    /// Put the cursor on `index`, extending the selection in visual mode
+    fn move_cursor(&mut self, index: usize) {
+        self.browser.selection.selected_index = Some(index);
+        self.browser.selection.last_selection_change = Instant::now();
+        self.browser.selection.disable_autoscroll = false; // Re-enable autoscroll on keyboard navigation
+        if self.mode.mode == AppMode::Visual {
+            if let Some(entry) = self.browser.entries.visible_entries.get(index) {
+                self.browser
+                    .selection
+                    .multi_selection
+                    .insert(entry.path.clone());
             }
-            return;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::S) && i.modifiers.shift) {
-            self.ui.search_in_progress = false;
-            self.ui.search_file_count = 0;
-            self.mode.set_mode(AppMode::SearchInput);
-            self.mode.focus_input = true;
-            return;
         }
+    }
 
-        // 5. File Operation Triggers (Phase 6)
-        // Check if we're waiting for a bookmark key - if so, skip file operations
-        let waiting_for_bookmark = if let Some(last) = self.selection.last_g_press {
-            Instant::now().duration_since(last) < Duration::from_millis(style::DOUBLE_PRESS_MS)
-        } else {
-            false
-        };
+    fn select_all_visible(&mut self) {
+        self.browser.selection.multi_selection = self
+            .browser
+            .entries
+            .visible_entries
+            .iter()
+            .map(|e| e.path.clone())
+            .collect();
+    }
 
-        if !waiting_for_bookmark && ctx.input(|i| i.key_pressed(egui::Key::Y)) {
-            self.yank_selection(ClipboardOp::Copy);
-        }
-        if !waiting_for_bookmark && ctx.input(|i| i.key_pressed(egui::Key::X)) {
-            self.yank_selection(ClipboardOp::Cut);
-        }
-        if !waiting_for_bookmark && ctx.input(|i| i.key_pressed(egui::Key::P)) {
-            self.paste_clipboard();
-        }
-        if !waiting_for_bookmark && ctx.input(|i| i.key_pressed(egui::Key::D) && !i.modifiers.ctrl)
-        {
-            self.mode.set_mode(AppMode::DeleteConfirm);
-        }
-        if !waiting_for_bookmark && ctx.input(|i| i.key_pressed(egui::Key::R) && i.modifiers.shift)
-        {
-            // Shift+R: Bulk rename - rename multiple files at once
-            self.enter_bulk_rename_mode();
+    /// Carry out a keymap command; `count` is the typed number prefix, if any
+    pub(crate) fn run_key_command(&mut self, command: Command, count: Option<usize>) {
+        let len = self.browser.entries.visible_entries.len();
+        let max_idx = len.saturating_sub(1);
+        let current = self.browser.selection.selected_index.unwrap_or(0);
+        let steps = count.unwrap_or(1);
+        let half_page = (len / 2).max(1) * steps;
+        let page = len.max(1) * steps;
+        // Cursor movement needs something to move over
+        let target = match command {
+            // A count moves that far and stops at the ends; a single step wraps around
+            Command::Down if count.is_some() => Some((current + steps).min(max_idx)),
+            Command::Down => Some(if current >= max_idx { 0 } else { current + 1 }),
+            Command::Up if count.is_some() => Some(current.saturating_sub(steps)),
+            Command::Up => Some(if current == 0 { max_idx } else { current - 1 }),
+            Command::Top | Command::Bottom if count.is_some() => Some((steps - 1).min(max_idx)),
+            Command::Top => Some(0),
+            Command::Bottom => Some(max_idx),
+            Command::HalfPageDown => Some((current + half_page).min(max_idx)),
+            Command::HalfPageUp => Some(current.saturating_sub(half_page)),
+            Command::PageDown => Some((current + page).min(max_idx)),
+            Command::PageUp => Some(current.saturating_sub(page)),
+            _ => None,
+        };
+        if let Some(index) = target {
+            if len > 0 {
+                self.move_cursor(index);
+            }
+            return;
         }
-        if !waiting_for_bookmark && ctx.input(|i| i.key_pressed(egui::Key::R) && !i.modifiers.shift)
-        {
-            if let Some(idx) = self.selection.selected_index {
-                if let Some(entry) = self.entries.visible_entries.get(idx) {
-                    self.mode.command_buffer = entry.name.clone();
-                    self.mode.set_mode(AppMode::Rename);
-                    self.mode.focus_input = true;
+
+        match command {
+            Command::Cancel => {
+                self.mode.set_mode(AppMode::Normal);
+                self.mode.command_buffer.clear();
+                self.browser.selection.multi_selection.clear();
+                self.apply_filter();
+            }
+            Command::Back => self.navigate_back(),
+            Command::Forward => self.navigate_forward(),
+            Command::Parent => self.navigate_up(),
+            Command::EnterDir => {
+                if let Some(entry) = self.cursor_entry().filter(|e| e.is_dir) {
+                    let path = entry.path.clone();
+                    self.navigate_to(path);
                 }
             }
-        }
-        if !waiting_for_bookmark && ctx.input(|i| i.key_pressed(egui::Key::E)) {
-            // 'e' key: open file with default app
-            if let Some(idx) = self.selection.selected_index {
-                if let Some(entry) = self.entries.visible_entries.get(idx) {
-                    // For directories, enter them (same as 'l')
-                    if entry.is_dir {
-                        let path = entry.path.clone();
+            Command::Open => {
+                if !self.browser.selection.multi_selection.is_empty() {
+                    // Enter with a multi-selection opens every selected file
+                    self.open_selection();
+                } else if let Some(entry) = self.cursor_entry() {
+                    let path = entry.path.clone();
+                    self.navigate_to(path);
+                }
+            }
+            Command::OpenEntry => {
+                if let Some(entry) = self.cursor_entry() {
+                    // Directories are entered, files go to the associated or default app
+                    let (path, is_dir) = (entry.path.clone(), entry.is_dir);
+                    if is_dir {
                         self.navigate_to(path);
                     } else {
-                        // For files, open with default app
-                        let _ = open::that(&entry.path);
+                        self.open_file(&path);
                     }
                 }
             }
-        }
-        if !waiting_for_bookmark && ctx.input(|i| i.key_pressed(egui::Key::E) && i.modifiers.shift)
-        {
-            // Shift+E: open command mode for extraction (user can use ':' commands)
-            // For now, just show a message since extraction requires special handling
-            if let Some(idx) = self.selection.selected_index {
-                if let Some(entry) = self.entries.visible_entries.get(idx) {
+            Command::OpenWith => {
+                let paths = self.selected_files();
+                self.start_open_with(paths);
+            }
+            Command::OpenAtPreview => self.open_at_preview(),
+            Command::Properties => {
+                if let Some(entry) = self.cursor_entry() {
+                    let path = entry.path.clone();
+                    self.open_properties(path);
+                }
+            }
+            Command::ExtractHint => {
+                if let Some(entry) = self.cursor_entry() {
                     if matches!(
                         entry.extension.as_str(),
                         "zip" | "tar" | "gz" | "tgz" | "bz2" | "xz"
@@ -429,200 +682,158 @@ impl Heike {
                     }
                 }
             }
-        }
-
-        // 6. Navigation (j/k/arrows)
-        if self.entries.visible_entries.is_empty() {
-            if ctx.input(|i| {
-                i.key_pressed(egui::Key::Backspace)
-                    || i.key_pressed(egui::Key::H)
-                    || i.key_pressed(egui::Key::ArrowLeft)
-            }) {
-                self.navigate_up();
+            Command::Colon | Command::Filter => {
+                self.mode.set_mode(if command == Command::Colon {
+                    AppMode::Command
+                } else {
+                    AppMode::Filtering
+                });
+                self.mode.focus_input = true;
+                self.mode.command_buffer.clear();
             }
-            return;
-        }
-
-        let mut changed = false;
-        let max_idx = self.entries.visible_entries.len() - 1;
-        let current = self.selection.selected_index.unwrap_or(0);
-        let mut new_index = current;
-
-        if ctx.input(|i| i.key_pressed(egui::Key::ArrowDown) || i.key_pressed(egui::Key::J)) {
-            new_index = if current >= max_idx { 0 } else { current + 1 };
-            changed = true;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::K)) {
-            new_index = if current == 0 { max_idx } else { current - 1 };
-            changed = true;
-        }
-        if ctx.input(|i| {
-            i.key_pressed(egui::Key::Backspace)
-                || i.key_pressed(egui::Key::H)
-                || i.key_pressed(egui::Key::ArrowLeft)
-                || i.key_pressed(egui::Key::Minus) // '-' for parent (vim standard)
-        }) {
-            self.navigate_up();
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
-            if let Some(idx) = self.selection.selected_index {
-                if let Some(entry) = self.entries.visible_entries.get(idx) {
-                    let path = entry.path.clone();
-                    self.navigate_to(path);
+            Command::Search => {
+                self.ui.search_in_progress = false;
+                self.ui.search_file_count = 0;
+                self.mode.set_mode(AppMode::SearchInput);
+                self.mode.focus_input = true;
+            }
+            Command::Help => self.mode.set_mode(AppMode::Help),
+            Command::ToggleHidden => {
+                self.ui.show_hidden = !self.ui.show_hidden;
+                self.request_refresh();
+            }
+            Command::ToggleFlatten => self.toggle_flatten(),
+            Command::CycleSort => {
+                self.ui.sort_options.cycle_sort_by();
+                self.apply_filter();
+            }
+            Command::ToggleSortOrder => {
+                self.ui.sort_options.toggle_order();
+                self.apply_filter();
+            }
+            Command::ToggleDirsFirst => {
+                self.ui.sort_options.toggle_dirs_first();
+                self.apply_filter();
+            }
+            Command::PreviewTab { forward } => {
+                if let Some(entry) = self.cursor_entry() {
+                    view::cycle_preview_tab(&self.preview_tab, entry, forward);
                 }
             }
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::L) || i.key_pressed(egui::Key::ArrowRight)) {
-            if let Some(idx) = self.selection.selected_index {
-                if let Some(entry) = self.entries.visible_entries.get(idx) {
-                    if entry.is_dir {
-                        let path = entry.path.clone();
-                        self.navigate_to(path);
+            Command::NewTab => self.new_tab(None),
+            Command::CloseTab => self.close_current_tab(),
+            Command::NextTab => self.next_tab(),
+            Command::PrevTab => self.prev_tab(),
+            Command::MirrorToTab => self.mirror_path_to_tab(None),
+            Command::SwitchTab(index) => self.switch_to_tab(index),
+            Command::Visual => {
+                if self.mode.mode == AppMode::Visual {
+                    self.mode.set_mode(AppMode::Normal);
+                    self.browser.selection.multi_selection.clear();
+                } else {
+                    self.mode.set_mode(AppMode::Visual);
+                    self.ui.visual_hint = true;
+                    if let Some(path) = self.cursor_entry().map(|e| e.path.clone()) {
+                        self.browser.selection.multi_selection.insert(path);
                     }
                 }
             }
-        }
-
-        // Page-down / half-page navigation (vim style)
-        if ctx.input(|i| i.key_pressed(egui::Key::D) && i.modifiers.ctrl) {
-            // Ctrl-D: half-page down
-            let page_size = (self.entries.visible_entries.len() / 2).max(1);
-            new_index = (current + page_size).min(max_idx);
-            changed = true;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::U) && i.modifiers.ctrl) {
-            // Ctrl-U: half-page up
-            let page_size = (self.entries.visible_entries.len() / 2).max(1);
-            new_index = if current >= page_size {
-                current - page_size
-            } else {
-                0
-            };
-            changed = true;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::F) && i.modifiers.ctrl) {
-            // Ctrl-F: full page down
-            let page_size = self.entries.visible_entries.len().max(1);
-            new_index = (current + page_size).min(max_idx);
-            changed = true;
-        }
-        if ctx.input(|i| i.key_pressed(egui::Key::B) && i.modifiers.ctrl) {
-            // Ctrl-B: full page up
-            let page_size = self.entries.visible_entries.len().max(1);
-            new_index = if current >= page_size {
-                current - page_size
-            } else {
-                0
-            };
-            changed = true;
-        }
-
-        if ctx.input(|i| i.key_pressed(egui::Key::G) && i.modifiers.shift) {
-            new_index = max_idx;
-            changed = true;
-        }
-        // Handle 'g' key for navigation (gg=top, gX=bookmark)
-        if ctx.input(|i| i.key_pressed(egui::Key::G) && !i.modifiers.shift) {
-            let now = Instant::now();
-            if let Some(last) = self.selection.last_g_press {
-                if now.duration_since(last) < Duration::from_millis(style::DOUBLE_PRESS_MS) {
-                    // Double 'g' press - jump to top
-                    new_index = 0;
-                    self.selection.last_g_press = None;
-                    changed = true;
-                } else {
-                    // Single 'g' press after timeout - start new sequence
-                    self.selection.last_g_press = Some(now);
+            Command::VisualAll | Command::SelectAll => {
+                if command == Command::VisualAll && self.mode.mode == AppMode::Visual {
+                    return;
                 }
-            } else {
-                // First 'g' press - start sequence
-                self.selection.last_g_press = Some(now);
+                self.mode.set_mode(AppMode::Visual);
+                self.select_all_visible();
             }
-        }
-
-        // Check for bookmark navigation (g + key)
-        if let Some(last) = self.selection.last_g_press {
-            let elapsed = Instant::now().duration_since(last);
-            if elapsed > Duration::from_millis(style::DOUBLE_PRESS_MS) {
-                // Timeout - clear the 'g' press
-                self.selection.last_g_press = None;
-            } else if elapsed > Duration::from_millis(style::KEY_SEQUENCE_DELAY_MS) {
-                // Short delay to allow keyboard input processing
-                // Check for any single-character key press for bookmarks
-                let bookmark_key = ctx.input(|i| {
-                    for key in &[
-                        egui::Key::A,
-                        egui::Key::B,
-                        egui::Key::C,
-                        egui::Key::D,
-                        egui::Key::E,
-                        egui::Key::F,
-                        egui::Key::H,
-                        egui::Key::I,
-                        egui::Key::J,
-                        egui::Key::K,
-                        egui::Key::L,
-                        egui::Key::M,
-                        egui::Key::N,
-                        egui::Key::O,
-                        egui::Key::P,
-                        egui::Key::Q,
-                        egui::Key::R,
-                        egui::Key::S,
-                        egui::Key::T,
-                        egui::Key::U,
-                        egui::Key::V,
-                        egui::Key::W,
-                        egui::Key::X,
-                        egui::Key::Y,
-                        egui::Key::Z,
-                        egui::Key::Num0,
-                        egui::Key::Num1,
-                        egui::Key::Num2,
-                        egui::Key::Num3,
-                        egui::Key::Num4,
-                        egui::Key::Num5,
-                        egui::Key::Num6,
-                        egui::Key::Num7,
-                        egui::Key::Num8,
-                        egui::Key::Num9,
-                    ] {
-                        if i.key_pressed(*key) {
-                            return Some(key.name().to_lowercase());
-                        }
+            Command::ToggleSelect => {
+                if let Some(path) = self.cursor_entry().map(|e| e.path.clone()) {
+                    if !self.browser.selection.multi_selection.remove(&path) {
+                        self.mode.set_mode(AppMode::Visual);
+                        self.browser.selection.multi_selection.insert(path);
                     }
-                    None
-                });
-
-                if let Some(key) = bookmark_key {
-                    if let Some(path) = self.bookmarks.resolve_path(&key) {
-                        if path.is_dir() {
-                            self.navigate_to(path);
-                        } else {
-                            self.ui.set_error(format!(
-                                "Bookmark '{}' does not exist or is not a directory",
-                                key
-                            ));
-                        }
-                    } else {
-                        self.ui.set_info(format!("No bookmark '{}' defined", key));
-                    }
-                    self.selection.last_g_press = None;
                 }
             }
-        }
-
-        if changed {
-            // Clear stale 'g' press timer when any other navigation action occurs
-            self.selection.last_g_press = None;
-            self.selection.selected_index = Some(new_index);
-            self.selection.last_selection_change = Instant::now();
-            self.selection.disable_autoscroll = false; // Re-enable autoscroll on keyboard navigation
-            if self.mode.mode == AppMode::Visual {
-                if let Some(entry) = self.entries.visible_entries.get(new_index) {
-                    self.selection.multi_selection.insert(entry.path.clone());
+            Command::InvertSelection => {
+                let selected = std::mem::take(&mut self.browser.selection.multi_selection);
+                self.browser.selection.multi_selection = self
+                    .browser
+                    .entries
+                    .visible_entries
+                    .iter()
+                    .filter(|e| !selected.contains(&e.path))
+                    .map(|e| e.path.clone())
+                    .collect();
+                if !self.browser.selection.multi_selection.is_empty() {
+                    self.mode.set_mode(AppMode::Visual);
                 }
             }
+            Command::Copy => self.yank_selection(ClipboardOp::Copy),
+            Command::Cut => self.yank_selection(ClipboardOp::Cut),
+            Command::Paste => self.paste_clipboard(),
+            Command::Stage => self.toggle_staged(),
+            Command::Delete => self.mode.set_mode(AppMode::DeleteConfirm),
+            Command::BulkRename => self.enter_bulk_rename_mode(),
+            Command::Rename => {
+                if let Some(entry) = self.cursor_entry() {
+                    // Flatten view names are relative paths; rename only the final component
+                    self.mode.command_buffer = entry
+                        .path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| entry.name.clone());
+                    self.mode.set_mode(AppMode::Rename);
+                    self.mode.focus_input = true;
+                }
+            }
+            Command::Bookmark(key) => self.jump_to_bookmark(&key),
+            Command::ProjectRoot => self.jump_to_project_root(),
+            Command::MountRoot => self.jump_to_mount_root(),
+            Command::Computer => self.open_computer(),
+            Command::Down
+            | Command::Up
+            | Command::Top
+            | Command::Bottom
+            | Command::HalfPageDown
+            | Command::HalfPageUp
+            | Command::PageDown
+            | Command::PageUp => {}
         }
     }
 }
+
+/// Toolkit-independent form of a key event; `None` for keys no binding uses
+fn key_press(key: egui::Key, modifiers: egui::Modifiers) -> Option<KeyPress> {
+    use egui::Key as K;
+    let key = match key {
+        K::Enter => Key::Enter,
+        K::Escape => Key::Escape,
+        K::Backspace => Key::Backspace,
+        K::Tab => Key::Tab,
+        K::Space => Key::Space,
+        K::ArrowUp => Key::Up,
+        K::ArrowDown => Key::Down,
+        K::ArrowLeft => Key::Left,
+        K::ArrowRight => Key::Right,
+        K::Colon => Key::Char(':'),
+        K::Slash => Key::Char('/'),
+        K::Questionmark => Key::Char('?'),
+        K::Period => Key::Char('.'),
+        K::Minus => Key::Char('-'),
+        K::OpenBracket => Key::Char('['),
+        K::CloseBracket => Key::Char(']'),
+        // Letters are named "A".."Z", digit keys "0".."9"
+        other => {
+            let mut chars = other.name().chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphanumeric() => Key::Char(c.to_ascii_lowercase()),
+                _ => return None,
+            }
+        }
+    };
+    Some(KeyPress::new(
+        key,
+        modifiers.ctrl,
+        modifiers.shift,
+        modifiers.alt,
+    ))
+}
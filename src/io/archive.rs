@@ -0,0 +1,538 @@
+// Paged archive listings: members are read a page at a time, each page resuming
+// where the previous one stopped, so archives with millions of members stay cheap
+// to preview. Totals need a full pass and are only computed on request.
+
+use flate2::read::GzDecoder;
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{PoisonError, RwLock};
+use std::thread;
+use std::time::Duration;
+use tar::Archive;
+use zip::ZipArchive;
+
+/// Tar pads every header and member to whole blocks
+const TAR_BLOCK: u64 = 512;
+
+/// One member of an archive listing
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArchiveMember {
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+    /// Stored size and CRC-32, recorded per member by zip only
+    pub packed: Option<(u64, u32)>,
+}
+
+/// Members read from one position onwards
+#[derive(Debug)]
+pub struct ArchivePage {
+    pub members: Vec<ArchiveMember>,
+    /// Member count of the whole archive, known up front for zip only
+    pub total: Option<usize>,
+    /// Where the next page starts, `None` once the archive is exhausted
+    pub next: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ListingKind {
+    Zip,
+    Tar,
+    TarGz,
+    /// 7z, rar, xz and bz2, read by the archive helper and listed from its tar output
+    Converted,
+}
+
+fn listing_kind(path: &Path) -> Option<ListingKind> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    match extension.as_str() {
+        "zip" => Some(ListingKind::Zip),
+        "tar" => Some(ListingKind::Tar),
+        "gz" | "tgz" => Some(ListingKind::TarGz),
+        "7z" | "rar" | "xz" | "txz" | "bz2" | "tbz" | "tbz2" => Some(ListingKind::Converted),
+        _ => None,
+    }
+}
+
+/// Whether listing `path` takes the archive helper (`Tool::Archiver`)
+pub fn needs_helper(path: &Path) -> bool {
+    listing_kind(path) == Some(ListingKind::Converted)
+}
+
+fn unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "unsupported archive format")
+}
+
+/// Command that rewrites other archive formats as tar (bsdtar); `None` until the
+/// external tools were probed and found one
+static HELPER: RwLock<Option<String>> = RwLock::new(None);
+
+/// Set the archive helper after the external tools were probed; `None` turns the
+/// 7z, rar, xz and bz2 listings off
+pub fn set_helper(command: Option<String>) {
+    *HELPER.write().unwrap_or_else(PoisonError::into_inner) = command;
+}
+
+/// How long the helper's stderr may stay open once its tar stream has ended
+const STDERR_GRACE: Duration = Duration::from_millis(500);
+
+/// The archive helper's tar rendering of an archive; a failed run surfaces as an
+/// error at the end of the stream, carrying the helper's last complaint
+struct HelperStream {
+    child: Child,
+    stdout: ChildStdout,
+    stderr: Receiver<String>,
+}
+
+impl HelperStream {
+    fn spawn(path: &Path) -> io::Result<Self> {
+        let command = HELPER
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "this format needs bsdtar, which was not found (see :diagnostics)",
+                )
+            })?;
+        let mut parts = command.split_whitespace();
+        let program = parts.next().ok_or_else(unsupported)?;
+        // "@archive" makes bsdtar copy the members of an existing archive
+        let mut source = OsString::from("@");
+        source.push(path);
+        let mut child = Command::new(program)
+            .args(parts)
+            .args(["-c", "-f", "-", "--format", "pax"])
+            .arg(source)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().ok_or_else(unsupported)?;
+        // Read on the side so a chatty helper can't fill the pipe and stall
+        let (stderr_tx, stderr) = channel();
+        if let Some(mut pipe) = child.stderr.take() {
+            thread::spawn(move || {
+                let mut text = String::new();
+                let _ = pipe.read_to_string(&mut text);
+                let _ = stderr_tx.send(text);
+            });
+        }
+        Ok(Self {
+            child,
+            stdout,
+            stderr,
+        })
+    }
+}
+
+impl Read for HelperStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.stdout.read(buf)?;
+        if read == 0 && !buf.is_empty() {
+            let status = self.child.wait()?;
+            if !status.success() {
+                let stderr = self.stderr.recv_timeout(STDERR_GRACE).unwrap_or_default();
+                return Err(io::Error::other(
+                    stderr
+                        .lines()
+                        .rev()
+                        .find(|l| !l.trim().is_empty())
+                        .map(|l| l.trim().to_string())
+                        .unwrap_or_else(|| status.to_string()),
+                ));
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl Drop for HelperStream {
+    fn drop(&mut self) {
+        // A listing left after one page must not keep the helper converting
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Tar stream positioned at `offset`; plain tar seeks there, a gzip or helper stream
+/// cannot, so everything before it is decompressed again and discarded
+fn tar_stream(path: &Path, kind: ListingKind, offset: u64) -> io::Result<Box<dyn Read>> {
+    let mut reader: Box<dyn Read> = match kind {
+        ListingKind::TarGz => Box::new(GzDecoder::new(fs::File::open(path)?)),
+        ListingKind::Converted => Box::new(HelperStream::spawn(path)?),
+        _ => {
+            let mut file = fs::File::open(path)?;
+            file.seek(SeekFrom::Start(offset))?;
+            return Ok(Box::new(file));
+        }
+    };
+    io::copy(&mut (&mut reader).take(offset), &mut io::sink())?;
+    Ok(reader)
+}
+
+/// Bytes a tar stream remembers for stepping back, enough for the long-name and
+/// extended headers read ahead of a page's first member
+const REPLAY_WINDOW: usize = 64 * 1024;
+
+/// Tar stream that can step back over the last bytes it handed out
+///
+/// Listing a page reads the header after its last member; the next page starts
+/// by reading that header again instead of reopening the stream.
+struct ReplayStream {
+    inner: Box<dyn Read>,
+    /// Offset of the next byte handed out
+    position: u64,
+    /// The last bytes handed out, up to `REPLAY_WINDOW`
+    window: VecDeque<u8>,
+    /// Bytes stepped back over, handed out again before `inner` is read
+    replay: VecDeque<u8>,
+}
+
+impl ReplayStream {
+    fn new(inner: Box<dyn Read>, position: u64) -> Self {
+        Self {
+            inner,
+            position,
+            window: VecDeque::new(),
+            replay: VecDeque::new(),
+        }
+    }
+
+    /// Continue from `offset` again; false when it is outside the window
+    fn rewind(&mut self, offset: u64) -> bool {
+        let Some(back) = self
+            .position
+            .checked_sub(offset)
+            .and_then(|back| usize::try_from(back).ok())
+            .filter(|&back| back <= self.window.len())
+        else {
+            return false;
+        };
+        for byte in self.window.drain(self.window.len() - back..).rev() {
+            self.replay.push_front(byte);
+        }
+        self.position = offset;
+        true
+    }
+}
+
+impl Read for ReplayStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = if self.replay.is_empty() {
+            self.inner.read(buf)?
+        } else {
+            self.replay.read(buf)?
+        };
+        let data = &buf[..read];
+        if data.len() >= REPLAY_WINDOW {
+            self.window.clear();
+            self.window.extend(&data[data.len() - REPLAY_WINDOW..]);
+        } else {
+            let overflow = (self.window.len() + data.len()).saturating_sub(REPLAY_WINDOW);
+            self.window.drain(..overflow);
+            self.window.extend(data);
+        }
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+enum Source {
+    Zip(ZipArchive<fs::File>),
+    Tar(ReplayStream),
+}
+
+/// An archive listing in progress
+///
+/// The IO worker keeps the cursor of the archive being previewed, so each
+/// "Load more" continues with the parsed zip directory or the open tar stream
+/// instead of starting over from the beginning of the file.
+pub struct ArchiveCursor {
+    path: PathBuf,
+    kind: ListingKind,
+    source: Source,
+    /// Where the next page starts: a member index for zip and the offset of a
+    /// header in the (decompressed) tar stream otherwise
+    next: u64,
+}
+
+impl ArchiveCursor {
+    /// Open `path` with the listing resuming at `resume`, as returned in `ArchivePage::next`
+    pub fn open(path: &Path, resume: u64) -> io::Result<Self> {
+        let kind = listing_kind(path).ok_or_else(unsupported)?;
+        let source = match kind {
+            ListingKind::Zip => Source::Zip(ZipArchive::new(fs::File::open(path)?)?),
+            _ => Source::Tar(ReplayStream::new(tar_stream(path, kind, resume)?, resume)),
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            kind,
+            source,
+            next: resume,
+        })
+    }
+
+    /// Where the next page starts
+    pub fn position(&self) -> u64 {
+        self.next
+    }
+
+    /// Read up to `limit` members from the current position
+    pub fn next_page(&mut self, limit: usize) -> io::Result<ArchivePage> {
+        let resume = self.next;
+        let stream = match &mut self.source {
+            Source::Zip(archive) => {
+                let total = archive.len();
+                let start = usize::try_from(resume).unwrap_or(total).min(total);
+                let end = start.saturating_add(limit).min(total);
+                let members = (start..end)
+                    .filter_map(|i| {
+                        let file = archive.by_index_raw(i).ok()?;
+                        Some(ArchiveMember {
+                            name: file.name().to_string(),
+                            size: file.size(),
+                            is_dir: file.is_dir(),
+                            packed: Some((file.compressed_size(), file.crc32())),
+                        })
+                    })
+                    .collect();
+                self.next = end as u64;
+                return Ok(ArchivePage {
+                    members,
+                    total: Some(total),
+                    next: (end < total).then_some(end as u64),
+                });
+            }
+            Source::Tar(stream) => stream,
+        };
+
+        let mut archive = Archive::new(&mut *stream);
+        let mut members = Vec::new();
+        // End of the last listed member, relative to `resume`
+        let mut end = 0;
+        let mut next = None;
+        // A damaged stream (or a .gz that holds no tar) ends the listing at the first error
+        for entry in archive.entries()?.map_while(Result::ok) {
+            if members.len() == limit {
+                // Long-name and extended headers precede their member, so resume right after
+                // the previous member rather than at this entry's own header
+                next = Some(resume + end);
+                break;
+            }
+            let header = entry.header();
+            let stored = header.entry_size().unwrap_or(0);
+            end = entry.raw_file_position() + stored.next_multiple_of(TAR_BLOCK);
+            members.push(ArchiveMember {
+                name: entry
+                    .path()
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                size: header.size().unwrap_or(0),
+                is_dir: header.entry_type().is_dir(),
+                packed: None,
+            });
+        }
+        if next.is_none() && self.kind == ListingKind::Converted {
+            // The helper writes an empty tar for an archive it cannot read and only
+            // its exit status tells; that is an error rather than an empty listing
+            if let Err(e) = io::copy(stream, &mut io::sink()) {
+                if resume == 0 && members.is_empty() {
+                    return Err(e);
+                }
+            }
+        }
+        if let Some(next) = next {
+            self.next = next;
+            if !stream.rewind(next) {
+                // Headers larger than the window: reopen, skipping to the next page
+                *stream = ReplayStream::new(tar_stream(&self.path, self.kind, next)?, next);
+            }
+        }
+        Ok(ArchivePage {
+            members,
+            total: None,
+            next,
+        })
+    }
+}
+
+/// Member count and unpacked size of a whole archive
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArchiveTotals {
+    pub members: u64,
+    pub bytes: u64,
+}
+
+impl ArchiveTotals {
+    /// Encode for the preview cache, which stores sections as strings
+    pub fn to_section(self) -> String {
+        format!("{} {}", self.members, self.bytes)
+    }
+
+    pub fn from_section(value: &str) -> Option<Self> {
+        let (members, bytes) = value.split_once(' ')?;
+        Some(Self {
+            members: members.parse().ok()?,
+            bytes: bytes.parse().ok()?,
+        })
+    }
+}
+
+/// Visit every member header; for tar this reads the whole (decompressed) stream
+pub fn count_archive(path: &Path) -> io::Result<ArchiveTotals> {
+    let kind = listing_kind(path).ok_or_else(unsupported)?;
+    let mut totals = ArchiveTotals::default();
+    if kind == ListingKind::Zip {
+        let mut archive = ZipArchive::new(fs::File::open(path)?)?;
+        for i in 0..archive.len() {
+            if let Ok(file) = archive.by_index_raw(i) {
+                totals.members += 1;
+                totals.bytes += file.size();
+            }
+        }
+        return Ok(totals);
+    }
+    let mut archive = Archive::new(tar_stream(path, kind, 0)?);
+    for entry in archive.entries()?.map_while(Result::ok) {
+        totals.members += 1;
+        totals.bytes += entry.header().size().unwrap_or(0);
+    }
+    if kind == ListingKind::Converted && totals.members == 0 {
+        // An archive the helper cannot read comes out as an empty tar and a failed exit
+        io::copy(&mut archive.into_inner(), &mut io::sink())?;
+    }
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use tempfile::TempDir;
+
+    #[test]
+    fn archive_pages_resume_where_the_previous_one_stopped() {
+        let dir = TempDir::new().unwrap();
+        let long_name = format!("{}/deep.txt", "nested".repeat(20));
+        let names = ["a.txt", "b.txt", long_name.as_str(), "c.txt", "d.txt"];
+        let mut builder = tar::Builder::new(Vec::new());
+        for (i, name) in names.iter().enumerate() {
+            let data = vec![b'x'; i * 300];
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            builder
+                .append_data(&mut header, name, data.as_slice())
+                .unwrap();
+        }
+        let tar_bytes = builder.into_inner().unwrap();
+        let tar_path = dir.path().join("pages.tar");
+        fs::write(&tar_path, &tar_bytes).unwrap();
+        let gz_path = dir.path().join("pages.tgz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        io::Write::write_all(&mut encoder, &tar_bytes).unwrap();
+        fs::write(&gz_path, encoder.finish().unwrap()).unwrap();
+        let expected: Vec<(String, u64)> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.to_string(), i as u64 * 300))
+            .collect();
+
+        for path in [&tar_path, &gz_path] {
+            // One cursor kept open across pages, as the worker does, and a fresh one
+            // reopened at each position, as after previewing another archive
+            let mut kept = ArchiveCursor::open(path, 0).unwrap();
+            let mut listed = Vec::new();
+            let mut reopened = Vec::new();
+            let mut resume = 0;
+            loop {
+                let page = kept.next_page(2).unwrap();
+                assert_eq!(page.total, None, "tar length is unknown up front");
+                let again = ArchiveCursor::open(path, resume)
+                    .unwrap()
+                    .next_page(2)
+                    .unwrap();
+                assert_eq!(again.next, page.next);
+                listed.extend(page.members.into_iter().map(|m| (m.name, m.size)));
+                reopened.extend(again.members.into_iter().map(|m| (m.name, m.size)));
+                match page.next {
+                    Some(next) => {
+                        assert_eq!(kept.position(), next);
+                        resume = next;
+                    }
+                    None => break,
+                }
+            }
+            assert_eq!(listed, expected, "{}", path.display());
+            assert_eq!(reopened, expected, "{}", path.display());
+
+            let totals = count_archive(path).unwrap();
+            assert_eq!((totals.members, totals.bytes), (5, 3000));
+        }
+    }
+
+    #[test]
+    fn other_formats_are_listed_through_the_helper() {
+        // Only runs where bsdtar is installed
+        if crate::io::tools::find_executable("bsdtar").is_none() {
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        for (name, size) in [("a.txt", 10), ("b.txt", 2000), ("c.txt", 0)] {
+            fs::write(dir.path().join(name), vec![b'x'; size]).unwrap();
+        }
+        let archive = dir.path().join("files.tar.xz");
+        let created = Command::new("bsdtar")
+            .args(["-c", "-J", "-f"])
+            .arg(&archive)
+            .args(["-C"])
+            .arg(dir.path())
+            .args(["a.txt", "b.txt", "c.txt"])
+            .status()
+            .unwrap();
+        assert!(created.success());
+        let broken = dir.path().join("broken.7z");
+        fs::write(&broken, b"not an archive").unwrap();
+
+        set_helper(None);
+        assert!(needs_helper(&archive));
+        let missing = ArchiveCursor::open(&archive, 0).err().unwrap();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+
+        set_helper(Some("bsdtar".into()));
+        let mut cursor = ArchiveCursor::open(&archive, 0).unwrap();
+        let first = cursor.next_page(2).unwrap();
+        let rest = ArchiveCursor::open(&archive, first.next.unwrap())
+            .unwrap()
+            .next_page(2)
+            .unwrap();
+        let listed: Vec<(String, u64)> = first
+            .members
+            .into_iter()
+            .chain(rest.members)
+            .map(|m| (m.name, m.size))
+            .collect();
+        assert_eq!(
+            listed,
+            [("a.txt", 10), ("b.txt", 2000), ("c.txt", 0)].map(|(n, s)| (n.to_string(), s))
+        );
+        assert_eq!(rest.next, None);
+        let totals = count_archive(&archive).unwrap();
+        assert_eq!((totals.members, totals.bytes), (3, 2010));
+
+        // The helper's complaint comes back instead of an empty listing
+        let error = ArchiveCursor::open(&broken, 0)
+            .unwrap()
+            .next_page(10)
+            .unwrap_err();
+        assert!(!error.to_string().is_empty());
+        assert!(count_archive(&broken).is_err());
+    }
+}
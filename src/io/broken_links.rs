@@ -0,0 +1,379 @@
+use super::cleanup::SKIPPED_DIRS;
+use super::tools::find_executable;
+use crate::state::{BrokenLink, LinkKind};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Stop collecting after this many links to keep the report responsive
+const MAX_BROKEN_LINKS: usize = 10_000;
+
+/// Launchers and shortcuts are small; anything bigger is not one
+const MAX_SHORTCUT_BYTES: u64 = 64 * 1024;
+
+/// Find symlinks whose target is gone, and `.desktop`/`.lnk` shortcuts pointing at
+/// missing files or programs, below `root`. Symlinked directories are not descended into.
+pub fn find_broken_links(root: &Path) -> Result<Vec<BrokenLink>, io::Error> {
+    let mut links = Vec::new();
+    scan_dir(root, &mut links)?;
+    links.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(links)
+}
+
+fn scan_dir(dir: &Path, links: &mut Vec<BrokenLink>) -> Result<(), io::Error> {
+    for entry in fs::read_dir(dir)?.filter_map(|entry| entry.ok()) {
+        if links.len() >= MAX_BROKEN_LINKS {
+            return Ok(());
+        }
+        let path = entry.path();
+        let Ok(meta) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if meta.is_symlink() {
+            if fs::metadata(&path).is_err() {
+                let target = fs::read_link(&path).unwrap_or_default();
+                links.push(BrokenLink {
+                    path,
+                    kind: LinkKind::Symlink,
+                    target: target.display().to_string(),
+                });
+            }
+        } else if meta.is_dir() {
+            if !SKIPPED_DIRS
+                .iter()
+                .any(|skipped| entry.file_name() == *skipped)
+            {
+                // Unreadable subdirectories are skipped rather than failing the whole scan
+                let _ = scan_dir(&path, links);
+            }
+        } else if meta.len() <= MAX_SHORTCUT_BYTES {
+            if let Some(link) = dangling_shortcut(&path) {
+                links.push(link);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A `.desktop` or `.lnk` file whose target is missing
+fn dangling_shortcut(path: &Path) -> Option<BrokenLink> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    let (kind, target) = match extension.as_str() {
+        "desktop" => {
+            let contents = fs::read_to_string(path).ok()?;
+            let target = desktop_target(&contents)?;
+            let missing = match &target {
+                DesktopTarget::File(file) => !Path::new(file).exists(),
+                DesktopTarget::Program(program) => find_executable(program).is_none(),
+            };
+            (
+                LinkKind::DesktopEntry,
+                missing.then(|| target.text().to_string())?,
+            )
+        }
+        "lnk" => {
+            let target = shortcut_target(&fs::read(path).ok()?)?;
+            // Drive paths only mean something on the system that made them
+            let target_path = Path::new(&target);
+            if !target_path.is_absolute() || target_path.exists() {
+                return None;
+            }
+            (LinkKind::Shortcut, target)
+        }
+        _ => return None,
+    };
+    Some(BrokenLink {
+        path: path.to_path_buf(),
+        kind,
+        target,
+    })
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DesktopTarget {
+    /// `Type=Link` entries: a local file from their `file://` URL
+    File(String),
+    /// Application entries: `TryExec`, or the program `Exec` starts
+    Program(String),
+}
+
+impl DesktopTarget {
+    fn text(&self) -> &str {
+        match self {
+            DesktopTarget::File(text) | DesktopTarget::Program(text) => text,
+        }
+    }
+}
+
+/// Key/value pairs of the `[Desktop Entry]` group
+fn desktop_entry(contents: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut in_entry = false;
+    contents.lines().filter_map(move |line| {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            return None;
+        }
+        if !in_entry {
+            return None;
+        }
+        let (key, value) = line.split_once('=')?;
+        Some((key.trim(), value.trim()))
+    })
+}
+
+/// What a `.desktop` file points at, when it is something we can check
+pub fn desktop_target(contents: &str) -> Option<DesktopTarget> {
+    let mut kind = None;
+    let (mut url, mut try_exec, mut exec) = (None, None, None);
+    for (key, value) in desktop_entry(contents) {
+        match key {
+            "Type" => kind = Some(value),
+            "URL" => url = Some(value),
+            "TryExec" => try_exec = Some(value),
+            "Exec" => exec = Some(value),
+            _ => {}
+        }
+    }
+    if kind == Some("Link") {
+        let path = url?.strip_prefix("file://")?;
+        return Some(DesktopTarget::File(percent_decode(path)));
+    }
+    let program = try_exec.or_else(|| exec.and_then(exec_program))?;
+    Some(DesktopTarget::Program(program.to_string()))
+}
+
+/// The program of an `Exec` line, without quotes
+fn exec_program(exec: &str) -> Option<&str> {
+    match exec.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next(),
+        None => exec.split_whitespace().next(),
+    }
+    .filter(|program| !program.is_empty())
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn percent_encode(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Local target path of a Windows shell link (MS-SHLLINK), from its LinkInfo block
+pub fn shortcut_target(data: &[u8]) -> Option<String> {
+    const HEADER_SIZE: usize = 0x4C;
+    const HAS_ID_LIST: u32 = 0x1;
+    const HAS_LINK_INFO: u32 = 0x2;
+    const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x1;
+
+    let u16_at = |at: usize| Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?));
+    let u32_at = |at: usize| Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    let string_at = |at: usize| {
+        let bytes = data.get(at..)?;
+        let end = bytes.iter().position(|b| *b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    };
+
+    if u32_at(0)? != HEADER_SIZE as u32 {
+        return None;
+    }
+    let flags = u32_at(0x14)?;
+    let mut offset = HEADER_SIZE;
+    if flags & HAS_ID_LIST != 0 {
+        offset += 2 + u16_at(offset)? as usize;
+    }
+    if flags & HAS_LINK_INFO == 0 || u32_at(offset + 8)? & VOLUME_ID_AND_LOCAL_BASE_PATH == 0 {
+        return None;
+    }
+    let base = string_at(offset + u32_at(offset + 16)? as usize)?;
+    let suffix = string_at(offset + u32_at(offset + 24)? as usize).unwrap_or_default();
+    Some(base + &suffix)
+}
+
+/// Point a broken link at `new_target`: symlinks are recreated, launchers have the
+/// target in their `URL`, `TryExec` and `Exec` lines replaced
+pub fn retarget(link: &BrokenLink, new_target: &str) -> Result<(), String> {
+    match link.kind {
+        LinkKind::Symlink => {
+            fs::remove_file(&link.path).map_err(|e| e.to_string())?;
+            #[cfg(unix)]
+            let result = std::os::unix::fs::symlink(new_target, &link.path);
+            #[cfg(windows)]
+            let result = {
+                let resolved = link.path.parent().map_or_else(
+                    || std::path::PathBuf::from(new_target),
+                    |dir| dir.join(new_target),
+                );
+                if resolved.is_dir() {
+                    std::os::windows::fs::symlink_dir(new_target, &link.path)
+                } else {
+                    std::os::windows::fs::symlink_file(new_target, &link.path)
+                }
+            };
+            result.map_err(|e| e.to_string())
+        }
+        LinkKind::DesktopEntry => {
+            let contents = fs::read_to_string(&link.path).map_err(|e| e.to_string())?;
+            let rewritten = retarget_desktop(&contents, new_target);
+            fs::write(&link.path, rewritten).map_err(|e| e.to_string())
+        }
+        LinkKind::Shortcut => Err("Windows shortcuts can only be deleted".into()),
+    }
+}
+
+/// `contents` with its `[Desktop Entry]` target swapped for `new_target`
+pub fn retarget_desktop(contents: &str, new_target: &str) -> String {
+    let old = desktop_target(contents);
+    let mut in_entry = false;
+    let mut lines: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                in_entry = trimmed == "[Desktop Entry]";
+            }
+            let Some((key, value)) = trimmed.split_once('=').filter(|_| in_entry) else {
+                return line.to_string();
+            };
+            let value = value.trim();
+            match (key.trim(), &old) {
+                ("URL", Some(DesktopTarget::File(_))) => {
+                    format!("URL=file://{}", percent_encode(new_target))
+                }
+                ("TryExec", Some(DesktopTarget::Program(_))) => format!("TryExec={}", new_target),
+                ("Exec", Some(DesktopTarget::Program(_))) => match exec_program(value) {
+                    Some(program) => {
+                        let quoted = value.starts_with('"');
+                        let rest = &value[program.len() + if quoted { 2 } else { 0 }..];
+                        if new_target.contains(' ') {
+                            format!("Exec=\"{}\"{}", new_target, rest)
+                        } else {
+                            format!("Exec={}{}", new_target, rest)
+                        }
+                    }
+                    None => line.to_string(),
+                },
+                _ => line.to_string(),
+            }
+        })
+        .collect();
+    if contents.ends_with('\n') {
+        lines.push(String::new());
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn broken_link_scan_finds_dangling_links_and_launchers() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        let launcher = "[Desktop Entry]\nType=Application\nExec=\"no such program\" %U\n";
+        fs::write(dir.path().join("app.desktop"), launcher).unwrap();
+        fs::write(
+            dir.path().join("docs/gone.desktop"),
+            "[Desktop Entry]\nType=Link\nURL=file:///no/such%20file\n",
+        )
+        .unwrap();
+        assert_eq!(
+            desktop_target(launcher),
+            Some(DesktopTarget::Program("no such program".into()))
+        );
+        assert_eq!(
+            retarget_desktop(launcher, "/usr/bin/true"),
+            "[Desktop Entry]\nType=Application\nExec=/usr/bin/true %U\n"
+        );
+
+        // Header, flags HasLinkInfo, then a LinkInfo block whose local base path is C:\gone.txt
+        let mut lnk = vec![0u8; 0x4C];
+        lnk[0] = 0x4C;
+        lnk[0x14] = 0x2;
+        let mut info = vec![0u8; 28];
+        info[8] = 0x1;
+        info[16] = 28;
+        info[24] = 28 + 12;
+        info.extend(b"C:\\gone.txt\0\0");
+        lnk.extend(info);
+        assert_eq!(shortcut_target(&lnk).as_deref(), Some("C:\\gone.txt"));
+
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("missing.txt", dir.path().join("src/dangling")).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("../a.txt", dir.path().join("src/fine")).unwrap();
+
+        let found = find_broken_links(dir.path()).unwrap();
+        let summary: Vec<(String, LinkKind, String)> = found
+            .iter()
+            .map(|link| {
+                let relative = link.path.strip_prefix(dir.path()).unwrap();
+                (
+                    relative.display().to_string(),
+                    link.kind,
+                    link.target.clone(),
+                )
+            })
+            .collect();
+        let mut expected = vec![
+            (
+                "app.desktop".to_string(),
+                LinkKind::DesktopEntry,
+                "no such program".to_string(),
+            ),
+            (
+                "docs/gone.desktop".to_string(),
+                LinkKind::DesktopEntry,
+                "/no/such file".to_string(),
+            ),
+        ];
+        if cfg!(unix) {
+            expected.push((
+                "src/dangling".to_string(),
+                LinkKind::Symlink,
+                "missing.txt".to_string(),
+            ));
+        }
+        assert_eq!(summary, expected);
+
+        if cfg!(unix) {
+            retarget(&found[2], "../notes.txt").unwrap();
+            assert_eq!(
+                fs::read_to_string(dir.path().join("src/dangling")).unwrap(),
+                "notes"
+            );
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::state::CleanupItem;
+use std::fs;
+use std::path::Path;
+
+/// Stop listing after this many items to keep the dialog responsive
+const MAX_CLEANUP_ITEMS: usize = 10_000;
+
+/// Version control metadata keeps empty files and folders on purpose
+pub const SKIPPED_DIRS: &[&str] = &[".git", ".hg", ".svn"];
+
+/// Find empty directories and zero-byte files below `root`.
+///
+/// A directory counts as empty when nothing but empty directories lies beneath it; it is
+/// listed once, without its empty subdirectories. Symlinks are never followed and keep
+/// their directory from counting as empty. The root itself is not listed.
+pub fn find_empty(root: &Path) -> Result<Vec<CleanupItem>, std::io::Error> {
+    let mut items = Vec::new();
+    scan_level(root, Path::new(""), &mut items)?;
+    items.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    Ok(items)
+}
+
+/// Scan one directory, returning whether it holds nothing but empty directories
+fn scan_level(
+    root: &Path,
+    relative: &Path,
+    items: &mut Vec<CleanupItem>,
+) -> Result<bool, std::io::Error> {
+    let mut children: Vec<_> = fs::read_dir(root.join(relative))?
+        .filter_map(|entry| entry.ok())
+        .collect();
+    children.sort_by_key(|entry| entry.file_name());
+
+    let mut empty = true;
+    let mut empty_dirs = Vec::new();
+    for child in children {
+        if items.len() + empty_dirs.len() >= MAX_CLEANUP_ITEMS {
+            empty = false;
+            break;
+        }
+        let name = child.file_name();
+        let relative_path = relative.join(&name);
+        let Ok(meta) = fs::symlink_metadata(child.path()) else {
+            empty = false;
+            continue;
+        };
+        if meta.is_dir() {
+            if SKIPPED_DIRS.iter().any(|skipped| name == *skipped) {
+                empty = false;
+                continue;
+            }
+            let start = items.len();
+            // Unreadable subdirectories are left alone rather than failing the scan
+            if scan_level(root, &relative_path, items).unwrap_or(false) {
+                items.truncate(start);
+                empty_dirs.push(relative_path);
+            } else {
+                empty = false;
+            }
+        } else {
+            if meta.is_file() && meta.len() == 0 {
+                items.push(CleanupItem::new(relative_path, false));
+            }
+            empty = false;
+        }
+    }
+    if !empty {
+        items.extend(
+            empty_dirs
+                .into_iter()
+                .map(|path| CleanupItem::new(path, true)),
+        );
+    }
+    Ok(empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn empty_scan_lists_outermost_empty_folders_and_zero_byte_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("docs/inner/deeper")).unwrap();
+        fs::create_dir_all(dir.path().join("build/cache")).unwrap();
+        fs::write(dir.path().join("build/keep.log"), "log").unwrap();
+        fs::write(dir.path().join("src/empty.rs"), "").unwrap();
+        fs::create_dir_all(dir.path().join(".git/refs/tags")).unwrap();
+        fs::write(dir.path().join(".git/index.lock"), "").unwrap();
+
+        let found: Vec<(String, bool)> = find_empty(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|item| {
+                assert!(item.checked);
+                (item.relative_path.display().to_string(), item.is_dir)
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("build/cache".to_string(), true),
+                ("docs".to_string(), true),
+                ("src/empty.rs".to_string(), false),
+            ]
+        );
+    }
+}
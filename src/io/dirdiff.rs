@@ -0,0 +1,183 @@
+use crate::state::{DiffKind, DirDiffEntry};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Stop collecting differences after this many entries to keep the report responsive
+const MAX_DIFF_ENTRIES: usize = 10_000;
+
+/// Modification times closer than this are treated as equal (FAT stores 2s resolution)
+const MTIME_TOLERANCE_SECS: u64 = 2;
+
+struct NodeInfo {
+    is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Compare two directory trees by name, size and mtime (or content when requested).
+///
+/// Directories present on both sides are descended into; a directory that exists on
+/// only one side is reported once without listing its children. Symlinks are compared
+/// as plain entries and never followed. The flag is true when the report stopped at
+/// `MAX_DIFF_ENTRIES` and more differences were left out.
+pub fn compare_directories(
+    left: &Path,
+    right: &Path,
+    compare_contents: bool,
+) -> Result<(Vec<DirDiffEntry>, bool), std::io::Error> {
+    let mut diffs = Vec::new();
+    let mut truncated = false;
+    compare_level(
+        left,
+        right,
+        Path::new(""),
+        compare_contents,
+        &mut diffs,
+        &mut truncated,
+    )?;
+    Ok((diffs, truncated))
+}
+
+fn compare_level(
+    left_root: &Path,
+    right_root: &Path,
+    relative: &Path,
+    compare_contents: bool,
+    diffs: &mut Vec<DirDiffEntry>,
+    truncated: &mut bool,
+) -> Result<(), std::io::Error> {
+    let left_dir = left_root.join(relative);
+    let right_dir = right_root.join(relative);
+    let left_entries = list_dir(&left_dir)?;
+    let right_entries = list_dir(&right_dir)?;
+
+    let names: BTreeSet<&OsString> = left_entries.keys().chain(right_entries.keys()).collect();
+    for name in names {
+        if diffs.len() >= MAX_DIFF_ENTRIES {
+            *truncated = true;
+            return Ok(());
+        }
+        let relative_path = relative.join(name);
+        match (left_entries.get(name), right_entries.get(name)) {
+            (Some(l), None) => diffs.push(DirDiffEntry {
+                relative_path,
+                kind: DiffKind::OnlyLeft,
+                is_dir: l.is_dir,
+                left_size: Some(l.size),
+                right_size: None,
+            }),
+            (None, Some(r)) => diffs.push(DirDiffEntry {
+                relative_path,
+                kind: DiffKind::OnlyRight,
+                is_dir: r.is_dir,
+                left_size: None,
+                right_size: Some(r.size),
+            }),
+            (Some(l), Some(r)) => {
+                if l.is_dir && r.is_dir {
+                    // Unreadable subdirectories are skipped rather than failing the whole report
+                    let _ = compare_level(
+                        left_root,
+                        right_root,
+                        &relative_path,
+                        compare_contents,
+                        diffs,
+                        truncated,
+                    );
+                } else if l.is_dir != r.is_dir
+                    || files_differ(
+                        l,
+                        r,
+                        &left_dir.join(name),
+                        &right_dir.join(name),
+                        compare_contents,
+                    )
+                {
+                    diffs.push(DirDiffEntry {
+                        relative_path,
+                        kind: DiffKind::Different,
+                        is_dir: l.is_dir,
+                        left_size: Some(l.size),
+                        right_size: Some(r.size),
+                    });
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    Ok(())
+}
+
+fn list_dir(path: &Path) -> Result<BTreeMap<OsString, NodeInfo>, std::io::Error> {
+    let mut entries = BTreeMap::new();
+    for entry in fs::read_dir(path)?.flatten() {
+        if let Ok(meta) = fs::symlink_metadata(entry.path()) {
+            entries.insert(
+                entry.file_name(),
+                NodeInfo {
+                    is_dir: meta.is_dir(),
+                    size: if meta.is_dir() { 0 } else { meta.len() },
+                    modified: meta.modified().ok(),
+                },
+            );
+        }
+    }
+    Ok(entries)
+}
+
+fn files_differ(
+    left: &NodeInfo,
+    right: &NodeInfo,
+    left_path: &Path,
+    right_path: &Path,
+    compare_contents: bool,
+) -> bool {
+    if left.size != right.size {
+        return true;
+    }
+    if compare_contents {
+        return !contents_equal(left_path, right_path).unwrap_or(false);
+    }
+    match (left.modified, right.modified) {
+        (Some(l), Some(r)) => {
+            let delta = l.duration_since(r).or_else(|_| r.duration_since(l));
+            delta
+                .map(|d| d.as_secs() > MTIME_TOLERANCE_SECS)
+                .unwrap_or(true)
+        }
+        _ => false,
+    }
+}
+
+fn contents_equal(left: &Path, right: &Path) -> Result<bool, std::io::Error> {
+    let mut left_file = fs::File::open(left)?;
+    let mut right_file = fs::File::open(right)?;
+    let mut left_buf = vec![0u8; 64 * 1024];
+    let mut right_buf = vec![0u8; 64 * 1024];
+    loop {
+        let left_len = read_full(&mut left_file, &mut left_buf)?;
+        let right_len = read_full(&mut right_file, &mut right_buf)?;
+        if left_len != right_len || left_buf[..left_len] != right_buf[..right_len] {
+            return Ok(false);
+        }
+        if left_len == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+/// Read until the buffer is full or EOF is reached
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
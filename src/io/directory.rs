@@ -1,16 +1,15 @@
-use crate::entry::{FileEntry, GitStatus};
+use crate::entry::{compare_names, mime_from_extension, EntryDetails, FileEntry, GitStatus};
+use ignore::WalkBuilder;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
 
 pub fn read_directory(path: &Path, show_hidden: bool) -> Result<Vec<FileEntry>, std::io::Error> {
     let mut entries = Vec::new();
     let read_dir = fs::read_dir(path)?;
 
-    // Fetch git statuses for the directory
-    let git_statuses = get_git_statuses(path);
-
     for entry in read_dir.flatten() {
         let path = entry.path();
         if !show_hidden {
@@ -20,10 +19,7 @@ pub fn read_directory(path: &Path, show_hidden: bool) -> Result<Vec<FileEntry>,
                 }
             }
         }
-        if let Some(mut file_entry) = FileEntry::from_path(path) {
-            if let Some(status) = git_statuses.get(&file_entry.name) {
-                file_entry.git_status = Some(status.clone());
-            }
+        if let Some(file_entry) = FileEntry::from_path(path) {
             entries.push(file_entry);
         }
     }
@@ -31,13 +27,364 @@ pub fn read_directory(path: &Path, show_hidden: bool) -> Result<Vec<FileEntry>,
         if a.is_dir != b.is_dir {
             return b.is_dir.cmp(&a.is_dir);
         }
-        a.name.to_lowercase().cmp(&b.name.to_lowercase())
+        compare_names(&a.name, &b.name)
     });
     Ok(entries)
 }
 
+/// Deepest level descended into by the flatten view
+pub const FLATTEN_MAX_DEPTH: usize = 12;
+/// Maximum number of files listed by the flatten view
+pub const FLATTEN_MAX_ENTRIES: usize = 10_000;
+/// Files per batch streamed back to the UI
+const FLATTEN_BATCH_SIZE: usize = 500;
+
+/// List every file below `root`, handing them to `on_batch` in chunks
+///
+/// Entry names are paths relative to `root` so the flat list stays readable.
+/// Symlinked directories are not followed. Returns true when the walk stopped
+/// at `FLATTEN_MAX_DEPTH` or `FLATTEN_MAX_ENTRIES`.
+pub fn read_flattened(
+    root: &Path,
+    show_hidden: bool,
+    mut on_batch: impl FnMut(Vec<FileEntry>),
+) -> Result<bool, std::io::Error> {
+    let mut batch = Vec::with_capacity(FLATTEN_BATCH_SIZE);
+    let mut count = 0;
+    let mut truncated = false;
+    let mut queue = std::collections::VecDeque::from([(root.to_path_buf(), 0usize)]);
+    let mut first = true;
+
+    while let Some((dir, depth)) = queue.pop_front() {
+        let read_dir = match fs::read_dir(&dir) {
+            Ok(read_dir) => read_dir,
+            // The root must be readable; unreadable subdirectories are skipped
+            Err(e) if first => return Err(e),
+            Err(_) => continue,
+        };
+        first = false;
+
+        let mut children: Vec<_> = read_dir.flatten().collect();
+        children.sort_by_key(|c| c.file_name());
+        for child in children {
+            if !show_hidden && child.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = child.path();
+            let is_real_dir = child.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if is_real_dir {
+                if depth + 1 < FLATTEN_MAX_DEPTH {
+                    queue.push_back((path, depth + 1));
+                } else {
+                    truncated = true;
+                }
+                continue;
+            }
+            if count >= FLATTEN_MAX_ENTRIES {
+                on_batch(batch);
+                return Ok(true);
+            }
+            if let Some(mut entry) = FileEntry::from_path(path) {
+                if entry.is_dir {
+                    continue;
+                }
+                if let Ok(relative) = entry.path.strip_prefix(root) {
+                    entry.name = relative.to_string_lossy().replace('\\', "/");
+                }
+                batch.push(entry);
+                count += 1;
+                if batch.len() == FLATTEN_BATCH_SIZE {
+                    on_batch(std::mem::replace(
+                        &mut batch,
+                        Vec::with_capacity(FLATTEN_BATCH_SIZE),
+                    ));
+                }
+            }
+        }
+    }
+    on_batch(batch);
+    Ok(truncated)
+}
+
+/// Maximum number of entries visited when totalling a directory's size
+pub const DIR_SIZE_ENTRY_LIMIT: usize = 20_000;
+
+/// File count and size totals for a directory tree
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DirUsage {
+    pub bytes: u64,
+    pub files: u64,
+    /// Totals without hidden and git-ignored files, when they were requested
+    pub source: Option<(u64, u64)>,
+    /// A walk hit `DIR_SIZE_ENTRY_LIMIT`, so the totals are lower bounds
+    pub truncated: bool,
+}
+
+impl DirUsage {
+    /// Encode for the preview cache, which stores sections as strings
+    pub fn to_section(self) -> String {
+        let mut value = format!("{} {}", self.bytes, self.files);
+        if let Some((bytes, files)) = self.source {
+            value.push_str(&format!(" {} {}", bytes, files));
+        }
+        if self.truncated {
+            value.push('+');
+        }
+        value
+    }
+
+    pub fn from_section(value: &str) -> Option<Self> {
+        let truncated = value.ends_with('+');
+        let numbers = value
+            .trim_end_matches('+')
+            .split(' ')
+            .map(|n| n.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let (bytes, files, source) = match numbers[..] {
+            [bytes, files] => (bytes, files, None),
+            [bytes, files, source_bytes, source_files] => {
+                (bytes, files, Some((source_bytes, source_files)))
+            }
+            _ => return None,
+        };
+        Some(Self {
+            bytes,
+            files,
+            source,
+            truncated,
+        })
+    }
+}
+
+/// Leave folders named in `skip` (e.g. node_modules) out of a walk; its roots are kept
+pub fn skip_dirs(walker: &mut WalkBuilder, skip: &[String]) {
+    if skip.is_empty() {
+        return;
+    }
+    let skip = skip.to_vec();
+    walker.filter_entry(move |entry| {
+        entry.depth() == 0
+            || !entry.file_type().is_some_and(|t| t.is_dir())
+            || !is_skipped_dir(entry.path(), &skip)
+    });
+}
+
+fn is_skipped_dir(path: &Path, skip: &[String]) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| skip.iter().any(|s| s == name))
+}
+
+/// Total size of all files below `path`, without following symlinks
+///
+/// Stops after `DIR_SIZE_ENTRY_LIMIT` entries; the flag is true when the
+/// total is therefore a lower bound.
+pub fn directory_size(path: &Path) -> (u64, bool) {
+    let (bytes, _, truncated) = total_usage(path, &[], &|| false);
+    (bytes, truncated)
+}
+
+/// Sizes and file counts below `path`, leaving out folders named in `skip`; with
+/// `source` also the totals a gitignore-aware walk that skips hidden files sees
+///
+/// `cancelled` is polled during the walks; `None` means it returned true.
+pub fn directory_usage(
+    path: &Path,
+    source: bool,
+    skip: &[String],
+    cancelled: &dyn Fn() -> bool,
+) -> Option<DirUsage> {
+    let (bytes, files, mut truncated) = total_usage(path, skip, cancelled);
+    let source = source.then(|| {
+        let (bytes, files, capped) = source_usage(path, skip, cancelled);
+        truncated |= capped;
+        (bytes, files)
+    });
+    if cancelled() {
+        return None;
+    }
+    Some(DirUsage {
+        bytes,
+        files,
+        source,
+        truncated,
+    })
+}
+
+/// Totals below `path`; a walk stopped by `cancelled` returns what it had
+fn total_usage(path: &Path, skip: &[String], cancelled: &dyn Fn() -> bool) -> (u64, u64, bool) {
+    let mut total = 0;
+    let mut files = 0;
+    let mut visited = 0;
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read_dir.flatten() {
+            visited += 1;
+            if visited > DIR_SIZE_ENTRY_LIMIT || cancelled() {
+                return (total, files, true);
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                let path = entry.path();
+                if !is_skipped_dir(&path, skip) {
+                    stack.push(path);
+                }
+            } else if file_type.is_file() {
+                total += entry.metadata().map(|m| m.len()).unwrap_or(0);
+                files += 1;
+            }
+        }
+    }
+    (total, files, false)
+}
+
+fn source_usage(path: &Path, skip: &[String], cancelled: &dyn Fn() -> bool) -> (u64, u64, bool) {
+    let mut total = 0;
+    let mut files = 0;
+    let mut walker = WalkBuilder::new(path);
+    skip_dirs(&mut walker, skip);
+    let walker = walker.hidden(true).build();
+    for (visited, entry) in walker.flatten().enumerate() {
+        if visited > DIR_SIZE_ENTRY_LIMIT || cancelled() {
+            return (total, files, true);
+        }
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            total += entry.metadata().map(|m| m.len()).unwrap_or(0);
+            files += 1;
+        }
+    }
+    (total, files, false)
+}
+
+/// Gather the `EntryDetails` of entries listed from `dir`
+///
+/// The worker runs this as an `Enrichment` once its queue is empty, so the `git status`
+/// call, owner lookups and content sniffing never hold up a listing.
+pub fn enrich_entries(dir: &Path, paths: &[PathBuf]) -> Vec<(PathBuf, EntryDetails)> {
+    Enrichment::new(dir.to_path_buf(), paths.to_vec()).next_batch(usize::MAX)
+}
+
+/// `enrich_entries` a batch at a time, so the worker can take newer commands in between
+pub struct Enrichment {
+    dir: PathBuf,
+    paths: std::vec::IntoIter<PathBuf>,
+    /// Read with the first batch, along with the project root
+    git_statuses: Option<HashMap<String, GitStatus>>,
+    git_root: Option<Arc<Path>>,
+}
+
+impl Enrichment {
+    pub fn new(dir: PathBuf, paths: Vec<PathBuf>) -> Self {
+        Self {
+            dir,
+            paths: paths.into_iter(),
+            git_statuses: None,
+            git_root: None,
+        }
+    }
+
+    /// Details of the next `count` entries; empty once every entry is done
+    pub fn next_batch(&mut self, count: usize) -> Vec<(PathBuf, EntryDetails)> {
+        let dir = &self.dir;
+        if self.git_statuses.is_none() {
+            self.git_root = project_root(dir).map(Arc::from);
+        }
+        let git_root = &self.git_root;
+        let git_statuses = self
+            .git_statuses
+            .get_or_insert_with(|| get_git_statuses(dir));
+        self.paths
+            .by_ref()
+            .take(count)
+            .map(|path| {
+                let git_status = path
+                    .file_name()
+                    .and_then(|name| git_statuses.get(name.to_string_lossy().as_ref()))
+                    .cloned();
+                let mut details = entry_details(&path, git_status);
+                // A folder holding its own repository is a project of its own
+                details.git_root =
+                    if details.mime == Some("inode/directory") && path.join(".git").exists() {
+                        Some(Arc::from(path.as_path()))
+                    } else {
+                        git_root.clone()
+                    };
+                (path, details)
+            })
+            .collect()
+    }
+}
+
+/// Top-level directory of the git project containing `path`: the nearest ancestor
+/// with a `.git` entry (a directory, or a file for worktrees and submodules)
+pub fn project_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// The git directory of the repository containing `path`
+///
+/// Follows `.git` files (worktrees, submodules) to the directory they point at.
+pub fn git_dir(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find_map(|dir| {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        let target = fs::read_to_string(&dot_git).ok()?;
+        let target = target.strip_prefix("gitdir:")?.trim();
+        Some(dir.join(target))
+    })
+}
+
+fn entry_details(path: &Path, git_status: Option<GitStatus>) -> EntryDetails {
+    let metadata = fs::metadata(path).ok();
+    let is_dir = metadata.as_ref().is_some_and(|m| m.is_dir());
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mime = if is_dir {
+        "inode/directory"
+    } else if let Some(mime) = mime_from_extension(&extension) {
+        mime
+    } else if metadata.is_some() && !is_likely_binary(path) {
+        "text/plain"
+    } else {
+        "application/octet-stream"
+    };
+
+    #[cfg(unix)]
+    let owner = fs::symlink_metadata(path).ok().map(|meta| {
+        use std::os::unix::fs::MetadataExt;
+        let user = crate::platform::user_name(meta.uid()).unwrap_or_else(|| meta.uid().to_string());
+        let group =
+            crate::platform::group_name(meta.gid()).unwrap_or_else(|| meta.gid().to_string());
+        format!("{}:{}", user, group)
+    });
+    #[cfg(not(unix))]
+    let owner = None;
+
+    EntryDetails {
+        git_status,
+        permissions: metadata.map(|m| m.permissions()),
+        owner,
+        mime: Some(mime),
+        git_root: None,
+    }
+}
+
 fn get_git_statuses(dir_path: &Path) -> HashMap<String, GitStatus> {
     let mut statuses = HashMap::new();
+    if !super::git::enabled() {
+        return statuses;
+    }
 
     // 1. Get prefix (relative path of current dir from repo root)
     let prefix = match Command::new("git")
@@ -151,3 +498,149 @@ pub fn is_likely_binary(path: &Path) -> bool {
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn enrichment_covers_every_entry_in_batches() {
+        let dir = TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = (0..5)
+            .map(|i| {
+                let path = dir.path().join(format!("{}.txt", i));
+                fs::write(&path, "text").unwrap();
+                path
+            })
+            .collect();
+
+        let mut enrichment = Enrichment::new(dir.path().to_path_buf(), paths.clone());
+        let first = enrichment.next_batch(3);
+        let second = enrichment.next_batch(3);
+
+        assert_eq!(first.len(), 3);
+        assert_eq!(second.len(), 2);
+        assert!(enrichment.next_batch(3).is_empty());
+        assert_eq!(second[1].0, paths[4]);
+        assert_eq!(second[1].1.mime, Some("text/plain"));
+    }
+
+    #[test]
+    fn enrichment_records_the_project_root() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join("vendor/.git")).unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+        let details: HashMap<PathBuf, EntryDetails> = enrich_entries(
+            dir.path(),
+            &[dir.path().join("main.rs"), dir.path().join("vendor")],
+        )
+        .into_iter()
+        .collect();
+
+        let root = |name: &str| details[&dir.path().join(name)].git_root.clone();
+        assert_eq!(root("main.rs").as_deref(), Some(dir.path()));
+        // A folder with its own repository is its own project
+        assert_eq!(
+            root("vendor").as_deref(),
+            Some(dir.path().join("vendor").as_path())
+        );
+    }
+
+    #[test]
+    fn build_folders_are_left_out_of_usage() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "needle").unwrap();
+        fs::create_dir_all(dir.path().join("web/node_modules/pkg")).unwrap();
+        fs::write(dir.path().join("web/node_modules/pkg/index.js"), "needle").unwrap();
+        fs::write(dir.path().join("web/app.js"), "needle").unwrap();
+        let skip = vec!["node_modules".to_string()];
+
+        assert!(is_skipped_dir(&dir.path().join("web/node_modules"), &skip));
+        assert!(!is_skipped_dir(&dir.path().join("web"), &skip));
+        let all = directory_usage(dir.path(), false, &[], &|| false).unwrap();
+        assert_eq!(all.files, 3);
+        let usage = directory_usage(dir.path(), true, &skip, &|| false).unwrap();
+        assert_eq!(usage.files, 2);
+        assert_eq!(usage.source, Some((12, 2)));
+    }
+
+    #[test]
+    fn git_dir_follows_worktree_files() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join("docs/inner")).unwrap();
+        let worktree = dir.path().join("src");
+        fs::create_dir(&worktree).unwrap();
+        fs::write(worktree.join(".git"), "gitdir: ../.git/worktrees/src\n").unwrap();
+
+        assert_eq!(
+            git_dir(&dir.path().join("docs/inner")),
+            Some(dir.path().join(".git"))
+        );
+        assert_eq!(
+            git_dir(&worktree),
+            Some(worktree.join("../.git/worktrees/src"))
+        );
+    }
+
+    #[test]
+    fn directory_usage_separates_source_from_total() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        fs::write(dir.path().join("b.md"), "bravo").unwrap();
+        fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+        fs::create_dir_all(dir.path().join("docs/inner")).unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        fs::create_dir(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("target/build.bin"), "0123456789").unwrap();
+        fs::write(dir.path().join(".env"), "KEY=1").unwrap();
+
+        let total = directory_usage(dir.path(), false, &[], &|| false).unwrap();
+        // a.txt, b.md, notes.txt, .gitignore, target/build.bin, .env
+        assert_eq!((total.bytes, total.files), (5 + 5 + 5 + 8 + 10 + 5, 6));
+        assert_eq!(total.source, None);
+
+        let usage = directory_usage(dir.path(), true, &[], &|| false).unwrap();
+        assert_eq!(usage.source, Some((15, 3)));
+        assert_eq!(DirUsage::from_section(&usage.to_section()), Some(usage));
+        assert_eq!(
+            DirUsage::from_section("10 2+").map(|u| u.truncated),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn project_root_stops_at_repositories_and_worktrees() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join("docs/inner")).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+
+        assert_eq!(
+            project_root(&dir.path().join("docs/inner")),
+            Some(dir.path().to_path_buf())
+        );
+        // A worktree's `.git` file makes it a project of its own
+        fs::write(
+            dir.path().join("src/.git"),
+            "gitdir: ../.git/worktrees/src\n",
+        )
+        .unwrap();
+        assert_eq!(
+            project_root(&dir.path().join("src")),
+            Some(dir.path().join("src"))
+        );
+    }
+
+    #[test]
+    fn size_walks_can_be_cancelled() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+
+        assert!(directory_usage(dir.path(), true, &[], &|| true).is_none());
+    }
+}
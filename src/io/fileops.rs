@@ -0,0 +1,279 @@
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Told the bytes written by each step of a long copy; returning false cancels it
+pub type Progress<'a> = &'a mut dyn FnMut(u64) -> bool;
+
+/// The error a copy stopped through `Progress` ends with
+pub fn cancelled() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "cancelled")
+}
+
+/// `io::copy` in 1 MiB steps, reporting each one to `progress`
+pub fn copy_stream(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    progress: Progress,
+) -> io::Result<u64> {
+    let mut buf = vec![0u8; 1024 * 1024];
+    let mut total = 0;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return Ok(total),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buf[..n])?;
+        total += n as u64;
+        if !progress(n as u64) {
+            return Err(cancelled());
+        }
+    }
+}
+
+/// Copy a file like `fs::copy`, permission bits included, reporting progress
+pub fn copy_file(src: &Path, dest: &Path, progress: Progress) -> io::Result<u64> {
+    let mut input = fs::File::open(src)?;
+    let permissions = input.metadata()?.permissions();
+    let mut output = fs::File::create(dest)?;
+    let copied = copy_stream(&mut input, &mut output, progress)?;
+    output.set_permissions(permissions)?;
+    Ok(copied)
+}
+
+/// Copy a file or a whole directory tree to `dest`, creating parent directories as needed.
+/// Symlinks are copied as links.
+///
+/// Returns the number of files copied.
+pub fn copy_recursive(src: &Path, dest: &Path, progress: Progress) -> io::Result<usize> {
+    if dest.starts_with(src) && dest != src {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot copy a directory into itself",
+        ));
+    }
+
+    let meta = fs::symlink_metadata(src)?;
+    if meta.is_dir() {
+        fs::create_dir_all(dest)?;
+        let mut count = 0;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            count += copy_recursive(&entry.path(), &dest.join(entry.file_name()), progress)?;
+        }
+        Ok(count)
+    } else {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if meta.is_symlink() {
+            copy_link(src, dest)?;
+        } else {
+            copy_file(src, dest, progress)?;
+        }
+        Ok(1)
+    }
+}
+
+/// Recreate the symlink `src` at `dest`, pointing at the same target
+fn copy_link(src: &Path, dest: &Path) -> io::Result<()> {
+    let target = fs::read_link(src)?;
+    #[cfg(unix)]
+    return std::os::unix::fs::symlink(target, dest);
+    #[cfg(windows)]
+    return if fs::metadata(src).is_ok_and(|m| m.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, dest)
+    } else {
+        std::os::windows::fs::symlink_file(target, dest)
+    };
+}
+
+/// Copy `src` to `dest` for a move between filesystems: directories recursively,
+/// symlinks as links, each file read back and compared with its original, and
+/// permissions and timestamps carried over. `dest` must not exist yet.
+pub fn copy_for_move(src: &Path, dest: &Path, progress: Progress) -> io::Result<()> {
+    if fs::symlink_metadata(dest).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dest.display()),
+        ));
+    }
+    let meta = fs::symlink_metadata(src)?;
+    if meta.is_symlink() {
+        return copy_link(src, dest);
+    }
+    if meta.is_dir() {
+        fs::create_dir(dest)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_for_move(&entry.path(), &dest.join(entry.file_name()), progress)?;
+        }
+        // After the contents, so a read-only folder can still be filled
+        fs::set_permissions(dest, meta.permissions())?;
+        // Some filesystems refuse directory times; the files inside matter more
+        let _ = preserve_times(dest, &meta);
+        return Ok(());
+    }
+    let copied = copy_file(src, dest, progress)?;
+    if copied != meta.len() || !same_contents(src, dest, progress)? {
+        return Err(io::Error::other(format!(
+            "the copy of {} does not match the original",
+            src.display()
+        )));
+    }
+    preserve_times(dest, &meta)
+}
+
+/// Whether two files hold the same bytes, read side by side in 1 MiB steps; each step
+/// is reported to `progress` so a long check shows movement and can be cancelled
+fn same_contents(a: &Path, b: &Path, progress: Progress) -> io::Result<bool> {
+    let (mut a, mut b) = (fs::File::open(a)?, fs::File::open(b)?);
+    let (mut buf_a, mut buf_b) = (vec![0u8; 1024 * 1024], vec![0u8; 1024 * 1024]);
+    loop {
+        let n = fill(&mut a, &mut buf_a)?;
+        if fill(&mut b, &mut buf_b)? != n || buf_a[..n] != buf_b[..n] {
+            return Ok(false);
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+        if !progress(n as u64) {
+            return Err(cancelled());
+        }
+    }
+}
+
+/// Read until `buf` is full or the file ends, returning how much was read
+fn fill(file: &mut fs::File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Give `dest` the modification and access times from `meta`
+fn preserve_times(dest: &Path, meta: &fs::Metadata) -> io::Result<()> {
+    let mut times = fs::FileTimes::new().set_modified(meta.modified()?);
+    if let Ok(accessed) = meta.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    // Opened for attributes only, so read-only files and folders work too
+    #[cfg(windows)]
+    let file = {
+        use std::os::windows::fs::OpenOptionsExt;
+        const FILE_WRITE_ATTRIBUTES: u32 = 0x100;
+        const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+        fs::OpenOptions::new()
+            .access_mode(FILE_WRITE_ATTRIBUTES)
+            .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+            .open(dest)?
+    };
+    #[cfg(not(windows))]
+    let file = fs::File::open(dest)?;
+    file.set_times(times)
+}
+
+/// First free "name (n).ext" next to `path`, so a colliding item can keep both copies
+///
+/// An existing "(n)" suffix is continued rather than nested: copying
+/// "report (1).pdf" over itself gives "report (2).pdf".
+pub fn unique_destination(path: &Path) -> PathBuf {
+    let parent = path.parent().unwrap_or(Path::new(""));
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (stem, extension) = match name.rfind('.') {
+        Some(dot) if dot > 0 && !path.is_dir() => name.split_at(dot),
+        _ => (name.as_str(), ""),
+    };
+    let (base, mut n) = match stem
+        .strip_suffix(')')
+        .and_then(|s| s.rsplit_once(" ("))
+        .and_then(|(base, n)| n.parse::<u32>().ok().map(|n| (base, n + 1)))
+    {
+        Some((base, n)) => (base, n),
+        None => (stem, 1),
+    };
+    loop {
+        let candidate = parent.join(format!("{} ({}){}", base, n, extension));
+        if fs::symlink_metadata(&candidate).is_err() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Hex-encoded SHA-256 of a file's contents
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    #[test]
+    fn copy_for_move_keeps_times_permissions_and_links() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        fs::create_dir_all(dir.path().join("docs/inner")).unwrap();
+        let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let file = fs::File::create(dir.path().join("docs/inner/deep.txt")).unwrap();
+        file.set_modified(old).unwrap();
+        drop(file);
+        let mut readonly = fs::metadata(dir.path().join("a.txt"))
+            .unwrap()
+            .permissions();
+        readonly.set_readonly(true);
+        fs::set_permissions(dir.path().join("docs/inner/deep.txt"), readonly).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("inner/deep.txt", dir.path().join("docs/link")).unwrap();
+
+        let dest = dir.path().join("moved");
+        copy_for_move(&dir.path().join("docs"), &dest, &mut |_| true).unwrap();
+
+        let copied = fs::metadata(dest.join("inner/deep.txt")).unwrap();
+        assert_eq!(copied.modified().unwrap(), old);
+        assert!(copied.permissions().readonly());
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read_link(dest.join("link")).unwrap(),
+            Path::new("inner/deep.txt")
+        );
+        assert!(
+            copy_for_move(
+                &dir.path().join("a.txt"),
+                &dest.join("inner/deep.txt"),
+                &mut |_| true
+            )
+            .is_err(),
+            "an existing destination is never overwritten"
+        );
+    }
+}
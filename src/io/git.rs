@@ -0,0 +1,251 @@
+// Git quick actions for the top bar: branch info, checkout and stashing untracked files
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Message prefix identifying stashes made by Heike, followed by the repo-relative directory
+const STASH_PREFIX: &str = "heike untracked:";
+
+/// Repository containing the current directory
+#[derive(Clone, Debug, PartialEq)]
+pub struct RepoInfo {
+    pub root: PathBuf,
+    /// `None` on a detached HEAD
+    pub branch: Option<String>,
+    /// Local branches, sorted by name
+    pub branches: Vec<String>,
+}
+
+/// Cleared when the startup check finds no git, so listings and previews stop
+/// spawning a process that can only fail
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Allow or stop running git, after the external tools were probed
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether git may be run
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    if !enabled() {
+        return Err("git was not found (see :diagnostics)".into());
+    }
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("git: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(stderr
+            .lines()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("git failed")
+            .trim()
+            .to_string())
+    }
+}
+
+/// Summary of the most recent commit touching `path`, for the preview Git tab
+pub fn last_commit(path: &Path) -> Option<String> {
+    if !enabled() {
+        return None;
+    }
+    let dir = if path.is_dir() { path } else { path.parent()? };
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%h  %an, %ar%n%s", "--"])
+        .arg(path)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !text.is_empty()).then_some(text)
+}
+
+/// Branch and root of the repository containing `dir`, `None` outside a repository
+pub fn repo_info(dir: &Path) -> Option<RepoInfo> {
+    let root = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"]).ok()?);
+    let branch = git(dir, &["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .ok()
+        .filter(|b| !b.is_empty());
+    let branches = git(
+        dir,
+        &[
+            "for-each-ref",
+            "--sort=refname",
+            "--format=%(refname:short)",
+            "refs/heads",
+        ],
+    )
+    .map(|out| out.lines().map(str::to_string).collect())
+    .unwrap_or_default();
+    Some(RepoInfo {
+        root,
+        branch,
+        branches,
+    })
+}
+
+pub fn checkout(dir: &Path, branch: &str) -> Result<(), String> {
+    git(dir, &["checkout", branch, "--"]).map(|_| ())
+}
+
+fn stash_message(dir: &Path) -> Result<String, String> {
+    let prefix = git(dir, &["rev-parse", "--show-prefix"])?;
+    let prefix = prefix.trim_end_matches('/');
+    Ok(format!(
+        "{} {}",
+        STASH_PREFIX,
+        if prefix.is_empty() { "." } else { prefix }
+    ))
+}
+
+/// Stash the untracked files below `dir`, returning how many were stashed
+///
+/// Tracked changes are left alone, and ignored files are not touched.
+pub fn stash_untracked(dir: &Path) -> Result<usize, String> {
+    // Stashing every file of an untracked directory would remove the directory itself
+    if git(dir, &["ls-files", "--", "."])?.is_empty() {
+        return Err("Directory has no tracked files, stash from its parent".into());
+    }
+    // NUL-separated and passed back literally, so any file name survives the round trip
+    let listing = git(
+        dir,
+        &[
+            "ls-files",
+            "-z",
+            "--others",
+            "--exclude-standard",
+            "--",
+            ".",
+        ],
+    )?;
+    let files: Vec<&str> = listing.split('\0').filter(|f| !f.is_empty()).collect();
+    if files.is_empty() {
+        return Ok(0);
+    }
+    let message = stash_message(dir)?;
+    // Per-path `:(literal)` rather than `--literal-pathspecs`, which git stash loses
+    // below the top-level directory
+    let pathspecs: Vec<String> = files.iter().map(|f| format!(":(literal){}", f)).collect();
+    let mut args = vec!["stash", "push", "--include-untracked", "-m", &message, "--"];
+    args.extend(pathspecs.iter().map(String::as_str));
+    git(dir, &args)?;
+    Ok(files.len())
+}
+
+/// A git quick action from the top bar, run by the worker
+#[derive(Clone, Debug, PartialEq)]
+pub enum GitAction {
+    Checkout(String),
+    StashUntracked,
+    UnstashUntracked,
+}
+
+impl GitAction {
+    /// Run the action in `dir`, returning the message to show either way
+    pub fn run(&self, dir: &Path) -> Result<String, String> {
+        match self {
+            GitAction::Checkout(branch) => checkout(dir, branch)
+                .map(|()| format!("Switched to branch {}", branch))
+                .map_err(|e| format!("Checkout failed: {}", e)),
+            GitAction::StashUntracked => match stash_untracked(dir) {
+                Ok(0) => Ok("No untracked files here".into()),
+                Ok(n) => Ok(format!("Stashed {} untracked items", n)),
+                Err(e) => Err(format!("Stash failed: {}", e)),
+            },
+            GitAction::UnstashUntracked => unstash_untracked(dir)
+                .map(|()| "Restored stashed untracked files".into())
+                .map_err(|e| format!("Unstash failed: {}", e)),
+        }
+    }
+}
+
+/// Restore the most recent untracked-files stash made for `dir`
+pub fn unstash_untracked(dir: &Path) -> Result<(), String> {
+    let message = stash_message(dir)?;
+    let list = git(dir, &["stash", "list", "--format=%gd%x09%s"])?;
+    let stash = list
+        .lines()
+        .filter_map(|l| l.split_once('\t'))
+        // `git stash push -m` records "On <branch>: <message>"
+        .find(|(_, subject)| subject.ends_with(&message))
+        .map(|(name, _)| name.to_string())
+        .ok_or_else(|| "No stashed untracked files for this directory".to_string())?;
+    git(dir, &["stash", "pop", &stash]).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn stash_untracked_round_trips_files_below_the_directory() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+        fs::create_dir_all(dir.path().join("docs/inner")).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        let run = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .is_ok_and(|o| o.status.success())
+        };
+        // Skip where git is not installed
+        if !run(&["init", "-q", "-b", "main"]) {
+            return;
+        }
+        fs::write(dir.path().join("docs/index.md"), "index").unwrap();
+        assert!(run(&["add", "a.txt", "docs/index.md"]));
+        assert!(run(&[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "-q",
+            "-m",
+            "init"
+        ]));
+        fs::write(dir.path().join("a.txt"), "changed").unwrap();
+        fs::write(dir.path().join("docs/draft.txt"), "draft").unwrap();
+        fs::write(dir.path().join("docs/inner/deep.txt"), "deep").unwrap();
+        // Glob characters in a name must not be read as a pattern
+        fs::write(dir.path().join("docs/[draft].txt"), "brackets").unwrap();
+
+        let git = repo_info(dir.path()).unwrap();
+        assert_eq!(git.branch.as_deref(), Some("main"));
+        assert_eq!(git.branches, vec!["main".to_string()]);
+
+        let docs = dir.path().join("docs");
+        assert_eq!(stash_untracked(&docs), Ok(3));
+        assert!(!docs.join("draft.txt").exists());
+        assert!(!docs.join("[draft].txt").exists());
+        assert!(!docs.join("inner/deep.txt").exists());
+        // Tracked edits and untracked files elsewhere stay put
+        assert!(dir.path().join("notes.txt").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "changed"
+        );
+
+        assert!(stash_untracked(&dir.path().join("src")).is_err());
+        assert!(unstash_untracked(&dir.path().join("src")).is_err());
+        unstash_untracked(&docs).unwrap();
+        assert!(docs.join("draft.txt").exists());
+        assert!(docs.join("[draft].txt").exists());
+        assert!(docs.join("inner/deep.txt").exists());
+    }
+}
@@ -0,0 +1,242 @@
+// Shell commands from `[[hooks]]` run before and after paste, trash, link, split and join jobs
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A before hook still running after this is stopped, and stops its job
+const BEFORE_HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest `HEIKE_FILES`; a bigger selection only gets `HEIKE_FILE_LIST`, as Windows
+/// caps a variable at 32K characters and Linux a single one at 128 KiB
+const FILES_ENV_LIMIT: usize = 16 * 1024;
+
+/// How long stderr may stay open once the hook is gone (a background child can hold it)
+const STDERR_GRACE: Duration = Duration::from_millis(500);
+
+/// The job's items NUL-separated in a temporary file, removed once the hook is done
+struct FileList(PathBuf);
+
+impl FileList {
+    fn write(items: &[PathBuf]) -> io::Result<Self> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "heike-hook-{}-{}.list",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut list = Vec::new();
+        for item in items {
+            list.extend_from_slice(item.as_os_str().as_encoded_bytes());
+            list.push(0);
+        }
+        fs::write(&path, list)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for FileList {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// `command` for the platform shell, in `folder`, with the job described in the environment
+///
+/// The shell leads its own process group, so a timed out hook can be stopped with
+/// everything it started.
+fn shell_command(
+    command: &str,
+    operation: &str,
+    folder: &Path,
+    items: &[PathBuf],
+) -> io::Result<(Command, FileList)> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+    let list = FileList::write(items)?;
+    let files: Vec<String> = items.iter().map(|p| p.display().to_string()).collect();
+    let files = files.join("\n");
+    if files.len() <= FILES_ENV_LIMIT {
+        shell.env("HEIKE_FILES", files);
+    }
+    shell
+        .arg(command)
+        .current_dir(folder)
+        .env("HEIKE_OPERATION", operation)
+        .env("HEIKE_DIR", folder)
+        .env("HEIKE_FILE_LIST", &list.0)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null());
+    Ok((shell, list))
+}
+
+/// Stop a hook and whatever it started
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    // SAFETY: kill has no memory effects; the negative pid names the hook's process group
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    #[cfg(windows)]
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+}
+
+/// Run a before hook to completion; an error (exit status, last line of stderr, or the
+/// timeout) means the job must not start
+pub fn run_before(
+    command: &str,
+    operation: &str,
+    folder: &Path,
+    items: &[PathBuf],
+) -> Result<(), String> {
+    let (mut shell, _list) =
+        shell_command(command, operation, folder, items).map_err(|e| e.to_string())?;
+    let mut child = shell
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    // Read on the side so a chatty hook can't fill the pipe and stall
+    let (stderr_tx, stderr_rx) = channel();
+    if let Some(mut stderr) = child.stderr.take() {
+        thread::spawn(move || {
+            let mut text = String::new();
+            let _ = stderr.read_to_string(&mut text);
+            let _ = stderr_tx.send(text);
+        });
+    }
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break Some(status),
+            None if started.elapsed() > BEFORE_HOOK_TIMEOUT => {
+                kill_tree(&mut child);
+                let _ = child.wait();
+                break None;
+            }
+            None => thread::sleep(Duration::from_millis(20)),
+        }
+    };
+    let stderr = stderr_rx.recv_timeout(STDERR_GRACE).unwrap_or_default();
+    match status {
+        Some(status) if status.success() => Ok(()),
+        Some(status) => Err(match stderr.lines().rev().find(|l| !l.trim().is_empty()) {
+            Some(line) => format!("{} ({})", line.trim(), status),
+            None => status.to_string(),
+        }),
+        None => Err(format!(
+            "still running after {}s",
+            BEFORE_HOOK_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+/// Run before hook `commands` one after another off the UI thread
+///
+/// The receiver gets `Ok` once all of them passed, or the failing command and its error.
+pub fn spawn_before(
+    commands: Vec<String>,
+    operation: &'static str,
+    folder: PathBuf,
+    items: Vec<PathBuf>,
+) -> Receiver<Result<(), (String, String)>> {
+    let (tx, rx) = channel();
+    thread::spawn(move || {
+        let outcome = commands.into_iter().try_for_each(|command| {
+            run_before(&command, operation, &folder, &items).map_err(|e| (command, e))
+        });
+        let _ = tx.send(outcome);
+    });
+    rx
+}
+
+/// Start an after hook without waiting for it
+pub fn spawn_after(
+    command: &str,
+    operation: &str,
+    folder: &Path,
+    items: &[PathBuf],
+) -> io::Result<()> {
+    let (mut shell, list) = shell_command(command, operation, folder, items)?;
+    let mut child = shell.stderr(Stdio::null()).spawn()?;
+    thread::spawn(move || {
+        let _ = child.wait();
+        drop(list);
+    });
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn before_hooks_pass_the_job_and_report_failures() {
+        let dir = TempDir::new().unwrap();
+        let items = [dir.path().join("a.txt"), dir.path().join("line\nbreak.md")];
+        let check = "test \"$HEIKE_OPERATION\" = trash && test \"$HEIKE_DIR\" = \"$PWD\" \
+                     && test \"$(tr -cd '\\0' < \"$HEIKE_FILE_LIST\" | wc -c)\" -eq 2";
+        assert_eq!(run_before(check, "trash", dir.path(), &items), Ok(()));
+
+        let error =
+            run_before("echo protected >&2; exit 3", "trash", dir.path(), &items).unwrap_err();
+        assert!(error.starts_with("protected ("), "{}", error);
+    }
+
+    #[test]
+    fn large_selections_are_passed_only_as_a_file_list() {
+        let dir = TempDir::new().unwrap();
+        let items: Vec<PathBuf> = (0..20_000)
+            .map(|i| dir.path().join(format!("photo-{:05}.jpg", i)))
+            .collect();
+        let check = "test -z \"${HEIKE_FILES+set}\" \
+                     && test \"$(tr -cd '\\0' < \"$HEIKE_FILE_LIST\" | wc -c)\" -eq 20000";
+        assert_eq!(run_before(check, "trash", dir.path(), &items), Ok(()));
+    }
+
+    #[test]
+    fn a_background_child_holding_stderr_does_not_hold_up_the_hook() {
+        let dir = TempDir::new().unwrap();
+        let started = Instant::now();
+
+        assert_eq!(
+            run_before("sleep 5 & exit 0", "trash", dir.path(), &[]),
+            Ok(())
+        );
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn hooks_run_off_the_calling_thread() {
+        let dir = TempDir::new().unwrap();
+        let outcome = spawn_before(
+            vec!["true".into(), "exit 4".into(), "touch never".into()],
+            "paste",
+            dir.path().to_path_buf(),
+            Vec::new(),
+        );
+
+        let (command, _) = outcome.recv().unwrap().unwrap_err();
+        assert_eq!(command, "exit 4");
+        assert!(!dir.path().join("never").exists());
+    }
+}
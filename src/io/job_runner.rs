@@ -0,0 +1,79 @@
+// Batch job items run on their own thread, so a long copy never holds up a frame
+use crate::core::run_job_item;
+use crate::state::JobKind;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the running item reports the bytes it has written
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What the job thread reports about the item it was given
+#[derive(Debug)]
+pub enum JobEvent {
+    /// Bytes the item has written so far
+    Progress(u64),
+    /// The item is finished; `Ok(false)` means it was skipped on purpose
+    Done(Result<bool, String>),
+}
+
+/// Handle to the thread that performs job items one at a time
+pub struct JobRunner {
+    items: Sender<(JobKind, PathBuf)>,
+    pub events: Receiver<JobEvent>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl JobRunner {
+    pub fn spawn(ctx: eframe::egui::Context) -> Self {
+        let (items, item_rx) = channel::<(JobKind, PathBuf)>();
+        let (event_tx, events) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cancelled = Arc::clone(&cancel);
+        thread::spawn(move || {
+            for (kind, path) in item_rx {
+                let mut written = 0;
+                let mut reported = Instant::now();
+                let mut progress = |bytes: u64| {
+                    written += bytes;
+                    if reported.elapsed() >= PROGRESS_INTERVAL {
+                        reported = Instant::now();
+                        let _ = event_tx.send(JobEvent::Progress(written));
+                        ctx.request_repaint();
+                    }
+                    !cancelled.load(Ordering::Relaxed)
+                };
+                let outcome = run_job_item(&kind, &path, &mut progress);
+                if event_tx.send(JobEvent::Done(outcome)).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+        Self {
+            items,
+            events,
+            cancel,
+        }
+    }
+
+    /// Hand `path` to the job thread; its events follow on `events`
+    pub fn run(&self, kind: JobKind, path: PathBuf) -> Result<(), String> {
+        self.cancel.store(false, Ordering::Relaxed);
+        self.items
+            .send((kind, path))
+            .map_err(|_| "the job thread has stopped".to_string())
+    }
+
+    /// Stop the running item at its next progress step (copies, split and join parts)
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
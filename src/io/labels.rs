@@ -0,0 +1,107 @@
+// Persistent file labels: Finder tags on macOS, a labels file elsewhere
+use crate::entry::FileEntry;
+use crate::platform;
+use crate::state::LabelColor;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Default)]
+struct LabelFile {
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+}
+
+#[derive(Default)]
+pub struct LabelStore {
+    labels: HashMap<PathBuf, LabelColor>,
+}
+
+impl LabelStore {
+    /// Load stored labels; on macOS labels are read per directory from Finder tags instead
+    pub fn load() -> Self {
+        let mut store = Self::default();
+        if platform::NATIVE_LABELS {
+            return store;
+        }
+        let Some(contents) = Self::file_path().and_then(|p| fs::read_to_string(p).ok()) else {
+            return store;
+        };
+        match toml::from_str::<LabelFile>(&contents) {
+            Ok(file) => {
+                store.labels = file
+                    .labels
+                    .into_iter()
+                    .filter_map(|(path, name)| {
+                        let path = PathBuf::from(path);
+                        let label = LabelColor::from_name(&name)?;
+                        path.exists().then_some((path, label))
+                    })
+                    .collect();
+            }
+            Err(e) => eprintln!("Failed to parse labels file: {}", e),
+        }
+        store
+    }
+
+    fn file_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", "heike").map(|d| d.data_dir().join("labels.toml"))
+    }
+
+    pub fn get(&self, path: &Path) -> Option<LabelColor> {
+        self.labels.get(path).copied()
+    }
+
+    pub fn set(&mut self, path: &Path, label: Option<LabelColor>) -> io::Result<()> {
+        #[cfg(target_os = "macos")]
+        platform::macos::write_label(path, label)?;
+
+        match label {
+            Some(label) => self.labels.insert(path.to_path_buf(), label),
+            None => self.labels.remove(path),
+        };
+
+        if platform::NATIVE_LABELS {
+            Ok(())
+        } else {
+            self.save()
+        }
+    }
+
+    /// Re-read native labels (Finder tags) for a freshly loaded listing
+    pub fn refresh(&mut self, entries: &[FileEntry]) {
+        #[cfg(target_os = "macos")]
+        for entry in entries {
+            match platform::macos::read_label(&entry.path) {
+                Some(label) => self.labels.insert(entry.path.clone(), label),
+                None => self.labels.remove(&entry.path),
+            };
+        }
+        #[cfg(not(target_os = "macos"))]
+        let _ = entries;
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = Self::file_path()
+            .ok_or_else(|| io::Error::other("Could not determine data directory"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = LabelFile {
+            labels: self
+                .labels
+                .iter()
+                .map(|(path, label)| {
+                    (
+                        path.to_string_lossy().into_owned(),
+                        label.name().to_lowercase(),
+                    )
+                })
+                .collect(),
+        };
+        let contents = toml::to_string_pretty(&file).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+}
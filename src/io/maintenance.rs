@@ -0,0 +1,104 @@
+// Housekeeping: system trash usage and cleanup for the maintenance panel
+use std::io;
+#[cfg(target_os = "macos")]
+use std::path::PathBuf;
+
+/// Contents of the system trash
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrashUsage {
+    pub items: usize,
+    pub bytes: u64,
+    /// The size walk hit its entry limit, so `bytes` is a lower bound
+    pub truncated: bool,
+}
+
+/// Whether the trash can be emptied from Heike (the trash crate cannot list it on macOS)
+pub const CAN_EMPTY_TRASH: bool = cfg!(not(target_os = "macos"));
+
+fn trash_error(e: trash::Error) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+/// Count the items in the trash and total their size
+#[cfg(not(target_os = "macos"))]
+pub fn scan_trash() -> io::Result<TrashUsage> {
+    use trash::os_limited;
+
+    let items = os_limited::list().map_err(trash_error)?;
+    let mut usage = TrashUsage {
+        items: items.len(),
+        bytes: 0,
+        truncated: false,
+    };
+
+    // Freedesktop trash: the `files` directory holds the trashed data itself
+    #[cfg(unix)]
+    {
+        for folder in os_limited::trash_folders().map_err(trash_error)? {
+            let (bytes, truncated) = super::directory::directory_size(&folder.join("files"));
+            usage.bytes += bytes;
+            usage.truncated |= truncated;
+        }
+    }
+
+    // Recycle Bin: directories only report an entry count, so they add nothing here
+    #[cfg(windows)]
+    {
+        for item in &items {
+            if let Ok(trash::TrashItemMetadata {
+                size: trash::TrashItemSize::Bytes(bytes),
+            }) = os_limited::metadata(item)
+            {
+                usage.bytes += bytes;
+            }
+        }
+    }
+
+    Ok(usage)
+}
+
+#[cfg(target_os = "macos")]
+pub fn scan_trash() -> io::Result<TrashUsage> {
+    let trash = directories::UserDirs::new()
+        .map(|d| d.home_dir().join(".Trash"))
+        .unwrap_or_else(|| PathBuf::from("~/.Trash"));
+    // Reading ~/.Trash needs Full Disk Access; report that instead of an empty trash
+    let items = std::fs::read_dir(&trash)?.count();
+    let (bytes, truncated) = super::directory::directory_size(&trash);
+    Ok(TrashUsage {
+        items,
+        bytes,
+        truncated,
+    })
+}
+
+/// Permanently delete trashed items, optionally only those older than `older_than_days`
+///
+/// Returns the number of items removed.
+#[cfg(not(target_os = "macos"))]
+pub fn empty_trash(older_than_days: Option<u64>) -> io::Result<usize> {
+    use trash::os_limited;
+
+    let mut items = os_limited::list().map_err(trash_error)?;
+    if let Some(days) = older_than_days {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let cutoff = now - (days as i64) * 24 * 60 * 60;
+        items.retain(|item| item.time_deleted < cutoff);
+    }
+    let count = items.len();
+    if count > 0 {
+        os_limited::purge_all(items).map_err(trash_error)?;
+    }
+    Ok(count)
+}
+
+#[cfg(target_os = "macos")]
+pub fn empty_trash(_older_than_days: Option<u64>) -> io::Result<usize> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Emptying the Trash is not available on macOS",
+    ))
+}
@@ -1,5 +1,22 @@
+pub mod archive;
+pub mod broken_links;
+pub mod cleanup;
+pub mod dirdiff;
 pub mod directory;
+pub mod fileops;
+pub mod git;
+pub mod hooks;
+pub mod job_runner;
+pub mod labels;
+pub mod maintenance;
+pub mod name_audit;
+pub mod oplog;
+pub mod photo;
 pub mod search;
+pub mod session;
+pub mod split;
+pub mod textdiff;
+pub mod tools;
 pub mod worker;
 
 pub use directory::fuzzy_match;
@@ -0,0 +1,78 @@
+use super::cleanup::SKIPPED_DIRS;
+use crate::state::{name_problems, portable_name, NameIssue};
+use std::fs;
+use std::path::Path;
+
+/// Stop collecting after this many names to keep the report responsive
+const MAX_NAME_ISSUES: usize = 10_000;
+
+/// Every name below `root` that Windows or an SMB share would reject, deepest first so
+/// renaming them in order never invalidates a path still to come
+pub fn audit_names(root: &Path) -> Result<Vec<NameIssue>, std::io::Error> {
+    let mut issues = Vec::new();
+    audit_dir(root, &mut issues)?;
+    issues.sort_by(|a, b| {
+        b.path
+            .components()
+            .count()
+            .cmp(&a.path.components().count())
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(issues)
+}
+
+fn audit_dir(dir: &Path, issues: &mut Vec<NameIssue>) -> Result<(), std::io::Error> {
+    for entry in fs::read_dir(dir)?.filter_map(|entry| entry.ok()) {
+        if issues.len() >= MAX_NAME_ISSUES {
+            return Ok(());
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let problems = name_problems(&name);
+        if !problems.is_empty() {
+            issues.push(NameIssue {
+                path: entry.path(),
+                problems,
+                suggestion: portable_name(&name),
+            });
+        }
+        let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+        if is_dir && !SKIPPED_DIRS.contains(&name.as_str()) {
+            // Unreadable subdirectories are skipped rather than failing the whole audit
+            let _ = audit_dir(&entry.path(), issues);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn name_audit_renames_deepest_first() {
+        // Only Unix names may hold the characters Windows rejects
+        if !cfg!(unix) {
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("docs/a:b")).unwrap();
+        fs::write(dir.path().join("docs/a:b/what?.txt"), "?").unwrap();
+        fs::write(dir.path().join("docs/a:b/what.txt"), "taken").unwrap();
+
+        let issues = audit_names(dir.path()).unwrap();
+        let names: Vec<&str> = issues.iter().map(|i| i.suggestion.as_str()).collect();
+        assert_eq!(names, vec!["what.txt", "ab"]);
+
+        let plan: Vec<(std::path::PathBuf, &str)> = issues
+            .iter()
+            .map(|issue| (issue.path.clone(), issue.suggestion.as_str()))
+            .collect();
+        let (renamed, errors) = crate::core::apply_renames(&plan, true, |_, _| {});
+        assert_eq!((renamed, errors.len()), (2, 0));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("docs/ab/what (1).txt")).unwrap(),
+            "?"
+        );
+    }
+}
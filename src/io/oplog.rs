@@ -0,0 +1,244 @@
+// Audit trail of file operations: who moved what where, and when, for this session,
+// exported as JSON or CSV, and optionally appended to one log file per day
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Prefix and extension of the daily log files, e.g. "operations-2026-10-17.jsonl"
+const DAILY_PREFIX: &str = "operations-";
+const DAILY_EXTENSION: &str = ".jsonl";
+
+/// One item of a file operation
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OpRecord {
+    /// Local time, RFC 3339
+    pub time: String,
+    pub session: String,
+    pub user: String,
+    /// "copy", "move", "trash", "link", "rename", "create", "split" or "join"
+    pub action: String,
+    pub source: String,
+    /// Empty for actions without one, like trashing
+    pub destination: String,
+    /// "done", "skipped", or the error
+    pub outcome: String,
+}
+
+/// This session's records; `daily_dir` also gets each one appended to the day's file
+pub struct OperationLog {
+    pub session: String,
+    user: String,
+    pub records: Vec<OpRecord>,
+    pub daily_dir: Option<PathBuf>,
+}
+
+impl OperationLog {
+    pub fn new(daily_dir: Option<PathBuf>) -> Self {
+        Self {
+            session: format!(
+                "{}-{}",
+                Local::now().format("%Y%m%d%H%M%S"),
+                std::process::id()
+            ),
+            user: current_user(),
+            records: Vec::new(),
+            daily_dir,
+        }
+    }
+
+    /// Add a record; a failure to write the daily file comes back as a message to show,
+    /// and stops the daily file for the rest of the session so it is reported once
+    pub fn record(
+        &mut self,
+        action: &str,
+        source: &Path,
+        destination: Option<&Path>,
+        outcome: &Result<bool, String>,
+    ) -> Result<(), String> {
+        let now = Local::now();
+        let record = OpRecord {
+            time: now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            session: self.session.clone(),
+            user: self.user.clone(),
+            action: action.to_string(),
+            source: source.display().to_string(),
+            destination: destination
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            outcome: match outcome {
+                Ok(true) => "done".into(),
+                Ok(false) => "skipped".into(),
+                Err(error) => error.clone(),
+            },
+        };
+        let written = match &self.daily_dir {
+            Some(dir) => append_daily(dir, &record, now),
+            None => Ok(()),
+        };
+        if written.is_err() {
+            self.daily_dir = None;
+        }
+        self.records.push(record);
+        written.map_err(|e| format!("Could not write the operation log: {}", e))
+    }
+}
+
+/// Login name from the environment, "unknown" without one
+fn current_user() -> String {
+    ["USER", "USERNAME", "LOGNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|user| !user.is_empty()))
+        .unwrap_or_else(|| "unknown".into())
+}
+
+/// Where the daily log files go
+pub fn default_log_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "heike").map(|d| d.data_dir().join("logs"))
+}
+
+fn daily_file(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!(
+        "{}{}{}",
+        DAILY_PREFIX,
+        date.format("%Y-%m-%d"),
+        DAILY_EXTENSION
+    ))
+}
+
+/// Append `record` as one JSON line to the file for the day of `now`
+fn append_daily(dir: &Path, record: &OpRecord, now: DateTime<Local>) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(daily_file(dir, now.date_naive()))?;
+    let line = serde_json::to_string(record).map_err(io::Error::other)?;
+    writeln!(file, "{}", line)
+}
+
+/// Delete daily files more than `keep_days` days older than `today`; returns how many went
+pub fn prune_daily(dir: &Path, keep_days: u32, today: NaiveDate) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(date) = name
+            .strip_prefix(DAILY_PREFIX)
+            .and_then(|rest| rest.strip_suffix(DAILY_EXTENSION))
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        else {
+            continue;
+        };
+        if (today - date).num_days() > i64::from(keep_days) && fs::remove_file(entry.path()).is_ok()
+        {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Records as CSV with a header row; fields are quoted where needed
+pub fn to_csv(records: &[OpRecord]) -> String {
+    let field = |value: &str| {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    };
+    let mut csv = String::from("time,session,user,action,source,destination,outcome\n");
+    for record in records {
+        let fields = [
+            &record.time,
+            &record.session,
+            &record.user,
+            &record.action,
+            &record.source,
+            &record.destination,
+            &record.outcome,
+        ];
+        let row: Vec<String> = fields.iter().map(|value| field(value)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Write `records` to `path`, as CSV for a ".csv" name and JSON for ".json"
+pub fn export(records: &[OpRecord], path: &Path) -> Result<(), String> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    let contents = match extension.as_deref() {
+        Some("csv") => to_csv(records),
+        Some("json") => serde_json::to_string_pretty(records).map_err(|e| e.to_string())?,
+        _ => return Err("name the export .json or .csv".into()),
+    };
+    fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::JobKind;
+    use tempfile::TempDir;
+
+    #[test]
+    fn operation_log_exports_csv_and_json() {
+        let dir = TempDir::new().unwrap();
+        let logs = dir.path().join("logs");
+        let mut log = OperationLog::new(Some(logs.clone()));
+        let (action, dest) = JobKind::Link {
+            dest_dir: PathBuf::from("/dest"),
+        }
+        .audit(Path::new("/src/a.txt"));
+        assert_eq!(
+            (action, dest.as_deref()),
+            ("link", Some(Path::new("/dest/a.txt")))
+        );
+        log.record(action, Path::new("/src/a.txt"), dest.as_deref(), &Ok(true))
+            .unwrap();
+        log.record(
+            "trash",
+            Path::new("/src/b, c.txt"),
+            None,
+            &Err("denied".into()),
+        )
+        .unwrap();
+        assert_eq!(fs::read_dir(&logs).unwrap().count(), 1, "one file per day");
+
+        let csv = to_csv(&log.records);
+        assert!(csv.starts_with("time,session,user,action,source,destination,outcome\n"));
+        assert!(csv.contains(",link,/src/a.txt,/dest/a.txt,done\n"));
+        assert!(csv.contains(",trash,\"/src/b, c.txt\",,denied\n"));
+        let json = dir.path().join("ops.JSON");
+        export(&log.records, &json).unwrap();
+        let back: Vec<OpRecord> = serde_json::from_str(&fs::read_to_string(json).unwrap()).unwrap();
+        assert_eq!(back, log.records);
+        assert!(export(&log.records, &dir.path().join("ops.txt")).is_err());
+    }
+
+    #[test]
+    fn only_old_daily_files_are_pruned() {
+        let dir = TempDir::new().unwrap();
+        let day = |text| NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap();
+        let today = day("2024-03-31");
+        assert_eq!(
+            daily_file(dir.path(), today),
+            dir.path().join("operations-2024-03-31.jsonl")
+        );
+        for date in ["2024-01-01", "2024-01-02", "2024-03-30"] {
+            fs::write(daily_file(dir.path(), day(date)), "").unwrap();
+        }
+        fs::write(dir.path().join("keep-me.jsonl"), "").unwrap();
+
+        assert_eq!(prune_daily(dir.path(), 88, today), 2);
+        assert!(daily_file(dir.path(), day("2024-03-30")).exists());
+        assert!(dir.path().join("keep-me.jsonl").exists());
+        assert_eq!(prune_daily(&dir.path().join("missing"), 0, today), 0);
+    }
+}
@@ -0,0 +1,66 @@
+// Photo capture times from EXIF, for date-based renaming and the image metadata tab
+use chrono::NaiveDateTime;
+use std::fs;
+use std::io::BufReader;
+use std::path::Path;
+
+/// When a photo was taken according to its EXIF data (original capture time,
+/// else the last-modified time the camera recorded); `None` without EXIF
+pub fn capture_time(path: &Path) -> Option<NaiveDateTime> {
+    let file = fs::File::open(path).ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+    [exif::Tag::DateTimeOriginal, exif::Tag::DateTime]
+        .into_iter()
+        .find_map(|tag| {
+            let field = exif.get_field(tag, exif::In::PRIMARY)?;
+            let exif::Value::Ascii(values) = &field.value else {
+                return None;
+            };
+            let time = exif::DateTime::from_ascii(values.first()?).ok()?;
+            chrono::NaiveDate::from_ymd_opt(time.year.into(), time.month.into(), time.day.into())?
+                .and_hms_opt(time.hour.into(), time.minute.into(), time.second.into())
+        })
+}
+
+/// Camera make and model, e.g. "FUJIFILM X-T3"
+pub fn camera_model(path: &Path) -> Option<String> {
+    let file = fs::File::open(path).ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+    let text = |tag| {
+        exif.get_field(tag, exif::In::PRIMARY)
+            .map(|field| {
+                field
+                    .display_value()
+                    .to_string()
+                    .trim_matches('"')
+                    .trim()
+                    .to_string()
+            })
+            .filter(|value| !value.is_empty())
+    };
+    match (text(exif::Tag::Make), text(exif::Tag::Model)) {
+        // Many models already start with the make ("Canon EOS R6")
+        (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => make.or(model),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn files_without_exif_have_no_capture_time() {
+        // The app falls back to the mtime for these
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        assert_eq!(capture_time(&dir.path().join("a.txt")), None);
+        assert_eq!(capture_time(&dir.path().join("missing.jpg")), None);
+    }
+}
@@ -1,8 +1,9 @@
 use crate::state::{SearchOptions, SearchResult};
 use calamine::{open_workbook, Reader, Xls, Xlsx};
 use docx_rs::read_docx;
-use grep_matcher::Matcher;
-use grep_regex::RegexMatcherBuilder;
+use flate2::read::GzDecoder;
+use grep_matcher::{Captures, Matcher};
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
 use grep_searcher::{Searcher, Sink, SinkMatch};
 use ignore::WalkBuilder;
 use lopdf::Document as PdfDocument;
@@ -11,6 +12,7 @@ use std::path::{Path, PathBuf};
 use std::sync::mpsc::SyncSender;
 use zip::ZipArchive;
 
+use super::directory::skip_dirs;
 use super::worker::IoResult;
 
 struct SearchSink {
@@ -59,6 +61,7 @@ impl Sink for SearchSink {
             line_content: trimmed_content,
             match_start,
             match_end,
+            archive_member: None,
         });
 
         Ok(true)
@@ -128,6 +131,7 @@ fn search_pdf_content(path: &Path, query: &str, case_sensitive: bool) -> Vec<Sea
                         line_content: line.trim().to_string(),
                         match_start: pos,
                         match_end: pos + search_query.len(),
+                        archive_member: None,
                     });
                 }
             }
@@ -137,59 +141,295 @@ fn search_pdf_content(path: &Path, query: &str, case_sensitive: bool) -> Vec<Sea
     results
 }
 
-fn search_zip_archive(path: &Path, query: &str, case_sensitive: bool) -> Vec<SearchResult> {
+/// Archive formats whose members can be searched
+#[derive(Clone, Copy, PartialEq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    /// A single gzip-compressed file such as `app.log.gz`
+    Gzip,
+}
+
+fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".gz") {
+        Some(ArchiveKind::Gzip)
+    } else {
+        None
+    }
+}
+
+/// Name shown for the only member of a plain `.gz` file
+fn gzip_member_name(path: &Path) -> String {
+    path.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Line matching shared by the archive searchers
+fn search_member_text(
+    path: &Path,
+    member: &str,
+    contents: &str,
+    query: &str,
+    case_sensitive: bool,
+    results: &mut Vec<SearchResult>,
+) {
+    let search_query = if case_sensitive {
+        query.to_string()
+    } else {
+        query.to_lowercase()
+    };
+
+    for (line_num, line) in contents.lines().enumerate() {
+        let check_line = if case_sensitive {
+            line.to_string()
+        } else {
+            line.to_lowercase()
+        };
+        if let Some(pos) = check_line.find(&search_query) {
+            results.push(SearchResult {
+                file_path: path.to_path_buf(),
+                file_name: format!(
+                    "{} -> {}",
+                    path.file_name().unwrap_or_default().to_string_lossy(),
+                    member
+                ),
+                line_number: line_num + 1,
+                line_content: line.trim().to_string(),
+                match_start: pos,
+                match_end: pos + search_query.len(),
+                archive_member: Some(member.to_string()),
+            });
+        }
+    }
+}
+
+/// Read up to `budget` bytes of a member; binary members yield `None`
+fn read_member_text(reader: impl std::io::Read, budget: &mut u64) -> Option<String> {
+    let mut data = Vec::new();
+    let read = std::io::Read::read_to_end(&mut reader.take(*budget), &mut data).ok()?;
+    *budget = budget.saturating_sub(read as u64);
+    if data.contains(&0) {
+        return None;
+    }
+    String::from_utf8(data).ok()
+}
+
+/// Search the text members of a zip, tar, tar.gz or gz archive
+///
+/// At most `max_bytes` of decompressed data are read per archive, so a huge or
+/// maliciously compressed archive cannot stall the search.
+fn search_archive(
+    path: &Path,
+    kind: ArchiveKind,
+    query: &str,
+    case_sensitive: bool,
+    max_bytes: u64,
+) -> Vec<SearchResult> {
     let mut results = Vec::new();
+    let mut budget = max_bytes;
+    let Ok(file) = fs::File::open(path) else {
+        return results;
+    };
 
-    if let Ok(file) = fs::File::open(path) {
-        if let Ok(mut archive) = ZipArchive::new(file) {
+    match kind {
+        ArchiveKind::Zip => {
+            let Ok(mut archive) = ZipArchive::new(file) else {
+                return results;
+            };
             for i in 0..archive.len() {
-                if let Ok(mut file) = archive.by_index(i) {
-                    let file_name = file.name().to_string();
-
-                    if file.is_file() && !file.name().ends_with('/') {
-                        let mut contents = String::new();
-                        if std::io::Read::read_to_string(&mut file, &mut contents).is_ok() {
-                            let search_query = if case_sensitive {
-                                query.to_string()
-                            } else {
-                                query.to_lowercase()
-                            };
-
-                            for (line_num, line) in contents.lines().enumerate() {
-                                let check_line = if case_sensitive {
-                                    line.to_string()
-                                } else {
-                                    line.to_lowercase()
-                                };
-                                if check_line.contains(&search_query) {
-                                    if let Some(pos) = check_line.find(&search_query) {
-                                        results.push(SearchResult {
-                                            file_path: path.to_path_buf(),
-                                            file_name: format!(
-                                                "{} -> {}",
-                                                path.file_name()
-                                                    .unwrap_or_default()
-                                                    .to_string_lossy(),
-                                                file_name
-                                            ),
-                                            line_number: line_num + 1,
-                                            line_content: line.trim().to_string(),
-                                            match_start: pos,
-                                            match_end: pos + search_query.len(),
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                    }
+                if budget == 0 {
+                    break;
+                }
+                let Ok(member) = archive.by_index(i) else {
+                    continue;
+                };
+                if !member.is_file() {
+                    continue;
+                }
+                let name = member.name().to_string();
+                if let Some(contents) = read_member_text(member, &mut budget) {
+                    search_member_text(path, &name, &contents, query, case_sensitive, &mut results);
                 }
             }
         }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let reader: Box<dyn std::io::Read> = if kind == ArchiveKind::TarGz {
+                Box::new(GzDecoder::new(file))
+            } else {
+                Box::new(file)
+            };
+            let mut archive = tar::Archive::new(reader);
+            let Ok(entries) = archive.entries() else {
+                return results;
+            };
+            for member in entries.filter_map(|e| e.ok()) {
+                if budget == 0 {
+                    break;
+                }
+                if !member.header().entry_type().is_file() {
+                    continue;
+                }
+                let Ok(name) = member.path().map(|p| p.to_string_lossy().to_string()) else {
+                    continue;
+                };
+                if let Some(contents) = read_member_text(member, &mut budget) {
+                    search_member_text(path, &name, &contents, query, case_sensitive, &mut results);
+                }
+            }
+        }
+        ArchiveKind::Gzip => {
+            if let Some(contents) = read_member_text(GzDecoder::new(file), &mut budget) {
+                search_member_text(
+                    path,
+                    &gzip_member_name(path),
+                    &contents,
+                    query,
+                    case_sensitive,
+                    &mut results,
+                );
+            }
+        }
     }
 
     results
 }
 
+/// Text shown for an archive match is cut off after this many bytes
+const MEMBER_PREVIEW_LIMIT: u64 = 4 * 1024 * 1024;
+
+/// Members larger than this are not extracted, so a compression bomb cannot fill the disk
+const MEMBER_EXTRACT_LIMIT: u64 = 1024 * 1024 * 1024;
+
+/// Find one archive member and hand its decompressed stream to `read`
+fn with_archive_member<T>(
+    archive_path: &Path,
+    member: &str,
+    read: impl FnOnce(&mut dyn std::io::Read) -> std::io::Result<T>,
+) -> std::io::Result<T> {
+    let not_found = || {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{} not found in archive", member),
+        )
+    };
+    let kind = archive_kind(archive_path).ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "not a searchable archive")
+    })?;
+    let file = fs::File::open(archive_path)?;
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = ZipArchive::new(file)?;
+            let mut entry = archive.by_name(member)?;
+            read(&mut entry)
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let reader: Box<dyn std::io::Read> = if kind == ArchiveKind::TarGz {
+                Box::new(GzDecoder::new(file))
+            } else {
+                Box::new(file)
+            };
+            let mut archive = tar::Archive::new(reader);
+            let mut entry = archive
+                .entries()?
+                .filter_map(|e| e.ok())
+                .find(|e| {
+                    e.path()
+                        .map(|p| p.to_string_lossy() == member)
+                        .unwrap_or(false)
+                })
+                .ok_or_else(not_found)?;
+            read(&mut entry)
+        }
+        ArchiveKind::Gzip => {
+            if member != gzip_member_name(archive_path) {
+                return Err(not_found());
+            }
+            read(&mut GzDecoder::new(file))
+        }
+    }
+}
+
+/// Read the start of one archive member as text, for previewing a match inside an archive
+///
+/// Only the first `MEMBER_PREVIEW_LIMIT` bytes are decompressed. Runs on the IO worker.
+pub fn read_member_for_preview(archive: &Path, member: &str) -> std::io::Result<String> {
+    let data = with_archive_member(archive, member, |reader| {
+        let mut data = Vec::new();
+        std::io::Read::read_to_end(
+            &mut std::io::Read::take(reader, MEMBER_PREVIEW_LIMIT),
+            &mut data,
+        )?;
+        Ok(data)
+    })?;
+    Ok(String::from_utf8_lossy(&data).into_owned())
+}
+
+/// Extract one archive member into Heike's temp directory so it can be opened
+///
+/// Each archive gets its own subdirectory and the member keeps its relative path,
+/// so editors show a recognisable name. Members that would escape that directory
+/// (absolute paths, `..`) are refused, as are members over `MEMBER_EXTRACT_LIMIT`.
+/// The member is streamed to disk on the IO worker.
+pub fn extract_archive_member(archive_path: &Path, member: &str) -> std::io::Result<PathBuf> {
+    use std::hash::{Hash, Hasher};
+    use std::path::Component;
+
+    let relative = Path::new(member);
+    if relative.as_os_str().is_empty()
+        || !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unsafe member path: {}", member),
+        ));
+    }
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    archive_path.hash(&mut hasher);
+    let target = std::env::temp_dir()
+        .join("heike-archives")
+        .join(format!("{:016x}", hasher.finish()))
+        .join(relative);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let written = with_archive_member(archive_path, member, |reader| {
+        let mut out = fs::File::create(&target)?;
+        std::io::copy(
+            &mut std::io::Read::take(reader, MEMBER_EXTRACT_LIMIT + 1),
+            &mut out,
+        )
+    });
+    match written {
+        Ok(bytes) if bytes <= MEMBER_EXTRACT_LIMIT => Ok(target),
+        Ok(_) => {
+            let _ = fs::remove_file(&target);
+            Err(std::io::Error::other(format!(
+                "{} is larger than {}",
+                member,
+                bytesize::ByteSize(MEMBER_EXTRACT_LIMIT)
+            )))
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&target);
+            Err(e)
+        }
+    }
+}
+
 fn search_docx_content(path: &Path, query: &str, case_sensitive: bool) -> Vec<SearchResult> {
     let mut results = Vec::new();
 
@@ -236,6 +476,7 @@ fn search_docx_content(path: &Path, query: &str, case_sensitive: bool) -> Vec<Se
                             line_content: line.trim().to_string(),
                             match_start: pos,
                             match_end: pos + search_query.len(),
+                            archive_member: None,
                         });
                     }
                 }
@@ -298,6 +539,7 @@ fn search_xlsx_content(path: &Path, query: &str, case_sensitive: bool) -> Vec<Se
                                             line_content: cell_text.trim().to_string(),
                                             match_start: pos,
                                             match_end: pos + search_query.len(),
+                                            archive_member: None,
                                         });
                                     }
                                 }
@@ -318,24 +560,119 @@ fn search_xlsx_content(path: &Path, query: &str, case_sensitive: bool) -> Vec<Se
     results
 }
 
+/// Build the matcher content search uses, so replace previews agree with its hits
+pub fn build_matcher(
+    query: &str,
+    options: &SearchOptions,
+) -> Result<RegexMatcher, grep_regex::Error> {
+    RegexMatcherBuilder::new()
+        .case_insensitive(!options.case_sensitive)
+        .build(query)
+}
+
+/// What a regex replace would do to one line, shown before anything is applied
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplacePreview {
+    /// `$0`, `$1`, … of the first match; `None` for groups that did not participate
+    pub groups: Vec<Option<String>>,
+    /// The line with every match substituted
+    pub replaced: String,
+}
+
+/// Substitute `replacement` (`$1`, `${name}`, `$$`) into every match in `line`
+pub fn replace_preview(
+    matcher: &RegexMatcher,
+    line: &str,
+    replacement: &str,
+) -> Option<ReplacePreview> {
+    let haystack = line.as_bytes();
+    let mut caps = matcher.new_captures().ok()?;
+    if !matcher.captures(haystack, &mut caps).ok()? {
+        return None;
+    }
+    let groups = (0..caps.len())
+        .map(|i| {
+            caps.get(i)
+                .map(|m| String::from_utf8_lossy(&haystack[m]).into_owned())
+        })
+        .collect();
+
+    let mut replaced = Vec::new();
+    matcher
+        .replace_with_captures(haystack, &mut caps, &mut replaced, |caps, dst| {
+            caps.interpolate(
+                |name| matcher.capture_index(name),
+                haystack,
+                replacement.as_bytes(),
+                dst,
+            );
+            true
+        })
+        .ok()?;
+
+    Some(ReplacePreview {
+        groups,
+        replaced: String::from_utf8_lossy(&replaced).into_owned(),
+    })
+}
+
+/// Capture group names by index, for labelling groups in the replace preview
+///
+/// The matcher only maps names to indices, so candidate names are taken from the
+/// `(?P<name>…)` / `(?<name>…)` syntax in the pattern and checked against it.
+pub fn capture_names(matcher: &RegexMatcher, pattern: &str) -> Vec<Option<String>> {
+    let count = matcher
+        .new_captures()
+        .map(|caps| caps.len())
+        .unwrap_or_default();
+    let mut names = vec![None; count];
+    for part in pattern.split("(?").skip(1) {
+        let part = part.strip_prefix('P').unwrap_or(part);
+        let Some(rest) = part.strip_prefix('<') else {
+            continue;
+        };
+        let Some(end) = rest.find('>') else {
+            continue;
+        };
+        let name = &rest[..end];
+        if let Some(index) = matcher.capture_index(name) {
+            if let Some(slot) = names.get_mut(index) {
+                *slot = Some(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// Search every file under `roots`, returning the hits and the files cut short by
+/// `max_per_file`
 pub fn perform_search(
     query: &str,
-    root: &Path,
+    roots: &[PathBuf],
     options: &SearchOptions,
     progress_tx: &SyncSender<IoResult>,
-) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+) -> Result<(Vec<SearchResult>, Vec<PathBuf>), Box<dyn std::error::Error>> {
     let mut all_results = Vec::new();
+    let mut capped = Vec::new();
+    let per_file = match options.max_per_file {
+        0 => usize::MAX,
+        cap => cap,
+    };
     let mut files_searched = 0;
     let mut files_skipped = 0;
     let mut errors = 0;
 
-    let matcher = RegexMatcherBuilder::new()
-        .case_insensitive(!options.case_sensitive)
-        .build(query)?;
+    let matcher = build_matcher(query, options)?;
 
-    let walker = WalkBuilder::new(root)
-        .hidden(!options.search_hidden)
-        .build();
+    let Some((first, others)) = roots.split_first() else {
+        return Ok((all_results, capped));
+    };
+    let mut walker = WalkBuilder::new(first);
+    for root in others {
+        walker.add(root);
+    }
+    skip_dirs(&mut walker, &options.skip_dirs);
+    let walker = walker.hidden(!options.search_hidden).build();
 
     for entry in walker {
         let entry = match entry {
@@ -360,7 +697,7 @@ pub fn perform_search(
         // Check if we should skip this file type
         let should_search = match extension.as_str() {
             "pdf" => options.search_pdfs,
-            "zip" => options.search_archives,
+            _ if archive_kind(path).is_some() => options.search_archives,
             "docx" | "doc" | "xlsx" | "xls" => true,
             _ => true, // Text files
         };
@@ -381,18 +718,25 @@ pub fn perform_search(
             });
         }
 
-        let mut file_results = match extension.as_str() {
-            "pdf" if options.search_pdfs => search_pdf_content(path, query, options.case_sensitive),
-            "zip" if options.search_archives => {
-                search_zip_archive(path, query, options.case_sensitive)
+        let mut file_results = match (extension.as_str(), archive_kind(path)) {
+            (_, Some(kind)) => search_archive(
+                path,
+                kind,
+                query,
+                options.case_sensitive,
+                options.max_archive_bytes,
+            ),
+            ("pdf", _) if options.search_pdfs => {
+                search_pdf_content(path, query, options.case_sensitive)
             }
-            "docx" | "doc" => search_docx_content(path, query, options.case_sensitive),
-            "xlsx" | "xls" => search_xlsx_content(path, query, options.case_sensitive),
+            ("docx" | "doc", _) => search_docx_content(path, query, options.case_sensitive),
+            ("xlsx" | "xls", _) => search_xlsx_content(path, query, options.case_sensitive),
             _ => {
                 match search_text_file(
                     path,
                     &matcher,
-                    options.max_results - all_results.len(),
+                    // One past the cap tells a capped file from one that just fits
+                    (options.max_results - all_results.len()).min(per_file.saturating_add(1)),
                     query,
                     options.case_sensitive,
                 ) {
@@ -405,6 +749,10 @@ pub fn perform_search(
             }
         };
 
+        if file_results.len() > per_file {
+            file_results.truncate(per_file);
+            capped.push(path.to_path_buf());
+        }
         all_results.append(&mut file_results);
 
         if all_results.len() >= options.max_results {
@@ -419,5 +767,179 @@ pub fn perform_search(
         errors,
     });
 
-    Ok(all_results)
+    Ok((all_results, capped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn tar_bytes(members: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, data) in members {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn archive_kind_follows_the_extension() {
+        let kind = |name: &str| archive_kind(Path::new(name));
+        assert!(kind("a.ZIP") == Some(ArchiveKind::Zip));
+        assert!(kind("a.tar") == Some(ArchiveKind::Tar));
+        assert!(kind("a.tar.gz") == Some(ArchiveKind::TarGz));
+        assert!(kind("a.tgz") == Some(ArchiveKind::TarGz));
+        assert!(kind("app.log.gz") == Some(ArchiveKind::Gzip));
+        assert!(kind("a.7z").is_none());
+        assert!(kind("notes.txt").is_none());
+    }
+
+    #[test]
+    fn archive_members_are_searched_within_the_byte_budget() {
+        let dir = TempDir::new().unwrap();
+        let members: [(&str, &[u8]); 3] = [
+            ("docs/readme.txt", b"intro\nthe Needle line\n"),
+            ("blob.bin", b"needle\0binary"),
+            ("later.txt", b"needle again\n"),
+        ];
+
+        let zip_path = dir.path().join("bundle.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        for (name, data) in members {
+            writer
+                .start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap();
+        let tar_path = dir.path().join("bundle.tgz");
+        fs::write(&tar_path, gzip(&tar_bytes(&members))).unwrap();
+
+        for path in [&zip_path, &tar_path] {
+            let kind = archive_kind(path).unwrap();
+            let hits = search_archive(path, kind, "needle", false, u64::MAX);
+            let found: Vec<(&str, usize)> = hits
+                .iter()
+                .map(|r| (r.archive_member.as_deref().unwrap(), r.line_number))
+                .collect();
+            assert_eq!(
+                found,
+                [("docs/readme.txt", 2), ("later.txt", 1)],
+                "{}",
+                path.display()
+            );
+            assert!(search_archive(path, kind, "needle", true, u64::MAX).len() == 1);
+
+            // The budget runs out inside the binary member, so later ones are never read
+            let hits = search_archive(path, kind, "needle", false, 25);
+            assert_eq!(hits.len(), 1, "{}", path.display());
+        }
+
+        let gz_path = dir.path().join("app.log.gz");
+        fs::write(&gz_path, gzip(b"start\nneedle\n")).unwrap();
+        let hits = search_archive(&gz_path, ArchiveKind::Gzip, "needle", false, u64::MAX);
+        assert_eq!(hits[0].archive_member.as_deref(), Some("app.log"));
+        assert_eq!(
+            read_member_for_preview(&gz_path, "app.log").unwrap(),
+            "start\nneedle\n"
+        );
+    }
+
+    #[test]
+    fn extraction_refuses_members_outside_its_folder() {
+        let dir = TempDir::new().unwrap();
+        let tar_path = dir.path().join("bundle.tar");
+        fs::write(&tar_path, tar_bytes(&[("notes/a.txt", b"hello")])).unwrap();
+
+        let extracted = extract_archive_member(&tar_path, "notes/a.txt").unwrap();
+        assert!(extracted.ends_with("notes/a.txt"));
+        assert_eq!(fs::read(&extracted).unwrap(), b"hello");
+        assert!(extract_archive_member(&tar_path, "../a.txt").is_err());
+        assert!(extract_archive_member(&tar_path, "notes/missing.txt").is_err());
+        fs::remove_dir_all(extracted.parent().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn content_search_merges_hits_from_several_roots() {
+        use crate::state::{distinct_roots, root_of};
+
+        let first = TempDir::new().unwrap();
+        fs::create_dir(first.path().join("docs")).unwrap();
+        fs::write(first.path().join("docs/hi.txt"), "hello").unwrap();
+        let second = TempDir::new().unwrap();
+        fs::write(second.path().join("todo.txt"), "hello again").unwrap();
+        let roots = distinct_roots(vec![
+            first.path().to_path_buf(),
+            first.path().join("docs"),
+            second.path().to_path_buf(),
+        ]);
+
+        let (tx, _rx) = std::sync::mpsc::sync_channel(64);
+        let (results, _) = perform_search("hello", &roots, &SearchOptions::default(), &tx).unwrap();
+        let mut found: Vec<&Path> = results
+            .iter()
+            .map(|hit| root_of(&hit.file_path, &roots).unwrap().as_path())
+            .collect();
+        found.sort();
+        let mut expected = vec![first.path(), second.path()];
+        expected.sort();
+        assert_eq!(found, expected, "each file is found once, under its root");
+    }
+
+    #[test]
+    fn content_search_caps_matches_per_file() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a_few.txt"), "hit\nhit\n").unwrap();
+        fs::write(dir.path().join("b_many.txt"), "hit\n".repeat(50)).unwrap();
+        let options = SearchOptions {
+            max_per_file: 5,
+            ..SearchOptions::default()
+        };
+
+        let (tx, _rx) = std::sync::mpsc::sync_channel(64);
+        let (results, capped) =
+            perform_search("hit", &[dir.path().to_path_buf()], &options, &tx).unwrap();
+        let many = dir.path().join("b_many.txt");
+        assert_eq!(capped, vec![many.clone()]);
+        let kept = |path: &Path| results.iter().filter(|r| r.file_path == path).count();
+        assert_eq!(kept(&many), 5);
+        assert_eq!(kept(&dir.path().join("a_few.txt")), 2);
+    }
+
+    #[test]
+    fn build_folders_are_skipped_unless_searched_directly() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "needle").unwrap();
+        fs::create_dir_all(dir.path().join("web/node_modules/pkg")).unwrap();
+        fs::write(dir.path().join("web/node_modules/pkg/index.js"), "needle").unwrap();
+        fs::write(dir.path().join("web/app.js"), "needle").unwrap();
+
+        let (tx, _rx) = std::sync::mpsc::sync_channel(64);
+        let search = |root: PathBuf, skip_dirs: Vec<String>| {
+            let options = SearchOptions {
+                skip_dirs,
+                ..SearchOptions::default()
+            };
+            perform_search("needle", &[root], &options, &tx)
+                .unwrap()
+                .0
+                .len()
+        };
+        let skip = vec!["node_modules".to_string()];
+        assert_eq!(search(dir.path().to_path_buf(), Vec::new()), 3);
+        assert_eq!(search(dir.path().to_path_buf(), skip.clone()), 2);
+        assert_eq!(search(dir.path().join("web/node_modules"), skip), 1);
+    }
 }
@@ -0,0 +1,49 @@
+// Tabs and their back/forward history, kept in a session file between runs
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionTab {
+    #[serde(default)]
+    pub history: Vec<String>,
+    #[serde(default)]
+    pub history_index: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default)]
+    pub active_tab: usize,
+    #[serde(default)]
+    pub tabs: Vec<SessionTab>,
+}
+
+impl Session {
+    /// The last saved session; a missing or damaged file counts as no session
+    pub fn load() -> Option<Self> {
+        let contents = fs::read_to_string(Self::file_path()?).ok()?;
+        match toml::from_str(&contents) {
+            Ok(session) => Some(session),
+            Err(e) => {
+                eprintln!("Failed to parse session file: {}", e);
+                None
+            }
+        }
+    }
+
+    fn file_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", "heike").map(|d| d.data_dir().join("session.toml"))
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::file_path()
+            .ok_or_else(|| io::Error::other("Could not determine data directory"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+}
@@ -0,0 +1,324 @@
+// Splitting big files into numbered parts with a manifest, and joining them back
+use super::fileops::{copy_stream, unique_destination, Progress};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Largest file FAT32 can hold, for `:split fat32`
+pub const FAT32_MAX_FILE: u64 = 4 * 1024 * 1024 * 1024 - 1;
+
+/// Suffix of the manifest written next to the parts
+pub const MANIFEST_SUFFIX: &str = ".split.toml";
+
+/// Refuse splits that would litter a folder with more parts than this
+const MAX_PARTS: u64 = 99_999;
+
+/// What a split produced, so a join can check it has every part
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SplitManifest {
+    /// Name of the original file
+    pub name: String,
+    pub size: u64,
+    pub chunk_size: u64,
+    /// Part file names, in order, next to the manifest
+    pub parts: Vec<String>,
+}
+
+/// Parse a chunk size like "650M", "4GiB", "1.5g" or "fat32" into bytes (units are binary)
+pub fn parse_chunk_size(text: &str) -> Option<u64> {
+    let text = text.trim().to_lowercase();
+    if text == "fat32" {
+        return Some(FAT32_MAX_FILE);
+    }
+    let unit_start = text
+        .find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(unit_start);
+    let multiplier: u64 = match unit.trim().trim_end_matches("ib").trim_end_matches('b') {
+        "" => 1,
+        "k" => 1024,
+        "m" => 1024 * 1024,
+        "g" => 1024 * 1024 * 1024,
+        "t" => 1024 * 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    let bytes = (number.parse::<f64>().ok()? * multiplier as f64) as u64;
+    (bytes > 0).then_some(bytes)
+}
+
+/// Name of part `index` (0-based) of `name`: "movie.mkv.001", …; wider when there are
+/// more than 999 parts so the names still sort in order
+fn part_name(name: &str, index: u64, count: u64) -> String {
+    let width = count.to_string().len().max(3);
+    format!("{}.{:0width$}", name, index + 1, width = width)
+}
+
+/// Paths a split of `source` into `chunk_size` pieces writes: the parts, then the manifest
+pub fn split_outputs(source: &Path, chunk_size: u64) -> Result<Vec<PathBuf>, String> {
+    let meta = fs::metadata(source).map_err(|e| e.to_string())?;
+    if !meta.is_file() {
+        return Err("only files can be split".into());
+    }
+    let name = source
+        .file_name()
+        .ok_or("no file name")?
+        .to_string_lossy()
+        .into_owned();
+    let count = meta.len().div_ceil(chunk_size);
+    if count < 2 {
+        return Err(format!(
+            "{} already fits in one {} part",
+            name,
+            bytesize::ByteSize(chunk_size)
+        ));
+    }
+    if count > MAX_PARTS {
+        return Err(format!("{} parts is too many, pick a bigger size", count));
+    }
+    let dir = source.parent().unwrap_or(Path::new(""));
+    let mut outputs: Vec<PathBuf> = (0..count)
+        .map(|index| dir.join(part_name(&name, index, count)))
+        .collect();
+    outputs.push(dir.join(format!("{}{}", name, MANIFEST_SUFFIX)));
+    if let Some(taken) = outputs
+        .iter()
+        .find(|path| fs::symlink_metadata(path).is_ok())
+    {
+        return Err(format!("{} already exists", taken.display()));
+    }
+    Ok(outputs)
+}
+
+/// Index of a part from its numeric extension, e.g. 3 for "movie.mkv.004"
+fn part_index(part: &Path) -> Option<u64> {
+    let number: u64 = part.extension()?.to_str()?.parse().ok()?;
+    number.checked_sub(1)
+}
+
+/// Write one output of a split: a part cut from `source`, or the manifest when
+/// `output` is the manifest path
+pub fn write_split_output(
+    source: &Path,
+    chunk_size: u64,
+    output: &Path,
+    progress: Progress,
+) -> Result<bool, String> {
+    let is_manifest = output.to_string_lossy().ends_with(MANIFEST_SUFFIX);
+    let result = if is_manifest {
+        write_manifest(source, chunk_size, output)
+    } else {
+        let index = part_index(output).ok_or("not a numbered part")?;
+        write_chunk(source, chunk_size, index, output, progress)
+    };
+    result.map(|_| true).map_err(|e| e.to_string())
+}
+
+fn write_chunk(
+    source: &Path,
+    chunk_size: u64,
+    index: u64,
+    part: &Path,
+    progress: Progress,
+) -> io::Result<()> {
+    let mut input = File::open(source)?;
+    input.seek(SeekFrom::Start(index * chunk_size))?;
+    let mut output = BufWriter::new(File::create(part)?);
+    copy_stream(&mut input.take(chunk_size), &mut output, progress)?;
+    output.flush()
+}
+
+fn write_manifest(source: &Path, chunk_size: u64, manifest: &Path) -> io::Result<()> {
+    let size = fs::metadata(source)?.len();
+    let name = source
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let count = size.div_ceil(chunk_size);
+    let contents = SplitManifest {
+        parts: (0..count)
+            .map(|index| part_name(&name, index, count))
+            .collect(),
+        name,
+        size,
+        chunk_size,
+    };
+    let text = toml::to_string_pretty(&contents).map_err(io::Error::other)?;
+    fs::write(manifest, text)
+}
+
+/// The file a join writes and the parts it reads, in order, from a manifest or any
+/// numbered part. Parts are checked against the manifest's sizes when there is one.
+pub fn join_inputs(selected: &Path) -> Result<(PathBuf, Vec<PathBuf>), String> {
+    let dir = selected.parent().unwrap_or(Path::new(""));
+    let selected_name = selected
+        .file_name()
+        .ok_or("no file name")?
+        .to_string_lossy()
+        .into_owned();
+
+    if let Some(name) = selected_name.strip_suffix(MANIFEST_SUFFIX) {
+        let text = fs::read_to_string(selected).map_err(|e| e.to_string())?;
+        let manifest: SplitManifest = toml::from_str(&text).map_err(|e| e.to_string())?;
+        // Only bare names count; a manifest cannot reach outside its folder
+        let bare = |name: &str| {
+            Path::new(name)
+                .file_name()
+                .filter(|bare| *bare == name)
+                .map(|bare| dir.join(bare))
+                .ok_or_else(|| format!("bad name in manifest: {}", name))
+        };
+        let parts = manifest
+            .parts
+            .iter()
+            .map(|part| bare(part))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut total = 0;
+        for (index, part) in parts.iter().enumerate() {
+            let size = fs::metadata(part)
+                .map_err(|_| format!("{} is missing", part.display()))?
+                .len();
+            let last = index + 1 == parts.len();
+            if (!last && size != manifest.chunk_size) || (last && size > manifest.chunk_size) {
+                return Err(format!("{} has the wrong size", part.display()));
+            }
+            total += size;
+        }
+        if total != manifest.size {
+            return Err(format!(
+                "parts add up to {} bytes, expected {}",
+                total, manifest.size
+            ));
+        }
+        let dest = if manifest.name.is_empty() {
+            dir.join(name)
+        } else {
+            bare(&manifest.name)?
+        };
+        return Ok((unique_destination_if_taken(&dest), parts));
+    }
+
+    let (name, number) = selected_name
+        .rsplit_once('.')
+        .filter(|(_, number)| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+        .ok_or("select a numbered part (.001) or a split manifest")?;
+    let width = number.len();
+    let parts: Vec<PathBuf> = (1..)
+        .map(|index| dir.join(format!("{}.{:0width$}", name, index, width = width)))
+        .take_while(|part| part.is_file())
+        .collect();
+    if parts.len() < 2 {
+        return Err(format!("no parts of {} to join", name));
+    }
+    Ok((unique_destination_if_taken(&dir.join(name)), parts))
+}
+
+fn unique_destination_if_taken(path: &Path) -> PathBuf {
+    if fs::symlink_metadata(path).is_ok() {
+        unique_destination(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Copy one part into `dest` at its place after the parts before it; writing at a fixed
+/// offset makes a retried part overwrite its earlier attempt instead of appending twice
+pub fn join_part(
+    parts: &[PathBuf],
+    part: &Path,
+    dest: &Path,
+    progress: Progress,
+) -> Result<bool, String> {
+    let index = parts
+        .iter()
+        .position(|p| p == part)
+        .ok_or("not one of the parts")?;
+    let result = (|| {
+        let mut offset = 0;
+        for before in &parts[..index] {
+            offset += fs::metadata(before)?.len();
+        }
+        let mut output = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dest)?;
+        output.seek(SeekFrom::Start(offset))?;
+        let mut writer = BufWriter::new(&mut output);
+        let copied = copy_stream(&mut File::open(part)?, &mut writer, progress)?;
+        writer.flush()?;
+        drop(writer);
+        if index + 1 == parts.len() {
+            output.set_len(offset + copied)?;
+        }
+        Ok::<_, io::Error>(())
+    })();
+    result.map(|_| true).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn chunk_sizes_parse_with_binary_units() {
+        assert_eq!(parse_chunk_size("650M"), Some(650 * 1024 * 1024));
+        assert_eq!(parse_chunk_size("4GiB"), Some(4 * 1024 * 1024 * 1024));
+        assert_eq!(parse_chunk_size(" 1.5KiB "), Some(1536));
+        assert_eq!(parse_chunk_size("2048"), Some(2048));
+        assert_eq!(parse_chunk_size("FAT32"), Some(FAT32_MAX_FILE));
+        assert_eq!(parse_chunk_size("0M"), None);
+        assert_eq!(parse_chunk_size("12 parsecs"), None);
+    }
+
+    #[test]
+    fn part_names_sort_in_order() {
+        assert_eq!(part_name("movie.mkv", 0, 3), "movie.mkv.001");
+        assert_eq!(part_name("movie.mkv", 41, 999), "movie.mkv.042");
+        assert_eq!(part_name("movie.mkv", 41, 1000), "movie.mkv.0042");
+        assert_eq!(part_index(Path::new("movie.mkv.0042")), Some(41));
+    }
+
+    #[test]
+    fn split_parts_join_back_into_the_original() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("big.bin");
+        let data: Vec<u8> = (0..2500u32).map(|n| (n % 251) as u8).collect();
+        fs::write(&source, &data).unwrap();
+
+        let outputs = split_outputs(&source, 1000).unwrap();
+        let names: Vec<String> = outputs
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "big.bin.001",
+                "big.bin.002",
+                "big.bin.003",
+                "big.bin.split.toml"
+            ]
+        );
+        for output in &outputs {
+            assert!(write_split_output(&source, 1000, output, &mut |_| true).unwrap());
+        }
+        assert_eq!(fs::metadata(&outputs[2]).unwrap().len(), 500);
+        assert!(split_outputs(&source, 1000).is_err(), "parts already exist");
+
+        // The manifest names the original, which still exists, so the join is numbered
+        let (dest, parts) = join_inputs(&outputs[3]).unwrap();
+        assert_eq!(dest, dir.path().join("big (1).bin"));
+        assert_eq!(join_inputs(&outputs[1]).unwrap().1, parts);
+        // Parts may arrive out of order or twice (a retry) and still land in place
+        for part in [&parts[1], &parts[0], &parts[2], &parts[1]] {
+            join_part(&parts, part, &dest, &mut |_| true).unwrap();
+        }
+        assert_eq!(fs::read(&dest).unwrap(), data);
+
+        fs::write(&parts[1], "short").unwrap();
+        assert!(join_inputs(&outputs[3]).unwrap_err().contains("wrong size"));
+    }
+}
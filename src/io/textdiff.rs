@@ -0,0 +1,208 @@
+// Line diffs between two small text files, for deciding a paste conflict
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// Largest file compared; bigger ones are left to a real diff tool
+pub const MAX_DIFF_BYTES: u64 = 1024 * 1024;
+
+/// Unchanged lines kept around each change
+const CONTEXT: usize = 3;
+
+/// Line pairs the LCS table may hold before falling back to "all removed, all added"
+const MAX_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    /// Only in the existing file
+    Removed(String),
+    /// Only in the incoming file
+    Added(String),
+    /// Unchanged lines folded away
+    Folded(usize),
+}
+
+/// Whether `path` is a regular file small enough to compare, without NUL bytes in its head
+pub fn is_comparable(path: &Path) -> bool {
+    let Ok(meta) = fs::metadata(path) else {
+        return false;
+    };
+    if !meta.is_file() || meta.len() > MAX_DIFF_BYTES {
+        return false;
+    }
+    let mut head = [0u8; 8192];
+    let Ok(read) = fs::File::open(path).and_then(|mut file| file.read(&mut head)) else {
+        return false;
+    };
+    !head[..read].contains(&0)
+}
+
+/// Diff the file already at a destination against the one about to replace it
+pub fn compare_files(existing: &Path, incoming: &Path) -> Result<Vec<DiffLine>, String> {
+    let read = |path: &Path| {
+        fs::read(path)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    };
+    Ok(diff_lines(&read(existing)?, &read(incoming)?))
+}
+
+/// Line diff of `old` against `new`, unchanged stretches folded down to their context
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Shared head and tail need no table
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_mid, new_mid) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut lines: Vec<DiffLine> = old[..prefix]
+        .iter()
+        .map(|line| DiffLine::Same(line.to_string()))
+        .collect();
+    lines.extend(diff_middle(old_mid, new_mid));
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Same(line.to_string())),
+    );
+    fold_unchanged(lines)
+}
+
+/// Longest-common-subsequence diff of the stretch between the shared head and tail
+fn diff_middle(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    let removed = || old.iter().map(|line| DiffLine::Removed(line.to_string()));
+    let added = || new.iter().map(|line| DiffLine::Added(line.to_string()));
+    if old.is_empty() || new.is_empty() || old.len() * new.len() > MAX_CELLS {
+        return removed().chain(added()).collect();
+    }
+
+    // table[i][j]: common lines of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut table = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines.extend(
+        old[i..]
+            .iter()
+            .map(|line| DiffLine::Removed(line.to_string())),
+    );
+    lines.extend(
+        new[j..]
+            .iter()
+            .map(|line| DiffLine::Added(line.to_string())),
+    );
+    lines
+}
+
+/// Replace unchanged runs longer than the context around changes with a fold marker
+fn fold_unchanged(lines: Vec<DiffLine>) -> Vec<DiffLine> {
+    let mut folded = Vec::with_capacity(lines.len());
+    let mut run: Vec<DiffLine> = Vec::new();
+    let flush = |run: &mut Vec<DiffLine>, folded: &mut Vec<DiffLine>, at_end: bool| {
+        let keep_before = if folded.is_empty() { 0 } else { CONTEXT };
+        let keep_after = if at_end { 0 } else { CONTEXT };
+        if run.len() > keep_before + keep_after {
+            let hidden = run.len() - keep_before - keep_after;
+            let after = run.split_off(keep_before + hidden);
+            run.truncate(keep_before);
+            folded.append(run);
+            folded.push(DiffLine::Folded(hidden));
+            folded.extend(after);
+        } else {
+            folded.append(run);
+        }
+    };
+    for line in lines {
+        if matches!(line, DiffLine::Same(_)) {
+            run.push(line);
+        } else {
+            flush(&mut run, &mut folded, false);
+            folded.push(line);
+        }
+    }
+    if !run.is_empty() {
+        flush(&mut run, &mut folded, true);
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn paste_compare_diffs_text_and_folds_unchanged_lines() {
+        let old: String = (1..=10).map(|n| format!("line {}\n", n)).collect();
+        let new = old.replace("line 5\n", "line five\n") + "line 11\n";
+        let same = |n: usize| DiffLine::Same(format!("line {}", n));
+        assert_eq!(
+            diff_lines(&old, &new),
+            vec![
+                DiffLine::Folded(1),
+                same(2),
+                same(3),
+                same(4),
+                DiffLine::Removed("line 5".into()),
+                DiffLine::Added("line five".into()),
+                same(6),
+                same(7),
+                same(8),
+                same(9),
+                same(10),
+                DiffLine::Added("line 11".into()),
+            ]
+        );
+
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        fs::write(dir.path().join("b.md"), "bravo").unwrap();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        let binary = dir.path().join("blob.bin");
+        fs::write(&binary, [0u8, 1, 2]).unwrap();
+        assert!(is_comparable(&dir.path().join("a.txt")));
+        assert!(!is_comparable(&binary));
+        assert!(!is_comparable(&dir.path().join("docs")));
+        assert_eq!(
+            compare_files(&dir.path().join("a.txt"), &dir.path().join("b.md")).unwrap(),
+            vec![
+                DiffLine::Removed("alpha".into()),
+                DiffLine::Added("bravo".into())
+            ]
+        );
+    }
+}
@@ -0,0 +1,478 @@
+// External tool discovery and launching (git, editor, terminal, archive helper)
+use crate::config::ToolsConfig;
+use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+
+/// Editors that need a terminal to run in when launched from the GUI
+const TERMINAL_EDITORS: &[&str] = &[
+    "vi", "vim", "nvim", "nano", "hx", "helix", "micro", "kak", "joe", "ne", "mg",
+];
+
+/// Terminal emulators probed on Linux/BSD when none is configured
+#[cfg(not(any(target_os = "macos", windows)))]
+const TERMINAL_CANDIDATES: &[&str] = &[
+    "x-terminal-emulator",
+    "gnome-terminal",
+    "konsole",
+    "xfce4-terminal",
+    "alacritty",
+    "kitty",
+    "wezterm",
+    "foot",
+    "xterm",
+];
+
+/// Archive helpers probed when none is configured; macOS and Windows ship bsdtar as `tar`
+#[cfg(any(target_os = "macos", windows))]
+const ARCHIVER_CANDIDATES: &[&str] = &["bsdtar", "tar"];
+#[cfg(not(any(target_os = "macos", windows)))]
+const ARCHIVER_CANDIDATES: &[&str] = &["bsdtar"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tool {
+    Git,
+    Editor,
+    Terminal,
+    /// bsdtar, which reads 7z, rar, xz and bz2 archives for their preview
+    Archiver,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 4] = [Tool::Git, Tool::Editor, Tool::Terminal, Tool::Archiver];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Tool::Git => "Git",
+            Tool::Editor => "Editor",
+            Tool::Terminal => "Terminal",
+            Tool::Archiver => "Archive helper",
+        }
+    }
+
+    /// Features that depend on this tool
+    pub fn purpose(self) -> &'static str {
+        match self {
+            Tool::Git => "Git status column, Git tab, branch menu",
+            Tool::Editor => "Open in Editor",
+            Tool::Terminal => "Open in Terminal, terminal editors",
+            Tool::Archiver => "7z, rar, xz and bz2 archive previews",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ToolStatus {
+    pub tool: Tool,
+    /// Command line that will be run (first word is the program)
+    pub command: Option<String>,
+    pub available: bool,
+    /// Explanation shown in tooltips and the diagnostics modal
+    pub detail: String,
+}
+
+/// Result of probing external tools once at startup
+#[derive(Clone, Debug, Default)]
+pub struct Capabilities {
+    statuses: Vec<ToolStatus>,
+    pub checked: bool,
+}
+
+impl Capabilities {
+    pub fn detect(config: &ToolsConfig) -> Self {
+        let statuses = Tool::ALL
+            .iter()
+            .map(|&tool| {
+                let command = configured_command(tool, config);
+                if config.check_on_startup {
+                    probe(tool, command)
+                } else {
+                    ToolStatus {
+                        tool,
+                        command,
+                        available: true,
+                        detail: "Not checked (tools.check_on_startup = false)".into(),
+                    }
+                }
+            })
+            .collect();
+        Self {
+            statuses,
+            checked: config.check_on_startup,
+        }
+    }
+
+    pub fn statuses(&self) -> &[ToolStatus] {
+        &self.statuses
+    }
+
+    pub fn status(&self, tool: Tool) -> Option<&ToolStatus> {
+        self.statuses.iter().find(|s| s.tool == tool)
+    }
+
+    /// Tooltip for a disabled action, `None` when the tool is usable
+    pub fn missing_reason(&self, tool: Tool) -> Option<String> {
+        match self.status(tool) {
+            Some(status) if status.available => None,
+            Some(status) => Some(format!("{} unavailable: {}", tool.label(), status.detail)),
+            None => Some(format!("{} unavailable", tool.label())),
+        }
+    }
+
+    /// Command line of a tool that was found, `None` when it is missing
+    pub fn usable_command(&self, tool: Tool) -> Option<String> {
+        self.status(tool)
+            .filter(|s| s.available)
+            .and_then(|s| s.command.clone())
+    }
+
+    fn command(&self, tool: Tool) -> io::Result<(String, Vec<String>)> {
+        self.status(tool)
+            .filter(|s| s.available)
+            .and_then(|s| s.command.as_deref())
+            .and_then(split_command)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    self.missing_reason(tool)
+                        .unwrap_or_else(|| format!("{} not configured", tool.label())),
+                )
+            })
+    }
+
+    /// Open a terminal window in `dir`
+    pub fn open_terminal(&self, dir: &Path) -> io::Result<()> {
+        let (program, mut args) = self.command(Tool::Terminal)?;
+        if cfg!(windows) && is_program(&program, "wt") {
+            args.push("-d".into());
+            args.push(dir.to_string_lossy().into_owned());
+        } else if cfg!(target_os = "macos") && is_program(&program, "open") {
+            args.push(dir.to_string_lossy().into_owned());
+        }
+        spawn_detached(Command::new(program).args(args).current_dir(dir))
+    }
+
+    /// Open `path` in the editor, jumping to `line` when the editor's syntax is known
+    ///
+    /// Terminal editors are wrapped in a terminal window.
+    pub fn open_in_editor_at(&self, path: &Path, line: Option<usize>) -> io::Result<()> {
+        let (program, _) = self.command(Tool::Editor)?;
+        self.launch_editor(&[editor_file_args(&program, path, line)], path)
+    }
+
+    /// Open several files in the editor, in one invocation unless the command
+    /// template asks for one per file (see `editor_invocations`)
+    pub fn open_files_in_editor(&self, paths: &[PathBuf]) -> io::Result<()> {
+        let Some(first) = paths.first() else {
+            return Ok(());
+        };
+        let files: Vec<Vec<String>> = paths
+            .iter()
+            .map(|p| vec![p.to_string_lossy().into_owned()])
+            .collect();
+        self.launch_editor(&files, first)
+    }
+
+    fn launch_editor(&self, files: &[Vec<String>], first: &Path) -> io::Result<()> {
+        let (program, template) = self.command(Tool::Editor)?;
+        let cwd = first.parent().unwrap_or(Path::new("."));
+
+        for args in editor_invocations(&template, files) {
+            if is_terminal_editor(&program, &args) {
+                self.run_in_terminal(&program, &args, cwd)?;
+            } else {
+                spawn_detached(Command::new(&program).args(args).current_dir(cwd))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn run_in_terminal(&self, program: &str, args: &[String], cwd: &Path) -> io::Result<()> {
+        let (terminal, mut term_args) = self.command(Tool::Terminal)?;
+
+        if cfg!(target_os = "macos") && is_program(&terminal, "open") {
+            let line = std::iter::once(program)
+                .chain(args.iter().map(String::as_str))
+                .map(shell_quote)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let script = format!(
+                "tell application \"Terminal\" to do script \"cd {} && {}\"",
+                applescript_escape(&shell_quote(&cwd.to_string_lossy())),
+                applescript_escape(&line)
+            );
+            return spawn_detached(Command::new("osascript").arg("-e").arg(script));
+        }
+
+        if cfg!(windows) && is_program(&terminal, "cmd") {
+            term_args.extend(["/c", "start", "", program].map(String::from));
+        } else {
+            term_args.extend(exec_flag(&terminal).iter().map(|s| s.to_string()));
+            term_args.push(program.to_string());
+        }
+        term_args.extend(args.iter().cloned());
+        spawn_detached(Command::new(terminal).args(term_args).current_dir(cwd))
+    }
+}
+
+fn configured_command(tool: Tool, config: &ToolsConfig) -> Option<String> {
+    let from_env = |var: &str| env::var(var).ok().filter(|v| !v.trim().is_empty());
+    let configured = |value: &Option<String>| value.clone().filter(|v| !v.trim().is_empty());
+    match tool {
+        Tool::Git => Some("git".into()),
+        Tool::Editor => configured(&config.editor)
+            .or_else(|| from_env("VISUAL"))
+            .or_else(|| from_env("EDITOR"))
+            .or_else(default_editor),
+        Tool::Terminal => configured(&config.terminal)
+            .or_else(|| from_env("TERMINAL"))
+            .or_else(default_terminal),
+        Tool::Archiver => configured(&config.archiver).or_else(|| {
+            ARCHIVER_CANDIDATES
+                .iter()
+                .find(|name| find_executable(name).is_some())
+                .map(|name| name.to_string())
+        }),
+    }
+}
+
+fn default_editor() -> Option<String> {
+    if cfg!(windows) {
+        Some("notepad".into())
+    } else if cfg!(target_os = "macos") {
+        Some("open -t".into())
+    } else {
+        None
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn default_terminal() -> Option<String> {
+    Some("open -a Terminal".into())
+}
+
+#[cfg(windows)]
+fn default_terminal() -> Option<String> {
+    Some(
+        if find_executable("wt").is_some() {
+            "wt"
+        } else {
+            "cmd"
+        }
+        .into(),
+    )
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn default_terminal() -> Option<String> {
+    TERMINAL_CANDIDATES
+        .iter()
+        .find(|name| find_executable(name).is_some())
+        .map(|name| name.to_string())
+}
+
+fn probe(tool: Tool, command: Option<String>) -> ToolStatus {
+    let Some((program, _)) = command.as_deref().and_then(split_command) else {
+        let detail = match tool {
+            Tool::Editor => "no editor configured (set tools.editor or $EDITOR)",
+            Tool::Terminal => "no terminal found (set tools.terminal or $TERMINAL)",
+            Tool::Archiver => "bsdtar not found (install libarchive or set tools.archiver)",
+            Tool::Git => "not configured",
+        };
+        return ToolStatus {
+            tool,
+            command,
+            available: false,
+            detail: detail.into(),
+        };
+    };
+
+    let resolved = find_executable(&program);
+    let detail = match &resolved {
+        Some(path) => path.display().to_string(),
+        None => format!("'{}' not found on PATH", program),
+    };
+    ToolStatus {
+        tool,
+        command,
+        available: resolved.is_some(),
+        detail,
+    }
+}
+
+/// Configured command for `path`'s extension, if any
+pub fn association_for<'a>(
+    path: &Path,
+    associations: &'a HashMap<String, String>,
+) -> Option<&'a str> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    associations
+        .get(&ext)
+        .map(String::as_str)
+        .filter(|command| !command.trim().is_empty())
+}
+
+/// Open `path` with its associated command, falling back to the OS default application
+pub fn open_path(path: &Path, associations: &HashMap<String, String>) -> io::Result<()> {
+    match association_for(path, associations) {
+        Some(command) => open_files_with(command, &[path.to_path_buf()]),
+        None => open::that(path),
+    }
+}
+
+/// Shared lowercase extension of `paths`, used to offer "Always open .X with…"
+pub fn common_extension(paths: &[PathBuf]) -> Option<String> {
+    let mut extensions = paths
+        .iter()
+        .map(|p| p.extension().map(|e| e.to_string_lossy().to_lowercase()));
+    let first = extensions.next()??;
+    extensions
+        .all(|ext| ext.as_deref() == Some(first.as_str()))
+        .then_some(first)
+}
+
+/// Run `command` once with every path appended, e.g. "gimp" for a set of images
+pub fn open_files_with(command: &str, paths: &[PathBuf]) -> io::Result<()> {
+    let (program, mut args) = split_command(command)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No command given"))?;
+    args.extend(paths.iter().map(|p| p.to_string_lossy().into_owned()));
+    spawn_detached(Command::new(program).args(args))
+}
+
+/// Locate an executable by name on PATH (or verify it directly when given a path)
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let candidate = Path::new(name);
+    if candidate.components().count() > 1 {
+        return is_executable(candidate).then(|| candidate.to_path_buf());
+    }
+
+    let extensions: Vec<String> = if cfg!(windows) {
+        env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT;.COM".into())
+            .split(';')
+            .map(|ext| ext.to_string())
+            .chain(std::iter::once(String::new()))
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+
+    let path_var = env::var_os("PATH")?;
+    env::split_paths(&path_var).find_map(|dir| {
+        extensions.iter().find_map(|ext| {
+            let full = dir.join(format!("{}{}", name, ext));
+            is_executable(&full).then_some(full)
+        })
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Split a configured command line on whitespace (no quoting support)
+fn split_command(command: &str) -> Option<(String, Vec<String>)> {
+    let mut parts = command.split_whitespace().map(String::from);
+    let program = parts.next()?;
+    Some((program, parts.collect()))
+}
+
+fn program_name(program: &str) -> String {
+    Path::new(program)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn is_program(program: &str, name: &str) -> bool {
+    program_name(program) == name
+}
+
+fn is_terminal_editor(program: &str, args: &[String]) -> bool {
+    let name = program_name(program);
+    TERMINAL_EDITORS.contains(&name.as_str())
+        || (name == "emacs" && args.iter().any(|a| a == "-nw" || a == "--no-window-system"))
+}
+
+/// Arguments opening `path` at `line`: `+N file` for vi-style editors, `file:N` or
+/// `-g file:N` for GUI editors, and just the file when the syntax is unknown
+fn editor_file_args(program: &str, path: &Path, line: Option<usize>) -> Vec<String> {
+    let file = path.to_string_lossy().into_owned();
+    let Some(line) = line else {
+        return vec![file];
+    };
+    match program_name(program).as_str() {
+        "code" | "code-insiders" | "codium" => vec!["-g".into(), format!("{}:{}", file, line)],
+        "subl" | "zed" | "hx" | "helix" => vec![format!("{}:{}", file, line)],
+        "notepad++" => vec![format!("-n{}", line), file],
+        "kate" => vec!["-l".into(), line.to_string(), file],
+        "open" | "notepad" | "textedit" => vec![file],
+        _ => vec![format!("+{}", line), file],
+    }
+}
+
+/// Argument lists for opening `files` with an editor whose arguments are `template`
+///
+/// A `{file}` argument runs the editor once per file, `{files}` is replaced by
+/// every file at once, and without a placeholder all files are appended to a
+/// single invocation (`code a b c`). Each file may expand to several arguments
+/// (e.g. `-g file:12`).
+fn editor_invocations(template: &[String], files: &[Vec<String>]) -> Vec<Vec<String>> {
+    let splice = |placeholder: &str, with: &[String]| -> Vec<String> {
+        template
+            .iter()
+            .flat_map(|arg| {
+                if arg == placeholder {
+                    with.to_vec()
+                } else {
+                    vec![arg.clone()]
+                }
+            })
+            .collect()
+    };
+    if template.iter().any(|a| a == "{file}") {
+        files.iter().map(|file| splice("{file}", file)).collect()
+    } else if template.iter().any(|a| a == "{files}") {
+        vec![splice("{files}", &files.concat())]
+    } else {
+        vec![template.iter().cloned().chain(files.concat()).collect()]
+    }
+}
+
+/// Flag that makes a terminal emulator run the following command
+fn exec_flag(terminal: &str) -> &'static [&'static str] {
+    match program_name(terminal).as_str() {
+        "gnome-terminal" | "kgx" | "ptyxis" => &["--"],
+        "wezterm" => &["start", "--"],
+        "kitty" | "wt" => &[],
+        _ => &["-e"],
+    }
+}
+
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+fn applescript_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Spawn a process without blocking, reaping it on a background thread
+fn spawn_detached(command: &mut Command) -> io::Result<()> {
+    let mut child: Child = command.spawn()?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
@@ -1,24 +1,136 @@
-use crate::entry::FileEntry;
-use crate::state::{SearchOptions, SearchResult};
+use crate::core::destination_warning;
+use crate::entry::{EntryDetails, FileEntry};
+use crate::platform::{self, Volume};
+use crate::state::{
+    BrokenLink, CleanupItem, ClipboardOp, DirDiffEntry, NameIssue, SearchOptions, SearchResult,
+};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TryRecvError};
+use std::sync::Arc;
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
 
-use super::directory::read_directory;
-use super::search::perform_search;
+use super::archive::{count_archive, ArchiveCursor, ArchivePage, ArchiveTotals};
+use super::broken_links::find_broken_links;
+use super::cleanup::find_empty;
+use super::dirdiff::compare_directories;
+use super::directory::{
+    directory_usage, enrich_entries, read_directory, read_flattened, DirUsage, Enrichment,
+};
+use super::fileops::sha256_file;
+use super::git::{self, last_commit, repo_info, GitAction, RepoInfo};
+use super::maintenance::{empty_trash, scan_trash, TrashUsage};
+use super::name_audit::audit_names;
+use super::search::{extract_archive_member, perform_search, read_member_for_preview};
 
 /// Maximum number of pending commands in the worker queue.
 /// This prevents memory exhaustion from rapid command submissions.
 const COMMAND_QUEUE_CAPACITY: usize = 16;
 
+/// Entries enriched between looks at the command queue
+const ENRICH_BATCH: usize = 200;
+
+/// How long quitting waits for the worker to finish the command it is busy with
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
 pub enum IoCommand {
     LoadDirectory(PathBuf, bool),
     LoadParent(PathBuf, bool),
+    /// Listing of a sibling shown without navigating, answered with `PeekLoaded`
+    LoadPeek(PathBuf, bool),
+    /// Listing of the directory under the cursor for its preview, as of `modified`
+    LoadPreviewListing {
+        path: PathBuf,
+        show_hidden: bool,
+        modified: SystemTime,
+        generation: u64,
+    },
+    /// Recursive file listing for the flatten view, streamed as `FlattenBatch`
+    LoadFlattened(PathBuf, bool),
+    /// Search the files under every root, merging the hits
     SearchContent {
         query: String,
-        root_path: PathBuf,
+        roots: Vec<PathBuf>,
         options: SearchOptions,
     },
+    CompareDirectories {
+        left: PathBuf,
+        right: PathBuf,
+        compare_contents: bool,
+    },
+    /// Empty directories and zero-byte files below a directory
+    FindEmpty(PathBuf),
+    /// Broken symlinks and dangling shortcuts below a directory
+    FindBrokenLinks(PathBuf),
+    /// Names below a directory that Windows or SMB shares would reject
+    AuditNames(PathBuf),
+    ComputeChecksum(PathBuf),
+    /// Look for trouble at a paste destination before the job starts, answered with
+    /// `DestinationChecked`
+    CheckDestination {
+        op: ClipboardOp,
+        dest_dir: PathBuf,
+        items: Vec<PathBuf>,
+    },
+    /// Recompute `EntryDetails` (git status etc.) for entries listed from `dir`
+    EnrichEntries {
+        dir: PathBuf,
+        paths: Vec<PathBuf>,
+    },
+    /// Total a directory's size and file count, plus its source-only totals when `source`
+    ComputeDirSize {
+        path: PathBuf,
+        source: bool,
+        /// Folder names left out, such as node_modules
+        skip: Vec<String>,
+        /// `WorkerHandle::preview_generation` when requested
+        generation: u64,
+    },
+    /// Page of an archive listing for its preview, `resume` as in `ArchivePage::next`
+    ListArchive {
+        path: PathBuf,
+        /// The entry's modification time when the listing was requested
+        modified: SystemTime,
+        resume: u64,
+        limit: usize,
+        generation: u64,
+    },
+    /// Count the members of an archive whose listing stopped after a page
+    CountArchive {
+        path: PathBuf,
+        generation: u64,
+    },
+    /// Start of an archive member, for the context of a search match inside it
+    ReadArchiveMember {
+        archive: PathBuf,
+        member: String,
+        /// The archive's modification time when it was searched
+        modified: SystemTime,
+    },
+    /// Extract an archive member so the search match at `line` can be opened
+    ExtractArchiveMember {
+        archive: PathBuf,
+        member: String,
+        line: usize,
+    },
+    /// Branch info for the top bar git menu
+    LoadGitRepo(PathBuf),
+    /// Most recent commit touching a path, for the preview Git tab
+    LoadLastCommit(PathBuf),
+    /// Checkout or stash from the top bar git menu; the branch info is re-read after
+    RunGit {
+        dir: PathBuf,
+        action: GitAction,
+    },
+    ScanTrash,
+    /// Drives and mounts with their capacity, for the Computer page
+    ListVolumes,
+    /// Permanently delete trashed items; `None` empties the whole trash
+    EmptyTrash {
+        older_than_days: Option<u64>,
+    },
     /// Graceful shutdown signal - worker thread will exit after receiving this
     Shutdown,
 }
@@ -27,14 +139,113 @@ pub enum IoResult {
     DirectoryLoaded {
         path: PathBuf,
         entries: Vec<FileEntry>,
+        /// How long reading the directory took, to spot slow (network) ones
+        elapsed: Duration,
     },
     ParentLoaded(Vec<FileEntry>),
-    SearchCompleted(Vec<SearchResult>),
+    PeekLoaded {
+        path: PathBuf,
+        entries: Result<Vec<FileEntry>, String>,
+    },
+    PreviewListingLoaded {
+        path: PathBuf,
+        show_hidden: bool,
+        modified: SystemTime,
+        entries: Result<Vec<FileEntry>, String>,
+    },
+    /// Details for entries of an earlier listing, matched by path
+    EntriesEnriched(Vec<(PathBuf, EntryDetails)>),
+    FlattenBatch {
+        path: PathBuf,
+        entries: Vec<FileEntry>,
+        /// First batch of a listing; replaces the previous entries
+        first: bool,
+        /// Last batch; `truncated` is only meaningful here
+        done: bool,
+        truncated: bool,
+    },
+    SearchCompleted {
+        results: Vec<SearchResult>,
+        /// Files with more matches than the per-file cap kept
+        capped: Vec<PathBuf>,
+        /// Modification time of each archive with hits, read once for its member previews
+        archives: HashMap<PathBuf, SystemTime>,
+    },
     SearchProgress {
         files_searched: usize,
         files_skipped: usize,
         errors: usize,
     },
+    DirDiffCompleted {
+        left: PathBuf,
+        right: PathBuf,
+        entries: Vec<DirDiffEntry>,
+        /// The report stopped at its entry limit
+        truncated: bool,
+    },
+    DirDiffFailed(String),
+    EmptyFound {
+        root: PathBuf,
+        items: Vec<CleanupItem>,
+    },
+    BrokenLinksFound {
+        root: PathBuf,
+        links: Vec<BrokenLink>,
+    },
+    NamesAudited {
+        root: PathBuf,
+        issues: Vec<NameIssue>,
+    },
+    ChecksumComputed {
+        path: PathBuf,
+        modified: SystemTime,
+        result: Result<String, String>,
+    },
+    DirSizeComputed {
+        path: PathBuf,
+        modified: SystemTime,
+        usage: DirUsage,
+    },
+    ArchiveListed {
+        path: PathBuf,
+        modified: SystemTime,
+        resume: u64,
+        page: Result<ArchivePage, String>,
+    },
+    ArchiveCounted {
+        path: PathBuf,
+        modified: SystemTime,
+        result: Result<ArchiveTotals, String>,
+    },
+    ArchiveMemberRead {
+        archive: PathBuf,
+        member: String,
+        modified: SystemTime,
+        text: Result<String, String>,
+    },
+    ArchiveMemberExtracted {
+        member: String,
+        line: usize,
+        path: Result<PathBuf, String>,
+    },
+    /// A preview listing, folder size or archive count dropped because the cursor moved on
+    PreviewWorkCancelled(PathBuf),
+    GitRepoLoaded {
+        dir: PathBuf,
+        repo: Option<RepoInfo>,
+    },
+    LastCommitLoaded {
+        path: PathBuf,
+        modified: SystemTime,
+        log: String,
+    },
+    /// Message for a finished `RunGit`
+    GitDone(Result<String, String>),
+    TrashScanned(Result<TrashUsage, String>),
+    VolumesListed(Vec<Volume>),
+    TrashEmptied(Result<usize, String>),
+    /// Warning about a read-only destination or a move across filesystems
+    DestinationChecked(Option<String>),
     Error(String),
 }
 
@@ -43,16 +254,36 @@ pub struct WorkerHandle {
     pub command_tx: SyncSender<IoCommand>,
     pub result_rx: Receiver<IoResult>,
     thread_handle: Option<JoinHandle<()>>,
+    /// Bumped when the cursor moves; preview work tagged with an older value is dropped
+    preview_generation: Arc<AtomicU64>,
 }
 
 impl WorkerHandle {
-    /// Request graceful shutdown and wait for worker to finish
-    pub fn shutdown(mut self) {
-        // Send shutdown signal (ignore error if channel is closed)
-        let _ = self.command_tx.send(IoCommand::Shutdown);
+    /// Tag for preview work requested now
+    pub fn preview_generation(&self) -> u64 {
+        self.preview_generation.load(Ordering::Relaxed)
+    }
+
+    /// Drop queued and running preview work (directory listings, folder sizes, archive counts)
+    pub fn cancel_preview_work(&self) {
+        self.preview_generation.fetch_add(1, Ordering::Relaxed);
+    }
 
-        // Wait for thread to finish
-        if let Some(handle) = self.thread_handle.take() {
+    /// Request graceful shutdown and wait up to `grace` for the worker to finish
+    ///
+    /// A long search or comparison may still be running after that; it ends with the process.
+    pub fn shutdown(&mut self, grace: Duration) {
+        // Never block on a full queue (ignore error if channel is closed)
+        let _ = self.command_tx.try_send(IoCommand::Shutdown);
+
+        let Some(handle) = self.thread_handle.take() else {
+            return;
+        };
+        let deadline = Instant::now() + grace;
+        while !handle.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        if handle.is_finished() {
             let _ = handle.join();
         }
     }
@@ -65,44 +296,350 @@ pub fn spawn_worker(ctx: eframe::egui::Context) -> WorkerHandle {
     let (res_tx, res_rx) = sync_channel(64);
 
     let ctx_clone = ctx.clone();
+    let preview_generation = Arc::new(AtomicU64::new(0));
+    let current_generation = Arc::clone(&preview_generation);
     let handle = thread::spawn(move || {
-        while let Ok(cmd) = cmd_rx.recv() {
+        let stale = |generation: u64| current_generation.load(Ordering::Relaxed) != generation;
+        // Details for the latest listings, worked on only while no command waits; a newer
+        // listing of the same pane replaces them
+        let mut enrich_current: Option<Enrichment> = None;
+        let mut enrich_parent: Option<Enrichment> = None;
+        // Listing of the archive being previewed, kept open for its next page
+        let mut archive_cursor: Option<(PathBuf, SystemTime, ArchiveCursor)> = None;
+        loop {
+            let cmd = match cmd_rx.try_recv() {
+                Ok(cmd) => cmd,
+                Err(TryRecvError::Disconnected) => break,
+                Err(TryRecvError::Empty) => {
+                    let slot = if enrich_current.is_some() {
+                        &mut enrich_current
+                    } else {
+                        &mut enrich_parent
+                    };
+                    if let Some(enrichment) = slot {
+                        let details = enrichment.next_batch(ENRICH_BATCH);
+                        if details.is_empty() {
+                            *slot = None;
+                        } else {
+                            let _ = res_tx.send(IoResult::EntriesEnriched(details));
+                            ctx_clone.request_repaint();
+                        }
+                        continue;
+                    }
+                    match cmd_rx.recv() {
+                        Ok(cmd) => cmd,
+                        Err(_) => break,
+                    }
+                }
+            };
             match cmd {
                 IoCommand::Shutdown => {
                     // Graceful shutdown - exit the loop
                     break;
                 }
-                IoCommand::LoadDirectory(path, hidden) => match read_directory(&path, hidden) {
-                    Ok(entries) => {
-                        let _ = res_tx.send(IoResult::DirectoryLoaded {
-                            path: path.clone(),
-                            entries,
-                        });
+                IoCommand::LoadDirectory(path, hidden) => {
+                    let started = Instant::now();
+                    match read_directory(&path, hidden) {
+                        Ok(entries) => {
+                            let paths: Vec<PathBuf> =
+                                entries.iter().map(|e| e.path.clone()).collect();
+                            let _ = res_tx.send(IoResult::DirectoryLoaded {
+                                path: path.clone(),
+                                entries,
+                                elapsed: started.elapsed(),
+                            });
+                            enrich_current = Some(Enrichment::new(path, paths));
+                        }
+                        Err(e) => {
+                            enrich_current = None;
+                            let _ = res_tx.send(IoResult::Error(e.to_string()));
+                        }
                     }
-                    Err(e) => {
-                        let _ = res_tx.send(IoResult::Error(e.to_string()));
-                    }
-                },
+                }
                 IoCommand::LoadParent(path, hidden) => match read_directory(&path, hidden) {
                     Ok(entries) => {
+                        let paths: Vec<PathBuf> = entries.iter().map(|e| e.path.clone()).collect();
                         let _ = res_tx.send(IoResult::ParentLoaded(entries));
+                        enrich_parent = Some(Enrichment::new(path, paths));
                     }
                     Err(_) => {
+                        enrich_parent = None;
                         let _ = res_tx.send(IoResult::ParentLoaded(Vec::new()));
                     }
                 },
+                IoCommand::LoadPeek(path, hidden) => {
+                    let entries = read_directory(&path, hidden).map_err(|e| e.to_string());
+                    let _ = res_tx.send(IoResult::PeekLoaded { path, entries });
+                    ctx_clone.request_repaint();
+                }
+                IoCommand::LoadPreviewListing {
+                    path, generation, ..
+                } if stale(generation) => {
+                    let _ = res_tx.send(IoResult::PreviewWorkCancelled(path));
+                }
+                IoCommand::LoadPreviewListing {
+                    path,
+                    show_hidden,
+                    modified,
+                    ..
+                } => {
+                    let entries = read_directory(&path, show_hidden).map_err(|e| e.to_string());
+                    let _ = res_tx.send(IoResult::PreviewListingLoaded {
+                        path,
+                        show_hidden,
+                        modified,
+                        entries,
+                    });
+                }
+                IoCommand::LoadFlattened(path, hidden) => {
+                    let mut first = true;
+                    let result = read_flattened(&path, hidden, |entries| {
+                        let _ = res_tx.send(IoResult::FlattenBatch {
+                            path: path.clone(),
+                            entries,
+                            first,
+                            done: false,
+                            truncated: false,
+                        });
+                        first = false;
+                        ctx_clone.request_repaint();
+                    });
+                    match result {
+                        Ok(truncated) => {
+                            let _ = res_tx.send(IoResult::FlattenBatch {
+                                path,
+                                entries: Vec::new(),
+                                first,
+                                done: true,
+                                truncated,
+                            });
+                        }
+                        Err(e) => {
+                            let _ = res_tx.send(IoResult::Error(e.to_string()));
+                        }
+                    }
+                }
                 IoCommand::SearchContent {
                     query,
-                    root_path,
+                    roots,
                     options,
-                } => match perform_search(&query, &root_path, &options, &res_tx) {
-                    Ok(results) => {
-                        let _ = res_tx.send(IoResult::SearchCompleted(results));
+                } => match perform_search(&query, &roots, &options, &res_tx) {
+                    Ok((results, capped)) => {
+                        let archives = results
+                            .iter()
+                            .filter(|r| r.archive_member.is_some())
+                            .filter_map(|r| {
+                                let modified = std::fs::metadata(&r.file_path)
+                                    .and_then(|m| m.modified())
+                                    .ok()?;
+                                Some((r.file_path.clone(), modified))
+                            })
+                            .collect();
+                        let _ = res_tx.send(IoResult::SearchCompleted {
+                            results,
+                            capped,
+                            archives,
+                        });
                     }
                     Err(e) => {
                         let _ = res_tx.send(IoResult::Error(format!("Search error: {}", e)));
                     }
                 },
+                IoCommand::CompareDirectories {
+                    left,
+                    right,
+                    compare_contents,
+                } => match compare_directories(&left, &right, compare_contents) {
+                    Ok((entries, truncated)) => {
+                        let _ = res_tx.send(IoResult::DirDiffCompleted {
+                            left,
+                            right,
+                            entries,
+                            truncated,
+                        });
+                    }
+                    Err(e) => {
+                        let _ = res_tx.send(IoResult::DirDiffFailed(e.to_string()));
+                    }
+                },
+                IoCommand::FindEmpty(root) => match find_empty(&root) {
+                    Ok(items) => {
+                        let _ = res_tx.send(IoResult::EmptyFound { root, items });
+                    }
+                    Err(e) => {
+                        let _ = res_tx.send(IoResult::Error(format!("Cleanup scan failed: {}", e)));
+                    }
+                },
+                IoCommand::FindBrokenLinks(root) => match find_broken_links(&root) {
+                    Ok(links) => {
+                        let _ = res_tx.send(IoResult::BrokenLinksFound { root, links });
+                    }
+                    Err(e) => {
+                        let _ = res_tx.send(IoResult::Error(format!("Link scan failed: {}", e)));
+                    }
+                },
+                IoCommand::AuditNames(root) => match audit_names(&root) {
+                    Ok(issues) => {
+                        let _ = res_tx.send(IoResult::NamesAudited { root, issues });
+                    }
+                    Err(e) => {
+                        let _ = res_tx.send(IoResult::Error(format!("Name audit failed: {}", e)));
+                    }
+                },
+                IoCommand::ComputeChecksum(path) => {
+                    let modified = std::fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .unwrap_or(SystemTime::UNIX_EPOCH);
+                    let result = sha256_file(&path).map_err(|e| e.to_string());
+                    let _ = res_tx.send(IoResult::ChecksumComputed {
+                        path,
+                        modified,
+                        result,
+                    });
+                }
+                IoCommand::CheckDestination {
+                    op,
+                    dest_dir,
+                    items,
+                } => {
+                    let _ = res_tx.send(IoResult::DestinationChecked(destination_warning(
+                        op, &dest_dir, &items,
+                    )));
+                }
+                IoCommand::EnrichEntries { dir, paths } => {
+                    let _ = res_tx.send(IoResult::EntriesEnriched(enrich_entries(&dir, &paths)));
+                }
+                IoCommand::ComputeDirSize {
+                    path,
+                    source,
+                    skip,
+                    generation,
+                } => {
+                    let modified = std::fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .unwrap_or(SystemTime::UNIX_EPOCH);
+                    match directory_usage(&path, source, &skip, &|| stale(generation)) {
+                        Some(usage) => {
+                            let _ = res_tx.send(IoResult::DirSizeComputed {
+                                path,
+                                modified,
+                                usage,
+                            });
+                        }
+                        None => {
+                            let _ = res_tx.send(IoResult::PreviewWorkCancelled(path));
+                        }
+                    }
+                }
+                IoCommand::ListArchive {
+                    path, generation, ..
+                } if stale(generation) => {
+                    let _ = res_tx.send(IoResult::PreviewWorkCancelled(path));
+                }
+                IoCommand::ListArchive {
+                    path,
+                    modified,
+                    resume,
+                    limit,
+                    ..
+                } => {
+                    let cursor = match archive_cursor.take() {
+                        Some((open, opened_at, cursor))
+                            if open == path
+                                && opened_at == modified
+                                && cursor.position() == resume =>
+                        {
+                            Ok(cursor)
+                        }
+                        _ => ArchiveCursor::open(&path, resume),
+                    };
+                    let page = cursor.and_then(|mut cursor| {
+                        let page = cursor.next_page(limit)?;
+                        if page.next.is_some() {
+                            archive_cursor = Some((path.clone(), modified, cursor));
+                        }
+                        Ok(page)
+                    });
+                    let _ = res_tx.send(IoResult::ArchiveListed {
+                        path,
+                        modified,
+                        resume,
+                        page: page.map_err(|e| e.to_string()),
+                    });
+                }
+                IoCommand::CountArchive { path, generation } if stale(generation) => {
+                    let _ = res_tx.send(IoResult::PreviewWorkCancelled(path));
+                }
+                IoCommand::CountArchive { path, .. } => {
+                    let modified = std::fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .unwrap_or(SystemTime::UNIX_EPOCH);
+                    let result = count_archive(&path).map_err(|e| e.to_string());
+                    let _ = res_tx.send(IoResult::ArchiveCounted {
+                        path,
+                        modified,
+                        result,
+                    });
+                }
+                IoCommand::ReadArchiveMember {
+                    archive,
+                    member,
+                    modified,
+                } => {
+                    let text =
+                        read_member_for_preview(&archive, &member).map_err(|e| e.to_string());
+                    let _ = res_tx.send(IoResult::ArchiveMemberRead {
+                        archive,
+                        member,
+                        modified,
+                        text,
+                    });
+                }
+                IoCommand::ExtractArchiveMember {
+                    archive,
+                    member,
+                    line,
+                } => {
+                    let path = extract_archive_member(&archive, &member).map_err(|e| e.to_string());
+                    let _ = res_tx.send(IoResult::ArchiveMemberExtracted { member, line, path });
+                }
+                IoCommand::LoadGitRepo(dir) => {
+                    let repo = repo_info(&dir);
+                    let _ = res_tx.send(IoResult::GitRepoLoaded { dir, repo });
+                }
+                IoCommand::LoadLastCommit(path) => {
+                    let modified = std::fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .unwrap_or(SystemTime::UNIX_EPOCH);
+                    let log = match last_commit(&path) {
+                        Some(log) => log,
+                        None if !git::enabled() => "Git was not found (see :diagnostics)".into(),
+                        None => "No commits for this path".into(),
+                    };
+                    let _ = res_tx.send(IoResult::LastCommitLoaded {
+                        path,
+                        modified,
+                        log,
+                    });
+                }
+                IoCommand::RunGit { dir, action } => {
+                    let _ = res_tx.send(IoResult::GitDone(action.run(&dir)));
+                    let repo = repo_info(&dir);
+                    let _ = res_tx.send(IoResult::GitRepoLoaded { dir, repo });
+                }
+                IoCommand::ListVolumes => {
+                    let _ = res_tx.send(IoResult::VolumesListed(platform::volumes()));
+                }
+                IoCommand::ScanTrash => {
+                    let _ = res_tx.send(IoResult::TrashScanned(
+                        scan_trash().map_err(|e| e.to_string()),
+                    ));
+                }
+                IoCommand::EmptyTrash { older_than_days } => {
+                    let _ = res_tx.send(IoResult::TrashEmptied(
+                        empty_trash(older_than_days).map_err(|e| e.to_string()),
+                    ));
+                }
             }
             ctx_clone.request_repaint();
         }
@@ -112,5 +649,6 @@ pub fn spawn_worker(ctx: eframe::egui::Context) -> WorkerHandle {
         command_tx: cmd_tx,
         result_rx: res_rx,
         thread_handle: Some(handle),
+        preview_generation,
     }
 }
@@ -1,8 +1,10 @@
 mod app;
 mod config;
+mod core;
 mod entry;
 mod input;
 mod io;
+mod platform;
 mod state;
 mod style;
 mod view;
@@ -12,16 +14,19 @@ use config::Config;
 use eframe::egui;
 use std::path::PathBuf;
 
+/// Window and tray icon
+const APP_ICON: &[u8] = include_bytes!("../assets/heike_icon.png");
+
 fn main() -> eframe::Result<()> {
     // Parse CLI arguments for starting directory
     let start_dir = std::env::args()
         .nth(1)
         .map(PathBuf::from)
-        .and_then(|p| p.canonicalize().ok());
+        .and_then(|p| p.canonicalize().ok())
+        .map(platform::simplify_path);
 
     // Load the app icon
-    let icon_bytes = include_bytes!("../assets/heike_icon.png");
-    let icon_image = image::load_from_memory(icon_bytes)
+    let icon_image = image::load_from_memory(APP_ICON)
         .expect("Failed to load icon")
         .to_rgba8();
     let (icon_width, icon_height) = icon_image.dimensions();
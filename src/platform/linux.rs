@@ -0,0 +1,80 @@
+// Linux integration: extended attributes and SELinux/AppArmor labels
+use std::io;
+use std::path::Path;
+
+/// Attributes shown as the security context rather than in the xattr list
+const SECURITY_LABELS: &[(&str, &str)] = &[
+    ("security.selinux", "SELinux"),
+    ("security.apparmor", "AppArmor"),
+];
+
+/// Longest value shown before truncating
+const MAX_VALUE_BYTES: usize = 256;
+
+pub fn list_xattrs(path: &Path) -> io::Result<Vec<(String, String)>> {
+    let mut attrs: Vec<(String, String)> = xattr::list(path)?
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| !SECURITY_LABELS.iter().any(|(label, _)| label == name))
+        .map(|name| {
+            let value = match xattr::get(path, &name) {
+                Ok(Some(bytes)) => printable(&bytes),
+                Ok(None) => String::new(),
+                Err(e) => format!("<{}>", e),
+            };
+            (name, value)
+        })
+        .collect();
+    attrs.sort();
+    Ok(attrs)
+}
+
+pub fn security_context(path: &Path) -> Option<String> {
+    SECURITY_LABELS.iter().find_map(|(name, kind)| {
+        let value = xattr::get(path, name).ok()??;
+        Some(format!("{}: {}", kind, printable(&value)))
+    })
+}
+
+pub fn set_user_xattr(path: &Path, name: &str, value: &str) -> io::Result<()> {
+    xattr::set(path, user_name(name)?, value.as_bytes())
+}
+
+pub fn remove_user_xattr(path: &Path, name: &str) -> io::Result<()> {
+    xattr::remove(path, user_name(name)?)
+}
+
+/// Only the user namespace is editable from the UI
+fn user_name(name: &str) -> io::Result<&str> {
+    if name.starts_with("user.") && name.len() > "user.".len() {
+        Ok(name)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only user.* attributes can be modified",
+        ))
+    }
+}
+
+/// Render a value as text when it is printable, hex otherwise
+fn printable(bytes: &[u8]) -> String {
+    let trimmed = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+    let shown = &trimmed[..trimmed.len().min(MAX_VALUE_BYTES)];
+    let ellipsis = if trimmed.len() > shown.len() {
+        "…"
+    } else {
+        ""
+    };
+    match std::str::from_utf8(shown) {
+        Ok(text)
+            if !text
+                .chars()
+                .any(|c| c.is_control() && c != '\n' && c != '\t') =>
+        {
+            format!("{}{}", text, ellipsis)
+        }
+        _ => {
+            let hex: String = shown.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("0x{}{}", hex, ellipsis)
+        }
+    }
+}
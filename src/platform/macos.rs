@@ -0,0 +1,93 @@
+// macOS integration: Quick Look and Finder color tags
+use crate::state::LabelColor;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// ENOATTR: the attribute is not present
+const ENOATTR: i32 = 93;
+
+/// Extended attribute holding Finder tags as a binary plist array of "Name\nIndex" strings
+const TAGS_XATTR: &str = "com.apple.metadata:_kMDItemUserTags";
+
+pub fn quick_look(path: &Path) -> io::Result<()> {
+    let mut child = Command::new("qlmanage")
+        .arg("-p")
+        .arg(path)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// Read all Finder tags of a file
+fn read_tags(path: &Path) -> Vec<String> {
+    let Ok(Some(data)) = xattr::get(path, TAGS_XATTR) else {
+        return Vec::new();
+    };
+    plist::from_bytes::<Vec<String>>(&data).unwrap_or_default()
+}
+
+/// The first Finder color tag of a file, mapped to a label
+pub fn read_label(path: &Path) -> Option<LabelColor> {
+    read_tags(path).iter().find_map(|tag| {
+        let (_, index) = tag.rsplit_once('\n')?;
+        LabelColor::from_finder_index(index.trim().parse().ok()?)
+    })
+}
+
+/// Replace the file's color tags with `label`, keeping any non-color tags
+pub fn write_label(path: &Path, label: Option<LabelColor>) -> io::Result<()> {
+    let mut tags: Vec<String> = read_tags(path)
+        .into_iter()
+        .filter(|tag| {
+            !tag.rsplit_once('\n')
+                .and_then(|(_, index)| index.trim().parse::<u8>().ok())
+                .is_some_and(|index| index > 0)
+        })
+        .collect();
+    if let Some(label) = label {
+        tags.insert(0, format!("{}\n{}", label.name(), label.finder_index()));
+    }
+
+    if tags.is_empty() {
+        return match xattr::remove(path, TAGS_XATTR) {
+            Err(e) if e.raw_os_error() != Some(ENOATTR) => Err(e),
+            _ => Ok(()),
+        };
+    }
+
+    let mut data = Vec::new();
+    plist::to_writer_binary(&mut data, &tags).map_err(io::Error::other)?;
+    xattr::set(path, TAGS_XATTR, &data)
+}
+
+/// Filesystem details from statfs; volumes not flagged local are network mounts
+pub fn filesystem_info(path: &Path) -> Option<super::FilesystemInfo> {
+    use std::ffi::CStr;
+    use std::os::unix::ffi::OsStrExt;
+    // <sys/mount.h> flags
+    const MNT_RDONLY: u32 = 0x0000_0001;
+    const MNT_LOCAL: u32 = 0x0000_1000;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let text = |field: &[libc::c_char]| {
+        unsafe { CStr::from_ptr(field.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    Some(super::FilesystemInfo {
+        mount_point: text(&stat.f_mntonname).into(),
+        fs_type: Some(text(&stat.f_fstypename)),
+        device: Some(text(&stat.f_mntfromname)),
+        read_only: stat.f_flags & MNT_RDONLY != 0,
+        remote: stat.f_flags & MNT_LOCAL == 0,
+    })
+}
@@ -0,0 +1,678 @@
+// Platform-specific integrations behind a common facade
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(target_os = "macos")]
+pub mod macos;
+pub mod tray;
+#[cfg(windows)]
+pub mod windows;
+
+use std::io;
+use std::path::{Path, PathBuf};
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+use std::process::Command;
+
+/// Whether Quick Look is available on this platform
+pub const HAS_QUICK_LOOK: bool = cfg!(target_os = "macos");
+
+/// Whether file labels are stored natively (Finder tags) instead of in Heike's label file
+pub const NATIVE_LABELS: bool = cfg!(target_os = "macos");
+
+/// Whether the native shell's "Open with…" and "Properties" dialogs are available
+pub const HAS_SHELL_VERBS: bool = cfg!(windows);
+
+/// Whether extended attributes and security labels are shown in Properties
+pub const HAS_XATTRS: bool = cfg!(target_os = "linux");
+
+/// What the platform calls the trash, for status messages
+pub const TRASH_NAME: &str = if cfg!(windows) {
+    "Recycle Bin"
+} else {
+    "Trash"
+};
+
+/// A named location offered in the Places menu
+#[derive(Clone, Debug)]
+pub struct Place {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Filesystem roots: drive letters on Windows, `/` elsewhere
+pub fn drives() -> Vec<Place> {
+    #[cfg(windows)]
+    {
+        windows::drives()
+    }
+    #[cfg(not(windows))]
+    {
+        vec![Place {
+            name: "File System".into(),
+            path: PathBuf::from("/"),
+        }]
+    }
+}
+
+/// A mounted filesystem shown on the Computer page
+#[derive(Clone, Debug, PartialEq)]
+pub struct Volume {
+    pub name: String,
+    pub path: PathBuf,
+    /// Total and available bytes, when the filesystem reports them
+    pub space: Option<(u64, u64)>,
+}
+
+/// Filesystem types that are kernel interfaces rather than storage
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "proc",
+    "pstore",
+    "securityfs",
+    "squashfs",
+    "sysfs",
+    "tmpfs",
+    "tracefs",
+];
+
+/// Mount points worth showing from a `/proc/mounts` table: storage filesystems outside
+/// the system's own trees, with the octal escapes (`\040` for a space) decoded
+pub fn parse_mounts(table: &str) -> Vec<PathBuf> {
+    let mut mounts: Vec<PathBuf> = Vec::new();
+    for line in table.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(_device), Some(mount), Some(fs_type)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if PSEUDO_FILESYSTEMS.contains(&fs_type) {
+            continue;
+        }
+        let mount = PathBuf::from(unescape_mount(mount));
+        let system = ["/proc", "/sys", "/dev", "/run", "/snap", "/boot"]
+            .iter()
+            .any(|dir| mount.starts_with(dir))
+            && !mount.starts_with("/run/media");
+        if !system && !mounts.contains(&mount) {
+            mounts.push(mount);
+        }
+    }
+    mounts
+}
+
+fn unescape_mount(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let code = bytes
+            .get(i + 1..i + 4)
+            .filter(|_| bytes[i] == b'\\')
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+        match code {
+            Some(byte) => {
+                out.push(byte);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Drives and mounted filesystems with their capacity; this may stall on an
+/// unreachable network share, so it runs on the worker
+pub fn volumes() -> Vec<Volume> {
+    let places = if cfg!(target_os = "linux") {
+        let table = std::fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+        parse_mounts(&table)
+            .into_iter()
+            .map(|path| Place {
+                name: match path.file_name() {
+                    Some(name) => name.to_string_lossy().into_owned(),
+                    None => "File System".into(),
+                },
+                path,
+            })
+            .collect()
+    } else if cfg!(target_os = "macos") {
+        // Every volume but the startup disk appears under /Volumes
+        let mut places = drives();
+        if let Ok(entries) = std::fs::read_dir("/Volumes") {
+            places.extend(entries.flatten().filter_map(|entry| {
+                let path = entry.path();
+                // The startup disk shows up here too, as a link to /
+                let target = path.canonicalize().ok()?;
+                (target != Path::new("/")).then(|| Place {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    path,
+                })
+            }));
+        }
+        places
+    } else {
+        drives()
+    };
+    places
+        .into_iter()
+        .map(|place| Volume {
+            space: disk_space(&place.path),
+            name: place.name,
+            path: place.path,
+        })
+        .collect()
+}
+
+/// Total and available bytes of the filesystem holding `path`
+pub fn disk_space(path: &Path) -> Option<(u64, u64)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        let block = stat.f_frsize as u64;
+        Some((stat.f_blocks as u64 * block, stat.f_bavail as u64 * block))
+    }
+    #[cfg(windows)]
+    {
+        windows::disk_space(path)
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// The filesystem a path lives on, for Properties and cross-filesystem warnings
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FilesystemInfo {
+    pub mount_point: PathBuf,
+    /// "ext4", "nfs4", "NTFS", …
+    pub fs_type: Option<String>,
+    /// Block device or share the filesystem comes from
+    pub device: Option<String>,
+    pub read_only: bool,
+    /// Served over the network rather than from a local disk
+    pub remote: bool,
+}
+
+/// Filesystem types that reach across the network
+const REMOTE_FILESYSTEMS: &[&str] = &[
+    "9p",
+    "afpfs",
+    "afs",
+    "ceph",
+    "cifs",
+    "davfs",
+    "fuse.rclone",
+    "fuse.sshfs",
+    "glusterfs",
+    "ncpfs",
+    "nfs",
+    "nfs4",
+    "smb3",
+    "smbfs",
+    "sshfs",
+    "webdav",
+];
+
+/// Whether a mount of `fs_type` from `device` is a network filesystem; shares given as
+/// `//server/share` or `host:/export` count even under an unfamiliar type
+pub fn is_remote_filesystem(fs_type: &str, device: &str) -> bool {
+    REMOTE_FILESYSTEMS.contains(&fs_type)
+        || device.starts_with("//")
+        || (device.contains(":/") && !device.starts_with('/'))
+}
+
+/// Details of the deepest mount in a `/proc/mounts` table containing the absolute,
+/// canonical `path`; a later mount on the same point hides an earlier one
+pub fn mount_entry(table: &str, path: &Path) -> Option<FilesystemInfo> {
+    let mut best: Option<FilesystemInfo> = None;
+    for line in table.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [device, mount, fs_type, options, ..] = fields[..] else {
+            continue;
+        };
+        let mount_point = PathBuf::from(unescape_mount(mount));
+        let deeper = best.as_ref().is_none_or(|best| {
+            mount_point.components().count() >= best.mount_point.components().count()
+        });
+        if !path.starts_with(&mount_point) || !deeper {
+            continue;
+        }
+        let device = unescape_mount(device);
+        best = Some(FilesystemInfo {
+            read_only: options.split(',').any(|option| option == "ro"),
+            remote: is_remote_filesystem(fs_type, &device),
+            mount_point,
+            fs_type: Some(fs_type.to_string()),
+            device: Some(device),
+        });
+    }
+    best
+}
+
+/// Filesystem type, mount point, device and flags for the filesystem holding `path`
+pub fn filesystem_info(path: &Path) -> Option<FilesystemInfo> {
+    #[cfg(target_os = "linux")]
+    {
+        let path = path.canonicalize().ok()?;
+        let table = std::fs::read_to_string("/proc/self/mounts").ok()?;
+        mount_entry(&table, &path)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        macos::filesystem_info(path)
+    }
+    #[cfg(windows)]
+    {
+        windows::filesystem_info(path)
+    }
+    #[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let mount_point = mount_root(path)?;
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        Some(FilesystemInfo {
+            mount_point,
+            read_only: stat.f_flag & libc::ST_RDONLY != 0,
+            ..Default::default()
+        })
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// Whether two existing paths are on the same filesystem, so a move is a rename
+/// rather than a copy and delete; `None` when either cannot be read
+pub fn same_filesystem(a: &Path, b: &Path) -> Option<bool> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(std::fs::metadata(a).ok()?.dev() == std::fs::metadata(b).ok()?.dev())
+    }
+    #[cfg(not(unix))]
+    {
+        Some(mount_root(&a.canonicalize().ok()?)? == mount_root(&b.canonicalize().ok()?)?)
+    }
+}
+
+/// Root of the filesystem holding `path`: its mount point on Unix, the drive root elsewhere
+pub fn mount_root(path: &Path) -> Option<PathBuf> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let path = path.canonicalize().ok()?;
+        let device = std::fs::metadata(&path).ok()?.dev();
+        path.ancestors()
+            .take_while(|dir| std::fs::metadata(dir).is_ok_and(|m| m.dev() == device))
+            .last()
+            .map(Path::to_path_buf)
+    }
+    #[cfg(not(unix))]
+    {
+        path.ancestors().last().map(Path::to_path_buf)
+    }
+}
+
+/// Breadcrumb labels and paths for `path`, with a Windows drive or UNC share and its
+/// root kept together as the first segment (`C:\`, `\\server\share`)
+pub fn path_segments(path: &Path) -> Vec<(String, PathBuf)> {
+    use std::path::{Component, Prefix};
+    let mut segments: Vec<(String, PathBuf)> = Vec::new();
+    let mut path_acc = PathBuf::new();
+    for component in path.components() {
+        path_acc.push(component);
+        let name = component.as_os_str().to_string_lossy().into_owned();
+        match component {
+            Component::Prefix(prefix) => {
+                let label = match prefix.kind() {
+                    Prefix::Disk(_) | Prefix::VerbatimDisk(_) => {
+                        format!("{}{}", name, std::path::MAIN_SEPARATOR)
+                    }
+                    _ => name,
+                };
+                segments.push((label, path_acc.clone()));
+            }
+            // The root after a prefix belongs to it: `C:` alone means "current dir on C:"
+            Component::RootDir if !segments.is_empty() => {
+                if let Some(first) = segments.last_mut() {
+                    first.1 = path_acc.clone();
+                }
+            }
+            _ => segments.push((name, path_acc.clone())),
+        }
+    }
+    segments
+}
+
+/// Drop the `\\?\` prefix `canonicalize` gives paths on Windows, so `\\?\UNC\server\share`
+/// reads `\\server\share` and `\\?\C:\dir` reads `C:\dir` in breadcrumbs, bookmarks and tools
+pub fn simplify_path(path: PathBuf) -> PathBuf {
+    if !cfg!(windows) {
+        return path;
+    }
+    match path.to_str().and_then(strip_verbatim) {
+        Some(simple) => PathBuf::from(simple),
+        None => path,
+    }
+}
+
+/// `path` without a verbatim `\\?\` prefix, when it names a drive or UNC share
+pub fn strip_verbatim(path: &str) -> Option<String> {
+    if let Some(share) = path.strip_prefix(r"\\?\UNC\") {
+        return Some(format!(r"\\{}", share));
+    }
+    let rest = path.strip_prefix(r"\\?\")?;
+    let bytes = rest.as_bytes();
+    (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+        .then(|| rest.to_string())
+}
+
+fn unsupported(feature: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{} is not available on this platform", feature),
+    )
+}
+
+/// Show a file in the platform's quick preview panel
+pub fn quick_look(path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::quick_look(path)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = path;
+        Err(unsupported("Quick Look"))
+    }
+}
+
+/// Raise a desktop notification (fire and forget)
+///
+/// Uses `notify-send` on Linux, Notification Center via `osascript` on macOS
+/// and a toast through PowerShell on Windows.
+pub fn notify(title: &str, body: &str) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    let mut command = {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name=Heike", "--", title, body]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(title)
+        ));
+        command
+    };
+    #[cfg(windows)]
+    let mut command = windows::notify_command(title, body);
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    {
+        let _ = (title, body);
+        return Err(unsupported("Desktop notifications"));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos", windows))]
+    {
+        let mut child = command
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()?;
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Show the native "Open with…" chooser
+pub fn open_with(path: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        windows::open_with(path)
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = path;
+        Err(unsupported("Open with"))
+    }
+}
+
+/// Show the native file Properties dialog
+pub fn show_properties(path: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        windows::show_properties(path)
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = path;
+        Err(unsupported("Shell properties"))
+    }
+}
+
+/// Extended attributes of a file as (name, printable value)
+pub fn list_xattrs(path: &Path) -> io::Result<Vec<(String, String)>> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::list_xattrs(path)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        Err(unsupported("Extended attributes"))
+    }
+}
+
+/// SELinux/AppArmor label of a file
+pub fn security_context(path: &Path) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::security_context(path)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+pub fn set_user_xattr(path: &Path, name: &str, value: &str) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::set_user_xattr(path, name, value)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (path, name, value);
+        Err(unsupported("Extended attributes"))
+    }
+}
+
+pub fn remove_user_xattr(path: &Path, name: &str) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::remove_user_xattr(path, name)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (path, name);
+        Err(unsupported("Extended attributes"))
+    }
+}
+
+/// Parse a colon-separated account database (`/etc/passwd`, `/etc/group`) into id → name
+#[cfg(unix)]
+fn account_names(file: &str) -> std::collections::HashMap<u32, String> {
+    std::fs::read_to_string(file)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((id, name.to_string()))
+        })
+        .collect()
+}
+
+/// Local user name for a uid (directory services such as LDAP are not consulted)
+#[cfg(unix)]
+pub fn user_name(uid: u32) -> Option<String> {
+    static USERS: std::sync::OnceLock<std::collections::HashMap<u32, String>> =
+        std::sync::OnceLock::new();
+    USERS
+        .get_or_init(|| account_names("/etc/passwd"))
+        .get(&uid)
+        .cloned()
+}
+
+/// Local group name for a gid
+#[cfg(unix)]
+pub fn group_name(gid: u32) -> Option<String> {
+    static GROUPS: std::sync::OnceLock<std::collections::HashMap<u32, String>> =
+        std::sync::OnceLock::new();
+    GROUPS
+        .get_or_init(|| account_names("/etc/group"))
+        .get(&gid)
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn mount_root_is_an_ancestor_and_its_own_mount_point() {
+        let dir = TempDir::new().unwrap();
+        let inner = dir.path().join("docs/inner");
+        std::fs::create_dir_all(&inner).unwrap();
+
+        let mount = mount_root(&inner).unwrap();
+        assert!(inner.canonicalize().unwrap().starts_with(&mount));
+        assert_eq!(mount_root(&mount), Some(mount));
+    }
+
+    #[test]
+    fn drive_and_share_roots_stay_one_breadcrumb() {
+        let labels: Vec<String> = path_segments(Path::new("/home/user"))
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        assert_eq!(labels, ["/", "home", "user"]);
+        #[cfg(windows)]
+        {
+            let segments = path_segments(Path::new(r"\\server\share\docs"));
+            assert_eq!(segments[0].0, r"\\server\share");
+            assert_eq!(segments[0].1, Path::new(r"\\server\share\"));
+            assert_eq!(segments[1].0, "docs");
+            assert_eq!(path_segments(Path::new(r"C:\Users"))[0].0, r"C:\");
+        }
+
+        assert_eq!(
+            strip_verbatim(r"\\?\UNC\server\share\docs").as_deref(),
+            Some(r"\\server\share\docs")
+        );
+        assert_eq!(
+            strip_verbatim(r"\\?\C:\Users").as_deref(),
+            Some(r"C:\Users")
+        );
+        assert_eq!(strip_verbatim(r"\\?\Volume{1234}\x"), None);
+        assert_eq!(strip_verbatim("/home/user"), None);
+    }
+
+    #[test]
+    fn computer_page_lists_storage_mounts() {
+        let table = "\
+/dev/nvme0n1p2 / ext4 rw,relatime 0 0
+proc /proc proc rw 0 0
+tmpfs /run tmpfs rw 0 0
+/dev/nvme0n1p1 /boot/efi vfat rw 0 0
+/dev/sdb1 /run/media/me/USB\\040Stick vfat rw 0 0
+server:/export /mnt/nas nfs4 rw 0 0
+cgroup2 /sys/fs/cgroup cgroup2 rw 0 0
+";
+        assert_eq!(
+            parse_mounts(table),
+            ["/", "/run/media/me/USB Stick", "/mnt/nas"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn filesystem_info_picks_the_deepest_mount_and_flags_shares() {
+        let table = "\
+/dev/nvme0n1p2 / ext4 rw,relatime 0 0
+/dev/sdb1 /media/usb vfat rw,nosuid 0 0
+/dev/sdb1 /media/usb vfat ro,nosuid 0 0
+//nas/photos /mnt/photos\\040share cifs rw,vers=3.0 0 0
+nas:/export /mnt/nfs nfs4 rw 0 0
+";
+        let usb = mount_entry(table, Path::new("/media/usb/dcim/img.jpg")).unwrap();
+        assert_eq!(usb.mount_point, Path::new("/media/usb"));
+        assert_eq!(usb.fs_type.as_deref(), Some("vfat"));
+        assert_eq!(usb.device.as_deref(), Some("/dev/sdb1"));
+        assert!(usb.read_only, "the later mount on the same point wins");
+        assert!(!usb.remote);
+
+        let share = mount_entry(table, Path::new("/mnt/photos share/2024")).unwrap();
+        assert!(share.remote && !share.read_only);
+        assert!(mount_entry(table, Path::new("/mnt/nfs")).unwrap().remote);
+        let root = mount_entry(table, Path::new("/home/me")).unwrap();
+        assert_eq!(
+            (root.mount_point.as_path(), root.remote),
+            (Path::new("/"), false)
+        );
+
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        assert_eq!(
+            same_filesystem(&dir.path().join("a.txt"), &dir.path().join("docs")),
+            Some(true)
+        );
+        assert_eq!(
+            same_filesystem(&dir.path().join("missing"), dir.path()),
+            None
+        );
+    }
+}
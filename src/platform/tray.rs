@@ -0,0 +1,249 @@
+// Tray icon for finishing jobs in the background, with quick actions in its menu
+//
+// Linux only for now, as a StatusNotifierItem over D-Bus (KDE, XFCE, GNOME with
+// the AppIndicator extension), built with the `tray` cargo feature. Elsewhere, or
+// when no tray host is running, the icon reports why it could not be shown and the
+// minimized window stands in for it.
+
+use eframe::egui;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+/// What the user picked in the tray menu
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(not(all(feature = "tray", target_os = "linux")), allow(dead_code))]
+pub enum TrayAction {
+    /// Bring the window back
+    Show,
+    /// Bring the window back with the task center open
+    ShowJobs,
+    /// Bring the window back with a new tab at this folder
+    OpenBookmark(PathBuf),
+    /// Cancel what is left and quit
+    Quit,
+}
+
+/// What the tray menu lists, sent again whenever it changes
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrayMenu {
+    /// One line per running or queued job
+    pub jobs: Vec<String>,
+    /// Bookmark label and folder, in menu order
+    pub bookmarks: Vec<(String, PathBuf)>,
+}
+
+/// A tray icon served from its own thread; dropping it removes the icon
+pub struct TrayIcon {
+    menus: Sender<TrayMenu>,
+    /// Picked actions, or why the icon could not be shown
+    events: Receiver<Result<TrayAction, String>>,
+    menu: TrayMenu,
+}
+
+impl TrayIcon {
+    /// Start showing the icon; a failure arrives later through `try_event`
+    pub fn spawn(ctx: egui::Context, menu: TrayMenu) -> Self {
+        let (menus, menu_rx) = channel();
+        let (event_tx, events) = channel();
+        let first = menu.clone();
+        thread::spawn(move || serve(ctx, first, menu_rx, event_tx));
+        Self {
+            menus,
+            events,
+            menu,
+        }
+    }
+
+    /// Show `menu` from now on (sent only when it differs from the current one)
+    pub fn set_menu(&mut self, menu: TrayMenu) {
+        if menu != self.menu {
+            let _ = self.menus.send(menu.clone());
+            self.menu = menu;
+        }
+    }
+
+    pub fn try_event(&self) -> Option<Result<TrayAction, String>> {
+        self.events.try_recv().ok()
+    }
+}
+
+#[cfg(all(feature = "tray", target_os = "linux"))]
+fn serve(
+    ctx: egui::Context,
+    menu: TrayMenu,
+    menus: Receiver<TrayMenu>,
+    events: Sender<Result<TrayAction, String>>,
+) {
+    use ksni::blocking::TrayMethods;
+
+    let tray = sni::HeikeTray {
+        menu,
+        icon: sni::icon(),
+        events: events.clone(),
+        ctx: ctx.clone(),
+    };
+    let handle = match tray.spawn() {
+        Ok(handle) => handle,
+        Err(e) => {
+            let _ = events.send(Err(format!("no tray host ({})", e)));
+            ctx.request_repaint();
+            return;
+        }
+    };
+    // Until the app drops its `TrayIcon`
+    for menu in menus {
+        handle.update(|tray| tray.menu = menu);
+    }
+    handle.shutdown().wait();
+}
+
+#[cfg(not(all(feature = "tray", target_os = "linux")))]
+fn serve(
+    ctx: egui::Context,
+    _menu: TrayMenu,
+    _menus: Receiver<TrayMenu>,
+    events: Sender<Result<TrayAction, String>>,
+) {
+    let _ = events.send(Err(
+        "this build has no tray support (Linux, `tray` feature)".into(),
+    ));
+    ctx.request_repaint();
+}
+
+#[cfg(all(feature = "tray", target_os = "linux"))]
+mod sni {
+    use super::{TrayAction, TrayMenu};
+    use eframe::egui;
+    use ksni::menu::{StandardItem, SubMenu};
+    use ksni::{Icon, MenuItem, ToolTip};
+    use std::sync::mpsc::Sender;
+
+    /// Edge of the icon handed to the tray host, which scales it further
+    const ICON_SIZE: u32 = 64;
+
+    pub struct HeikeTray {
+        pub menu: TrayMenu,
+        pub icon: Icon,
+        pub events: Sender<Result<TrayAction, String>>,
+        pub ctx: egui::Context,
+    }
+
+    impl HeikeTray {
+        fn send(&self, action: TrayAction) {
+            let _ = self.events.send(Ok(action));
+            self.ctx.request_repaint();
+        }
+    }
+
+    /// The app icon in the ARGB layout StatusNotifierItem expects
+    pub fn icon() -> Icon {
+        let image = image::load_from_memory(crate::APP_ICON)
+            .map(|image| {
+                image
+                    .resize(ICON_SIZE, ICON_SIZE, image::imageops::FilterType::Triangle)
+                    .to_rgba8()
+            })
+            .unwrap_or_default();
+        let (width, height) = image.dimensions();
+        let data = image
+            .pixels()
+            .flat_map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                [a, r, g, b]
+            })
+            .collect();
+        Icon {
+            width: width as i32,
+            height: height as i32,
+            data,
+        }
+    }
+
+    /// Menu label showing `text` as is; a lone underscore would mark an access key
+    fn label(text: &str) -> String {
+        text.replace('_', "__")
+    }
+
+    fn item(text: &str, action: TrayAction) -> MenuItem<HeikeTray> {
+        StandardItem {
+            label: label(text),
+            activate: Box::new(move |tray: &mut HeikeTray| tray.send(action.clone())),
+            ..Default::default()
+        }
+        .into()
+    }
+
+    impl ksni::Tray for HeikeTray {
+        fn id(&self) -> String {
+            "heike".into()
+        }
+
+        fn title(&self) -> String {
+            "Heike".into()
+        }
+
+        fn icon_pixmap(&self) -> Vec<Icon> {
+            vec![self.icon.clone()]
+        }
+
+        fn tool_tip(&self) -> ToolTip {
+            ToolTip {
+                title: "Heike".into(),
+                description: match self.menu.jobs.as_slice() {
+                    [] => "No jobs running".into(),
+                    jobs => jobs.join("\n"),
+                },
+                ..Default::default()
+            }
+        }
+
+        fn activate(&mut self, _x: i32, _y: i32) {
+            self.send(TrayAction::Show);
+        }
+
+        fn menu(&self) -> Vec<MenuItem<Self>> {
+            let mut items: Vec<MenuItem<Self>> = if self.menu.jobs.is_empty() {
+                vec![StandardItem {
+                    label: "No jobs running".into(),
+                    enabled: false,
+                    ..Default::default()
+                }
+                .into()]
+            } else {
+                self.menu
+                    .jobs
+                    .iter()
+                    .map(|job| {
+                        StandardItem {
+                            label: label(job),
+                            enabled: false,
+                            ..Default::default()
+                        }
+                        .into()
+                    })
+                    .collect()
+            };
+            items.push(item("Show jobs", TrayAction::ShowJobs));
+            items.push(MenuItem::Separator);
+            items.push(
+                SubMenu {
+                    label: "Open at bookmark".into(),
+                    enabled: !self.menu.bookmarks.is_empty(),
+                    submenu: self
+                        .menu
+                        .bookmarks
+                        .iter()
+                        .map(|(name, path)| item(name, TrayAction::OpenBookmark(path.clone())))
+                        .collect(),
+                    ..Default::default()
+                }
+                .into(),
+            );
+            items.push(item("Show Heike", TrayAction::Show));
+            items.push(MenuItem::Separator);
+            items.push(item("Quit", TrayAction::Quit));
+            items
+        }
+    }
+}
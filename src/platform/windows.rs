@@ -0,0 +1,179 @@
+// Windows integration: drive letters and shell verbs (Open with, Properties)
+use super::Place;
+use std::ffi::OsStr;
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use windows_sys::Win32::Storage::FileSystem::{
+    GetDiskFreeSpaceExW, GetDriveTypeW, GetLogicalDrives, GetVolumeInformationW,
+};
+use windows_sys::Win32::UI::Shell::{ShellExecuteExW, SEE_MASK_INVOKEIDLIST, SHELLEXECUTEINFOW};
+use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+// GetDriveTypeW return values
+const DRIVE_REMOVABLE: u32 = 2;
+const DRIVE_REMOTE: u32 = 4;
+const DRIVE_CDROM: u32 = 5;
+
+fn wide(s: &OsStr) -> Vec<u16> {
+    s.encode_wide().chain(std::iter::once(0)).collect()
+}
+
+/// Enumerate mounted drive letters with their volume labels
+pub fn drives() -> Vec<Place> {
+    let mask = unsafe { GetLogicalDrives() };
+    (0..26u8)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(|bit| {
+            let root = format!("{}:\\", (b'A' + bit) as char);
+            let root_w = wide(OsStr::new(&root));
+            let kind = match unsafe { GetDriveTypeW(root_w.as_ptr()) } {
+                DRIVE_REMOVABLE => "Removable",
+                DRIVE_REMOTE => "Network",
+                DRIVE_CDROM => "CD/DVD",
+                _ => "Local Disk",
+            };
+
+            let mut label = [0u16; 261];
+            let ok = unsafe {
+                GetVolumeInformationW(
+                    root_w.as_ptr(),
+                    label.as_mut_ptr(),
+                    label.len() as u32,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    0,
+                )
+            };
+            let len = label.iter().position(|&c| c == 0).unwrap_or(0);
+            let volume = if ok != 0 && len > 0 {
+                String::from_utf16_lossy(&label[..len])
+            } else {
+                kind.to_string()
+            };
+
+            Place {
+                name: format!("{} ({})", volume, &root[..2]),
+                path: PathBuf::from(root),
+            }
+        })
+        .collect()
+}
+
+/// Total and available bytes of the volume holding `path`
+pub fn disk_space(path: &Path) -> Option<(u64, u64)> {
+    let path_w = wide(path.as_os_str());
+    let (mut available, mut total) = (0u64, 0u64);
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            path_w.as_ptr(),
+            &mut available,
+            &mut total,
+            std::ptr::null_mut(),
+        )
+    };
+    (ok != 0).then_some((total, available))
+}
+
+/// Filesystem name and flags of the volume holding `path`, from its drive root
+pub fn filesystem_info(path: &Path) -> Option<super::FilesystemInfo> {
+    // GetVolumeInformationW file system flag
+    const FILE_READ_ONLY_VOLUME: u32 = 0x0008_0000;
+
+    let root = super::mount_root(&path.canonicalize().ok()?)?;
+    let root = super::simplify_path(root);
+    let root_w = wide(root.as_os_str());
+    let mut fs_name = [0u16; 261];
+    let mut flags = 0u32;
+    let ok = unsafe {
+        GetVolumeInformationW(
+            root_w.as_ptr(),
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut flags,
+            fs_name.as_mut_ptr(),
+            fs_name.len() as u32,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+    let len = fs_name.iter().position(|&c| c == 0).unwrap_or(0);
+    Some(super::FilesystemInfo {
+        remote: unsafe { GetDriveTypeW(root_w.as_ptr()) } == DRIVE_REMOTE,
+        read_only: flags & FILE_READ_ONLY_VOLUME != 0,
+        fs_type: (len > 0).then(|| String::from_utf16_lossy(&fs_name[..len])),
+        device: None,
+        mount_point: root,
+    })
+}
+
+/// Invoke a shell verb ("openas", "properties") on a file
+fn shell_verb(path: &Path, verb: &str) -> io::Result<()> {
+    let verb_w = wide(OsStr::new(verb));
+    let file_w = wide(path.as_os_str());
+    let mut info: SHELLEXECUTEINFOW = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+    info.fMask = SEE_MASK_INVOKEIDLIST;
+    info.lpVerb = verb_w.as_ptr();
+    info.lpFile = file_w.as_ptr();
+    info.nShow = SW_SHOWNORMAL;
+    if unsafe { ShellExecuteExW(&mut info) } != 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+pub fn open_with(path: &Path) -> io::Result<()> {
+    shell_verb(path, "openas")
+}
+
+pub fn show_properties(path: &Path) -> io::Result<()> {
+    shell_verb(path, "properties")
+}
+
+/// PowerShell command showing a toast with two text lines
+///
+/// The text goes in through the environment, never into the script, so no quote
+/// in a file name (PowerShell also takes U+2018–U+201B as quotes) can end a string.
+pub fn notify_command(title: &str, body: &str) -> std::process::Command {
+    use std::os::windows::process::CommandExt;
+    // Don't flash a console window
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    const SCRIPT: &str = "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+         $xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+         $text = $xml.GetElementsByTagName('text'); \
+         $text.Item(0).AppendChild($xml.CreateTextNode($env:HEIKE_TITLE)) > $null; \
+         $text.Item(1).AppendChild($xml.CreateTextNode($env:HEIKE_BODY)) > $null; \
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('Heike').Show([Windows.UI.Notifications.ToastNotification]::new($xml))";
+    let mut command = std::process::Command::new("powershell");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("HEIKE_TITLE", title)
+        .env("HEIKE_BODY", body)
+        .creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn notification_text_stays_out_of_the_script() {
+        let name = "a\u{2019};calc;\u{2019}";
+        let command = notify_command("New file", name);
+        assert!(command
+            .get_args()
+            .all(|arg| !arg.to_string_lossy().contains("calc")));
+        let envs: Vec<_> = command.get_envs().collect();
+        assert!(envs.contains(&(OsStr::new("HEIKE_BODY"), Some(OsStr::new(name)))));
+        assert!(envs.contains(&(OsStr::new("HEIKE_TITLE"), Some(OsStr::new("New file")))));
+    }
+}
@@ -0,0 +1,34 @@
+// Links and shortcuts whose target no longer exists
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkKind {
+    Symlink,
+    /// A freedesktop `.desktop` launcher or link
+    DesktopEntry,
+    /// A Windows `.lnk` shortcut
+    Shortcut,
+}
+
+impl LinkKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            LinkKind::Symlink => "symlink",
+            LinkKind::DesktopEntry => ".desktop",
+            LinkKind::Shortcut => ".lnk",
+        }
+    }
+
+    /// Shortcuts are a binary format we only read
+    pub fn can_retarget(self) -> bool {
+        self != LinkKind::Shortcut
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BrokenLink {
+    pub path: PathBuf,
+    pub kind: LinkKind,
+    /// The missing target as written in the link: a path, or the program a launcher runs
+    pub target: String,
+}
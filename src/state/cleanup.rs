@@ -0,0 +1,22 @@
+// Empty directories and zero-byte files offered for cleanup
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CleanupItem {
+    /// Path relative to the scanned directory
+    pub relative_path: PathBuf,
+    /// An empty directory rather than a zero-byte file
+    pub is_dir: bool,
+    /// Ticked for deletion
+    pub checked: bool,
+}
+
+impl CleanupItem {
+    pub fn new(relative_path: PathBuf, is_dir: bool) -> Self {
+        Self {
+            relative_path,
+            is_dir,
+            checked: true,
+        }
+    }
+}
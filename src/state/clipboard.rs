@@ -1,5 +1,79 @@
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ClipboardOp {
     Copy,
     Cut,
 }
+
+/// What dropping files from another application does
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropAction {
+    Copy,
+    Move,
+    Link,
+}
+
+impl DropAction {
+    /// Action for the modifiers held while dropping
+    ///
+    /// Ctrl copies, Shift moves and Alt (or Ctrl+Shift) links; on macOS Option
+    /// copies, Command moves and Option+Command links. Plain drops copy.
+    pub fn from_modifiers(ctrl: bool, shift: bool, alt: bool, command: bool) -> Self {
+        if cfg!(target_os = "macos") {
+            match (alt, command) {
+                (true, true) => DropAction::Link,
+                (false, true) => DropAction::Move,
+                _ => DropAction::Copy,
+            }
+        } else if alt || (ctrl && shift) {
+            DropAction::Link
+        } else if shift {
+            DropAction::Move
+        } else {
+            DropAction::Copy
+        }
+    }
+
+    pub fn verb(self) -> &'static str {
+        match self {
+            DropAction::Copy => "copy",
+            DropAction::Move => "move",
+            DropAction::Link => "link",
+        }
+    }
+}
+
+/// What a paste does when an item with the same name is already there
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Show the conflict dialog
+    Ask,
+    /// Keep both: the incoming item becomes "name (1)", "name (2)", …
+    Rename,
+    /// Move the existing item to the trash first
+    Overwrite,
+    Skip,
+}
+
+impl ConflictPolicy {
+    pub const ALL: [ConflictPolicy; 4] = [
+        ConflictPolicy::Ask,
+        ConflictPolicy::Rename,
+        ConflictPolicy::Overwrite,
+        ConflictPolicy::Skip,
+    ];
+
+    /// Name used in `file_ops.conflict_policy`
+    pub fn name(self) -> &'static str {
+        match self {
+            ConflictPolicy::Ask => "ask",
+            ConflictPolicy::Rename => "rename",
+            ConflictPolicy::Overwrite => "overwrite",
+            ConflictPolicy::Skip => "skip",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::ALL.into_iter().find(|p| p.name() == name)
+    }
+}
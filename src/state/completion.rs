@@ -0,0 +1,212 @@
+// Name completion and templates for the rename and mkdir/touch prompts
+use crate::entry::compare_names;
+use chrono::{DateTime, Local};
+use std::path::Path;
+
+/// Placeholders expanded when a typed name is committed, with their descriptions
+pub const NAME_TEMPLATES: [(&str, &str); 4] = [
+    ("{date}", "today, e.g. 2025-01-31"),
+    ("{time}", "current time, e.g. 14-05"),
+    ("{parent}", "name of the containing directory"),
+    ("{n}", "lowest number giving a name that is not taken yet"),
+];
+
+/// Tab completion against existing names, cycling through the matches on repeated presses
+#[derive(Clone, Debug, Default)]
+pub struct NameCompletion {
+    matches: Vec<String>,
+    index: usize,
+}
+
+impl NameCompletion {
+    /// Next name starting with `input` (case-insensitively), or the one after the
+    /// previous completion when `input` is still that completion
+    pub fn complete<'a>(
+        &mut self,
+        input: &str,
+        names: impl IntoIterator<Item = &'a str>,
+        backward: bool,
+    ) -> Option<String> {
+        let count = self.matches.len();
+        if count > 0 && self.matches.get(self.index).map(String::as_str) == Some(input) {
+            self.index = if backward {
+                (self.index + count - 1) % count
+            } else {
+                (self.index + 1) % count
+            };
+            return self.matches.get(self.index).cloned();
+        }
+
+        let prefix = input.to_lowercase();
+        self.matches = names
+            .into_iter()
+            .filter(|name| *name != input && name.to_lowercase().starts_with(&prefix))
+            .map(str::to_string)
+            .collect();
+        self.matches.sort_by(|a, b| compare_names(a, b));
+        self.index = if backward {
+            self.matches.len().saturating_sub(1)
+        } else {
+            0
+        };
+        self.matches.get(self.index).cloned()
+    }
+
+    pub fn reset(&mut self) {
+        self.matches.clear();
+        self.index = 0;
+    }
+}
+
+/// Candidates within a small edit distance of `word`, closest first, for "did you mean" hints
+pub fn near_matches<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let word = word.to_lowercase();
+    let limit = (word.chars().count() / 3).max(1);
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(&word, &candidate.to_lowercase());
+            let is_prefix = word.len() >= 2 && candidate.starts_with(word.as_str());
+            (distance <= limit || is_prefix).then_some((distance, candidate))
+        })
+        .collect();
+    matches.sort();
+    matches.dedup();
+    matches
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Edit distance over chars where swapping two neighbours counts as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Replace the `NAME_TEMPLATES` placeholders in a name typed for `parent`
+///
+/// `{n}` is resolved last, against the entries that exist in `parent` at the time of the call.
+pub fn expand_name_template(name: &str, parent: &Path, now: DateTime<Local>) -> String {
+    if !name.contains('{') {
+        return name.to_string();
+    }
+    let parent_name = parent
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = name
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H-%M").to_string())
+        .replace("{parent}", &parent_name);
+    if !name.contains("{n}") {
+        return name;
+    }
+    (1u64..)
+        .map(|n| name.replace("{n}", &n.to_string()))
+        .find(|candidate| parent.join(candidate).symlink_metadata().is_err())
+        .unwrap_or(name)
+}
+
+/// Expand shell-style alternatives, so `{src,tests}/mod.rs` gives `src/mod.rs` and `tests/mod.rs`
+///
+/// Braces without a top-level comma, such as the `NAME_TEMPLATES` placeholders, are kept as typed.
+pub fn expand_braces(input: &str) -> Vec<String> {
+    let mut search_from = 0;
+    while let Some(open) = input[search_from..].find('{').map(|i| i + search_from) {
+        let mut depth = 0;
+        let mut bounds = vec![open];
+        let mut close = None;
+        for (i, c) in input[open..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = Some(open + i);
+                        break;
+                    }
+                }
+                ',' if depth == 1 => bounds.push(open + i),
+                _ => {}
+            }
+        }
+        let Some(close) = close else {
+            break;
+        };
+        if bounds.len() == 1 {
+            search_from = open + 1;
+            continue;
+        }
+        bounds.push(close);
+        let (head, tail) = (&input[..open], &input[close + 1..]);
+        return bounds
+            .windows(2)
+            .flat_map(|w| expand_braces(&format!("{}{}{}", head, &input[w[0] + 1..w[1]], tail)))
+            .collect();
+    }
+    vec![input.to_string()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn new_names_expand_braces_and_counters() {
+        assert_eq!(
+            expand_braces("{src,tests,docs}"),
+            vec!["src", "tests", "docs"]
+        );
+        assert_eq!(
+            expand_braces("{a,b}/{x,y}.rs"),
+            vec!["a/x.rs", "a/y.rs", "b/x.rs", "b/y.rs"]
+        );
+        assert_eq!(
+            expand_braces("{date}-{draft,final}"),
+            vec!["{date}-draft", "{date}-final"]
+        );
+        assert_eq!(expand_braces("plain{"), vec!["plain{"]);
+
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        fs::write(dir.path().join("take-1.txt"), "").unwrap();
+        let now = Local::now();
+        assert_eq!(
+            expand_name_template("take-{n}.txt", dir.path(), now),
+            "take-2.txt"
+        );
+        assert_eq!(
+            expand_name_template("{parent}-{date}", &dir.path().join("docs"), now),
+            format!("docs-{}", now.format("%Y-%m-%d"))
+        );
+    }
+
+    #[test]
+    fn unknown_commands_suggest_near_matches() {
+        let commands = ["mkdir", "mirror", "messages", "touch", "tasks"];
+        assert_eq!(near_matches("mkdri", commands), vec!["mkdir"]);
+        assert_eq!(near_matches("mes", commands), vec!["messages"]);
+        assert_eq!(near_matches("TUOCH", commands), vec!["touch"]);
+        assert!(near_matches("zzz", commands).is_empty());
+        assert_eq!(near_matches("tuch", commands), vec!["touch"]);
+    }
+}
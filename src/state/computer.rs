@@ -0,0 +1,98 @@
+// The Computer page: drives, places and recent locations as cards to jump to
+use crate::platform::Volume;
+use std::path::{Path, PathBuf};
+
+/// Recent locations offered on the Computer page
+pub const MAX_RECENT: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CardKind {
+    Volume,
+    Home,
+    Bookmark,
+    Recent,
+}
+
+impl CardKind {
+    pub fn icon(self) -> &'static str {
+        match self {
+            CardKind::Volume => "💾",
+            CardKind::Home => "🏠",
+            CardKind::Bookmark => "🔖",
+            CardKind::Recent => "🕘",
+        }
+    }
+
+    /// Section heading the card is listed under
+    pub fn section(self) -> &'static str {
+        match self {
+            CardKind::Volume => "Drives",
+            CardKind::Home | CardKind::Bookmark => "Places",
+            CardKind::Recent => "Recent",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComputerCard {
+    pub kind: CardKind,
+    pub title: String,
+    pub path: PathBuf,
+    /// Total and available bytes, for drives
+    pub space: Option<(u64, u64)>,
+}
+
+impl ComputerCard {
+    pub fn new(kind: CardKind, title: String, path: PathBuf) -> Self {
+        Self {
+            kind,
+            title,
+            path,
+            space: None,
+        }
+    }
+
+    pub fn from_volume(volume: Volume) -> Self {
+        Self {
+            kind: CardKind::Volume,
+            title: volume.name,
+            path: volume.path,
+            space: volume.space,
+        }
+    }
+}
+
+/// Directories visited most recently: the active tab's history walking back from its
+/// current entry, then the other tabs' current directories, without repeats
+pub fn recent_locations(
+    active: (&[PathBuf], usize),
+    others: &[PathBuf],
+    exists: impl Fn(&Path) -> bool,
+) -> Vec<PathBuf> {
+    let (history, index) = active;
+    let back = history.iter().take(index + 1).rev();
+    let mut recent: Vec<PathBuf> = Vec::new();
+    for path in back.chain(others) {
+        if recent.len() == MAX_RECENT {
+            break;
+        }
+        if !recent.contains(path) && exists(path) {
+            recent.push(path.clone());
+        }
+    }
+    recent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computer_page_lists_recent_places() {
+        let history = ["/a", "/b", "/a", "/c", "/d"].map(PathBuf::from);
+        let others = ["/e", "/b", "/gone"].map(PathBuf::from);
+        // Walking back from the current entry; forward history is not "recent"
+        let recent = recent_locations((&history, 3), &others, |p| p != Path::new("/gone"));
+        assert_eq!(recent, ["/c", "/a", "/b", "/e"].map(PathBuf::from));
+    }
+}
@@ -0,0 +1,22 @@
+// Directory tree comparison results
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffKind {
+    /// Exists only under the left (current) root
+    OnlyLeft,
+    /// Exists only under the right (other) root
+    OnlyRight,
+    /// Exists on both sides but size, mtime or content differ
+    Different,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirDiffEntry {
+    /// Path relative to both roots
+    pub relative_path: PathBuf,
+    pub kind: DiffKind,
+    pub is_dir: bool,
+    pub left_size: Option<u64>,
+    pub right_size: Option<u64>,
+}
@@ -0,0 +1,96 @@
+// Dry runs - what a batch operation would do, collected instead of touching the disk
+use std::path::PathBuf;
+
+/// One item of a dry run
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlannedChange {
+    /// Verb of the change, e.g. "rename", "copy", "skip"
+    pub action: &'static str,
+    pub source: PathBuf,
+    /// Where the item would end up, if it moves or is created anywhere
+    pub target: Option<PathBuf>,
+    /// Why the item is skipped or what else happens to it
+    pub note: Option<String>,
+    /// The real run would fail on this item
+    pub problem: bool,
+}
+
+impl PlannedChange {
+    pub fn new(action: &'static str, source: PathBuf, target: Option<PathBuf>) -> Self {
+        Self {
+            action,
+            source,
+            target,
+            note: None,
+            problem: false,
+        }
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    /// An item the real run would fail on, with the reason
+    pub fn failure(source: PathBuf, reason: impl Into<String>) -> Self {
+        Self {
+            problem: true,
+            ..Self::new("fail", source, None).with_note(reason)
+        }
+    }
+
+    /// One line of the text report, e.g. "rename  /a/x.txt → /a/y.txt  (numbered)"
+    pub fn line(&self) -> String {
+        let mut line = format!("{:<8}{}", self.action, self.source.display());
+        if let Some(target) = &self.target {
+            line.push_str(&format!(" → {}", target.display()));
+        }
+        if let Some(note) = &self.note {
+            line.push_str(&format!("  ({})", note));
+        }
+        line
+    }
+}
+
+/// Everything a batch operation would change, for the review window
+#[derive(Clone, Debug, PartialEq)]
+pub struct DryRunReport {
+    /// The operation, e.g. "Move 12 items → /mnt/backup"
+    pub title: String,
+    pub changes: Vec<PlannedChange>,
+}
+
+impl DryRunReport {
+    pub fn new(title: String, changes: Vec<PlannedChange>) -> Self {
+        Self { title, changes }
+    }
+
+    /// Items the real run would fail on
+    pub fn problems(&self) -> usize {
+        self.changes.iter().filter(|c| c.problem).count()
+    }
+
+    /// Items that would be left alone
+    pub fn unchanged(&self) -> usize {
+        self.changes
+            .iter()
+            .filter(|c| matches!(c.action, "skip" | "keep"))
+            .count()
+    }
+
+    /// The whole report as plain text, for the clipboard or a file
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Dry run: {}\n{} items, {} unchanged, {} would fail; nothing was written\n\n",
+            self.title,
+            self.changes.len(),
+            self.unchanged(),
+            self.problems()
+        );
+        for change in &self.changes {
+            text.push_str(&change.line());
+            text.push('\n');
+        }
+        text
+    }
+}
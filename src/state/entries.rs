@@ -1,5 +1,7 @@
 // Entry state - holds file entries for different panes
-use crate::entry::FileEntry;
+use crate::entry::{EntryDetails, FileEntry};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 pub struct EntryState {
     pub all_entries: Vec<FileEntry>,
@@ -15,4 +17,34 @@ impl EntryState {
             parent_entries: Vec::new(),
         }
     }
+
+    /// Fill in details gathered after a listing, in whichever pane shows the entry
+    pub fn apply_details(&mut self, details: Vec<(PathBuf, EntryDetails)>) {
+        let details: HashMap<PathBuf, EntryDetails> = details.into_iter().collect();
+        for entry in self
+            .all_entries
+            .iter_mut()
+            .chain(self.visible_entries.iter_mut())
+            .chain(self.parent_entries.iter_mut())
+        {
+            if let Some(found) = details.get(&entry.path) {
+                entry.details = found.clone();
+            }
+        }
+    }
+}
+
+/// Keep the details of entries already on screen when their listing is reloaded,
+/// so git markers don't flicker off until the new enrichment arrives
+pub fn carry_details(previous: &[FileEntry], entries: &mut [FileEntry]) {
+    if previous.is_empty() {
+        return;
+    }
+    let previous: HashMap<&PathBuf, &EntryDetails> =
+        previous.iter().map(|e| (&e.path, &e.details)).collect();
+    for entry in entries {
+        if let Some(details) = previous.get(&entry.path) {
+            entry.details = (*details).clone();
+        }
+    }
 }
@@ -0,0 +1,310 @@
+// Multi-file operations processed item by item so failures can be handled one at a time
+use super::clipboard::{ClipboardOp, ConflictPolicy};
+use chrono::{DateTime, Duration, Local, NaiveTime, Timelike};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::time::Instant;
+
+/// What a batch job does with each item
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobKind {
+    /// Copy or move clipboard items into `dest_dir`
+    Paste {
+        op: ClipboardOp,
+        policy: ConflictPolicy,
+        dest_dir: PathBuf,
+    },
+    /// Create symbolic links to the items in `dest_dir`
+    Link { dest_dir: PathBuf },
+    /// Move items to the system trash
+    Trash,
+    /// Cut `source` into `chunk_size` parts; the items are the parts, then the manifest
+    Split { source: PathBuf, chunk_size: u64 },
+    /// Write the item parts, in order, into `dest`
+    Join { dest: PathBuf, parts: Vec<PathBuf> },
+    /// Copy items from below `from` to the same place below `to`, replacing what is
+    /// there (directory comparison)
+    Mirror { from: PathBuf, to: PathBuf },
+}
+
+impl JobKind {
+    /// Action name for the operation log and `[[hooks]]`
+    pub fn operation(&self) -> &'static str {
+        match self {
+            JobKind::Paste {
+                op: ClipboardOp::Copy,
+                ..
+            }
+            | JobKind::Mirror { .. } => "copy",
+            JobKind::Paste {
+                op: ClipboardOp::Cut,
+                ..
+            } => "move",
+            JobKind::Link { .. } => "link",
+            JobKind::Trash => "trash",
+            JobKind::Split { .. } => "split",
+            JobKind::Join { .. } => "join",
+        }
+    }
+
+    /// Action name and destination of one item, for the operation log
+    pub fn audit(&self, path: &Path) -> (&'static str, Option<PathBuf>) {
+        let into = |dir: &Path| path.file_name().map(|name| dir.join(name));
+        let destination = match self {
+            JobKind::Paste { dest_dir, .. } | JobKind::Link { dest_dir } => into(dest_dir),
+            JobKind::Trash => None,
+            // The item is the part or manifest written
+            JobKind::Split { source, .. } => Some(source.clone()),
+            JobKind::Join { dest, .. } => Some(dest.clone()),
+            JobKind::Mirror { from, to } => path.strip_prefix(from).ok().map(|rel| to.join(rel)),
+        };
+        (self.operation(), destination)
+    }
+
+    /// Folder the job works in: the destination, or where the items are
+    pub fn folder(&self, items: &[PathBuf]) -> Option<PathBuf> {
+        match self {
+            JobKind::Paste { dest_dir, .. } | JobKind::Link { dest_dir } => Some(dest_dir.clone()),
+            JobKind::Join { dest, .. } => dest.parent().map(Path::to_path_buf),
+            JobKind::Mirror { to, .. } => Some(to.clone()),
+            JobKind::Trash | JobKind::Split { .. } => items
+                .first()
+                .and_then(|item| item.parent())
+                .map(Path::to_path_buf),
+        }
+    }
+
+    /// Verb for status messages
+    pub fn verb(&self) -> &'static str {
+        match self {
+            JobKind::Paste {
+                op: ClipboardOp::Copy,
+                ..
+            }
+            | JobKind::Mirror { .. } => "Copied",
+            JobKind::Paste {
+                op: ClipboardOp::Cut,
+                ..
+            } => "Moved",
+            JobKind::Link { .. } => "Linked",
+            JobKind::Trash => "Trashed",
+            JobKind::Split { .. } => "Wrote",
+            JobKind::Join { .. } => "Joined",
+        }
+    }
+
+    /// One-line summary for the task center, e.g. "Move 12 items → /mnt/backup"
+    pub fn describe(&self, count: usize) -> String {
+        match self {
+            JobKind::Paste { op, dest_dir, .. } => format!(
+                "{} {} items → {}",
+                if *op == ClipboardOp::Copy {
+                    "Copy"
+                } else {
+                    "Move"
+                },
+                count,
+                dest_dir.display()
+            ),
+            JobKind::Link { dest_dir } => {
+                format!("Link {} items → {}", count, dest_dir.display())
+            }
+            JobKind::Trash => format!("Trash {} items", count),
+            JobKind::Split { source, chunk_size } => format!(
+                "Split {} into {} parts",
+                source.file_name().unwrap_or_default().to_string_lossy(),
+                bytesize::ByteSize(*chunk_size)
+            ),
+            JobKind::Join { dest, .. } => format!("Join {} parts → {}", count, dest.display()),
+            JobKind::Mirror { to, .. } => format!("Copy {} items across → {}", count, to.display()),
+        }
+    }
+}
+
+/// When a queued job may start
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JobStart {
+    /// As soon as no other job is running
+    AfterCurrent,
+    /// Not before this time
+    At(DateTime<Local>),
+}
+
+impl JobStart {
+    /// The next time the clock shows `hour:minute` (today or tomorrow)
+    pub fn next_at(hour: u32, minute: u32) -> Option<Self> {
+        let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+        let now = Local::now();
+        let today = now.date_naive().and_time(time).and_local_timezone(Local);
+        let mut start = today.earliest()?;
+        if start <= now {
+            start += Duration::days(1);
+        }
+        Some(JobStart::At(start))
+    }
+
+    /// Parse "HH:MM" into the next matching time
+    pub fn parse_time(text: &str) -> Option<Self> {
+        let time = NaiveTime::parse_from_str(text.trim(), "%H:%M").ok()?;
+        Self::next_at(time.hour(), time.minute())
+    }
+
+    pub fn is_due(&self, now: DateTime<Local>) -> bool {
+        match self {
+            JobStart::AfterCurrent => true,
+            JobStart::At(time) => *time <= now,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            JobStart::AfterCurrent => "after current job".to_string(),
+            JobStart::At(time) if time.date_naive() == Local::now().date_naive() => {
+                format!("today at {}", time.format("%H:%M"))
+            }
+            JobStart::At(time) => format!("{}", time.format("%a %H:%M")),
+        }
+    }
+}
+
+/// A job waiting in the task center
+#[derive(Debug)]
+pub struct QueuedJob {
+    pub job: BatchJob,
+    pub start: JobStart,
+}
+
+/// A requested job whose before hooks are still running
+#[derive(Debug)]
+pub struct HookCheck {
+    pub kind: JobKind,
+    pub items: Vec<PathBuf>,
+    pub start: JobStart,
+    /// `Ok` once every hook passed, or the failing command and its error
+    pub outcome: Receiver<Result<(), (String, String)>>,
+}
+
+/// Answer to a failed item while the job is paused
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobErrorAction {
+    Retry,
+    Skip,
+    /// Skip this and every later failure without asking
+    SkipAll,
+    /// Stop the job, leaving the remaining items untouched
+    Abort,
+}
+
+/// What to do with pending jobs when the window is closed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExitChoice {
+    /// Keep the window open and quit once the last job is done
+    Wait,
+    /// Abort the running job, drop the queued ones and quit
+    CancelAll,
+    /// Minimize and quit once the last job is done
+    Background,
+    /// Do not quit after all
+    Stay,
+}
+
+/// A running batch job; the item at the front of `pending` is the next (or failed) one
+#[derive(Debug)]
+pub struct BatchJob {
+    pub kind: JobKind,
+    pub pending: VecDeque<PathBuf>,
+    /// Item out of `pending` that the job thread is working on
+    pub running: Option<PathBuf>,
+    /// Bytes the running item has copied (and, for moves across filesystems, checked) so far
+    pub item_bytes: u64,
+    pub total: usize,
+    pub done: usize,
+    /// Items left alone on purpose (name conflicts with the skip policy)
+    pub skipped: usize,
+    /// Items skipped after a failure, with the error
+    pub failed: Vec<(PathBuf, String)>,
+    /// Items done, handed to the after hooks
+    pub completed: Vec<PathBuf>,
+    pub skip_errors: bool,
+    /// Held by the user; finished items are already out of `pending`, so resuming
+    /// continues with the first item that has not been done
+    pub paused: bool,
+    /// Set when the user cancelled or aborted the job: how many items were left undone
+    pub aborted: Option<usize>,
+    /// When the job became the running one (not when it was queued)
+    pub started: Instant,
+}
+
+impl BatchJob {
+    pub fn new(kind: JobKind, items: Vec<PathBuf>) -> Self {
+        Self {
+            kind,
+            total: items.len(),
+            pending: items.into(),
+            running: None,
+            item_bytes: 0,
+            done: 0,
+            skipped: 0,
+            failed: Vec::new(),
+            completed: Vec::new(),
+            skip_errors: false,
+            paused: false,
+            aborted: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// Items handled so far, whatever the outcome
+    pub fn processed(&self) -> usize {
+        self.total - self.pending.len() - usize::from(self.running.is_some())
+    }
+
+    /// Drop the items not started yet, remembering how many there were
+    pub fn abort(&mut self) {
+        *self.aborted.get_or_insert(0) += self.pending.len();
+        self.pending.clear();
+    }
+
+    /// Status line once the job is over, e.g. "Copied 3 of 5 items, aborted with 2 left"
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{} {} of {} items", self.kind.verb(), self.done, self.total);
+        if self.skipped > 0 {
+            summary.push_str(&format!(", skipped {} existing", self.skipped));
+        }
+        match self.aborted {
+            Some(0) => summary.push_str(", aborted"),
+            Some(left) => summary.push_str(&format!(", aborted with {} left", left)),
+            None => {}
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aborted_jobs_report_what_was_left() {
+        let items: Vec<PathBuf> = ["a", "b", "c", "d"].iter().map(PathBuf::from).collect();
+        let mut job = BatchJob::new(JobKind::Trash, items);
+        assert_eq!(job.summary(), "Trashed 0 of 4 items");
+
+        // "a" is done, "b" failed and the user chose Abort
+        job.pending.pop_front();
+        job.done += 1;
+        let failed = job.pending.pop_front().unwrap();
+        job.failed.push((failed, "Permission denied".into()));
+        job.abort();
+        assert!(job.pending.is_empty());
+        assert_eq!(job.aborted, Some(2));
+        assert_eq!(job.summary(), "Trashed 1 of 4 items, aborted with 2 left");
+
+        // Cancelled while the last item was running: nothing was waiting
+        let mut job = BatchJob::new(JobKind::Trash, vec![PathBuf::from("a")]);
+        job.running = job.pending.pop_front();
+        job.abort();
+        assert_eq!(job.summary(), "Trashed 0 of 1 items, aborted");
+    }
+}
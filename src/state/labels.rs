@@ -0,0 +1,67 @@
+// Color labels for files (Finder-compatible palette)
+
+/// Color label attached to a file; order and numbering follow Finder
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LabelColor {
+    Gray,
+    Green,
+    Purple,
+    Blue,
+    Yellow,
+    Red,
+    Orange,
+}
+
+impl LabelColor {
+    pub const ALL: [LabelColor; 7] = [
+        LabelColor::Red,
+        LabelColor::Orange,
+        LabelColor::Yellow,
+        LabelColor::Green,
+        LabelColor::Blue,
+        LabelColor::Purple,
+        LabelColor::Gray,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LabelColor::Gray => "Gray",
+            LabelColor::Green => "Green",
+            LabelColor::Purple => "Purple",
+            LabelColor::Blue => "Blue",
+            LabelColor::Yellow => "Yellow",
+            LabelColor::Red => "Red",
+            LabelColor::Orange => "Orange",
+        }
+    }
+
+    /// Parse a label name case-insensitively (also accepts "grey")
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if name == "grey" {
+            return Some(LabelColor::Gray);
+        }
+        Self::ALL
+            .into_iter()
+            .find(|c| c.name().to_lowercase() == name)
+    }
+
+    /// Finder's numeric color index (1-7)
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub fn finder_index(self) -> u8 {
+        match self {
+            LabelColor::Gray => 1,
+            LabelColor::Green => 2,
+            LabelColor::Purple => 3,
+            LabelColor::Blue => 4,
+            LabelColor::Yellow => 5,
+            LabelColor::Red => 6,
+            LabelColor::Orange => 7,
+        }
+    }
+
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub fn from_finder_index(index: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.finder_index() == index)
+    }
+}
@@ -0,0 +1,108 @@
+// Listings of slow (typically network) directories, shown at once when one is entered
+// again while a fresh read runs in the background
+use crate::entry::FileEntry;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Directories that take at least this long to read get their listing kept
+pub const SLOW_LISTING: Duration = Duration::from_millis(500);
+
+/// Most listings kept; the oldest is dropped beyond this
+const MAX_LISTINGS: usize = 32;
+
+pub struct CachedListing {
+    pub entries: Vec<FileEntry>,
+    /// Whether hidden entries were included
+    pub show_hidden: bool,
+    pub loaded_at: Instant,
+}
+
+#[derive(Default)]
+pub struct ListingCache {
+    listings: HashMap<PathBuf, CachedListing>,
+}
+
+impl ListingCache {
+    /// Keep the listing of `path` if reading it took `elapsed` or longer than
+    /// `SLOW_LISTING`, and forget one that has become fast
+    pub fn record(
+        &mut self,
+        path: &Path,
+        entries: &[FileEntry],
+        show_hidden: bool,
+        elapsed: Duration,
+        now: Instant,
+    ) {
+        if elapsed < SLOW_LISTING {
+            self.listings.remove(path);
+            return;
+        }
+        if self.listings.len() >= MAX_LISTINGS && !self.listings.contains_key(path) {
+            if let Some(oldest) = self
+                .listings
+                .iter()
+                .min_by_key(|(_, listing)| listing.loaded_at)
+                .map(|(path, _)| path.clone())
+            {
+                self.listings.remove(&oldest);
+            }
+        }
+        self.listings.insert(
+            path.to_path_buf(),
+            CachedListing {
+                entries: entries.to_vec(),
+                show_hidden,
+                loaded_at: now,
+            },
+        );
+    }
+
+    /// Kept listing of `path` read with the same hidden-file setting
+    pub fn get(&self, path: &Path, show_hidden: bool) -> Option<&CachedListing> {
+        self.listings
+            .get(path)
+            .filter(|listing| listing.show_hidden == show_hidden)
+    }
+}
+
+/// How old a cached listing is, e.g. "12 s" or "3 min"
+pub fn listing_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{} s", secs),
+        60..=3599 => format!("{} min", secs / 60),
+        _ => format!("{} h", secs / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::directory::read_directory;
+    use tempfile::TempDir;
+
+    #[test]
+    fn slow_listings_are_kept_and_fast_ones_forgotten() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        let entries = read_directory(dir.path(), false).unwrap();
+        let mut cache = ListingCache::default();
+        let now = Instant::now();
+
+        cache.record(dir.path(), &entries, false, Duration::from_millis(5), now);
+        assert!(cache.get(dir.path(), false).is_none());
+
+        cache.record(dir.path(), &entries, false, SLOW_LISTING, now);
+        let cached = cache.get(dir.path(), false).unwrap();
+        assert_eq!(cached.entries.len(), entries.len());
+        assert!(
+            cache.get(dir.path(), true).is_none(),
+            "hidden setting differs"
+        );
+
+        // The directory became fast again, e.g. the share is now local or warm
+        cache.record(dir.path(), &entries, false, Duration::ZERO, now);
+        assert!(cache.get(dir.path(), false).is_none());
+    }
+}
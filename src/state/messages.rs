@@ -0,0 +1,110 @@
+// Status messages: severity levels, on-screen toasts and a browsable history
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Toasts shown at once; older ones are dropped from the screen (not the history)
+pub const MAX_TOASTS: usize = 4;
+/// Messages kept in the history panel
+pub const MESSAGE_HISTORY_LIMIT: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Info, Severity::Warning, Severity::Error];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Severity::Info => "ℹ",
+            Severity::Warning => "⚠",
+            Severity::Error => "✘",
+        }
+    }
+
+    /// Errors stay on screen twice as long as the base timeout
+    pub fn timeout_secs(self, base: u64) -> u64 {
+        match self {
+            Severity::Error => base * 2,
+            _ => base,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub text: String,
+    pub severity: Severity,
+    /// When the toast was raised, for expiry
+    pub raised: Instant,
+    /// Wall-clock time shown in the history panel
+    pub timestamp: DateTime<Local>,
+}
+
+#[derive(Default)]
+pub struct MessageLog {
+    toasts: VecDeque<Message>,
+    history: VecDeque<Message>,
+}
+
+impl MessageLog {
+    pub fn push(&mut self, severity: Severity, text: String) {
+        let message = Message {
+            text,
+            severity,
+            raised: Instant::now(),
+            timestamp: Local::now(),
+        };
+        // Repeating the newest toast just refreshes it instead of stacking duplicates
+        self.toasts
+            .retain(|t| !(t.text == message.text && t.severity == message.severity));
+        self.toasts.push_back(message.clone());
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.history.push_back(message);
+        while self.history.len() > MESSAGE_HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+    }
+
+    /// Messages currently on screen, oldest first
+    pub fn toasts(&self) -> impl Iterator<Item = &Message> {
+        self.toasts.iter()
+    }
+
+    /// Every recorded message, oldest first
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &Message> {
+        self.history.iter()
+    }
+
+    pub fn dismiss(&mut self, index: usize) {
+        self.toasts.remove(index);
+    }
+
+    /// Remove on-screen toasts of one severity (the history keeps them)
+    pub fn dismiss_severity(&mut self, severity: Severity) {
+        self.toasts.retain(|t| t.severity != severity);
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    pub fn clear_expired(&mut self, timeout_secs: u64) {
+        self.toasts
+            .retain(|t| t.raised.elapsed().as_secs() < t.severity.timeout_secs(timeout_secs));
+    }
+}
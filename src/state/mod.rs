@@ -1,21 +1,57 @@
+pub mod broken_links;
+pub mod cleanup;
 pub mod clipboard;
+pub mod completion;
+pub mod computer;
+pub mod dirdiff;
+pub mod dry_run;
 pub mod entries;
+pub mod jobs;
+pub mod labels;
+pub mod listing_cache;
+pub mod messages;
 pub mod mode;
 pub mod mode_state;
+pub mod name_audit;
 pub mod navigation;
+pub mod peek;
+pub mod properties;
+pub mod rename;
 pub mod search;
 pub mod selection;
+pub mod settings;
+pub mod snapshot;
 pub mod sort;
 pub mod tabs;
 pub mod ui;
 
-pub use clipboard::ClipboardOp;
+pub use broken_links::{BrokenLink, LinkKind};
+pub use cleanup::CleanupItem;
+pub use clipboard::{ClipboardOp, ConflictPolicy, DropAction};
+pub use completion::{
+    expand_braces, expand_name_template, near_matches, NameCompletion, NAME_TEMPLATES,
+};
+pub use computer::{recent_locations, CardKind, ComputerCard};
+pub use dirdiff::{DiffKind, DirDiffEntry};
+pub use dry_run::{DryRunReport, PlannedChange};
 pub use entries::EntryState;
+pub use jobs::{BatchJob, ExitChoice, HookCheck, JobErrorAction, JobKind, JobStart, QueuedJob};
+pub use labels::LabelColor;
+pub use listing_cache::{listing_age, ListingCache};
+pub use messages::{MessageLog, Severity};
 pub use mode::AppMode;
 pub use mode_state::ModeState;
+pub use name_audit::{name_problems, portable_name, NameIssue};
 pub use navigation::NavigationState;
-pub use search::{SearchOptions, SearchResult};
+pub use peek::PeekState;
+pub use properties::PropertiesState;
+pub use rename::{photo_names, NameTransform};
+pub use search::{
+    distinct_roots, file_hit_counts, rank_by_file, root_of, SearchOptions, SearchResult,
+};
 pub use selection::SelectionState;
-pub use sort::{SortBy, SortOptions, SortOrder};
-pub use tabs::TabsManager;
+pub use settings::{SettingsState, SettingsTab};
+pub use snapshot::{DirSnapshot, SnapshotChange};
+pub use sort::{group_starts, SortBy, SortOptions, SortOrder};
+pub use tabs::{bounded_history, mirror_path, prune_history, TabState, TabsManager};
 pub use ui::UIState;
@@ -1,4 +1,13 @@
+use super::broken_links::BrokenLink;
+use super::cleanup::CleanupItem;
+use super::computer::ComputerCard;
+use super::dirdiff::DirDiffEntry;
+use super::dry_run::DryRunReport;
+use super::jobs::{JobKind, JobStart};
+use super::name_audit::NameIssue;
 use super::search::SearchResult;
+use crate::io::textdiff::DiffLine;
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -10,11 +19,22 @@ pub enum AppMode {
     Help,
     Rename,
     DeleteConfirm,
+    Diagnostics,
+    Properties,
+    /// The settings window; the draft lives in `Heike::settings`
+    Settings,
+    /// Cache and trash sizes with cleanup actions
+    Maintenance {
+        /// The Empty button was pressed once and now asks for confirmation
+        confirm_empty: bool,
+    },
     SearchInput,
     SearchResults {
         query: String,
         results: Vec<SearchResult>,
         selected_index: usize,
+        /// Files marked with Space; all hits in a file share its mark
+        marked: HashSet<PathBuf>,
     },
     BulkRename {
         // Original paths and names for the bulk rename operation
@@ -23,5 +43,85 @@ pub enum AppMode {
         edit_buffer: String,
         // Cursor position in the text editor
         cursor_line: usize,
+        // Suffix "(1)", "(2)", … instead of failing when a target name is taken
+        auto_suffix: bool,
+        // Pattern of the "Photo date" preset, starting from `file_ops.photo_pattern`
+        photo_pattern: String,
+    },
+    /// A batch job is paused on an item that failed; offer retry / skip / abort
+    JobError {
+        path: PathBuf,
+        error: String,
+    },
+    /// The window was closed with jobs pending; pick an `ExitChoice`
+    ExitConfirm,
+    /// Some pasted items collide with existing ones; pick a `ConflictPolicy`
+    PasteConflict {
+        /// Names that already exist in the destination
+        names: Vec<String>,
+        /// Save the chosen policy as `file_ops.conflict_policy`
+        remember: bool,
+        /// Names where both the existing and the pasted file are text, offered a Compare
+        comparable: HashSet<String>,
+        /// Name being compared and its diff, existing file against pasted one
+        diff: Option<(String, Result<Vec<DiffLine>, String>)>,
+    },
+    /// A before hook asks for `phrase` to be typed into the input popup before the job starts
+    HookConfirm {
+        kind: JobKind,
+        items: Vec<PathBuf>,
+        start: JobStart,
+        phrase: String,
+    },
+    /// Confirm opening a selection larger than `open.confirm_threshold`
+    OpenConfirm {
+        paths: Vec<PathBuf>,
+    },
+    /// Pick one application for a set of files; the command is typed into the input popup
+    OpenWith {
+        paths: Vec<PathBuf>,
+        /// Record the command as the association for the files' shared extension
+        remember: bool,
+    },
+    /// Overview of drives, places and recent locations in place of the listing
+    Computer {
+        cards: Vec<ComputerCard>,
+        selected_index: usize,
+        /// Drives are still being listed on the worker
+        loading_volumes: bool,
+    },
+    /// What a batch operation would have changed while dry run is on
+    DryRun {
+        report: DryRunReport,
+        /// The dialog the operation came from, restored on close (bulk rename keeps its edits)
+        return_to: Option<Box<AppMode>>,
+    },
+    DirDiff {
+        // Current directory (left side of the comparison)
+        left: PathBuf,
+        // Directory compared against (right side)
+        right: PathBuf,
+        entries: Vec<DirDiffEntry>,
+        selected_index: usize,
+    },
+    /// Empty directories and zero-byte files under `root`, ticked ones go to the trash
+    Cleanup {
+        root: PathBuf,
+        items: Vec<CleanupItem>,
+        selected_index: usize,
+    },
+    /// Broken symlinks and dangling shortcuts under `root`
+    BrokenLinks {
+        root: PathBuf,
+        links: Vec<BrokenLink>,
+        selected_index: usize,
+        /// New target being typed for the selected link
+        retarget: Option<String>,
+    },
+    /// Names under `root` that Windows/SMB would reject, with their portable renames
+    NameAudit {
+        root: PathBuf,
+        issues: Vec<NameIssue>,
+        selected_index: usize,
     },
 }
@@ -1,10 +1,13 @@
 // Mode state - application modal and input state
-use crate::state::AppMode;
+use crate::state::{AppMode, NameCompletion};
 
 pub struct ModeState {
     pub mode: AppMode,
     pub command_buffer: String,
     pub focus_input: bool,
+    /// Move the input cursor to the end, after the buffer was replaced by a completion
+    pub cursor_to_end: bool,
+    pub completion: NameCompletion,
 }
 
 impl ModeState {
@@ -13,10 +16,13 @@ impl ModeState {
             mode: AppMode::Normal,
             command_buffer: String::new(),
             focus_input: false,
+            cursor_to_end: false,
+            completion: NameCompletion::default(),
         }
     }
 
     pub fn set_mode(&mut self, mode: AppMode) {
         self.mode = mode;
+        self.completion.reset();
     }
 }
@@ -0,0 +1,100 @@
+// Names Windows and SMB shares reject, and portable replacements for them
+use super::rename::remove_illegal;
+use std::path::PathBuf;
+
+/// Device names Windows reserves, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Longest name NTFS and SMB accept, in UTF-16 units
+pub const MAX_PORTABLE_NAME: usize = 255;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NameIssue {
+    pub path: PathBuf,
+    pub problems: Vec<&'static str>,
+    /// Portable name the sanitizing rename gives the item
+    pub suggestion: String,
+}
+
+fn is_reserved(name: &str) -> bool {
+    let device = name.split('.').next().unwrap_or(name).trim_end();
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(device))
+}
+
+/// Why Windows would refuse `name`; empty when it is portable
+pub fn name_problems(name: &str) -> Vec<&'static str> {
+    let mut problems = Vec::new();
+    if name
+        .chars()
+        .any(|ch| matches!(ch, '<' | '>' | ':' | '"' | '\\' | '|' | '?' | '*'))
+    {
+        problems.push("forbidden character");
+    }
+    if name.chars().any(char::is_control) {
+        problems.push("control character");
+    }
+    if name.ends_with(['.', ' ']) {
+        problems.push("ends in a dot or space");
+    }
+    if is_reserved(name) {
+        problems.push("reserved device name");
+    }
+    if name.encode_utf16().count() > MAX_PORTABLE_NAME {
+        problems.push("longer than 255 characters");
+    }
+    problems
+}
+
+/// `name` made acceptable to Windows: forbidden and control characters dropped,
+/// trailing dots and spaces trimmed, reserved device names suffixed with `_` and
+/// over-long names shortened, keeping the extension where possible
+pub fn portable_name(name: &str) -> String {
+    let mut portable = remove_illegal(name);
+    if portable.is_empty() {
+        portable.push('_');
+    }
+    if is_reserved(&portable) {
+        let device_len = portable.split('.').next().unwrap_or("").len();
+        portable.insert(device_len, '_');
+    }
+    if portable.encode_utf16().count() > MAX_PORTABLE_NAME {
+        let (stem, extension) = match portable.rfind('.') {
+            Some(dot) if dot > 0 && portable.len() - dot <= 16 => portable.split_at(dot),
+            _ => (portable.as_str(), ""),
+        };
+        let budget = MAX_PORTABLE_NAME - extension.encode_utf16().count();
+        let mut used = 0;
+        let stem: String = stem
+            .chars()
+            .take_while(|ch| {
+                used += ch.len_utf16();
+                used <= budget
+            })
+            .collect();
+        portable = remove_illegal(&format!("{}{}", stem.trim_end(), extension));
+    }
+    portable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_names_are_flagged_and_made_portable() {
+        assert!(name_problems("report.txt").is_empty());
+        assert_eq!(name_problems("con.txt"), vec!["reserved device name"]);
+        assert_eq!(portable_name("con.txt"), "con_.txt");
+        assert_eq!(portable_name("AUX"), "AUX_");
+        assert_eq!(portable_name("notes: draft?.md"), "notes draft.md");
+        assert_eq!(portable_name("trailing. "), "trailing");
+        assert_eq!(portable_name("???"), "_");
+        let long = format!("{}.txt", "x".repeat(300));
+        assert_eq!(portable_name(&long), format!("{}.txt", "x".repeat(251)));
+    }
+}
@@ -0,0 +1,18 @@
+// A sibling directory shown in the listing pane without navigating to it
+use crate::entry::FileEntry;
+use std::path::PathBuf;
+
+pub struct PeekState {
+    pub path: PathBuf,
+    /// `None` while the worker reads the directory
+    pub entries: Option<Result<Vec<FileEntry>, String>>,
+}
+
+impl PeekState {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            entries: None,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// Properties dialog state
+use crate::entry::FileEntry;
+use crate::platform::FilesystemInfo;
+
+pub struct PropertiesState {
+    pub entry: FileEntry,
+    /// Extended attributes as (name, printable value)
+    pub xattrs: Vec<(String, String)>,
+    /// SELinux or AppArmor label, if the file carries one
+    pub security_context: Option<String>,
+    /// The filesystem the item lives on
+    pub filesystem: Option<FilesystemInfo>,
+    /// Input buffers for adding a user.* attribute
+    pub new_xattr_name: String,
+    pub new_xattr_value: String,
+}
+
+impl PropertiesState {
+    pub fn new(entry: FileEntry) -> Self {
+        Self {
+            entry,
+            xattrs: Vec::new(),
+            security_context: None,
+            filesystem: None,
+            new_xattr_name: String::new(),
+            new_xattr_value: String::new(),
+        }
+    }
+}
@@ -0,0 +1,255 @@
+// One-click name transforms for the bulk rename editor
+use chrono::NaiveDateTime;
+
+/// A rewrite applied to every line of the bulk rename buffer
+///
+/// Case, separator and numbering transforms change the stem only, so extensions
+/// survive them; accent and illegal-character cleanup covers the whole name.
+/// Dotfiles (`.bashrc`) are treated as all stem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameTransform {
+    Lowercase,
+    Uppercase,
+    TitleCase,
+    SpacesToUnderscores,
+    SpacesToDashes,
+    SeparatorsToSpaces,
+    StripDiacritics,
+    RemoveIllegal,
+    Renumber,
+}
+
+impl NameTransform {
+    pub const ALL: [NameTransform; 9] = [
+        NameTransform::Lowercase,
+        NameTransform::Uppercase,
+        NameTransform::TitleCase,
+        NameTransform::SpacesToUnderscores,
+        NameTransform::SpacesToDashes,
+        NameTransform::SeparatorsToSpaces,
+        NameTransform::StripDiacritics,
+        NameTransform::RemoveIllegal,
+        NameTransform::Renumber,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NameTransform::Lowercase => "lowercase",
+            NameTransform::Uppercase => "UPPERCASE",
+            NameTransform::TitleCase => "Title Case",
+            NameTransform::SpacesToUnderscores => "spaces → _",
+            NameTransform::SpacesToDashes => "spaces → -",
+            NameTransform::SeparatorsToSpaces => "_ / - → spaces",
+            NameTransform::StripDiacritics => "Strip accents",
+            NameTransform::RemoveIllegal => "Remove illegal chars",
+            NameTransform::Renumber => "Renumber",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            NameTransform::StripDiacritics => "é → e, ß → ss, ø → o …",
+            NameTransform::RemoveIllegal => {
+                "Drop < > : \" / \\ | ? * and control characters, trim trailing dots and spaces"
+            }
+            NameTransform::Renumber => {
+                "Prefix 01_, 02_, … in line order, replacing an existing number prefix"
+            }
+            _ => "Applies to the name, not the extension",
+        }
+    }
+
+    /// Rewrite every line of a bulk rename buffer
+    pub fn apply(self, buffer: &str) -> String {
+        let lines: Vec<&str> = buffer.lines().collect();
+        let width = lines.len().to_string().len().max(2);
+        lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let (stem, extension) = split_extension(line);
+                let stem = match self {
+                    NameTransform::Lowercase => stem.to_lowercase(),
+                    NameTransform::Uppercase => stem.to_uppercase(),
+                    NameTransform::TitleCase => title_case(stem),
+                    NameTransform::SpacesToUnderscores => stem.replace(' ', "_"),
+                    NameTransform::SpacesToDashes => stem.replace(' ', "-"),
+                    NameTransform::SeparatorsToSpaces => stem.replace(['_', '-'], " "),
+                    NameTransform::StripDiacritics => strip_diacritics(stem),
+                    NameTransform::RemoveIllegal => remove_illegal(stem),
+                    NameTransform::Renumber => {
+                        format!("{:0width$}_{}", index + 1, strip_number_prefix(stem))
+                    }
+                };
+                let extension = match self {
+                    NameTransform::StripDiacritics => strip_diacritics(extension),
+                    NameTransform::RemoveIllegal => remove_illegal(extension),
+                    _ => extension.to_string(),
+                };
+                format!("{}{}", stem, extension)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Rename photos after their capture `times`, given in line order
+///
+/// `{date}` becomes YYYYMMDD and `{time}` HHMMSS; `{seq}` numbers the photos of each
+/// day 001, 002, … in shooting order, so bursts within one second stay apart.
+/// Every line keeps its extension.
+pub fn photo_names(buffer: &str, times: &[NaiveDateTime], pattern: &str) -> String {
+    let lines: Vec<&str> = buffer.lines().collect();
+    let mut order: Vec<usize> = (0..lines.len().min(times.len())).collect();
+    order.sort_by_key(|&index| times[index]);
+    let mut seq = vec![0; order.len()];
+    let mut day_count = 0;
+    let mut day = None;
+    for &index in &order {
+        let date = times[index].date();
+        if day != Some(date) {
+            day = Some(date);
+            day_count = 0;
+        }
+        day_count += 1;
+        seq[index] = day_count;
+    }
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let Some(time) = times.get(index) else {
+                return line.to_string();
+            };
+            let (_, extension) = split_extension(line);
+            let stem = pattern
+                .replace("{date}", &time.format("%Y%m%d").to_string())
+                .replace("{time}", &time.format("%H%M%S").to_string())
+                .replace("{seq}", &format!("{:03}", seq[index]));
+            format!("{}{}", stem, extension)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Split `name.ext` into `("name", ".ext")`; dotfiles and extensionless names keep everything in the stem
+fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(0) | None => (name, ""),
+        Some(dot) => name.split_at(dot),
+    }
+}
+
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word_start = true;
+    for ch in text.chars() {
+        if word_start {
+            result.extend(ch.to_uppercase());
+        } else {
+            result.extend(ch.to_lowercase());
+        }
+        word_start = ch.is_whitespace() || matches!(ch, '_' | '-' | '.' | '(' | '[');
+    }
+    result
+}
+
+/// Fold accented Latin letters to ASCII
+fn strip_diacritics(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        let lower = ch.to_lowercase().next().unwrap_or(ch);
+        let folded = match lower {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+            'æ' => "ae",
+            'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+            'ď' | 'đ' | 'ð' => "d",
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+            'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+            'ĥ' | 'ħ' => "h",
+            'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+            'ĵ' => "j",
+            'ķ' => "k",
+            'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+            'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+            'œ' => "oe",
+            'ŕ' | 'ŗ' | 'ř' => "r",
+            'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => "s",
+            'ß' => "ss",
+            'ţ' | 'ť' | 'ŧ' | 'ț' => "t",
+            'þ' => "th",
+            'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+            'ŵ' => "w",
+            'ý' | 'ÿ' | 'ŷ' => "y",
+            'ź' | 'ż' | 'ž' => "z",
+            _ => {
+                result.push(ch);
+                continue;
+            }
+        };
+        if ch.is_uppercase() {
+            // Keep the case of the first letter: Æ → Ae, Ø → O
+            let mut chars = folded.chars();
+            if let Some(first) = chars.next() {
+                result.extend(first.to_uppercase());
+                result.push_str(chars.as_str());
+            }
+        } else {
+            result.push_str(folded);
+        }
+    }
+    result
+}
+
+/// Remove characters that are invalid in file names on any supported platform
+pub(super) fn remove_illegal(text: &str) -> String {
+    let cleaned: String = text
+        .chars()
+        .filter(|ch| {
+            !ch.is_control() && !matches!(ch, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*')
+        })
+        .collect();
+    // Windows rejects names ending in a dot or space
+    cleaned.trim_end_matches(['.', ' ']).to_string()
+}
+
+/// Drop a leading `12_`, `12-`, `12 ` or `12.` so renumbering can be repeated
+fn strip_number_prefix(stem: &str) -> &str {
+    let digits = stem.len() - stem.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return stem;
+    }
+    let rest = &stem[digits..];
+    match rest.chars().next() {
+        Some('_' | '-' | ' ' | '.') if rest.len() > 1 => &rest[1..],
+        _ => stem,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn photo_names_number_each_day_in_shooting_order() {
+        let at = |day, hour, min| {
+            NaiveDate::from_ymd_opt(2024, 5, day)
+                .unwrap()
+                .and_hms_opt(hour, min, 0)
+                .unwrap()
+        };
+        // Listed by name, shot in a different order; the last one a day later
+        let buffer = "DSC_0003.JPG\nDSC_0001.jpg\nDSC_0002.jpg\nclip.mov";
+        let times = [at(12, 9, 30), at(12, 8, 0), at(12, 8, 0), at(13, 7, 5)];
+        assert_eq!(
+            photo_names(buffer, &times, "IMG_{date}_{seq}"),
+            "IMG_20240512_003.JPG\nIMG_20240512_001.jpg\nIMG_20240512_002.jpg\nIMG_20240513_001.mov"
+        );
+        assert_eq!(
+            photo_names("a.jpg", &times[..1], "{date}-{time}"),
+            "20240512-093000.jpg"
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct SearchResult {
@@ -8,6 +9,8 @@ pub struct SearchResult {
     pub line_content: String,
     pub match_start: usize,
     pub match_end: usize,
+    /// Member name when the match is inside an archive (`file_path` is the archive)
+    pub archive_member: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -17,7 +20,15 @@ pub struct SearchOptions {
     pub search_hidden: bool,
     pub search_pdfs: bool,
     pub search_archives: bool,
+    /// Decompressed bytes read per archive before the rest of it is skipped
+    pub max_archive_bytes: u64,
     pub max_results: usize,
+    /// Matches kept per file (0 keeps all), so one generated file can't fill the results
+    pub max_per_file: usize,
+    /// List the files with the most matches first instead of in walk order
+    pub rank_by_matches: bool,
+    /// Folder names not searched, such as node_modules (`ui.build_dirs` in the config)
+    pub skip_dirs: Vec<String>,
 }
 
 impl Default for SearchOptions {
@@ -28,7 +39,123 @@ impl Default for SearchOptions {
             search_hidden: false,
             search_pdfs: true,
             search_archives: true,
+            max_archive_bytes: 50 * 1024 * 1024,
             max_results: 1000,
+            max_per_file: 100,
+            rank_by_matches: false,
+            skip_dirs: Vec::new(),
         }
     }
 }
+
+/// Folders to search, without repeats or folders inside another one, which the walk of
+/// that one already covers; order is kept
+pub fn distinct_roots(roots: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut distinct: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        if distinct.iter().any(|kept| root.starts_with(kept)) {
+            continue;
+        }
+        distinct.retain(|kept| !kept.starts_with(&root));
+        distinct.push(root);
+    }
+    distinct
+}
+
+/// Which of the searched roots `path` was found under
+pub fn root_of<'a>(path: &Path, roots: &'a [PathBuf]) -> Option<&'a PathBuf> {
+    roots.iter().find(|root| path.starts_with(root))
+}
+
+/// Number of hits in each file (an archive counts as one file)
+pub fn file_hit_counts(results: &[SearchResult]) -> HashMap<PathBuf, usize> {
+    let mut counts = HashMap::new();
+    for result in results {
+        *counts.entry(result.file_path.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Reorder hits so files with more matches come first; each file's hits stay together
+/// and in line order, and files with equal counts keep their walk order
+pub fn rank_by_file(results: &mut [SearchResult]) {
+    let counts = file_hit_counts(results);
+    results.sort_by_key(|result| std::cmp::Reverse(counts[&result.file_path]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_and_repeated_roots_are_searched_once() {
+        let roots = distinct_roots(
+            [
+                "/work/docs",
+                "/work",
+                "/home",
+                "/work",
+                "/home/me/notes",
+                "/workshop",
+            ]
+            .map(PathBuf::from)
+            .to_vec(),
+        );
+        assert_eq!(
+            roots,
+            [
+                Path::new("/work"),
+                Path::new("/home"),
+                Path::new("/workshop")
+            ]
+        );
+
+        let hit = Path::new("/workshop/plan.txt");
+        assert_eq!(
+            root_of(hit, &roots).map(PathBuf::as_path),
+            Some(Path::new("/workshop"))
+        );
+        assert_eq!(root_of(Path::new("/tmp/x"), &roots), None);
+    }
+
+    #[test]
+    fn files_with_more_hits_rank_first_in_line_order() {
+        let hit = |file: &str, line_number| SearchResult {
+            file_path: PathBuf::from(file),
+            file_name: file.to_string(),
+            line_number,
+            line_content: String::new(),
+            match_start: 0,
+            match_end: 0,
+            archive_member: None,
+        };
+        let mut results = vec![
+            hit("a", 1),
+            hit("b", 1),
+            hit("b", 2),
+            hit("b", 3),
+            hit("c", 1),
+            hit("d", 4),
+            hit("d", 9),
+        ];
+        assert_eq!(file_hit_counts(&results)[Path::new("b")], 3);
+
+        rank_by_file(&mut results);
+        let order: Vec<(&str, usize)> = results
+            .iter()
+            .map(|r| (r.file_name.as_str(), r.line_number))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("b", 1),
+                ("b", 2),
+                ("b", 3),
+                ("d", 4),
+                ("d", 9),
+                ("a", 1),
+                ("c", 1)
+            ]
+        );
+    }
+}
@@ -9,7 +9,6 @@ pub struct SelectionState {
     pub directory_selections: HashMap<PathBuf, usize>,
     pub last_selection_change: Instant,
     pub disable_autoscroll: bool,
-    pub last_g_press: Option<Instant>,
 }
 
 impl SelectionState {
@@ -21,7 +20,6 @@ impl SelectionState {
             directory_selections: HashMap::new(),
             last_selection_change: Instant::now(),
             disable_autoscroll: false,
-            last_g_press: None,
         }
     }
 }
@@ -0,0 +1,61 @@
+// Settings window state - a draft of the config, edited until it is saved
+use crate::config::Config;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsTab {
+    Appearance,
+    Behavior,
+    Previews,
+    Bookmarks,
+    Keybindings,
+    Integrations,
+}
+
+impl SettingsTab {
+    pub const ALL: [SettingsTab; 6] = [
+        SettingsTab::Appearance,
+        SettingsTab::Behavior,
+        SettingsTab::Previews,
+        SettingsTab::Bookmarks,
+        SettingsTab::Keybindings,
+        SettingsTab::Integrations,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingsTab::Appearance => "Appearance",
+            SettingsTab::Behavior => "Behavior",
+            SettingsTab::Previews => "Previews",
+            SettingsTab::Bookmarks => "Bookmarks",
+            SettingsTab::Keybindings => "Keybindings",
+            SettingsTab::Integrations => "Integrations",
+        }
+    }
+}
+
+pub struct SettingsState {
+    pub draft: Config,
+    pub tab: SettingsTab,
+    /// Input buffers (key, value) for the rows that add a bookmark, alias or association
+    pub new_bookmark: (String, String),
+    pub new_alias: (String, String),
+    pub new_association: (String, String),
+    /// Input buffer for adding a watched folder
+    pub new_watch: String,
+    /// Input buffer for adding a skipped build folder name
+    pub new_build_dir: String,
+}
+
+impl SettingsState {
+    pub fn new(draft: Config) -> Self {
+        Self {
+            draft,
+            tab: SettingsTab::Appearance,
+            new_bookmark: Default::default(),
+            new_alias: Default::default(),
+            new_association: Default::default(),
+            new_watch: String::new(),
+            new_build_dir: String::new(),
+        }
+    }
+}
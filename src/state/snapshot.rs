@@ -0,0 +1,109 @@
+// Session-only directory snapshots, compared later against what the directory holds now
+use crate::entry::FileEntry;
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotChange {
+    /// Not in the snapshot
+    Added,
+    /// Size, modification time or type differ from the snapshot
+    Changed,
+}
+
+#[derive(Clone, Debug)]
+struct SnapshotEntry {
+    is_dir: bool,
+    size: u64,
+    modified: SystemTime,
+}
+
+impl SnapshotEntry {
+    fn of(entry: &FileEntry) -> Self {
+        Self {
+            is_dir: entry.is_dir,
+            size: entry.size,
+            modified: entry.modified,
+        }
+    }
+}
+
+/// Names, sizes and modification times of one directory's entries at some moment
+#[derive(Clone, Debug)]
+pub struct DirSnapshot {
+    pub taken: DateTime<Local>,
+    entries: HashMap<String, SnapshotEntry>,
+    /// Whether the listing marks entries that differ from the snapshot
+    pub highlight: bool,
+}
+
+impl DirSnapshot {
+    /// Record `entries`, which should include hidden ones
+    pub fn capture(entries: &[FileEntry]) -> Self {
+        Self {
+            taken: Local::now(),
+            entries: entries
+                .iter()
+                .map(|e| (e.name.clone(), SnapshotEntry::of(e)))
+                .collect(),
+            highlight: false,
+        }
+    }
+
+    /// How `entry` differs from its recorded state, `None` if unchanged
+    pub fn change(&self, entry: &FileEntry) -> Option<SnapshotChange> {
+        match self.entries.get(&entry.name) {
+            None => Some(SnapshotChange::Added),
+            Some(old) => {
+                let now = SnapshotEntry::of(entry);
+                (old.is_dir != now.is_dir || old.size != now.size || old.modified != now.modified)
+                    .then_some(SnapshotChange::Changed)
+            }
+        }
+    }
+
+    /// Names recorded in the snapshot that `entries` no longer has, sorted
+    pub fn removed(&self, entries: &[FileEntry]) -> Vec<String> {
+        let mut removed: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|name| !entries.iter().any(|e| &e.name == *name))
+            .cloned()
+            .collect();
+        removed.sort();
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::directory::read_directory;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn snapshot_reports_added_changed_and_removed_entries() {
+        let dir = TempDir::new().unwrap();
+        for (name, body) in [
+            ("a.txt", "alpha"),
+            ("b.md", "bravo"),
+            ("notes.txt", "notes"),
+        ] {
+            fs::write(dir.path().join(name), body).unwrap();
+        }
+        let snapshot = DirSnapshot::capture(&read_directory(dir.path(), true).unwrap());
+
+        fs::write(dir.path().join("new.txt"), "fresh").unwrap();
+        fs::write(dir.path().join("a.txt"), "a longer body than before").unwrap();
+        fs::remove_file(dir.path().join("notes.txt")).unwrap();
+
+        let now = read_directory(dir.path(), true).unwrap();
+        let change = |name: &str| snapshot.change(now.iter().find(|e| e.name == name).unwrap());
+        assert_eq!(change("new.txt"), Some(SnapshotChange::Added));
+        assert_eq!(change("a.txt"), Some(SnapshotChange::Changed));
+        assert_eq!(change("b.md"), None);
+        assert_eq!(snapshot.removed(&now), vec!["notes.txt"]);
+    }
+}
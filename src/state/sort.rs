@@ -1,10 +1,14 @@
-// Sort options for file listing
+// Sort options for file listing, and the group headings a sorted listing falls into
+use crate::entry::{same_base_letter, FileEntry};
+use chrono::{DateTime, Datelike, Local, NaiveDate};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SortBy {
     Name,
     Size,
     Modified,
+    /// Birth time, falling back to the modification time where it isn't recorded
+    Created,
     Extension,
 }
 
@@ -31,12 +35,49 @@ impl Default for SortOptions {
     }
 }
 
+impl SortBy {
+    /// Parse a config or command name: "name", "size", "modified", "created" or "extension"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(SortBy::Name),
+            "size" => Some(SortBy::Size),
+            "modified" => Some(SortBy::Modified),
+            "created" => Some(SortBy::Created),
+            "extension" => Some(SortBy::Extension),
+            _ => None,
+        }
+    }
+
+    /// Config and command name, the inverse of `from_name`
+    pub fn name(self) -> &'static str {
+        match self {
+            SortBy::Name => "name",
+            SortBy::Size => "size",
+            SortBy::Modified => "modified",
+            SortBy::Created => "created",
+            SortBy::Extension => "extension",
+        }
+    }
+}
+
+impl SortOrder {
+    /// Parse "asc" or "desc"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "asc" => Some(SortOrder::Ascending),
+            "desc" => Some(SortOrder::Descending),
+            _ => None,
+        }
+    }
+}
+
 impl SortOptions {
     pub fn cycle_sort_by(&mut self) {
         self.sort_by = match self.sort_by {
             SortBy::Name => SortBy::Size,
             SortBy::Size => SortBy::Modified,
-            SortBy::Modified => SortBy::Extension,
+            SortBy::Modified => SortBy::Created,
+            SortBy::Created => SortBy::Extension,
             SortBy::Extension => SortBy::Name,
         };
     }
@@ -58,6 +99,7 @@ impl SortOptions {
             SortBy::Name => "Name",
             SortBy::Size => "Size",
             SortBy::Modified => "Modified",
+            SortBy::Created => "Created",
             SortBy::Extension => "Ext",
         };
 
@@ -75,3 +117,138 @@ impl SortOptions {
         }
     }
 }
+
+/// Group headings of a listing sorted by `sort_by`, as (index of the group's first entry,
+/// heading) pairs; empty for sizes, which have no natural groups
+///
+/// Dates fall into "Today", "Yesterday", "This week" (since Monday) and "Older", names
+/// group by first letter (accents and case aside) and extensions by extension. Folders,
+/// which sort apart from files, always form one "Folders" group.
+pub fn group_starts(
+    entries: &[FileEntry],
+    sort_by: SortBy,
+    today: NaiveDate,
+) -> Vec<(usize, String)> {
+    if sort_by == SortBy::Size {
+        return Vec::new();
+    }
+    let week_start = today - chrono::Days::new(today.weekday().num_days_from_monday().into());
+    let label = |entry: &FileEntry| -> String {
+        if entry.is_dir {
+            return "Folders".into();
+        }
+        match sort_by {
+            SortBy::Name => entry
+                .name
+                .chars()
+                .next()
+                .filter(|c| c.is_alphanumeric())
+                .map_or("#".into(), |c| c.to_uppercase().collect()),
+            SortBy::Extension if entry.extension.is_empty() => "No extension".into(),
+            SortBy::Extension => format!(".{}", entry.extension),
+            SortBy::Modified | SortBy::Created => {
+                let time = match sort_by {
+                    SortBy::Created => entry.created.unwrap_or(entry.modified),
+                    _ => entry.modified,
+                };
+                let date = DateTime::<Local>::from(time).date_naive();
+                if date >= today {
+                    "Today".into()
+                } else if Some(date) == today.pred_opt() {
+                    "Yesterday".into()
+                } else if date >= week_start {
+                    "This week".into()
+                } else {
+                    "Older".into()
+                }
+            }
+            SortBy::Size => unreachable!("sizes are not grouped"),
+        }
+    };
+
+    let mut starts: Vec<(usize, String)> = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let heading = label(entry);
+        let same = starts.last().is_some_and(|(_, last)| {
+            *last == heading
+                || (sort_by == SortBy::Name && !entry.is_dir && same_base_letter(last, &heading))
+        });
+        if !same {
+            starts.push((index, heading));
+        }
+    }
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use std::time::SystemTime;
+    use tempfile::TempDir;
+
+    #[test]
+    fn group_headers_follow_dates_letters_and_extensions() {
+        // A Thursday, so Monday and Tuesday are earlier this week
+        let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let at = |days: i64| {
+            let day = today - Duration::days(days);
+            SystemTime::from(
+                Local
+                    .from_local_datetime(&day.and_hms_opt(12, 0, 0).unwrap())
+                    .unwrap(),
+            )
+        };
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        let entry = |name: &str, modified: SystemTime, is_dir: bool| {
+            let mut entry = FileEntry::from_path(dir.path().join("a.txt")).unwrap();
+            entry.name = name.to_string();
+            entry.extension = std::path::Path::new(name)
+                .extension()
+                .map(|e| e.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            entry.modified = modified;
+            entry.created = None;
+            entry.is_dir = is_dir;
+            entry
+        };
+
+        let by_date = [
+            entry("src", at(0), true),
+            entry("a", at(0), false),
+            entry("b", at(1), false),
+            entry("c", at(3), false),
+            entry("d", at(30), false),
+            entry("e", at(40), false),
+        ];
+        assert_eq!(
+            group_starts(&by_date, SortBy::Modified, today),
+            [
+                (0, "Folders".to_string()),
+                (1, "Today".to_string()),
+                (2, "Yesterday".to_string()),
+                (3, "This week".to_string()),
+                (4, "Older".to_string()),
+            ]
+        );
+        assert!(group_starts(&by_date, SortBy::Size, today).is_empty());
+
+        let by_name = [
+            entry("apple.txt", at(0), false),
+            entry("Éclair.md", at(0), false),
+            entry("egg.md", at(0), false),
+            entry("_notes", at(0), false),
+        ];
+        let letters: Vec<String> = group_starts(&by_name, SortBy::Name, today)
+            .into_iter()
+            .map(|(_, heading)| heading)
+            .collect();
+        assert_eq!(letters, ["A", "É", "#"]);
+        let extensions: Vec<String> = group_starts(&by_name, SortBy::Extension, today)
+            .into_iter()
+            .map(|(_, heading)| heading)
+            .collect();
+        assert_eq!(extensions, [".txt", ".md", "No extension"]);
+    }
+}
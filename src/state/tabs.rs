@@ -1,7 +1,8 @@
 // Tabs state management for multiple directory views
 use crate::entry::FileEntry;
+use crate::platform;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 /// State for a single tab (directory view)
 #[derive(Clone)]
@@ -16,6 +17,9 @@ pub struct TabState {
     pub selected_index: Option<usize>,
     pub directory_selections: HashMap<PathBuf, usize>,
     pub pending_selection_path: Option<PathBuf>,
+    /// Staging area: entries gathered with `s` from any directory, to be copied or
+    /// moved somewhere in one batch
+    pub staged: Vec<PathBuf>,
 }
 
 impl TabState {
@@ -37,16 +41,28 @@ impl TabState {
             selected_index: None,
             directory_selections: HashMap::new(),
             pending_selection_path: None,
+            staged: Vec::new(),
         }
     }
 
+    /// A tab reopened on a saved history, whose current entry becomes its directory
+    pub fn with_history(history: Vec<PathBuf>, history_index: usize) -> Option<Self> {
+        let mut tab = Self::new(history.get(history_index)?.clone());
+        tab.history = history;
+        tab.history_index = history_index;
+        tab.update_label();
+        Some(tab)
+    }
+
     pub fn update_label(&mut self) {
-        self.label = self
-            .current_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("/")
-            .to_string();
+        // A drive or share root has no file name; label it `C:\` or `\\server\share`
+        self.label = match self.current_path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => platform::path_segments(&self.current_path)
+                .into_iter()
+                .next()
+                .map_or_else(|| "/".to_string(), |(label, _)| label),
+        };
     }
 }
 
@@ -118,3 +134,108 @@ impl TabsManager {
         self.tabs.len()
     }
 }
+
+/// Most history entries saved per tab
+pub const MAX_SAVED_HISTORY: usize = 50;
+
+/// Cut a tab's history down to `MAX_SAVED_HISTORY` entries around `index`,
+/// keeping mostly the back history; returns the kept entries and the new index
+pub fn bounded_history(history: &[PathBuf], index: usize) -> (Vec<PathBuf>, usize) {
+    if history.is_empty() {
+        return (Vec::new(), 0);
+    }
+    let index = index.min(history.len() - 1);
+    let forward = (history.len() - 1 - index).min(MAX_SAVED_HISTORY / 4);
+    let end = index + 1 + forward;
+    let start = end.saturating_sub(MAX_SAVED_HISTORY);
+    (history[start..end].to_vec(), index - start)
+}
+
+/// Drop history entries that no longer `exists`, merging the neighbours that meet
+/// as a result; the index moves to the nearest earlier survivor (or later one).
+/// Returns `None` when nothing is left.
+pub fn prune_history(
+    history: Vec<PathBuf>,
+    index: usize,
+    exists: impl Fn(&Path) -> bool,
+) -> Option<(Vec<PathBuf>, usize)> {
+    let mut kept: Vec<PathBuf> = Vec::new();
+    let mut new_index = None;
+    for (i, path) in history.into_iter().enumerate() {
+        if exists(&path) && kept.last() != Some(&path) {
+            kept.push(path);
+        }
+        if i <= index && !kept.is_empty() {
+            new_index = Some(kept.len() - 1);
+        }
+    }
+    if kept.is_empty() {
+        return None;
+    }
+    Some((kept, new_index.unwrap_or(0)))
+}
+
+/// Map `source` onto the tree `target` lives in, for side-by-side checkouts.
+///
+/// Each path's root is the shared ancestor plus its first diverging component
+/// (e.g. `~/proj-v1` and `~/proj-v2`); the subpath below the source root is
+/// re-applied under the target root, falling back to the deepest existing parent.
+/// Returns `None` when one path contains the other and there is nothing to map.
+pub fn mirror_path(
+    source: &Path,
+    target: &Path,
+    exists: impl Fn(&Path) -> bool,
+) -> Option<PathBuf> {
+    let source_parts: Vec<Component> = source.components().collect();
+    let target_parts: Vec<Component> = target.components().collect();
+    let common = source_parts
+        .iter()
+        .zip(&target_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common >= source_parts.len() || common >= target_parts.len() {
+        return None;
+    }
+
+    let target_root: PathBuf = target_parts[..=common].iter().collect();
+    let relative: PathBuf = source_parts[common + 1..].iter().collect();
+    let mut candidate = target_root.join(relative);
+    while candidate != target_root && !exists(&candidate) {
+        if !candidate.pop() {
+            break;
+        }
+    }
+    Some(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restored_history_skips_deleted_directories() {
+        let history: Vec<PathBuf> = ["/a", "/gone", "/a", "/b", "/gone2", "/c"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let exists = |path: &Path| !path.to_string_lossy().contains("gone");
+
+        // The current entry was deleted: step back to the nearest survivor, and the
+        // two "/a" entries that now meet collapse into one
+        let (kept, index) = prune_history(history.clone(), 4, exists).unwrap();
+        assert_eq!(kept, ["/a", "/b", "/c"].map(PathBuf::from));
+        assert_eq!(index, 1);
+        // Nothing before it survives: the first later entry takes over
+        let (kept, index) = prune_history(history.clone(), 0, |p| p != Path::new("/a")).unwrap();
+        assert_eq!((kept[index].as_path(), index), (Path::new("/gone"), 0));
+        assert_eq!(prune_history(history, 0, |_| false), None);
+
+        let long: Vec<PathBuf> = (0..200)
+            .map(|i| PathBuf::from(format!("/d{}", i)))
+            .collect();
+        let (kept, index) = bounded_history(&long, 100);
+        assert_eq!(kept.len(), MAX_SAVED_HISTORY);
+        assert_eq!(kept[index], long[100]);
+        assert!(index >= MAX_SAVED_HISTORY / 2, "back history is kept first");
+    }
+}
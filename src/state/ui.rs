@@ -1,63 +1,126 @@
 // UI state - presentation and layout settings
-use crate::state::{SearchOptions, SortOptions};
-use crate::style::Theme;
-use std::time::Instant;
+use crate::state::{MessageLog, SearchOptions, Severity, SortBy, SortOptions};
+use crate::style::{Ellipsis, Theme};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime};
 
 pub struct UIState {
     pub show_hidden: bool,
+    /// List all files below the current directory instead of its children
+    pub flatten: bool,
     pub theme: Theme,
     pub sort_options: SortOptions,
-    pub error_message: Option<(String, Instant)>,
-    pub info_message: Option<(String, Instant)>,
+    /// Sort fields whose listings are split by group header rows
+    pub group_headers: Vec<SortBy>,
+    /// First listing row drawn last frame; its group names the sticky header
+    pub list_top_row: usize,
+    /// How long names are shortened in the name columns and preview header
+    pub name_ellipsis: Ellipsis,
+    /// Query the listing is currently filtered by, empty when unfiltered
+    pub filter_query: String,
+    /// Whether a directory preview is filtered by `filter_query` too
+    pub filter_preview: bool,
+    pub messages: MessageLog,
+    /// Whether the message history window is open
+    pub show_message_history: bool,
+    /// Whether the task center (running and queued jobs) is open
+    pub show_task_center: bool,
+    /// Whether the clipboard panel is docked beside the listing
+    pub show_clipboard: bool,
+    /// Lowest severity listed in the history window
+    pub message_history_filter: Severity,
     pub panel_widths: [f32; 2],
     pub is_loading: bool,
+    /// Batch tools report what they would change instead of changing it
+    pub dry_run: bool,
+    /// When the cached listing on screen was read; `None` once a fresh one has arrived
+    pub stale_listing: Option<Instant>,
     pub search_query: String,
     pub search_options: SearchOptions,
+    /// Search the current folder (it may be left out when other roots are picked)
+    pub search_current: bool,
+    /// Further folders picked in the search dialog, e.g. bookmarks
+    pub search_roots: Vec<PathBuf>,
+    /// Roots of the last search, to tag each hit with its root when there were several
+    pub searched_roots: Vec<PathBuf>,
+    /// Hits per file in the last search, shown as subtotals
+    pub search_file_hits: HashMap<PathBuf, usize>,
+    /// Files of the last search that had more matches than the per-file cap
+    pub search_capped: HashSet<PathBuf>,
+    /// Modification times of the archives with hits, validating their cached member text
+    pub search_archive_mtimes: HashMap<PathBuf, SystemTime>,
+    /// Replacement template (`$1`, `${name}`) previewed against each search hit
+    pub replace_text: String,
     pub search_in_progress: bool,
     pub search_file_count: usize,
     pub search_files_skipped: usize,
     pub search_errors: usize,
     pub last_settings_save: Instant,
+    /// Last key press or typed text, so the which-key overlay waits for a pause
+    pub last_key_press: Instant,
+    /// Visual mode was just entered by key; its keys are listed after a pause
+    pub visual_hint: bool,
 }
 
 impl UIState {
     pub fn new(theme: Theme, sort_options: SortOptions) -> Self {
         Self {
             show_hidden: false,
+            flatten: false,
             theme,
             sort_options,
-            error_message: None,
-            info_message: None,
+            group_headers: Vec::new(),
+            list_top_row: 0,
+            name_ellipsis: Ellipsis::End,
+            filter_query: String::new(),
+            filter_preview: false,
+            messages: MessageLog::default(),
+            show_message_history: false,
+            show_task_center: false,
+            show_clipboard: false,
+            message_history_filter: Severity::Info,
             panel_widths: [200.0, 350.0],
             is_loading: false,
+            dry_run: false,
+            stale_listing: None,
             search_query: String::new(),
             search_options: SearchOptions::default(),
+            search_current: true,
+            search_roots: Vec::new(),
+            searched_roots: Vec::new(),
+            search_file_hits: HashMap::new(),
+            search_capped: HashSet::new(),
+            search_archive_mtimes: HashMap::new(),
+            replace_text: String::new(),
             search_in_progress: false,
             search_file_count: 0,
             search_files_skipped: 0,
             search_errors: 0,
             last_settings_save: Instant::now(),
+            last_key_press: Instant::now(),
+            visual_hint: false,
         }
     }
 
     pub fn set_error(&mut self, message: String) {
-        self.error_message = Some((message, Instant::now()));
+        self.messages.push(Severity::Error, message);
+    }
+
+    pub fn set_warning(&mut self, message: String) {
+        self.messages.push(Severity::Warning, message);
     }
 
     pub fn set_info(&mut self, message: String) {
-        self.info_message = Some((message, Instant::now()));
+        self.messages.push(Severity::Info, message);
+    }
+
+    /// Take error toasts off screen, e.g. once a refresh supersedes them
+    pub fn clear_errors(&mut self) {
+        self.messages.dismiss_severity(Severity::Error);
     }
 
     pub fn clear_expired_messages(&mut self, timeout_secs: u64) {
-        if let Some((_, time)) = &self.error_message {
-            if time.elapsed().as_secs() >= timeout_secs {
-                self.error_message = None;
-            }
-        }
-        if let Some((_, time)) = &self.info_message {
-            if time.elapsed().as_secs() >= timeout_secs {
-                self.info_message = None;
-            }
-        }
+        self.messages.clear_expired(timeout_secs);
     }
 }
@@ -22,8 +22,11 @@ pub const MODAL_WIDTH_RATIO: f32 = 0.6;
 pub const MODAL_HEIGHT_RATIO: f32 = 0.8;
 
 // --- Timing ---
-pub const DOUBLE_PRESS_MS: u64 = 500;
-pub const KEY_SEQUENCE_DELAY_MS: u64 = 10; // Delay between keys in sequences like 'g' + key
+/// How long an unfinished key sequence (`g…`, a count) waits for its next key
+pub const KEY_SEQUENCE_TIMEOUT_MS: u64 = 500;
+/// Pause after a prefix (`g`, `:`, `v`) before its continuations are listed; shorter
+/// than the sequence timeout, so a waiting `g` is still there to be held
+pub const WHICH_KEY_DELAY_MS: u64 = 300;
 pub const MESSAGE_TIMEOUT_SECS: u64 = 5;
 
 // --- Preview limits ---
@@ -41,8 +44,28 @@ pub fn modal_max_height(ctx: &egui::Context) -> f32 {
     height * MODAL_HEIGHT_RATIO
 }
 
+/// Which part of a label too long for its column gives way to `…`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Ellipsis {
+    #[default]
+    End,
+    /// Keep the beginning and the extension, cut in between
+    Middle,
+}
+
+impl Ellipsis {
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "middle" => Ellipsis::Middle,
+            _ => Ellipsis::End,
+        }
+    }
+}
+
 pub fn truncated_label(ui: &mut egui::Ui, text: impl Into<egui::WidgetText>) -> egui::Response {
-    ui.add(egui::Label::new(text).truncate())
+    let (text, full) = elide_graphemes(ui, text.into(), Ellipsis::End);
+    let response = ui.add(egui::Label::new(text).truncate());
+    with_full_text(response, full)
 }
 
 pub fn truncated_label_with_sense(
@@ -50,5 +73,149 @@ pub fn truncated_label_with_sense(
     text: impl Into<egui::WidgetText>,
     sense: egui::Sense,
 ) -> egui::Response {
-    ui.add(egui::Label::new(text).truncate().sense(sense))
+    name_label(ui, text, sense, Ellipsis::End)
+}
+
+/// File name label shortened according to `ui.name_ellipsis`
+pub fn name_label(
+    ui: &mut egui::Ui,
+    text: impl Into<egui::WidgetText>,
+    sense: egui::Sense,
+    ellipsis: Ellipsis,
+) -> egui::Response {
+    let (text, full) = elide_graphemes(ui, text.into(), ellipsis);
+    let response = ui.add(egui::Label::new(text).truncate().sense(sense));
+    with_full_text(response, full)
+}
+
+fn with_full_text(response: egui::Response, full: Option<String>) -> egui::Response {
+    match full {
+        Some(full) => response.on_hover_text(full),
+        None => response,
+    }
+}
+
+/// Longest extension (with its dot, in graphemes) kept whole by the middle ellipsis
+const MAX_KEPT_EXTENSION: usize = 12;
+/// Graphemes of the stem kept before the extension, where numbered names differ
+const KEPT_STEM_TAIL: usize = 4;
+
+/// Shorten `text` to the available width with `…`, cutting only between
+/// grapheme clusters so accents and emoji sequences stay whole
+///
+/// egui's own truncation drops single glyphs, which can strip a combining mark
+/// from its letter. Returns the full text alongside when it had to shorten.
+fn elide_graphemes(
+    ui: &egui::Ui,
+    text: egui::WidgetText,
+    ellipsis: Ellipsis,
+) -> (egui::WidgetText, Option<String>) {
+    use egui::text::LayoutJob;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let max_width = ui.available_width();
+    let job = text.into_layout_job(ui.style(), egui::FontSelection::Default, ui.text_valign());
+    let width = |job: &LayoutJob| ui.fonts_mut(|f| f.layout_job(job.clone())).size().x;
+    if width(&job) <= max_width {
+        return (egui::WidgetText::LayoutJob(job), None);
+    }
+
+    let cuts: Vec<usize> = job.text.grapheme_indices(true).map(|(i, _)| i).collect();
+    // Start of the part kept after the `…`
+    let tail = match ellipsis {
+        Ellipsis::End => job.text.len(),
+        Ellipsis::Middle => {
+            let count = cuts.len();
+            let dot = job
+                .text
+                .rfind('.')
+                .and_then(|dot| cuts.iter().position(|&c| c == dot))
+                .filter(|&idx| idx > 0 && count - idx <= MAX_KEPT_EXTENSION);
+            let keep = match dot {
+                Some(idx) => (count - idx + KEPT_STEM_TAIL).min(count - idx + idx / 2),
+                None => (count / 3).min(KEPT_STEM_TAIL * 2),
+            };
+            cuts.get(count - keep).copied().unwrap_or(job.text.len())
+        }
+    };
+
+    // Largest grapheme boundary whose prefix, the ellipsis and the tail still fit
+    let heads: Vec<usize> = cuts.iter().copied().filter(|&c| c < tail).collect();
+    let (mut low, mut high) = (0, heads.len());
+    while low + 1 < high {
+        let mid = (low + high) / 2;
+        if width(&splice(&job, heads[mid], tail)) <= max_width {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    let full = job.text.clone();
+    let head = heads.get(low).copied().unwrap_or(0);
+    (splice(&job, head, tail).into(), Some(full))
+}
+
+/// `job` with the text between `head` and `tail` replaced by `…`, keeping each
+/// section's formatting
+fn splice(job: &egui::text::LayoutJob, head: usize, tail: usize) -> egui::text::LayoutJob {
+    let head_text = job.text[..head].trim_end();
+    let mut text = format!("{}…", head_text);
+    let tail_at = text.len();
+    text.push_str(&job.text[tail..]);
+
+    // Copy the formatting of `range`, moved by `shift` bytes
+    let keep = |range: std::ops::Range<usize>, shift: isize| {
+        job.sections.iter().filter_map(move |section| {
+            let start = section.byte_range.start.max(range.start);
+            let end = section.byte_range.end.min(range.end);
+            (start < end).then(|| egui::text::LayoutSection {
+                byte_range: start.saturating_add_signed(shift)..end.saturating_add_signed(shift),
+                ..section.clone()
+            })
+        })
+    };
+    let mut sections: Vec<_> = keep(0..head_text.len(), 0).collect();
+    let format = job
+        .sections
+        .iter()
+        .find(|s| s.byte_range.contains(&head.saturating_sub(1)))
+        .or(job.sections.first())
+        .map(|s| s.format.clone())
+        .unwrap_or_default();
+    sections.push(egui::text::LayoutSection {
+        leading_space: 0.0,
+        byte_range: head_text.len()..tail_at,
+        format,
+    });
+    sections.extend(keep(tail..job.text.len(), tail_at as isize - tail as isize));
+
+    egui::text::LayoutJob {
+        text,
+        sections,
+        ..job.clone()
+    }
+}
+
+// --- Messages ---
+pub fn severity_color(severity: crate::state::Severity) -> egui::Color32 {
+    use crate::state::Severity;
+    match severity {
+        Severity::Info => egui::Color32::from_rgb(100, 200, 100),
+        Severity::Warning => egui::Color32::from_rgb(255, 180, 50),
+        Severity::Error => egui::Color32::from_rgb(230, 90, 90),
+    }
+}
+
+// --- Labels ---
+pub fn label_color(label: crate::state::LabelColor) -> egui::Color32 {
+    use crate::state::LabelColor;
+    match label {
+        LabelColor::Red => egui::Color32::from_rgb(255, 95, 87),
+        LabelColor::Orange => egui::Color32::from_rgb(255, 165, 50),
+        LabelColor::Yellow => egui::Color32::from_rgb(255, 214, 60),
+        LabelColor::Green => egui::Color32::from_rgb(80, 200, 100),
+        LabelColor::Blue => egui::Color32::from_rgb(60, 150, 255),
+        LabelColor::Purple => egui::Color32::from_rgb(190, 120, 230),
+        LabelColor::Gray => egui::Color32::from_rgb(150, 150, 150),
+    }
 }
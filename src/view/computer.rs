@@ -0,0 +1,119 @@
+// Computer page: drives with capacity bars, places and recent locations as cards
+use crate::app::Heike;
+use crate::state::{AppMode, ComputerCard};
+use crate::style;
+use eframe::egui;
+
+/// Width of one card; cards wrap to as many columns as fit
+const CARD_WIDTH: f32 = 240.0;
+
+/// Share of a drive in use above which its bar turns red
+const NEARLY_FULL: f32 = 0.9;
+
+impl Heike {
+    pub(crate) fn render_computer(&mut self, ctx: &egui::Context) {
+        let AppMode::Computer {
+            cards,
+            selected_index,
+            loading_volumes,
+        } = &self.mode.mode
+        else {
+            return;
+        };
+        let mut open = None;
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                ui.heading("🖥 Computer");
+                ui.label(
+                    egui::RichText::new("hjkl/arrows: move  Enter: open  Esc: back")
+                        .weak()
+                        .italics(),
+                );
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    let mut start = 0;
+                    // Cards arrive grouped by kind; lay out each group under its heading
+                    while start < cards.len() {
+                        let heading = cards[start].kind.section();
+                        let end = cards[start..]
+                            .iter()
+                            .position(|card| card.kind.section() != heading)
+                            .map_or(cards.len(), |len| start + len);
+                        if start > 0 {
+                            ui.add_space(8.0);
+                        }
+                        ui.heading(heading);
+                        ui.horizontal_wrapped(|ui| {
+                            for (index, card) in cards.iter().enumerate().take(end).skip(start) {
+                                if card_widget(ui, card, index == *selected_index).clicked() {
+                                    open = Some(index);
+                                }
+                            }
+                        });
+                        start = end;
+                    }
+                    if *loading_volumes {
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(egui::RichText::new("Listing drives…").weak());
+                        });
+                    }
+                });
+        });
+
+        if let Some(index) = open {
+            self.open_computer_card(index);
+        }
+    }
+}
+
+fn card_widget(ui: &mut egui::Ui, card: &ComputerCard, selected: bool) -> egui::Response {
+    let visuals = ui.visuals();
+    let stroke = if selected {
+        visuals.selection.stroke
+    } else {
+        visuals.widgets.noninteractive.bg_stroke
+    };
+    let response = egui::Frame::group(ui.style())
+        .stroke(stroke)
+        .show(ui, |ui| {
+            ui.set_width(CARD_WIDTH);
+            ui.horizontal(|ui| {
+                ui.label(card.kind.icon());
+                ui.add(egui::Label::new(egui::RichText::new(&card.title).strong()).truncate());
+            });
+            style::truncated_label(
+                ui,
+                egui::RichText::new(card.path.display().to_string()).weak(),
+            );
+            if let Some((total, available)) = card.space.filter(|(total, _)| *total > 0) {
+                let used = 1.0 - available as f32 / total as f32;
+                let mut bar = egui::ProgressBar::new(used)
+                    .desired_width(CARD_WIDTH)
+                    .text(format!(
+                        "{} free of {}",
+                        bytesize::ByteSize(available),
+                        bytesize::ByteSize(total)
+                    ));
+                if used > NEARLY_FULL {
+                    bar = bar.fill(egui::Color32::from_rgb(200, 70, 70));
+                }
+                ui.add(bar);
+            }
+        })
+        .response
+        .interact(egui::Sense::click())
+        .on_hover_cursor(egui::CursorIcon::PointingHand)
+        .on_hover_text(card.path.display().to_string());
+    if selected {
+        response.scroll_to_me(None);
+    }
+    response
+}
@@ -0,0 +1,615 @@
+// Menu bar: every keyboard action and `:` command, grouped for mouse users
+//
+// Items run through the same paths as their keys (`run_key_command`) and commands
+// (`run_command`), after the frame via the context action, so the menu adds no
+// behavior of its own.
+
+use crate::app::{ContextAction, Heike};
+use crate::core::keymap::Command;
+use crate::state::{AppMode, LabelColor, SortBy, SortOrder};
+use crate::style::Theme;
+use eframe::egui;
+
+/// Add an item that runs `action` once the frame is done
+fn item(
+    ui: &mut egui::Ui,
+    label: &str,
+    shortcut: &str,
+    enabled: bool,
+    context_action: &ContextAction,
+    action: impl FnOnce(&mut Heike) + 'static,
+) {
+    if ui
+        .add_enabled(enabled, egui::Button::new(label).shortcut_text(shortcut))
+        .clicked()
+    {
+        *context_action.borrow_mut() = Some(Box::new(action));
+        ui.close();
+    }
+}
+
+/// Add an item with a check mark while `checked`
+fn toggle(
+    ui: &mut egui::Ui,
+    label: &str,
+    shortcut: &str,
+    checked: bool,
+    context_action: &ContextAction,
+    action: impl FnOnce(&mut Heike) + 'static,
+) {
+    let label = format!("{} {}", if checked { "✔" } else { "  " }, label);
+    item(ui, &label, shortcut, true, context_action, action);
+}
+
+/// Item for a key binding
+fn key(
+    ui: &mut egui::Ui,
+    label: &str,
+    shortcut: &str,
+    enabled: bool,
+    context_action: &ContextAction,
+    command: Command,
+) {
+    item(ui, label, shortcut, enabled, context_action, move |app| {
+        app.run_key_command(command, None)
+    });
+}
+
+/// Item for a `:` command that takes no input
+fn command(ui: &mut egui::Ui, label: &str, line: &'static str, context_action: &ContextAction) {
+    item(
+        ui,
+        label,
+        &format!(":{}", line),
+        true,
+        context_action,
+        move |app| app.run_command(line),
+    );
+}
+
+/// Item that opens the command line with `line` typed, for commands that need an argument
+fn prompt(
+    ui: &mut egui::Ui,
+    label: &str,
+    line: &'static str,
+    enabled: bool,
+    context_action: &ContextAction,
+) {
+    item(
+        ui,
+        label,
+        &format!(":{}", line.trim_end()),
+        enabled,
+        context_action,
+        move |app| {
+            app.mode.set_mode(AppMode::Command);
+            app.mode.command_buffer = line.to_string();
+            app.mode.focus_input = true;
+        },
+    );
+}
+
+impl Heike {
+    /// File / Edit / View / Tools / Tabs / Help; greyed out while a dialog or prompt is open
+    pub(crate) fn render_menu_bar(&mut self, ctx: &egui::Context, context_action: &ContextAction) {
+        if !self.config.ui.menu_bar {
+            return;
+        }
+        let idle = matches!(self.mode.mode, AppMode::Normal | AppMode::Visual);
+        let has_cursor = self
+            .browser
+            .selection
+            .selected_index
+            .is_some_and(|idx| idx < self.browser.entries.visible_entries.len());
+        let has_targets = has_cursor || !self.browser.selection.multi_selection.is_empty();
+        let has_files = !self.selected_files().is_empty();
+        let can_paste = !self.browser.clipboard.is_empty() && self.browser.clipboard_op.is_some();
+        let tab_count = self.tabs.tab_count();
+        let history = &self.browser.navigation;
+        let can_go_back = history.history_index > 0;
+        let can_go_forward = history.history_index + 1 < history.history.len();
+        let has_parent = history.current_path.parent().is_some();
+        let job = self.job.as_ref().map(|job| job.paused);
+
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            ui.add_enabled_ui(idle, |ui| {
+                egui::MenuBar::new().ui(ui, |ui| {
+                    ui.menu_button("File", |ui| {
+                        prompt(ui, "New folder…", "mkdir ", true, context_action);
+                        prompt(ui, "New file…", "touch ", true, context_action);
+                        ui.separator();
+                        key(
+                            ui,
+                            "Open",
+                            "Enter",
+                            has_targets,
+                            context_action,
+                            Command::Open,
+                        );
+                        key(
+                            ui,
+                            "Open with…",
+                            "Shift+Enter",
+                            has_files,
+                            context_action,
+                            Command::OpenWith,
+                        );
+                        key(
+                            ui,
+                            "Open in default app",
+                            "e",
+                            has_cursor,
+                            context_action,
+                            Command::OpenEntry,
+                        );
+                        key(
+                            ui,
+                            "Open at previewed line",
+                            "o",
+                            has_cursor,
+                            context_action,
+                            Command::OpenAtPreview,
+                        );
+                        key(
+                            ui,
+                            "Properties",
+                            "Alt+Enter",
+                            has_cursor,
+                            context_action,
+                            Command::Properties,
+                        );
+                        ui.separator();
+                        command(ui, "Open terminal here", "terminal", context_action);
+                        prompt(ui, "Go to folder…", "cd ", true, context_action);
+                        ui.separator();
+                        command(ui, "Quit", "q", context_action);
+                    });
+
+                    ui.menu_button("Edit", |ui| {
+                        key(ui, "Copy", "y", has_targets, context_action, Command::Copy);
+                        key(ui, "Cut", "x", has_targets, context_action, Command::Cut);
+                        key(ui, "Paste", "p", can_paste, context_action, Command::Paste);
+                        prompt(ui, "Paste at…", "paste ", can_paste, context_action);
+                        ui.separator();
+                        key(
+                            ui,
+                            "Rename",
+                            "r",
+                            has_cursor,
+                            context_action,
+                            Command::Rename,
+                        );
+                        key(
+                            ui,
+                            "Bulk rename",
+                            "Shift+R",
+                            has_targets,
+                            context_action,
+                            Command::BulkRename,
+                        );
+                        key(
+                            ui,
+                            "Move to trash",
+                            "d",
+                            has_targets,
+                            context_action,
+                            Command::Delete,
+                        );
+                        ui.menu_button("Label", |ui| {
+                            for label in LabelColor::ALL {
+                                item(
+                                    ui,
+                                    label.name(),
+                                    "",
+                                    has_targets,
+                                    context_action,
+                                    move |app| app.set_label(Some(label)),
+                                );
+                            }
+                            ui.separator();
+                            item(ui, "None", "", has_targets, context_action, |app| {
+                                app.set_label(None)
+                            });
+                        });
+                        ui.separator();
+                        key(
+                            ui,
+                            "Stage",
+                            "s",
+                            has_targets,
+                            context_action,
+                            Command::Stage,
+                        );
+                        key(
+                            ui,
+                            "Visual mode",
+                            "v",
+                            true,
+                            context_action,
+                            Command::Visual,
+                        );
+                        key(
+                            ui,
+                            "Select all",
+                            "Ctrl+A",
+                            true,
+                            context_action,
+                            Command::SelectAll,
+                        );
+                        key(
+                            ui,
+                            "Invert selection",
+                            "Ctrl+R",
+                            true,
+                            context_action,
+                            Command::InvertSelection,
+                        );
+                        key(
+                            ui,
+                            "Clear selection and filter",
+                            "Esc",
+                            true,
+                            context_action,
+                            Command::Cancel,
+                        );
+                        ui.separator();
+                        key(ui, "Filter…", "/", true, context_action, Command::Filter);
+                        key(
+                            ui,
+                            "Search in files…",
+                            "Shift+S",
+                            true,
+                            context_action,
+                            Command::Search,
+                        );
+                        key(
+                            ui,
+                            "Command line…",
+                            ":",
+                            true,
+                            context_action,
+                            Command::Colon,
+                        );
+                    });
+
+                    ui.menu_button("View", |ui| {
+                        toggle(
+                            ui,
+                            "Hidden files",
+                            ".",
+                            self.ui.show_hidden,
+                            context_action,
+                            |app| app.run_key_command(Command::ToggleHidden, None),
+                        );
+                        toggle(
+                            ui,
+                            "Flatten",
+                            "Shift+F",
+                            self.ui.flatten,
+                            context_action,
+                            |app| app.toggle_flatten(),
+                        );
+                        ui.menu_button("Sort by", |ui| {
+                            for (name, sort_by) in [
+                                ("Name", SortBy::Name),
+                                ("Size", SortBy::Size),
+                                ("Modified", SortBy::Modified),
+                                ("Created", SortBy::Created),
+                                ("Extension", SortBy::Extension),
+                            ] {
+                                toggle(
+                                    ui,
+                                    name,
+                                    "",
+                                    self.ui.sort_options.sort_by == sort_by,
+                                    context_action,
+                                    move |app| {
+                                        app.ui.sort_options.sort_by = sort_by;
+                                        app.apply_filter();
+                                    },
+                                );
+                            }
+                            ui.separator();
+                            key(
+                                ui,
+                                "Next field",
+                                "Shift+O",
+                                true,
+                                context_action,
+                                Command::CycleSort,
+                            );
+                            toggle(
+                                ui,
+                                "Descending",
+                                "Alt+O",
+                                self.ui.sort_options.sort_order == SortOrder::Descending,
+                                context_action,
+                                |app| app.run_key_command(Command::ToggleSortOrder, None),
+                            );
+                            toggle(
+                                ui,
+                                "Folders first",
+                                "Ctrl+O",
+                                self.ui.sort_options.dirs_first,
+                                context_action,
+                                |app| app.run_key_command(Command::ToggleDirsFirst, None),
+                            );
+                            let sort_by = self.ui.sort_options.sort_by;
+                            if sort_by != SortBy::Size {
+                                toggle(
+                                    ui,
+                                    "Group headers",
+                                    ":group",
+                                    self.ui.group_headers.contains(&sort_by),
+                                    context_action,
+                                    |app| app.toggle_group_headers(),
+                                );
+                            }
+                        });
+                        ui.menu_button("Preview", |ui| {
+                            key(
+                                ui,
+                                "Next tab",
+                                "]",
+                                has_cursor,
+                                context_action,
+                                Command::PreviewTab { forward: true },
+                            );
+                            key(
+                                ui,
+                                "Previous tab",
+                                "[",
+                                has_cursor,
+                                context_action,
+                                Command::PreviewTab { forward: false },
+                            );
+                            prompt(ui, "Preview as…", "preview-as ", has_cursor, context_action);
+                        });
+                        ui.separator();
+                        toggle(
+                            ui,
+                            "Clipboard panel",
+                            ":clipboard",
+                            self.ui.show_clipboard,
+                            context_action,
+                            |app| app.run_command("clipboard"),
+                        );
+                        toggle(
+                            ui,
+                            "Task center",
+                            ":tasks",
+                            self.ui.show_task_center,
+                            context_action,
+                            |app| app.run_command("tasks"),
+                        );
+                        toggle(
+                            ui,
+                            "Message history",
+                            ":messages",
+                            self.ui.show_message_history,
+                            context_action,
+                            |app| app.run_command("messages"),
+                        );
+                        toggle(
+                            ui,
+                            "Dark theme",
+                            "",
+                            self.ui.theme == Theme::Dark,
+                            context_action,
+                            |app| {
+                                app.ui.theme = match app.ui.theme {
+                                    Theme::Light => Theme::Dark,
+                                    Theme::Dark => Theme::Light,
+                                }
+                            },
+                        );
+                        ui.separator();
+                        key(
+                            ui,
+                            "Back",
+                            "Alt+Left",
+                            can_go_back,
+                            context_action,
+                            Command::Back,
+                        );
+                        key(
+                            ui,
+                            "Forward",
+                            "Alt+Right",
+                            can_go_forward,
+                            context_action,
+                            Command::Forward,
+                        );
+                        key(
+                            ui,
+                            "Parent folder",
+                            "h",
+                            has_parent,
+                            context_action,
+                            Command::Parent,
+                        );
+                        key(
+                            ui,
+                            "First entry",
+                            "gg",
+                            has_cursor,
+                            context_action,
+                            Command::Top,
+                        );
+                        key(
+                            ui,
+                            "Last entry",
+                            "Shift+G",
+                            has_cursor,
+                            context_action,
+                            Command::Bottom,
+                        );
+                        key(
+                            ui,
+                            "Project root",
+                            "g Shift+P",
+                            true,
+                            context_action,
+                            Command::ProjectRoot,
+                        );
+                        key(
+                            ui,
+                            "Mount root",
+                            "g Shift+M",
+                            true,
+                            context_action,
+                            Command::MountRoot,
+                        );
+                        key(
+                            ui,
+                            "Computer",
+                            "g Shift+C",
+                            true,
+                            context_action,
+                            Command::Computer,
+                        );
+                        ui.menu_button("Bookmarks", |ui| {
+                            let mut keys = self.bookmarks.keys();
+                            keys.sort();
+                            if keys.is_empty() {
+                                ui.weak("No bookmarks in config.toml");
+                            }
+                            for bookmark in keys {
+                                let label = match self.bookmarks.resolve_path(&bookmark) {
+                                    Some(path) => path.display().to_string(),
+                                    None => bookmark.clone(),
+                                };
+                                let shortcut = format!("g{}", bookmark);
+                                item(ui, &label, &shortcut, true, context_action, move |app| {
+                                    app.jump_to_bookmark(&bookmark)
+                                });
+                            }
+                        });
+                    });
+
+                    ui.menu_button("Tools", |ui| {
+                        prompt(ui, "Compare folders…", "dirdiff ", true, context_action);
+                        ui.menu_button("Snapshot", |ui| {
+                            command(ui, "Take", "snapshot", context_action);
+                            command(ui, "Compare", "snapshot compare", context_action);
+                            command(ui, "Clear", "snapshot clear", context_action);
+                        });
+                        command(ui, "Find empty files and folders", "empty", context_action);
+                        command(ui, "Find broken links", "brokenlinks", context_action);
+                        command(ui, "Check names for Windows", "portable", context_action);
+                        ui.separator();
+                        prompt(ui, "Split file…", "split ", has_files, context_action);
+                        command(ui, "Join parts", "join", context_action);
+                        key(
+                            ui,
+                            "Archive extraction hint",
+                            "Shift+E",
+                            has_cursor,
+                            context_action,
+                            Command::ExtractHint,
+                        );
+                        ui.separator();
+                        toggle(
+                            ui,
+                            "Dry run",
+                            ":dryrun",
+                            self.ui.dry_run,
+                            context_action,
+                            |app| app.toggle_dry_run(),
+                        );
+                        match job {
+                            Some(true) => {
+                                command(ui, "Resume file operation", "job resume", context_action)
+                            }
+                            Some(false) => {
+                                command(ui, "Pause file operation", "job pause", context_action)
+                            }
+                            None => {
+                                ui.add_enabled(false, egui::Button::new("Pause file operation"));
+                            }
+                        }
+                        ui.separator();
+                        prompt(ui, "Associations…", "assoc ", true, context_action);
+                        command(ui, "Aliases", "alias", context_action);
+                        command(ui, "Maintenance", "maintenance", context_action);
+                        command(ui, "Diagnostics", "diagnostics", context_action);
+                        ui.separator();
+                        command(ui, "Settings", "settings", context_action);
+                        command(ui, "Edit config.toml", "config edit", context_action);
+                    });
+
+                    ui.menu_button("Tabs", |ui| {
+                        key(
+                            ui,
+                            "New tab",
+                            "Ctrl+T",
+                            true,
+                            context_action,
+                            Command::NewTab,
+                        );
+                        key(
+                            ui,
+                            "Close tab",
+                            "Ctrl+W",
+                            tab_count > 1,
+                            context_action,
+                            Command::CloseTab,
+                        );
+                        key(
+                            ui,
+                            "Next tab",
+                            "Ctrl+Tab",
+                            tab_count > 1,
+                            context_action,
+                            Command::NextTab,
+                        );
+                        key(
+                            ui,
+                            "Previous tab",
+                            "Ctrl+Shift+Tab",
+                            tab_count > 1,
+                            context_action,
+                            Command::PrevTab,
+                        );
+                        key(
+                            ui,
+                            "Show this folder in the next tab",
+                            "Ctrl+M",
+                            tab_count > 1,
+                            context_action,
+                            Command::MirrorToTab,
+                        );
+                        ui.separator();
+                        for (index, tab) in self.tabs.tabs.iter().enumerate() {
+                            let shortcut = if index < 9 {
+                                format!("Alt+{}", index + 1)
+                            } else {
+                                String::new()
+                            };
+                            toggle(
+                                ui,
+                                &tab.label,
+                                &shortcut,
+                                index == self.tabs.active_tab,
+                                context_action,
+                                move |app| app.switch_to_tab(index),
+                            );
+                        }
+                    });
+
+                    ui.menu_button("Help", |ui| {
+                        key(
+                            ui,
+                            "Keyboard shortcuts",
+                            "?",
+                            true,
+                            context_action,
+                            Command::Help,
+                        );
+                        command(ui, "Commands", "help", context_action);
+                    });
+                });
+            });
+        });
+    }
+}
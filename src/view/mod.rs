@@ -1,5 +1,12 @@
+pub mod computer;
+pub mod menu;
 pub mod modals;
 pub mod panels;
 pub mod preview;
+pub mod settings;
 
-pub use preview::{create_default_registry, render_preview, PreviewCache, PreviewRegistry};
+pub use preview::{
+    create_default_registry, cycle_preview_tab, render_preview, ArchiveListing, PreviewCache,
+    PreviewListing, PreviewRegistry, PreviewTab, ARCHIVE_TOTALS_SECTION, CHECKSUM_SECTION,
+    DIR_SIZE_SECTION, DIR_SIZE_TTL, GIT_SECTION,
+};
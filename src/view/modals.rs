@@ -2,12 +2,130 @@
 // Extracted from app.rs for better code organization
 
 use crate::app::Heike;
-use crate::io::worker::IoCommand;
-use crate::state::AppMode;
+use crate::core::keymap::{self, Command};
+use crate::io::directory::DirUsage;
+use crate::io::maintenance;
+use crate::io::textdiff::DiffLine;
+use crate::io::tools;
+use crate::platform;
+use crate::state::{
+    AppMode, ConflictPolicy, DiffKind, ExitChoice, JobErrorAction, JobStart, NameTransform,
+    Severity, NAME_TEMPLATES,
+};
 use crate::style;
+use chrono::Timelike;
 use eframe::egui;
+use std::path::PathBuf;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Rows per column of the which-key overlay before it wraps into another column
+const WHICH_KEY_ROWS: usize = 8;
 
 impl Heike {
+    /// Which-key overlay: after a pause following `g`, `:` or `v`, what can come next
+    pub(crate) fn render_which_key(&mut self, ctx: &egui::Context) {
+        let now = std::time::Instant::now();
+        let delay = std::time::Duration::from_millis(style::WHICH_KEY_DELAY_MS);
+        let paused = self.ui.last_key_press.elapsed() >= delay;
+        let (title, rows): (String, Vec<(String, String)>) = match self.mode.mode {
+            AppMode::Normal | AppMode::Visual if self.keymap.pending().contains('g') => {
+                let Some(continuations) = self.keymap.continuations(now, delay) else {
+                    return;
+                };
+                let rows = continuations
+                    .into_iter()
+                    .map(|(keys, command)| {
+                        let description = match &command {
+                            Command::Bookmark(key) => self
+                                .bookmarks
+                                .resolve_path(key)
+                                .map(|path| path.display().to_string())
+                                .unwrap_or_else(|| command.description().to_string()),
+                            _ => command.description().to_string(),
+                        };
+                        (keys, description)
+                    })
+                    .collect();
+                (self.keymap.pending(), rows)
+            }
+            AppMode::Command if paused => {
+                let typed = self.mode.command_buffer.as_str();
+                if typed.contains(char::is_whitespace) {
+                    return;
+                }
+                let mut aliases: Vec<(String, String)> = self
+                    .config
+                    .aliases
+                    .iter()
+                    .filter(|(name, _)| name.starts_with(typed))
+                    .map(|(name, expansion)| (name.clone(), format!("→ {}", expansion)))
+                    .collect();
+                aliases.sort();
+                let rows = Self::COMMANDS
+                    .iter()
+                    .filter(|(name, _)| name.starts_with(typed))
+                    .map(|(name, description)| (name.to_string(), description.to_string()))
+                    .chain(aliases)
+                    .collect();
+                (format!(":{}", typed), rows)
+            }
+            AppMode::Visual if self.ui.visual_hint && paused => {
+                let rows = keymap::visual_commands()
+                    .iter()
+                    .filter_map(|command| {
+                        let press = keymap::key_for(command)?;
+                        Some((
+                            keymap::press_label(press),
+                            command.description().to_string(),
+                        ))
+                    })
+                    .collect();
+                ("Visual".to_string(), rows)
+            }
+            _ => return,
+        };
+        if rows.is_empty() {
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("which_key"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -36.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new(title).monospace().strong());
+                    ui.separator();
+                    let columns = rows.len().div_ceil(WHICH_KEY_ROWS);
+                    egui::Grid::new("which_key_grid")
+                        .num_columns(columns * 2)
+                        .spacing([12.0, 2.0])
+                        .show(ui, |ui| {
+                            for row in 0..rows.len().min(WHICH_KEY_ROWS) {
+                                for column in 0..columns {
+                                    match rows.get(column * WHICH_KEY_ROWS + row) {
+                                        Some((keys, description)) => {
+                                            ui.label(
+                                                egui::RichText::new(keys)
+                                                    .monospace()
+                                                    .color(egui::Color32::LIGHT_BLUE),
+                                            );
+                                            ui.label(description);
+                                        }
+                                        None => {
+                                            ui.label("");
+                                            ui.label("");
+                                        }
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+    }
+
     pub(crate) fn render_help_modal(&mut self, ctx: &egui::Context) {
         if self.mode.mode == AppMode::Help {
             egui::Window::new("Help")
@@ -54,6 +172,9 @@ impl Heike {
                             ui.label(".");
                             ui.label("Toggle Hidden");
                             ui.end_row();
+                            ui.label("F (Shift+f)");
+                            ui.label("Flatten View (All Files Below)");
+                            ui.end_row();
                             ui.label("/");
                             ui.label("Filter Mode");
                             ui.end_row();
@@ -72,9 +193,27 @@ impl Heike {
                             ui.label("y / x / p");
                             ui.label("Copy / Cut / Paste");
                             ui.end_row();
+                            ui.label("s");
+                            ui.label("Stage / Unstage (gather, then copy or move here)");
+                            ui.end_row();
                             ui.label("d / r");
                             ui.label("Delete / Rename");
                             ui.end_row();
+                            ui.label("Enter (selection)");
+                            ui.label("Open All Selected Files");
+                            ui.end_row();
+                            ui.label("Shift+Enter");
+                            ui.label("Open Selection With Command");
+                            ui.end_row();
+                            ui.label("Alt+Enter");
+                            ui.label("Properties");
+                            ui.end_row();
+                            ui.label("o");
+                            ui.label("Enter Previewed Dir / Edit at Previewed Line");
+                            ui.end_row();
+                            ui.label("[ / ]");
+                            ui.label("Previous / Next Preview Tab");
+                            ui.end_row();
                             ui.label("R (Shift+r)");
                             ui.label("Bulk Rename (vidir-style)");
                             ui.end_row();
@@ -93,6 +232,12 @@ impl Heike {
                             ui.label("g + key");
                             ui.label("Jump to Bookmark");
                             ui.end_row();
+                            ui.label("gP / gM");
+                            ui.label("Project Root / Mount Root");
+                            ui.end_row();
+                            ui.label("gC");
+                            ui.label("Computer (drives, places, recent)");
+                            ui.end_row();
                         });
                         ui.add_space(10.0);
                         ui.heading("Tab Management");
@@ -113,6 +258,9 @@ impl Heike {
                             ui.label("Alt+1...9");
                             ui.label("Switch to Tab 1-9");
                             ui.end_row();
+                            ui.label("Ctrl+M");
+                            ui.label("Mirror Path to Next Tab");
+                            ui.end_row();
                         });
                         ui.add_space(10.0);
                         ui.heading("Sort Options");
@@ -164,6 +312,13 @@ impl Heike {
                             self.mode.focus_input = false;
                         }
 
+                        ui.add_space(5.0);
+                        ui.label("Replace with (preview only):");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.ui.replace_text)
+                                .hint_text("$1, ${name}, $$ for a literal $"),
+                        );
+
                         ui.add_space(10.0);
                         ui.label("Options:");
                         ui.checkbox(&mut self.ui.search_options.case_sensitive, "Case sensitive");
@@ -173,25 +328,54 @@ impl Heike {
                             "Search hidden files",
                         );
                         ui.checkbox(&mut self.ui.search_options.search_pdfs, "Search PDFs");
-                        ui.checkbox(
-                            &mut self.ui.search_options.search_archives,
-                            "Search archives",
-                        );
+                        let build_dirs = &self.config.ui.build_dirs;
+                        let mut skip = !self.ui.search_options.skip_dirs.is_empty();
+                        if !build_dirs.is_empty()
+                            && ui
+                                .checkbox(&mut skip, format!("Skip {}", build_dirs.join(", ")))
+                                .changed()
+                        {
+                            self.ui.search_options.skip_dirs =
+                                if skip { build_dirs.clone() } else { Vec::new() };
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("Matches per file:");
+                            ui.add(
+                                egui::DragValue::new(&mut self.ui.search_options.max_per_file)
+                                    .range(0..=10_000),
+                            )
+                            .on_hover_text("0 keeps every match");
+                            ui.checkbox(
+                                &mut self.ui.search_options.rank_by_matches,
+                                "Files with most matches first",
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            ui.checkbox(
+                                &mut self.ui.search_options.search_archives,
+                                "Search archives (zip, tar, tar.gz, gz)",
+                            );
+                            let mut limit_mb = self.ui.search_options.max_archive_bytes / MIB;
+                            let response = ui
+                                .add_enabled(
+                                    self.ui.search_options.search_archives,
+                                    egui::DragValue::new(&mut limit_mb)
+                                        .range(1..=4096)
+                                        .suffix(" MiB"),
+                                )
+                                .on_hover_text("Decompressed data read per archive");
+                            if response.changed() {
+                                self.ui.search_options.max_archive_bytes = limit_mb * MIB;
+                            }
+                        });
+
+                        ui.add_space(10.0);
+                        self.render_search_roots(ui);
 
                         ui.add_space(10.0);
                         ui.horizontal(|ui| {
                             if ui.button("Search").clicked() && !self.ui.search_query.is_empty() {
-                                self.ui.search_in_progress = true;
-                                // Reset all search statistics
-                                self.ui.search_file_count = 0;
-                                self.ui.search_files_skipped = 0;
-                                self.ui.search_errors = 0;
-                                let _ = self.command_tx.send(IoCommand::SearchContent {
-                                    query: self.ui.search_query.clone(),
-                                    root_path: self.navigation.current_path.clone(),
-                                    options: self.ui.search_options.clone(),
-                                });
-                                self.mode.set_mode(AppMode::Normal);
+                                self.start_content_search();
                             }
                             if ui.button("Cancel").clicked() {
                                 self.mode.set_mode(AppMode::Normal);
@@ -215,10 +399,53 @@ impl Heike {
         }
     }
 
+    /// "Search in" choices: the current folder and each bookmark, merged into one search
+    fn render_search_roots(&mut self, ui: &mut egui::Ui) {
+        ui.label("Search in:");
+        ui.checkbox(
+            &mut self.ui.search_current,
+            format!(
+                "This folder ({})",
+                self.browser.navigation.current_path.display()
+            ),
+        );
+        let mut bookmarks: Vec<(String, PathBuf)> = self
+            .bookmarks
+            .keys()
+            .into_iter()
+            .filter_map(|key| Some((key.clone(), self.bookmarks.resolve_path(&key)?)))
+            .collect();
+        bookmarks.sort();
+        for (key, path) in &bookmarks {
+            let mut picked = self.ui.search_roots.contains(path);
+            if ui
+                .checkbox(&mut picked, format!("g{}  {}", key, path.display()))
+                .changed()
+            {
+                self.ui.search_roots.retain(|root| root != path);
+                if picked {
+                    self.ui.search_roots.push(path.clone());
+                }
+            }
+        }
+        ui.horizontal(|ui| {
+            if ui.small_button("All bookmarks").clicked() {
+                self.ui.search_roots = bookmarks.into_iter().map(|(_, path)| path).collect();
+            }
+            if ui.small_button("No bookmarks").clicked() {
+                self.ui.search_roots.clear();
+            }
+        });
+    }
+
     pub(crate) fn render_input_modal(&mut self, ctx: &egui::Context) {
         if matches!(
             self.mode.mode,
-            AppMode::Command | AppMode::Filtering | AppMode::Rename
+            AppMode::Command
+                | AppMode::Filtering
+                | AppMode::Rename
+                | AppMode::OpenWith { .. }
+                | AppMode::HookConfirm { .. }
         ) {
             egui::Area::new("input_popup".into())
                 .anchor(egui::Align2::CENTER_TOP, [0.0, 50.0])
@@ -226,10 +453,21 @@ impl Heike {
                 .show(ctx, |ui| {
                     egui::Frame::popup(ui.style()).show(ui, |ui| {
                         ui.set_min_width(400.0);
-                        let prefix = match self.mode.mode {
-                            AppMode::Rename => "Rename:",
-                            AppMode::Filtering => "/",
-                            _ => ":",
+                        let prefix = match &self.mode.mode {
+                            AppMode::Rename => "Rename:".to_string(),
+                            AppMode::Filtering => "/".to_string(),
+                            AppMode::OpenWith { paths, .. } => {
+                                format!("Open {} file(s) with:", paths.len())
+                            }
+                            AppMode::HookConfirm {
+                                kind,
+                                items,
+                                phrase,
+                                ..
+                            } => {
+                                format!("{}? Type {}:", kind.describe(items.len()), phrase)
+                            }
+                            _ => ":".to_string(),
                         };
                         ui.horizontal(|ui| {
                             ui.label(prefix);
@@ -238,7 +476,56 @@ impl Heike {
                                 response.request_focus();
                                 self.mode.focus_input = false;
                             }
+                            if self.mode.cursor_to_end {
+                                self.mode.cursor_to_end = false;
+                                if let Some(mut state) =
+                                    egui::TextEdit::load_state(ui.ctx(), response.id)
+                                {
+                                    let end = egui::text::CCursor::new(
+                                        self.mode.command_buffer.chars().count(),
+                                    );
+                                    state
+                                        .cursor
+                                        .set_char_range(Some(egui::text::CCursorRange::one(end)));
+                                    state.store(ui.ctx(), response.id);
+                                }
+                            }
                         });
+                        if self.accepts_name_completion() {
+                            let templates: Vec<&str> =
+                                NAME_TEMPLATES.iter().map(|(t, _)| *t).collect();
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "Tab: complete from this folder · {}",
+                                    templates.join(" ")
+                                ))
+                                .small()
+                                .weak(),
+                            )
+                            .on_hover_text(
+                                NAME_TEMPLATES
+                                    .iter()
+                                    .map(|(t, desc)| format!("{} → {}", t, desc))
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                        }
+                        if self.mode.mode == AppMode::Filtering
+                            && ui
+                                .checkbox(
+                                    &mut self.ui.filter_preview,
+                                    "Filter the folder preview too",
+                                )
+                                .changed()
+                        {
+                            // Keep typing into the filter after clicking
+                            self.mode.focus_input = true;
+                        }
+                        if let AppMode::OpenWith { paths, remember } = &mut self.mode.mode {
+                            if let Some(ext) = tools::common_extension(paths) {
+                                ui.checkbox(remember, format!("Always open .{} with this", ext));
+                            }
+                        }
                     });
                 });
         }
@@ -251,12 +538,25 @@ impl Heike {
             return;
         }
 
-        let (file_count, focus_input) =
+        let (original_names, focus_input) =
             if let AppMode::BulkRename { original_paths, .. } = &self.mode.mode {
-                (original_paths.len(), self.mode.focus_input)
+                let names: Vec<String> = original_paths
+                    .iter()
+                    .map(|p| {
+                        p.file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    })
+                    .collect();
+                (names, self.mode.focus_input)
             } else {
                 return;
             };
+        let file_count = original_names.len();
+
+        let mut transform: Option<NameTransform> = None;
+        let mut reset = false;
+        let mut photo_dates = false;
 
         egui::Window::new("Bulk Rename")
             .collapsible(false)
@@ -271,13 +571,54 @@ impl Heike {
                         .weak()
                         .italics(),
                 );
+                ui.horizontal_wrapped(|ui| {
+                    for candidate in NameTransform::ALL {
+                        if ui
+                            .small_button(candidate.label())
+                            .on_hover_text(candidate.description())
+                            .clicked()
+                        {
+                            transform = Some(candidate);
+                        }
+                    }
+                    if ui
+                        .small_button("↺ Reset")
+                        .on_hover_text("Restore the original names")
+                        .clicked()
+                    {
+                        reset = true;
+                    }
+                });
+                if let AppMode::BulkRename { photo_pattern, .. } = &mut self.mode.mode {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .small_button("📷 Photo date")
+                            .on_hover_text(
+                                "Name each file after its EXIF capture time (the modification \
+                                 time without EXIF): {date} YYYYMMDD, {time} HHMMSS, \
+                                 {seq} 001, 002, … per day",
+                            )
+                            .clicked()
+                        {
+                            photo_dates = true;
+                        }
+                        ui.add(
+                            egui::TextEdit::singleline(photo_pattern)
+                                .font(egui::TextStyle::Monospace)
+                                .desired_width(220.0),
+                        );
+                    });
+                }
                 ui.separator();
 
                 // Get mutable reference to edit_buffer
                 if let AppMode::BulkRename { edit_buffer, .. } = &mut self.mode.mode {
                     // Multi-line text editor
                     let response = ui.add_sized(
-                        [ui.available_width(), ui.available_height() - 60.0],
+                        [
+                            ui.available_width(),
+                            (ui.available_height() - 200.0).max(80.0),
+                        ],
                         egui::TextEdit::multiline(edit_buffer)
                             .font(egui::TextStyle::Monospace)
                             .code_editor()
@@ -288,10 +629,47 @@ impl Heike {
                         response.request_focus();
                         self.mode.focus_input = false;
                     }
+
+                    // Preview of what Ctrl+Enter will do
+                    let new_names: Vec<&str> = edit_buffer.lines().collect();
+                    let changes: Vec<(&String, &str)> = original_names
+                        .iter()
+                        .zip(new_names.iter().map(|n| n.trim()))
+                        .filter(|(old, new)| old.as_str() != *new)
+                        .collect();
+                    ui.separator();
+                    if new_names.len() != file_count {
+                        ui.colored_label(
+                            style::severity_color(Severity::Error),
+                            format!("{} names for {} files", new_names.len(), file_count),
+                        );
+                    } else {
+                        ui.label(format!("{} of {} names change", changes.len(), file_count));
+                    }
+                    egui::ScrollArea::vertical()
+                        .id_salt("bulk_rename_preview")
+                        .max_height(120.0)
+                        .auto_shrink([false, true])
+                        .show(ui, |ui| {
+                            egui::Grid::new("bulk_rename_preview_grid")
+                                .num_columns(3)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (old, new) in changes {
+                                        ui.monospace(old);
+                                        ui.label("→");
+                                        ui.monospace(new);
+                                        ui.end_row();
+                                    }
+                                });
+                        });
                 }
 
                 ui.separator();
                 ui.horizontal(|ui| {
+                    if let AppMode::BulkRename { auto_suffix, .. } = &mut self.mode.mode {
+                        ui.checkbox(auto_suffix, "Add (1), (2)… when a name is taken");
+                    }
                     if ui.button("Apply (Ctrl+Enter)").clicked() {
                         self.apply_bulk_rename();
                     }
@@ -300,5 +678,1573 @@ impl Heike {
                     }
                 });
             });
+
+        if let AppMode::BulkRename { edit_buffer, .. } = &mut self.mode.mode {
+            if let Some(transform) = transform {
+                *edit_buffer = transform.apply(edit_buffer);
+            }
+            if reset {
+                *edit_buffer = original_names.join("\n");
+            }
+        }
+        if photo_dates {
+            self.apply_photo_dates();
+        }
+    }
+
+    /// A paste or delete item failed: retry it, skip it (or all failures), or abort the job
+    pub(crate) fn render_job_error_modal(&mut self, ctx: &egui::Context) {
+        let AppMode::JobError { path, error } = &self.mode.mode else {
+            return;
+        };
+        let Some(job) = &self.job else {
+            return;
+        };
+        let mut action: Option<JobErrorAction> = None;
+
+        egui::Window::new("Operation Failed")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} {} of {} items so far. This one failed:",
+                    job.kind.verb(),
+                    job.done,
+                    job.total
+                ));
+                ui.monospace(path.display().to_string());
+                ui.colored_label(style::severity_color(Severity::Error), error);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Retry (r)").clicked() {
+                        action = Some(JobErrorAction::Retry);
+                    }
+                    if ui.button("Skip (s)").clicked() {
+                        action = Some(JobErrorAction::Skip);
+                    }
+                    if ui
+                        .button("Skip all (a)")
+                        .on_hover_text("Skip this and any later failures without asking")
+                        .clicked()
+                    {
+                        action = Some(JobErrorAction::SkipAll);
+                    }
+                    if ui
+                        .button("Abort (Esc)")
+                        .on_hover_text(format!("Leave the remaining {} items", job.pending.len()))
+                        .clicked()
+                    {
+                        action = Some(JobErrorAction::Abort);
+                    }
+                });
+            });
+
+        if let Some(action) = action {
+            self.resolve_job_error(action);
+        }
+    }
+
+    /// The window was closed while jobs run or wait: wait for them, cancel them, or go to the background
+    pub(crate) fn render_exit_confirm_modal(&mut self, ctx: &egui::Context) {
+        if self.mode.mode != AppMode::ExitConfirm {
+            return;
+        }
+        let mut choice: Option<ExitChoice> = None;
+
+        egui::Window::new("Jobs Still Pending")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx))
+            .show(ctx, |ui| {
+                ui.label("Quitting now would stop these file operations part way:");
+                if let Some(job) = &self.job {
+                    ui.horizontal(|ui| {
+                        style::truncated_label(ui, job.kind.describe(job.total));
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{}/{}{}",
+                                job.processed(),
+                                job.total,
+                                if job.paused { ", paused" } else { "" }
+                            ))
+                            .weak(),
+                        );
+                    });
+                }
+                for queued in &self.queued_jobs {
+                    ui.horizontal(|ui| {
+                        style::truncated_label(ui, queued.job.kind.describe(queued.job.total));
+                        ui.label(egui::RichText::new(queued.start.describe()).weak());
+                    });
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Wait (w)")
+                        .on_hover_text("Keep the window open and quit when the last job is done")
+                        .clicked()
+                    {
+                        choice = Some(ExitChoice::Wait);
+                    }
+                    if ui
+                        .button("Run in background (b)")
+                        .on_hover_text("Minimize the window and quit when the last job is done")
+                        .clicked()
+                    {
+                        choice = Some(ExitChoice::Background);
+                    }
+                    if ui
+                        .button("Cancel all and quit (c)")
+                        .on_hover_text("Items already done stay done; the rest are left untouched")
+                        .clicked()
+                    {
+                        choice = Some(ExitChoice::CancelAll);
+                    }
+                    if ui.button("Don't quit (Esc)").clicked() {
+                        choice = Some(ExitChoice::Stay);
+                    }
+                });
+            });
+
+        if let Some(choice) = choice {
+            self.resolve_exit(ctx, choice);
+        }
+    }
+
+    /// Choose how a paste handles items that already exist in the destination
+    pub(crate) fn render_paste_conflict_modal(&mut self, ctx: &egui::Context) {
+        const SHOWN_NAMES: usize = 10;
+        let mut choice: Option<ConflictPolicy> = None;
+        let mut cancel = false;
+        let mut compare: Option<String> = None;
+
+        let AppMode::PasteConflict {
+            names,
+            remember,
+            comparable,
+            diff,
+        } = &mut self.mode.mode
+        else {
+            return;
+        };
+        egui::Window::new("Items Already Exist")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} of the pasted items already exist here:",
+                    names.len()
+                ));
+                for name in names.iter().take(SHOWN_NAMES) {
+                    ui.horizontal(|ui| {
+                        ui.monospace(name);
+                        if comparable.contains(name) {
+                            let shown = diff.as_ref().is_some_and(|(shown, _)| shown == name);
+                            if ui
+                                .selectable_label(shown, "Compare")
+                                .on_hover_text("Show how the pasted file differs from this one")
+                                .clicked()
+                            {
+                                compare = Some(name.clone());
+                            }
+                        }
+                    });
+                }
+                if names.len() > SHOWN_NAMES {
+                    ui.label(
+                        egui::RichText::new(format!("…and {} more", names.len() - SHOWN_NAMES))
+                            .weak(),
+                    );
+                }
+                if let Some((name, lines)) = diff {
+                    ui.separator();
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{}: − only here, + only in the pasted file (c: next)",
+                            name
+                        ))
+                        .weak(),
+                    );
+                    match lines {
+                        Ok(lines) if lines.is_empty() => {
+                            ui.label("Both files are empty.");
+                        }
+                        Ok(lines)
+                            if lines
+                                .iter()
+                                .all(|l| matches!(l, DiffLine::Same(_) | DiffLine::Folded(_))) =>
+                        {
+                            ui.label("The files have the same lines.");
+                        }
+                        Ok(lines) => {
+                            egui::ScrollArea::vertical()
+                                .max_height(300.0)
+                                .auto_shrink([false, true])
+                                .show(ui, |ui| {
+                                    for line in lines.iter() {
+                                        let (text, color) = match line {
+                                            DiffLine::Same(text) => {
+                                                (format!("  {}", text), ui.visuals().text_color())
+                                            }
+                                            DiffLine::Removed(text) => (
+                                                format!("− {}", text),
+                                                egui::Color32::from_rgb(230, 90, 90),
+                                            ),
+                                            DiffLine::Added(text) => (
+                                                format!("+ {}", text),
+                                                egui::Color32::from_rgb(100, 200, 100),
+                                            ),
+                                            DiffLine::Folded(count) => (
+                                                format!("  … {} unchanged lines", count),
+                                                ui.visuals().weak_text_color(),
+                                            ),
+                                        };
+                                        ui.label(
+                                            egui::RichText::new(text).monospace().color(color),
+                                        );
+                                    }
+                                });
+                        }
+                        Err(e) => {
+                            ui.colored_label(egui::Color32::RED, e.as_str());
+                        }
+                    }
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Keep both (r)")
+                        .on_hover_text("Paste as \"name (1)\", \"name (2)\", …")
+                        .clicked()
+                    {
+                        choice = Some(ConflictPolicy::Rename);
+                    }
+                    if ui
+                        .button("Overwrite (o)")
+                        .on_hover_text(format!(
+                            "Move the existing items to the {} first",
+                            platform::TRASH_NAME
+                        ))
+                        .clicked()
+                    {
+                        choice = Some(ConflictPolicy::Overwrite);
+                    }
+                    if ui.button("Skip (s)").clicked() {
+                        choice = Some(ConflictPolicy::Skip);
+                    }
+                    if ui.button("Cancel (Esc)").clicked() {
+                        cancel = true;
+                    }
+                });
+                ui.checkbox(
+                    remember,
+                    "Always do this (saved as file_ops.conflict_policy)",
+                );
+            });
+
+        if let Some(name) = compare {
+            self.compare_paste_conflict(&name);
+        }
+        if let Some(policy) = choice {
+            self.resolve_paste_conflict(policy);
+        } else if cancel {
+            self.mode.set_mode(AppMode::Normal);
+        }
+    }
+
+    pub(crate) fn render_dirdiff_modal(&mut self, ctx: &egui::Context) {
+        let AppMode::DirDiff {
+            left,
+            right,
+            entries,
+            selected_index,
+        } = &self.mode.mode
+        else {
+            return;
+        };
+        let selected_index = *selected_index;
+
+        let mut copy_request: Option<(usize, bool)> = None;
+        let mut copy_all_request: Option<bool> = None;
+        let mut close = false;
+
+        egui::Window::new("Directory Comparison")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx) * 1.4)
+            .max_height(style::modal_max_height(ctx))
+            .show(ctx, |ui| {
+                ui.label(format!("Left:  {}", left.display()));
+                ui.label(format!("Right: {}", right.display()));
+                ui.label(
+                    egui::RichText::new(
+                        "j/k: move  l/→: copy to right  h/←: copy to left  Shift: all missing  Esc: close",
+                    )
+                    .weak()
+                    .italics(),
+                );
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(style::modal_max_height(ctx) - 120.0)
+                    .show(ui, |ui| {
+                        for (idx, entry) in entries.iter().enumerate() {
+                            let (marker, color) = match entry.kind {
+                                DiffKind::OnlyLeft => ("<", egui::Color32::from_rgb(100, 200, 100)),
+                                DiffKind::OnlyRight => (">", egui::Color32::from_rgb(100, 150, 255)),
+                                DiffKind::Different => ("≠", egui::Color32::from_rgb(230, 180, 80)),
+                            };
+                            let size = |s: Option<u64>| {
+                                s.map(|b| bytesize::ByteSize(b).to_string())
+                                    .unwrap_or_else(|| "-".into())
+                            };
+                            let suffix = if entry.is_dir { "/" } else { "" };
+
+                            ui.horizontal(|ui| {
+                                ui.colored_label(color, marker);
+                                let text = format!(
+                                    "{}{}  ({} | {})",
+                                    entry.relative_path.display(),
+                                    suffix,
+                                    size(entry.left_size),
+                                    size(entry.right_size)
+                                );
+                                let response = ui.selectable_label(idx == selected_index, text);
+                                if idx == selected_index {
+                                    response.scroll_to_me(None);
+                                }
+                                if entry.kind != DiffKind::OnlyRight
+                                    && ui.small_button("→").on_hover_text("Copy to right").clicked()
+                                {
+                                    copy_request = Some((idx, true));
+                                }
+                                if entry.kind != DiffKind::OnlyLeft
+                                    && ui.small_button("←").on_hover_text("Copy to left").clicked()
+                                {
+                                    copy_request = Some((idx, false));
+                                }
+                            });
+                        }
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Copy all missing →").clicked() {
+                        copy_all_request = Some(true);
+                    }
+                    if ui.button("← Copy all missing").clicked() {
+                        copy_all_request = Some(false);
+                    }
+                    if ui.button("Close (Esc)").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if let Some((idx, to_right)) = copy_request {
+            self.copy_dir_diff_entry(idx, to_right);
+        }
+        if let Some(to_right) = copy_all_request {
+            self.copy_dir_diff_missing(to_right);
+        }
+        if close {
+            self.mode.set_mode(AppMode::Normal);
+        }
+    }
+
+    pub(crate) fn render_cleanup_modal(&mut self, ctx: &egui::Context) {
+        let AppMode::Cleanup {
+            root,
+            items,
+            selected_index,
+        } = &self.mode.mode
+        else {
+            return;
+        };
+        let selected_index = *selected_index;
+        let checked = items.iter().filter(|item| item.checked).count();
+
+        let mut toggle: Option<Option<usize>> = None;
+        let mut trash = false;
+        let mut close = false;
+
+        egui::Window::new("Empty Items")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx))
+            .max_height(style::modal_max_height(ctx))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Empty folders and zero-byte files under {}",
+                    root.display()
+                ));
+                ui.label(
+                    egui::RichText::new(
+                        "j/k: move  Space: tick  a: tick all/none  Enter/d: delete ticked  Esc: close",
+                    )
+                    .weak()
+                    .italics(),
+                );
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(style::modal_max_height(ctx) - 120.0)
+                    .show(ui, |ui| {
+                        for (idx, item) in items.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let mut ticked = item.checked;
+                                if ui.checkbox(&mut ticked, "").changed() {
+                                    toggle = Some(Some(idx));
+                                }
+                                let (icon, suffix) =
+                                    if item.is_dir { ("📁", "/") } else { ("📄", "") };
+                                let text = format!(
+                                    "{} {}{}",
+                                    icon,
+                                    item.relative_path.display(),
+                                    suffix
+                                );
+                                let response = ui.selectable_label(idx == selected_index, text);
+                                if idx == selected_index {
+                                    response.scroll_to_me(None);
+                                }
+                                if response.clicked() {
+                                    toggle = Some(Some(idx));
+                                }
+                            });
+                        }
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            checked > 0,
+                            egui::Button::new(format!(
+                                "Move {} to the {} (Enter)",
+                                checked,
+                                platform::TRASH_NAME
+                            )),
+                        )
+                        .clicked()
+                    {
+                        trash = true;
+                    }
+                    if ui.button("Tick all/none (a)").clicked() {
+                        toggle = Some(None);
+                    }
+                    if ui.button("Close (Esc)").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if let Some(index) = toggle {
+            if let (Some(index), AppMode::Cleanup { selected_index, .. }) =
+                (index, &mut self.mode.mode)
+            {
+                *selected_index = index;
+            }
+            self.toggle_cleanup_item(index);
+        }
+        if trash {
+            self.trash_cleanup_items();
+        } else if close {
+            self.mode.set_mode(AppMode::Normal);
+        }
+    }
+
+    pub(crate) fn render_broken_links_modal(&mut self, ctx: &egui::Context) {
+        let AppMode::BrokenLinks {
+            root,
+            links,
+            selected_index,
+            retarget,
+        } = &mut self.mode.mode
+        else {
+            return;
+        };
+
+        let mut select: Option<usize> = None;
+        let mut reveal = false;
+        let mut begin_retarget = false;
+        let mut apply_retarget = false;
+        let mut trash: Option<bool> = None;
+        let mut close = false;
+        let focus_input = std::mem::take(&mut self.mode.focus_input);
+
+        egui::Window::new("Broken Links")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx) * 1.4)
+            .max_height(style::modal_max_height(ctx))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Links and shortcuts under {} whose target is missing",
+                    root.display()
+                ));
+                ui.label(
+                    egui::RichText::new(
+                        "j/k: move  Enter/g: show in folder  r: retarget  d: delete  D: delete all  Esc: close",
+                    )
+                    .weak()
+                    .italics(),
+                );
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(style::modal_max_height(ctx) - 150.0)
+                    .show(ui, |ui| {
+                        for (idx, link) in links.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(link.kind.label())
+                                        .monospace()
+                                        .color(egui::Color32::from_rgb(230, 180, 80)),
+                                );
+                                let relative = link.path.strip_prefix(&*root).unwrap_or(&link.path);
+                                let text = format!("{}  →  {}", relative.display(), link.target);
+                                let response =
+                                    ui.selectable_label(idx == *selected_index, text);
+                                if idx == *selected_index {
+                                    response.scroll_to_me(None);
+                                }
+                                if response.clicked() {
+                                    select = Some(idx);
+                                }
+                                if response.double_clicked() {
+                                    reveal = true;
+                                }
+                            });
+                        }
+                    });
+
+                ui.separator();
+                if let Some(target) = retarget {
+                    ui.horizontal(|ui| {
+                        ui.label("New target:");
+                        let response = ui.text_edit_singleline(target);
+                        if focus_input {
+                            response.request_focus();
+                        }
+                        if ui.button("Apply (Enter)").clicked() {
+                            apply_retarget = true;
+                        }
+                    });
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Show in folder (g)").clicked() {
+                        reveal = true;
+                    }
+                    let can_retarget = links
+                        .get(*selected_index)
+                        .is_some_and(|link| link.kind.can_retarget());
+                    if ui
+                        .add_enabled(can_retarget, egui::Button::new("Retarget (r)"))
+                        .clicked()
+                    {
+                        begin_retarget = true;
+                    }
+                    if ui.button("Delete (d)").clicked() {
+                        trash = Some(false);
+                    }
+                    if ui.button("Delete all (D)").clicked() {
+                        trash = Some(true);
+                    }
+                    if ui.button("Close (Esc)").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if let (Some(index), AppMode::BrokenLinks { selected_index, .. }) =
+            (select, &mut self.mode.mode)
+        {
+            *selected_index = index;
+        }
+        if reveal {
+            self.reveal_broken_link();
+        } else if begin_retarget {
+            self.begin_retarget_link();
+        } else if apply_retarget {
+            self.apply_retarget_link();
+        } else if let Some(all) = trash {
+            self.trash_broken_links(all);
+        } else if close {
+            self.mode.set_mode(AppMode::Normal);
+        }
+    }
+
+    pub(crate) fn render_name_audit_modal(&mut self, ctx: &egui::Context) {
+        let AppMode::NameAudit {
+            root,
+            issues,
+            selected_index,
+        } = &self.mode.mode
+        else {
+            return;
+        };
+        let selected_index = *selected_index;
+
+        let mut select: Option<usize> = None;
+        let mut sanitize: Option<bool> = None;
+        let mut close = false;
+
+        egui::Window::new("Portable Names")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx) * 1.4)
+            .max_height(style::modal_max_height(ctx))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Names under {} that Windows and SMB shares reject",
+                    root.display()
+                ));
+                ui.label(
+                    egui::RichText::new(
+                        "j/k: move  Enter: rename all  d: dry run report  Esc: close",
+                    )
+                    .weak()
+                    .italics(),
+                );
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(style::modal_max_height(ctx) - 120.0)
+                    .show(ui, |ui| {
+                        for (idx, issue) in issues.iter().enumerate() {
+                            let relative = issue.path.strip_prefix(root).unwrap_or(&issue.path);
+                            let text = format!("{}  →  {}", relative.display(), issue.suggestion);
+                            let response = ui
+                                .selectable_label(idx == selected_index, text)
+                                .on_hover_text(issue.problems.join(", "));
+                            if idx == selected_index {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                select = Some(idx);
+                            }
+                            ui.label(
+                                egui::RichText::new(format!("   {}", issue.problems.join(", ")))
+                                    .small()
+                                    .color(egui::Color32::from_rgb(230, 180, 80)),
+                            );
+                        }
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(format!("Rename {} items (Enter)", issues.len()))
+                        .on_hover_text("Taken names get a number, as in \"name (1)\"")
+                        .clicked()
+                    {
+                        sanitize = Some(false);
+                    }
+                    if ui.button("Dry run report (d)").clicked() {
+                        sanitize = Some(true);
+                    }
+                    if ui.button("Close (Esc)").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if let (Some(index), AppMode::NameAudit { selected_index, .. }) =
+            (select, &mut self.mode.mode)
+        {
+            *selected_index = index;
+        }
+        if let Some(report_only) = sanitize {
+            self.sanitize_audited_names(report_only);
+        } else if close {
+            self.mode.set_mode(AppMode::Normal);
+        }
+    }
+
+    pub(crate) fn render_dry_run_modal(&mut self, ctx: &egui::Context) {
+        let AppMode::DryRun { report, return_to } = &self.mode.mode else {
+            return;
+        };
+
+        let mut save = false;
+        let mut close = false;
+
+        egui::Window::new("Dry Run")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx) * 1.4)
+            .max_height(style::modal_max_height(ctx))
+            .show(ctx, |ui| {
+                ui.heading(&report.title);
+                ui.label(format!(
+                    "{} items, {} unchanged, {} would fail. Nothing was written.",
+                    report.changes.len(),
+                    report.unchanged(),
+                    report.problems()
+                ));
+                ui.separator();
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                egui::ScrollArea::both()
+                    .max_height(style::modal_max_height(ctx) - 140.0)
+                    .auto_shrink([false, true])
+                    .show_rows(ui, row_height, report.changes.len(), |ui, rows| {
+                        for change in &report.changes[rows] {
+                            let color = match change.action {
+                                _ if change.problem => egui::Color32::from_rgb(230, 90, 90),
+                                "skip" | "keep" => ui.visuals().weak_text_color(),
+                                "trash" => egui::Color32::from_rgb(230, 180, 80),
+                                _ => egui::Color32::from_rgb(100, 200, 100),
+                            };
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(change.line()).monospace().color(color),
+                                )
+                                .extend(),
+                            );
+                        }
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("📋 Copy report")
+                        .on_hover_text("Copy the report as plain text")
+                        .clicked()
+                    {
+                        ui.ctx().copy_text(report.to_text());
+                    }
+                    if ui
+                        .button("💾 Save report")
+                        .on_hover_text("Write the report as a text file into the current directory")
+                        .clicked()
+                    {
+                        save = true;
+                    }
+                    let close_text = if return_to.is_some() {
+                        "Back (Esc)"
+                    } else {
+                        "Close (Esc)"
+                    };
+                    if ui.button(close_text).clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if save {
+            self.save_dry_run_report();
+        }
+        if close {
+            self.close_dry_run();
+        }
+    }
+
+    pub(crate) fn render_diagnostics_modal(&mut self, ctx: &egui::Context) {
+        if self.mode.mode != AppMode::Diagnostics {
+            return;
+        }
+
+        let selection = self
+            .browser
+            .selection
+            .selected_index
+            .and_then(|idx| self.browser.entries.visible_entries.get(idx));
+        let chosen = selection
+            .and_then(|entry| self.preview_registry.handler_for(entry))
+            .map(|handler| handler.name().to_string());
+        let handlers = self.preview_registry.statuses(selection);
+
+        let mut close = false;
+        egui::Window::new("Diagnostics")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx))
+            .show(ctx, |ui| {
+                ui.heading("External Tools");
+                if !self.capabilities.checked {
+                    ui.label(
+                        egui::RichText::new("Startup checks disabled (tools.check_on_startup)")
+                            .weak()
+                            .italics(),
+                    );
+                }
+                ui.separator();
+                egui::Grid::new("diagnostics_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for status in self.capabilities.statuses() {
+                            let (mark, color) = if status.available {
+                                ("✔", egui::Color32::from_rgb(100, 200, 100))
+                            } else {
+                                ("✘", egui::Color32::from_rgb(230, 90, 90))
+                            };
+                            ui.colored_label(color, mark);
+                            ui.label(status.tool.label());
+                            ui.monospace(status.command.as_deref().unwrap_or("-"));
+                            ui.label(&status.detail);
+                            ui.label(egui::RichText::new(status.tool.purpose()).weak());
+                            ui.end_row();
+                        }
+                    });
+
+                ui.add_space(8.0);
+                ui.heading("Preview Handlers");
+                if let Some(entry) = selection {
+                    ui.label(match &chosen {
+                        Some(name) => format!("{} is previewed by \"{}\"", entry.name, name),
+                        None => format!("No enabled handler accepts {}", entry.name),
+                    });
+                }
+                ui.separator();
+                egui::Grid::new("preview_handlers_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for title in ["", "Handler", "Priority", "Selection", "Renders", "Average", "Last error"] {
+                            ui.strong(title);
+                        }
+                        ui.end_row();
+                        for handler in &handlers {
+                            let (mark, color) = if handler.enabled {
+                                ("✔", egui::Color32::from_rgb(100, 200, 100))
+                            } else {
+                                ("✘", egui::Color32::from_rgb(230, 90, 90))
+                            };
+                            ui.colored_label(color, mark).on_hover_text(if handler.enabled {
+                                "Enabled"
+                            } else {
+                                "Disabled (previews.enabled)"
+                            });
+                            if chosen.as_deref() == Some(handler.name.as_str()) {
+                                ui.strong(&handler.name);
+                            } else {
+                                ui.label(&handler.name);
+                            }
+                            ui.label(handler.priority.to_string());
+                            ui.label(match handler.accepts_selection {
+                                Some(true) => "accepts",
+                                Some(false) => "-",
+                                None => "",
+                            });
+                            ui.label(handler.stats.renders.to_string());
+                            ui.label(
+                                handler
+                                    .stats
+                                    .average()
+                                    .map(|avg| format!("{:.2} ms", avg.as_secs_f64() * 1000.0))
+                                    .unwrap_or_default(),
+                            );
+                            match &handler.stats.last_error {
+                                Some((file, error)) => {
+                                    ui.colored_label(egui::Color32::from_rgb(230, 90, 90), file)
+                                        .on_hover_text(error);
+                                }
+                                None => {
+                                    ui.label("");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                ui.label(
+                    egui::RichText::new(
+                        "Handlers are tried top to bottom; a forced extension mapping or :preview-as wins.",
+                    )
+                    .weak(),
+                );
+                ui.separator();
+                if ui.button("Close (Esc)").clicked() {
+                    close = true;
+                }
+            });
+
+        if close {
+            self.mode.set_mode(AppMode::Normal);
+        }
+    }
+
+    pub(crate) fn render_properties_modal(&mut self, ctx: &egui::Context) {
+        if self.mode.mode != AppMode::Properties {
+            return;
+        }
+        // Directory totals come from the same worker job as the preview summary
+        let dir_usage = match self.properties.as_ref().map(|p| &p.entry) {
+            Some(entry) if entry.is_dir => {
+                let cached = self.preview_cache.borrow().get_recent_section(
+                    &entry.path,
+                    crate::view::DIR_SIZE_SECTION,
+                    entry.modified,
+                    crate::view::DIR_SIZE_TTL,
+                );
+                if cached.is_none() {
+                    let path = entry.path.clone();
+                    self.request_dir_size(path);
+                }
+                cached.as_deref().and_then(DirUsage::from_section)
+            }
+            _ => None,
+        };
+        let Some(props) = self.properties.as_mut() else {
+            return;
+        };
+
+        let mut close = false;
+        let mut add_xattr = false;
+        let mut remove_xattr: Option<String> = None;
+
+        egui::Window::new("Properties")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx))
+            .max_height(style::modal_max_height(ctx))
+            .show(ctx, |ui| {
+                let entry = &props.entry;
+                egui::Grid::new("properties_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Name");
+                        ui.label(&entry.name);
+                        ui.end_row();
+                        ui.label("Location");
+                        ui.label(
+                            entry
+                                .path
+                                .parent()
+                                .map(|p| p.display().to_string())
+                                .unwrap_or_default(),
+                        );
+                        ui.end_row();
+                        ui.label("Type");
+                        ui.label(if entry.is_dir {
+                            "Directory".to_string()
+                        } else {
+                            entry.get_file_type()
+                        });
+                        ui.end_row();
+                        ui.label("MIME type");
+                        ui.monospace(entry.mime_type());
+                        ui.end_row();
+                        if entry.is_symlink {
+                            ui.label("Link target");
+                            ui.label(
+                                std::fs::read_link(&entry.path)
+                                    .map(|t| t.display().to_string())
+                                    .unwrap_or_else(|e| format!("<{}>", e)),
+                            );
+                            ui.end_row();
+                        }
+                        if !entry.is_dir {
+                            ui.label("Size");
+                            ui.label(format!(
+                                "{} ({} bytes)",
+                                bytesize::ByteSize(entry.size),
+                                entry.size
+                            ));
+                            ui.end_row();
+                        } else if let Some(usage) = dir_usage {
+                            let capped = if usage.truncated { "≥ " } else { "" };
+                            ui.label("Total size");
+                            ui.label(format!(
+                                "{}{} in {} files",
+                                capped,
+                                bytesize::ByteSize(usage.bytes),
+                                usage.files
+                            ));
+                            ui.end_row();
+                            if let Some((bytes, files)) = usage.source {
+                                ui.label("Source size")
+                                    .on_hover_text("Without hidden and git-ignored files");
+                                ui.label(format!(
+                                    "{}{} in {} files",
+                                    capped,
+                                    bytesize::ByteSize(bytes),
+                                    files
+                                ));
+                                ui.end_row();
+                            }
+                        }
+                        let times = [
+                            ("Modified", Some(entry.modified)),
+                            ("Accessed", entry.accessed),
+                            ("Created", entry.created),
+                        ];
+                        for (label, time) in times {
+                            let Some(time) = time else {
+                                continue;
+                            };
+                            ui.label(label);
+                            ui.label(
+                                chrono::DateTime::<chrono::Local>::from(time)
+                                    .format("%Y-%m-%d %H:%M:%S")
+                                    .to_string(),
+                            );
+                            ui.end_row();
+                        }
+                        ui.label("Permissions");
+                        ui.monospace(entry.get_permissions_string());
+                        ui.end_row();
+                        if let Some(fs) = &props.filesystem {
+                            let mut flags = Vec::new();
+                            if fs.remote {
+                                flags.push("remote");
+                            }
+                            if fs.read_only {
+                                flags.push("read-only");
+                            }
+                            ui.label("Filesystem");
+                            ui.label(match (&fs.fs_type, flags.is_empty()) {
+                                (Some(fs_type), true) => fs_type.clone(),
+                                (Some(fs_type), false) => {
+                                    format!("{} ({})", fs_type, flags.join(", "))
+                                }
+                                (None, true) => "-".into(),
+                                (None, false) => flags.join(", "),
+                            });
+                            ui.end_row();
+                            ui.label("Mount point");
+                            ui.label(fs.mount_point.display().to_string());
+                            ui.end_row();
+                            if let Some(device) = &fs.device {
+                                ui.label("Device");
+                                ui.monospace(device);
+                                ui.end_row();
+                            }
+                        }
+                        if let Some(owner) = &entry.details.owner {
+                            ui.label("Owner");
+                            ui.monospace(owner);
+                            ui.end_row();
+                        }
+                        if platform::HAS_XATTRS {
+                            ui.label("Security context");
+                            ui.monospace(props.security_context.as_deref().unwrap_or("-"));
+                            ui.end_row();
+                        }
+                    });
+
+                if platform::HAS_XATTRS {
+                    ui.add_space(8.0);
+                    ui.heading("Extended Attributes");
+                    ui.separator();
+                    if props.xattrs.is_empty() {
+                        ui.label(egui::RichText::new("None").weak().italics());
+                    } else {
+                        egui::Grid::new("xattr_grid").striped(true).show(ui, |ui| {
+                            for (name, value) in &props.xattrs {
+                                ui.monospace(name);
+                                ui.monospace(value);
+                                if name.starts_with("user.") {
+                                    if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                                        remove_xattr = Some(name.clone());
+                                    }
+                                } else {
+                                    ui.label("");
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut props.new_xattr_name)
+                                .hint_text("user.name")
+                                .desired_width(120.0),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(&mut props.new_xattr_value)
+                                .hint_text("value")
+                                .desired_width(160.0),
+                        );
+                        let can_add = !props.new_xattr_name.trim().is_empty();
+                        if ui.add_enabled(can_add, egui::Button::new("Add")).clicked() {
+                            add_xattr = true;
+                        }
+                    });
+                }
+
+                ui.separator();
+                if ui.button("Close (Esc)").clicked() {
+                    close = true;
+                }
+            });
+
+        if add_xattr {
+            self.add_properties_xattr();
+        }
+        if let Some(name) = remove_xattr {
+            self.remove_properties_xattr(&name);
+        }
+        if close {
+            self.close_properties();
+        }
+    }
+
+    /// Cache and trash sizes with cleanup buttons (`:maintenance`)
+    pub(crate) fn render_maintenance_modal(&mut self, ctx: &egui::Context) {
+        let AppMode::Maintenance { confirm_empty } = self.mode.mode else {
+            return;
+        };
+
+        let (preview_entries, preview_bytes) = {
+            let cache = self.preview_cache.borrow();
+            (cache.len(), cache.byte_size())
+        };
+        let image_bytes: usize = {
+            let loaders = ctx.loaders();
+            let bytes: usize = loaders
+                .bytes
+                .lock()
+                .iter()
+                .map(|loader| loader.byte_size())
+                .sum();
+            let images: usize = loaders
+                .image
+                .lock()
+                .iter()
+                .map(|loader| loader.byte_size())
+                .sum();
+            let textures: usize = loaders
+                .texture
+                .lock()
+                .iter()
+                .map(|loader| loader.byte_size())
+                .sum();
+            bytes + images + textures
+        };
+        let maintenance = &self.config.maintenance;
+
+        let mut close = false;
+        let mut clear_previews = false;
+        let mut clear_images = false;
+        let mut rescan = false;
+        let mut empty = false;
+        let mut arm_empty = false;
+
+        egui::Window::new("Maintenance")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx))
+            .show(ctx, |ui| {
+                egui::Grid::new("maintenance_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Preview cache");
+                        ui.label(format!(
+                            "{} in {} entries",
+                            bytesize::ByteSize(preview_bytes as u64),
+                            preview_entries
+                        ));
+                        if ui.button("Clear").clicked() {
+                            clear_previews = true;
+                        }
+                        ui.end_row();
+
+                        ui.label("Image cache");
+                        ui.label(bytesize::ByteSize(image_bytes as u64).to_string());
+                        if ui.button("Clear").clicked() {
+                            clear_images = true;
+                        }
+                        ui.end_row();
+
+                        ui.label(platform::TRASH_NAME);
+                        match &self.trash_usage {
+                            None => {
+                                ui.spinner();
+                            }
+                            Some(Ok(usage)) => {
+                                ui.label(format!(
+                                    "{}{} in {} items",
+                                    if usage.truncated { "≥ " } else { "" },
+                                    bytesize::ByteSize(usage.bytes),
+                                    usage.items
+                                ));
+                            }
+                            Some(Err(e)) => {
+                                ui.colored_label(
+                                    style::severity_color(Severity::Error),
+                                    format!("Unavailable: {}", e),
+                                );
+                            }
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button("⟳").on_hover_text("Rescan").clicked() {
+                                rescan = true;
+                            }
+                            let can_empty = maintenance::CAN_EMPTY_TRASH
+                                && matches!(&self.trash_usage, Some(Ok(u)) if u.items > 0);
+                            let label = if confirm_empty {
+                                "Really empty?"
+                            } else {
+                                "Empty"
+                            };
+                            let button = ui.add_enabled(can_empty, egui::Button::new(label));
+                            if button.clicked() {
+                                if confirm_empty {
+                                    empty = true;
+                                } else {
+                                    arm_empty = true;
+                                }
+                            }
+                        });
+                        ui.end_row();
+                    });
+
+                ui.add_space(8.0);
+                ui.heading("Policies");
+                ui.separator();
+                ui.label(format!(
+                    "Preview cache limit: {}",
+                    if maintenance.preview_cache_mb == 0 {
+                        "100 entries".to_string()
+                    } else {
+                        format!("{} MiB", maintenance.preview_cache_mb)
+                    }
+                ));
+                ui.label(if maintenance.trash_retention_days == 0 {
+                    format!("{} auto-cleanup: off", platform::TRASH_NAME)
+                } else {
+                    format!(
+                        "{} auto-cleanup: items older than {} days, at startup",
+                        platform::TRASH_NAME,
+                        maintenance.trash_retention_days
+                    )
+                });
+                ui.label(
+                    egui::RichText::new("Set under [maintenance] in config.toml")
+                        .weak()
+                        .italics(),
+                );
+
+                ui.separator();
+                if ui.button("Close (Esc)").clicked() {
+                    close = true;
+                }
+            });
+
+        if clear_previews {
+            self.preview_cache.borrow_mut().clear();
+            self.ui.set_info("Preview cache cleared".into());
+        }
+        if clear_images {
+            ctx.forget_all_images();
+            self.ui.set_info("Image cache cleared".into());
+        }
+        if rescan {
+            self.scan_trash();
+        }
+        if arm_empty {
+            self.mode.set_mode(AppMode::Maintenance {
+                confirm_empty: true,
+            });
+        }
+        if empty {
+            self.empty_trash();
+            self.mode.set_mode(AppMode::Maintenance {
+                confirm_empty: false,
+            });
+        }
+        if close {
+            self.mode.set_mode(AppMode::Normal);
+        }
+    }
+
+    /// Stacked status toasts in the bottom-right corner; click one to dismiss it
+    pub(crate) fn render_toasts(&mut self, ctx: &egui::Context) {
+        let mut dismiss = None;
+        egui::Area::new("toasts".into())
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -36.0])
+            .order(egui::Order::Foreground)
+            .interactable(true)
+            .show(ctx, |ui| {
+                ui.set_max_width(style::modal_width(ctx) * 0.6);
+                for (index, message) in self.ui.messages.toasts().enumerate() {
+                    let response = egui::Frame::popup(ui.style())
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.colored_label(
+                                    style::severity_color(message.severity),
+                                    message.severity.icon(),
+                                );
+                                ui.add(egui::Label::new(&message.text).wrap());
+                            });
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_text("Click to dismiss");
+                    if response.clicked() {
+                        dismiss = Some(index);
+                    }
+                }
+            });
+        if let Some(index) = dismiss {
+            self.ui.messages.dismiss(index);
+        }
+    }
+
+    /// Task center: the running job plus queued and scheduled ones (`:tasks` or the 📋 status button)
+    pub(crate) fn render_task_center(&mut self, ctx: &egui::Context) {
+        if !self.ui.show_task_center {
+            return;
+        }
+
+        enum Edit {
+            Up(usize),
+            Down(usize),
+            RunNext(usize),
+            Remove(usize),
+            Start(usize, JobStart),
+        }
+        let mut edit: Option<Edit> = None;
+        let mut toggle_pause = None;
+        let mut cancel = false;
+        let mut open = true;
+
+        egui::Window::new("Tasks")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(style::modal_width(ctx))
+            .max_height(style::modal_max_height(ctx))
+            .show(ctx, |ui| {
+                ui.heading("Running");
+                match &self.job {
+                    Some(job) => {
+                        ui.horizontal(|ui| {
+                            ui.label(job.kind.describe(job.total));
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    cancel = ui
+                                        .button("✖ Cancel")
+                                        .on_hover_text(
+                                            "Stop the item in progress and leave the rest",
+                                        )
+                                        .clicked();
+                                    let label = if job.paused {
+                                        "▶ Resume"
+                                    } else {
+                                        "⏸ Pause"
+                                    };
+                                    if ui.button(label).clicked() {
+                                        toggle_pause = Some(!job.paused);
+                                    }
+                                },
+                            );
+                        });
+                        ui.add(
+                            egui::ProgressBar::new(job.processed() as f32 / job.total as f32)
+                                .text(format!("{}/{}", job.processed(), job.total)),
+                        );
+                        if let Some(path) = &job.running {
+                            let name = path.file_name().unwrap_or_default().to_string_lossy();
+                            let text = if job.item_bytes > 0 {
+                                format!("{} · {} done", name, bytesize::ByteSize(job.item_bytes))
+                            } else {
+                                name.to_string()
+                            };
+                            style::truncated_label(ui, egui::RichText::new(text).weak());
+                        }
+                    }
+                    None => {
+                        ui.label(egui::RichText::new("Nothing running").weak().italics());
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.heading(format!("Queued ({})", self.queued_jobs.len()));
+                ui.separator();
+                if self.queued_jobs.is_empty() {
+                    ui.label(
+                        egui::RichText::new(
+                            "Pastes and deletes started while a job runs wait here; \
+                             :paste HH:MM schedules the clipboard for later",
+                        )
+                        .weak()
+                        .italics(),
+                    );
+                }
+                let last = self.queued_jobs.len().saturating_sub(1);
+                for (index, queued) in self.queued_jobs.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}.", index + 1));
+                        style::truncated_label(ui, queued.job.kind.describe(queued.job.total));
+                    });
+                    ui.horizontal(|ui| {
+                        let mut timed = matches!(queued.start, JobStart::At(_));
+                        if ui.checkbox(&mut timed, "Start at").changed() {
+                            let start = if timed {
+                                JobStart::next_at(22, 0)
+                            } else {
+                                Some(JobStart::AfterCurrent)
+                            };
+                            if let Some(start) = start {
+                                edit = Some(Edit::Start(index, start));
+                            }
+                        }
+                        if let JobStart::At(time) = queued.start {
+                            let (mut hour, mut minute) = (time.hour(), time.minute());
+                            let hour_changed = ui
+                                .add(egui::DragValue::new(&mut hour).range(0..=23))
+                                .changed();
+                            ui.label(":");
+                            let minute_changed = ui
+                                .add(egui::DragValue::new(&mut minute).range(0..=59))
+                                .changed();
+                            if hour_changed || minute_changed {
+                                if let Some(start) = JobStart::next_at(hour, minute) {
+                                    edit = Some(Edit::Start(index, start));
+                                }
+                            }
+                        }
+                        ui.label(egui::RichText::new(queued.start.describe()).weak());
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                                edit = Some(Edit::Remove(index));
+                            }
+                            if ui
+                                .small_button("⏭")
+                                .on_hover_text("Run next, without waiting for a time")
+                                .clicked()
+                            {
+                                edit = Some(Edit::RunNext(index));
+                            }
+                            if ui
+                                .add_enabled(index < last, egui::Button::new("⏷").small())
+                                .clicked()
+                            {
+                                edit = Some(Edit::Down(index));
+                            }
+                            if ui
+                                .add_enabled(index > 0, egui::Button::new("⏶").small())
+                                .clicked()
+                            {
+                                edit = Some(Edit::Up(index));
+                            }
+                        });
+                    });
+                    ui.separator();
+                }
+            });
+
+        match edit {
+            Some(Edit::Up(index)) => self.queued_jobs.swap(index, index - 1),
+            Some(Edit::Down(index)) => self.queued_jobs.swap(index, index + 1),
+            Some(Edit::RunNext(index)) => {
+                let mut queued = self.queued_jobs.remove(index);
+                queued.start = JobStart::AfterCurrent;
+                self.queued_jobs.insert(0, queued);
+            }
+            Some(Edit::Remove(index)) => {
+                self.queued_jobs.remove(index);
+            }
+            Some(Edit::Start(index, start)) => self.queued_jobs[index].start = start,
+            None => {}
+        }
+        if let Some(paused) = toggle_pause {
+            self.set_job_paused(paused);
+        }
+        if cancel {
+            self.cancel_job();
+        }
+        if !open {
+            self.ui.show_task_center = false;
+        }
+    }
+
+    /// Window listing past messages, newest first (`:messages` or the 🔔 status button)
+    pub(crate) fn render_message_history(&mut self, ctx: &egui::Context) {
+        if !self.ui.show_message_history {
+            return;
+        }
+
+        let mut open = true;
+        let mut clear = false;
+        egui::Window::new("Messages")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(style::modal_width(ctx))
+            .max_height(style::modal_max_height(ctx))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Show:");
+                    for severity in Severity::ALL {
+                        let label = match severity {
+                            Severity::Info => "All",
+                            Severity::Warning => "Warnings & errors",
+                            Severity::Error => "Errors",
+                        };
+                        if ui
+                            .selectable_label(self.ui.message_history_filter == severity, label)
+                            .clicked()
+                        {
+                            self.ui.message_history_filter = severity;
+                        }
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Clear").clicked() {
+                            clear = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                let filter = self.ui.message_history_filter;
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        egui::Grid::new("message_history_grid")
+                            .num_columns(3)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for message in self
+                                    .ui
+                                    .messages
+                                    .history()
+                                    .rev()
+                                    .filter(|m| m.severity >= filter)
+                                {
+                                    ui.label(
+                                        egui::RichText::new(
+                                            message.timestamp.format("%H:%M:%S").to_string(),
+                                        )
+                                        .weak()
+                                        .monospace(),
+                                    );
+                                    ui.colored_label(
+                                        style::severity_color(message.severity),
+                                        format!(
+                                            "{} {}",
+                                            message.severity.icon(),
+                                            message.severity.label()
+                                        ),
+                                    );
+                                    ui.add(egui::Label::new(&message.text).wrap());
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if clear {
+            self.ui.messages.clear_history();
+        }
+        if !open {
+            self.ui.show_message_history = false;
+        }
     }
 }
@@ -1,14 +1,309 @@
 // Panel rendering for Heike
 // Miller columns layout rendering
 
-use crate::app::Heike;
-use crate::state::{AppMode, ClipboardOp};
+use crate::app::{ContextAction, Heike};
+use crate::io::tools::Tool;
+use crate::platform;
+use crate::state::{AppMode, ClipboardOp, LabelColor, PeekState, SnapshotChange};
 use crate::style;
 use eframe::egui;
+use std::ops::Range;
 use std::path::PathBuf;
-use std::time::Instant;
+
+/// Segments of the hidden middle of a breadcrumb trail, given each segment's width
+///
+/// Keeps the first segment and as many trailing ones as fit next to the `…`
+/// button; `None` when everything fits or there is no middle to hide.
+fn elided_segments(widths: &[f32], ellipsis: f32, available: f32) -> Option<Range<usize>> {
+    if widths.len() < 3 || widths.iter().sum::<f32>() <= available {
+        return None;
+    }
+    let mut used = widths[0] + ellipsis + widths[widths.len() - 1];
+    let mut first_shown = widths.len() - 1;
+    while first_shown > 1 && used + widths[first_shown - 1] <= available {
+        first_shown -= 1;
+        used += widths[first_shown];
+    }
+    (first_shown > 1).then_some(1..first_shown)
+}
 
 impl Heike {
+    /// Yanked or cut items, docked beside the browser while `:clipboard` is on, so a
+    /// large move can be checked and trimmed before it is pasted
+    pub(crate) fn render_clipboard_panel(&mut self, ctx: &egui::Context) {
+        if !self.ui.show_clipboard {
+            return;
+        }
+        let mut items: Vec<PathBuf> = self.browser.clipboard.iter().cloned().collect();
+        items.sort();
+        let op = self.browser.clipboard_op;
+        let action: ContextAction = std::cell::RefCell::new(None);
+
+        egui::SidePanel::right("clipboard_panel")
+            .resizable(true)
+            .default_width(240.0)
+            .show(ctx, |ui| {
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    let title = match op {
+                        Some(ClipboardOp::Cut) => "Cut",
+                        _ => "Copied",
+                    };
+                    ui.strong(format!("{} ({})", title, items.len()));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .small_button("✕")
+                            .on_hover_text("Hide (:clipboard)")
+                            .clicked()
+                        {
+                            *action.borrow_mut() = Some(Box::new(|app: &mut Heike| {
+                                app.ui.show_clipboard = false;
+                            }));
+                        }
+                        if !items.is_empty() && ui.small_button("Clear").clicked() {
+                            *action.borrow_mut() = Some(Box::new(|app: &mut Heike| {
+                                app.clear_clipboard();
+                            }));
+                        }
+                    });
+                });
+                if items.is_empty() {
+                    ui.label(egui::RichText::new("Empty: y copies, x cuts").weak());
+                    return;
+                }
+                let verb = if op == Some(ClipboardOp::Cut) {
+                    "Move"
+                } else {
+                    "Paste"
+                };
+                if ui
+                    .button(format!("{} here (p)", verb))
+                    .on_hover_text(format!(
+                        "Into {}",
+                        self.browser.navigation.current_path.display()
+                    ))
+                    .clicked()
+                {
+                    *action.borrow_mut() = Some(Box::new(|app: &mut Heike| {
+                        app.paste_clipboard();
+                    }));
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for path in &items {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .small_button("✕")
+                                .on_hover_text("Remove from the clipboard")
+                                .clicked()
+                            {
+                                let path = path.clone();
+                                *action.borrow_mut() = Some(Box::new(move |app: &mut Heike| {
+                                    app.remove_from_clipboard(&path);
+                                }));
+                            }
+                            let name = path
+                                .file_name()
+                                .map(|n| n.to_string_lossy().into_owned())
+                                .unwrap_or_else(|| path.display().to_string());
+                            // Moved or deleted since it was yanked: pasting will report it
+                            let missing = !path.exists();
+                            let text = if missing {
+                                egui::RichText::new(&name).strikethrough().weak()
+                            } else {
+                                egui::RichText::new(&name)
+                            };
+                            let response = ui
+                                .add(
+                                    egui::Label::new(text)
+                                        .sense(egui::Sense::click())
+                                        .truncate(),
+                                )
+                                .on_hover_text(format!(
+                                    "{}{}\nDouble-click to reveal",
+                                    path.display(),
+                                    if missing { " (no longer exists)" } else { "" }
+                                ));
+                            if response.double_clicked() && !missing {
+                                let path = path.clone();
+                                *action.borrow_mut() = Some(Box::new(move |app: &mut Heike| {
+                                    if let Some(parent) = path.parent() {
+                                        let parent = parent.to_path_buf();
+                                        app.browser.navigation.pending_selection_path = Some(path);
+                                        app.navigate_to(parent);
+                                    }
+                                }));
+                            }
+                        });
+                    }
+                });
+            });
+
+        if let Some(action) = action.into_inner() {
+            action(self);
+        }
+    }
+
+    /// Staging area of the active tab, shown beside the browser while it holds anything
+    pub(crate) fn render_staging_panel(&mut self, ctx: &egui::Context) {
+        let Some(staged) = self
+            .tabs
+            .get_active()
+            .map(|tab| tab.staged.clone())
+            .filter(|staged| !staged.is_empty())
+        else {
+            return;
+        };
+        let action: ContextAction = std::cell::RefCell::new(None);
+        let here = self
+            .browser
+            .navigation
+            .current_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.browser.navigation.current_path.display().to_string());
+
+        egui::SidePanel::right("staging_panel")
+            .resizable(true)
+            .default_width(220.0)
+            .show(ctx, |ui| {
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.strong(format!("Staged ({})", staged.len()));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("Clear").clicked() {
+                            *action.borrow_mut() = Some(Box::new(|app: &mut Heike| {
+                                app.clear_staged();
+                            }));
+                        }
+                    });
+                });
+                ui.horizontal(|ui| {
+                    for (label, op) in [
+                        ("Copy here", ClipboardOp::Copy),
+                        ("Move here", ClipboardOp::Cut),
+                    ] {
+                        if ui
+                            .button(label)
+                            .on_hover_text(format!("Into {}", here))
+                            .clicked()
+                        {
+                            *action.borrow_mut() = Some(Box::new(move |app: &mut Heike| {
+                                app.drop_staged(op);
+                            }));
+                        }
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for path in &staged {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("✕").on_hover_text("Unstage").clicked() {
+                                let path = path.clone();
+                                *action.borrow_mut() = Some(Box::new(move |app: &mut Heike| {
+                                    app.unstage(&path);
+                                }));
+                            }
+                            let name = path
+                                .file_name()
+                                .map(|n| n.to_string_lossy().into_owned())
+                                .unwrap_or_else(|| path.display().to_string());
+                            let response = style::name_label(
+                                ui,
+                                &name,
+                                egui::Sense::click(),
+                                self.ui.name_ellipsis,
+                            )
+                            .on_hover_text(format!("{}\nDouble-click to reveal", path.display()));
+                            if response.double_clicked() {
+                                let path = path.clone();
+                                *action.borrow_mut() = Some(Box::new(move |app: &mut Heike| {
+                                    if let Some(parent) = path.parent() {
+                                        let parent = parent.to_path_buf();
+                                        app.browser.navigation.pending_selection_path = Some(path);
+                                        app.navigate_to(parent);
+                                    }
+                                }));
+                            }
+                        });
+                    }
+                });
+            });
+
+        if let Some(action) = action.into_inner() {
+            action(self);
+        }
+    }
+
+    /// Path breadcrumbs; deep paths collapse their middle into a `…` dropdown
+    pub(crate) fn render_breadcrumbs(
+        &self,
+        ui: &mut egui::Ui,
+        max_width: f32,
+        next_navigation: &std::cell::RefCell<Option<PathBuf>>,
+    ) {
+        let segments = platform::path_segments(&self.browser.navigation.current_path);
+
+        let spacing = ui.spacing().item_spacing.x;
+        let padding = ui.spacing().button_padding.x * 2.0;
+        let text_width = |ui: &egui::Ui, text: &str| {
+            egui::WidgetText::from(text)
+                .into_galley(
+                    ui,
+                    Some(egui::TextWrapMode::Extend),
+                    f32::INFINITY,
+                    egui::TextStyle::Button,
+                )
+                .size()
+                .x
+        };
+        let separator = text_width(ui, ">") + spacing;
+        let widths: Vec<f32> = segments
+            .iter()
+            .map(|(label, _)| text_width(ui, label) + padding + spacing + separator)
+            .collect();
+        let ellipsis = text_width(ui, "…") + padding + spacing + separator;
+        let hidden = elided_segments(&widths, ellipsis, max_width);
+
+        egui::ScrollArea::horizontal()
+            .id_salt("breadcrumbs")
+            .max_width(max_width)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for (idx, (label, path)) in segments.iter().enumerate() {
+                        if let Some(hidden) = &hidden {
+                            if hidden.contains(&idx) {
+                                if idx == hidden.start {
+                                    ui.menu_button("…", |ui| {
+                                        for (label, path) in &segments[hidden.clone()] {
+                                            if ui.button(format!("📁 {}", label)).clicked() {
+                                                *next_navigation.borrow_mut() = Some(path.clone());
+                                                ui.close();
+                                            }
+                                        }
+                                    })
+                                    .response
+                                    .on_hover_text(
+                                        self.browser.navigation.current_path.display().to_string(),
+                                    );
+                                    ui.label(">");
+                                }
+                                continue;
+                            }
+                        }
+                        if ui
+                            .button(label)
+                            .on_hover_text(path.display().to_string())
+                            .clicked()
+                        {
+                            *next_navigation.borrow_mut() = Some(path.clone());
+                        }
+                        ui.label(">");
+                    }
+                });
+            });
+    }
+
     pub(crate) fn render_divider(&mut self, ui: &mut egui::Ui, index: usize) {
         let response = ui.allocate_response(ui.available_size(), egui::Sense::drag());
 
@@ -43,6 +338,7 @@ impl Heike {
         &self,
         ui: &mut egui::Ui,
         next_navigation: &std::cell::RefCell<Option<PathBuf>>,
+        context_action: &ContextAction,
     ) {
         ui.add_space(4.0);
         ui.vertical_centered(|ui| {
@@ -66,29 +362,135 @@ impl Heike {
                     .column(Column::auto().at_least(30.0))
                     .column(Column::remainder().clip(true))
                     .body(|body| {
-                        body.rows(24.0, self.entries.parent_entries.len(), |mut row| {
-                            let entry = &self.entries.parent_entries[row.index()];
-                            let is_active = entry.path == self.navigation.current_path;
+                        body.rows(
+                            24.0,
+                            self.browser.entries.parent_entries.len(),
+                            |mut row| {
+                                let entry = &self.browser.entries.parent_entries[row.index()];
+                                let is_active = entry.path == self.browser.navigation.current_path;
+                                let is_peeked = self
+                                    .peek
+                                    .as_ref()
+                                    .is_some_and(|peek| peek.path == entry.path);
 
-                            let icon_color = if is_active { accent } else { default_color };
+                                let icon_color = if is_active { accent } else { default_color };
+
+                                row.col(|ui| {
+                                    ui.label(
+                                        egui::RichText::new(entry.get_icon())
+                                            .size(14.0)
+                                            .color(icon_color),
+                                    );
+                                });
+                                row.col(|ui| {
+                                    let text_color = if is_active { accent } else { default_color };
+                                    let mut text =
+                                        egui::RichText::new(entry.display_name()).color(text_color);
+                                    if is_peeked {
+                                        text = text.underline();
+                                    }
+                                    let response = style::name_label(
+                                        ui,
+                                        text,
+                                        egui::Sense::click(),
+                                        self.ui.name_ellipsis,
+                                    );
+                                    if response.clicked() {
+                                        let path = entry.path.clone();
+                                        if ui.input(|i| i.modifiers.command) && entry.is_dir {
+                                            // Ctrl-click only shows the sibling in the listing pane
+                                            *context_action.borrow_mut() =
+                                                Some(Box::new(move |app| app.peek_at(path)));
+                                        } else {
+                                            // Navigate to the clicked directory in the parent pane
+                                            *next_navigation.borrow_mut() = Some(path);
+                                        }
+                                    }
+                                });
+                            },
+                        );
+                    });
+            });
+    }
 
+    /// Read-only listing of a peeked sibling; clicking an entry opens the sibling with
+    /// the cursor on it, Esc returns to the current directory
+    fn render_peek_listing(
+        &self,
+        ui: &mut egui::Ui,
+        peek: &PeekState,
+        context_action: &ContextAction,
+    ) {
+        ui.horizontal(|ui| {
+            let name = peek
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| peek.path.display().to_string());
+            ui.label(egui::RichText::new(format!("Peeking at {}", name)).strong());
+            if ui.button("Open").clicked() {
+                *context_action.borrow_mut() = Some(Box::new(|app| app.open_peek(None)));
+            }
+            if ui.button("Back").clicked() {
+                *context_action.borrow_mut() = Some(Box::new(|app| app.peek = None));
+            }
+        });
+        ui.label(
+            egui::RichText::new("Enter opens it, Esc goes back")
+                .small()
+                .weak(),
+        );
+        ui.separator();
+
+        let entries = match &peek.entries {
+            None => {
+                ui.spinner();
+                return;
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::LIGHT_RED, e);
+                return;
+            }
+            Some(Ok(entries)) => entries,
+        };
+        if entries.is_empty() {
+            ui.weak("Empty folder");
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_salt("peek_scroll")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                use egui_extras::{Column, TableBuilder};
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .resizable(false)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(Column::initial(30.0))
+                    .column(Column::remainder().clip(true))
+                    .column(Column::exact(72.0))
+                    .body(|body| {
+                        body.rows(24.0, entries.len(), |mut row| {
+                            let entry = &entries[row.index()];
                             row.col(|ui| {
-                                ui.label(
-                                    egui::RichText::new(entry.get_icon())
-                                        .size(14.0)
-                                        .color(icon_color),
-                                );
+                                ui.label(egui::RichText::new(entry.get_icon()).size(14.0));
                             });
                             row.col(|ui| {
-                                let text_color = if is_active { accent } else { default_color };
-                                let response = style::truncated_label_with_sense(
+                                let response = style::name_label(
                                     ui,
-                                    egui::RichText::new(entry.display_name()).color(text_color),
+                                    egui::RichText::new(entry.display_name()),
                                     egui::Sense::click(),
+                                    self.ui.name_ellipsis,
                                 );
                                 if response.clicked() {
-                                    // Navigate to the clicked directory in the parent pane
-                                    *next_navigation.borrow_mut() = Some(entry.path.clone());
+                                    let path = entry.path.clone();
+                                    *context_action.borrow_mut() =
+                                        Some(Box::new(move |app| app.open_peek(Some(path))));
+                                }
+                            });
+                            row.col(|ui| {
+                                if !entry.is_dir {
+                                    ui.label(bytesize::ByteSize(entry.size).to_string());
                                 }
                             });
                         });
@@ -101,18 +503,69 @@ impl Heike {
         ui: &mut egui::Ui,
         next_navigation: &std::cell::RefCell<Option<PathBuf>>,
         next_selection: &std::cell::RefCell<Option<usize>>,
-        context_action: &std::cell::RefCell<Option<Box<dyn FnOnce(&mut Self)>>>,
+        context_action: &ContextAction,
         ctx: &egui::Context,
     ) {
+        if let Some(peek) = &self.peek {
+            self.render_peek_listing(ui, peek, context_action);
+            return;
+        }
+
         // Detect manual scrolling in the central panel
         if ui.ui_contains_pointer()
             && ctx.input(|i| {
                 i.smooth_scroll_delta != egui::Vec2::ZERO || i.raw_scroll_delta != egui::Vec2::ZERO
             })
         {
-            self.selection.disable_autoscroll = true;
+            self.browser.selection.disable_autoscroll = true;
         }
 
+        // Resolved once per frame; context menus disable items whose tool is missing
+        let editor_missing = self.capabilities.missing_reason(Tool::Editor);
+        let terminal_missing = self.capabilities.missing_reason(Tool::Terminal);
+        // Largest file in the listing scales the size bars
+        let size_bars = self.config.ui.size_bars;
+        let max_file_size = self
+            .browser
+            .entries
+            .visible_entries
+            .iter()
+            .filter(|e| !e.is_dir)
+            .map(|e| e.size)
+            .max()
+            .unwrap_or(0);
+        // Flattened listings name entries by relative path, which snapshots don't record
+        let snapshot = self
+            .snapshots
+            .get(&self.browser.navigation.current_path)
+            .filter(|snapshot| snapshot.highlight && !self.ui.flatten);
+
+        // Group header rows sit above each group's first entry
+        let sort_by = self.ui.sort_options.sort_by;
+        let groups = if self.ui.group_headers.contains(&sort_by) {
+            crate::state::group_starts(
+                &self.browser.entries.visible_entries,
+                sort_by,
+                chrono::Local::now().date_naive(),
+            )
+        } else {
+            Vec::new()
+        };
+        let header_rows: Vec<usize> = groups
+            .iter()
+            .enumerate()
+            .map(|(i, (start, _))| start + i)
+            .collect();
+        // Headers at or above `row`, so a row is an entry index or (when it is one) a header
+        let headers_through = |row: usize| header_rows.partition_point(|&h| h <= row);
+        let row_count = self.browser.entries.visible_entries.len() + groups.len();
+        let top_row = std::cell::Cell::new(usize::MAX);
+        // The column header names the group of the top row, so it stays in view
+        let top_group = headers_through(self.ui.list_top_row)
+            .checked_sub(1)
+            .and_then(|group| groups.get(group))
+            .map(|(_, heading)| heading.as_str());
+
         egui::ScrollArea::vertical()
             .id_salt("current_scroll")
             .auto_shrink([false, false])
@@ -123,12 +576,14 @@ impl Heike {
                     .resizable(false)
                     .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
                     .column(Column::initial(30.0))
-                    .column(Column::remainder().clip(true));
+                    .column(Column::remainder().clip(true))
+                    .column(Column::exact(72.0));
 
                 // Only scroll to selected row if autoscroll is not disabled
-                if !self.selection.disable_autoscroll {
-                    if let Some(idx) = self.selection.selected_index {
-                        table = table.scroll_to_row(idx, None);
+                if !self.browser.selection.disable_autoscroll {
+                    if let Some(idx) = self.browser.selection.selected_index {
+                        let row = idx + groups.partition_point(|(start, _)| *start <= idx);
+                        table = table.scroll_to_row(row, None);
                     }
                 }
 
@@ -139,17 +594,45 @@ impl Heike {
                         });
                         header.col(|ui| {
                             ui.label("Name");
+                            if let Some(heading) = top_group {
+                                ui.label(egui::RichText::new(format!("· {}", heading)).weak());
+                            }
+                        });
+                        header.col(|ui| {
+                            ui.label("Size");
                         });
                     })
                     .body(|body| {
-                        body.rows(24.0, self.entries.visible_entries.len(), |mut row| {
-                            let row_index = row.index();
-                            let entry = &self.entries.visible_entries[row_index];
-                            let is_focused = self.selection.selected_index == Some(row_index);
+                        body.rows(24.0, row_count, |mut row| {
+                            top_row.set(top_row.get().min(row.index()));
+                            let headers = headers_through(row.index());
+                            if headers > 0 && header_rows[headers - 1] == row.index() {
+                                let (start, heading) = &groups[headers - 1];
+                                let end = groups
+                                    .get(headers)
+                                    .map_or(self.browser.entries.visible_entries.len(), |g| g.0);
+                                row.col(|_| {});
+                                row.col(|ui| {
+                                    ui.label(egui::RichText::new(heading).strong());
+                                });
+                                row.col(|ui| {
+                                    ui.label(
+                                        egui::RichText::new(format!("{}", end - start))
+                                            .small()
+                                            .weak(),
+                                    );
+                                });
+                                return;
+                            }
+                            // Index into the listing, past the group headers above it
+                            let row_index = row.index() - headers;
+                            let entry = &self.browser.entries.visible_entries[row_index];
+                            let is_focused =
+                                self.browser.selection.selected_index == Some(row_index);
                             let is_multi_selected =
-                                self.selection.multi_selection.contains(&entry.path);
-                            let is_cut = self.clipboard_op == Some(ClipboardOp::Cut)
-                                && self.clipboard.contains(&entry.path);
+                                self.browser.selection.multi_selection.contains(&entry.path);
+                            let is_cut = self.browser.clipboard_op == Some(ClipboardOp::Cut)
+                                && self.browser.clipboard.contains(&entry.path);
 
                             if is_multi_selected || is_focused {
                                 row.set_selected(true);
@@ -164,7 +647,7 @@ impl Heike {
                                 }
 
                                 // Git status indicator
-                                if let Some(status) = &entry.git_status {
+                                if let Some(status) = &entry.details.git_status {
                                     let status_char = match status {
                                         crate::entry::GitStatus::Modified => 'M',
                                         crate::entry::GitStatus::Untracked => '?',
@@ -176,11 +659,30 @@ impl Heike {
                                     icon_text.push(' ');
                                 }
 
+                                let snapshot_change =
+                                    snapshot.and_then(|snapshot| snapshot.change(entry));
+                                if let Some(change) = snapshot_change {
+                                    icon_text.push(match change {
+                                        SnapshotChange::Added => '+',
+                                        SnapshotChange::Changed => '~',
+                                    });
+                                    icon_text.push(' ');
+                                }
+
                                 icon_text.push_str(entry.get_icon());
 
                                 let icon_color = if is_focused {
                                     egui::Color32::YELLOW
-                                } else if let Some(status) = &entry.git_status {
+                                } else if let Some(change) = snapshot_change {
+                                    match change {
+                                        SnapshotChange::Added => {
+                                            egui::Color32::from_rgb(100, 200, 100)
+                                        }
+                                        SnapshotChange::Changed => {
+                                            egui::Color32::from_rgb(230, 180, 80)
+                                        }
+                                    }
+                                } else if let Some(status) = &entry.details.git_status {
                                     match status {
                                         crate::entry::GitStatus::Modified => {
                                             egui::Color32::from_rgb(255, 180, 50)
@@ -222,11 +724,24 @@ impl Heike {
                                     // Keep default text color for files
                                 }
 
-                                let response = style::truncated_label_with_sense(
+                                if let Some(label) = self.labels.get(&entry.path) {
+                                    ui.colored_label(style::label_color(label), "●");
+                                }
+
+                                let response = style::name_label(
                                     ui,
                                     text,
                                     egui::Sense::click(),
+                                    self.ui.name_ellipsis,
                                 );
+                                // Dimmed target after the arrow, in whatever room is left
+                                if let Some(link) = &entry.link {
+                                    style::truncated_label(
+                                        ui,
+                                        egui::RichText::new(link.target.display().to_string())
+                                            .weak(),
+                                    );
+                                }
 
                                 // Single click for selection only
                                 if response.clicked() {
@@ -235,7 +750,8 @@ impl Heike {
 
                                 // Double click to open/navigate
                                 if response.double_clicked() {
-                                    if let Some(entry) = self.entries.visible_entries.get(row_index)
+                                    if let Some(entry) =
+                                        self.browser.entries.visible_entries.get(row_index)
                                     {
                                         *next_navigation.borrow_mut() = Some(entry.path.clone());
                                     }
@@ -246,31 +762,168 @@ impl Heike {
                                 let entry_path = entry.path.clone();
                                 let entry_is_dir = entry.is_dir;
                                 let entry_name = entry.name.clone();
-                                let entry_size = entry.size;
-                                let entry_modified = entry.modified;
-                                let entry_perms = entry.get_permissions_string();
                                 response.context_menu(|ui| {
                                     if ui.button("📂 Open").clicked() {
                                         if entry_is_dir {
                                             *next_navigation.borrow_mut() =
                                                 Some(entry_path.clone());
                                         } else {
-                                            let _ = open::that(&entry_path);
+                                            let path = entry_path.clone();
+                                            *context_action.borrow_mut() =
+                                                Some(Box::new(move |app: &mut Self| {
+                                                    app.open_file(&path);
+                                                }));
                                         }
                                         ui.close();
                                     }
 
+                                    if ui
+                                        .add_enabled(
+                                            !entry_is_dir,
+                                            egui::Button::new(
+                                                "🧰 Open With Command… (Shift+Enter)",
+                                            ),
+                                        )
+                                        .clicked()
+                                    {
+                                        let path = entry_path.clone();
+                                        *context_action.borrow_mut() =
+                                            Some(Box::new(move |app: &mut Self| {
+                                                // Apply to the whole selection when this row is part of it
+                                                let paths = if app
+                                                    .browser
+                                                    .selection
+                                                    .multi_selection
+                                                    .contains(&path)
+                                                {
+                                                    app.selected_files()
+                                                } else {
+                                                    vec![path]
+                                                };
+                                                app.start_open_with(paths);
+                                            }));
+                                        ui.close();
+                                    }
+
+                                    let editor_button = ui
+                                        .add_enabled(
+                                            editor_missing.is_none() && !entry_is_dir,
+                                            egui::Button::new("📝 Open in Editor"),
+                                        )
+                                        .on_disabled_hover_text(
+                                            editor_missing.as_deref().unwrap_or("Not a file"),
+                                        );
+                                    if editor_button.clicked() {
+                                        let path = entry_path.clone();
+                                        *context_action.borrow_mut() =
+                                            Some(Box::new(move |app: &mut Self| {
+                                                app.open_in_editor(path)
+                                            }));
+                                        ui.close();
+                                    }
+
+                                    let terminal_button = ui
+                                        .add_enabled(
+                                            terminal_missing.is_none(),
+                                            egui::Button::new("🖥 Open in Terminal"),
+                                        )
+                                        .on_disabled_hover_text(
+                                            terminal_missing.as_deref().unwrap_or_default(),
+                                        );
+                                    if terminal_button.clicked() {
+                                        let dir = if entry_is_dir {
+                                            entry_path.clone()
+                                        } else {
+                                            entry_path
+                                                .parent()
+                                                .map(|p| p.to_path_buf())
+                                                .unwrap_or_else(|| entry_path.clone())
+                                        };
+                                        *context_action.borrow_mut() =
+                                            Some(Box::new(move |app: &mut Self| {
+                                                if let Err(e) = app.capabilities.open_terminal(&dir)
+                                                {
+                                                    app.ui.set_error(format!(
+                                                        "Could not open terminal: {}",
+                                                        e
+                                                    ));
+                                                }
+                                            }));
+                                        ui.close();
+                                    }
+
+                                    if platform::HAS_QUICK_LOOK
+                                        && ui.button("👁 Quick Look").clicked()
+                                    {
+                                        let path = entry_path.clone();
+                                        *context_action.borrow_mut() =
+                                            Some(Box::new(move |app: &mut Self| {
+                                                if let Err(e) = platform::quick_look(&path) {
+                                                    app.ui.set_error(format!(
+                                                        "Quick Look failed: {}",
+                                                        e
+                                                    ));
+                                                }
+                                            }));
+                                        ui.close();
+                                    }
+
+                                    if platform::HAS_SHELL_VERBS {
+                                        if ui.button("🗔 Open with…").clicked() {
+                                            if let Err(e) = platform::open_with(&entry_path) {
+                                                let msg = format!("Open with failed: {}", e);
+                                                *context_action.borrow_mut() =
+                                                    Some(Box::new(move |app: &mut Self| {
+                                                        app.ui.set_error(msg);
+                                                    }));
+                                            }
+                                            ui.close();
+                                        }
+                                        if ui.button("🗔 Windows Properties").clicked() {
+                                            if let Err(e) = platform::show_properties(&entry_path) {
+                                                let msg = format!("Properties failed: {}", e);
+                                                *context_action.borrow_mut() =
+                                                    Some(Box::new(move |app: &mut Self| {
+                                                        app.ui.set_error(msg);
+                                                    }));
+                                            }
+                                            ui.close();
+                                        }
+                                    }
+
+                                    ui.menu_button("🏷 Label", |ui| {
+                                        let mut choice = None;
+                                        for label in LabelColor::ALL {
+                                            let text =
+                                                egui::RichText::new(format!("● {}", label.name()))
+                                                    .color(style::label_color(label));
+                                            if ui.button(text).clicked() {
+                                                choice = Some(Some(label));
+                                            }
+                                        }
+                                        if ui.button("None").clicked() {
+                                            choice = Some(None);
+                                        }
+                                        if let Some(label) = choice {
+                                            *next_selection.borrow_mut() = Some(row_index);
+                                            *context_action.borrow_mut() =
+                                                Some(Box::new(move |app: &mut Self| {
+                                                    app.set_label(label);
+                                                }));
+                                            ui.close();
+                                        }
+                                    });
+
                                     ui.separator();
 
                                     if ui.button("📋 Copy (y)").clicked() {
                                         let path = entry_path.clone();
                                         *context_action.borrow_mut() =
                                             Some(Box::new(move |app: &mut Self| {
-                                                app.clipboard.clear();
-                                                app.clipboard.insert(path);
-                                                app.clipboard_op = Some(ClipboardOp::Copy);
-                                                app.ui.info_message =
-                                                    Some(("Copied 1 file".into(), Instant::now()));
+                                                app.browser.clipboard.clear();
+                                                app.browser.clipboard.insert(path);
+                                                app.browser.clipboard_op = Some(ClipboardOp::Copy);
+                                                app.ui.set_info("Copied 1 file".into());
                                             }));
                                         ui.close();
                                     }
@@ -279,11 +932,10 @@ impl Heike {
                                         let path = entry_path.clone();
                                         *context_action.borrow_mut() =
                                             Some(Box::new(move |app: &mut Self| {
-                                                app.clipboard.clear();
-                                                app.clipboard.insert(path);
-                                                app.clipboard_op = Some(ClipboardOp::Cut);
-                                                app.ui.info_message =
-                                                    Some(("Cut 1 file".into(), Instant::now()));
+                                                app.browser.clipboard.clear();
+                                                app.browser.clipboard.insert(path);
+                                                app.browser.clipboard_op = Some(ClipboardOp::Cut);
+                                                app.ui.set_info("Cut 1 file".into());
                                             }));
                                         ui.close();
                                     }
@@ -300,7 +952,11 @@ impl Heike {
 
                                     if ui.button("✏️ Rename (r)").clicked() {
                                         *next_selection.borrow_mut() = Some(row_index);
-                                        let name = entry_name.clone();
+                                        let name = entry
+                                            .path
+                                            .file_name()
+                                            .map(|n| n.to_string_lossy().into_owned())
+                                            .unwrap_or_else(|| entry_name.clone());
                                         *context_action.borrow_mut() =
                                             Some(Box::new(move |app: &mut Self| {
                                                 app.mode.command_buffer = name;
@@ -321,32 +977,50 @@ impl Heike {
 
                                     ui.separator();
 
-                                    if ui.button("ℹ️ Properties").clicked() {
-                                        let size = entry_size;
-                                        let modified = entry_modified;
-                                        let is_dir = entry_is_dir;
-                                        let perms = entry_perms.clone();
+                                    if ui.button("ℹ️ Properties (Alt+Enter)").clicked() {
+                                        let path = entry_path.clone();
                                         *context_action.borrow_mut() =
                                             Some(Box::new(move |app: &mut Self| {
-                                                app.ui.info_message =
-                                                    Some((
-                                                        format!(
-                                            "{} | {} | {} | Modified: {}",
-                                            if is_dir { "Directory" } else { "File" },
-                                            bytesize::ByteSize(size),
-                                            perms,
-                                            chrono::DateTime::<chrono::Local>::from(modified)
-                                                .format("%Y-%m-%d %H:%M")
-                                        ),
-                                                        Instant::now(),
-                                                    ));
+                                                app.open_properties(path);
                                             }));
                                         ui.close();
                                     }
                                 });
                             });
+
+                            // Size column, with an optional bar scaled to the largest file
+                            row.col(|ui| {
+                                if entry.is_dir {
+                                    return;
+                                }
+                                if size_bars && max_file_size > 0 {
+                                    let rect = ui.max_rect().shrink2(egui::vec2(0.0, 4.0));
+                                    let fraction = entry.size as f32 / max_file_size as f32;
+                                    let mut bar = rect;
+                                    bar.set_width(rect.width() * fraction);
+                                    ui.painter().rect_filled(
+                                        bar,
+                                        2.0,
+                                        ui.visuals().selection.bg_fill.gamma_multiply(0.35),
+                                    );
+                                }
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        ui.label(
+                                            egui::RichText::new(
+                                                bytesize::ByteSize(entry.size).to_string(),
+                                            )
+                                            .small(),
+                                        );
+                                    },
+                                );
+                            });
                         });
                     });
             });
+        if top_row.get() != usize::MAX {
+            self.ui.list_top_row = top_row.get();
+        }
     }
 }
@@ -1,27 +1,77 @@
 // Preview handler trait and context for extensible file preview system
 
 use crate::entry::FileEntry;
-use crate::style::Theme;
-use crate::view::preview::PreviewCache;
+use crate::style::Ellipsis;
+use crate::view::preview::{ArchiveListing, PreviewCache, PreviewListing};
 use eframe::egui;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
 use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
 
+/// Rest on an entry this long before an expensive preview loads, so j/k can pass over it
+pub const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Text up to this size is cheap enough to preview without waiting
+pub const CHEAP_PREVIEW_BYTES: u64 = 64 * 1024;
+
 /// Context passed to preview handlers containing shared resources
 pub struct PreviewContext<'a> {
     pub syntax_set: &'a SyntaxSet,
     pub theme_set: &'a ThemeSet,
-    pub theme: Theme,
+    /// Name of the syntect theme for code previews
+    pub code_theme: &'a str,
     pub show_hidden: bool,
+    pub name_ellipsis: Ellipsis,
+    /// Packed size, ratio and CRC columns in archive listings
+    pub archive_details: bool,
+    /// Filter applied to directory listings, empty for none
+    pub filter: &'a str,
     pub last_selection_change: Instant,
     pub directory_selections: &'a HashMap<PathBuf, usize>,
     pub next_navigation: &'a std::cell::RefCell<Option<PathBuf>>,
     pub pending_selection: &'a std::cell::RefCell<Option<PathBuf>>,
     pub preview_cache: &'a RefCell<PreviewCache>,
+    /// Set by the Metadata tab to ask the worker for a file checksum
+    pub checksum_request: &'a RefCell<Option<PathBuf>>,
+    /// Set by the directory preview to ask the worker for a recursive size
+    pub dir_size_request: &'a RefCell<Option<PathBuf>>,
+    /// Set by the archive preview to ask the worker to count every member
+    pub archive_count_request: &'a RefCell<Option<PathBuf>>,
+    /// The listing last read for the directory preview, whichever directory it was
+    pub dir_listing: Option<&'a PreviewListing>,
+    /// Set by the directory preview to ask the worker for a listing as of the given mtime
+    pub dir_listing_request: &'a RefCell<Option<(PathBuf, SystemTime)>>,
+    /// The listing last read for the archive preview, whichever archive it was
+    pub archive_listing: Option<&'a ArchiveListing>,
+    /// Set by the archive preview to ask the worker for the page starting at the given position
+    pub archive_page_request: &'a RefCell<Option<(PathBuf, SystemTime, u64)>>,
+    /// Set by the Git tab to ask the worker for the last commit touching the entry
+    pub last_commit_request: &'a RefCell<Option<PathBuf>>,
+    /// First visible line of a text preview, used to open the editor at that line
+    pub top_line: &'a RefCell<Option<(PathBuf, usize)>>,
+}
+
+/// Tabs of the preview pane
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreviewTab {
+    Content,
+    Metadata,
+    Permissions,
+    Git,
+}
+
+impl PreviewTab {
+    pub fn label(self) -> &'static str {
+        match self {
+            PreviewTab::Content => "Content",
+            PreviewTab::Metadata => "Metadata",
+            PreviewTab::Permissions => "Permissions",
+            PreviewTab::Git => "Git",
+        }
+    }
 }
 
 /// Trait for file preview handlers
@@ -63,4 +113,30 @@ pub trait PreviewHandler: Send + Sync {
     fn enabled_by_default(&self) -> bool {
         true
     }
+
+    /// Tabs this handler populates
+    ///
+    /// Permissions and Git tabs are filled by the core. Handlers that list
+    /// `Metadata` get `render_metadata` called below the generic file details.
+    fn tabs(&self) -> &'static [PreviewTab] {
+        &[PreviewTab::Content]
+    }
+
+    /// How long the selection must rest on `entry` before this handler renders it
+    ///
+    /// Cheap handlers return zero so their previews show at once; the default
+    /// suits handlers that decode or parse the whole file.
+    fn debounce(&self, _entry: &FileEntry) -> Duration {
+        PREVIEW_DEBOUNCE
+    }
+
+    /// Render handler-specific metadata (EXIF, ID3, document info, ...)
+    fn render_metadata(
+        &self,
+        _ui: &mut egui::Ui,
+        _entry: &FileEntry,
+        _context: &PreviewContext,
+    ) -> Result<(), String> {
+        Ok(())
+    }
 }
@@ -1,12 +1,10 @@
 // Archive preview handler (zip, tar, gz, etc.)
 
 use crate::entry::FileEntry;
+use crate::io::archive::ArchiveTotals;
 use crate::view::preview::handler::{PreviewContext, PreviewHandler};
+use crate::view::preview::{ArchiveListing, ARCHIVE_TOTALS_SECTION};
 use eframe::egui;
-use flate2::read::GzDecoder;
-use std::fs;
-use tar::Archive;
-use zip::ZipArchive;
 
 pub struct ArchivePreviewHandler;
 
@@ -15,118 +13,77 @@ impl ArchivePreviewHandler {
         Self
     }
 
-    const MAX_PREVIEW_ITEMS: usize = 100;
-    const ARCHIVE_SIZE_LIMIT: u64 = 100 * 1024 * 1024; // 100MB
-
     fn is_archive_extension(ext: &str) -> bool {
-        matches!(ext, "zip" | "tar" | "gz" | "tgz" | "bz2" | "xz")
+        matches!(
+            ext,
+            "zip" | "tar" | "gz" | "tgz" | "bz2" | "tbz" | "tbz2" | "xz" | "txz" | "7z" | "rar"
+        )
     }
 
-    /// Extract archive contents as a cacheable string
-    /// Format: "total:<N>|+" on first line, then "D|F\tname\tsize" per item
-    fn extract_contents(entry: &FileEntry) -> Result<String, String> {
-        let result = if entry.extension == "zip" {
-            fs::File::open(&entry.path).ok().and_then(|file| {
-                ZipArchive::new(file).ok().map(|mut archive| {
-                    let total = archive.len();
-                    let mut items = Vec::new();
-                    for i in 0..total.min(Self::MAX_PREVIEW_ITEMS) {
-                        if let Ok(file) = archive.by_index(i) {
-                            items.push((file.name().to_string(), file.size(), file.is_dir()));
-                        }
-                    }
-                    (items, Some(total))
-                })
-            })
-        } else if entry.extension == "tar" || entry.extension == "gz" || entry.extension == "tgz" {
-            fs::File::open(&entry.path).ok().and_then(|file| {
-                let reader: Box<dyn std::io::Read> =
-                    if entry.extension == "gz" || entry.extension == "tgz" {
-                        Box::new(GzDecoder::new(file))
-                    } else {
-                        Box::new(file)
-                    };
-
-                Archive::new(reader).entries().ok().map(|entries| {
-                    let items: Vec<_> = entries
-                        .filter_map(|e| e.ok())
-                        .take(Self::MAX_PREVIEW_ITEMS + 1)
-                        .map(|e| {
-                            let size = e.header().size().unwrap_or(0);
-                            let path = e
-                                .path()
-                                .ok()
-                                .map(|p| p.to_string_lossy().to_string())
-                                .unwrap_or_default();
-                            let is_dir = e.header().entry_type().is_dir();
-                            (path, size, is_dir)
-                        })
-                        .collect();
-
-                    let has_more = items.len() > Self::MAX_PREVIEW_ITEMS;
-                    let shown_count = items.len().min(Self::MAX_PREVIEW_ITEMS);
-                    let items_to_show: Vec<_> = if has_more {
-                        items.into_iter().take(Self::MAX_PREVIEW_ITEMS).collect()
-                    } else {
-                        items
-                    };
-
-                    (
-                        items_to_show,
-                        if has_more { None } else { Some(shown_count) },
-                    )
-                })
-            })
+    /// Packed size as a share of the original, e.g. "38%"
+    fn ratio(size: u64, packed: u64) -> String {
+        if size == 0 {
+            "–".to_string()
         } else {
-            None
-        };
-
-        match result {
-            Some((items, total)) => {
-                let mut lines = Vec::with_capacity(items.len() + 1);
-                // First line: total count
-                match total {
-                    Some(t) => lines.push(format!("total:{}", t)),
-                    None => lines.push(format!("total:{}+", items.len())),
-                }
-                // Subsequent lines: type\tname\tsize
-                for (name, size, is_dir) in items {
-                    let type_char = if is_dir { 'D' } else { 'F' };
-                    lines.push(format!("{}\t{}\t{}", type_char, name, size));
-                }
-                Ok(lines.join("\n"))
-            }
-            None => Err("Failed to read archive".to_string()),
+            format!("{:.0}%", packed as f64 * 100.0 / size as f64)
         }
     }
 
-    /// Parse cached content back into items and total
-    fn parse_cached(content: &str) -> Option<(Vec<(String, u64, bool)>, Option<usize>)> {
-        let mut lines = content.lines();
-        let first_line = lines.next()?;
-
-        let total = if let Some(rest) = first_line.strip_prefix("total:") {
-            if rest.ends_with('+') {
-                None // Unknown total
+    /// Total line over the listing, with "Count all" for tar streams of unknown length
+    fn render_count(
+        ui: &mut egui::Ui,
+        entry: &FileEntry,
+        listing: &ArchiveListing,
+        context: &PreviewContext,
+    ) {
+        let shown = listing.members.len();
+        if let Some(total) = listing.total {
+            if total > shown {
+                ui.label(format!(
+                    "Archive contains {} items (showing first {}):",
+                    total, shown
+                ));
             } else {
-                rest.parse().ok()
+                ui.label(format!("Archive contains {} items:", total));
             }
-        } else {
-            return None;
-        };
-
-        let items: Vec<_> = lines
-            .filter_map(|line| {
-                let mut parts = line.splitn(3, '\t');
-                let type_char = parts.next()?;
-                let name = parts.next()?.to_string();
-                let size: u64 = parts.next()?.parse().ok()?;
-                let is_dir = type_char == "D";
-                Some((name, size, is_dir))
-            })
-            .collect();
-
-        Some((items, total))
+            return;
+        }
+        let counted = context.preview_cache.borrow().get_section(
+            &entry.path,
+            ARCHIVE_TOTALS_SECTION,
+            entry.modified,
+        );
+        match counted.as_deref() {
+            None => {
+                ui.label(format!("Showing the first {} items", shown));
+                if ui
+                    .small_button("Count all")
+                    .on_hover_text("Read the whole archive in the background to total its members")
+                    .clicked()
+                {
+                    *context.archive_count_request.borrow_mut() = Some(entry.path.clone());
+                }
+            }
+            Some("") => {
+                ui.label(format!("Showing the first {} items", shown));
+                ui.spinner();
+                ui.label(egui::RichText::new("counting…").weak());
+            }
+            Some(value) => match ArchiveTotals::from_section(value) {
+                Some(totals) => {
+                    ui.label(format!(
+                        "Archive contains {} items, {} unpacked (showing first {}):",
+                        totals.members,
+                        bytesize::ByteSize(totals.bytes),
+                        shown
+                    ));
+                }
+                None => {
+                    ui.label(format!("Showing the first {} items", shown));
+                    ui.label(egui::RichText::new(value).weak());
+                }
+            },
+        }
     }
 }
 
@@ -145,106 +102,147 @@ impl PreviewHandler for ArchivePreviewHandler {
         entry: &FileEntry,
         context: &PreviewContext,
     ) -> Result<(), String> {
-        // File size check
-        if entry.size > Self::ARCHIVE_SIZE_LIMIT {
+        // The worker reads the listing; "Load more" continues it where the last page stopped
+        let listing = context
+            .archive_listing
+            .filter(|l| l.path == entry.path && l.modified == entry.modified);
+        let Some(listing) = listing else {
+            *context.archive_page_request.borrow_mut() =
+                Some((entry.path.clone(), entry.modified, 0));
             ui.centered_and_justified(|ui| {
-                ui.label(format!(
-                    "Archive too large for preview ({} > {})",
-                    bytesize::ByteSize(entry.size),
-                    bytesize::ByteSize(Self::ARCHIVE_SIZE_LIMIT)
-                ));
+                ui.spinner();
             });
             return Ok(());
-        }
-
-        // Try to get cached content
-        let cached_content = {
-            let cache = context.preview_cache.borrow();
-            cache.get(&entry.path, entry.modified)
         };
-
-        let parsed = if let Some(cached) = cached_content {
-            Self::parse_cached(&cached)
-        } else {
-            let result = Self::extract_contents(entry);
-            match result {
-                Ok(ref content) => {
-                    context.preview_cache.borrow_mut().insert(
-                        entry.path.clone(),
-                        content.clone(),
-                        entry.modified,
-                    );
-                    Self::parse_cached(content)
-                }
-                Err(e) => return Err(e),
+        if listing.members.is_empty() {
+            if let Some(e) = &listing.error {
+                return Err(format!("Failed to read archive: {}", e));
             }
-        };
+            ui.centered_and_justified(|ui| {
+                ui.label("Empty archive");
+            });
+            return Ok(());
+        }
 
-        match parsed {
-            Some((items, total)) => {
-                if items.is_empty() {
-                    ui.centered_and_justified(|ui| {
-                        ui.label("Empty archive");
-                    });
-                    return Ok(());
+        ui.horizontal(|ui| {
+            Self::render_count(ui, entry, listing, context);
+            let Some(next) = listing.next else {
+                return;
+            };
+            if listing.loading {
+                ui.spinner();
+            } else if ui
+                .small_button(format!("Load {} more", ArchiveListing::PAGE_ITEMS))
+                .on_hover_text("Continue the listing where it stopped")
+                .clicked()
+            {
+                *context.archive_page_request.borrow_mut() =
+                    Some((entry.path.clone(), entry.modified, next));
+            }
+        });
+        if let Some(e) = &listing.error {
+            ui.colored_label(
+                egui::Color32::RED,
+                format!("Failed to read more members: {}", e),
+            );
+        }
+        let items = &listing.members;
+        let total = listing.total;
+        // Only zip records sizes per member
+        let details = context.archive_details && items.iter().any(|i| i.packed.is_some());
+        if details {
+            let (size, packed) = items
+                .iter()
+                .filter_map(|i| i.packed.map(|(packed, _)| (i.size, packed)))
+                .fold((0, 0), |(s, p), (size, packed)| (s + size, p + packed));
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} packed into {} ({}){}",
+                    bytesize::ByteSize(size),
+                    bytesize::ByteSize(packed),
+                    Self::ratio(size, packed),
+                    if total.is_some_and(|t| t > items.len()) {
+                        ", listed members only"
+                    } else {
+                        ""
+                    }
+                ))
+                .weak(),
+            );
+        }
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .id_salt("preview_archive")
+            .auto_shrink([false, false])
+            .max_height(ui.available_height())
+            .show(ui, |ui| {
+                ui.set_max_width(ui.available_width());
+                use egui_extras::{Column, TableBuilder};
+                let mut table = TableBuilder::new(ui)
+                    .striped(true)
+                    .resizable(false)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(Column::auto().at_least(30.0))
+                    .column(Column::remainder().clip(true))
+                    .column(Column::auto().at_least(80.0));
+                if details {
+                    table = table
+                        .column(Column::auto().at_least(80.0))
+                        .column(Column::auto().at_least(48.0))
+                        .column(Column::auto().at_least(72.0));
                 }
-
-                let count_msg = match total {
-                    Some(t) => {
-                        if t > Self::MAX_PREVIEW_ITEMS {
-                            format!(
-                                "Archive contains {} items (showing first {})",
-                                t,
-                                Self::MAX_PREVIEW_ITEMS
-                            )
-                        } else {
-                            format!("Archive contains {} items", t)
+                let rows = |body: egui_extras::TableBody| {
+                    body.rows(20.0, items.len(), |mut row| {
+                        let item = &items[row.index()];
+                        row.col(|ui| {
+                            let icon = if item.is_dir { "\u{f07c}" } else { "\u{f15b}" };
+                            ui.label(icon);
+                        });
+                        row.col(|ui| {
+                            ui.label(&item.name);
+                        });
+                        row.col(|ui| {
+                            if !item.is_dir {
+                                ui.label(bytesize::ByteSize(item.size).to_string());
+                            }
+                        });
+                        if !details {
+                            return;
                         }
-                    }
-                    None => {
-                        format!("Archive contains {}+ items (lazy preview)", items.len())
-                    }
+                        let packed = item.packed.filter(|_| !item.is_dir);
+                        row.col(|ui| {
+                            if let Some((packed, _)) = packed {
+                                ui.label(bytesize::ByteSize(packed).to_string());
+                            }
+                        });
+                        row.col(|ui| {
+                            if let Some((packed, _)) = packed {
+                                ui.label(Self::ratio(item.size, packed));
+                            }
+                        });
+                        row.col(|ui| {
+                            if let Some((_, crc)) = packed {
+                                ui.monospace(format!("{:08x}", crc));
+                            }
+                        });
+                    });
                 };
-
-                ui.label(format!("{}:", count_msg));
-                ui.separator();
-
-                egui::ScrollArea::vertical()
-                    .id_salt("preview_archive")
-                    .auto_shrink([false, false])
-                    .max_height(ui.available_height())
-                    .show(ui, |ui| {
-                        ui.set_max_width(ui.available_width());
-                        use egui_extras::{Column, TableBuilder};
-                        TableBuilder::new(ui)
-                            .striped(true)
-                            .resizable(false)
-                            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-                            .column(Column::auto().at_least(30.0))
-                            .column(Column::remainder().clip(true))
-                            .column(Column::auto().at_least(80.0))
-                            .body(|body| {
-                                body.rows(20.0, items.len(), |mut row| {
-                                    let (name, size, is_dir) = &items[row.index()];
-                                    row.col(|ui| {
-                                        let icon = if *is_dir { "\u{f07c}" } else { "\u{f15b}" };
-                                        ui.label(icon);
-                                    });
-                                    row.col(|ui| {
-                                        ui.label(name);
-                                    });
-                                    row.col(|ui| {
-                                        if !*is_dir {
-                                            ui.label(bytesize::ByteSize(*size).to_string());
-                                        }
-                                    });
+                if details {
+                    table
+                        .header(20.0, |mut header| {
+                            for title in ["", "Name", "Size", "Packed", "Ratio", "CRC-32"] {
+                                header.col(|ui| {
+                                    ui.strong(title);
                                 });
-                            });
-                    });
-                Ok(())
-            }
-            None => Err("Failed to parse archive data".to_string()),
-        }
+                            }
+                        })
+                        .body(rows);
+                } else {
+                    table.body(rows);
+                }
+            });
+        Ok(())
     }
 
     fn priority(&self) -> i32 {
@@ -1,7 +1,7 @@
 // Audio metadata preview handler
 
 use crate::entry::FileEntry;
-use crate::view::preview::handler::{PreviewContext, PreviewHandler};
+use crate::view::preview::handler::{PreviewContext, PreviewHandler, PreviewTab};
 use eframe::egui;
 use id3::TagLike;
 
@@ -16,6 +16,27 @@ impl AudioPreviewHandler {
         matches!(ext, "mp3" | "flac" | "ogg" | "m4a" | "wav")
     }
 
+    /// Tag summary from the preview cache, extracting it on a miss
+    fn cached_metadata(entry: &FileEntry, context: &PreviewContext) -> Result<String, String> {
+        let cached_content = {
+            let cache = context.preview_cache.borrow();
+            cache.get(&entry.path, entry.modified)
+        };
+        if let Some(cached) = cached_content {
+            return Ok(cached);
+        }
+
+        let result = Self::extract_metadata(entry);
+        if let Ok(ref content) = result {
+            context.preview_cache.borrow_mut().insert(
+                entry.path.clone(),
+                content.clone(),
+                entry.modified,
+            );
+        }
+        result
+    }
+
     /// Extract metadata as a cacheable string
     fn extract_metadata(entry: &FileEntry) -> Result<String, String> {
         if entry.extension != "mp3" {
@@ -77,38 +98,48 @@ impl PreviewHandler for AudioPreviewHandler {
             return Ok(());
         }
 
-        // Try to get cached metadata
-        let cached_content = {
-            let cache = context.preview_cache.borrow();
-            cache.get(&entry.path, entry.modified)
-        };
+        let content = Self::cached_metadata(entry, context)?;
+        ui.heading("Audio Metadata");
+        ui.separator();
+        // Title and artist only; the Metadata tab lists every tag
+        for line in content
+            .lines()
+            .filter(|l| l.starts_with("Title:") || l.starts_with("Artist:"))
+        {
+            ui.label(line);
+        }
+        ui.add_space(10.0);
+        ui.label(egui::RichText::new("Full tags in the Metadata tab").weak());
+        Ok(())
+    }
 
-        let metadata = if let Some(cached) = cached_content {
-            Ok(cached)
-        } else {
-            let result = Self::extract_metadata(entry);
-            if let Ok(ref content) = result {
-                context.preview_cache.borrow_mut().insert(
-                    entry.path.clone(),
-                    content.clone(),
-                    entry.modified,
-                );
-            }
-            result
-        };
+    fn tabs(&self) -> &'static [PreviewTab] {
+        &[PreviewTab::Content, PreviewTab::Metadata]
+    }
 
-        match metadata {
-            Ok(content) => {
-                ui.heading("Audio Metadata");
-                ui.separator();
+    fn render_metadata(
+        &self,
+        ui: &mut egui::Ui,
+        entry: &FileEntry,
+        context: &PreviewContext,
+    ) -> Result<(), String> {
+        if entry.extension != "mp3" {
+            return Ok(());
+        }
+        let content = Self::cached_metadata(entry, context)?;
+        ui.label(egui::RichText::new("ID3 tags").strong());
+        egui::Grid::new("audio_metadata_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
                 for line in content.lines() {
-                    ui.label(line);
+                    let (key, value) = line.split_once(": ").unwrap_or((line, ""));
+                    ui.label(egui::RichText::new(key).weak());
+                    ui.label(value);
+                    ui.end_row();
                 }
-                ui.add_space(10.0);
-                Ok(())
-            }
-            Err(e) => Err(e),
-        }
+            });
+        Ok(())
     }
 
     fn priority(&self) -> i32 {
@@ -36,6 +36,12 @@ impl PreviewHandler for BinaryPreviewHandler {
                 ui.label("Preview not available for this file type");
                 ui.add_space(5.0);
                 ui.label(format!("Extension: .{}", entry.extension));
+                if crate::platform::HAS_QUICK_LOOK {
+                    ui.add_space(10.0);
+                    if ui.button("👁 Open in Quick Look").clicked() {
+                        let _ = crate::platform::quick_look(&entry.path);
+                    }
+                }
             });
         });
         Ok(())
@@ -1,9 +1,11 @@
 // Directory preview handler
 
 use crate::entry::FileEntry;
-use crate::io::directory::read_directory;
+use crate::io::directory::{fuzzy_match, DirUsage};
 use crate::style;
-use crate::view::preview::handler::{PreviewContext, PreviewHandler};
+use crate::view::preview::handler::{PreviewContext, PreviewHandler, PREVIEW_DEBOUNCE};
+use crate::view::preview::{DIR_SIZE_SECTION, DIR_SIZE_TTL};
+use chrono::{DateTime, Local};
 use eframe::egui;
 use std::time::Duration;
 
@@ -13,6 +15,86 @@ impl DirectoryPreviewHandler {
     pub fn new() -> Self {
         Self
     }
+
+    /// Summary line above the listing: counts, recursive size and newest child
+    fn render_summary(
+        ui: &mut egui::Ui,
+        entry: &FileEntry,
+        entries: &[&FileEntry],
+        context: &PreviewContext,
+    ) {
+        let dirs = entries.iter().filter(|e| e.is_dir).count();
+        let files = entries.len() - dirs;
+
+        let cached_size = context.preview_cache.borrow().get_recent_section(
+            &entry.path,
+            DIR_SIZE_SECTION,
+            entry.modified,
+            DIR_SIZE_TTL,
+        );
+        let size = match cached_size.as_deref() {
+            None | Some("") => {
+                // The recursive walk waits until the selection settles
+                if cached_size.is_none()
+                    && context.last_selection_change.elapsed() > PREVIEW_DEBOUNCE
+                {
+                    *context.dir_size_request.borrow_mut() = Some(entry.path.clone());
+                }
+                None
+            }
+            Some(value) => DirUsage::from_section(value),
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{} {}, {} {}",
+                files,
+                if files == 1 { "file" } else { "files" },
+                dirs,
+                if dirs == 1 { "dir" } else { "dirs" },
+            ));
+            ui.label("·");
+            match size {
+                Some(usage) => {
+                    let capped = if usage.truncated { "≥ " } else { "" };
+                    ui.label(format!(
+                        "{}{} in {} files",
+                        capped,
+                        bytesize::ByteSize(usage.bytes),
+                        usage.files
+                    ))
+                    .on_hover_text("Total size of everything below this directory");
+                    if let Some((bytes, files)) = usage.source {
+                        ui.label("·");
+                        ui.label(format!(
+                            "source {}{} in {} files",
+                            capped,
+                            bytesize::ByteSize(bytes),
+                            files
+                        ))
+                        .on_hover_text("Without hidden and git-ignored files");
+                    }
+                }
+                None => {
+                    ui.spinner();
+                }
+            }
+        });
+
+        if let Some(newest) = entries.iter().max_by_key(|e| e.modified) {
+            let datetime: DateTime<Local> = newest.modified.into();
+            style::truncated_label(
+                ui,
+                egui::RichText::new(format!(
+                    "Newest: {} ({})",
+                    newest.display_name(),
+                    datetime.format("%Y-%m-%d %H:%M")
+                ))
+                .weak(),
+            );
+        }
+        ui.separator();
+    }
 }
 
 impl PreviewHandler for DirectoryPreviewHandler {
@@ -30,19 +112,52 @@ impl PreviewHandler for DirectoryPreviewHandler {
         entry: &FileEntry,
         context: &PreviewContext,
     ) -> Result<(), String> {
-        // Debounce directory loading
-        if context.last_selection_change.elapsed() <= Duration::from_millis(200) {
+        // The worker reads the listing; an outdated one stays up until the new one arrives
+        let listing = context.dir_listing.filter(|l| l.path == entry.path);
+        let current = listing
+            .is_some_and(|l| l.modified == entry.modified && l.show_hidden == context.show_hidden);
+        if !current {
+            *context.dir_listing_request.borrow_mut() = Some((entry.path.clone(), entry.modified));
+        }
+        let Some(listing) = listing else {
             ui.centered_and_justified(|ui| {
                 ui.spinner();
             });
             return Ok(());
-        }
+        };
+        let mut entries: Vec<&FileEntry> = listing
+            .entries
+            .as_ref()
+            .map_err(|e| format!("Cannot read directory: {}", e))?
+            .iter()
+            .collect();
 
-        let entries = read_directory(&entry.path, context.show_hidden)
-            .map_err(|e| format!("Cannot read directory: {}", e))?;
+        Self::render_summary(ui, entry, &entries, context);
+
+        // The remembered selection indexes the unfiltered listing
+        let highlighted_path = context
+            .directory_selections
+            .get(&entry.path)
+            .and_then(|&idx| entries.get(idx))
+            .map(|e| &e.path);
+        if !context.filter.is_empty() {
+            let total = entries.len();
+            entries.retain(|e| fuzzy_match(&e.name, context.filter));
+            ui.label(
+                egui::RichText::new(format!(
+                    "Filter \"{}\": {} of {}",
+                    context.filter,
+                    entries.len(),
+                    total
+                ))
+                .small()
+                .weak(),
+            );
+        }
 
         let accent = egui::Color32::from_rgb(120, 180, 255);
-        let highlighted_index = context.directory_selections.get(&entry.path).copied();
+        let highlighted_index =
+            highlighted_path.and_then(|path| entries.iter().position(|e| e.path == *path));
 
         egui::ScrollArea::vertical()
             .id_salt("preview_dir")
@@ -61,7 +176,7 @@ impl PreviewHandler for DirectoryPreviewHandler {
                     .body(|body| {
                         body.rows(24.0, entries.len(), |mut row| {
                             let row_index = row.index();
-                            let preview_entry = &entries[row_index];
+                            let preview_entry = entries[row_index];
                             let is_highlighted = highlighted_index == Some(row_index);
                             let text_color = if is_highlighted || preview_entry.is_dir {
                                 accent
@@ -76,13 +191,20 @@ impl PreviewHandler for DirectoryPreviewHandler {
                                 );
                             });
                             row.col(|ui| {
-                                let response = style::truncated_label_with_sense(
+                                let response = style::name_label(
                                     ui,
                                     egui::RichText::new(preview_entry.display_name())
                                         .color(text_color),
                                     egui::Sense::click(),
+                                    context.name_ellipsis,
                                 );
-                                if response.clicked() {
+                                // Ctrl/Cmd+click enters a subdirectory itself; a double-click
+                                // can't, as its first click has already navigated
+                                let direct = ui.input(|i| i.modifiers.command);
+                                if response.clicked() && direct && preview_entry.is_dir {
+                                    *context.next_navigation.borrow_mut() =
+                                        Some(preview_entry.path.clone());
+                                } else if response.clicked() {
                                     *context.next_navigation.borrow_mut() =
                                         Some(entry.path.clone());
                                     *context.pending_selection.borrow_mut() =
@@ -96,6 +218,11 @@ impl PreviewHandler for DirectoryPreviewHandler {
         Ok(())
     }
 
+    /// The listing is read off-thread and the recursive size waits in `render_summary`
+    fn debounce(&self, _entry: &FileEntry) -> Duration {
+        Duration::ZERO
+    }
+
     fn priority(&self) -> i32 {
         5 // Very high priority - directories are common
     }
@@ -1,9 +1,13 @@
 // Image preview handler
 
 use crate::entry::FileEntry;
-use crate::view::preview::handler::{PreviewContext, PreviewHandler};
+use crate::io::photo;
+use crate::view::preview::handler::{PreviewContext, PreviewHandler, PreviewTab};
 use eframe::egui;
 
+/// Cache section holding "<capture time>\t<camera>" from a photo's EXIF data
+const EXIF_SECTION: &str = "exif";
+
 pub struct ImagePreviewHandler;
 
 impl ImagePreviewHandler {
@@ -11,6 +15,31 @@ impl ImagePreviewHandler {
         Self
     }
 
+    /// EXIF capture time and camera, either empty when not recorded
+    fn exif_summary(entry: &FileEntry, context: &PreviewContext) -> (String, String) {
+        let cached =
+            context
+                .preview_cache
+                .borrow()
+                .get_section(&entry.path, EXIF_SECTION, entry.modified);
+        let summary = cached.unwrap_or_else(|| {
+            let taken = photo::capture_time(&entry.path)
+                .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            let camera = photo::camera_model(&entry.path).unwrap_or_default();
+            let summary = format!("{}\t{}", taken, camera);
+            context.preview_cache.borrow_mut().insert_section(
+                entry.path.clone(),
+                EXIF_SECTION,
+                summary.clone(),
+                entry.modified,
+            );
+            summary
+        });
+        let (taken, camera) = summary.split_once('\t').unwrap_or_default();
+        (taken.to_string(), camera.to_string())
+    }
+
     fn is_image_extension(ext: &str) -> bool {
         matches!(
             ext,
@@ -76,6 +105,48 @@ impl PreviewHandler for ImagePreviewHandler {
         Ok(())
     }
 
+    fn tabs(&self) -> &'static [PreviewTab] {
+        &[PreviewTab::Content, PreviewTab::Metadata]
+    }
+
+    fn render_metadata(
+        &self,
+        ui: &mut egui::Ui,
+        entry: &FileEntry,
+        context: &PreviewContext,
+    ) -> Result<(), String> {
+        // SVGs are vector data; the image crate only reads raster headers
+        if entry.extension == "svg" {
+            return Ok(());
+        }
+        let (width, height) = image::image_dimensions(&entry.path)
+            .map_err(|e| format!("Failed to read image header: {}", e))?;
+        ui.label(egui::RichText::new("Image").strong());
+        egui::Grid::new("image_metadata_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new("Dimensions").weak());
+                ui.label(format!("{} × {} px", width, height));
+                ui.end_row();
+                ui.label(egui::RichText::new("Megapixels").weak());
+                ui.label(format!(
+                    "{:.1}",
+                    (width as f64 * height as f64) / 1_000_000.0
+                ));
+                ui.end_row();
+                let (taken, camera) = Self::exif_summary(entry, context);
+                for (label, value) in [("Taken", taken), ("Camera", camera)] {
+                    if !value.is_empty() {
+                        ui.label(egui::RichText::new(label).weak());
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                }
+            });
+        Ok(())
+    }
+
     fn priority(&self) -> i32 {
         10 // High priority - specific handler
     }
@@ -2,10 +2,13 @@
 
 use crate::entry::FileEntry;
 use crate::style;
-use crate::view::preview::handler::{PreviewContext, PreviewHandler};
+use crate::view::preview::handler::{
+    PreviewContext, PreviewHandler, CHEAP_PREVIEW_BYTES, PREVIEW_DEBOUNCE,
+};
 use eframe::egui;
 use pulldown_cmark::{Event as MarkdownEvent, HeadingLevel, Parser, Tag, TagEnd};
 use std::fs;
+use std::time::Duration;
 
 pub struct MarkdownPreviewHandler;
 
@@ -143,6 +146,15 @@ impl PreviewHandler for MarkdownPreviewHandler {
         Ok(())
     }
 
+    fn debounce(&self, entry: &FileEntry) -> Duration {
+        // Files over the size limit only show a notice
+        if entry.size <= CHEAP_PREVIEW_BYTES || entry.size > style::MAX_PREVIEW_SIZE {
+            Duration::ZERO
+        } else {
+            PREVIEW_DEBOUNCE
+        }
+    }
+
     fn priority(&self) -> i32 {
         20 // High priority - specific file type
     }
@@ -2,7 +2,7 @@
 
 use crate::entry::FileEntry;
 use crate::style;
-use crate::view::preview::handler::{PreviewContext, PreviewHandler};
+use crate::view::preview::handler::{PreviewContext, PreviewHandler, PreviewTab};
 use eframe::egui;
 use lopdf::Document as PdfDocument;
 
@@ -13,6 +13,27 @@ impl PdfPreviewHandler {
         Self
     }
 
+    /// Metadata from the preview cache, extracting it on a miss
+    fn cached_metadata(entry: &FileEntry, context: &PreviewContext) -> Result<String, String> {
+        let cached_content = {
+            let cache = context.preview_cache.borrow();
+            cache.get(&entry.path, entry.modified)
+        };
+        if let Some(cached) = cached_content {
+            return Ok(cached);
+        }
+
+        let result = Self::extract_metadata(entry);
+        if let Ok(ref content) = result {
+            context.preview_cache.borrow_mut().insert(
+                entry.path.clone(),
+                content.clone(),
+                entry.modified,
+            );
+        }
+        result
+    }
+
     /// Extract PDF metadata as a cacheable string
     /// Format: "pages:<N>\ntitle:<title>\nauthor:<author>"
     fn extract_metadata(entry: &FileEntry) -> Result<String, String> {
@@ -86,25 +107,7 @@ impl PreviewHandler for PdfPreviewHandler {
             return Ok(());
         }
 
-        // Try to get cached metadata
-        let cached_content = {
-            let cache = context.preview_cache.borrow();
-            cache.get(&entry.path, entry.modified)
-        };
-
-        let metadata = if let Some(cached) = cached_content {
-            Ok(cached)
-        } else {
-            let result = Self::extract_metadata(entry);
-            if let Ok(ref content) = result {
-                context.preview_cache.borrow_mut().insert(
-                    entry.path.clone(),
-                    content.clone(),
-                    entry.modified,
-                );
-            }
-            result
-        };
+        let metadata = Self::cached_metadata(entry, context);
 
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
@@ -113,28 +116,9 @@ impl PreviewHandler for PdfPreviewHandler {
 
             match metadata {
                 Ok(content) => {
-                    let mut has_metadata = false;
-                    for line in content.lines() {
-                        if let Some(pages) = line.strip_prefix("pages:") {
-                            ui.label(format!("Pages: {}", pages));
-                            ui.add_space(5.0);
-                        } else if let Some(title) = line.strip_prefix("title:") {
-                            ui.label(format!("Title: {}", title));
-                            has_metadata = true;
-                        } else if let Some(author) = line.strip_prefix("author:") {
-                            ui.label(format!("Author: {}", author));
-                            has_metadata = true;
-                        }
+                    if let Some(pages) = content.lines().find_map(|l| l.strip_prefix("pages:")) {
+                        ui.label(format!("Pages: {}", pages));
                     }
-
-                    if !has_metadata {
-                        ui.label(
-                            egui::RichText::new("No metadata available")
-                                .italics()
-                                .weak(),
-                        );
-                    }
-
                     ui.add_space(10.0);
                     ui.label(
                         egui::RichText::new("Text content extraction disabled for performance")
@@ -150,6 +134,52 @@ impl PreviewHandler for PdfPreviewHandler {
         Ok(())
     }
 
+    fn tabs(&self) -> &'static [PreviewTab] {
+        &[PreviewTab::Content, PreviewTab::Metadata]
+    }
+
+    fn render_metadata(
+        &self,
+        ui: &mut egui::Ui,
+        entry: &FileEntry,
+        context: &PreviewContext,
+    ) -> Result<(), String> {
+        if entry.size > style::MAX_PREVIEW_SIZE {
+            return Ok(());
+        }
+        let content = Self::cached_metadata(entry, context)?;
+        let mut rows = Vec::new();
+        for line in content.lines() {
+            if let Some(title) = line.strip_prefix("title:") {
+                rows.push(("Title", title));
+            } else if let Some(author) = line.strip_prefix("author:") {
+                rows.push(("Author", author));
+            } else if let Some(pages) = line.strip_prefix("pages:") {
+                rows.push(("Pages", pages));
+            }
+        }
+
+        ui.label(egui::RichText::new("PDF document info").strong());
+        if rows.len() <= 1 {
+            ui.label(
+                egui::RichText::new("No metadata available")
+                    .italics()
+                    .weak(),
+            );
+        }
+        egui::Grid::new("pdf_metadata_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for (key, value) in rows {
+                    ui.label(egui::RichText::new(key).weak());
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+        Ok(())
+    }
+
     fn priority(&self) -> i32 {
         40 // Medium priority
     }
@@ -3,9 +3,12 @@
 use crate::entry::FileEntry;
 use crate::io::directory::is_likely_binary;
 use crate::style;
-use crate::view::preview::handler::{PreviewContext, PreviewHandler};
+use crate::view::preview::handler::{
+    PreviewContext, PreviewHandler, CHEAP_PREVIEW_BYTES, PREVIEW_DEBOUNCE,
+};
 use eframe::egui;
 use std::fs;
+use std::time::Duration;
 use syntect::easy::HighlightLines;
 use syntect::util::LinesWithEndings;
 
@@ -185,15 +188,25 @@ impl PreviewHandler for TextPreviewHandler {
         let syntax = context
             .syntax_set
             .find_syntax_by_extension(&entry.extension)
+            // Syntaxes may list whole file names such as `justfile`
+            .or_else(|| context.syntax_set.find_syntax_by_extension(&entry.name))
             .or_else(|| context.syntax_set.find_syntax_by_first_line(&content))
             .unwrap_or_else(|| context.syntax_set.find_syntax_plain_text());
 
-        let theme_name = if context.theme == style::Theme::Dark {
-            "base16-ocean.dark"
-        } else {
-            "base16-ocean.light"
+        // Unknown names were reported at startup; fall back to the built-in default
+        let Some(theme) = context
+            .theme_set
+            .themes
+            .get(context.code_theme)
+            .or_else(|| context.theme_set.themes.get("base16-ocean.dark"))
+        else {
+            return Err(format!("Code theme \"{}\" not found", context.code_theme));
         };
-        let theme = &context.theme_set.themes[theme_name];
+        let background = theme
+            .settings
+            .background
+            .map(|c| egui::Color32::from_rgb(c.r, c.g, c.b))
+            .unwrap_or_else(|| ui.visuals().extreme_bg_color);
 
         // Count total lines and check if we need to truncate
         let all_lines: Vec<&str> = content.lines().collect();
@@ -216,52 +229,85 @@ impl PreviewHandler for TextPreviewHandler {
             ui.separator();
         }
 
-        egui::ScrollArea::vertical()
-            .id_salt("preview_code")
-            .auto_shrink([false, false])
-            .max_height(ui.available_height())
+        // Paint the code area in the theme's own background so its colors keep their contrast
+        let output = egui::Frame::new()
+            .fill(background)
+            .inner_margin(egui::Margin::same(6))
             .show(ui, |ui| {
-                ui.set_max_width(ui.available_width());
-                let mut highlighter = HighlightLines::new(syntax, theme);
+                egui::ScrollArea::vertical()
+                    .id_salt("preview_code")
+                    .auto_shrink([false, false])
+                    .max_height(ui.available_height())
+                    .show(ui, |ui| {
+                        ui.set_max_width(ui.available_width());
+                        let mut highlighter = HighlightLines::new(syntax, theme);
 
-                let mut job = egui::text::LayoutJob::default();
+                        let mut job = egui::text::LayoutJob::default();
 
-                // Only highlight up to MAX_HIGHLIGHTED_LINES
-                let lines_to_highlight = if is_truncated {
-                    Self::MAX_HIGHLIGHTED_LINES
-                } else {
-                    total_lines
-                };
+                        // Only highlight up to MAX_HIGHLIGHTED_LINES
+                        let lines_to_highlight = if is_truncated {
+                            Self::MAX_HIGHLIGHTED_LINES
+                        } else {
+                            total_lines
+                        };
 
-                for line in LinesWithEndings::from(content.as_ref()).take(lines_to_highlight) {
-                    let ranges = highlighter
-                        .highlight_line(line, context.syntax_set)
-                        .unwrap_or_default();
+                        for line in
+                            LinesWithEndings::from(content.as_ref()).take(lines_to_highlight)
+                        {
+                            let ranges = highlighter
+                                .highlight_line(line, context.syntax_set)
+                                .unwrap_or_default();
 
-                    for (style, text) in ranges {
-                        let color = egui::Color32::from_rgb(
-                            style.foreground.r,
-                            style.foreground.g,
-                            style.foreground.b,
-                        );
-                        job.append(
-                            text,
-                            0.0,
-                            egui::TextFormat {
-                                font_id: egui::FontId::monospace(12.0),
-                                color,
-                                ..Default::default()
-                            },
-                        );
-                    }
-                }
+                            for (style, text) in ranges {
+                                let color = egui::Color32::from_rgb(
+                                    style.foreground.r,
+                                    style.foreground.g,
+                                    style.foreground.b,
+                                );
+                                job.append(
+                                    text,
+                                    0.0,
+                                    egui::TextFormat {
+                                        font_id: egui::FontId::monospace(12.0),
+                                        color,
+                                        ..Default::default()
+                                    },
+                                );
+                            }
+                        }
 
-                ui.label(job);
-            });
+                        // Lay out once; the same galley maps the scroll offset back to a line
+                        job.wrap.max_width = ui.available_width();
+                        let galley = ui.fonts_mut(|fonts| fonts.layout_job(job));
+                        ui.label(galley.clone());
+                        galley
+                    })
+            })
+            .inner;
+
+        let cursor = output
+            .inner
+            .cursor_from_pos(egui::vec2(0.0, output.state.offset.y));
+        let line = content
+            .chars()
+            .take(cursor.index)
+            .filter(|&c| c == '\n')
+            .count()
+            + 1;
+        *context.top_line.borrow_mut() = Some((entry.path.clone(), line));
 
         Ok(())
     }
 
+    fn debounce(&self, entry: &FileEntry) -> Duration {
+        // Files over the size limit only show a notice
+        if entry.size <= CHEAP_PREVIEW_BYTES || entry.size > style::MAX_PREVIEW_SIZE {
+            Duration::ZERO
+        } else {
+            PREVIEW_DEBOUNCE
+        }
+    }
+
     fn priority(&self) -> i32 {
         90 // Lower priority - generic text handler
     }
@@ -7,19 +7,25 @@
 mod handler;
 mod handlers;
 mod registry;
+mod tabs;
 
-pub use handler::PreviewContext;
+pub use handler::{PreviewContext, PreviewTab};
 pub use handlers::*;
 pub use registry::PreviewRegistry;
+pub use tabs::{
+    ARCHIVE_TOTALS_SECTION, CHECKSUM_SECTION, DIR_SIZE_SECTION, DIR_SIZE_TTL, GIT_SECTION,
+};
 
 use crate::entry::FileEntry;
-use crate::style::{self, Theme};
+use crate::io::archive::{ArchiveMember, ArchivePage};
+use crate::style::{self, Ellipsis};
 use chrono::{DateTime, Local};
 use eframe::egui;
+use std::cell::Cell;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Instant, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
 
@@ -31,10 +37,76 @@ pub struct CachedPreview {
     pub cached_at: Instant,
 }
 
+/// A directory's listing for the preview, read by the worker
+pub struct PreviewListing {
+    pub path: PathBuf,
+    /// The entry's modification time when the listing was requested
+    pub modified: SystemTime,
+    pub show_hidden: bool,
+    pub entries: Result<Vec<FileEntry>, String>,
+}
+
+/// An archive's listing for the preview, read by the worker a page at a time
+pub struct ArchiveListing {
+    pub path: PathBuf,
+    /// The entry's modification time when the first page was requested
+    pub modified: SystemTime,
+    pub members: Vec<ArchiveMember>,
+    /// Member count of the whole archive, once known
+    pub total: Option<usize>,
+    /// Where the next page starts, `None` once every member is listed
+    pub next: Option<u64>,
+    /// The next page was requested and has not arrived yet
+    pub loading: bool,
+    /// Why the last page could not be read
+    pub error: Option<String>,
+}
+
+impl ArchiveListing {
+    /// Members read per page, first when previewing and again for each "Load more"
+    pub const PAGE_ITEMS: usize = 200;
+
+    pub fn new(path: PathBuf, modified: SystemTime) -> Self {
+        Self {
+            path,
+            modified,
+            members: Vec::new(),
+            total: None,
+            next: None,
+            loading: false,
+            error: None,
+        }
+    }
+
+    /// Append a page read by the worker
+    pub fn add_page(&mut self, page: Result<ArchivePage, String>) {
+        self.loading = false;
+        match page {
+            Ok(page) => {
+                self.members.extend(page.members);
+                self.next = page.next;
+                // A tar stream only reveals its length once it has been read to the end
+                self.total = page
+                    .total
+                    .or_else(|| page.next.is_none().then_some(self.members.len()));
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+/// Cache section used by content handlers
+const CONTENT_SECTION: &str = "content";
+
 /// Preview cache to avoid re-rendering identical files
+///
+/// Entries are keyed by path and section so the content preview, metadata
+/// tabs and checksums of one file can be cached side by side.
 pub struct PreviewCache {
-    cache: HashMap<PathBuf, CachedPreview>,
+    cache: HashMap<(PathBuf, &'static str), CachedPreview>,
     max_entries: usize,
+    /// Upper bound on cached text; 0 means no limit beyond `max_entries`
+    max_bytes: usize,
 }
 
 impl PreviewCache {
@@ -42,12 +114,69 @@ impl PreviewCache {
         Self {
             cache: HashMap::new(),
             max_entries: 100, // Cache up to 100 file previews
+            max_bytes: 0,
+        }
+    }
+
+    /// Limit the total size of cached text (0 disables the limit)
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.evict_to_fit(0);
+    }
+
+    /// Number of cached values across all sections
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Approximate memory held by cached text
+    pub fn byte_size(&self) -> usize {
+        self.cache.values().map(|v| v.content.len()).sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Drop oldest entries until `incoming` more bytes fit under `max_bytes`
+    fn evict_to_fit(&mut self, incoming: usize) {
+        if self.max_bytes == 0 {
+            return;
+        }
+        let mut total = self.byte_size();
+        while total + incoming > self.max_bytes {
+            let Some(oldest_key) = self
+                .cache
+                .iter()
+                .min_by_key(|(_, v)| v.cached_at)
+                .map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            if let Some(removed) = self.cache.remove(&oldest_key) {
+                total -= removed.content.len();
+            }
         }
     }
 
     /// Get cached preview if valid (not modified since caching)
-    pub fn get(&self, path: &PathBuf, current_mtime: SystemTime) -> Option<String> {
-        if let Some(cached) = self.cache.get(path) {
+    pub fn get(&self, path: &Path, current_mtime: SystemTime) -> Option<String> {
+        self.get_section(path, CONTENT_SECTION, current_mtime)
+    }
+
+    /// Store preview in cache
+    pub fn insert(&mut self, path: PathBuf, content: String, mtime: SystemTime) {
+        self.insert_section(path, CONTENT_SECTION, content, mtime);
+    }
+
+    /// Get a cached value from a named section if the file is unchanged
+    pub fn get_section(
+        &self,
+        path: &Path,
+        section: &'static str,
+        current_mtime: SystemTime,
+    ) -> Option<String> {
+        if let Some(cached) = self.cache.get(&(path.to_path_buf(), section)) {
             // Validate that file hasn't been modified
             if cached.modified_time == current_mtime {
                 return Some(cached.content.clone());
@@ -56,8 +185,56 @@ impl PreviewCache {
         None
     }
 
-    /// Store preview in cache
-    pub fn insert(&mut self, path: PathBuf, content: String, mtime: SystemTime) {
+    /// Like `get_section`, but a value cached more than `max_age` ago counts as missing
+    ///
+    /// For values that depend on more than the file itself, such as a folder's
+    /// recursive size. Empty placeholders for running work never expire.
+    pub fn get_recent_section(
+        &self,
+        path: &Path,
+        section: &'static str,
+        current_mtime: SystemTime,
+        max_age: Duration,
+    ) -> Option<String> {
+        self.cache
+            .get(&(path.to_path_buf(), section))
+            .filter(|cached| cached.modified_time == current_mtime)
+            .filter(|cached| cached.content.is_empty() || cached.cached_at.elapsed() <= max_age)
+            .map(|cached| cached.content.clone())
+    }
+
+    /// Drop a section's value for `path` and every folder above it
+    ///
+    /// Placeholders stay, so work still running is not requested a second time.
+    pub fn remove_section_up(&mut self, path: &Path, section: &'static str) {
+        for ancestor in path.ancestors() {
+            let key = (ancestor.to_path_buf(), section);
+            if self.cache.get(&key).is_some_and(|v| !v.content.is_empty()) {
+                self.cache.remove(&key);
+            }
+        }
+    }
+
+    /// Whether a section holds a value for the file as it is now
+    pub fn contains(&self, path: &Path, section: &'static str, current_mtime: SystemTime) -> bool {
+        self.cache
+            .get(&(path.to_path_buf(), section))
+            .is_some_and(|cached| cached.modified_time == current_mtime)
+    }
+
+    /// Drop a section's value, e.g. a placeholder for work that was cancelled
+    pub fn remove_section(&mut self, path: &Path, section: &'static str) {
+        self.cache.remove(&(path.to_path_buf(), section));
+    }
+
+    /// Store a value in a named section
+    pub fn insert_section(
+        &mut self,
+        path: PathBuf,
+        section: &'static str,
+        content: String,
+        mtime: SystemTime,
+    ) {
         // Simple LRU: remove oldest entry if cache is full
         if self.cache.len() >= self.max_entries {
             if let Some(oldest_key) = self
@@ -69,9 +246,10 @@ impl PreviewCache {
                 self.cache.remove(&oldest_key);
             }
         }
+        self.evict_to_fit(content.len());
 
         self.cache.insert(
-            path,
+            (path, section),
             CachedPreview {
                 content,
                 modified_time: mtime,
@@ -105,24 +283,77 @@ pub fn create_default_registry() -> PreviewRegistry {
     registry
 }
 
-/// Render preview pane header with file metadata
-pub fn render_preview_header(ui: &mut egui::Ui, entry: &FileEntry) {
-    style::truncated_label(
+/// Render preview pane header: file name, a one-line summary and the tab bar
+///
+/// Returns the tab to render, falling back to Content when the selected tab
+/// does not apply to this entry.
+pub fn render_preview_header(
+    ui: &mut egui::Ui,
+    entry: &FileEntry,
+    preview_tab: &Cell<PreviewTab>,
+    name_ellipsis: Ellipsis,
+) -> PreviewTab {
+    style::name_label(
         ui,
         egui::RichText::new(format!("{} {}", entry.get_icon(), entry.display_name())).heading(),
+        egui::Sense::hover(),
+        name_ellipsis,
     );
-    ui.add_space(5.0);
-    ui.label(format!("Type: {}", entry.get_file_type()));
-    style::truncated_label(ui, format!("Size: {}", bytesize::ByteSize(entry.size)));
+    if let Some(link) = &entry.link {
+        style::truncated_label(
+            ui,
+            egui::RichText::new(link.target.display().to_string()).weak(),
+        );
+    }
     let datetime: DateTime<Local> = entry.modified.into();
-    ui.label(format!("Modified: {}", datetime.format("%Y-%m-%d %H:%M")));
-    ui.label(format!("Permissions: {}", entry.get_permissions_string()));
+    style::truncated_label(
+        ui,
+        egui::RichText::new(format!(
+            "{} · {} · {}",
+            entry.get_file_type(),
+            bytesize::ByteSize(entry.size),
+            datetime.format("%Y-%m-%d %H:%M")
+        ))
+        .weak(),
+    );
+    ui.add_space(4.0);
+
+    let tabs = tabs::available_tabs(entry);
+    let mut active = preview_tab.get();
+    if !tabs.contains(&active) {
+        active = PreviewTab::Content;
+    }
+    ui.horizontal(|ui| {
+        for tab in &tabs {
+            if ui.selectable_label(active == *tab, tab.label()).clicked() {
+                preview_tab.set(*tab);
+                active = *tab;
+            }
+        }
+    });
     ui.separator();
+    active
+}
+
+/// Cycle through the tabs available for `entry` ('[' / ']')
+pub fn cycle_preview_tab(preview_tab: &Cell<PreviewTab>, entry: &FileEntry, forward: bool) {
+    let tabs = tabs::available_tabs(entry);
+    let idx = tabs
+        .iter()
+        .position(|t| *t == preview_tab.get())
+        .unwrap_or(0);
+    let next = if forward {
+        (idx + 1) % tabs.len()
+    } else {
+        (idx + tabs.len() - 1) % tabs.len()
+    };
+    preview_tab.set(tabs[next]);
 }
 
 /// Main preview dispatcher using the handler registry
 ///
 /// This is the public API for rendering file previews.
+#[allow(clippy::too_many_arguments)]
 pub fn render_preview(
     ui: &mut egui::Ui,
     entry: &FileEntry,
@@ -132,16 +363,32 @@ pub fn render_preview(
     directory_selections: &HashMap<PathBuf, usize>,
     syntax_set: &SyntaxSet,
     theme_set: &ThemeSet,
-    theme: Theme,
+    code_theme: &str,
+    name_ellipsis: Ellipsis,
+    archive_details: bool,
+    filter: &str,
     next_navigation: &std::cell::RefCell<Option<PathBuf>>,
     pending_selection: &std::cell::RefCell<Option<PathBuf>>,
     preview_cache: &std::cell::RefCell<PreviewCache>,
+    preview_tab: &Cell<PreviewTab>,
+    checksum_request: &std::cell::RefCell<Option<PathBuf>>,
+    dir_size_request: &std::cell::RefCell<Option<PathBuf>>,
+    archive_count_request: &std::cell::RefCell<Option<PathBuf>>,
+    dir_listing: Option<&PreviewListing>,
+    dir_listing_request: &std::cell::RefCell<Option<(PathBuf, SystemTime)>>,
+    archive_listing: Option<&ArchiveListing>,
+    archive_page_request: &std::cell::RefCell<Option<(PathBuf, SystemTime, u64)>>,
+    last_commit_request: &std::cell::RefCell<Option<PathBuf>>,
+    top_line: &std::cell::RefCell<Option<(PathBuf, usize)>>,
 ) {
-    // Render file metadata header
-    render_preview_header(ui, entry);
+    // Render file name, summary line and tab bar
+    let active_tab = render_preview_header(ui, entry, preview_tab, name_ellipsis);
 
-    // Debounce for initial file selection change
-    if last_selection_change.elapsed() <= std::time::Duration::from_millis(200) {
+    // Expensive previews wait for the selection to settle; cached ones show at once
+    let cached = preview_cache
+        .borrow()
+        .contains(&entry.path, CONTENT_SECTION, entry.modified);
+    if !cached && last_selection_change.elapsed() <= registry.debounce_for(entry) {
         ui.centered_and_justified(|ui| {
             ui.spinner();
         });
@@ -152,15 +399,48 @@ pub fn render_preview(
     let context = PreviewContext {
         syntax_set,
         theme_set,
-        theme,
+        code_theme,
         show_hidden,
+        name_ellipsis,
+        archive_details,
+        filter,
         last_selection_change,
         directory_selections,
         next_navigation,
         pending_selection,
         preview_cache,
+        checksum_request,
+        dir_size_request,
+        archive_count_request,
+        dir_listing,
+        dir_listing_request,
+        archive_listing,
+        archive_page_request,
+        last_commit_request,
+        top_line,
     };
 
+    match active_tab {
+        PreviewTab::Content => {}
+        PreviewTab::Metadata => {
+            egui::ScrollArea::vertical()
+                .id_salt("preview_metadata")
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    tabs::render_metadata_tab(ui, entry, registry.handler_for(entry), &context)
+                });
+            return;
+        }
+        PreviewTab::Permissions => {
+            tabs::render_permissions_tab(ui, entry);
+            return;
+        }
+        PreviewTab::Git => {
+            tabs::render_git_tab(ui, entry, &context);
+            return;
+        }
+    }
+
     // Try to render using registry
     if !registry.render_preview(ui, entry, &context) {
         // No handler found - show fallback message
@@ -174,3 +454,33 @@ pub fn render_preview(
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folder_sizes_expire_and_are_dropped_above_a_change() {
+        let mut cache = PreviewCache::new();
+        let mtime = SystemTime::UNIX_EPOCH;
+        let (root, running) = (Path::new("/data"), Path::new("/data/running"));
+        cache.insert_section(root.into(), DIR_SIZE_SECTION, "10 2".into(), mtime);
+        cache.insert_section(running.into(), DIR_SIZE_SECTION, String::new(), mtime);
+        std::thread::sleep(Duration::from_millis(2));
+
+        let recent = |cache: &PreviewCache, path, max_age| {
+            cache.get_recent_section(path, DIR_SIZE_SECTION, mtime, max_age)
+        };
+        assert_eq!(recent(&cache, root, DIR_SIZE_TTL).as_deref(), Some("10 2"));
+        assert_eq!(recent(&cache, root, Duration::from_millis(1)), None);
+        assert_eq!(
+            recent(&cache, running, Duration::ZERO).as_deref(),
+            Some(""),
+            "placeholders never expire"
+        );
+
+        cache.remove_section_up(&running.join("new.txt"), DIR_SIZE_SECTION);
+        assert_eq!(recent(&cache, root, DIR_SIZE_TTL), None);
+        assert_eq!(recent(&cache, running, DIR_SIZE_TTL).as_deref(), Some(""));
+    }
+}
@@ -3,13 +3,50 @@
 use super::handler::{PreviewContext, PreviewHandler};
 use crate::entry::FileEntry;
 use eframe::egui;
-use std::collections::HashSet;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Render counters of one handler, shown in the diagnostics window
+#[derive(Clone, Debug, Default)]
+pub struct HandlerStats {
+    /// Frames rendered, errors included
+    pub renders: u64,
+    pub total_time: Duration,
+    /// File name and message of the most recent failure
+    pub last_error: Option<(String, String)>,
+}
+
+impl HandlerStats {
+    pub fn average(&self) -> Option<Duration> {
+        (self.renders > 0).then(|| self.total_time / self.renders as u32)
+    }
+}
+
+/// A registered handler as listed in the diagnostics window
+pub struct HandlerStatus {
+    pub name: String,
+    pub priority: i32,
+    pub enabled: bool,
+    /// Whether `can_preview` accepts the selected entry
+    pub accepts_selection: Option<bool>,
+    pub stats: HandlerStats,
+}
 
 /// Registry for managing preview handlers
 pub struct PreviewRegistry {
     handlers: Vec<Arc<dyn PreviewHandler>>,
     enabled_handlers: HashSet<String>,
+    /// Handler name → priority, replacing the handler's own
+    priority_overrides: HashMap<String, i32>,
+    /// Lowercase extension → handler name, consulted before any priority
+    extension_handlers: HashMap<String, String>,
+    /// `:preview-as` choice for a single file
+    temporary_override: Option<(PathBuf, String)>,
+    /// Handler name → render counters, updated while drawing
+    stats: RefCell<HashMap<String, HandlerStats>>,
 }
 
 impl PreviewRegistry {
@@ -18,6 +55,10 @@ impl PreviewRegistry {
         Self {
             handlers: Vec::new(),
             enabled_handlers: HashSet::new(),
+            priority_overrides: HashMap::new(),
+            extension_handlers: HashMap::new(),
+            temporary_override: None,
+            stats: RefCell::new(HashMap::new()),
         }
     }
 
@@ -30,8 +71,66 @@ impl PreviewRegistry {
             self.enabled_handlers.insert(handler.name().to_string());
         }
         self.handlers.push(handler);
-        // Sort handlers by priority (lower priority value = checked first)
-        self.handlers.sort_by_key(|h| h.priority());
+        self.sort_handlers();
+    }
+
+    /// Sort handlers by priority (lower priority value = checked first)
+    fn sort_handlers(&mut self) {
+        let overrides = &self.priority_overrides;
+        self.handlers
+            .sort_by_key(|h| overrides.get(h.name()).copied().unwrap_or(h.priority()));
+    }
+
+    /// Replace the built-in priorities of the named handlers
+    pub fn set_priority_overrides(&mut self, priorities: HashMap<String, i32>) {
+        self.priority_overrides = priorities;
+        self.sort_handlers();
+    }
+
+    /// Map extensions to a handler, bypassing the priority order for them
+    pub fn set_extension_handlers(&mut self, handlers: HashMap<String, String>) {
+        self.extension_handlers = handlers
+            .into_iter()
+            .map(|(ext, name)| (ext.trim_start_matches('.').to_lowercase(), name))
+            .collect();
+    }
+
+    /// Preview `path` with the named handler until another file is chosen this way
+    ///
+    /// `None` drops the override. Fails for an unknown handler name.
+    pub fn set_temporary_override(
+        &mut self,
+        path: PathBuf,
+        name: Option<&str>,
+    ) -> Result<(), String> {
+        match name {
+            None => self.temporary_override = None,
+            Some(name) => {
+                if self.handler_named(name).is_none() {
+                    return Err(format!(
+                        "Unknown preview handler: {} (available: {})",
+                        name,
+                        self.handler_names().join(", ")
+                    ));
+                }
+                self.temporary_override = Some((path, name.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Registered handler names in priority order
+    pub fn handler_names(&self) -> Vec<&str> {
+        self.handlers.iter().map(|h| h.name()).collect()
+    }
+
+    /// A handler's own priority, before `priorities` overrides
+    pub fn default_priority(&self, name: &str) -> Option<i32> {
+        self.handler_named(name).map(|h| h.priority())
+    }
+
+    fn handler_named(&self, name: &str) -> Option<&Arc<dyn PreviewHandler>> {
+        self.handlers.iter().find(|h| h.name() == name)
     }
 
     /// Check if a handler is enabled
@@ -44,6 +143,31 @@ impl PreviewRegistry {
         self.enabled_handlers = enabled.into_iter().collect();
     }
 
+    /// Handler for this entry
+    ///
+    /// A `:preview-as` override wins, then a configured extension mapping (both
+    /// skip `can_preview`, so e.g. SVG can be forced to text), then the first
+    /// enabled handler in priority order that accepts the entry.
+    pub fn handler_for(&self, entry: &FileEntry) -> Option<&Arc<dyn PreviewHandler>> {
+        let forced = match &self.temporary_override {
+            Some((path, name)) if *path == entry.path => Some(name),
+            _ if entry.is_dir => None,
+            _ => self.extension_handlers.get(&entry.extension),
+        };
+        if let Some(handler) = forced.and_then(|name| self.handler_named(name)) {
+            return Some(handler);
+        }
+        self.handlers
+            .iter()
+            .find(|h| self.is_enabled(h.name()) && h.can_preview(entry))
+    }
+
+    /// How long the selection must rest on `entry` before its preview renders
+    pub fn debounce_for(&self, entry: &FileEntry) -> Duration {
+        self.handler_for(entry)
+            .map_or(Duration::ZERO, |handler| handler.debounce(entry))
+    }
+
     /// Render preview using the first matching enabled handler
     ///
     /// Returns true if a handler was found and rendered successfully.
@@ -53,32 +177,50 @@ impl PreviewRegistry {
         entry: &FileEntry,
         context: &PreviewContext,
     ) -> bool {
-        for handler in &self.handlers {
-            // Skip disabled handlers
-            if !self.is_enabled(handler.name()) {
-                continue;
-            }
-
-            // Check if handler can preview this file
-            if handler.can_preview(entry) {
-                match handler.render(ui, entry, context) {
-                    Ok(()) => return true,
-                    Err(e) => {
-                        ui.colored_label(
-                            egui::Color32::RED,
-                            format!("Preview error ({}): {}", handler.name(), e),
-                        );
-                        return true; // Still handled, even if error
-                    }
-                }
-            }
+        let Some(handler) = self.handler_for(entry) else {
+            return false;
+        };
+        let started = Instant::now();
+        let result = handler.render(ui, entry, context);
+        let mut stats = self.stats.borrow_mut();
+        let stats = stats.entry(handler.name().to_string()).or_default();
+        stats.renders += 1;
+        stats.total_time += started.elapsed();
+        if let Err(e) = result {
+            ui.colored_label(
+                egui::Color32::RED,
+                format!("Preview error ({}): {}", handler.name(), e),
+            );
+            stats.last_error = Some((entry.name.clone(), e));
         }
-        false
+        true // Still handled, even if error
+    }
+
+    /// Every registered handler in priority order, with its counters and whether it
+    /// would take `selection`
+    pub fn statuses(&self, selection: Option<&FileEntry>) -> Vec<HandlerStatus> {
+        let stats = self.stats.borrow();
+        self.handlers
+            .iter()
+            .map(|h| HandlerStatus {
+                name: h.name().to_string(),
+                priority: self
+                    .priority_overrides
+                    .get(h.name())
+                    .copied()
+                    .unwrap_or(h.priority()),
+                enabled: self.is_enabled(h.name()),
+                accepts_selection: selection.map(|entry| h.can_preview(entry)),
+                stats: stats.get(h.name()).cloned().unwrap_or_default(),
+            })
+            .collect()
     }
 
-    /// Get list of enabled handler names
+    /// Get list of enabled handler names, sorted so the saved config stays stable
     pub fn enabled_handler_names(&self) -> Vec<String> {
-        self.enabled_handlers.iter().cloned().collect()
+        let mut names: Vec<String> = self.enabled_handlers.iter().cloned().collect();
+        names.sort();
+        names
     }
 }
 
@@ -87,3 +229,36 @@ impl Default for PreviewRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enabled_handler_names_are_sorted() {
+        let mut registry = PreviewRegistry::new();
+        registry.set_enabled_handlers(vec!["text".into(), "archive".into(), "image".into()]);
+
+        assert_eq!(
+            registry.enabled_handler_names(),
+            vec!["archive", "image", "text"]
+        );
+    }
+
+    #[test]
+    fn cheap_previews_skip_the_debounce() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+        std::fs::write(dir.path().join("big.txt"), "x\n".repeat(100_000)).unwrap();
+        std::fs::write(dir.path().join("photo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        let registry = crate::view::preview::create_default_registry();
+        let debounce = |name: &str| {
+            registry.debounce_for(&FileEntry::from_path(dir.path().join(name)).unwrap())
+        };
+        assert_eq!(debounce("docs"), Duration::ZERO);
+        assert_eq!(debounce("notes.txt"), Duration::ZERO);
+        assert!(debounce("big.txt") > Duration::ZERO);
+        assert!(debounce("photo.png") > Duration::ZERO);
+    }
+}
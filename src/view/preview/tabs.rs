@@ -0,0 +1,209 @@
+// Core preview tabs: generic metadata, permissions and git details
+//
+// Handlers only render the Content tab and, optionally, their own section of the
+// Metadata tab. Everything here works for any file regardless of handler.
+
+use super::handler::{PreviewContext, PreviewHandler, PreviewTab};
+use crate::entry::FileEntry;
+use chrono::{DateTime, Local};
+use eframe::egui;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Cache section holding the SHA-256 digest computed by the worker
+pub const CHECKSUM_SECTION: &str = "sha256";
+
+/// Cache section holding a directory's recursive size, `<bytes>` or `<bytes>+` when capped
+pub const DIR_SIZE_SECTION: &str = "dir_size";
+
+/// A folder's mtime only changes with its direct children, so its size is recomputed
+/// after this long; changes the directory watcher reports drop it sooner
+pub const DIR_SIZE_TTL: Duration = Duration::from_secs(60);
+
+/// Cache section holding an archive's member count and unpacked size, empty while counting
+pub const ARCHIVE_TOTALS_SECTION: &str = "archive_totals";
+
+/// Cache section holding `git log` output for the Git tab, empty while the worker reads it
+pub const GIT_SECTION: &str = "git";
+
+/// Tabs offered for an entry: Git only appears inside a repository
+pub(super) fn available_tabs(entry: &FileEntry) -> Vec<PreviewTab> {
+    let mut tabs = vec![
+        PreviewTab::Content,
+        PreviewTab::Metadata,
+        PreviewTab::Permissions,
+    ];
+    if entry.details.git_status.is_some() || entry.details.git_root.is_some() {
+        tabs.push(PreviewTab::Git);
+    }
+    tabs
+}
+
+fn detail_grid(ui: &mut egui::Ui, id: &str, rows: &[(&str, String)]) {
+    egui::Grid::new(id)
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for (label, value) in rows {
+                ui.label(egui::RichText::new(*label).weak());
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+}
+
+fn format_time(time: std::time::SystemTime) -> String {
+    let datetime: DateTime<Local> = time.into();
+    datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+pub(super) fn render_metadata_tab(
+    ui: &mut egui::Ui,
+    entry: &FileEntry,
+    handler: Option<&Arc<dyn PreviewHandler>>,
+    context: &PreviewContext,
+) {
+    let mut rows = vec![
+        ("Type", entry.get_file_type()),
+        ("MIME type", entry.mime_type().to_string()),
+    ];
+    if !entry.is_dir {
+        rows.push((
+            "Size",
+            format!("{} ({} bytes)", bytesize::ByteSize(entry.size), entry.size),
+        ));
+    }
+    rows.push(("Modified", format_time(entry.modified)));
+    if let Some(accessed) = entry.accessed {
+        rows.push(("Accessed", format_time(accessed)));
+    }
+    if let Some(created) = entry.created {
+        rows.push(("Created", format_time(created)));
+    }
+    if entry.is_symlink {
+        if let Ok(target) = std::fs::read_link(&entry.path) {
+            rows.push(("Link target", target.display().to_string()));
+        }
+    }
+    detail_grid(ui, "preview_metadata_grid", &rows);
+
+    if let Some(handler) = handler.filter(|h| h.tabs().contains(&PreviewTab::Metadata)) {
+        ui.add_space(8.0);
+        ui.separator();
+        if let Err(e) = handler.render_metadata(ui, entry, context) {
+            ui.colored_label(egui::Color32::RED, e);
+        }
+    }
+
+    if !entry.is_dir {
+        ui.add_space(8.0);
+        ui.separator();
+        let digest = context.preview_cache.borrow().get_section(
+            &entry.path,
+            CHECKSUM_SECTION,
+            entry.modified,
+        );
+        match digest {
+            // An empty entry is the placeholder stored while the worker hashes the file
+            Some(digest) if digest.is_empty() => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Computing SHA-256…");
+                });
+            }
+            Some(digest) => {
+                ui.label(egui::RichText::new("SHA-256").weak());
+                ui.add(egui::Label::new(egui::RichText::new(digest).monospace()).wrap());
+            }
+            None => {
+                if ui.button("Compute SHA-256").clicked() {
+                    *context.checksum_request.borrow_mut() = Some(entry.path.clone());
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn render_permissions_tab(ui: &mut egui::Ui, entry: &FileEntry) {
+    let mut rows = vec![("Permissions", entry.get_permissions_string())];
+
+    #[cfg(unix)]
+    if let Ok(meta) = std::fs::symlink_metadata(&entry.path) {
+        use std::os::unix::fs::MetadataExt;
+        rows.push(("Mode", format!("{:04o}", meta.mode() & 0o7777)));
+        rows.push((
+            "Owner",
+            format!(
+                "{} ({})",
+                crate::platform::user_name(meta.uid()).unwrap_or_default(),
+                meta.uid()
+            ),
+        ));
+        rows.push((
+            "Group",
+            format!(
+                "{} ({})",
+                crate::platform::group_name(meta.gid()).unwrap_or_default(),
+                meta.gid()
+            ),
+        ));
+        let special: Vec<&str> = [(0o4000, "setuid"), (0o2000, "setgid"), (0o1000, "sticky")]
+            .iter()
+            .filter(|(bit, _)| meta.mode() & bit != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        if !special.is_empty() {
+            rows.push(("Special", special.join(", ")));
+        }
+    }
+
+    if let Ok(meta) = std::fs::metadata(&entry.path) {
+        rows.push((
+            "Writable",
+            if meta.permissions().readonly() {
+                "No (read-only)".into()
+            } else {
+                "Yes".into()
+            },
+        ));
+    }
+    detail_grid(ui, "preview_permissions_grid", &rows);
+}
+
+pub(super) fn render_git_tab(ui: &mut egui::Ui, entry: &FileEntry, context: &PreviewContext) {
+    let root = &entry.details.git_root;
+    let status = match &entry.details.git_status {
+        Some(status) => format!("{:?}", status),
+        None => "Clean / tracked".into(),
+    };
+    let mut rows = vec![("Status", status)];
+    if let Some(root) = root {
+        rows.push(("Repository", root.display().to_string()));
+    }
+    detail_grid(ui, "preview_git_grid", &rows);
+
+    let cached =
+        context
+            .preview_cache
+            .borrow()
+            .get_section(&entry.path, GIT_SECTION, entry.modified);
+
+    ui.add_space(8.0);
+    ui.label(egui::RichText::new("Last commit").weak());
+    ui.separator();
+    match cached {
+        // An empty entry is the placeholder stored while the worker runs git log
+        Some(log) if log.is_empty() => {
+            ui.spinner();
+        }
+        Some(log) => {
+            for line in log.lines() {
+                ui.label(line);
+            }
+        }
+        None => {
+            *context.last_commit_request.borrow_mut() = Some(entry.path.clone());
+            ui.spinner();
+        }
+    }
+}
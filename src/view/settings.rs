@@ -0,0 +1,614 @@
+// Settings window: tabs over a draft of the config, applied and saved as a whole
+use crate::app::Heike;
+use crate::config::Config;
+use crate::core::keymap::is_bookmark_sequence;
+use crate::state::{AppMode, SettingsTab};
+use crate::style;
+use eframe::egui;
+use std::collections::HashMap;
+
+/// Sort fields and conflict policies as spelled in config.toml
+const SORT_FIELDS: [&str; 5] = ["name", "size", "modified", "created", "extension"];
+const CONFLICT_POLICIES: [&str; 4] = ["ask", "rename", "overwrite", "skip"];
+
+impl Heike {
+    pub(crate) fn render_settings_modal(&mut self, ctx: &egui::Context) {
+        if self.mode.mode != AppMode::Settings {
+            return;
+        }
+        let mut code_themes: Vec<String> = self.theme_set.themes.keys().cloned().collect();
+        code_themes.sort();
+        let handlers: Vec<(String, i32)> = self
+            .preview_registry
+            .handler_names()
+            .into_iter()
+            .map(|name| {
+                let priority = self.preview_registry.default_priority(name).unwrap_or(0);
+                (name.to_string(), priority)
+            })
+            .collect();
+        let config_path = Config::config_path();
+        let Some(settings) = self.settings.as_mut() else {
+            return;
+        };
+        let unsaved = settings.draft != self.config;
+
+        let mut open = true;
+        let mut save = false;
+        let mut close = false;
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(style::modal_width(ctx) * 1.2)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for tab in SettingsTab::ALL {
+                        ui.selectable_value(&mut settings.tab, tab, tab.label());
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(style::modal_max_height(ctx) - 120.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        let draft = &mut settings.draft;
+                        match settings.tab {
+                            SettingsTab::Appearance => appearance(ui, draft, &code_themes),
+                            SettingsTab::Behavior => {
+                                behavior(ui, draft, &mut settings.new_build_dir)
+                            }
+                            SettingsTab::Previews => {
+                                previews(ui, draft, &handlers, &mut settings.new_association)
+                            }
+                            SettingsTab::Bookmarks => {
+                                ui.label(
+                                    egui::RichText::new(
+                                        "Typed after g; a path may point at a folder or a file \
+                                         and use ~, $VAR and ${VAR}",
+                                    )
+                                    .weak(),
+                                );
+                                map_editor(
+                                    ui,
+                                    "settings_bookmarks",
+                                    &mut draft.bookmarks.shortcuts,
+                                    &mut settings.new_bookmark,
+                                    ("keys, e.g. dd", "path, e.g. ~/Documents"),
+                                    is_bookmark_sequence,
+                                );
+                            }
+                            SettingsTab::Keybindings => {
+                                keybindings(ui, draft, &mut settings.new_alias)
+                            }
+                            SettingsTab::Integrations => integrations(
+                                ui,
+                                draft,
+                                &mut settings.new_association,
+                                &mut settings.new_watch,
+                            ),
+                        }
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(unsaved, egui::Button::new("Save"))
+                        .on_hover_text("Apply the changes and write them to config.toml")
+                        .clicked()
+                    {
+                        save = true;
+                    }
+                    if ui.button("Close (Esc)").clicked() {
+                        close = true;
+                    }
+                    if unsaved {
+                        ui.label(egui::RichText::new("Unsaved changes").weak().italics());
+                    }
+                    if let Some(path) = &config_path {
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            style::truncated_label(
+                                ui,
+                                egui::RichText::new(path.display().to_string()).weak(),
+                            );
+                        });
+                    }
+                });
+            });
+
+        if save {
+            self.save_settings_draft();
+        }
+        if close || !open {
+            self.close_settings();
+        }
+    }
+}
+
+fn appearance(ui: &mut egui::Ui, draft: &mut Config, code_themes: &[String]) {
+    egui::Grid::new("settings_appearance")
+        .num_columns(2)
+        .spacing([16.0, 6.0])
+        .show(ui, |ui| {
+            ui.label("Theme");
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut draft.theme.mode, "dark".to_string(), "Dark");
+                ui.radio_value(&mut draft.theme.mode, "light".to_string(), "Light");
+            });
+            ui.end_row();
+
+            ui.label("Long names");
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut draft.ui.name_ellipsis,
+                    "end".to_string(),
+                    "Cut the end",
+                );
+                ui.radio_value(
+                    &mut draft.ui.name_ellipsis,
+                    "middle".to_string(),
+                    "Cut the middle",
+                );
+            });
+            ui.end_row();
+
+            ui.label("Symlinks");
+            ui.checkbox(
+                &mut draft.ui.link_target_stats,
+                "Show the target's size and dates",
+            )
+            .on_hover_text("Off: the link's own size and modification time");
+            ui.end_row();
+
+            ui.label("Size bars");
+            ui.checkbox(&mut draft.ui.size_bars, "Bar behind each file size");
+            ui.end_row();
+
+            ui.label("Group headers");
+            ui.horizontal(|ui| {
+                for (label, name) in [
+                    ("Name", "name"),
+                    ("Modified", "modified"),
+                    ("Created", "created"),
+                    ("Extension", "extension"),
+                ] {
+                    let groups = &mut draft.ui.group_headers;
+                    let mut on = groups.iter().any(|g| g == name);
+                    if ui.checkbox(&mut on, label).changed() {
+                        groups.retain(|g| g != name);
+                        if on {
+                            groups.push(name.to_string());
+                        }
+                    }
+                }
+            })
+            .response
+            .on_hover_text("Split the listing into groups when sorted by these fields");
+            ui.end_row();
+
+            ui.label("Menu bar");
+            ui.checkbox(
+                &mut draft.ui.menu_bar,
+                "File, Edit, View, Tools, Tabs and Help menus",
+            );
+            ui.end_row();
+
+            ui.label("Parent pane width");
+            ui.add(
+                egui::DragValue::new(&mut draft.panel.parent_width)
+                    .range(style::PARENT_MIN..=1000.0)
+                    .suffix(" px"),
+            );
+            ui.end_row();
+
+            ui.label("Preview pane width");
+            ui.add(
+                egui::DragValue::new(&mut draft.panel.preview_width)
+                    .range(style::PREVIEW_MIN..=1500.0)
+                    .suffix(" px"),
+            );
+            ui.end_row();
+
+            for (label, theme) in [
+                ("Code theme (dark)", &mut draft.previews.code_theme_dark),
+                ("Code theme (light)", &mut draft.previews.code_theme_light),
+            ] {
+                ui.label(label);
+                choice(ui, label, theme, code_themes);
+                ui.end_row();
+            }
+
+            ui.label("Font size");
+            ui.add(
+                egui::DragValue::new(&mut draft.font.font_size)
+                    .range(6.0..=48.0)
+                    .suffix(" pt"),
+            );
+            ui.end_row();
+
+            ui.label("Icon size");
+            ui.add(
+                egui::DragValue::new(&mut draft.font.icon_size)
+                    .range(6.0..=48.0)
+                    .suffix(" pt"),
+            );
+            ui.end_row();
+
+            ui.label("Custom font");
+            optional_text(ui, &mut draft.font.custom_font_path, "path to a .ttf file");
+            ui.end_row();
+        });
+    ui.label(
+        egui::RichText::new("Font changes take effect after a restart")
+            .small()
+            .weak(),
+    );
+}
+
+fn behavior(ui: &mut egui::Ui, draft: &mut Config, new_build_dir: &mut String) {
+    egui::Grid::new("settings_behavior")
+        .num_columns(2)
+        .spacing([16.0, 6.0])
+        .show(ui, |ui| {
+            ui.label("Listing");
+            ui.vertical(|ui| {
+                ui.checkbox(&mut draft.ui.show_hidden, "Show hidden files");
+                ui.checkbox(&mut draft.ui.dirs_first, "Directories first");
+                ui.checkbox(
+                    &mut draft.ui.source_size,
+                    "Also total folders without hidden and git-ignored files",
+                );
+                ui.checkbox(
+                    &mut draft.ui.filter_preview,
+                    "Apply the / filter to the folder preview too",
+                );
+                ui.checkbox(
+                    &mut draft.ui.clipboard_panel,
+                    "Show the clipboard panel beside the listing",
+                );
+            });
+            ui.end_row();
+
+            ui.label("Build folders");
+            ui.vertical(|ui| {
+                ui.checkbox(
+                    &mut draft.ui.skip_build_dirs,
+                    "Leave these out of content search and folder sizes",
+                );
+                ui.add_enabled_ui(draft.ui.skip_build_dirs, |ui| {
+                    list_editor(ui, &mut draft.ui.build_dirs, new_build_dir, "node_modules");
+                });
+            });
+            ui.end_row();
+
+            ui.label("Sort by");
+            ui.horizontal(|ui| {
+                let fields: Vec<String> = SORT_FIELDS.iter().map(|f| f.to_string()).collect();
+                choice(ui, "settings_sort_by", &mut draft.ui.sort_by, &fields);
+                ui.radio_value(&mut draft.ui.sort_order, "asc".to_string(), "Ascending");
+                ui.radio_value(&mut draft.ui.sort_order, "desc".to_string(), "Descending");
+            });
+            ui.end_row();
+
+            ui.label("Paste conflicts");
+            let policies: Vec<String> = CONFLICT_POLICIES.iter().map(|p| p.to_string()).collect();
+            choice(
+                ui,
+                "settings_conflicts",
+                &mut draft.file_ops.conflict_policy,
+                &policies,
+            );
+            ui.end_row();
+
+            ui.label("Quitting");
+            ui.checkbox(
+                &mut draft.file_ops.finish_jobs_on_close,
+                "Ask what to do with running jobs",
+            );
+            ui.end_row();
+
+            ui.label("Photo rename pattern");
+            ui.add(
+                egui::TextEdit::singleline(&mut draft.file_ops.photo_pattern)
+                    .hint_text("IMG_{date}_{seq}")
+                    .font(egui::TextStyle::Monospace),
+            );
+            ui.end_row();
+
+            ui.label("Operation log");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut draft.file_ops.daily_log, "Write a file per day");
+                ui.add_enabled(
+                    draft.file_ops.daily_log,
+                    egui::DragValue::new(&mut draft.file_ops.log_keep_days)
+                        .prefix("keep ")
+                        .suffix(" days"),
+                );
+            });
+            ui.end_row();
+
+            ui.label("Confirm opening more than");
+            ui.add(egui::DragValue::new(&mut draft.open.confirm_threshold).suffix(" files"));
+            ui.end_row();
+
+            ui.label("Files opened per batch");
+            ui.add(egui::DragValue::new(&mut draft.open.batch_size).range(1..=100));
+            ui.end_row();
+
+            ui.label("Preview cache");
+            ui.add(egui::DragValue::new(&mut draft.maintenance.preview_cache_mb).suffix(" MiB"));
+            ui.end_row();
+
+            ui.label("Purge trash older than");
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut draft.maintenance.trash_retention_days)
+                        .suffix(" days"),
+                );
+                ui.label(egui::RichText::new("0 = never, checked at startup").weak());
+            });
+            ui.end_row();
+        });
+}
+
+fn previews(
+    ui: &mut egui::Ui,
+    draft: &mut Config,
+    handlers: &[(String, i32)],
+    new_row: &mut (String, String),
+) {
+    let previews = &mut draft.previews;
+    egui::Grid::new("settings_previews")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Handler");
+            ui.strong("Priority");
+            ui.label("");
+            ui.end_row();
+            for (name, default_priority) in handlers {
+                let mut enabled = previews.enabled.contains(name);
+                if ui.checkbox(&mut enabled, name.as_str()).changed() {
+                    if enabled {
+                        previews.enabled.push(name.clone());
+                    } else {
+                        previews.enabled.retain(|n| n != name);
+                    }
+                }
+                let mut priority = previews
+                    .priorities
+                    .get(name)
+                    .copied()
+                    .unwrap_or(*default_priority);
+                if ui
+                    .add(egui::DragValue::new(&mut priority))
+                    .on_hover_text("Lower is tried first")
+                    .changed()
+                {
+                    previews.priorities.insert(name.clone(), priority);
+                }
+                if previews.priorities.contains_key(name)
+                    && ui
+                        .small_button("↺")
+                        .on_hover_text(format!("Back to the default ({})", default_priority))
+                        .clicked()
+                {
+                    previews.priorities.remove(name);
+                }
+                ui.end_row();
+            }
+        });
+    ui.checkbox(
+        &mut previews.archive_details,
+        "Packed size, ratio and CRC-32 columns for zip archives",
+    );
+    ui.add_space(8.0);
+    ui.strong("Handler per extension");
+    map_editor(
+        ui,
+        "settings_preview_handlers",
+        &mut previews.handlers,
+        new_row,
+        ("extension", "handler, e.g. text"),
+        |ext| !ext.contains('.'),
+    );
+}
+
+fn keybindings(ui: &mut egui::Ui, draft: &mut Config, new_row: &mut (String, String)) {
+    ui.label(
+        egui::RichText::new(
+            "Keys are built in (press ? for the list). Bookmarks add g-sequences; \
+             aliases add words to the : command line",
+        )
+        .weak(),
+    );
+    let mut sequences: Vec<(&String, &String)> = draft.bookmarks.shortcuts.iter().collect();
+    sequences.sort();
+    egui::Grid::new("settings_sequences")
+        .num_columns(2)
+        .show(ui, |ui| {
+            for (keys, path) in sequences {
+                ui.monospace(format!("g{}", keys));
+                ui.label(path);
+                ui.end_row();
+            }
+        });
+    ui.add_space(8.0);
+    ui.strong("Command aliases");
+    map_editor(
+        ui,
+        "settings_aliases",
+        &mut draft.aliases,
+        new_row,
+        ("name", "commands, separated by ;"),
+        |name| !name.contains(char::is_whitespace),
+    );
+}
+
+fn integrations(
+    ui: &mut egui::Ui,
+    draft: &mut Config,
+    new_association: &mut (String, String),
+    new_watch: &mut String,
+) {
+    egui::Grid::new("settings_integrations")
+        .num_columns(2)
+        .spacing([16.0, 6.0])
+        .show(ui, |ui| {
+            ui.label("Editor");
+            optional_text(ui, &mut draft.tools.editor, "$VISUAL / $EDITOR");
+            ui.end_row();
+
+            ui.label("Terminal");
+            optional_text(
+                ui,
+                &mut draft.tools.terminal,
+                "$TERMINAL / platform default",
+            );
+            ui.end_row();
+
+            ui.label("Archive helper");
+            optional_text(ui, &mut draft.tools.archiver, "bsdtar");
+            ui.end_row();
+
+            ui.label("Tool check");
+            ui.checkbox(
+                &mut draft.tools.check_on_startup,
+                "Look for git, editor, terminal and bsdtar at startup",
+            );
+            ui.end_row();
+
+            ui.label("Notifications");
+            ui.vertical(|ui| {
+                ui.checkbox(&mut draft.notifications.enabled, "Desktop notifications");
+                ui.horizontal(|ui| {
+                    ui.label("for jobs running at least");
+                    ui.add(
+                        egui::DragValue::new(&mut draft.notifications.job_min_secs).suffix(" s"),
+                    );
+                });
+            });
+            ui.end_row();
+
+            ui.label("Watched folders");
+            list_editor(ui, &mut draft.notifications.watch, new_watch, "~/Downloads");
+            ui.end_row();
+        });
+    ui.label(
+        egui::RichText::new("Watched folders change after a restart")
+            .small()
+            .weak(),
+    );
+    ui.add_space(8.0);
+    ui.strong("Open with, per extension");
+    map_editor(
+        ui,
+        "settings_associations",
+        &mut draft.associations,
+        new_association,
+        ("extension", "command, e.g. zathura"),
+        |ext| !ext.contains('.'),
+    );
+}
+
+/// Combo box over `options`; a value not among them (typed into config.toml) is kept and shown
+fn choice(ui: &mut egui::Ui, id: &str, value: &mut String, options: &[String]) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(value.as_str())
+        .show_ui(ui, |ui| {
+            for option in options {
+                ui.selectable_value(value, option.clone(), option);
+            }
+        });
+}
+
+/// One row per item with a remove button, plus a row to add one
+fn list_editor(ui: &mut egui::Ui, list: &mut Vec<String>, new_item: &mut String, hint: &str) {
+    ui.vertical(|ui| {
+        let mut remove = None;
+        for (index, item) in list.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.monospace(item);
+                if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                    remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = remove {
+            list.remove(index);
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(new_item).hint_text(hint));
+            if ui
+                .add_enabled(!new_item.trim().is_empty(), egui::Button::new("Add"))
+                .clicked()
+            {
+                list.push(std::mem::take(new_item).trim().to_string());
+            }
+        });
+    });
+}
+
+/// Text field for an optional setting; clearing it restores the fallback named by `hint`
+fn optional_text(ui: &mut egui::Ui, value: &mut Option<String>, hint: &str) {
+    let mut text = value.clone().unwrap_or_default();
+    if ui
+        .add(egui::TextEdit::singleline(&mut text).hint_text(hint))
+        .changed()
+    {
+        *value = Some(text).filter(|t| !t.trim().is_empty());
+    }
+}
+
+/// Sorted key → value rows with editable values and a remove button, plus a row to add one
+fn map_editor(
+    ui: &mut egui::Ui,
+    id: &str,
+    map: &mut HashMap<String, String>,
+    new_row: &mut (String, String),
+    hints: (&str, &str),
+    valid_key: fn(&str) -> bool,
+) {
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort();
+    let mut remove = None;
+    egui::Grid::new(id)
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for key in &keys {
+                ui.monospace(key);
+                if let Some(value) = map.get_mut(key) {
+                    ui.add(egui::TextEdit::singleline(value).desired_width(280.0));
+                }
+                if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                    remove = Some(key.clone());
+                }
+                ui.end_row();
+            }
+            ui.add(
+                egui::TextEdit::singleline(&mut new_row.0)
+                    .hint_text(hints.0)
+                    .desired_width(100.0),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut new_row.1)
+                    .hint_text(hints.1)
+                    .desired_width(280.0),
+            );
+            let key = new_row.0.trim().to_string();
+            let valid = valid_key(&key) && !new_row.1.trim().is_empty();
+            let button = ui.add_enabled(valid, egui::Button::new("Add"));
+            if !key.is_empty() && !valid_key(&key) {
+                button.on_disabled_hover_text(format!("\"{}\" cannot be used here", key));
+            } else if button.clicked() {
+                let value = new_row.1.trim().to_string();
+                map.insert(key, value);
+                *new_row = Default::default();
+            }
+            ui.end_row();
+        });
+    if let Some(key) = remove {
+        map.remove(&key);
+    }
+}